@@ -0,0 +1,257 @@
+//! Animation frames of mode shapes and time histories.
+//!
+//! A mode shape oscillates: frame `k` of `n` displaces the nodes by the
+//! mode scaled with `amplitude * sin(2πk/n)`, over one period as CGX
+//! animates eigenmodes. A time history steps through the displacement
+//! datasets of the result blocks, one frame per block. Every frame holds
+//! the deformed coordinates of all nodes of the base model, for a renderer
+//! or for [`write_pvd`], which writes one VTU per frame and a ParaView
+//! `.pvd` index.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use ccx_io::{FrdFile, ResultBlock, ResultDataset, VtkFormat, VtkWriter};
+
+use crate::results::VectorField;
+
+/// One picture of an animation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Time of a history frame; fraction of the period of a mode frame
+    pub time: f64,
+    /// Factor the displacements are scaled with
+    pub factor: f64,
+    /// Displacement of every node, scaled, zero where the field has none
+    pub displacements: HashMap<i32, [f64; 3]>,
+    /// Deformed coordinates of every node
+    pub coords: HashMap<i32, [f64; 3]>,
+}
+
+impl Frame {
+    fn new(nodes: &HashMap<i32, [f64; 3]>, field: &VectorField, time: f64, factor: f64) -> Self {
+        let displacements: HashMap<i32, [f64; 3]> = nodes
+            .keys()
+            .map(|node| {
+                let u = field.values.get(node).copied().unwrap_or_default();
+                (*node, u.map(|x| x * factor))
+            })
+            .collect();
+        let coords = nodes
+            .iter()
+            .map(|(node, x)| {
+                let u = displacements[node];
+                (*node, [x[0] + u[0], x[1] + u[1], x[2] + u[2]])
+            })
+            .collect();
+        Self {
+            time,
+            factor,
+            displacements,
+            coords,
+        }
+    }
+}
+
+/// `steps` frames over one period of the mode shape `mode`, scaled by
+/// `amplitude` at the peaks
+pub fn mode_frames(
+    nodes: &HashMap<i32, [f64; 3]>,
+    mode: &VectorField,
+    amplitude: f64,
+    steps: usize,
+) -> Vec<Frame> {
+    (0..steps)
+        .map(|k| {
+            let time = k as f64 / steps as f64;
+            Frame::new(nodes, mode, time, amplitude * (TAU * time).sin())
+        })
+        .collect()
+}
+
+/// One frame per time and displacement field of `history`, scaled by
+/// `scale`
+pub fn history_frames(
+    nodes: &HashMap<i32, [f64; 3]>,
+    history: &[(f64, VectorField)],
+    scale: f64,
+) -> Vec<Frame> {
+    history
+        .iter()
+        .map(|(time, field)| Frame::new(nodes, field, *time, scale))
+        .collect()
+}
+
+/// Time and field of the nodal dataset `name` of every result block of
+/// `frd` that has it, in block order
+pub fn displacement_history(frd: &FrdFile, name: &str) -> Result<Vec<(f64, VectorField)>, String> {
+    frd.result_blocks
+        .iter()
+        .filter_map(|block| {
+            let dataset = block
+                .datasets
+                .iter()
+                .find(|d| d.name.eq_ignore_ascii_case(name))?;
+            Some(VectorField::new(dataset).map(|field| (block.time, field)))
+        })
+        .collect()
+}
+
+/// Scale that makes the largest displacement of `field` the fraction
+/// `fraction` of the diagonal of the bounding box of `nodes`; 1 for a
+/// field without displacements
+pub fn auto_scale(nodes: &HashMap<i32, [f64; 3]>, field: &VectorField, fraction: f64) -> f64 {
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for x in nodes.values() {
+        for i in 0..3 {
+            min[i] = min[i].min(x[i]);
+            max[i] = max[i].max(x[i]);
+        }
+    }
+    let diagonal = (0..3)
+        .map(|i| (max[i] - min[i]).powi(2))
+        .sum::<f64>()
+        .sqrt();
+    match field.max_magnitude {
+        Some(largest) if largest.value > 0.0 && diagonal.is_finite() => {
+            fraction * diagonal / largest.value
+        }
+        _ => 1.0,
+    }
+}
+
+/// Write every frame on the elements of `model` as `<stem>_0000.vtu`,
+/// `<stem>_0001.vtu`, ... next to `pvd_path`, with its displacements as
+/// `DISP`, and the `.pvd` index of the frames; returns the frame paths
+pub fn write_pvd(
+    model: &FrdFile,
+    frames: &[Frame],
+    pvd_path: &Path,
+    format: VtkFormat,
+) -> Result<Vec<PathBuf>, String> {
+    let dir = pvd_path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = pvd_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| format!("invalid .pvd path {}", pvd_path.display()))?;
+    let mut pvd = String::from("<?xml version=\"1.0\"?>\n");
+    pvd.push_str("<VTKFile type=\"Collection\" version=\"0.1\" byte_order=\"LittleEndian\">\n");
+    pvd.push_str("  <Collection>\n");
+    let mut written = Vec::with_capacity(frames.len());
+    for (index, frame) in frames.iter().enumerate() {
+        let values = frame
+            .displacements
+            .iter()
+            .map(|(&node, u)| (node, u.to_vec()))
+            .collect();
+        let deformed = FrdFile {
+            header: model.header.clone(),
+            nodes: frame.coords.clone(),
+            elements: model.elements.clone(),
+            result_blocks: vec![ResultBlock {
+                step: 1,
+                time: frame.time,
+                mode: None,
+                datasets: vec![ResultDataset::nodal("DISP", values)],
+            }],
+        };
+        let name = format!("{stem}_{index:04}.vtu");
+        let path = dir.join(&name);
+        VtkWriter::new(&deformed)
+            .write_vtu(&path, format)
+            .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+        writeln!(
+            pvd,
+            "    <DataSet timestep=\"{}\" group=\"\" part=\"0\" file=\"{name}\"/>",
+            frame.time
+        )
+        .unwrap();
+        written.push(path);
+    }
+    pvd.push_str("  </Collection>\n</VTKFile>\n");
+    std::fs::write(pvd_path, pvd)
+        .map_err(|e| format!("cannot write {}: {e}", pvd_path.display()))?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ccx_io::FrdElement;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn model() -> FrdFile {
+        let mut frd = FrdFile::new();
+        frd.nodes = HashMap::from([(1, [0.0, 0.0, 0.0]), (2, [3.0, 4.0, 0.0])]);
+        frd.elements.insert(
+            1,
+            FrdElement {
+                id: 1,
+                element_type: 11,
+                nodes: vec![1, 2],
+            },
+        );
+        for (time, tip) in [(0.5, 0.1), (1.0, 0.2)] {
+            let values = HashMap::from([(2, vec![0.0, tip, 0.0])]);
+            frd.result_blocks.push(ResultBlock {
+                step: 1,
+                time,
+                mode: None,
+                datasets: vec![ResultDataset::nodal("DISP", values)],
+            });
+        }
+        frd
+    }
+
+    #[test]
+    fn scales_modes_harmonically_and_steps_histories() {
+        let frd = model();
+        let history = displacement_history(&frd, "disp").unwrap();
+        assert_eq!(history.len(), 2);
+        let mode = &history[1].1;
+        assert!((auto_scale(&frd.nodes, mode, 0.1) - 2.5).abs() < 1e-12);
+
+        let frames = mode_frames(&frd.nodes, mode, 10.0, 4);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].coords, frd.nodes);
+        assert_eq!(frames[1].time, 0.25);
+        assert!((frames[1].coords[&2][1] - 6.0).abs() < 1e-12);
+        assert!((frames[3].coords[&2][1] - 2.0).abs() < 1e-12);
+        assert_eq!(frames[1].coords[&1], [0.0; 3]);
+
+        let frames = history_frames(&frd.nodes, &history, 2.0);
+        let tips: Vec<(f64, f64)> = frames.iter().map(|f| (f.time, f.coords[&2][1])).collect();
+        assert_eq!(tips, [(0.5, 4.2), (1.0, 4.4)]);
+        assert_eq!(frames[1].displacements[&2], [0.0, 0.4, 0.0]);
+    }
+
+    #[test]
+    fn writes_frames_as_a_pvd_series() {
+        let frd = model();
+        let history = displacement_history(&frd, "DISP").unwrap();
+        let frames = history_frames(&frd.nodes, &history, 1.0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock should be valid")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "calculix_gui_animation_{}_{nanos}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pvd = dir.join("anim.pvd");
+        let written = write_pvd(&frd, &frames, &pvd, VtkFormat::Ascii).unwrap();
+        assert_eq!(
+            written,
+            [dir.join("anim_0000.vtu"), dir.join("anim_0001.vtu")]
+        );
+        let index = std::fs::read_to_string(&pvd).unwrap();
+        assert!(index.contains("timestep=\"0.5\" group=\"\" part=\"0\" file=\"anim_0000.vtu\""));
+        let vtu = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(vtu.contains("4.2"), "{vtu}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
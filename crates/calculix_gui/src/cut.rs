@@ -0,0 +1,267 @@
+//! Cut planes and iso-surfaces of solid meshes.
+//!
+//! Both are level sets over the solid elements: the signed distance to a
+//! plane for [`cut_plane`], like the `cut` command of CGX, and a nodal
+//! field minus the level for [`iso_surface`]. Every brick, wedge and
+//! tetrahedron is split into tetrahedra over its corner nodes, and each
+//! tetrahedron crossed by the level set contributes one or two triangles
+//! with corners interpolated linearly along its edges, together with the
+//! values of a nodal field. Triangles face the positive side of the level
+//! set, the side the normal of a plane points to.
+
+use std::collections::HashMap;
+
+use ccx_solver::{Element, ElementType, Mesh};
+
+use crate::ported::{v_prod, v_result, v_sprod};
+use crate::results::ScalarField;
+
+/// Tetrahedra of a brick over its corners, around the diagonal from corner
+/// 0 to corner 6
+const HEX_TETS: [[usize; 4]; 6] = [
+    [0, 1, 2, 6],
+    [0, 2, 3, 6],
+    [0, 3, 7, 6],
+    [0, 7, 4, 6],
+    [0, 4, 5, 6],
+    [0, 5, 1, 6],
+];
+
+/// Tetrahedra of a wedge over its corners
+const WEDGE_TETS: [[usize; 4]; 3] = [[0, 1, 2, 3], [1, 2, 3, 4], [2, 3, 4, 5]];
+
+const TET: [[usize; 4]; 1] = [[0, 1, 2, 3]];
+
+/// Plane through `point` with the normal `normal`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub point: [f64; 3],
+    pub normal: [f64; 3],
+}
+
+/// Triangles of a cut or an iso-surface
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    pub points: Vec<[f64; 3]>,
+    /// Field value at every point; empty without a field
+    pub values: Vec<f64>,
+    pub triangles: Vec<[usize; 3]>,
+    /// Element every triangle lies in
+    pub elements: Vec<i32>,
+}
+
+impl Section {
+    /// Sum of the triangle areas
+    pub fn area(&self) -> f64 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let p = &self.points;
+                let n = v_prod(v_result(p[a], p[b]), v_result(p[a], p[c]));
+                0.5 * v_sprod(n, n).sqrt()
+            })
+            .sum()
+    }
+}
+
+/// Section of the solid elements of `mesh` by `plane`, with the values of
+/// `field` where it has a value at all corners of the element
+pub fn cut_plane(mesh: &Mesh, plane: &Plane, field: Option<&ScalarField>) -> Section {
+    let level = |node: i32| {
+        let coords = mesh.nodes.get(&node)?.coords();
+        Some(v_sprod(v_result(plane.point, coords), plane.normal))
+    };
+    march(mesh, level, field)
+}
+
+/// Surface where `field` equals `level` in the solid elements of `mesh`
+pub fn iso_surface(mesh: &Mesh, field: &ScalarField, level: f64) -> Section {
+    let distance = |node: i32| field.values.get(&node).map(|value| value - level);
+    march(mesh, distance, Some(field))
+}
+
+/// Marching tetrahedra over the zero level of `level`
+fn march(mesh: &Mesh, level: impl Fn(i32) -> Option<f64>, field: Option<&ScalarField>) -> Section {
+    let mut ids: Vec<i32> = mesh.elements.keys().copied().collect();
+    ids.sort_unstable();
+    let mut section = Section::default();
+    // Points by the edge they lie on, so neighbouring triangles share them
+    let mut on_edge: HashMap<(i32, i32), usize> = HashMap::new();
+    for id in ids {
+        let element = &mesh.elements[&id];
+        let Some(corners) = corners(element) else {
+            continue;
+        };
+        let Some(levels) = corners
+            .iter()
+            .map(|&n| level(n))
+            .collect::<Option<Vec<f64>>>()
+        else {
+            continue;
+        };
+        let values = match field {
+            Some(field) => {
+                let values: Option<Vec<f64>> = corners
+                    .iter()
+                    .map(|n| field.values.get(n).copied())
+                    .collect();
+                let Some(values) = values else { continue };
+                Some(values)
+            }
+            None => None,
+        };
+        let Some(coords) = corners
+            .iter()
+            .map(|n| mesh.nodes.get(n).map(|node| node.coords()))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+
+        for tet in tets(element.element_type) {
+            let (positive, negative): (Vec<usize>, Vec<usize>) =
+                tet.iter().partition(|&&c| levels[c] >= 0.0);
+            let crossed: Vec<(usize, usize)> = match (positive.len(), negative.len()) {
+                (1, 3) => negative.iter().map(|&n| (positive[0], n)).collect(),
+                (3, 1) => positive.iter().map(|&p| (p, negative[0])).collect(),
+                (2, 2) => vec![
+                    (positive[0], negative[0]),
+                    (positive[0], negative[1]),
+                    (positive[1], negative[1]),
+                    (positive[1], negative[0]),
+                ],
+                _ => continue,
+            };
+            let mut point = |(a, b): (usize, usize)| {
+                let key = (corners[a].min(corners[b]), corners[a].max(corners[b]));
+                *on_edge.entry(key).or_insert_with(|| {
+                    let t = levels[a] / (levels[a] - levels[b]);
+                    let lerp = |x: f64, y: f64| x + t * (y - x);
+                    let (pa, pb) = (coords[a], coords[b]);
+                    section.points.push([0, 1, 2].map(|i| lerp(pa[i], pb[i])));
+                    if let Some(values) = &values {
+                        section.values.push(lerp(values[a], values[b]));
+                    }
+                    section.points.len() - 1
+                })
+            };
+            let polygon: Vec<usize> = crossed.into_iter().map(&mut point).collect();
+
+            // Face the positive side: away from the negative corners
+            let centroid = |cs: &[usize]| {
+                let n = cs.len() as f64;
+                [0, 1, 2].map(|i| cs.iter().map(|&c| coords[c][i]).sum::<f64>() / n)
+            };
+            let towards = v_result(centroid(&negative), centroid(&positive));
+            for k in 1..polygon.len() - 1 {
+                let mut triangle = [polygon[0], polygon[k], polygon[k + 1]];
+                let p = &section.points;
+                let normal = v_prod(
+                    v_result(p[triangle[0]], p[triangle[1]]),
+                    v_result(p[triangle[0]], p[triangle[2]]),
+                );
+                if v_sprod(normal, towards) < 0.0 {
+                    triangle.swap(1, 2);
+                }
+                section.triangles.push(triangle);
+                section.elements.push(id);
+            }
+        }
+    }
+    section
+}
+
+/// Corner nodes of a solid element
+fn corners(element: &Element) -> Option<&[i32]> {
+    let count = match element.element_type {
+        ElementType::C3D8 | ElementType::C3D20 => 8,
+        ElementType::C3D6 | ElementType::C3D15 => 6,
+        ElementType::C3D4 | ElementType::C3D10 => 4,
+        _ => return None,
+    };
+    element.nodes.get(..count)
+}
+
+fn tets(element_type: ElementType) -> &'static [[usize; 4]] {
+    match element_type {
+        ElementType::C3D8 | ElementType::C3D20 => &HEX_TETS,
+        ElementType::C3D6 | ElementType::C3D15 => &WEDGE_TETS,
+        _ => &TET,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ccx_solver::{Division, MeshBuilder};
+
+    fn cube(element_type: ElementType) -> Mesh {
+        let divisions = [2, 2, 2].map(Division::uniform);
+        MeshBuilder::brick([1.0, 1.0, 1.0], divisions, element_type)
+            .unwrap()
+            .mesh
+    }
+
+    fn field(mesh: &Mesh, f: impl Fn([f64; 3]) -> f64) -> ScalarField {
+        let values: HashMap<i32, f64> = mesh
+            .nodes
+            .values()
+            .map(|node| (node.id, f(node.coords())))
+            .collect();
+        ScalarField {
+            dataset: "TEST".to_string(),
+            entity: "1".to_string(),
+            values,
+            min: None,
+            max: None,
+        }
+    }
+
+    #[test]
+    fn cuts_solids_with_planes() {
+        let mesh = cube(ElementType::C3D20);
+        let x = field(&mesh, |c| c[0]);
+        let plane = Plane {
+            point: [0.5, 0.5, 0.3],
+            normal: [0.0, 0.0, 1.0],
+        };
+        let section = cut_plane(&mesh, &plane, Some(&x));
+        assert!((section.area() - 1.0).abs() < 1e-12);
+        assert_eq!(section.values.len(), section.points.len());
+        for (point, value) in section.points.iter().zip(&section.values) {
+            assert!((point[2] - 0.3).abs() < 1e-12);
+            assert!((point[0] - value).abs() < 1e-12);
+        }
+        for &[a, b, c] in &section.triangles {
+            let p = &section.points;
+            let normal = v_prod(v_result(p[a], p[b]), v_result(p[a], p[c]));
+            assert!(normal[2] > 0.0);
+        }
+
+        let diagonal = Plane {
+            point: [0.5; 3],
+            normal: [1.0, 1.0, 1.0],
+        };
+        let hexagon = 3.0 * 3f64.sqrt() / 4.0;
+        let section = cut_plane(&mesh, &diagonal, None);
+        assert!((section.area() - hexagon).abs() < 1e-12);
+        assert!(section.values.is_empty());
+
+        let outside = Plane {
+            point: [0.0, 0.0, 2.0],
+            normal: [0.0, 0.0, 1.0],
+        };
+        assert!(cut_plane(&mesh, &outside, None).triangles.is_empty());
+    }
+
+    #[test]
+    fn extracts_iso_surfaces_of_nodal_fields() {
+        let mesh = cube(ElementType::C3D8);
+        let sum = field(&mesh, |c| c[0] + c[1] + c[2]);
+        let section = iso_surface(&mesh, &sum, 1.5);
+        assert!((section.area() - 3.0 * 3f64.sqrt() / 4.0).abs() < 1e-12);
+        assert!(section.values.iter().all(|v| (v - 1.5).abs() < 1e-12));
+        assert_eq!(section.elements.len(), section.triangles.len());
+        assert!(iso_surface(&mesh, &sum, 4.0).triangles.is_empty());
+    }
+}
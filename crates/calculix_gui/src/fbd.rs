@@ -0,0 +1,686 @@
+//! Parser of CGX command files (`.fbd`).
+//!
+//! A command file holds one command per line: a case-insensitive keyword
+//! followed by whitespace-separated arguments; `#` starts a comment line.
+//! The geometry and meshing commands (`pnt`, `line`, `lcmb`, `surf`/`gsur`,
+//! `body`/`gbod`, `seta`, `elty`, `div`, `mesh`, `send`, `valu`) are parsed
+//! into typed [`FbdCommand`]s; the other CGX commands are kept as
+//! [`FbdCommand::Other`] with their raw arguments.
+//!
+//! Numeric arguments may name a value defined before by `valu`, as in CGX.
+//! Malformed commands are dropped with an error diagnostic; unknown keywords
+//! are kept with a warning, so a script can be replayed as far as it goes.
+
+use std::fmt;
+use std::path::Path;
+
+use crate::ported::check_if_number;
+
+/// Keywords of the CGX commands kept as [`FbdCommand::Other`] without a
+/// diagnostic
+const OTHER_COMMANDS: &[&str] = &[
+    "ANIM", "AREA", "ASGN", "BIA", "CALC", "COMP", "COPY", "CORRAD", "CSYSA", "CUT", "DEL", "DEMO",
+    "DIST", "DIV", "DS", "ELEM", "ENQ", "EQAL", "EXIT", "FIL", "FLIP", "FLPC", "FONT", "FRAME",
+    "GONLY", "GRAPH", "GRPA", "GRPS", "GTOL", "HCPY", "HELP", "ILLU", "INT", "LENGTH", "MATA",
+    "MAX", "MERG", "MIDS", "MIN", "MINUS", "MOVE", "MSG", "NODE", "NORM", "NURL", "NURS", "ORI",
+    "PLOT", "PLUS", "PRNT", "PROJ", "QADD", "QCNT", "QDEL", "QUIT", "READ", "REP", "ROT", "SCAL",
+    "SEQA", "SETC", "SETI", "SETO", "SETR", "SHPE", "SMOOTH", "SPLIT", "STEPS", "STOP", "SWEP",
+    "SYS", "TEXT", "TRA", "TRFM", "UCUT", "VIEW", "VOLU", "WSIZE", "ZAP", "ZOOM",
+];
+
+/// Name of a new entity; `!` lets CGX generate one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityName {
+    Auto,
+    Named(String),
+}
+
+impl EntityName {
+    fn parse(token: &str) -> Self {
+        match token {
+            "!" => EntityName::Auto,
+            name => EntityName::Named(name.to_string()),
+        }
+    }
+}
+
+/// Reference to a line, line combination or surface with an optional
+/// orientation; without one, the geometry kernel picks the orientation
+/// that closes the loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Oriented {
+    pub name: String,
+    /// `Some(true)` for `-`, `Some(false)` for `+`
+    pub reversed: Option<bool>,
+}
+
+/// Shape of the elements of an `elty` assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementShape {
+    Be2,
+    Be3,
+    Tr3,
+    Tr6,
+    Qu4,
+    Qu8,
+    He8,
+    He20,
+    Pe6,
+    Pe15,
+    Te4,
+    Te10,
+}
+
+impl ElementShape {
+    const ALL: [ElementShape; 12] = [
+        ElementShape::Be2,
+        ElementShape::Be3,
+        ElementShape::Tr3,
+        ElementShape::Tr6,
+        ElementShape::Qu4,
+        ElementShape::Qu8,
+        ElementShape::He8,
+        ElementShape::He20,
+        ElementShape::Pe6,
+        ElementShape::Pe15,
+        ElementShape::Te4,
+        ElementShape::Te10,
+    ];
+
+    /// CGX name, e.g. `he20`
+    pub fn name(self) -> &'static str {
+        match self {
+            ElementShape::Be2 => "be2",
+            ElementShape::Be3 => "be3",
+            ElementShape::Tr3 => "tr3",
+            ElementShape::Tr6 => "tr6",
+            ElementShape::Qu4 => "qu4",
+            ElementShape::Qu8 => "qu8",
+            ElementShape::He8 => "he8",
+            ElementShape::He20 => "he20",
+            ElementShape::Pe6 => "pe6",
+            ElementShape::Pe15 => "pe15",
+            ElementShape::Te4 => "te4",
+            ElementShape::Te10 => "te10",
+        }
+    }
+
+    /// 1 for beams, 2 for shells, 3 for solids
+    pub fn dimension(self) -> usize {
+        match self {
+            ElementShape::Be2 | ElementShape::Be3 => 1,
+            ElementShape::Tr3 | ElementShape::Tr6 | ElementShape::Qu4 | ElementShape::Qu8 => 2,
+            _ => 3,
+        }
+    }
+
+    /// Whether the elements have mid-side nodes
+    pub fn is_quadratic(self) -> bool {
+        matches!(
+            self,
+            ElementShape::Be3
+                | ElementShape::Tr6
+                | ElementShape::Qu8
+                | ElementShape::He20
+                | ElementShape::Pe15
+                | ElementShape::Te10
+        )
+    }
+}
+
+/// Element type of an `elty` assignment, e.g. `he20r`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementKind {
+    pub shape: ElementShape,
+    /// Formulation letters after the shape, e.g. `r` for reduced
+    /// integration or `c` for axisymmetric
+    pub suffix: String,
+}
+
+impl ElementKind {
+    /// Element type of `token`, case-insensitive
+    pub fn parse(token: &str) -> Option<Self> {
+        let token = token.to_ascii_lowercase();
+        // Longest name first, so he20 is not read as he2 plus a suffix
+        let mut shapes = ElementShape::ALL;
+        shapes.sort_by_key(|shape| std::cmp::Reverse(shape.name().len()));
+        let shape = shapes
+            .into_iter()
+            .find(|shape| token.starts_with(shape.name()))?;
+        let suffix = token[shape.name().len()..].to_string();
+        if !suffix.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some(ElementKind { shape, suffix })
+    }
+}
+
+/// Kind of the entities added by `seta`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetItemKind {
+    Node,
+    Element,
+    Point,
+    Line,
+    Lcmb,
+    Surface,
+    Body,
+    Set,
+}
+
+impl SetItemKind {
+    fn parse(token: &str) -> Option<Self> {
+        let kind = match token {
+            "n" => SetItemKind::Node,
+            "e" => SetItemKind::Element,
+            "p" => SetItemKind::Point,
+            "l" => SetItemKind::Line,
+            "c" => SetItemKind::Lcmb,
+            "s" => SetItemKind::Surface,
+            "b" => SetItemKind::Body,
+            "se" => SetItemKind::Set,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// One command of a command file
+#[derive(Debug, Clone, PartialEq)]
+pub enum FbdCommand {
+    /// `pnt <name|!> <x> <y> <z>`
+    Pnt { name: EntityName, coords: [f64; 3] },
+    /// `line <name|!> <p1> <p2> [<center|seqa set>] [<div>]`
+    Line {
+        name: EntityName,
+        start: String,
+        end: String,
+        /// Center point of an arc or sequence set of a spline, told apart
+        /// by the entity it names
+        via: Option<String>,
+        division: Option<u32>,
+    },
+    /// `lcmb <name|!> <+|-> <line> <+|-> <line> ...`
+    Lcmb {
+        name: EntityName,
+        lines: Vec<Oriented>,
+    },
+    /// `surf <name|!> <line|lcmb> ...` or
+    /// `gsur <name|!> <+|-> <BLEND|nurbs> <+|-> <line|lcmb> ...`
+    Surf {
+        name: EntityName,
+        reversed: bool,
+        /// `BLEND` or the NURBS surface the edges are trimmed on
+        support: String,
+        edges: Vec<Oriented>,
+    },
+    /// `body <name|!> <surf> ...` or `gbod <name|!> NORM <+|-> <surf> ...`
+    Body {
+        name: EntityName,
+        surfaces: Vec<Oriented>,
+    },
+    /// `seta <set|!> [<kind>] <entity> ...`; without a kind the entities
+    /// are sets or `all`
+    Seta {
+        set: EntityName,
+        kind: Option<SetItemKind>,
+        entities: Vec<String>,
+    },
+    /// `elty <set> [<type>] [<args>]`; without a type the assignment is
+    /// removed
+    Elty {
+        set: String,
+        element: Option<ElementKind>,
+        args: Vec<String>,
+    },
+    /// `div <set> <div>`; the other forms of `div` are kept as
+    /// [`FbdCommand::Other`]
+    Div { set: String, division: u32 },
+    /// `mesh <set> [<options>]`
+    Mesh { set: String, options: Vec<String> },
+    /// `send <set> <format> [<options>]`
+    Send {
+        set: String,
+        format: String,
+        options: Vec<String>,
+    },
+    /// `valu <name> <value>`, with the value of an operation already
+    /// evaluated
+    Valu { name: String, value: String },
+    /// Any other command, keyword in upper case
+    Other { keyword: String, args: Vec<String> },
+}
+
+/// Command and the 1-based line it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct FbdLine {
+    pub line: usize,
+    pub command: FbdCommand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problem of one line of a command file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FbdDiagnostic {
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for FbdDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "line {}: {}: {}", self.line, severity, self.message)
+    }
+}
+
+/// Parsed command file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FbdScript {
+    pub commands: Vec<FbdLine>,
+    pub diagnostics: Vec<FbdDiagnostic>,
+}
+
+impl FbdScript {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+}
+
+/// Read and parse the command file at `path`
+pub fn read_fbd(path: impl AsRef<Path>) -> Result<FbdScript, String> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+    Ok(parse_fbd(&text))
+}
+
+/// Parse the commands of `text`
+pub fn parse_fbd(text: &str) -> FbdScript {
+    let mut parser = Parser::default();
+    let mut script = FbdScript::default();
+    for (index, line) in text.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((keyword, args)) = tokens.split_first() else {
+            continue;
+        };
+        if keyword.starts_with('#') {
+            continue;
+        }
+        let keyword = keyword.to_ascii_uppercase();
+        let line = index + 1;
+        match parser.command(&keyword, args) {
+            Ok(command) => {
+                if let FbdCommand::Other { keyword, .. } = &command
+                    && !OTHER_COMMANDS.contains(&keyword.as_str())
+                {
+                    script.diagnostics.push(FbdDiagnostic {
+                        line,
+                        severity: Severity::Warning,
+                        message: format!("unknown command {keyword}"),
+                    });
+                }
+                script.commands.push(FbdLine { line, command });
+            }
+            Err(message) => script.diagnostics.push(FbdDiagnostic {
+                line,
+                severity: Severity::Error,
+                message: format!("{}: {}", keyword.to_ascii_lowercase(), message),
+            }),
+        }
+    }
+    script
+}
+
+/// Values defined by `valu` so far
+#[derive(Debug, Default)]
+struct Parser {
+    values: Vec<(String, String)>,
+}
+
+impl Parser {
+    fn command(&mut self, keyword: &str, args: &[&str]) -> Result<FbdCommand, String> {
+        let command = match keyword {
+            "PNT" => self.pnt(args)?,
+            "LINE" => self.line(args)?,
+            "LCMB" => {
+                let (name, lines) = args.split_first().ok_or("missing name")?;
+                FbdCommand::Lcmb {
+                    name: EntityName::parse(name),
+                    lines: signed_list(lines)?,
+                }
+            }
+            "SURF" => {
+                let (name, edges) = args.split_first().ok_or("missing name")?;
+                if edges.is_empty() {
+                    return Err("missing edges".to_string());
+                }
+                FbdCommand::Surf {
+                    name: EntityName::parse(name),
+                    reversed: false,
+                    support: "BLEND".to_string(),
+                    edges: unsigned_list(edges),
+                }
+            }
+            "GSUR" => match args {
+                [name, sign, support, edges @ ..] if !edges.is_empty() => FbdCommand::Surf {
+                    name: EntityName::parse(name),
+                    reversed: parse_sign(sign)?,
+                    support: support.to_string(),
+                    edges: signed_list(edges)?,
+                },
+                _ => return Err("expected <name> <+|-> <BLEND|nurbs> <edges>".to_string()),
+            },
+            "BODY" => match args {
+                [name, surfaces @ ..] if !surfaces.is_empty() => FbdCommand::Body {
+                    name: EntityName::parse(name),
+                    surfaces: unsigned_list(surfaces),
+                },
+                _ => return Err("expected <name> <surfaces>".to_string()),
+            },
+            "GBOD" => match args {
+                [name, norm, surfaces @ ..] if norm.eq_ignore_ascii_case("NORM") => {
+                    FbdCommand::Body {
+                        name: EntityName::parse(name),
+                        surfaces: signed_list(surfaces)?,
+                    }
+                }
+                _ => return Err("expected <name> NORM <+|-> <surf> ...".to_string()),
+            },
+            "SETA" => {
+                let (set, rest) = args.split_first().ok_or("missing set name")?;
+                let kind = rest.first().and_then(|token| SetItemKind::parse(token));
+                let entities = &rest[kind.map_or(0, |_| 1)..];
+                FbdCommand::Seta {
+                    set: EntityName::parse(set),
+                    kind,
+                    entities: entities.iter().map(|s| s.to_string()).collect(),
+                }
+            }
+            "ELTY" => {
+                let (set, rest) = args.split_first().ok_or("missing set name")?;
+                let element = match rest.first() {
+                    Some(token) => Some(
+                        ElementKind::parse(token)
+                            .ok_or_else(|| format!("unknown element type {token}"))?,
+                    ),
+                    None => None,
+                };
+                FbdCommand::Elty {
+                    set: set.to_string(),
+                    element,
+                    args: rest.iter().skip(1).map(|s| s.to_string()).collect(),
+                }
+            }
+            "DIV" if args.len() == 2 && self.number(args[1]).is_some() => FbdCommand::Div {
+                set: args[0].to_string(),
+                division: self.division(args[1])?,
+            },
+            "MESH" => {
+                let (set, options) = args.split_first().ok_or("missing set name")?;
+                FbdCommand::Mesh {
+                    set: set.to_string(),
+                    options: options.iter().map(|s| s.to_string()).collect(),
+                }
+            }
+            "SEND" => match args {
+                [set, format, options @ ..] => FbdCommand::Send {
+                    set: set.to_string(),
+                    format: format.to_string(),
+                    options: options.iter().map(|s| s.to_string()).collect(),
+                },
+                _ => return Err("expected <set> <format>".to_string()),
+            },
+            "VALU" => self.valu(args)?,
+            _ => FbdCommand::Other {
+                keyword: keyword.to_string(),
+                args: args.iter().map(|s| s.to_string()).collect(),
+            },
+        };
+        Ok(command)
+    }
+
+    fn pnt(&self, args: &[&str]) -> Result<FbdCommand, String> {
+        let [name, x, y, z] = args else {
+            return Err("expected <name> <x> <y> <z>".to_string());
+        };
+        let coord = |token: &str| {
+            self.number(token)
+                .ok_or_else(|| format!("invalid coordinate {token}"))
+        };
+        Ok(FbdCommand::Pnt {
+            name: EntityName::parse(name),
+            coords: [coord(x)?, coord(y)?, coord(z)?],
+        })
+    }
+
+    fn line(&self, args: &[&str]) -> Result<FbdCommand, String> {
+        if args.len() < 3 {
+            return Err("expected <name> <p1> <p2>".to_string());
+        }
+        let (via, division) = match &args[3..] {
+            [] => (None, None),
+            [last] if self.number(last).is_some() => (None, Some(self.division(last)?)),
+            [via] => (Some(via.to_string()), None),
+            [via, div] => (Some(via.to_string()), Some(self.division(div)?)),
+            _ => return Err("too many arguments".to_string()),
+        };
+        Ok(FbdCommand::Line {
+            name: EntityName::parse(args[0]),
+            start: args[1].to_string(),
+            end: args[2].to_string(),
+            via,
+            division,
+        })
+    }
+
+    fn valu(&mut self, args: &[&str]) -> Result<FbdCommand, String> {
+        let (name, rest) = args.split_first().ok_or("missing name")?;
+        let value = match rest {
+            [value] => value.to_string(),
+            ["&", a, b] => {
+                format!(
+                    "{}{}",
+                    self.lookup(a).unwrap_or(a),
+                    self.lookup(b).unwrap_or(b)
+                )
+            }
+            [op @ ("+" | "-" | "*" | "/"), a, b] => {
+                let operand = |token: &str| {
+                    self.number(token)
+                        .ok_or_else(|| format!("invalid operand {token}"))
+                };
+                let (a, b) = (operand(a)?, operand(b)?);
+                let value = match *op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    _ => a / b,
+                };
+                value.to_string()
+            }
+            _ => return Err("expected <name> <value> or <name> <op> <a> <b>".to_string()),
+        };
+        self.values.retain(|(n, _)| n != name);
+        self.values.push((name.to_string(), value.clone()));
+        Ok(FbdCommand::Valu {
+            name: name.to_string(),
+            value,
+        })
+    }
+
+    fn lookup(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Number of `token` or of the value it names; exponents may be
+    /// written with `d` as in Fortran
+    fn number(&self, token: &str) -> Option<f64> {
+        let token = match check_if_number(token) {
+            true => token,
+            false => self.lookup(token)?,
+        };
+        token.replace(['d', 'D'], "e").parse().ok()
+    }
+
+    fn division(&self, token: &str) -> Result<u32, String> {
+        match self.number(token) {
+            Some(div) if div >= 1.0 && div.fract() == 0.0 && div <= u32::MAX as f64 => {
+                Ok(div as u32)
+            }
+            _ => Err(format!("invalid division {token}")),
+        }
+    }
+}
+
+fn parse_sign(token: &str) -> Result<bool, String> {
+    match token {
+        "+" => Ok(false),
+        "-" => Ok(true),
+        _ => Err(format!("expected + or -, got {token}")),
+    }
+}
+
+/// Pairs of sign and name
+fn signed_list(tokens: &[&str]) -> Result<Vec<Oriented>, String> {
+    if tokens.is_empty() || !tokens.len().is_multiple_of(2) {
+        return Err("expected pairs of <+|-> <name>".to_string());
+    }
+    tokens
+        .chunks(2)
+        .map(|pair| {
+            Ok(Oriented {
+                name: pair[1].to_string(),
+                reversed: Some(parse_sign(pair[0])?),
+            })
+        })
+        .collect()
+}
+
+fn unsigned_list(tokens: &[&str]) -> Vec<Oriented> {
+    tokens
+        .iter()
+        .map(|name| Oriented {
+            name: name.to_string(),
+            reversed: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: &str = "\
+# unit block
+valu len 2.5
+valu half / len 2
+PNT p1 0 0 0
+pnt p2 len 0 0
+pnt ! half 1.d0 0
+line l1 p1 p2 4
+line l2 p2 p3 pc 8
+lcmb c1 + l1 - l2
+gsur s1 + BLEND + l1 - l2 + l3 - l4
+surf s2 l1 l2 l3 l4
+gbod b1 NORM - s1 + s2
+seta fix p p1 p2
+elty all he20r
+div all 6
+mesh all
+send fix abq nam
+plot f all
+";
+
+    #[test]
+    fn parses_geometry_and_mesh_commands() {
+        let script = parse_fbd(BLOCK);
+        assert!(script.diagnostics.is_empty(), "{:?}", script.diagnostics);
+        let commands: Vec<&FbdCommand> = script.commands.iter().map(|l| &l.command).collect();
+        assert_eq!(commands.len(), 17);
+        assert_eq!(script.commands[0].line, 2);
+        assert_eq!(
+            commands[1],
+            &FbdCommand::Valu {
+                name: "half".to_string(),
+                value: "1.25".to_string()
+            }
+        );
+        assert_eq!(
+            commands[3],
+            &FbdCommand::Pnt {
+                name: EntityName::Named("p2".to_string()),
+                coords: [2.5, 0.0, 0.0]
+            }
+        );
+        assert_eq!(
+            commands[4],
+            &FbdCommand::Pnt {
+                name: EntityName::Auto,
+                coords: [1.25, 1.0, 0.0]
+            }
+        );
+        assert!(matches!(
+            commands[6],
+            FbdCommand::Line { via: Some(via), division: Some(8), .. } if via == "pc"
+        ));
+        let FbdCommand::Surf { edges, support, .. } = commands[8] else {
+            panic!("{:?}", commands[8]);
+        };
+        assert_eq!(support, "BLEND");
+        assert_eq!(edges[1].reversed, Some(true));
+        assert!(matches!(commands[10], FbdCommand::Body { surfaces, .. } if surfaces.len() == 2));
+        assert!(matches!(
+            commands[11],
+            FbdCommand::Seta { kind: Some(SetItemKind::Point), entities, .. } if entities.len() == 2
+        ));
+        let FbdCommand::Elty { element, .. } = commands[12] else {
+            panic!("{:?}", commands[12]);
+        };
+        let element = element.as_ref().unwrap();
+        assert_eq!(
+            (element.shape, element.suffix.as_str()),
+            (ElementShape::He20, "r")
+        );
+        assert_eq!(
+            commands[13],
+            &FbdCommand::Div {
+                set: "all".to_string(),
+                division: 6
+            }
+        );
+        assert!(matches!(commands[16], FbdCommand::Other { keyword, .. } if keyword == "PLOT"));
+    }
+
+    #[test]
+    fn reports_malformed_and_unknown_commands() {
+        let script =
+            parse_fbd("pnt p1 0 0\nline l1 p1 p2 0\nelty all xx9\nfoo bar\npnt p2 1 2 3\n");
+        let messages: Vec<String> = script.diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 1: error: pnt: expected <name> <x> <y> <z>",
+                "line 2: error: line: invalid division 0",
+                "line 3: error: elty: unknown element type xx9",
+                "line 4: warning: unknown command FOO",
+            ]
+        );
+        assert!(script.has_errors());
+        assert_eq!(script.commands.len(), 2);
+        assert_eq!(script.commands[1].line, 5);
+    }
+}
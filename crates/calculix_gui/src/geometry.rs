@@ -0,0 +1,968 @@
+//! Geometry entities of CGX: points, lines, line combinations, surfaces,
+//! bodies and sets.
+//!
+//! Lines run between two points, straight, as an arc around a center point
+//! or as a spline through a sequence of points, and carry the number of
+//! elements the mesher puts on them. Line combinations (`lcmb`) chain lines
+//! into one edge, surfaces are closed loops of lines and line combinations,
+//! and bodies are bounded by surfaces. New entities without a name get the
+//! next free name of their kind (`P001`, `L001`, `C001`, `A001`, `B001`) as
+//! in CGX, and defining an entity again replaces it.
+//!
+//! [`Geometry::replay`] builds the geometry of a parsed `.fbd` script.
+
+use std::collections::BTreeMap;
+
+use crate::fbd::{
+    ElementKind, EntityName, FbdCommand, FbdDiagnostic, FbdScript, SetItemKind, Severity,
+};
+use crate::ported::{v_add, v_norm, v_prod, v_result, v_sprod};
+
+/// Elements on a line without a `div`, as in CGX
+pub const DEFAULT_DIVISION: u32 = 4;
+
+/// Smallest angle between the radii to the end points of an arc, and
+/// smallest angle to half a circle
+const MIN_ARC_ANGLE: f64 = 1e-6;
+
+/// Kind of a geometry entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntityKind {
+    Point,
+    Line,
+    Lcmb,
+    Surface,
+    Body,
+}
+
+impl EntityKind {
+    pub const ALL: [EntityKind; 5] = [
+        EntityKind::Point,
+        EntityKind::Line,
+        EntityKind::Lcmb,
+        EntityKind::Surface,
+        EntityKind::Body,
+    ];
+
+    /// First letter of generated names
+    fn prefix(self) -> char {
+        match self {
+            EntityKind::Point => 'P',
+            EntityKind::Line => 'L',
+            EntityKind::Lcmb => 'C',
+            EntityKind::Surface => 'A',
+            EntityKind::Body => 'B',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EntityKind::Point => "point",
+            EntityKind::Line => "line",
+            EntityKind::Lcmb => "lcmb",
+            EntityKind::Surface => "surface",
+            EntityKind::Body => "body",
+        }
+    }
+}
+
+/// Use of a line, lcmb or surface by another entity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrientedRef {
+    pub kind: EntityKind,
+    pub name: String,
+    pub reversed: bool,
+}
+
+/// Course of a line between its end points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineShape {
+    Straight,
+    /// Arc around a center point, shorter than half a circle; the radius
+    /// changes linearly when the end points are at different distances
+    Arc {
+        center: String,
+    },
+    /// Catmull-Rom spline through the intermediate points, in order
+    Spline {
+        points: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub start: String,
+    pub end: String,
+    pub shape: LineShape,
+    /// Number of elements along the line
+    pub division: u32,
+}
+
+/// Lines chained into one edge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lcmb {
+    pub lines: Vec<OrientedRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Surface {
+    /// Normal opposite to the one of the edge loop
+    pub reversed: bool,
+    /// `BLEND` or the NURBS surface the edges are trimmed on
+    pub support: String,
+    /// Closed loop of lines and lcmbs, each oriented along the loop
+    pub edges: Vec<OrientedRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Body {
+    pub surfaces: Vec<OrientedRef>,
+}
+
+/// Named set of entities, each kind in the order the entities were added
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeometrySet {
+    pub points: Vec<String>,
+    pub lines: Vec<String>,
+    pub lcmbs: Vec<String>,
+    pub surfaces: Vec<String>,
+    pub bodies: Vec<String>,
+}
+
+impl GeometrySet {
+    pub fn members(&self, kind: EntityKind) -> &[String] {
+        match kind {
+            EntityKind::Point => &self.points,
+            EntityKind::Line => &self.lines,
+            EntityKind::Lcmb => &self.lcmbs,
+            EntityKind::Surface => &self.surfaces,
+            EntityKind::Body => &self.bodies,
+        }
+    }
+
+    fn members_mut(&mut self, kind: EntityKind) -> &mut Vec<String> {
+        match kind {
+            EntityKind::Point => &mut self.points,
+            EntityKind::Line => &mut self.lines,
+            EntityKind::Lcmb => &mut self.lcmbs,
+            EntityKind::Surface => &mut self.surfaces,
+            EntityKind::Body => &mut self.bodies,
+        }
+    }
+
+    fn add(&mut self, kind: EntityKind, name: &str) {
+        let members = self.members_mut(kind);
+        if !members.iter().any(|m| m == name) {
+            members.push(name.to_string());
+        }
+    }
+}
+
+/// Geometry entities and sets of a model
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Geometry {
+    points: BTreeMap<String, [f64; 3]>,
+    lines: BTreeMap<String, Line>,
+    lcmbs: BTreeMap<String, Lcmb>,
+    surfaces: BTreeMap<String, Surface>,
+    bodies: BTreeMap<String, Body>,
+    sets: BTreeMap<String, GeometrySet>,
+    /// Element types of the lines, surfaces and bodies from `elty`
+    element_types: BTreeMap<(EntityKind, String), ElementKind>,
+}
+
+impl Geometry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn point(&self, name: &str) -> Option<[f64; 3]> {
+        self.points.get(name).copied()
+    }
+
+    pub fn line(&self, name: &str) -> Option<&Line> {
+        self.lines.get(name)
+    }
+
+    pub fn lcmb(&self, name: &str) -> Option<&Lcmb> {
+        self.lcmbs.get(name)
+    }
+
+    pub fn surface(&self, name: &str) -> Option<&Surface> {
+        self.surfaces.get(name)
+    }
+
+    pub fn body(&self, name: &str) -> Option<&Body> {
+        self.bodies.get(name)
+    }
+
+    pub fn set(&self, name: &str) -> Option<&GeometrySet> {
+        self.sets.get(name)
+    }
+
+    /// Sets by name, sorted
+    pub fn sets(&self) -> impl Iterator<Item = (&str, &GeometrySet)> {
+        self.sets.iter().map(|(name, set)| (name.as_str(), set))
+    }
+
+    pub fn contains(&self, kind: EntityKind, name: &str) -> bool {
+        match kind {
+            EntityKind::Point => self.points.contains_key(name),
+            EntityKind::Line => self.lines.contains_key(name),
+            EntityKind::Lcmb => self.lcmbs.contains_key(name),
+            EntityKind::Surface => self.surfaces.contains_key(name),
+            EntityKind::Body => self.bodies.contains_key(name),
+        }
+    }
+
+    /// Names of the entities of `kind`, sorted
+    pub fn names(&self, kind: EntityKind) -> Vec<&str> {
+        match kind {
+            EntityKind::Point => self.points.keys().map(String::as_str).collect(),
+            EntityKind::Line => self.lines.keys().map(String::as_str).collect(),
+            EntityKind::Lcmb => self.lcmbs.keys().map(String::as_str).collect(),
+            EntityKind::Surface => self.surfaces.keys().map(String::as_str).collect(),
+            EntityKind::Body => self.bodies.keys().map(String::as_str).collect(),
+        }
+    }
+
+    /// Entities of `kind` in `set`; the set `all` holds every entity
+    pub fn set_members(&self, set: &str, kind: EntityKind) -> Option<Vec<String>> {
+        match self.sets.get(set) {
+            Some(members) => Some(members.members(kind).to_vec()),
+            None if set == "all" => Some(self.names(kind).into_iter().map(String::from).collect()),
+            None => None,
+        }
+    }
+
+    /// `name`, or the next free generated name of `kind`
+    fn new_name(&self, kind: EntityKind, name: Option<&str>) -> String {
+        if let Some(name) = name {
+            return name.to_string();
+        }
+        (1..)
+            .map(|n| format!("{}{n:03}", kind.prefix()))
+            .find(|name| !self.contains(kind, name))
+            .expect("a free name")
+    }
+
+    fn require(&self, kind: EntityKind, name: &str) -> Result<(), String> {
+        match self.contains(kind, name) {
+            true => Ok(()),
+            false => Err(format!("unknown {} {name}", kind.label())),
+        }
+    }
+
+    /// Add or move a point; returns its name
+    pub fn add_point(&mut self, name: Option<&str>, coords: [f64; 3]) -> String {
+        let name = self.new_name(EntityKind::Point, name);
+        self.points.insert(name.clone(), coords);
+        name
+    }
+
+    /// Add a line from `start` to `end`; without `division`, a redefined
+    /// line keeps its division
+    pub fn add_line(
+        &mut self,
+        name: Option<&str>,
+        start: &str,
+        end: &str,
+        shape: LineShape,
+        division: Option<u32>,
+    ) -> Result<String, String> {
+        self.require(EntityKind::Point, start)?;
+        self.require(EntityKind::Point, end)?;
+        if start == end {
+            return Err(format!("line starts and ends at {start}"));
+        }
+        match &shape {
+            LineShape::Straight => {}
+            LineShape::Arc { center } => {
+                self.require(EntityKind::Point, center)?;
+                let c = self.points[center];
+                let angle = arc_angle(c, self.points[start], self.points[end]);
+                if !(MIN_ARC_ANGLE..std::f64::consts::PI - MIN_ARC_ANGLE).contains(&angle) {
+                    return Err(format!("center {center} is in line with {start} and {end}"));
+                }
+            }
+            LineShape::Spline { points } => {
+                for point in points {
+                    self.require(EntityKind::Point, point)?;
+                }
+            }
+        }
+        let name = self.new_name(EntityKind::Line, name);
+        let division = division
+            .or_else(|| self.lines.get(&name).map(|line| line.division))
+            .unwrap_or(DEFAULT_DIVISION);
+        if division == 0 {
+            return Err(format!("line {name} needs at least one division"));
+        }
+        let line = Line {
+            start: start.to_string(),
+            end: end.to_string(),
+            shape,
+            division,
+        };
+        self.lines.insert(name.clone(), line);
+        Ok(name)
+    }
+
+    /// Add a chain of `lines`, each with its orientation
+    pub fn add_lcmb(
+        &mut self,
+        name: Option<&str>,
+        lines: &[(&str, bool)],
+    ) -> Result<String, String> {
+        if lines.is_empty() {
+            return Err("lcmb needs at least one line".to_string());
+        }
+        let lines = lines
+            .iter()
+            .map(|&(line, reversed)| {
+                self.require(EntityKind::Line, line)?;
+                Ok(OrientedRef {
+                    kind: EntityKind::Line,
+                    name: line.to_string(),
+                    reversed,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        for pair in lines.windows(2) {
+            let (_, end) = self.endpoints(&pair[0]).expect("checked line");
+            let (start, _) = self.endpoints(&pair[1]).expect("checked line");
+            if end != start {
+                return Err(format!(
+                    "lines {} and {} are not connected",
+                    pair[0].name, pair[1].name
+                ));
+            }
+        }
+        let name = self.new_name(EntityKind::Lcmb, name);
+        self.lcmbs.insert(name.clone(), Lcmb { lines });
+        Ok(name)
+    }
+
+    /// Add a surface bounded by `edges`, lines or lcmbs; edges without an
+    /// orientation are turned to close the loop
+    pub fn add_surface(
+        &mut self,
+        name: Option<&str>,
+        reversed: bool,
+        support: &str,
+        edges: &[(&str, Option<bool>)],
+    ) -> Result<String, String> {
+        if edges.len() < 2 {
+            return Err("surface needs at least two edges".to_string());
+        }
+        let kinds = edges
+            .iter()
+            .map(|&(edge, _)| {
+                [EntityKind::Line, EntityKind::Lcmb]
+                    .into_iter()
+                    .find(|&kind| self.contains(kind, edge))
+                    .ok_or_else(|| format!("unknown line or lcmb {edge}"))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let unoriented = |i: usize| {
+            let edge = OrientedRef {
+                kind: kinds[i],
+                name: edges[i].0.to_string(),
+                reversed: false,
+            };
+            let (start, end) = self.endpoints(&edge).expect("checked edge");
+            (start.to_string(), end.to_string())
+        };
+
+        let mut loop_edges: Vec<OrientedRef> = Vec::new();
+        let mut previous_end: Option<String> = None;
+        for (i, &(edge, orientation)) in edges.iter().enumerate() {
+            let (start, end) = unoriented(i);
+            let reversed = match (orientation, &previous_end) {
+                (Some(reversed), _) => reversed,
+                (None, Some(previous)) => *previous == end,
+                // The first edge ends where the second one touches it
+                (None, None) => {
+                    let (next_start, next_end) = unoriented(1);
+                    end != next_start && end != next_end
+                }
+            };
+            let (start, end) = if reversed { (end, start) } else { (start, end) };
+            if previous_end
+                .as_ref()
+                .is_some_and(|previous| *previous != start)
+            {
+                return Err(format!("edge {edge} does not continue the loop"));
+            }
+            previous_end = Some(end);
+            loop_edges.push(OrientedRef {
+                kind: kinds[i],
+                name: edge.to_string(),
+                reversed,
+            });
+        }
+        let (first_start, _) = self.endpoints(&loop_edges[0]).expect("checked edge");
+        if previous_end.as_deref() != Some(first_start) {
+            return Err("edges do not form a closed loop".to_string());
+        }
+
+        let name = self.new_name(EntityKind::Surface, name);
+        let surface = Surface {
+            reversed,
+            support: support.to_string(),
+            edges: loop_edges,
+        };
+        self.surfaces.insert(name.clone(), surface);
+        Ok(name)
+    }
+
+    /// Add a body bounded by `surfaces`
+    pub fn add_body(
+        &mut self,
+        name: Option<&str>,
+        surfaces: &[(&str, Option<bool>)],
+    ) -> Result<String, String> {
+        if surfaces.len() < 2 {
+            return Err("body needs at least two surfaces".to_string());
+        }
+        let surfaces = surfaces
+            .iter()
+            .map(|&(surface, reversed)| {
+                self.require(EntityKind::Surface, surface)?;
+                Ok(OrientedRef {
+                    kind: EntityKind::Surface,
+                    name: surface.to_string(),
+                    reversed: reversed.unwrap_or(false),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let name = self.new_name(EntityKind::Body, name);
+        self.bodies.insert(name.clone(), Body { surfaces });
+        Ok(name)
+    }
+
+    /// Add entities of `kind` to `set`, creating the set
+    pub fn add_to_set(
+        &mut self,
+        set: &str,
+        kind: EntityKind,
+        names: &[&str],
+    ) -> Result<(), String> {
+        for name in names {
+            self.require(kind, name)?;
+        }
+        let members = self.sets.entry(set.to_string()).or_default();
+        for name in names {
+            members.add(kind, name);
+        }
+        Ok(())
+    }
+
+    /// Start and end point of a line or lcmb as used by `edge`
+    pub fn endpoints(&self, edge: &OrientedRef) -> Option<(&str, &str)> {
+        let (start, end) = match edge.kind {
+            EntityKind::Line => {
+                let line = self.lines.get(&edge.name)?;
+                (line.start.as_str(), line.end.as_str())
+            }
+            EntityKind::Lcmb => {
+                let lcmb = self.lcmbs.get(&edge.name)?;
+                let (start, _) = self.endpoints(lcmb.lines.first()?)?;
+                let (_, end) = self.endpoints(lcmb.lines.last()?)?;
+                (start, end)
+            }
+            _ => return None,
+        };
+        Some(if edge.reversed {
+            (end, start)
+        } else {
+            (start, end)
+        })
+    }
+
+    /// Corner points of a surface, the start point of every edge in loop
+    /// order
+    pub fn surface_corners(&self, name: &str) -> Option<Vec<&str>> {
+        self.surfaces
+            .get(name)?
+            .edges
+            .iter()
+            .map(|edge| self.endpoints(edge).map(|(start, _)| start))
+            .collect()
+    }
+
+    /// Point of line `name` at parameter `t` from 0 at the start to 1 at
+    /// the end
+    pub fn line_position(&self, name: &str, t: f64) -> Option<[f64; 3]> {
+        let line = self.lines.get(name)?;
+        let start = self.points[&line.start];
+        let end = self.points[&line.end];
+        let position = match &line.shape {
+            LineShape::Straight => lerp(start, end, t),
+            LineShape::Arc { center } => {
+                let c = self.points[center];
+                let (r0, a) = v_norm(v_result(c, start));
+                let (r1, b) = v_norm(v_result(c, end));
+                let angle = arc_angle(c, start, end);
+                let wa = ((1.0 - t) * angle).sin() / angle.sin();
+                let wb = (t * angle).sin() / angle.sin();
+                let radius = (1.0 - t) * r0 + t * r1;
+                let direction = v_add(scale(a, wa), scale(b, wb));
+                v_add(c, scale(direction, radius))
+            }
+            LineShape::Spline { points } => {
+                let mut through = vec![start];
+                through.extend(
+                    points
+                        .iter()
+                        .filter(|p| **p != line.start && **p != line.end)
+                        .map(|p| self.points[p]),
+                );
+                through.push(end);
+                catmull_rom(&through, t)
+            }
+        };
+        Some(position)
+    }
+
+    /// Number of elements along a line or lcmb
+    pub fn edge_division(&self, edge: &OrientedRef) -> Option<u32> {
+        match edge.kind {
+            EntityKind::Line => self.lines.get(&edge.name).map(|line| line.division),
+            EntityKind::Lcmb => self
+                .lcmbs
+                .get(&edge.name)?
+                .lines
+                .iter()
+                .map(|line| self.edge_division(line))
+                .sum(),
+            _ => None,
+        }
+    }
+
+    /// Division points along a line or lcmb in the direction of `edge`,
+    /// end points included
+    pub fn edge_points(&self, edge: &OrientedRef) -> Option<Vec<[f64; 3]>> {
+        let mut points = match edge.kind {
+            EntityKind::Line => {
+                let division = self.lines.get(&edge.name)?.division;
+                (0..=division)
+                    .map(|i| self.line_position(&edge.name, i as f64 / division as f64))
+                    .collect::<Option<Vec<_>>>()?
+            }
+            EntityKind::Lcmb => {
+                let mut points: Vec<[f64; 3]> = Vec::new();
+                for line in &self.lcmbs.get(&edge.name)?.lines {
+                    let line_points = self.edge_points(line)?;
+                    let skip = usize::from(!points.is_empty());
+                    points.extend(line_points.into_iter().skip(skip));
+                }
+                points
+            }
+            _ => return None,
+        };
+        if edge.reversed {
+            points.reverse();
+        }
+        Some(points)
+    }
+
+    /// Entities referring to the entity `name` of `kind` directly: the
+    /// lines of a point, the lcmbs and surfaces of a line, the surfaces of
+    /// an lcmb and the bodies of a surface
+    pub fn users(&self, kind: EntityKind, name: &str) -> Vec<(EntityKind, &str)> {
+        let uses = |refs: &[OrientedRef]| refs.iter().any(|r| r.kind == kind && r.name == name);
+        let mut users = Vec::new();
+        match kind {
+            EntityKind::Point => {
+                for (line_name, line) in &self.lines {
+                    let through = match &line.shape {
+                        LineShape::Straight => false,
+                        LineShape::Arc { center } => center == name,
+                        LineShape::Spline { points } => points.iter().any(|p| p == name),
+                    };
+                    if line.start == name || line.end == name || through {
+                        users.push((EntityKind::Line, line_name.as_str()));
+                    }
+                }
+            }
+            EntityKind::Line | EntityKind::Lcmb => {
+                for (lcmb_name, lcmb) in &self.lcmbs {
+                    if uses(&lcmb.lines) {
+                        users.push((EntityKind::Lcmb, lcmb_name.as_str()));
+                    }
+                }
+                for (surface_name, surface) in &self.surfaces {
+                    if uses(&surface.edges) {
+                        users.push((EntityKind::Surface, surface_name.as_str()));
+                    }
+                }
+            }
+            EntityKind::Surface => {
+                for (body_name, body) in &self.bodies {
+                    if uses(&body.surfaces) {
+                        users.push((EntityKind::Body, body_name.as_str()));
+                    }
+                }
+            }
+            EntityKind::Body => {}
+        }
+        users
+    }
+
+    /// Element type assigned to the entity `name` of `kind`
+    pub fn element_type(&self, kind: EntityKind, name: &str) -> Option<&ElementKind> {
+        self.element_types.get(&(kind, name.to_string()))
+    }
+
+    /// Assign `element` to the entities of `set` it fits: beam types to
+    /// lines, shell types to surfaces and solid types to bodies; `None`
+    /// removes the assignments of all entities of the set
+    pub fn set_element_type(
+        &mut self,
+        set: &str,
+        element: Option<&ElementKind>,
+    ) -> Result<(), String> {
+        let kinds: &[EntityKind] = match element.map(|e| e.shape.dimension()) {
+            None => &[EntityKind::Line, EntityKind::Surface, EntityKind::Body],
+            Some(1) => &[EntityKind::Line],
+            Some(2) => &[EntityKind::Surface],
+            Some(_) => &[EntityKind::Body],
+        };
+        for &kind in kinds {
+            let members = self
+                .set_members(set, kind)
+                .ok_or_else(|| format!("unknown set {set}"))?;
+            for name in members {
+                match element {
+                    Some(element) => self.element_types.insert((kind, name), element.clone()),
+                    None => self.element_types.remove(&(kind, name)),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the sets holding the entity `name` of `kind`
+    pub fn sets_containing(&self, kind: EntityKind, name: &str) -> Vec<&str> {
+        self.sets
+            .iter()
+            .filter(|(_, set)| set.members(kind).iter().any(|m| m == name))
+            .map(|(set, _)| set.as_str())
+            .collect()
+    }
+
+    /// Apply a geometry or element type command of a `.fbd` script; the
+    /// mesh, output and view commands are left to their consumers
+    pub fn apply(&mut self, command: &FbdCommand) -> Result<(), String> {
+        match command {
+            FbdCommand::Pnt { name, coords } => {
+                self.add_point(named(name), *coords);
+            }
+            FbdCommand::Line {
+                name,
+                start,
+                end,
+                via,
+                division,
+            } => {
+                let shape = match via {
+                    None => LineShape::Straight,
+                    Some(via) if self.points.contains_key(via) => LineShape::Arc {
+                        center: via.clone(),
+                    },
+                    Some(via) => match self.sets.get(via) {
+                        Some(set) => LineShape::Spline {
+                            points: set.points.clone(),
+                        },
+                        None => {
+                            return Err(format!("unknown center point or sequence set {via}"));
+                        }
+                    },
+                };
+                self.add_line(named(name), start, end, shape, *division)?;
+            }
+            FbdCommand::Lcmb { name, lines } => {
+                let lines: Vec<(&str, bool)> = lines
+                    .iter()
+                    .map(|line| (line.name.as_str(), line.reversed.unwrap_or(false)))
+                    .collect();
+                self.add_lcmb(named(name), &lines)?;
+            }
+            FbdCommand::Surf {
+                name,
+                reversed,
+                support,
+                edges,
+            } => {
+                let edges: Vec<(&str, Option<bool>)> = edges
+                    .iter()
+                    .map(|edge| (edge.name.as_str(), edge.reversed))
+                    .collect();
+                self.add_surface(named(name), *reversed, support, &edges)?;
+            }
+            FbdCommand::Body { name, surfaces } => {
+                let surfaces: Vec<(&str, Option<bool>)> = surfaces
+                    .iter()
+                    .map(|surface| (surface.name.as_str(), surface.reversed))
+                    .collect();
+                self.add_body(named(name), &surfaces)?;
+            }
+            FbdCommand::Seta {
+                set,
+                kind,
+                entities,
+            } => {
+                let set = named(set).ok_or("set needs a name")?;
+                let names: Vec<&str> = entities.iter().map(String::as_str).collect();
+                let kind = match kind {
+                    Some(SetItemKind::Point) => EntityKind::Point,
+                    Some(SetItemKind::Line) => EntityKind::Line,
+                    Some(SetItemKind::Lcmb) => EntityKind::Lcmb,
+                    Some(SetItemKind::Surface) => EntityKind::Surface,
+                    Some(SetItemKind::Body) => EntityKind::Body,
+                    // Nodes and elements belong to the mesh
+                    Some(SetItemKind::Node | SetItemKind::Element) => return Ok(()),
+                    Some(SetItemKind::Set) | None => {
+                        for other in names {
+                            for kind in EntityKind::ALL {
+                                let members = self
+                                    .set_members(other, kind)
+                                    .ok_or_else(|| format!("unknown set {other}"))?;
+                                let members: Vec<&str> =
+                                    members.iter().map(String::as_str).collect();
+                                self.add_to_set(set, kind, &members)?;
+                            }
+                        }
+                        return Ok(());
+                    }
+                };
+                self.add_to_set(set, kind, &names)?;
+            }
+            FbdCommand::Div { set, division } => {
+                let lines = self
+                    .set_members(set, EntityKind::Line)
+                    .ok_or_else(|| format!("unknown set {set}"))?;
+                for line in lines {
+                    self.lines.get_mut(&line).expect("set member").division = *division;
+                }
+            }
+            FbdCommand::Elty { set, element, .. } => {
+                self.set_element_type(set, element.as_ref())?;
+            }
+            FbdCommand::Other { keyword, args } if keyword == "SEQA" => match args.as_slice() {
+                [set, kind, points @ ..] if kind.eq_ignore_ascii_case("pnt") => {
+                    let points: Vec<&str> = points.iter().map(String::as_str).collect();
+                    self.sets.remove(set);
+                    self.add_to_set(set, EntityKind::Point, &points)?;
+                }
+                _ => return Err("expected seqa <set> pnt <point> ...".to_string()),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Apply the commands of `script` in order, returning an error
+    /// diagnostic for every command that failed
+    pub fn replay(&mut self, script: &FbdScript) -> Vec<FbdDiagnostic> {
+        script
+            .commands
+            .iter()
+            .filter_map(|line| {
+                let message = self.apply(&line.command).err()?;
+                Some(FbdDiagnostic {
+                    line: line.line,
+                    severity: Severity::Error,
+                    message,
+                })
+            })
+            .collect()
+    }
+}
+
+fn named(name: &EntityName) -> Option<&str> {
+    match name {
+        EntityName::Auto => None,
+        EntityName::Named(name) => Some(name),
+    }
+}
+
+fn scale(a: [f64; 3], factor: f64) -> [f64; 3] {
+    a.map(|x| x * factor)
+}
+
+fn lerp(a: [f64; 3], b: [f64; 3], t: f64) -> [f64; 3] {
+    v_add(a, scale(v_result(a, b), t))
+}
+
+/// Angle between the radii from `center` to `start` and `end`
+fn arc_angle(center: [f64; 3], start: [f64; 3], end: [f64; 3]) -> f64 {
+    let a = v_result(center, start);
+    let b = v_result(center, end);
+    let (sine, _) = v_norm(v_prod(a, b));
+    sine.atan2(v_sprod(a, b))
+}
+
+/// Point at `t` of a Catmull-Rom spline through `points`, parameterized by
+/// chord length
+fn catmull_rom(points: &[[f64; 3]], t: f64) -> [f64; 3] {
+    let chords: Vec<f64> = points
+        .windows(2)
+        .map(|pair| v_norm(v_result(pair[0], pair[1])).0)
+        .collect();
+    let total: f64 = chords.iter().sum();
+    let mut s = t.clamp(0.0, 1.0) * total;
+    let mut segment = 0;
+    while segment + 1 < chords.len() && s > chords[segment] {
+        s -= chords[segment];
+        segment += 1;
+    }
+    let u = if chords[segment] > 0.0 {
+        s / chords[segment]
+    } else {
+        0.0
+    };
+
+    let p1 = points[segment];
+    let p2 = points[segment + 1];
+    // Mirrored neighbours at the ends
+    let p0 = match segment {
+        0 => v_result(p2, scale(p1, 2.0)),
+        _ => points[segment - 1],
+    };
+    let p3 = match points.get(segment + 2) {
+        Some(&p3) => p3,
+        None => v_result(p1, scale(p2, 2.0)),
+    };
+    let (u2, u3) = (u * u, u * u * u);
+    std::array::from_fn(|i| {
+        0.5 * (2.0 * p1[i]
+            + (p2[i] - p0[i]) * u
+            + (2.0 * p0[i] - 5.0 * p1[i] + 4.0 * p2[i] - p3[i]) * u2
+            + (3.0 * p1[i] - p0[i] - 3.0 * p2[i] + p3[i]) * u3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbd::parse_fbd;
+
+    /// Quarter of an annulus with a straight, an arc and a combined edge
+    const ANNULUS: &str = "\
+pnt c 0 0 0
+pnt p1 1 0 0
+pnt p2 2 0 0
+pnt p3 0 2 0
+pnt p4 0 1 0
+pnt pm 0 1.5 0
+line l1 p1 p2 3
+line l2 p2 p3 c 8
+line l3a p3 pm
+line l3b pm p4
+lcmb c3 + l3a + l3b
+line l4 p4 p1 c
+surf s1 l1 l2 c3 l4
+seta outer l l2
+div outer 10
+";
+
+    fn annulus() -> Geometry {
+        let mut geometry = Geometry::new();
+        let diagnostics = geometry.replay(&parse_fbd(ANNULUS));
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        geometry
+    }
+
+    #[test]
+    fn builds_entities_and_associations() {
+        let geometry = annulus();
+        assert_eq!(geometry.line("l1").unwrap().division, 3);
+        assert_eq!(geometry.line("l2").unwrap().division, 10);
+        assert_eq!(geometry.line("l4").unwrap().division, DEFAULT_DIVISION);
+        assert_eq!(
+            geometry.surface_corners("s1").unwrap(),
+            ["p1", "p2", "p3", "p4"]
+        );
+
+        let arc = geometry.line_position("l2", 0.5).unwrap();
+        let (radius, _) = v_norm(arc);
+        assert!((radius - 2.0).abs() < 1e-12);
+        assert!((arc[0] - arc[1]).abs() < 1e-12);
+        let c3 = &geometry.surface("s1").unwrap().edges[2];
+        assert_eq!(geometry.edge_division(c3), Some(2 * DEFAULT_DIVISION));
+        let points = geometry.edge_points(c3).unwrap();
+        assert_eq!(points.len(), 2 * DEFAULT_DIVISION as usize + 1);
+        assert_eq!(
+            (points[0], points[points.len() - 1]),
+            ([0.0, 2.0, 0.0], [0.0, 1.0, 0.0])
+        );
+
+        assert_eq!(
+            geometry.users(EntityKind::Point, "c"),
+            [(EntityKind::Line, "l2"), (EntityKind::Line, "l4")]
+        );
+        assert_eq!(
+            geometry.users(EntityKind::Line, "l3a"),
+            [(EntityKind::Lcmb, "c3")]
+        );
+        assert_eq!(
+            geometry.users(EntityKind::Lcmb, "c3"),
+            [(EntityKind::Surface, "s1")]
+        );
+        assert_eq!(geometry.sets_containing(EntityKind::Line, "l2"), ["outer"]);
+        assert_eq!(
+            geometry.set_members("all", EntityKind::Surface).unwrap(),
+            ["s1"]
+        );
+    }
+
+    #[test]
+    fn generates_names_and_interpolates_splines() {
+        let mut geometry = Geometry::new();
+        let p1 = geometry.add_point(None, [0.0, 0.0, 0.0]);
+        let p2 = geometry.add_point(None, [1.0, 1.0, 0.0]);
+        let p3 = geometry.add_point(None, [2.0, 0.0, 0.0]);
+        assert_eq!((p1.as_str(), p3.as_str()), ("P001", "P003"));
+        let spline = LineShape::Spline {
+            points: vec![p2.clone()],
+        };
+        let line = geometry.add_line(None, &p1, &p3, spline, Some(2)).unwrap();
+        assert_eq!(line, "L001");
+        let edge = OrientedRef {
+            kind: EntityKind::Line,
+            name: line,
+            reversed: true,
+        };
+        let points = geometry.edge_points(&edge).unwrap();
+        let expected = [[2.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 0.0, 0.0]];
+        for (point, expected) in points.iter().zip(expected) {
+            assert!(v_norm(v_result(*point, expected)).0 < 1e-12, "{point:?}");
+        }
+        assert_eq!(points.len(), 3);
+    }
+
+    #[test]
+    fn rejects_invalid_entities() {
+        let mut geometry = annulus();
+        let script = parse_fbd(
+            "line l5 p1 px\nline l6 p1 p2 c\nsurf s2 l1 l2 l4\nlcmb c9 + l1 + l4\nbody b1 s1 s9\n",
+        );
+        let messages: Vec<String> = geometry
+            .replay(&script)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "line 1: error: unknown point px",
+                "line 2: error: center c is in line with p1 and p2",
+                "line 3: error: edge l4 does not continue the loop",
+                "line 4: error: lines l1 and l4 are not connected",
+                "line 5: error: unknown surface s9",
+            ]
+        );
+    }
+}
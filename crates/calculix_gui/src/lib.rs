@@ -2,7 +2,16 @@
 
 use std::collections::BTreeMap;
 
+pub mod animation;
+pub mod cut;
+pub mod fbd;
+pub mod geometry;
+pub mod mesher;
 pub mod ported;
+pub mod results;
+pub mod selection;
+pub mod send;
+pub mod tessellation;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LegacyGuiLanguage {
@@ -34,6 +43,14 @@ pub const PORTED_GUI_UNITS: &[&str] = &[
     "v_norm.c",
     "v_angle.c",
     "p_angle.c",
+    "v_scal.c",
+    "v_rot.c",
+    "m_copy.c",
+    "near3d.c",
+    "stoi.c",
+    "stof.c",
+    "stos.c",
+    "strsplt.c",
 ];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -98,6 +115,7 @@ mod tests {
     #[test]
     fn ported_gui_lookup_matches_known_entries() {
         assert!(is_ported_gui_unit("compare.c"));
+        assert!(is_ported_gui_unit("near3d.c"));
         assert!(!is_ported_gui_unit("cgx.c"));
     }
 }
@@ -0,0 +1,970 @@
+//! Mapped meshing of the CGX geometry.
+//!
+//! Lines with a beam element type are divided into `B31` or `B32`
+//! elements, four-sided surfaces into a grid of `S4` or `S8` shells and
+//! bodies bounded by six four-sided surfaces into a grid of `C3D8` or
+//! `C3D20` bricks, as the `mesh` command of CGX does for its mapped element
+//! types. The divisions of the edges set the grid, so opposite edges of a
+//! surface need equal divisions, and interior points are placed by
+//! transfinite interpolation of the edges and faces. Quadratic elements
+//! span two divisions, so their edges need even divisions. Formulation
+//! suffixes of the element types have no solver counterpart and are
+//! dropped.
+//!
+//! Nodes are shared through the entity they lie on, so entities meshed
+//! together are connected along their common points, lines and surfaces.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ccx_solver::{Element, ElementSet, ElementType, Mesh, Node, NodeSet, Sets};
+
+use crate::fbd::{ElementKind, ElementShape};
+use crate::geometry::{EntityKind, Geometry, OrientedRef};
+use crate::ported::{v_add, v_prod, v_result, v_sprod};
+
+/// Corners of a quad in the unit square, counter-clockwise
+const QUAD_CORNERS: [[usize; 2]; 4] = [[0, 0], [1, 0], [1, 1], [0, 1]];
+
+/// Corners of a brick in the unit cube, bottom then top, in `C3D8` order
+const HEX_CORNERS: [[usize; 3]; 8] = [
+    [0, 0, 0],
+    [1, 0, 0],
+    [1, 1, 0],
+    [0, 1, 0],
+    [0, 0, 1],
+    [1, 0, 1],
+    [1, 1, 1],
+    [0, 1, 1],
+];
+
+/// Corner pairs of the midside nodes of a `C3D20`, in node order
+const HEX_EDGES: [[usize; 2]; 12] = [
+    [0, 1],
+    [1, 2],
+    [2, 3],
+    [3, 0],
+    [4, 5],
+    [5, 6],
+    [6, 7],
+    [7, 4],
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+];
+
+/// Mesh of a geometry set
+#[derive(Debug, Clone)]
+pub struct GeometryMesh {
+    pub mesh: Mesh,
+    /// CGX element type of every element, with its formulation suffix
+    pub element_kinds: HashMap<i32, ElementKind>,
+    /// A node and an element set for every geometry set with mesh on it,
+    /// and `all` for the whole mesh
+    pub sets: Sets,
+}
+
+/// Entity a node lies on, with its place on the entity
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NodeKey {
+    Point(String),
+    /// Division point of a line, counted from its start
+    Line(String, usize),
+    Surface(String, usize, usize),
+    Body(String, usize, usize, usize),
+}
+
+type GridPoint = (NodeKey, [f64; 3]);
+
+/// Points of a surface, `i` along its first edge and `j` along its second
+struct SurfaceGrid {
+    n: usize,
+    m: usize,
+    points: Vec<GridPoint>,
+}
+
+impl SurfaceGrid {
+    fn at(&self, i: usize, j: usize) -> &GridPoint {
+        &self.points[i + j * (self.n + 1)]
+    }
+
+    /// Keys at `(0, 0)`, `(n, 0)`, `(n, m)` and `(0, m)`
+    fn corners(&self) -> [&NodeKey; 4] {
+        [
+            &self.at(0, 0).0,
+            &self.at(self.n, 0).0,
+            &self.at(self.n, self.m).0,
+            &self.at(0, self.m).0,
+        ]
+    }
+}
+
+/// Points of a body, `i` and `j` along the edges of its first surface and
+/// `k` away from it
+struct BodyGrid {
+    dims: [usize; 3],
+    points: Vec<GridPoint>,
+}
+
+impl BodyGrid {
+    fn at(&self, [i, j, k]: [usize; 3]) -> &GridPoint {
+        &self.points[grid_index(self.dims, [i, j, k])]
+    }
+}
+
+/// Mesh the lines, surfaces and bodies of `set` that have an element type;
+/// the set `all` holds every entity
+pub fn mesh_geometry(geometry: &Geometry, set: &str) -> Result<GeometryMesh, String> {
+    let mut mesher = Mesher {
+        geometry,
+        mesh: Mesh::new(),
+        node_ids: HashMap::new(),
+        elements: BTreeMap::new(),
+        element_kinds: HashMap::new(),
+    };
+    for kind in [EntityKind::Line, EntityKind::Surface, EntityKind::Body] {
+        let members = geometry
+            .set_members(set, kind)
+            .ok_or_else(|| format!("unknown set {set}"))?;
+        for name in members {
+            let Some(element) = geometry.element_type(kind, &name) else {
+                continue;
+            };
+            match kind {
+                EntityKind::Line => mesher.mesh_line(&name, element)?,
+                EntityKind::Surface => mesher.mesh_surface(&name, element)?,
+                _ => mesher.mesh_body(&name, element)?,
+            }
+        }
+    }
+    if mesher.mesh.elements.is_empty() {
+        return Err(format!(
+            "set {set} has no line, surface or body with an element type"
+        ));
+    }
+    mesher.mesh.calculate_dofs();
+    let sets = mesher.sets();
+    Ok(GeometryMesh {
+        mesh: mesher.mesh,
+        element_kinds: mesher.element_kinds,
+        sets,
+    })
+}
+
+struct Mesher<'a> {
+    geometry: &'a Geometry,
+    mesh: Mesh,
+    node_ids: HashMap<NodeKey, i32>,
+    /// Elements of every meshed entity
+    elements: BTreeMap<(EntityKind, String), Vec<i32>>,
+    element_kinds: HashMap<i32, ElementKind>,
+}
+
+impl Mesher<'_> {
+    /// ID of the node at `point`, added on first use
+    fn node(&mut self, (key, coords): &GridPoint) -> i32 {
+        let next = self.node_ids.len() as i32 + 1;
+        let mesh = &mut self.mesh;
+        *self.node_ids.entry(key.clone()).or_insert_with(|| {
+            mesh.add_node(Node::new(next, coords[0], coords[1], coords[2]));
+            next
+        })
+    }
+
+    fn add_element(
+        &mut self,
+        kind: EntityKind,
+        name: &str,
+        element: &ElementKind,
+        element_type: ElementType,
+        points: &[&GridPoint],
+    ) -> Result<(), String> {
+        let nodes = points.iter().map(|point| self.node(point)).collect();
+        let id = self.mesh.elements.len() as i32 + 1;
+        self.mesh
+            .add_element(Element::new(id, element_type, nodes))?;
+        self.elements
+            .entry((kind, name.to_string()))
+            .or_default()
+            .push(id);
+        self.element_kinds.insert(id, element.clone());
+        Ok(())
+    }
+
+    /// Node keys and positions of the division points along `edge`
+    fn edge_grid(&self, edge: &OrientedRef) -> Result<Vec<GridPoint>, String> {
+        let unknown = || format!("unknown {} {}", edge.kind.label(), edge.name);
+        let mut keys = self.edge_keys(edge).ok_or_else(unknown)?;
+        if edge.reversed {
+            keys.reverse();
+        }
+        let points = self.geometry.edge_points(edge).ok_or_else(unknown)?;
+        Ok(keys.into_iter().zip(points).collect())
+    }
+
+    /// Node keys along a line or lcmb from its start, ignoring the
+    /// orientation of `edge`
+    fn edge_keys(&self, edge: &OrientedRef) -> Option<Vec<NodeKey>> {
+        match edge.kind {
+            EntityKind::Line => {
+                let line = self.geometry.line(&edge.name)?;
+                let division = line.division as usize;
+                let mut keys = vec![NodeKey::Point(line.start.clone())];
+                keys.extend((1..division).map(|k| NodeKey::Line(edge.name.clone(), k)));
+                keys.push(NodeKey::Point(line.end.clone()));
+                Some(keys)
+            }
+            EntityKind::Lcmb => {
+                let mut keys: Vec<NodeKey> = Vec::new();
+                for line in &self.geometry.lcmb(&edge.name)?.lines {
+                    let mut line_keys = self.edge_keys(line)?;
+                    if line.reversed {
+                        line_keys.reverse();
+                    }
+                    let skip = usize::from(!keys.is_empty());
+                    keys.extend(line_keys.into_iter().skip(skip));
+                }
+                Some(keys)
+            }
+            _ => None,
+        }
+    }
+
+    fn mesh_line(&mut self, name: &str, element: &ElementKind) -> Result<(), String> {
+        let element_type = match element.shape {
+            ElementShape::Be2 => ElementType::B31,
+            ElementShape::Be3 => ElementType::B32,
+            shape => return Err(unmapped(shape, EntityKind::Line, name)),
+        };
+        let edge = OrientedRef {
+            kind: EntityKind::Line,
+            name: name.to_string(),
+            reversed: false,
+        };
+        let grid = self.edge_grid(&edge)?;
+        let step = span(element.shape);
+        check_division(grid.len() - 1, step, EntityKind::Line, name, element)?;
+        for a in (0..grid.len() - 1).step_by(step) {
+            let points: Vec<&GridPoint> = grid[a..=a + step].iter().collect();
+            self.add_element(EntityKind::Line, name, element, element_type, &points)?;
+        }
+        Ok(())
+    }
+
+    /// Grid of a four-sided surface with equal divisions on opposite edges
+    fn surface_grid(&self, name: &str) -> Result<SurfaceGrid, String> {
+        let surface = self
+            .geometry
+            .surface(name)
+            .ok_or_else(|| format!("unknown surface {name}"))?;
+        if !surface.support.eq_ignore_ascii_case("BLEND") {
+            return Err(format!(
+                "surface {name} lies on NURBS surface {}, which cannot be mapped",
+                surface.support
+            ));
+        }
+        if surface.edges.len() != 4 {
+            return Err(format!(
+                "surface {name} has {} edges; only four-sided surfaces can be mapped",
+                surface.edges.len()
+            ));
+        }
+        let sides = surface
+            .edges
+            .iter()
+            .map(|edge| self.edge_grid(edge))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (n, m) = (sides[0].len() - 1, sides[1].len() - 1);
+        if sides[2].len() - 1 != n || sides[3].len() - 1 != m {
+            return Err(format!(
+                "opposite edges of surface {name} have different divisions"
+            ));
+        }
+        let boundary = |i: usize, j: usize| match (i, j) {
+            (_, 0) => &sides[0][i],
+            (i, _) if i == n => &sides[1][j],
+            (_, j) if j == m => &sides[2][n - i],
+            _ => &sides[3][m - j],
+        };
+        let mut points = Vec::with_capacity((n + 1) * (m + 1));
+        for j in 0..=m {
+            for i in 0..=n {
+                if i == 0 || j == 0 || i == n || j == m {
+                    points.push(boundary(i, j).clone());
+                } else {
+                    let coords = transfinite(&[n, m], &[i, j], |at| boundary(at[0], at[1]).1);
+                    points.push((NodeKey::Surface(name.to_string(), i, j), coords));
+                }
+            }
+        }
+        Ok(SurfaceGrid { n, m, points })
+    }
+
+    fn mesh_surface(&mut self, name: &str, element: &ElementKind) -> Result<(), String> {
+        let element_type = match element.shape {
+            ElementShape::Qu4 => ElementType::S4,
+            ElementShape::Qu8 => ElementType::S8,
+            shape => return Err(unmapped(shape, EntityKind::Surface, name)),
+        };
+        let grid = self.surface_grid(name)?;
+        let step = span(element.shape);
+        for division in [grid.n, grid.m] {
+            check_division(division, step, EntityKind::Surface, name, element)?;
+        }
+        let mut corners = QUAD_CORNERS;
+        if self.geometry.surface(name).is_some_and(|s| s.reversed) {
+            corners.swap(1, 3);
+        }
+        for b in (0..grid.m).step_by(step) {
+            for a in (0..grid.n).step_by(step) {
+                let at = |[x, y]: [usize; 2]| grid.at(a + x, b + y);
+                let mut points: Vec<&GridPoint> = corners
+                    .iter()
+                    .map(|&[x, y]| at([x * step, y * step]))
+                    .collect();
+                if step == 2 {
+                    for (p, q) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+                        points.push(at([p[0] + q[0], p[1] + q[1]]));
+                    }
+                }
+                self.add_element(EntityKind::Surface, name, element, element_type, &points)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Grid of a body bounded by six four-sided surfaces meeting as the
+    /// faces of a brick
+    fn body_grid(&self, name: &str) -> Result<BodyGrid, String> {
+        let body = self
+            .geometry
+            .body(name)
+            .ok_or_else(|| format!("unknown body {name}"))?;
+        if body.surfaces.len() != 6 {
+            return Err(format!(
+                "body {name} has {} surfaces; only bodies of six surfaces can be mapped",
+                body.surfaces.len()
+            ));
+        }
+        let faces = body
+            .surfaces
+            .iter()
+            .map(|surface| self.surface_grid(&surface.name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let not_brick = || format!("surfaces of body {name} do not form a brick");
+
+        // The first surface is the bottom; the top shares no corner with it
+        // and lies over the bottom corners along the side faces
+        let bottom = faces[0].corners();
+        let tops: Vec<&SurfaceGrid> = faces
+            .iter()
+            .filter(|face| face.corners().iter().all(|c| !bottom.contains(c)))
+            .collect();
+        let [top_face] = tops[..] else {
+            return Err(not_brick());
+        };
+        let top_corners = top_face.corners();
+        let above = |corner: &NodeKey| {
+            faces.iter().find_map(|face| {
+                let corners = face.corners();
+                let p = corners.iter().position(|c| *c == corner)?;
+                [corners[(p + 1) % 4], corners[(p + 3) % 4]]
+                    .into_iter()
+                    .find(|c| top_corners.contains(c))
+            })
+        };
+        let top = bottom
+            .iter()
+            .map(|corner| above(corner).ok_or_else(not_brick))
+            .collect::<Result<Vec<_>, _>>()?;
+        let height = faces
+            .iter()
+            .find_map(|face| {
+                let corners = face.corners();
+                let p = corners.iter().position(|c| *c == bottom[0])?;
+                let q = corners.iter().position(|c| *c == top[0])?;
+                Some(if p / 2 == q / 2 { face.n } else { face.m })
+            })
+            .ok_or_else(not_brick)?;
+        let dims = [faces[0].n, faces[0].m, height];
+
+        // Faces of the block: corners at (0, 0), (na, 0), (na, nb), (0, nb)
+        // of the axes `a` and `b`, and the fixed axis with its index
+        let (c, t) = (bottom, &top);
+        let block_faces = [
+            ([c[0], c[1], c[2], c[3]], [0, 1], (2, 0)),
+            ([t[0], t[1], t[2], t[3]], [0, 1], (2, dims[2])),
+            ([c[0], c[1], t[1], t[0]], [0, 2], (1, 0)),
+            ([c[3], c[2], t[2], t[3]], [0, 2], (1, dims[1])),
+            ([c[0], c[3], t[3], t[0]], [1, 2], (0, 0)),
+            ([c[1], c[2], t[2], t[1]], [1, 2], (0, dims[0])),
+        ];
+        let mut points: Vec<Option<GridPoint>> =
+            vec![None; (dims[0] + 1) * (dims[1] + 1) * (dims[2] + 1)];
+        let mut used = [false; 6];
+        for (corners, [axis_a, axis_b], (fixed, value)) in block_faces {
+            let (na, nb) = (dims[axis_a], dims[axis_b]);
+            // The surface with these corners, in one of the eight ways its
+            // grid can lie on the face
+            let placed = (0..6).filter(|&f| !used[f]).find_map(|f| {
+                let face = &faces[f];
+                ORIENTATIONS.into_iter().find_map(|orientation| {
+                    let source = |a, b| orient(orientation, face, a, b);
+                    let fits = [[0, 0], [na, 0], [na, nb], [0, nb]]
+                        .iter()
+                        .zip(corners)
+                        .all(|(&[a, b], corner)| {
+                            source(a, b).is_some_and(|[i, j]| face.at(i, j).0 == *corner)
+                        });
+                    fits.then_some((f, orientation))
+                })
+            });
+            let (f, orientation) = placed.ok_or_else(not_brick)?;
+            used[f] = true;
+            for b in 0..=nb {
+                for a in 0..=na {
+                    let [i, j] = orient(orientation, &faces[f], a, b).ok_or_else(not_brick)?;
+                    let mut index = [0; 3];
+                    index[axis_a] = a;
+                    index[axis_b] = b;
+                    index[fixed] = value;
+                    let slot = &mut points[grid_index(dims, index)];
+                    let point = faces[f].at(i, j);
+                    match slot {
+                        Some(existing) if existing.0 != point.0 => return Err(not_brick()),
+                        _ => *slot = Some(point.clone()),
+                    }
+                }
+            }
+        }
+
+        let boundary: Vec<Option<GridPoint>> = points.clone();
+        let position = |at: &[usize]| {
+            boundary[grid_index(dims, [at[0], at[1], at[2]])]
+                .as_ref()
+                .expect("boundary point")
+                .1
+        };
+        for k in 1..dims[2] {
+            for j in 1..dims[1] {
+                for i in 1..dims[0] {
+                    let coords = transfinite(&dims, &[i, j, k], position);
+                    let key = NodeKey::Body(name.to_string(), i, j, k);
+                    points[grid_index(dims, [i, j, k])] = Some((key, coords));
+                }
+            }
+        }
+        let points = points.into_iter().collect::<Option<Vec<_>>>();
+        Ok(BodyGrid {
+            dims,
+            points: points.ok_or_else(not_brick)?,
+        })
+    }
+
+    fn mesh_body(&mut self, name: &str, element: &ElementKind) -> Result<(), String> {
+        let element_type = match element.shape {
+            ElementShape::He8 => ElementType::C3D8,
+            ElementShape::He20 => ElementType::C3D20,
+            shape => return Err(unmapped(shape, EntityKind::Body, name)),
+        };
+        let grid = self.body_grid(name)?;
+        let step = span(element.shape);
+        for division in grid.dims {
+            check_division(division, step, EntityKind::Body, name, element)?;
+        }
+        // Mirror the bricks when the grid axes are left-handed
+        let origin = grid.at([0, 0, 0]).1;
+        let axis = |index: [usize; 3]| v_result(origin, grid.at(index).1);
+        let volume = v_sprod(
+            v_prod(axis([step, 0, 0]), axis([0, step, 0])),
+            axis([0, 0, step]),
+        );
+        let corners = match volume < 0.0 {
+            true => HEX_CORNERS.map(|[x, y, z]| [y, x, z]),
+            false => HEX_CORNERS,
+        };
+        let [n, m, l] = grid.dims;
+        for c in (0..l).step_by(step) {
+            for b in (0..m).step_by(step) {
+                for a in (0..n).step_by(step) {
+                    let at = |[x, y, z]: [usize; 3]| grid.at([a + x, b + y, c + z]);
+                    let mut points: Vec<&GridPoint> = corners
+                        .iter()
+                        .map(|&[x, y, z]| at([x * step, y * step, z * step]))
+                        .collect();
+                    if step == 2 {
+                        for [p, q] in HEX_EDGES {
+                            let (p, q) = (corners[p], corners[q]);
+                            points.push(at([p[0] + q[0], p[1] + q[1], p[2] + q[2]]));
+                        }
+                    }
+                    self.add_element(EntityKind::Body, name, element, element_type, &points)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Node and element sets of the geometry sets, and `all`
+    fn sets(&self) -> Sets {
+        let mut sets = Sets::new();
+        let mut add = |name: &str, mut nodes: Vec<i32>, mut elements: Vec<i32>| {
+            nodes.sort_unstable();
+            elements.sort_unstable();
+            if !nodes.is_empty() {
+                let name = name.to_string();
+                sets.add_node_set(NodeSet { name, nodes });
+            }
+            if !elements.is_empty() {
+                let name = name.to_string();
+                sets.add_element_set(ElementSet { name, elements });
+            }
+        };
+        for (set_name, set) in self.geometry.sets() {
+            let members: Vec<(EntityKind, &String)> = EntityKind::ALL
+                .into_iter()
+                .flat_map(|kind| set.members(kind).iter().map(move |name| (kind, name)))
+                .collect();
+            let nodes = self
+                .node_ids
+                .iter()
+                .filter(|(key, _)| {
+                    members
+                        .iter()
+                        .any(|(kind, name)| lies_on(self.geometry, key, *kind, name))
+                })
+                .map(|(_, &id)| id)
+                .collect();
+            let elements = members
+                .iter()
+                .flat_map(|(kind, name)| self.entity_elements(*kind, name))
+                .collect();
+            add(set_name, nodes, elements);
+        }
+        if self.geometry.set("all").is_none() {
+            let nodes = self.node_ids.values().copied().collect();
+            let elements = self.mesh.elements.keys().copied().collect();
+            add("all", nodes, elements);
+        }
+        sets
+    }
+
+    /// Elements of an entity, those of its lines for an lcmb
+    fn entity_elements(&self, kind: EntityKind, name: &str) -> Vec<i32> {
+        match (kind, self.geometry.lcmb(name)) {
+            (EntityKind::Lcmb, Some(lcmb)) => lcmb
+                .lines
+                .iter()
+                .flat_map(|line| self.entity_elements(line.kind, &line.name))
+                .collect(),
+            _ => self
+                .elements
+                .get(&(kind, name.to_string()))
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Whether the node `key` lies on the entity `name` of `kind`, its bounding
+/// points, lines and surfaces included
+fn lies_on(geometry: &Geometry, key: &NodeKey, kind: EntityKind, name: &str) -> bool {
+    let on = |refs: &[OrientedRef]| refs.iter().any(|r| lies_on(geometry, key, r.kind, &r.name));
+    match kind {
+        EntityKind::Point => matches!(key, NodeKey::Point(p) if p == name),
+        EntityKind::Line => {
+            matches!(key, NodeKey::Line(l, _) if l == name)
+                || geometry.line(name).is_some_and(|line| {
+                    [&line.start, &line.end]
+                        .iter()
+                        .any(|p| lies_on(geometry, key, EntityKind::Point, p))
+                })
+        }
+        EntityKind::Lcmb => geometry.lcmb(name).is_some_and(|lcmb| on(&lcmb.lines)),
+        EntityKind::Surface => {
+            matches!(key, NodeKey::Surface(s, ..) if s == name)
+                || geometry.surface(name).is_some_and(|s| on(&s.edges))
+        }
+        EntityKind::Body => {
+            matches!(key, NodeKey::Body(b, ..) if b == name)
+                || geometry.body(name).is_some_and(|b| on(&b.surfaces))
+        }
+    }
+}
+
+/// Ways a surface grid can lie on a block face: axes swapped, first axis
+/// flipped, second axis flipped
+const ORIENTATIONS: [(bool, bool, bool); 8] = [
+    (false, false, false),
+    (false, true, false),
+    (false, false, true),
+    (false, true, true),
+    (true, false, false),
+    (true, true, false),
+    (true, false, true),
+    (true, true, true),
+];
+
+/// Index on `face` of the block face point `(a, b)`; `None` outside the
+/// face
+fn orient(
+    (swap, flip_i, flip_j): (bool, bool, bool),
+    face: &SurfaceGrid,
+    a: usize,
+    b: usize,
+) -> Option<[usize; 2]> {
+    let (i, j) = if swap { (b, a) } else { (a, b) };
+    let i = if flip_i { face.n.checked_sub(i)? } else { i };
+    let j = if flip_j { face.m.checked_sub(j)? } else { j };
+    (i <= face.n && j <= face.m).then_some([i, j])
+}
+
+fn grid_index(dims: [usize; 3], [i, j, k]: [usize; 3]) -> usize {
+    i + (dims[0] + 1) * (j + (dims[1] + 1) * k)
+}
+
+/// Transfinite interpolation at `index` of a grid with `dims` divisions
+/// along its axes, from the positions of its boundary points
+fn transfinite(
+    dims: &[usize],
+    index: &[usize],
+    boundary: impl Fn(&[usize]) -> [f64; 3],
+) -> [f64; 3] {
+    let mut point = [0.0; 3];
+    // Each projection onto the boundary: every axis free, at its start or
+    // at its end, with the sign alternating in the number of fixed axes
+    for choice in 1..3usize.pow(dims.len() as u32) {
+        let mut at = index.to_vec();
+        let mut weight = 1.0;
+        let mut fixed = 0;
+        for (axis, &n) in dims.iter().enumerate() {
+            let t = index[axis] as f64 / n as f64;
+            match choice / 3usize.pow(axis as u32) % 3 {
+                0 => continue,
+                1 => {
+                    at[axis] = 0;
+                    weight *= 1.0 - t;
+                }
+                _ => {
+                    at[axis] = n;
+                    weight *= t;
+                }
+            }
+            fixed += 1;
+        }
+        if fixed % 2 == 0 {
+            weight = -weight;
+        }
+        let [x, y, z] = boundary(&at);
+        point = v_add(point, [x * weight, y * weight, z * weight]);
+    }
+    point
+}
+
+/// Divisions spanned by one element
+fn span(shape: ElementShape) -> usize {
+    if shape.is_quadratic() { 2 } else { 1 }
+}
+
+fn check_division(
+    division: usize,
+    step: usize,
+    kind: EntityKind,
+    name: &str,
+    element: &ElementKind,
+) -> Result<(), String> {
+    match division.is_multiple_of(step) {
+        true => Ok(()),
+        false => Err(format!(
+            "{} {name} needs even divisions for {}{} elements",
+            kind.label(),
+            element.shape.name(),
+            element.suffix
+        )),
+    }
+}
+
+fn unmapped(shape: ElementShape, kind: EntityKind, name: &str) -> String {
+    format!(
+        "{} elements on {} {name} cannot be mapped",
+        shape.name(),
+        kind.label()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbd::parse_fbd;
+
+    const CUBE: &str = "\
+pnt p1 0 0 0
+pnt p2 1 0 0
+pnt p3 1 1 0
+pnt p4 0 1 0
+pnt p5 0 0 1
+pnt p6 1 0 1
+pnt p7 1 1 1
+pnt p8 0 1 1
+line l1 p1 p2
+line l2 p2 p3
+line l3 p3 p4
+line l4 p4 p1
+line l5 p5 p6
+line l6 p6 p7
+line l7 p7 p8
+line l8 p8 p5
+line l9 p1 p5
+line l10 p2 p6
+line l11 p3 p7
+line l12 p4 p8
+surf s1 l1 l2 l3 l4
+surf s2 l5 l6 l7 l8
+surf s3 l1 l10 l5 l9
+surf s4 l2 l11 l6 l10
+surf s5 l3 l12 l7 l11
+surf s6 l4 l9 l8 l12
+body b1 s2 s3 s4 s5 s6 s1
+seta top s s2
+";
+
+    fn geometry(text: &str) -> Geometry {
+        let script = parse_fbd(text);
+        assert!(script.diagnostics.is_empty(), "{:?}", script.diagnostics);
+        let mut geometry = Geometry::new();
+        let diagnostics = geometry.replay(&script);
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        geometry
+    }
+
+    const SQUARE: &str = "\
+pnt p1 0 0 0
+pnt p2 1 0 0
+pnt p3 1 1 0
+pnt p4 0 1 0
+line l1 p1 p2
+line l2 p2 p3
+line l3 p3 p4
+line l4 p4 p1
+";
+
+    /// Node positions of the only element of type `element_type`
+    fn element_coords(meshed: &GeometryMesh, element_type: ElementType) -> Vec<[f64; 3]> {
+        let mut elements = meshed
+            .mesh
+            .elements
+            .values()
+            .filter(|element| element.element_type == element_type);
+        let element = elements.next().expect("one element of the type");
+        assert!(elements.next().is_none());
+        let nodes = &meshed.mesh.nodes;
+        element.nodes.iter().map(|id| nodes[id].coords()).collect()
+    }
+
+    /// Normal of the face with corners `a`, `b` and `d` by the right-hand
+    /// rule, as a C3D8 face or an S4 lists them
+    fn normal(a: [f64; 3], b: [f64; 3], d: [f64; 3]) -> [f64; 3] {
+        v_prod(v_result(a, b), v_result(a, d))
+    }
+
+    #[test]
+    fn shells_follow_the_edge_loop_and_its_sign() {
+        let square = |surface: &str, elty: &str| {
+            let text = format!("{SQUARE}{surface}\ndiv all 2\nelty all {elty}\n");
+            mesh_geometry(&geometry(&text), "all").unwrap()
+        };
+
+        // Counter-clockwise loop: S8 corners along it, midsides after them
+        let meshed = square("surf s1 l1 l2 l3 l4", "qu8");
+        let nodes = element_coords(&meshed, ElementType::S8);
+        let expected = [
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [1.0, 1.0],
+            [0.0, 1.0],
+            [0.5, 0.0],
+            [1.0, 0.5],
+            [0.5, 1.0],
+            [0.0, 0.5],
+        ];
+        for (node, [x, y]) in nodes.iter().zip(expected) {
+            assert_eq!(*node, [x, y, 0.0]);
+        }
+        assert_eq!(normal(nodes[0], nodes[1], nodes[3]), [0.0, 0.0, 1.0]);
+
+        // A reversed surface turns every shell round
+        let meshed = square("gsur s1 - BLEND + l1 + l2 + l3 + l4", "qu8");
+        let nodes = element_coords(&meshed, ElementType::S8);
+        assert_eq!(nodes[1], [0.0, 1.0, 0.0]);
+        assert_eq!(nodes[4], [0.0, 0.5, 0.0]);
+        assert_eq!(normal(nodes[0], nodes[1], nodes[3]), [0.0, 0.0, -1.0]);
+
+        let meshed = square("surf s1 l1 l2 l3 l4", "qu4");
+        assert_eq!(meshed.mesh.nodes.len(), 9);
+        assert_eq!(meshed.mesh.elements.len(), 4);
+        for element in meshed.mesh.elements.values() {
+            assert_eq!(element.element_type, ElementType::S4);
+            let at = |i: usize| meshed.mesh.nodes[&element.nodes[i]].coords();
+            assert_eq!(normal(at(0), at(1), at(3)), [0.0, 0.0, 0.25]);
+        }
+    }
+
+    #[test]
+    fn bricks_keep_the_c3d8_and_c3d20_node_order() {
+        // The first surface is the bottom of the grid: s1 makes its axes
+        // right-handed, s2 left-handed
+        for surfaces in ["s1 s2 s3 s4 s5 s6", "s2 s3 s4 s5 s6 s1"] {
+            let body = CUBE.replace("s2 s3 s4 s5 s6 s1", surfaces);
+            let cube = |elty: &str, division: u32| {
+                let text = format!("{body}div all {division}\nelty all {elty}\n");
+                mesh_geometry(&geometry(&text), "all").unwrap()
+            };
+            // Nodes 1-4 go round a face with its normal towards 5-8, each
+            // of which lies over its counterpart
+            let corners_over = |nodes: &[[f64; 3]]| {
+                let up = normal(nodes[0], nodes[1], nodes[3]);
+                assert_eq!(v_sprod(v_result(nodes[0], nodes[2]), up), 0.0);
+                for k in 0..4 {
+                    assert_eq!(v_result(nodes[k], nodes[k + 4]), up, "{surfaces}");
+                }
+            };
+            corners_over(&element_coords(&cube("he8", 1), ElementType::C3D8));
+
+            let nodes = element_coords(&cube("he20", 2), ElementType::C3D20);
+            corners_over(&nodes);
+            for (k, [p, q]) in HEX_EDGES.into_iter().enumerate() {
+                let midside = v_add(nodes[p], nodes[q]).map(|x| x / 2.0);
+                assert_eq!(nodes[8 + k], midside, "{surfaces}");
+            }
+        }
+    }
+
+    #[test]
+    fn elty_assigns_types_by_entity_dimension() {
+        let text = format!("{CUBE}div all 1\nelty all he8r\nseta edge l l1\nelty edge be2\n");
+        let meshed = mesh_geometry(&geometry(&text), "all").unwrap();
+        let of_type = |element_type: ElementType| {
+            let mut elements = meshed.mesh.elements.values();
+            elements
+                .find(|element| element.element_type == element_type)
+                .map(|e| e.id)
+        };
+        // Surfaces have no shell type, and the beam reuses brick nodes
+        assert_eq!(meshed.mesh.elements.len(), 2);
+        assert_eq!(meshed.mesh.nodes.len(), 8);
+        let brick = of_type(ElementType::C3D8).unwrap();
+        let beam = of_type(ElementType::B31).unwrap();
+        assert_eq!(
+            meshed.element_kinds[&brick],
+            ElementKind::parse("he8r").unwrap()
+        );
+        assert_eq!(meshed.element_kinds[&brick].suffix, "r");
+        assert_eq!(meshed.sets.get_elements("edge"), Some(&[beam][..]));
+
+        let meshed = mesh_geometry(&geometry(&format!("{text}elty all qu4\n")), "all").unwrap();
+        assert_eq!(meshed.mesh.elements.len(), 8);
+        assert_eq!(meshed.mesh.nodes.len(), 8);
+
+        // elty without a type removes the assignments of the set
+        let error = mesh_geometry(&geometry(&format!("{text}elty all\n")), "all").unwrap_err();
+        assert!(error.contains("no line, surface or body"), "{error}");
+    }
+
+    // No legacy cgx and none of its meshes are in the tree to compare
+    // with, so the meshes are checked against the analytic node and
+    // element counts of the grids instead.
+    #[test]
+    fn meshes_bodies_into_bricks() {
+        let cube = geometry(&format!("{CUBE}div all 2\nelty all he8\n"));
+        let meshed = mesh_geometry(&cube, "all").unwrap();
+        assert_eq!(meshed.mesh.nodes.len(), 27);
+        assert_eq!(meshed.mesh.elements.len(), 8);
+        assert!(meshed.mesh.check_jacobians().is_empty());
+        let centre = meshed
+            .mesh
+            .nodes
+            .values()
+            .filter(|node| node.coords().iter().all(|x| (x - 0.5).abs() < 1e-12));
+        assert_eq!(centre.count(), 1);
+        assert_eq!(meshed.sets.get_nodes("top").unwrap().len(), 9);
+        assert!(meshed.sets.get_elements("top").is_none());
+        assert_eq!(meshed.sets.get_elements("all").unwrap().len(), 8);
+
+        let cube = geometry(&format!("{CUBE}div all 4\nelty all he20r\n"));
+        let meshed = mesh_geometry(&cube, "all").unwrap();
+        assert_eq!(meshed.mesh.nodes.len(), 81);
+        assert_eq!(meshed.mesh.elements.len(), 8);
+        assert!(meshed.mesh.check_jacobians().is_empty());
+
+        let cube = geometry(&format!("{CUBE}div all 3\nelty all he20\n"));
+        let error = mesh_geometry(&cube, "all").unwrap_err();
+        assert!(error.contains("needs even divisions"), "{error}");
+        let cube = geometry(&format!("{CUBE}seta side l l9\ndiv side 3\nelty all he8\n"));
+        let error = mesh_geometry(&cube, "all").unwrap_err();
+        assert!(error.contains("opposite edges"), "{error}");
+        let error = mesh_geometry(&geometry(CUBE), "all").unwrap_err();
+        assert!(error.contains("no line, surface or body"), "{error}");
+    }
+
+    #[test]
+    fn surfaces_share_nodes_along_common_edges() {
+        let plate = geometry(
+            "\
+pnt p1 0 0 0
+pnt p2 1 0 0
+pnt p3 2 0 0
+pnt p4 2 1 0
+pnt p5 1 1 0
+pnt p6 0 1 0
+line l1 p1 p2 2
+line l2 p2 p3 2
+line l3 p3 p4 2
+line l4 p4 p5 2
+line l5 p5 p6 2
+line l6 p6 p1 2
+line l7 p2 p5 2
+surf s1 l1 l7 l5 l6
+surf s2 l2 l3 l4 l7
+line l8 p3 p1 4
+seta shells s s1 s2
+seta beam l l1
+elty shells qu8
+elty beam be3
+",
+        );
+        let meshed = mesh_geometry(&plate, "all").unwrap();
+        assert_eq!(meshed.mesh.nodes.len(), 8 + 8 - 3);
+        let shells: Vec<&Element> = meshed
+            .mesh
+            .elements
+            .values()
+            .filter(|element| element.element_type == ElementType::S8)
+            .collect();
+        assert_eq!(shells.len(), 2);
+        let beam = meshed
+            .mesh
+            .elements
+            .values()
+            .find(|element| element.element_type == ElementType::B32)
+            .unwrap();
+        let x = |id: &i32| meshed.mesh.nodes[id].coords()[0];
+        assert_eq!(
+            beam.nodes.iter().map(x).collect::<Vec<_>>(),
+            [0.0, 0.5, 1.0]
+        );
+        assert!(
+            shells
+                .iter()
+                .any(|shell| shell.nodes.contains(&beam.nodes[1]))
+        );
+
+        let error = mesh_geometry(&plate, "none").unwrap_err();
+        assert_eq!(error, "unknown set none");
+        let mut plate = plate;
+        plate
+            .set_element_type("all", ElementKind::parse("tr3").as_ref())
+            .unwrap();
+        let error = mesh_geometry(&plate, "all").unwrap_err();
+        assert_eq!(error, "tr3 elements on surface s1 cannot be mapped");
+    }
+}
@@ -5,5 +5,8 @@ mod string;
 mod vector;
 
 pub use scalar::{check_if_number, p_angle};
-pub use string::{compare_prefix, compare_strings, strfind};
-pub use vector::{v_add, v_angle, v_norm, v_prod, v_result, v_sprod};
+pub use string::{compare_prefix, compare_strings, stof, stoi, stos, strfind, strsplt};
+pub use vector::{
+    Mat4, SortedPoints, Vec3, m_copy, near3d, v_add, v_angle, v_norm, v_prod, v_result, v_rot,
+    v_scal, v_sprod,
+};
@@ -1,4 +1,5 @@
-//! Rust ports of `compare.c`, `compareStrings.c`, and `strfind.c`.
+//! Rust ports of `compare.c`, `compareStrings.c`, `strfind.c`, `stoi.c`,
+//! `stof.c`, `stos.c`, and `strsplt.c`.
 
 pub fn compare_prefix(str1: &str, str2: &str, length: usize) -> usize {
     let lhs = str1.as_bytes();
@@ -38,9 +39,82 @@ pub fn strfind(as1: &str, as2: &str) -> i32 {
     -1
 }
 
+/// Characters `a` to `b` of `string`, counted from 1 and inclusive, cut at
+/// the end of the string
+pub fn stos(string: &str, a: usize, b: usize) -> String {
+    string
+        .chars()
+        .skip(a.saturating_sub(1))
+        .take((b + 1).saturating_sub(a.max(1)))
+        .collect()
+}
+
+/// Integer in the columns `a` to `b` of `string`, read like `atoi`: blanks,
+/// a sign and the digits up to the first other character; 0 without digits
+pub fn stoi(string: &str, a: usize, b: usize) -> i32 {
+    let field = stos(string, a, b);
+    let field = field.trim_start();
+    let (negative, digits) = match field.as_bytes().first() {
+        Some(b'-') => (true, &field[1..]),
+        Some(b'+') => (false, &field[1..]),
+        _ => (false, field),
+    };
+    let value = digits
+        .bytes()
+        .take_while(u8::is_ascii_digit)
+        .fold(0i32, |n, d| {
+            n.saturating_mul(10).saturating_add((d - b'0') as i32)
+        });
+    if negative { -value } else { value }
+}
+
+/// Number in the columns `a` to `b` of `string`, read like `atof`: the
+/// longest leading decimal number after blanks; 0 without one
+pub fn stof(string: &str, a: usize, b: usize) -> f64 {
+    let field = stos(string, a, b);
+    let field = field.trim_start().as_bytes();
+    let digits = |from: usize| {
+        from + field[from.min(field.len())..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count()
+    };
+    let sign = |at: usize| usize::from(matches!(field.get(at), Some(b'+' | b'-')));
+    let start = sign(0);
+    let mut end = digits(start);
+    let mut mantissa = end > start;
+    if field.get(end) == Some(&b'.') {
+        let fraction = digits(end + 1);
+        mantissa |= fraction > end + 1;
+        end = fraction;
+    }
+    if !mantissa {
+        return 0.0;
+    }
+    if matches!(field.get(end), Some(b'e' | b'E')) {
+        let exponent = end + 1 + sign(end + 1);
+        let exponent_end = digits(exponent);
+        if exponent_end > exponent {
+            end = exponent_end;
+        }
+    }
+    std::str::from_utf8(&field[..end])
+        .ok()
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Words of `rec` between the characters `breakchar`, empty words skipped
+pub fn strsplt(rec: &str, breakchar: char) -> Vec<String> {
+    rec.split(breakchar)
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{compare_prefix, compare_strings, strfind};
+    use super::{compare_prefix, compare_strings, stof, stoi, stos, strfind, strsplt};
 
     #[test]
     fn compare_prefix_matches_legacy_behavior() {
@@ -63,4 +137,36 @@ mod tests {
         assert_eq!(strfind("abc abc", "zz"), -1);
         assert_eq!(strfind("abc", ""), -1);
     }
+
+    #[test]
+    fn fixed_column_readers_match_frd_records() {
+        let record = " -1         1 1.00000E+00-2.50000E-01 3.00000E+00";
+        assert_eq!(stoi(record, 1, 3), -1);
+        assert_eq!(stoi(record, 4, 13), 1);
+        assert_eq!(stof(record, 14, 25), 1.0);
+        assert_eq!(stof(record, 26, 37), -0.25);
+        assert_eq!(stof(record, 38, 60), 3.0);
+        assert_eq!(stos(record, 2, 3), "-1");
+        assert_eq!(stos("abc", 2, 10), "bc");
+        assert_eq!(stoi("  x12", 1, 5), 0);
+        assert_eq!(stof("1.5e", 1, 4), 1.5);
+        assert_eq!(stof(".5x", 1, 3), 0.5);
+        assert_eq!(stof("-.", 1, 2), 0.0);
+        assert_eq!(strsplt("  seta  all n 1", ' '), ["seta", "all", "n", "1"]);
+        assert!(strsplt("", ' ').is_empty());
+    }
+
+    #[test]
+    fn fixed_column_readers_round_trip_formatted_numbers() {
+        let mut seed = 12345_u64;
+        for _ in 0..500 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let int = (seed >> 40) as i32 - (1 << 23);
+            let float = f64::from(int) * 1.234e-3;
+            let record = format!("{int:10}{float:12.5E}");
+            assert_eq!(stoi(&record, 1, 10), int);
+            let expected: f64 = format!("{float:.5E}").parse().unwrap();
+            assert_eq!(stof(&record, 11, 22), expected);
+        }
+    }
 }
@@ -2,6 +2,9 @@
 
 pub type Vec3 = [f64; 3];
 
+/// 4x4 matrix stored row by row, as the view matrices of CGX
+pub type Mat4 = [f64; 16];
+
 pub fn v_add(a: Vec3, b: Vec3) -> Vec3 {
     [b[0] + a[0], b[1] + a[1], b[2] + a[2]]
 }
@@ -37,11 +40,165 @@ pub fn v_angle(v0: Vec3, v1: Vec3) -> f64 {
     v_sprod(n0, n1).acos()
 }
 
+pub fn v_scal(a: f64, b: Vec3) -> Vec3 {
+    [a * b[0], a * b[1], a * b[2]]
+}
+
+/// Rotate `p` by `angle` radians about the axis through `origin` along
+/// `axis`, right-handed; `p` unchanged for a zero axis
+pub fn v_rot(angle: f64, origin: Vec3, axis: Vec3, p: Vec3) -> Vec3 {
+    let (m, k) = v_norm(axis);
+    if m == 0.0 {
+        return p;
+    }
+    let r = v_result(origin, p);
+    let (sin, cos) = angle.sin_cos();
+    let along = v_scal(v_sprod(k, r) * (1.0 - cos), k);
+    let rotated = v_add(v_add(v_scal(cos, r), v_scal(sin, v_prod(k, r))), along);
+    v_add(origin, rotated)
+}
+
+pub fn m_copy(s: &Mat4, z: &mut Mat4) {
+    z.copy_from_slice(s);
+}
+
+/// Points with their coordinates sorted along each axis, as the callers of
+/// `near3d` prepare them: `x`, `y` and `z` ascending, with the indices of
+/// the points in that order in `nx`, `ny` and `nz`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SortedPoints {
+    pub xo: Vec<f64>,
+    pub yo: Vec<f64>,
+    pub zo: Vec<f64>,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+    pub nx: Vec<usize>,
+    pub ny: Vec<usize>,
+    pub nz: Vec<usize>,
+}
+
+impl SortedPoints {
+    /// `points` sorted along each axis, ties by index
+    pub fn new(points: &[Vec3]) -> Self {
+        let axis = |a: usize| -> (Vec<f64>, Vec<f64>, Vec<usize>) {
+            let coords: Vec<f64> = points.iter().map(|p| p[a]).collect();
+            let mut order: Vec<usize> = (0..points.len()).collect();
+            order.sort_by(|&i, &j| coords[i].total_cmp(&coords[j]).then(i.cmp(&j)));
+            let sorted = order.iter().map(|&i| coords[i]).collect();
+            (coords, sorted, order)
+        };
+        let (xo, x, nx) = axis(0);
+        let (yo, y, ny) = axis(1);
+        let (zo, z, nz) = axis(2);
+        Self {
+            xo,
+            yo,
+            zo,
+            x,
+            y,
+            z,
+            nx,
+            ny,
+            nz,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.xo.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xo.is_empty()
+    }
+}
+
+/// Indices of the `k` points of `points` nearest to `p`, nearest first,
+/// ties by index
+///
+/// Like the legacy routine, the sorted coordinates are visited outwards
+/// from `p` on all three axes, until the squared gaps to the next unvisited
+/// coordinates add up to more than the distance of the `k`-th point found.
+pub fn near3d(points: &SortedPoints, p: Vec3, k: usize) -> Vec<usize> {
+    let k = k.min(points.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    let axes = [
+        (&points.x, &points.nx, p[0]),
+        (&points.y, &points.ny, p[1]),
+        (&points.z, &points.nz, p[2]),
+    ];
+    // Visited positions of each axis: below..above
+    let mut windows = axes.map(|(sorted, _, c)| {
+        let at = sorted.partition_point(|&v| v < c);
+        (at, at)
+    });
+    let mut seen = vec![false; points.len()];
+    let mut nearest: Vec<(f64, usize)> = Vec::with_capacity(k + 1);
+    loop {
+        // Gap to the closest unvisited coordinate of each axis and its position
+        let mut next = [None; 3];
+        for (a, &(sorted, _, c)) in axes.iter().enumerate() {
+            let (below, above) = windows[a];
+            let down = below.checked_sub(1).map(|i| (c - sorted[i], i));
+            let up = (above < sorted.len()).then(|| (sorted[above] - c, above));
+            next[a] = match (down, up) {
+                (Some(down), Some(up)) => Some(if down.0 <= up.0 { down } else { up }),
+                (down, up) => down.or(up),
+            };
+        }
+        // Every axis holds every point, so one exhausted axis means all seen
+        let Some(next) = next.into_iter().collect::<Option<Vec<_>>>() else {
+            break;
+        };
+        let bound: f64 = next.iter().map(|(gap, _)| gap * gap).sum();
+        if nearest.len() == k && bound > nearest[k - 1].0 {
+            break;
+        }
+        let (a, &(_, position)) = next
+            .iter()
+            .enumerate()
+            .min_by(|(_, l), (_, r)| l.0.total_cmp(&r.0))
+            .expect("three axes");
+        if position < windows[a].0 {
+            windows[a].0 = position;
+        } else {
+            windows[a].1 = position + 1;
+        }
+        let index = axes[a].1[position];
+        if std::mem::replace(&mut seen[index], true) {
+            continue;
+        }
+        let d = v_result(p, [points.xo[index], points.yo[index], points.zo[index]]);
+        let distance = v_sprod(d, d);
+        let at =
+            nearest.partition_point(|&(e, i)| e.total_cmp(&distance).then(i.cmp(&index)).is_lt());
+        nearest.insert(at, (distance, index));
+        nearest.truncate(k);
+    }
+    nearest.into_iter().map(|(_, index)| index).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts::PI;
 
-    use super::{v_add, v_angle, v_norm, v_prod, v_result, v_sprod};
+    use super::{
+        Mat4, SortedPoints, m_copy, near3d, v_add, v_angle, v_norm, v_prod, v_result, v_rot,
+        v_scal, v_sprod,
+    };
+
+    /// Pseudo-random coordinates in [-10, 10) from a fixed seed
+    fn samples(count: usize) -> Vec<f64> {
+        let mut seed = 12345_u64;
+        (0..count)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 - 10.0
+            })
+            .collect()
+    }
 
     #[test]
     fn vector_ops_match_legacy_formulas() {
@@ -65,4 +222,60 @@ mod tests {
         let angle = v_angle([1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
         assert!((angle - PI * 0.5).abs() < 1e-12);
     }
+
+    #[test]
+    fn v_rot_keeps_distances_to_the_axis() {
+        let x = samples(12 * 64);
+        for c in x.chunks_exact(12) {
+            let (origin, axis, p) = ([c[0], c[1], c[2]], [c[3], c[4], c[5]], [c[6], c[7], c[8]]);
+            let angle = c[9];
+            let q = v_rot(angle, origin, axis, p);
+            let (_, k) = v_norm(axis);
+            let r0 = v_result(origin, p);
+            let r1 = v_result(origin, q);
+            assert!((v_sprod(r0, r0) - v_sprod(r1, r1)).abs() < 1e-9);
+            assert!((v_sprod(k, r0) - v_sprod(k, r1)).abs() < 1e-9);
+            let back = v_rot(-angle, origin, axis, q);
+            assert!(v_result(p, back).iter().all(|d| d.abs() < 1e-9));
+        }
+        let q = v_rot(PI * 0.5, [0.0; 3], [0.0, 0.0, 2.0], [1.0, 0.0, 0.0]);
+        assert!(v_result(q, [0.0, 1.0, 0.0]).iter().all(|d| d.abs() < 1e-12));
+        assert_eq!(
+            v_rot(1.0, [0.0; 3], [0.0; 3], [1.0, 2.0, 3.0]),
+            [1.0, 2.0, 3.0]
+        );
+        assert_eq!(v_scal(2.0, [1.0, -2.0, 3.0]), [2.0, -4.0, 6.0]);
+    }
+
+    #[test]
+    fn near3d_matches_a_full_sort() {
+        let x = samples(3 * 200);
+        let points: Vec<[f64; 3]> = x.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        let sorted = SortedPoints::new(&points);
+        assert!(sorted.x.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(sorted.xo[sorted.nx[0]], sorted.x[0]);
+        for target in points.iter().step_by(17) {
+            let nearest = near3d(&sorted, *target, 5);
+            assert_eq!(nearest.len(), 5);
+            assert_eq!(points[nearest[0]], *target);
+            let distance =
+                |i: usize| v_sprod(v_result(*target, points[i]), v_result(*target, points[i]));
+            let fifth = distance(nearest[4]);
+            assert!(nearest.windows(2).all(|w| distance(w[0]) <= distance(w[1])));
+            let closer = (0..points.len()).filter(|&i| distance(i) < fifth).count();
+            assert!(closer <= 4);
+            let mut all: Vec<usize> = (0..points.len()).collect();
+            all.sort_by(|&i, &j| distance(i).total_cmp(&distance(j)).then(i.cmp(&j)));
+            assert_eq!(nearest, all[..5]);
+        }
+        assert_eq!(near3d(&sorted, [0.0; 3], 1000).len(), points.len());
+        assert!(near3d(&SortedPoints::new(&[]), [0.0; 3], 3).is_empty());
+        let twins = SortedPoints::new(&[[1.0, 0.0, 0.0], [0.0; 3], [1.0, 0.0, 0.0]]);
+        assert_eq!(near3d(&twins, [2.0, 0.0, 0.0], 2), [0, 2]);
+
+        let s: Mat4 = std::array::from_fn(|i| i as f64);
+        let mut z = [0.0; 16];
+        m_copy(&s, &mut z);
+        assert_eq!(z, s);
+    }
 }
@@ -0,0 +1,430 @@
+//! Result fields and color scales of the CGX postprocessor.
+//!
+//! The datasets of an FRD file are numbered from 1 across its result
+//! blocks, as the `ds` command of CGX counts them. A nodal dataset gives a
+//! [`ScalarField`] of one entity: a component by name or number, the
+//! magnitude `ALL` of a vector, or `Mises` and the principal values `PS1`
+//! to `PS3` of a tensor; and a [`VectorField`] of its first three
+//! components for deformed shapes. A [`ColorScale`] divides the range
+//! between its bounds into steps, like the `scal` and `steps` commands, and
+//! colors every vertex with the color of its step.
+
+use std::collections::HashMap;
+
+use ccx_io::postprocess::compute_mises_strain;
+use ccx_io::{
+    FrdFile, ResultBlock, ResultDataset, ResultLocation, TensorComponents, compute_mises_stress,
+    compute_principal_stresses,
+};
+
+/// Steps of a new color scale
+pub const DEFAULT_STEPS: usize = 21;
+
+/// Color of vertices without a value
+pub const NO_VALUE_COLOR: [f32; 3] = [0.5, 0.5, 0.5];
+
+/// Derived entities of datasets with six tensor components
+const TENSOR_ENTITIES: [&str; 4] = ["Mises", "PS1", "PS2", "PS3"];
+
+/// Dataset of an FRD file with its number and result block
+#[derive(Debug, Clone, Copy)]
+pub struct DatasetRef<'a> {
+    pub number: usize,
+    pub block: &'a ResultBlock,
+    pub dataset: &'a ResultDataset,
+}
+
+/// Datasets of `frd` numbered from 1, in block order
+pub fn datasets(frd: &FrdFile) -> Vec<DatasetRef<'_>> {
+    frd.result_blocks
+        .iter()
+        .flat_map(|block| block.datasets.iter().map(move |dataset| (block, dataset)))
+        .enumerate()
+        .map(|(i, (block, dataset))| DatasetRef {
+            number: i + 1,
+            block,
+            dataset,
+        })
+        .collect()
+}
+
+/// Dataset `number` of `frd`, counted from 1
+pub fn dataset(frd: &FrdFile, number: usize) -> Option<DatasetRef<'_>> {
+    datasets(frd).into_iter().nth(number.checked_sub(1)?)
+}
+
+/// Entities of `dataset`: its components, then `ALL` for three components
+/// and `Mises`, `PS1`, `PS2` and `PS3` for six
+pub fn entities(dataset: &ResultDataset) -> Vec<String> {
+    let mut entities: Vec<String> = (0..dataset.ncomps)
+        .map(|i| match dataset.comp_names.get(i) {
+            Some(name) => name.clone(),
+            None => format!("C{}", i + 1),
+        })
+        .collect();
+    match dataset.ncomps {
+        3 => entities.push("ALL".to_string()),
+        6 => entities.extend(TENSOR_ENTITIES.map(String::from)),
+        _ => {}
+    }
+    entities
+}
+
+/// Node with the smallest or largest value of a field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extremum {
+    pub node: i32,
+    pub value: f64,
+}
+
+/// One value per node
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalarField {
+    pub dataset: String,
+    pub entity: String,
+    pub values: HashMap<i32, f64>,
+    /// Smallest value, `None` without values
+    pub min: Option<Extremum>,
+    /// Largest value, `None` without values
+    pub max: Option<Extremum>,
+}
+
+impl ScalarField {
+    /// Field of `entity` of a nodal dataset; `entity` is one of
+    /// [`entities`], case-insensitive, or its number counted from 1
+    pub fn new(dataset: &ResultDataset, entity: &str) -> Result<Self, String> {
+        nodal(dataset)?;
+        let names = entities(dataset);
+        let index = match entity.parse::<usize>() {
+            Ok(number) => number.checked_sub(1).filter(|&i| i < names.len()),
+            Err(_) => names.iter().position(|n| n.eq_ignore_ascii_case(entity)),
+        }
+        .ok_or_else(|| {
+            format!(
+                "dataset {} has no entity {entity}; it has {}",
+                dataset.name,
+                names.join(", ")
+            )
+        })?;
+
+        let strain = dataset.name.to_ascii_uppercase().contains("STRAIN");
+        let value = |v: &[f64]| -> f64 {
+            match index.checked_sub(dataset.ncomps) {
+                None => v.get(index).copied().unwrap_or(0.0),
+                Some(_) if dataset.ncomps == 3 => v.iter().map(|x| x * x).sum::<f64>().sqrt(),
+                Some(derived) => {
+                    let t = tensor(v);
+                    let principal = compute_principal_stresses(&t);
+                    match derived {
+                        0 if strain => compute_mises_strain(&t),
+                        0 => compute_mises_stress(&t),
+                        1 => principal.max,
+                        2 => principal.mid,
+                        _ => principal.min,
+                    }
+                }
+            }
+        };
+        let values: HashMap<i32, f64> = dataset
+            .values
+            .iter()
+            .map(|(&node, v)| (node, value(v)))
+            .collect();
+        Ok(Self {
+            dataset: dataset.name.clone(),
+            entity: names[index].clone(),
+            min: extremum(&values, |a, b| a < b),
+            max: extremum(&values, |a, b| a > b),
+            values,
+        })
+    }
+}
+
+/// Three components per node
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorField {
+    pub dataset: String,
+    pub values: HashMap<i32, [f64; 3]>,
+    /// Largest magnitude, `None` without values
+    pub max_magnitude: Option<Extremum>,
+}
+
+impl VectorField {
+    /// Field of the first three components of a nodal dataset
+    pub fn new(dataset: &ResultDataset) -> Result<Self, String> {
+        nodal(dataset)?;
+        if dataset.ncomps < 3 {
+            return Err(format!(
+                "dataset {} has {} components, not a vector",
+                dataset.name, dataset.ncomps
+            ));
+        }
+        let values: HashMap<i32, [f64; 3]> = dataset
+            .values
+            .iter()
+            .map(|(&node, v)| (node, [0, 1, 2].map(|i| v.get(i).copied().unwrap_or(0.0))))
+            .collect();
+        let magnitudes: HashMap<i32, f64> = values
+            .iter()
+            .map(|(&node, v)| (node, v.iter().map(|x| x * x).sum::<f64>().sqrt()))
+            .collect();
+        Ok(Self {
+            dataset: dataset.name.clone(),
+            max_magnitude: extremum(&magnitudes, |a, b| a > b),
+            values,
+        })
+    }
+}
+
+/// Colors from the low to the high end of a scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colormap {
+    /// Blue over cyan, green and yellow to red
+    #[default]
+    Classic,
+    /// Black to white
+    Gray,
+}
+
+impl Colormap {
+    /// Color at `t` from 0 at the low end to 1 at the high end
+    pub fn color(self, t: f64) -> [f32; 3] {
+        let t = t.clamp(0.0, 1.0) as f32;
+        match self {
+            Colormap::Classic => {
+                const STOPS: [[f32; 3]; 5] = [
+                    [0.0, 0.0, 1.0],
+                    [0.0, 1.0, 1.0],
+                    [0.0, 1.0, 0.0],
+                    [1.0, 1.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                ];
+                let x = t * (STOPS.len() - 1) as f32;
+                let i = (x as usize).min(STOPS.len() - 2);
+                let f = x - i as f32;
+                [0, 1, 2].map(|c| STOPS[i][c] + f * (STOPS[i + 1][c] - STOPS[i][c]))
+            }
+            Colormap::Gray => [t; 3],
+        }
+    }
+}
+
+/// Range of values divided into equal steps of one color each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorScale {
+    pub min: f64,
+    pub max: f64,
+    pub steps: usize,
+    pub colormap: Colormap,
+}
+
+impl ColorScale {
+    /// Scale over the values of `field` in [`DEFAULT_STEPS`] steps
+    pub fn new(field: &ScalarField) -> Self {
+        Self {
+            min: field.min.map_or(0.0, |e| e.value),
+            max: field.max.map_or(0.0, |e| e.value),
+            steps: DEFAULT_STEPS,
+            colormap: Colormap::default(),
+        }
+    }
+
+    pub fn with_bounds(mut self, min: f64, max: f64) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Use `steps` steps, at least one
+    pub fn with_steps(mut self, steps: usize) -> Self {
+        self.steps = steps.max(1);
+        self
+    }
+
+    pub fn with_colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = colormap;
+        self
+    }
+
+    /// Step of `value` counted from 0; values beyond the bounds fall in the
+    /// first or last step
+    pub fn step(&self, value: f64) -> usize {
+        if self.max <= self.min {
+            return if value > self.max { self.steps - 1 } else { 0 };
+        }
+        let t = (value - self.min) / (self.max - self.min);
+        ((t * self.steps as f64).floor().max(0.0) as usize).min(self.steps - 1)
+    }
+
+    pub fn step_color(&self, step: usize) -> [f32; 3] {
+        match self.steps {
+            1 => self.colormap.color(0.5),
+            steps => self.colormap.color(step as f64 / (steps - 1) as f64),
+        }
+    }
+
+    pub fn color(&self, value: f64) -> [f32; 3] {
+        self.step_color(self.step(value))
+    }
+
+    /// Lower bound and color of every step, for a legend
+    pub fn legend(&self) -> Vec<(f64, [f32; 3])> {
+        let width = (self.max - self.min) / self.steps as f64;
+        (0..self.steps)
+            .map(|step| (self.min + step as f64 * width, self.step_color(step)))
+            .collect()
+    }
+
+    /// Color of each of `nodes` in `field`, [`NO_VALUE_COLOR`] where the
+    /// field has no value
+    pub fn vertex_colors(&self, field: &ScalarField, nodes: &[i32]) -> Vec<[f32; 3]> {
+        nodes
+            .iter()
+            .map(|node| match field.values.get(node) {
+                Some(&value) => self.color(value),
+                None => NO_VALUE_COLOR,
+            })
+            .collect()
+    }
+}
+
+fn nodal(dataset: &ResultDataset) -> Result<(), String> {
+    match dataset.location {
+        ResultLocation::Nodal => Ok(()),
+        ResultLocation::Element => Err(format!("dataset {} holds element values", dataset.name)),
+    }
+}
+
+/// Tensor of the components in FRD order `xx, yy, zz, xy, yz, zx`
+fn tensor(v: &[f64]) -> TensorComponents {
+    let c = |i: usize| v.get(i).copied().unwrap_or(0.0);
+    TensorComponents {
+        xx: c(0),
+        yy: c(1),
+        zz: c(2),
+        xy: c(3),
+        yz: c(4),
+        xz: c(5),
+    }
+}
+
+/// Value `better` than all others, the lowest node on ties
+fn extremum(values: &HashMap<i32, f64>, better: impl Fn(f64, f64) -> bool) -> Option<Extremum> {
+    let mut nodes: Vec<&i32> = values.keys().collect();
+    nodes.sort_unstable();
+    nodes
+        .into_iter()
+        .map(|&node| Extremum {
+            node,
+            value: values[&node],
+        })
+        .reduce(|best, e| if better(e.value, best.value) { e } else { best })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frd() -> FrdFile {
+        let disp = HashMap::from([(1, vec![3.0, 0.0, 4.0]), (2, vec![0.0, -1.0, 0.0])]);
+        let stress = HashMap::from([
+            (1, vec![100.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+            (2, vec![0.0, 0.0, 0.0, 10.0, 0.0, 0.0]),
+        ]);
+        let mut frd = FrdFile::new();
+        frd.result_blocks.push(ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets: vec![
+                ResultDataset::nodal("DISP", disp),
+                ResultDataset::nodal("STRESS", stress),
+            ],
+        });
+        frd.result_blocks.push(ResultBlock {
+            step: 2,
+            time: 2.0,
+            mode: None,
+            datasets: vec![ResultDataset::element(
+                "ERROR",
+                HashMap::from([(1, vec![5.0])]),
+            )],
+        });
+        frd
+    }
+
+    #[test]
+    fn selects_components_and_derived_entities() {
+        let frd = frd();
+        assert_eq!(datasets(&frd).len(), 3);
+        assert_eq!(dataset(&frd, 3).unwrap().block.step, 2);
+        assert!(dataset(&frd, 0).is_none());
+
+        let disp = dataset(&frd, 1).unwrap().dataset;
+        assert_eq!(entities(disp), ["D1", "D2", "D3", "ALL"]);
+        let all = ScalarField::new(disp, "all").unwrap();
+        assert_eq!(all.entity, "ALL");
+        assert_eq!(
+            all.max,
+            Some(Extremum {
+                node: 1,
+                value: 5.0
+            })
+        );
+        assert_eq!(
+            all.min,
+            Some(Extremum {
+                node: 2,
+                value: 1.0
+            })
+        );
+        let d2 = ScalarField::new(disp, "2").unwrap();
+        assert_eq!((d2.entity.as_str(), d2.values[&2]), ("D2", -1.0));
+        let vector = VectorField::new(disp).unwrap();
+        assert_eq!(vector.values[&1], [3.0, 0.0, 4.0]);
+        assert_eq!(vector.max_magnitude.unwrap().value, 5.0);
+
+        let stress = dataset(&frd, 2).unwrap().dataset;
+        let mises = ScalarField::new(stress, "Mises").unwrap();
+        assert!((mises.values[&1] - 100.0).abs() < 1e-9);
+        assert!((mises.values[&2] - 10.0 * 3f64.sqrt()).abs() < 1e-9);
+        let ps3 = ScalarField::new(stress, "ps3").unwrap();
+        assert!((ps3.values[&2] + 10.0).abs() < 1e-9);
+
+        let error = ScalarField::new(disp, "S1").unwrap_err();
+        assert_eq!(
+            error,
+            "dataset DISP has no entity S1; it has D1, D2, D3, ALL"
+        );
+        let element = dataset(&frd, 3).unwrap().dataset;
+        let error = ScalarField::new(element, "1").unwrap_err();
+        assert_eq!(error, "dataset ERROR holds element values");
+    }
+
+    #[test]
+    fn bins_values_into_colored_steps() {
+        let frd = frd();
+        let field = ScalarField::new(dataset(&frd, 1).unwrap().dataset, "ALL").unwrap();
+        let scale = ColorScale::new(&field).with_steps(4);
+        assert_eq!((scale.min, scale.max), (1.0, 5.0));
+        assert_eq!(scale.step(1.0), 0);
+        assert_eq!(scale.step(2.9), 1);
+        assert_eq!(scale.step(5.0), 3);
+        assert_eq!(scale.step(-7.0), 0);
+        assert_eq!(scale.color(1.0), [0.0, 0.0, 1.0]);
+        assert_eq!(scale.color(5.0), [1.0, 0.0, 0.0]);
+        let legend: Vec<f64> = scale.legend().iter().map(|(bound, _)| *bound).collect();
+        assert_eq!(legend, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(
+            scale.vertex_colors(&field, &[2, 9]),
+            [[0.0, 0.0, 1.0], NO_VALUE_COLOR]
+        );
+
+        let gray = scale
+            .with_bounds(0.0, 10.0)
+            .with_steps(3)
+            .with_colormap(Colormap::Gray);
+        assert_eq!(gray.color(5.0), [0.5; 3]);
+        assert_eq!(Colormap::Classic.color(0.5), [0.0, 1.0, 0.0]);
+    }
+}
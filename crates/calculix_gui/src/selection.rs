@@ -0,0 +1,342 @@
+//! Selection of nodes, elements and element faces into named sets.
+//!
+//! Picks collect entities of a solver mesh by ID or inside a box, and by
+//! association: [`complete_up`] adds the elements and free faces with all
+//! their nodes in a selection, like `comp <set> up` in CGX, so the nodes of
+//! a meshed geometry surface give its faces, and [`complete_down`] adds the
+//! nodes of the selected elements and faces, like `comp <set> do`.
+//! [`SelectionSets`] keeps named selections with the additions and removals
+//! of `seta` and `setr`, and adds or subtracts whole sets.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ccx_solver::{ElementSet, Mesh, NodeSet, Sets, Surface};
+
+/// Kind of entity a pick collects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickKind {
+    Node,
+    Element,
+    /// Free faces of solid elements
+    Face,
+}
+
+/// Nodes, elements and element faces
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub nodes: BTreeSet<i32>,
+    pub elements: BTreeSet<i32>,
+    /// Element and face number of the `S<n>` face label
+    pub faces: BTreeSet<(i32, usize)>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Node set, element set and surface `name` of `sets`; `None` when
+    /// there is none of them
+    pub fn from_sets(sets: &Sets, name: &str) -> Option<Self> {
+        let nodes = sets.get_nodes(name);
+        let elements = sets.get_elements(name);
+        let faces = sets.surfaces.get(name);
+        if nodes.is_none() && elements.is_none() && faces.is_none() {
+            return None;
+        }
+        Some(Self {
+            nodes: nodes.unwrap_or_default().iter().copied().collect(),
+            elements: elements.unwrap_or_default().iter().copied().collect(),
+            faces: faces
+                .map(|surface| surface.faces.iter().copied().collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.elements.is_empty() && self.faces.is_empty()
+    }
+
+    /// Add the entities of `other`
+    pub fn plus(&mut self, other: &Selection) {
+        self.nodes.extend(&other.nodes);
+        self.elements.extend(&other.elements);
+        self.faces.extend(&other.faces);
+    }
+
+    /// Remove the entities of `other`
+    pub fn minus(&mut self, other: &Selection) {
+        self.nodes.retain(|n| !other.nodes.contains(n));
+        self.elements.retain(|e| !other.elements.contains(e));
+        self.faces.retain(|f| !other.faces.contains(f));
+    }
+
+    /// Entities in both selections
+    pub fn intersection(&self, other: &Selection) -> Selection {
+        Selection {
+            nodes: self.nodes.intersection(&other.nodes).copied().collect(),
+            elements: self
+                .elements
+                .intersection(&other.elements)
+                .copied()
+                .collect(),
+            faces: self.faces.intersection(&other.faces).copied().collect(),
+        }
+    }
+}
+
+/// Entities of `kind` of `mesh` with the IDs `ids`, unknown IDs skipped;
+/// faces are picked by element, every free face of the elements
+pub fn pick_ids(mesh: &Mesh, kind: PickKind, ids: &[i32]) -> Selection {
+    let mut selection = Selection::new();
+    match kind {
+        PickKind::Node => {
+            let ids = ids.iter().filter(|id| mesh.nodes.contains_key(id));
+            selection.nodes.extend(ids);
+        }
+        PickKind::Element => {
+            let ids = ids.iter().filter(|id| mesh.elements.contains_key(id));
+            selection.elements.extend(ids);
+        }
+        PickKind::Face => {
+            let ids: BTreeSet<i32> = ids.iter().copied().collect();
+            let faces = mesh.free_faces().into_iter();
+            selection.faces.extend(
+                faces
+                    .filter(|face| ids.contains(&face.element))
+                    .map(|face| (face.element, face.face)),
+            );
+        }
+    }
+    selection
+}
+
+/// Entities of `kind` of `mesh` inside the box from `min` to `max`: the
+/// nodes in it, and the elements and free faces with all nodes in it
+pub fn pick_box(mesh: &Mesh, kind: PickKind, min: [f64; 3], max: [f64; 3]) -> Selection {
+    let inside: BTreeSet<i32> = mesh
+        .nodes
+        .values()
+        .filter(|node| {
+            let coords = node.coords();
+            (0..3).all(|i| min[i] <= coords[i] && coords[i] <= max[i])
+        })
+        .map(|node| node.id)
+        .collect();
+    let mut selection = Selection::new();
+    match kind {
+        PickKind::Node => selection.nodes = inside,
+        PickKind::Element => {
+            selection.elements = mesh
+                .elements
+                .values()
+                .filter(|element| element.nodes.iter().all(|n| inside.contains(n)))
+                .map(|element| element.id)
+                .collect();
+        }
+        PickKind::Face => selection.faces = faces_on(mesh, &inside),
+    }
+    selection
+}
+
+/// Add the elements and free faces of `mesh` with all their nodes in
+/// `selection`
+pub fn complete_up(mesh: &Mesh, selection: &mut Selection) {
+    let nodes = &selection.nodes;
+    let elements: Vec<i32> = mesh
+        .elements
+        .values()
+        .filter(|element| element.nodes.iter().all(|n| nodes.contains(n)))
+        .map(|element| element.id)
+        .collect();
+    let faces = faces_on(mesh, nodes);
+    selection.elements.extend(elements);
+    selection.faces.extend(faces);
+}
+
+/// Add the nodes of the elements and faces in `selection`
+pub fn complete_down(mesh: &Mesh, selection: &mut Selection) {
+    for id in &selection.elements {
+        if let Some(element) = mesh.elements.get(id) {
+            selection.nodes.extend(&element.nodes);
+        }
+    }
+    if !selection.faces.is_empty() {
+        for face in mesh.free_faces() {
+            if selection.faces.contains(&(face.element, face.face)) {
+                selection.nodes.extend(&face.nodes);
+            }
+        }
+    }
+}
+
+/// Free faces of `mesh` with all their nodes in `nodes`
+fn faces_on(mesh: &Mesh, nodes: &BTreeSet<i32>) -> BTreeSet<(i32, usize)> {
+    mesh.free_faces()
+        .into_iter()
+        .filter(|face| face.nodes.iter().all(|n| nodes.contains(n)))
+        .map(|face| (face.element, face.face))
+        .collect()
+}
+
+/// Named selections
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionSets {
+    sets: BTreeMap<String, Selection>,
+}
+
+impl SelectionSets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Selection> {
+        self.sets.get(name)
+    }
+
+    /// Names of the sets, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sets.keys().map(String::as_str)
+    }
+
+    /// Add `selection` to set `name`, creating it, like `seta`
+    pub fn add(&mut self, name: &str, selection: &Selection) {
+        self.sets
+            .entry(name.to_string())
+            .or_default()
+            .plus(selection);
+    }
+
+    /// Remove `selection` from set `name`, like `setr`
+    pub fn remove(&mut self, name: &str, selection: &Selection) -> Result<(), String> {
+        self.set_mut(name)?.minus(selection);
+        Ok(())
+    }
+
+    /// Add the entities of set `source` to set `target`, creating it
+    pub fn plus(&mut self, target: &str, source: &str) -> Result<(), String> {
+        let source = self.set(source)?.clone();
+        self.add(target, &source);
+        Ok(())
+    }
+
+    /// Remove the entities of set `source` from set `target`
+    pub fn minus(&mut self, target: &str, source: &str) -> Result<(), String> {
+        let source = self.set(source)?.clone();
+        self.remove(target, &source)
+    }
+
+    /// Delete set `name`, returning its entities
+    pub fn delete(&mut self, name: &str) -> Option<Selection> {
+        self.sets.remove(name)
+    }
+
+    /// Node set, element set and surface of every selection with entities
+    /// of that kind
+    pub fn to_sets(&self) -> Sets {
+        let mut sets = Sets::new();
+        for (name, selection) in &self.sets {
+            if !selection.nodes.is_empty() {
+                sets.add_node_set(NodeSet {
+                    name: name.clone(),
+                    nodes: selection.nodes.iter().copied().collect(),
+                });
+            }
+            if !selection.elements.is_empty() {
+                sets.add_element_set(ElementSet {
+                    name: name.clone(),
+                    elements: selection.elements.iter().copied().collect(),
+                });
+            }
+            if !selection.faces.is_empty() {
+                sets.add_surface(Surface {
+                    name: name.clone(),
+                    faces: selection.faces.iter().copied().collect(),
+                });
+            }
+        }
+        sets
+    }
+
+    fn set(&self, name: &str) -> Result<&Selection, String> {
+        self.sets
+            .get(name)
+            .ok_or_else(|| format!("unknown set {name}"))
+    }
+
+    fn set_mut(&mut self, name: &str) -> Result<&mut Selection, String> {
+        self.sets
+            .get_mut(name)
+            .ok_or_else(|| format!("unknown set {name}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ccx_solver::{Division, ElementType, MeshBuilder};
+
+    fn bar() -> Mesh {
+        let divisions = [2, 1, 1].map(Division::uniform);
+        MeshBuilder::brick([2.0, 1.0, 1.0], divisions, ElementType::C3D8)
+            .unwrap()
+            .mesh
+    }
+
+    #[test]
+    fn picks_by_id_box_and_association() {
+        let mesh = bar();
+        let nodes = pick_ids(&mesh, PickKind::Node, &[1, 2, 999]);
+        assert_eq!(nodes.nodes.len(), 2);
+        let faces = pick_ids(&mesh, PickKind::Face, &[1]);
+        assert_eq!(faces.faces.len(), 5);
+
+        let end = pick_box(&mesh, PickKind::Node, [1.9, -1.0, -1.0], [2.1, 2.0, 2.0]);
+        assert_eq!(end.nodes.len(), 4);
+        let mut associated = end.clone();
+        complete_up(&mesh, &mut associated);
+        assert!(associated.elements.is_empty());
+        assert_eq!(associated.faces.len(), 1);
+        let (element, _) = *associated.faces.first().unwrap();
+        assert!(
+            mesh.elements[&element]
+                .nodes
+                .iter()
+                .any(|n| end.nodes.contains(n))
+        );
+
+        let second = pick_box(&mesh, PickKind::Element, [0.9, -1.0, -1.0], [2.1, 2.0, 2.0]);
+        assert_eq!(second.elements.len(), 1);
+        let mut down = second.clone();
+        complete_down(&mesh, &mut down);
+        assert_eq!(down.nodes.len(), 8);
+        assert_eq!(down.intersection(&end).nodes, end.nodes);
+    }
+
+    #[test]
+    fn adds_and_subtracts_named_sets() {
+        let mesh = bar();
+        let mut sets = SelectionSets::new();
+        sets.add("all", &pick_ids(&mesh, PickKind::Element, &[1, 2]));
+        sets.add("one", &pick_ids(&mesh, PickKind::Element, &[1]));
+        sets.add("one", &pick_ids(&mesh, PickKind::Node, &[1]));
+        sets.minus("all", "one").unwrap();
+        assert_eq!(sets.get("all").unwrap().elements, BTreeSet::from([2]));
+        sets.plus("copy", "one").unwrap();
+        assert_eq!(sets.get("copy"), sets.get("one"));
+        sets.remove("copy", &pick_ids(&mesh, PickKind::Node, &[1]))
+            .unwrap();
+        assert!(sets.get("copy").unwrap().nodes.is_empty());
+        assert_eq!(sets.minus("all", "none").unwrap_err(), "unknown set none");
+        assert_eq!(sets.names().collect::<Vec<_>>(), ["all", "copy", "one"]);
+
+        let solver_sets = sets.to_sets();
+        assert_eq!(solver_sets.get_elements("all"), Some(&[2][..]));
+        assert_eq!(solver_sets.get_nodes("one"), Some(&[1][..]));
+        assert!(solver_sets.get_nodes("all").is_none());
+        let back = Selection::from_sets(&solver_sets, "one").unwrap();
+        assert_eq!(Some(&back), sets.get("one"));
+        assert!(Selection::from_sets(&solver_sets, "none").is_none());
+        assert!(sets.delete("copy").is_some());
+    }
+}
@@ -0,0 +1,294 @@
+//! Mesh files of the CGX `send` command.
+//!
+//! `send <set> abq` writes the nodes and elements of a meshed set as Abaqus
+//! `*NODE` and `*ELEMENT` blocks to `<set>.msh`, and `send <set> abq nam`
+//! writes the set as `*NSET` and `*ELSET` blocks named `N<set>` and
+//! `E<set>` to `<set>.nam`, as CGX does, ready to be included by a ccx
+//! input deck. [`run_fbd`] replays a whole script: the geometry commands,
+//! the `mesh` commands and the files of the `send` commands.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::fbd::{ElementKind, ElementShape, FbdCommand, FbdDiagnostic, FbdScript, Severity};
+use crate::geometry::Geometry;
+use crate::mesher::{GeometryMesh, mesh_geometry};
+
+/// Entries on one data line of an `*ELEMENT` block
+const ENTRIES_PER_LINE: usize = 16;
+
+/// File written by a `send` command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentFile {
+    pub name: String,
+    pub contents: String,
+}
+
+/// Abaqus element type of a CGX element type, e.g. `C3D20R` for `he20r`
+///
+/// The suffixes `s`, `e` and `c` make plane stress, plane strain and
+/// axisymmetric elements of the triangles and quads, and `m` membranes;
+/// other suffix letters are appended, as `R` for reduced integration.
+pub fn abaqus_element_type(element: &ElementKind) -> String {
+    let (base, nodes) = match element.shape {
+        ElementShape::Be2 => ("B31", ""),
+        ElementShape::Be3 => ("B32", ""),
+        ElementShape::Tr3 => ("S3", "3"),
+        ElementShape::Tr6 => ("S6", "6"),
+        ElementShape::Qu4 => ("S4", "4"),
+        ElementShape::Qu8 => ("S8", "8"),
+        ElementShape::He8 => ("C3D8", ""),
+        ElementShape::He20 => ("C3D20", ""),
+        ElementShape::Pe6 => ("C3D6", ""),
+        ElementShape::Pe15 => ("C3D15", ""),
+        ElementShape::Te4 => ("C3D4", ""),
+        ElementShape::Te10 => ("C3D10", ""),
+    };
+    let suffix = element.suffix.to_ascii_uppercase();
+    let planar = match suffix.chars().next() {
+        Some('S') => Some("CPS"),
+        Some('E') => Some("CPE"),
+        Some('C') => Some("CAX"),
+        Some('M') => Some("M3D"),
+        _ => None,
+    };
+    match planar {
+        Some(family) if !nodes.is_empty() => format!("{family}{nodes}{}", &suffix[1..]),
+        _ => format!("{base}{suffix}"),
+    }
+}
+
+/// `*NODE` and `*ELEMENT` blocks of the nodes and elements of `set`, the
+/// elements grouped by type
+pub fn abaqus_mesh(meshed: &GeometryMesh, set: &str) -> Result<String, String> {
+    let (nodes, elements) = set_members(meshed, set)?;
+    let mut out = String::new();
+    if !nodes.is_empty() {
+        writeln!(out, "*NODE, NSET=N{set}").unwrap();
+        for id in nodes {
+            let node = &meshed.mesh.nodes[id];
+            writeln!(
+                out,
+                "{id:>10},{},{},{}",
+                exponent(node.x),
+                exponent(node.y),
+                exponent(node.z)
+            )
+            .unwrap();
+        }
+    }
+
+    let mut by_type: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for id in elements {
+        let element_type = match meshed.element_kinds.get(id) {
+            Some(kind) => abaqus_element_type(kind),
+            None => format!("{:?}", meshed.mesh.elements[id].element_type),
+        };
+        by_type.entry(element_type).or_default().push(*id);
+    }
+    for (element_type, ids) in by_type {
+        writeln!(out, "*ELEMENT, TYPE={element_type}, ELSET=E{set}").unwrap();
+        for id in ids {
+            let mut entries = vec![id];
+            entries.extend(&meshed.mesh.elements[&id].nodes);
+            let lines: Vec<String> = entries
+                .chunks(ENTRIES_PER_LINE)
+                .map(|chunk| {
+                    let entries: Vec<String> = chunk.iter().map(|e| format!("{e:>8}")).collect();
+                    entries.join(",")
+                })
+                .collect();
+            writeln!(out, "{}", lines.join(",\n")).unwrap();
+        }
+    }
+    Ok(out)
+}
+
+/// `*NSET` and `*ELSET` blocks of `set`
+pub fn abaqus_names(meshed: &GeometryMesh, set: &str) -> Result<String, String> {
+    let (nodes, elements) = set_members(meshed, set)?;
+    let mut out = format!("** Names based on {set}\n");
+    if !nodes.is_empty() {
+        writeln!(out, "*NSET,NSET=N{set}").unwrap();
+        for id in nodes {
+            writeln!(out, "{id}, ").unwrap();
+        }
+    }
+    if !elements.is_empty() {
+        writeln!(out, "*ELSET,ELSET=E{set}").unwrap();
+        for id in elements {
+            writeln!(out, "{id}, ").unwrap();
+        }
+    }
+    Ok(out)
+}
+
+/// File of `send <set> <format> <options>`
+pub fn send(
+    meshed: &GeometryMesh,
+    set: &str,
+    format: &str,
+    options: &[String],
+) -> Result<SentFile, String> {
+    if !format.eq_ignore_ascii_case("abq") {
+        return Err(format!("send format {format} is not supported"));
+    }
+    match options {
+        [] => Ok(SentFile {
+            name: format!("{set}.msh"),
+            contents: abaqus_mesh(meshed, set)?,
+        }),
+        [option] if option.eq_ignore_ascii_case("nam") || option.eq_ignore_ascii_case("names") => {
+            Ok(SentFile {
+                name: format!("{set}.nam"),
+                contents: abaqus_names(meshed, set)?,
+            })
+        }
+        _ => Err(format!(
+            "send option {} is not supported for abq",
+            options.join(" ")
+        )),
+    }
+}
+
+/// Replay `script` in order and write the files of its `send` commands to
+/// `dir`, returning their paths and an error diagnostic for every command
+/// that failed
+///
+/// Every `mesh` command meshes its set anew, from the geometry and element
+/// types defined before it, and `send` writes the last mesh.
+pub fn run_fbd(script: &FbdScript, dir: &Path) -> (Vec<PathBuf>, Vec<FbdDiagnostic>) {
+    let mut geometry = Geometry::new();
+    let mut meshed: Option<GeometryMesh> = None;
+    let mut written = Vec::new();
+    let mut diagnostics = Vec::new();
+    for line in &script.commands {
+        let result = match &line.command {
+            FbdCommand::Mesh { set, .. } => {
+                mesh_geometry(&geometry, set).map(|mesh| meshed = Some(mesh))
+            }
+            FbdCommand::Send {
+                set,
+                format,
+                options,
+            } => meshed
+                .as_ref()
+                .ok_or_else(|| "send before mesh".to_string())
+                .and_then(|meshed| send(meshed, set, format, options))
+                .and_then(|file| {
+                    let path = dir.join(&file.name);
+                    std::fs::write(&path, file.contents)
+                        .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+                    written.push(path);
+                    Ok(())
+                }),
+            command => geometry.apply(command),
+        };
+        if let Err(message) = result {
+            diagnostics.push(FbdDiagnostic {
+                line: line.line,
+                severity: Severity::Error,
+                message,
+            });
+        }
+    }
+    (written, diagnostics)
+}
+
+/// Nodes and elements of `set`, sorted
+fn set_members<'a>(meshed: &'a GeometryMesh, set: &str) -> Result<(&'a [i32], &'a [i32]), String> {
+    let nodes = meshed.sets.get_nodes(set);
+    let elements = meshed.sets.get_elements(set);
+    if nodes.is_none() && elements.is_none() {
+        return Err(format!("set {set} has no mesh"));
+    }
+    Ok((nodes.unwrap_or_default(), elements.unwrap_or_default()))
+}
+
+/// `x` with twelve decimals and a signed two-digit exponent, as written by
+/// CGX
+fn exponent(x: f64) -> String {
+    let formatted = format!("{x:.12e}");
+    let (mantissa, power) = formatted.split_once('e').expect("exponent");
+    let power: i32 = power.parse().expect("integer exponent");
+    let sign = if power < 0 { '-' } else { '+' };
+    format!("{mantissa}e{sign}{:02}", power.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbd::parse_fbd;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const PLATE: &str = "\
+pnt p1 0 0 0
+pnt p2 2 0 0
+pnt p3 2 1 0
+pnt p4 0 1 0
+line l1 p1 p2 4
+line l2 p2 p3 2
+line l3 p3 p4 4
+line l4 p4 p1 2
+surf s1 l1 l2 l3 l4
+seta edge l l4
+elty all qu8e
+mesh all
+send all abq
+send edge abq nam
+send all vtk
+";
+
+    #[test]
+    fn writes_msh_and_nam_files_of_a_script() {
+        let dir = unique_temp_dir("calculix_gui_send");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (written, diagnostics) = run_fbd(&parse_fbd(PLATE), &dir);
+        assert_eq!(written, [dir.join("all.msh"), dir.join("edge.nam")]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "line 15: error: send format vtk is not supported"
+        );
+
+        let msh = std::fs::read_to_string(dir.join("all.msh")).unwrap();
+        let lines: Vec<&str> = msh.lines().collect();
+        assert_eq!(lines[0], "*NODE, NSET=Nall");
+        assert_eq!(
+            lines[1],
+            "         1,0.000000000000e+00,0.000000000000e+00,0.000000000000e+00"
+        );
+        assert_eq!(lines[14], "*ELEMENT, TYPE=CPE8, ELSET=Eall");
+        assert_eq!(lines.len(), 1 + 13 + 1 + 2);
+
+        let nam = std::fs::read_to_string(dir.join("edge.nam")).unwrap();
+        let lines: Vec<&str> = nam.lines().collect();
+        assert_eq!(lines[..2], ["** Names based on edge", "*NSET,NSET=Nedge"]);
+        assert_eq!(lines.len(), 2 + 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn maps_element_types_with_their_suffixes() {
+        let abaqus = |token| abaqus_element_type(&ElementKind::parse(token).unwrap());
+        assert_eq!(abaqus("he20r"), "C3D20R");
+        assert_eq!(abaqus("he8i"), "C3D8I");
+        assert_eq!(abaqus("qu8"), "S8");
+        assert_eq!(abaqus("qu8cr"), "CAX8R");
+        assert_eq!(abaqus("tr6s"), "CPS6");
+        assert_eq!(abaqus("qu4m"), "M3D4");
+        assert_eq!(abaqus("be3r"), "B32R");
+        assert_eq!(exponent(-1234.5), "-1.234500000000e+03");
+        assert_eq!(exponent(0.001), "1.000000000000e-03");
+    }
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let pid = std::process::id();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock should be valid")
+            .as_nanos();
+        std::env::temp_dir().join(format!("{prefix}_{pid}_{nanos}"))
+    }
+}
@@ -0,0 +1,407 @@
+//! Vertex buffers of meshes, deformed shapes and color fields.
+//!
+//! A [`Tessellator`] turns the free faces of solid elements and the shell
+//! and membrane elements of a mesh into indexed triangles, with the
+//! midside nodes of quadratic faces as vertices so curved faces stay
+//! curved, and the face outlines and beam and truss elements into indexed
+//! line segments. Positions come from the mesh or from the deformed
+//! coordinates of an animation frame, and colors from a color scale over a
+//! scalar field. [`section_buffers`] does the same for cut planes and
+//! iso-surfaces. The buffers hold plain `f32` and `u32` arrays, ready to
+//! upload to any renderer.
+
+use std::collections::{BTreeSet, HashMap};
+
+use ccx_solver::{ElementType, Mesh};
+
+use crate::cut::Section;
+use crate::results::{ColorScale, ScalarField};
+
+/// Color of vertices without a color field
+pub const DEFAULT_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+
+/// Indexed triangles and line segments over shared vertex arrays
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VertexBuffers {
+    pub positions: Vec<[f32; 3]>,
+    /// Unit normal of every vertex; zero for vertices of lines only
+    pub normals: Vec<[f32; 3]>,
+    pub colors: Vec<[f32; 3]>,
+    /// Three vertex indices per triangle, counter-clockwise seen from the
+    /// outside
+    pub triangles: Vec<u32>,
+    /// Two vertex indices per segment
+    pub lines: Vec<u32>,
+}
+
+impl VertexBuffers {
+    fn push_vertex(&mut self, position: [f64; 3], normal: [f64; 3], color: [f32; 3]) -> u32 {
+        self.positions.push(position.map(|x| x as f32));
+        self.normals.push(normal.map(|x| x as f32));
+        self.colors.push(color);
+        (self.positions.len() - 1) as u32
+    }
+}
+
+/// Normals of the triangles of a face
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shading {
+    /// One normal per face; faces get vertices of their own
+    #[default]
+    Flat,
+    /// Normals averaged over the faces at a node; one vertex per node
+    Smooth,
+}
+
+/// Vertex buffers of a mesh
+pub struct Tessellator<'a> {
+    mesh: &'a Mesh,
+    coords: Option<&'a HashMap<i32, [f64; 3]>>,
+    colors: Option<(&'a ScalarField, &'a ColorScale)>,
+    shading: Shading,
+}
+
+impl<'a> Tessellator<'a> {
+    pub fn new(mesh: &'a Mesh) -> Self {
+        Self {
+            mesh,
+            coords: None,
+            colors: None,
+            shading: Shading::default(),
+        }
+    }
+
+    /// Draw the nodes at `coords` instead of their mesh positions, e.g.
+    /// the deformed coordinates of an animation frame
+    pub fn with_coords(mut self, coords: &'a HashMap<i32, [f64; 3]>) -> Self {
+        self.coords = Some(coords);
+        self
+    }
+
+    /// Color the vertices by the values of `field` on `scale`
+    pub fn with_colors(mut self, field: &'a ScalarField, scale: &'a ColorScale) -> Self {
+        self.colors = Some((field, scale));
+        self
+    }
+
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.shading = shading;
+        self
+    }
+
+    fn position(&self, node: i32) -> Option<[f64; 3]> {
+        match self.coords {
+            Some(coords) => coords.get(&node).copied(),
+            None => self.mesh.nodes.get(&node).map(|n| n.coords()),
+        }
+    }
+
+    fn color(&self, node: i32) -> [f32; 3] {
+        match self.colors {
+            Some((field, scale)) => scale.vertex_colors(field, &[node])[0],
+            None => DEFAULT_COLOR,
+        }
+    }
+
+    /// Triangles of the free solid faces and shell elements, and segments
+    /// of their outlines and of the beam and truss elements
+    pub fn tessellate(&self) -> VertexBuffers {
+        let mut faces: Vec<Vec<i32>> = self
+            .mesh
+            .free_faces()
+            .into_iter()
+            .map(|face| face.nodes)
+            .collect();
+        let mut segments: Vec<[i32; 2]> = Vec::new();
+        let mut ids: Vec<i32> = self.mesh.elements.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let element = &self.mesh.elements[&id];
+            let nodes = &element.nodes;
+            match element.element_type {
+                ElementType::S3
+                | ElementType::S4
+                | ElementType::S6
+                | ElementType::S8
+                | ElementType::M3D3
+                | ElementType::M3D4
+                | ElementType::M3D6
+                | ElementType::M3D8 => faces.push(nodes.clone()),
+                ElementType::T3D2 | ElementType::B31 if nodes.len() == 2 => {
+                    segments.push([nodes[0], nodes[1]]);
+                }
+                ElementType::B32 if nodes.len() == 3 => {
+                    segments.extend([[nodes[0], nodes[1]], [nodes[1], nodes[2]]]);
+                }
+                _ => {}
+            }
+        }
+
+        let mut buffers = VertexBuffers::default();
+        let mut node_vertex: HashMap<i32, u32> = HashMap::new();
+        let mut outline: BTreeSet<[i32; 2]> = BTreeSet::new();
+        for face in &faces {
+            let Some(pattern) = triangulation(face.len()) else {
+                continue;
+            };
+            let Some(positions) = face
+                .iter()
+                .map(|&n| self.position(n))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            let corners = if face.len() > 4 {
+                face.len() / 2
+            } else {
+                face.len()
+            };
+            let normal = newell(&positions[..corners]);
+            let vertices: Vec<u32> = face
+                .iter()
+                .zip(&positions)
+                .map(|(&node, &position)| match self.shading {
+                    Shading::Flat => {
+                        let unit = normalized(normal);
+                        let vertex = buffers.push_vertex(position, unit, self.color(node));
+                        node_vertex.entry(node).or_insert(vertex);
+                        vertex
+                    }
+                    Shading::Smooth => {
+                        let vertex = *node_vertex.entry(node).or_insert_with(|| {
+                            buffers.push_vertex(position, [0.0; 3], self.color(node))
+                        });
+                        let sum = &mut buffers.normals[vertex as usize];
+                        for i in 0..3 {
+                            sum[i] += normal[i] as f32;
+                        }
+                        vertex
+                    }
+                })
+                .collect();
+            for triangle in pattern {
+                buffers.triangles.extend(triangle.map(|i| vertices[i]));
+            }
+            for [a, b] in outline_edges(face.len()) {
+                outline.insert(sorted([face[a], face[b]]));
+            }
+        }
+        if self.shading == Shading::Smooth {
+            for normal in &mut buffers.normals {
+                *normal = normalized(normal.map(f64::from)).map(|x| x as f32);
+            }
+        }
+
+        segments.extend(outline);
+        for segment in segments {
+            let mut ends = [0; 2];
+            for (end, node) in ends.iter_mut().zip(segment) {
+                let Some(position) = self.position(node) else {
+                    break;
+                };
+                *end = *node_vertex
+                    .entry(node)
+                    .or_insert_with(|| buffers.push_vertex(position, [0.0; 3], self.color(node)));
+            }
+            if segment.iter().all(|node| node_vertex.contains_key(node)) {
+                buffers.lines.extend(ends);
+            }
+        }
+        buffers
+    }
+}
+
+/// Triangles of a cut plane or iso-surface, colored by `scale` over the
+/// values of the section when it has both
+pub fn section_buffers(section: &Section, scale: Option<&ColorScale>) -> VertexBuffers {
+    let mut buffers = VertexBuffers::default();
+    let mut normals = vec![[0.0; 3]; section.points.len()];
+    for triangle in &section.triangles {
+        let normal = newell(&triangle.map(|i| section.points[i]));
+        for &i in triangle {
+            for k in 0..3 {
+                normals[i][k] += normal[k];
+            }
+        }
+    }
+    for (i, point) in section.points.iter().enumerate() {
+        let color = match (scale, section.values.get(i)) {
+            (Some(scale), Some(&value)) => scale.color(value),
+            _ => DEFAULT_COLOR,
+        };
+        buffers.push_vertex(*point, normalized(normals[i]), color);
+    }
+    buffers.triangles = section
+        .triangles
+        .iter()
+        .flat_map(|t| t.map(|i| i as u32))
+        .collect();
+    buffers
+}
+
+/// Triangles over the nodes of a face: corners first, then the midside
+/// node of every edge for quadratic faces
+fn triangulation(nodes: usize) -> Option<&'static [[usize; 3]]> {
+    match nodes {
+        3 => Some(&[[0, 1, 2]]),
+        4 => Some(&[[0, 1, 2], [0, 2, 3]]),
+        6 => Some(&[[0, 3, 5], [3, 1, 4], [4, 2, 5], [3, 4, 5]]),
+        8 => Some(&[
+            [0, 4, 7],
+            [4, 1, 5],
+            [5, 2, 6],
+            [6, 3, 7],
+            [4, 5, 6],
+            [4, 6, 7],
+        ]),
+        _ => None,
+    }
+}
+
+/// Segments along the edges of a face, through the midside nodes of
+/// quadratic faces
+fn outline_edges(nodes: usize) -> Vec<[usize; 2]> {
+    let (corners, quadratic) = match nodes {
+        6 | 8 => (nodes / 2, true),
+        _ => (nodes, false),
+    };
+    (0..corners)
+        .flat_map(|i| {
+            let next = (i + 1) % corners;
+            match quadratic {
+                true => vec![[i, corners + i], [corners + i, next]],
+                false => vec![[i, next]],
+            }
+        })
+        .collect()
+}
+
+/// Normal of a polygon, twice its area long
+fn newell(points: &[[f64; 3]]) -> [f64; 3] {
+    let mut normal = [0.0; 3];
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    normal
+}
+
+fn normalized(v: [f64; 3]) -> [f64; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    match length > 0.0 {
+        true => v.map(|x| x / length),
+        false => v,
+    }
+}
+
+fn sorted([a, b]: [i32; 2]) -> [i32; 2] {
+    [a.min(b), a.max(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cut::{Plane, cut_plane};
+    use ccx_solver::{Division, Element, MeshBuilder, Node};
+
+    fn cube(element_type: ElementType) -> Mesh {
+        let divisions = [1, 1, 1].map(Division::uniform);
+        MeshBuilder::brick([1.0, 1.0, 1.0], divisions, element_type)
+            .unwrap()
+            .mesh
+    }
+
+    fn outward(buffers: &VertexBuffers) -> bool {
+        buffers.triangles.chunks_exact(3).all(|t| {
+            let p = [0, 1, 2].map(|k| buffers.positions[t[k] as usize].map(f64::from));
+            let center = [0, 1, 2].map(|k| (p[0][k] + p[1][k] + p[2][k]) / 3.0 - 0.5);
+            let normal = newell(&p);
+            (0..3).map(|k| normal[k] * center[k]).sum::<f64>() > 0.0
+        })
+    }
+
+    #[test]
+    fn tessellates_solid_skins_with_flat_and_smooth_normals() {
+        let mesh = cube(ElementType::C3D8);
+        let smooth = Tessellator::new(&mesh)
+            .with_shading(Shading::Smooth)
+            .tessellate();
+        assert_eq!(smooth.positions.len(), 8);
+        assert_eq!(smooth.triangles.len(), 12 * 3);
+        assert_eq!(smooth.lines.len(), 12 * 2);
+        assert!(outward(&smooth));
+        let corner = 1.0 / 3f32.sqrt();
+        for normal in &smooth.normals {
+            assert!(normal.iter().all(|x| (x.abs() - corner).abs() < 1e-6));
+        }
+
+        let flat = Tessellator::new(&mesh).tessellate();
+        assert_eq!(flat.positions.len(), 24);
+        assert_eq!(flat.colors, vec![DEFAULT_COLOR; 24]);
+        assert!(outward(&flat));
+        for normal in &flat.normals {
+            assert_eq!(normal.iter().filter(|x| x.abs() == 1.0).count(), 1);
+        }
+
+        let quadratic = Tessellator::new(&cube(ElementType::C3D20))
+            .with_shading(Shading::Smooth)
+            .tessellate();
+        assert_eq!(quadratic.positions.len(), 20);
+        assert_eq!(quadratic.triangles.len(), 6 * 6 * 3);
+        assert_eq!(quadratic.lines.len(), 24 * 2);
+        assert!(outward(&quadratic));
+    }
+
+    #[test]
+    fn draws_deformed_colored_shells_beams_and_sections() {
+        let mut mesh = Mesh::new();
+        for (id, x, y) in [(1, 0.0, 0.0), (2, 1.0, 0.0), (3, 1.0, 1.0), (4, 0.0, 1.0)] {
+            mesh.add_node(Node::new(id, x, y, 0.0));
+        }
+        mesh.add_node(Node::new(5, 2.0, 0.0, 0.0));
+        mesh.add_element(Element::new(1, ElementType::S4, vec![1, 2, 3, 4]))
+            .unwrap();
+        mesh.add_element(Element::new(2, ElementType::B31, vec![2, 5]))
+            .unwrap();
+        let deformed: HashMap<i32, [f64; 3]> = mesh
+            .nodes
+            .values()
+            .map(|n| (n.id, [n.x, n.y, n.z + n.x]))
+            .collect();
+        let field = ScalarField {
+            dataset: "DISP".to_string(),
+            entity: "D3".to_string(),
+            values: deformed.iter().map(|(&id, c)| (id, c[2])).collect(),
+            min: None,
+            max: None,
+        };
+        let scale = ColorScale::new(&field).with_bounds(0.0, 2.0).with_steps(2);
+        let buffers = Tessellator::new(&mesh)
+            .with_coords(&deformed)
+            .with_colors(&field, &scale)
+            .with_shading(Shading::Smooth)
+            .tessellate();
+        assert_eq!(buffers.positions.len(), 5);
+        assert_eq!(buffers.triangles.len(), 6);
+        assert_eq!(buffers.lines.len(), 5 * 2);
+        assert_eq!(buffers.normals[4], [0.0; 3]);
+        let beam_end = buffers.positions[4];
+        assert_eq!(beam_end, [2.0, 0.0, 2.0]);
+        assert_eq!(buffers.colors[4], [1.0, 0.0, 0.0]);
+        assert_eq!(buffers.colors[0], [0.0, 0.0, 1.0]);
+
+        let solid = cube(ElementType::C3D8);
+        let plane = Plane {
+            point: [0.5; 3],
+            normal: [0.0, 0.0, 1.0],
+        };
+        let cut = cut_plane(&solid, &plane, None);
+        let section = section_buffers(&cut, Some(&scale));
+        assert_eq!(section.positions.len(), cut.points.len());
+        assert_eq!(section.triangles.len(), cut.triangles.len() * 3);
+        assert_eq!(section.colors, vec![DEFAULT_COLOR; cut.points.len()]);
+        assert!(section.normals.iter().all(|n| n[2] > 0.99));
+    }
+}
@@ -0,0 +1,269 @@
+//! Differential tests of `calculix_gui::ported` against the legacy cgx C
+//! units.
+//!
+//! The units are compiled from the cgx `src` directory in `CCX_LEGACY`, or
+//! else from `calculix_migration_tooling/cgx_2.23/src`, together with the
+//! driver `legacy_differential/driver.c`, and both sides run on the same
+//! seeded random inputs. Without the C sources the comparison is skipped
+//! with a note on stderr; with them, a unit that fails to compile fails the
+//! tests.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use calculix_gui::ported::{
+    SortedPoints, Vec3, m_copy, near3d, stof, stoi, stos, strsplt, v_rot, v_scal,
+};
+
+/// Legacy units compared with their ports
+const UNITS: &[&str] = &[
+    "v_scal.c",
+    "v_rot.c",
+    "m_copy.c",
+    "near3d.c",
+    "stoi.c",
+    "stof.c",
+    "stos.c",
+    "strsplt.c",
+];
+
+/// Units the compared ones may call, linked when present
+const SUPPORT_UNITS: &[&str] = &["v_add.c", "v_result.c", "v_prod.c", "v_sprod.c", "v_norm.c"];
+
+fn legacy_source_dir() -> PathBuf {
+    env::var_os("CCX_LEGACY")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../calculix_migration_tooling/cgx_2.23/src")
+        })
+}
+
+/// Driver linked with the legacy units, built once; `None` without the
+/// sources
+fn legacy_driver() -> Option<&'static Path> {
+    static DRIVER: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DRIVER
+        .get_or_init(|| {
+            let src = legacy_source_dir();
+            let missing: Vec<&str> = UNITS
+                .iter()
+                .copied()
+                .filter(|unit| !src.join(unit).is_file())
+                .collect();
+            if !missing.is_empty() {
+                eprintln!(
+                    "skipping the legacy comparison: {} not in {}",
+                    missing.join(", "),
+                    src.display()
+                );
+                return None;
+            }
+            let dir = env::temp_dir().join(format!("calculix_gui_legacy_{}", std::process::id()));
+            fs::create_dir_all(&dir).expect("create driver dir");
+            let driver = dir.join("driver");
+            let units = UNITS
+                .iter()
+                .chain(SUPPORT_UNITS)
+                .map(|unit| src.join(unit))
+                .filter(|path| path.is_file());
+            let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+            let output = Command::new(&cc)
+                .arg("-o")
+                .arg(&driver)
+                .arg("-I")
+                .arg(&src)
+                .arg(
+                    Path::new(env!("CARGO_MANIFEST_DIR"))
+                        .join("tests/legacy_differential/driver.c"),
+                )
+                .args(units)
+                .arg("-lm")
+                .output()
+                .unwrap_or_else(|err| panic!("cannot run {cc}: {err}"));
+            assert!(
+                output.status.success(),
+                "compiling the legacy units failed:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Some(driver)
+        })
+        .as_deref()
+}
+
+/// Result lines of the legacy driver for the commands `input`
+fn run_legacy(driver: &Path, input: String) -> Vec<String> {
+    let mut child = Command::new(driver)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("start driver");
+    let mut stdin = child.stdin.take().expect("driver stdin");
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().expect("run driver");
+    writer.join().unwrap().expect("write commands");
+    assert!(output.status.success(), "legacy driver failed");
+    String::from_utf8(output.stdout)
+        .expect("driver output is UTF-8")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Pseudo-random numbers in [-10, 10) from a fixed seed
+fn samples(count: usize) -> Vec<f64> {
+    let mut seed = 12345_u64;
+    (0..count)
+        .map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 20.0 - 10.0
+        })
+        .collect()
+}
+
+fn join(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(f64::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn numbers(line: &str) -> Vec<f64> {
+    line.split_whitespace()
+        .map(|value| value.parse().expect("number"))
+        .collect()
+}
+
+#[test]
+fn vector_routines_match_the_legacy_c() {
+    let Some(driver) = legacy_driver() else {
+        return;
+    };
+    let x = samples(16 * 100);
+    let mut input = String::new();
+    for c in x.chunks_exact(16) {
+        input += &format!(
+            "scal {}\nrot {}\ncopy {}\n",
+            join(&c[..4]),
+            join(&c[..10]),
+            join(c)
+        );
+    }
+    let lines = run_legacy(driver, input);
+    assert_eq!(lines.len(), 3 * 100);
+    for (c, lines) in x.chunks_exact(16).zip(lines.chunks_exact(3)) {
+        assert_eq!(numbers(&lines[0]), v_scal(c[0], [c[1], c[2], c[3]]));
+
+        let rotated = v_rot(
+            c[0],
+            [c[1], c[2], c[3]],
+            [c[4], c[5], c[6]],
+            [c[7], c[8], c[9]],
+        );
+        let legacy = numbers(&lines[1]);
+        for (port, legacy) in rotated.iter().zip(&legacy) {
+            assert!(
+                (port - legacy).abs() < 1e-9 * (1.0 + legacy.abs()),
+                "{rotated:?} {legacy:?}"
+            );
+        }
+
+        let mut z = [0.0; 16];
+        m_copy(c.try_into().unwrap(), &mut z);
+        assert_eq!(numbers(&lines[2]), z);
+    }
+}
+
+#[test]
+fn near3d_matches_the_legacy_c() {
+    let Some(driver) = legacy_driver() else {
+        return;
+    };
+    let x = samples(3 * 200 + 3 * 40);
+    let (coords, targets) = x.split_at(3 * 200);
+    let points: Vec<Vec3> = coords.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+    let targets: Vec<Vec3> = targets
+        .chunks_exact(3)
+        .map(|c| [c[0], c[1], c[2]])
+        .chain(points.iter().step_by(23).copied())
+        .collect();
+    let mut input = String::new();
+    for (i, target) in targets.iter().enumerate() {
+        input += &format!("near {} {} {}", points.len(), 1 + i % 8, join(target));
+        for point in &points {
+            input += &format!(" {}", join(point));
+        }
+        input.push('\n');
+    }
+    let lines = run_legacy(driver, input);
+    assert_eq!(lines.len(), targets.len());
+    let sorted = SortedPoints::new(&points);
+    for (i, (target, line)) in targets.iter().zip(&lines).enumerate() {
+        let legacy: Vec<usize> = line
+            .split_whitespace()
+            .map(|node| node.parse().expect("node index"))
+            .collect();
+        assert_eq!(near3d(&sorted, *target, 1 + i % 8), legacy, "{target:?}");
+    }
+}
+
+#[test]
+fn string_readers_match_the_legacy_c() {
+    let Some(driver) = legacy_driver() else {
+        return;
+    };
+    let mut seed = 54321_u64;
+    let mut next = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (seed >> 33) % bound
+    };
+    let mut cases = Vec::new();
+    for _ in 0..300 {
+        let int = next(1 << 24) as i32 - (1 << 23);
+        let float = f64::from(int) * 1.234e-3;
+        let record = format!("{int:10}{float:12.5E}");
+        let a = 1 + next(record.len() as u64) as usize;
+        let b = a + next((record.len() + 1 - a) as u64) as usize;
+        let breakchar = [' ', ',', '/'][next(3) as usize];
+        let words: Vec<String> = (0..next(5))
+            .map(|_| {
+                let runs = breakchar.to_string().repeat(1 + next(3) as usize);
+                format!("{runs}w{}", next(1000))
+            })
+            .collect();
+        cases.push((record, a, b, breakchar, words.concat()));
+    }
+    let mut input = String::new();
+    for (record, a, b, breakchar, words) in &cases {
+        input += &format!("stoi 1 10|{record}\nstof 11 22|{record}\n");
+        input += &format!("stoi {a} {b}|{record}\nstof {a} {b}|{record}\nstos {a} {b}|{record}\n");
+        input += &format!("strsplt {}|{words}\n", *breakchar as u32);
+    }
+    let lines = run_legacy(driver, input);
+    assert_eq!(lines.len(), 6 * cases.len());
+    for ((record, a, b, breakchar, words), lines) in cases.iter().zip(lines.chunks_exact(6)) {
+        let (a, b) = (*a, *b);
+        assert_eq!(lines[0], stoi(record, 1, 10).to_string(), "{record:?}");
+        assert_eq!(numbers(&lines[1]), [stof(record, 11, 22)], "{record:?}");
+        assert_eq!(
+            lines[2],
+            stoi(record, a, b).to_string(),
+            "{record:?} {a} {b}"
+        );
+        assert_eq!(
+            numbers(&lines[3]),
+            [stof(record, a, b)],
+            "{record:?} {a} {b}"
+        );
+        assert_eq!(lines[4], stos(record, a, b), "{record:?} {a} {b}");
+        let split = strsplt(words, *breakchar);
+        let legacy: Vec<&str> = lines[5].split('\t').collect();
+        assert_eq!(legacy[0], split.len().to_string(), "{words:?}");
+        assert_eq!(legacy[1..], split, "{words:?}");
+    }
+}
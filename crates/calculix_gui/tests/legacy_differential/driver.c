@@ -0,0 +1,140 @@
+/*
+ * Runs the legacy cgx routines ported to calculix_gui::ported on the
+ * commands read from stdin and prints one result line per command:
+ *
+ *   scal a b0 b1 b2                    v_scal(a, b)
+ *   rot fi p0(3) a(3) p1(3)            v_rot(fi, p0, a, p1)
+ *   copy s(16)                         m_copy(s)
+ *   near n k xp yp zp x0 y0 z0 ...     near3d of the n points
+ *   stoi a b|string                    stoi(string, a, b)
+ *   stof a b|string                    stof(string, a, b)
+ *   stos a b|string                    stos(string, a, b)
+ *   strsplt c|string                   strsplt(string, c): count and words
+ *
+ * The prototypes come from extUtil.h, so a port whose signature drifted
+ * from the legacy one fails to compile here.
+ */
+
+#include <extUtil.h>
+
+#define LINE_LENGTH 4096
+
+typedef struct {
+  double value;
+  int index;
+} Coordinate;
+
+static int compare_coordinates(const void *a, const void *b)
+{
+  const Coordinate *l = a, *r = b;
+  if (l->value < r->value) return -1;
+  if (l->value > r->value) return 1;
+  return l->index - r->index;
+}
+
+/* sorted coordinates and their point indices, as callers of near3d build them */
+static void sort_axis(int n, double *o, double *sorted, int *order)
+{
+  int i;
+  Coordinate *c = malloc(n * sizeof(Coordinate));
+  for (i = 0; i < n; i++) {
+    c[i].value = o[i];
+    c[i].index = i;
+  }
+  qsort(c, n, sizeof(Coordinate), compare_coordinates);
+  for (i = 0; i < n; i++) {
+    sorted[i] = c[i].value;
+    order[i] = c[i].index;
+  }
+  free(c);
+}
+
+static void print_vector(int n, double *v)
+{
+  int i;
+  for (i = 0; i < n; i++) printf(i ? " %.17g" : "%.17g", v[i]);
+  printf("\n");
+}
+
+/* rest of the line after the next '|', without the newline */
+static void read_string(char *string)
+{
+  size_t length;
+  if (!fgets(string, LINE_LENGTH, stdin)) string[0] = '\0';
+  length = strlen(string);
+  if (length && string[length - 1] == '\n') string[length - 1] = '\0';
+}
+
+int main(void)
+{
+  char command[16], string[LINE_LENGTH], puffer[LINE_LENGTH], **words;
+  double a, b[3], p0[3], axis[3], p1[3], p2[3], s[16], z[16], p[3];
+  double *xo, *yo, *zo, *x, *y, *z_sorted;
+  int i, n, k, from, to, c, *nx, *ny, *nz, *node;
+
+  while (scanf("%15s", command) == 1) {
+    if (!strcmp(command, "scal")) {
+      if (scanf("%lf %lf %lf %lf", &a, &b[0], &b[1], &b[2]) != 4) return 1;
+      v_scal(&a, b, p2);
+      print_vector(3, p2);
+    } else if (!strcmp(command, "rot")) {
+      if (scanf("%lf", &a) != 1) return 1;
+      for (i = 0; i < 3; i++)
+        if (scanf("%lf", &p0[i]) != 1) return 1;
+      for (i = 0; i < 3; i++)
+        if (scanf("%lf", &axis[i]) != 1) return 1;
+      for (i = 0; i < 3; i++)
+        if (scanf("%lf", &p1[i]) != 1) return 1;
+      v_rot(a, p0, axis, p1, p2);
+      print_vector(3, p2);
+    } else if (!strcmp(command, "copy")) {
+      for (i = 0; i < 16; i++)
+        if (scanf("%lf", &s[i]) != 1) return 1;
+      m_copy(s, z);
+      print_vector(16, z);
+    } else if (!strcmp(command, "near")) {
+      if (scanf("%d %d %lf %lf %lf", &n, &k, &p[0], &p[1], &p[2]) != 5) return 1;
+      xo = malloc(n * sizeof(double));
+      yo = malloc(n * sizeof(double));
+      zo = malloc(n * sizeof(double));
+      x = malloc(n * sizeof(double));
+      y = malloc(n * sizeof(double));
+      z_sorted = malloc(n * sizeof(double));
+      nx = malloc(n * sizeof(int));
+      ny = malloc(n * sizeof(int));
+      nz = malloc(n * sizeof(int));
+      node = malloc(k * sizeof(int));
+      for (i = 0; i < n; i++)
+        if (scanf("%lf %lf %lf", &xo[i], &yo[i], &zo[i]) != 3) return 1;
+      sort_axis(n, xo, x, nx);
+      sort_axis(n, yo, y, ny);
+      sort_axis(n, zo, z_sorted, nz);
+      near3d(xo, yo, zo, x, y, z_sorted, nx, ny, nz, p[0], p[1], p[2], n, node, k);
+      for (i = 0; i < k; i++) printf(i ? " %d" : "%d", node[i]);
+      printf("\n");
+      free(xo); free(yo); free(zo); free(x); free(y); free(z_sorted);
+      free(nx); free(ny); free(nz); free(node);
+    } else if (!strcmp(command, "stoi") || !strcmp(command, "stof") ||
+               !strcmp(command, "stos")) {
+      if (scanf("%d %d|", &from, &to) != 2) return 1;
+      read_string(string);
+      if (command[3] == 'i') printf("%d\n", stoi(string, from, to));
+      else if (command[3] == 'f') printf("%.17g\n", stof(string, from, to));
+      else {
+        stos(string, from, to, puffer);
+        printf("%s\n", puffer);
+      }
+    } else if (!strcmp(command, "strsplt")) {
+      if (scanf("%d|", &c) != 1) return 1;
+      read_string(string);
+      n = strsplt(string, (char)c, &words);
+      printf("%d", n);
+      for (i = 0; i < n; i++) printf("\t%s", words[i]);
+      printf("\n");
+    } else {
+      fprintf(stderr, "unknown command %s\n", command);
+      return 1;
+    }
+  }
+  return 0;
+}
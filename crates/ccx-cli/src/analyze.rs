@@ -0,0 +1,343 @@
+//! Model summaries of `analyze`, `mesh-info`, `keyword-coverage` and
+//! `analyze-fixtures`.
+
+use std::path::Path;
+
+use ccx_model::ModelSummary;
+
+use crate::error::CliError;
+use crate::validate::collect_inp_files;
+use crate::{DeckInput, print_json, read_deck};
+
+pub fn summary_json(summary: &ModelSummary) -> serde_json::Value {
+    serde_json::json!({
+        "total_cards": summary.total_cards,
+        "total_data_lines": summary.total_data_lines,
+        "node_rows": summary.node_rows,
+        "element_rows": summary.element_rows,
+        "material_defs": summary.material_defs,
+        "has_step": summary.has_step,
+        "has_static": summary.has_static,
+        "has_dynamic": summary.has_dynamic,
+        "has_frequency": summary.has_frequency,
+        "has_heat_transfer": summary.has_heat_transfer,
+        "include_files": summary.include_files,
+        "unique_keywords": summary.keyword_counts.len(),
+    })
+}
+
+pub fn print_summary(summary: &ModelSummary) {
+    println!("total_cards: {}", summary.total_cards);
+    println!("total_data_lines: {}", summary.total_data_lines);
+    println!("node_rows: {}", summary.node_rows);
+    println!("element_rows: {}", summary.element_rows);
+    println!("material_defs: {}", summary.material_defs);
+    println!("has_step: {}", summary.has_step);
+    println!("has_static: {}", summary.has_static);
+    println!("has_dynamic: {}", summary.has_dynamic);
+    println!("has_frequency: {}", summary.has_frequency);
+    println!("has_heat_transfer: {}", summary.has_heat_transfer);
+    if !summary.include_files.is_empty() {
+        println!("include_files: {}", summary.include_files.join(", "));
+    }
+    println!("unique_keywords: {}", summary.keyword_counts.len());
+}
+
+pub fn mass_properties(deck: &ccx_inp::Deck) -> Result<ccx_solver::MassProperties, String> {
+    let mesh = ccx_solver::MeshBuilder::build_from_deck(deck)?;
+    ccx_solver::MassProperties::from_deck(deck, &mesh)
+}
+
+/// Mass properties of a deck, or why they are not available
+pub fn mass_properties_json(deck: &ccx_inp::Deck) -> serde_json::Value {
+    match mass_properties(deck) {
+        Ok(properties) => serde_json::json!({
+            "mass": properties.mass,
+            "center_of_gravity": properties.center_of_gravity,
+            "inertia_about_cg": properties.inertia,
+            "skipped_elements": properties.skipped,
+        }),
+        Err(err) => serde_json::json!({ "error": err }),
+    }
+}
+
+/// Print the mass properties of a deck, or why they are not available
+pub fn print_mass_properties(deck: &ccx_inp::Deck) {
+    let properties = match mass_properties(deck) {
+        Ok(properties) => properties,
+        Err(err) => {
+            println!("mass: unavailable ({err})");
+            return;
+        }
+    };
+    let [x, y, z] = properties.center_of_gravity;
+    let i = properties.inertia;
+    println!("mass: {:.6e}", properties.mass);
+    println!("center_of_gravity: {x:.6e}, {y:.6e}, {z:.6e}");
+    println!(
+        "inertia_about_cg: Ixx {:.6e}, Iyy {:.6e}, Izz {:.6e}, Ixy {:.6e}, Ixz {:.6e}, Iyz {:.6e}",
+        i[0][0], i[1][1], i[2][2], i[0][1], i[0][2], i[1][2]
+    );
+    if !properties.skipped.is_empty() {
+        println!(
+            "mass_skipped_elements: {} (no section, density, thickness or area)",
+            properties.skipped.len()
+        );
+    }
+}
+
+/// Counts and cards of `keyword-coverage --json`, each card with the Rust
+/// modules and legacy units of its keyword
+pub fn keyword_coverage_json(coverage: &ccx_solver::KeywordCoverage) -> serde_json::Value {
+    let counts = coverage.counts();
+    let count = |support| counts.get(&support).copied().unwrap_or(0);
+    let cards: Vec<serde_json::Value> = coverage
+        .cards
+        .iter()
+        .map(|card| {
+            serde_json::json!({
+                "keyword": card.keyword,
+                "line": card.line,
+                "support": card.support.as_str(),
+                "known": card.entry.is_some(),
+                "rust_modules": card.entry.map_or(&[][..], |entry| entry.rust_modules),
+                "legacy_units": card.entry.map_or(&[][..], |entry| entry.legacy_units),
+                "note": card.entry.map(|entry| entry.note).filter(|note| !note.is_empty()),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "cards": coverage.cards.len(),
+        "full": count(ccx_solver::Support::Full),
+        "partial": count(ccx_solver::Support::Partial),
+        "unimplemented": count(ccx_solver::Support::Unimplemented),
+        "complete": coverage.is_complete(),
+        "by_card": cards,
+    })
+}
+
+pub fn analyze_file(path: &Path) -> Result<ModelSummary, CliError> {
+    let deck = read_deck(path, &[], &[]).map_err(|err| CliError::deck(path, &err))?;
+    Ok(ModelSummary::from_deck(&deck))
+}
+
+/// Print mesh statistics, element quality and sets of a deck without solving it
+pub fn mesh_info_file(input: &DeckInput, worst: usize, json: bool) -> Result<(), CliError> {
+    let deck = input.read()?;
+    let (mesh, sets) = deck_mesh(&input.input, &deck)?;
+    let audit = mesh.audit().with_unused_sets(&sets, &deck);
+    if json {
+        return print_json(&mesh_info_json(&mesh, &sets, &audit, worst)).map_err(CliError::io);
+    }
+    println!("{}", mesh.statistics().format());
+    println!("{}", mesh.quality().format(worst));
+    println!("{}", sets.inventory());
+    println!("{}", audit.format());
+    Ok(())
+}
+
+/// Mesh, with its DOFs numbered, and sets of the deck read from `path`; the
+/// cards that do not describe a valid mesh are parse errors
+pub fn deck_mesh(
+    path: &Path,
+    deck: &ccx_inp::Deck,
+) -> Result<(ccx_solver::Mesh, ccx_solver::Sets), CliError> {
+    let invalid = |err: String| CliError::parse(format!("{}: {err}", path.display()));
+    let mut mesh = ccx_solver::MeshBuilder::build_from_deck(deck).map_err(invalid)?;
+    mesh.calculate_dofs();
+    let mut sets = ccx_solver::Sets::build_from_deck(deck).map_err(invalid)?;
+    sets.add_card_sets(deck).map_err(invalid)?;
+    Ok((mesh, sets))
+}
+
+/// Statistics, element quality, sets and audit of `mesh-info --json`
+pub fn mesh_info_json(
+    mesh: &ccx_solver::Mesh,
+    sets: &ccx_solver::Sets,
+    audit: &ccx_solver::MeshAudit,
+    worst: usize,
+) -> serde_json::Value {
+    let stats = mesh.statistics();
+    let element_types: std::collections::BTreeMap<String, usize> = stats
+        .element_type_counts
+        .iter()
+        .map(|(element_type, &count)| (format!("{element_type:?}"), count))
+        .collect();
+    let quality = mesh.quality();
+    let metrics: Vec<serde_json::Value> = ccx_solver::QualityMetric::ALL
+        .into_iter()
+        .filter_map(|metric| {
+            let summary = quality.summary(metric)?;
+            let worst: Vec<serde_json::Value> = quality
+                .worst(metric, worst)
+                .into_iter()
+                .map(|(element, value)| serde_json::json!({ "element": element, "value": value }))
+                .collect();
+            Some(serde_json::json!({
+                "metric": metric.name(),
+                "min": summary.min,
+                "max": summary.max,
+                "mean": summary.mean,
+                "worst": worst,
+            }))
+        })
+        .collect();
+    let set_sizes = |sizes: Vec<(&String, usize)>| -> std::collections::BTreeMap<String, usize> {
+        sizes
+            .into_iter()
+            .map(|(name, size)| (name.clone(), size))
+            .collect()
+    };
+    serde_json::json!({
+        "nodes": stats.num_nodes,
+        "elements": stats.num_elements,
+        "dofs": stats.num_dofs,
+        "element_types": element_types,
+        "node_id_range": stats.node_id_range,
+        "element_id_range": stats.element_id_range,
+        "bounding_box": stats.bounding_box,
+        "quality": {
+            "measured": quality.elements.len(),
+            "skipped": quality.skipped,
+            "metrics": metrics,
+        },
+        "sets": {
+            "node_sets": set_sizes(
+                sets.node_sets.iter().map(|(k, s)| (k, s.nodes.len())).collect()
+            ),
+            "element_sets": set_sizes(
+                sets.element_sets.iter().map(|(k, s)| (k, s.elements.len())).collect()
+            ),
+            "surfaces": set_sizes(
+                sets.surfaces.iter().map(|(k, s)| (k, s.faces.len())).collect()
+            ),
+        },
+        "audit": {
+            "orphan_nodes": audit.orphan_nodes,
+            "missing_nodes": audit.missing_nodes,
+            "components": audit.components.len(),
+            "unused_sets": audit.unused_sets,
+            "warnings": audit.warnings(),
+        },
+    })
+}
+
+pub fn analyze_fixture_tree(root: &Path) -> Result<usize, String> {
+    let files = collect_inp_files(root)?;
+    if files.is_empty() {
+        println!("no .inp files found in {}", root.display());
+        return Ok(0);
+    }
+
+    let mut failures = 0usize;
+    for path in &files {
+        if let Err(err) = analyze_file(path) {
+            failures += 1;
+            eprintln!("parse_error: {}", err.message);
+        }
+    }
+
+    println!("fixtures_root: {}", root.display());
+    println!("total_inp: {}", files.len());
+    println!("parse_ok: {}", files.len().saturating_sub(failures));
+    println!("parse_failed: {}", failures);
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{MeshInfoArgs, parse_args};
+    use crate::unique_temp_dir;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn keyword_coverage_json_lists_modules_and_units_per_card() {
+        let deck = ccx_inp::Deck::parse_str(
+            "*NODE\n1, 0, 0, 0\n*MATERIAL, NAME=STEEL\n*PLASTIC\n250, 0\n*GAP\n",
+        )
+        .unwrap();
+        let value = keyword_coverage_json(&ccx_solver::keyword_coverage(&deck));
+        assert_eq!(value["cards"], 4);
+        assert_eq!(value["full"], 2);
+        assert_eq!(value["partial"], 1);
+        assert_eq!(value["unimplemented"], 1);
+        assert_eq!(value["complete"], false);
+        let plastic = &value["by_card"][2];
+        assert_eq!(plastic["line"], 4);
+        assert_eq!(plastic["support"], "partial");
+        assert_eq!(plastic["legacy_units"], serde_json::json!(["plastics.f"]));
+        assert_eq!(
+            plastic["rust_modules"],
+            serde_json::json!(["ccx_solver::materials"])
+        );
+        let gap = &value["by_card"][3];
+        assert_eq!(gap["known"], false);
+        assert_eq!(gap["note"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn analyze_file_expands_includes() {
+        let root = unique_temp_dir("ccx_cli_analyze_include");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let deck = root.join("root.inp");
+        let inc = root.join("mesh.inc");
+
+        fs::write(
+            &deck,
+            "*NODE\n1,0,0,0\n*INCLUDE,INPUT=mesh.inc\n*ELEMENT\n1,1,1,1,1\n",
+        )
+        .expect("write root deck");
+        fs::write(&inc, "*MATERIAL,NAME=STEEL\n").expect("write include");
+
+        let summary = analyze_file(&deck).expect("analysis should parse");
+        assert_eq!(summary.node_rows, 1);
+        assert_eq!(summary.element_rows, 1);
+        assert_eq!(summary.material_defs, 1);
+        assert_eq!(summary.include_files, vec!["mesh.inc".to_string()]);
+    }
+
+    #[test]
+    fn analyze_fixture_tree_counts_failures() {
+        let root = unique_temp_dir("ccx_cli_fixture_tree");
+        fs::create_dir_all(&root).expect("create temp dir");
+
+        fs::write(
+            root.join("ok.inp"),
+            "*NODE\n1,0,0,0\n*ELEMENT\n1,1,1,1,1\n*STEP\n*STATIC\n*END STEP\n",
+        )
+        .expect("write ok fixture");
+        fs::write(root.join("bad.inp"), "1,2,3\n*NODE\n1,0,0,0\n").expect("write bad fixture");
+
+        let failures = analyze_fixture_tree(&root).expect("scan should succeed");
+        assert_eq!(failures, 1);
+    }
+
+    #[test]
+    fn parse_mesh_info_args_reads_worst_count() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let parsed = parse_args::<MeshInfoArgs>(&to_args(&[
+            "--worst", "3", "-p", "len=2.5", "-I", "inc", "a.inp",
+        ]))
+        .unwrap();
+        let input = DeckInput {
+            input: PathBuf::from("a.inp"),
+            overrides: vec![("len".to_string(), "2.5".to_string())],
+            include_paths: vec![PathBuf::from("inc")],
+        };
+        assert_eq!(parsed, (input, 3, false));
+        assert_eq!(
+            parse_args::<MeshInfoArgs>(&to_args(&["a.inp"])).unwrap().1,
+            5
+        );
+        assert!(
+            parse_args::<MeshInfoArgs>(&to_args(&["--json", "a.inp"]))
+                .unwrap()
+                .2
+        );
+        assert!(parse_args::<MeshInfoArgs>(&to_args(&[])).is_err());
+        assert!(parse_args::<MeshInfoArgs>(&to_args(&["a.inp", "b.inp"])).is_err());
+        assert!(parse_args::<MeshInfoArgs>(&to_args(&["--worst", "x", "a.inp"])).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Stage timings of `bench`.
+
+use std::path::PathBuf;
+
+use crate::read_deck;
+
+pub struct BenchOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    /// Runs of every stage
+    pub repeat: usize,
+    /// Linear solver backend; the pipeline default when absent
+    pub backend: Option<ccx_solver::LinearSolverKind>,
+}
+
+/// Wall times and peak memory of one benchmarked stage
+struct BenchStage {
+    name: &'static str,
+    seconds: Vec<f64>,
+    /// Largest peak resident set size of the runs, where the platform
+    /// reports it
+    peak_bytes: Option<usize>,
+}
+
+impl BenchStage {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            seconds: Vec::new(),
+            peak_bytes: None,
+        }
+    }
+
+    /// Run and time one pass of the stage
+    fn time<T>(&mut self, run: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        reset_peak_memory();
+        let start = std::time::Instant::now();
+        let value = run()?;
+        self.seconds.push(start.elapsed().as_secs_f64());
+        if let Some(bytes) = peak_memory_bytes() {
+            self.peak_bytes = Some(self.peak_bytes.map_or(bytes, |peak| peak.max(bytes)));
+        }
+        Ok(value)
+    }
+
+    fn min(&self) -> f64 {
+        self.seconds.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    fn mean(&self) -> f64 {
+        self.seconds.iter().sum::<f64>() / self.seconds.len().max(1) as f64
+    }
+
+    fn max(&self) -> f64 {
+        self.seconds.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+/// Peak resident set size of the process, from `VmHWM` of
+/// `/proc/self/status`; `None` off Linux
+pub fn peak_memory_bytes() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Reset the peak resident set size to the current one, so that
+/// [`peak_memory_bytes`] covers only what follows
+///
+/// Best effort: where the kernel does not allow it the peak is that of the
+/// whole process so far.
+fn reset_peak_memory() {
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Stage timings and problem size of `ccx-cli bench`
+pub struct BenchReport {
+    backend: String,
+    nodes: usize,
+    elements: usize,
+    dofs: usize,
+    equations: usize,
+    nnz: usize,
+    stages: Vec<BenchStage>,
+}
+
+impl BenchReport {
+    pub fn format(&self) -> String {
+        let mut text = format!(
+            "Model: {} nodes, {} elements, {} DOFs, {} equations, {} nnz\n",
+            self.nodes, self.elements, self.dofs, self.equations, self.nnz
+        );
+        text.push_str(&format!("Linear solver: {}\n", self.backend));
+        let runs = self.stages.first().map_or(0, |stage| stage.seconds.len());
+        text.push_str(&format!("Runs: {}\n\n", runs));
+        text.push_str(&format!(
+            "{:<10} {:>10} {:>10} {:>10} {:>10}\n",
+            "stage", "min ms", "mean ms", "max ms", "peak MB"
+        ));
+        for stage in &self.stages {
+            let peak = stage
+                .peak_bytes
+                .map_or_else(|| "-".to_string(), |b| format!("{:.1}", b as f64 / 1e6));
+            text.push_str(&format!(
+                "{:<10} {:>10.3} {:>10.3} {:>10.3} {:>10}\n",
+                stage.name,
+                stage.min() * 1e3,
+                stage.mean() * 1e3,
+                stage.max() * 1e3,
+                peak
+            ));
+        }
+        let total: f64 = self.stages.iter().map(BenchStage::mean).sum();
+        text.push_str(&format!("{:<10} {:>21.3}\n", "total", total * 1e3));
+        text
+    }
+}
+
+/// Run the parse, mesh, assembly, solve and output stages of a static solve
+/// `options.repeat` times
+///
+/// The mesh stage also builds the materials and boundary conditions; the
+/// output stage formats the `.dat` and `.frd` results in memory, from one
+/// untimed pipeline run.
+pub fn bench_file(options: &BenchOptions) -> Result<BenchReport, String> {
+    let path = options.input.as_path();
+    let parse = || {
+        read_deck(path, &options.overrides, &options.include_paths)
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    };
+    let deck = parse()?;
+    let mut pipeline = ccx_solver::AnalysisPipeline::detect_from_deck(&deck);
+    if pipeline.config().analysis_type != ccx_solver::AnalysisType::LinearStatic {
+        return Err(format!(
+            "only linear static decks can be benchmarked, not {:?}",
+            pipeline.config().analysis_type
+        ));
+    }
+    if let Some(backend) = options.backend {
+        pipeline = pipeline.with_linear_solver(backend);
+    }
+    let backend = pipeline.config().linear_solver;
+    let results = pipeline.run(&deck)?;
+    if results.displacements.is_empty() {
+        return Err(format!("deck was not solved: {}", results.message));
+    }
+
+    let mut stages = ["parse", "mesh", "assembly", "solve", "output"].map(BenchStage::new);
+    let mut report = None;
+    for _ in 0..options.repeat {
+        let [parsing, meshing, assembly, solve, output] = &mut stages;
+        let deck = parsing.time(parse)?;
+        let (mesh, materials, bcs) = meshing.time(|| {
+            let mut mesh = ccx_solver::MeshBuilder::build_from_deck(&deck)?;
+            mesh.calculate_dofs();
+            let mut materials = ccx_solver::MaterialLibrary::build_from_deck(&deck)?;
+            // Unassigned elements get the first material, like the pipeline
+            if let Some(name) = materials.material_names().first().cloned() {
+                for id in mesh.elements.keys() {
+                    if materials.get_element_material(*id).is_none() {
+                        materials.assign_material(*id, name.clone());
+                    }
+                }
+            }
+            let bcs = ccx_solver::BCBuilder::build_from_deck(&deck)?;
+            Ok((mesh, materials, bcs))
+        })?;
+        let system = assembly
+            .time(|| ccx_solver::SparseGlobalSystem::assemble(&mesh, &materials, &bcs, 0.001))?;
+        let (_, info) = solve.time(|| system.solve_with_info(backend.create().as_mut()))?;
+        output.time(|| {
+            let (_, steps) = ccx_io::static_dat_steps(&deck, &results)?;
+            let frd = ccx_io::static_frd(&mesh, &results, "bench");
+            let mut out = Vec::new();
+            ccx_io::write_dat_results_to(&mut out, &steps)
+                .and_then(|()| ccx_io::write_frd_to(&mut out, &frd))
+                .map_err(|err| err.to_string())
+        })?;
+        report.get_or_insert((mesh.nodes.len(), mesh.elements.len(), system.num_dofs, info));
+    }
+    let (nodes, elements, dofs, info) = report.ok_or_else(|| "no runs".to_string())?;
+    Ok(BenchReport {
+        backend: info.backend,
+        nodes,
+        elements,
+        dofs,
+        equations: info.num_equations,
+        nnz: info.nnz,
+        stages: stages.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{BenchArgs, parse_args};
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn bench_times_every_stage() {
+        let root = unique_temp_dir("ccx_cli_bench");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let deck = root.join("truss.inp");
+        fs::write(
+            &deck,
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("write deck");
+        let args = ["-n", "2", &deck.display().to_string()].map(String::from);
+        let report = bench_file(&parse_args::<BenchArgs>(&args).unwrap()).unwrap();
+        let names: Vec<&str> = report.stages.iter().map(|stage| stage.name).collect();
+        assert_eq!(names, ["parse", "mesh", "assembly", "solve", "output"]);
+        assert!(report.stages.iter().all(|stage| stage.seconds.len() == 2));
+        assert_eq!((report.nodes, report.elements, report.dofs), (2, 1, 6));
+        assert!(report.nnz > 0);
+        assert!(report.format().contains("assembly"));
+        assert!(parse_args::<BenchArgs>(&["-n".to_string(), "0".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Model checks of `check`: lint findings of the deck, then connectivity,
+//! Jacobians, materials and boundary conditions of the model built from it.
+
+use std::path::{Path, PathBuf};
+
+use ccx_model::ModelSummary;
+
+use crate::read_deck;
+
+pub struct CheckOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    /// Unit system for the plausibility checks; a `** UNITS:` comment of the
+    /// deck when absent
+    pub units: Option<ccx_solver::UnitSystem>,
+    /// JSON file for the diagnostics
+    pub json: Option<PathBuf>,
+    /// Fail on warnings as well as errors
+    pub strict: bool,
+}
+
+/// Source line of every element definition
+fn element_lines(deck: &ccx_inp::Deck) -> std::collections::HashMap<i32, usize> {
+    let mut lines = std::collections::HashMap::new();
+    for card in deck.cards.iter().filter(|c| c.keyword == "ELEMENT") {
+        for (index, line) in card.data_lines.iter().enumerate() {
+            if let Some(Ok(id)) = line.split(',').next().map(|f| f.trim().parse::<i32>()) {
+                lines.entry(id).or_insert(card.data_line_number(index));
+            }
+        }
+    }
+    lines
+}
+
+/// Diagnostics of the model built from a deck that passed the static checks:
+/// mesh connectivity and Jacobians, material plausibility and boundary
+/// conditions
+fn check_model(deck: &ccx_inp::Deck, options: &CheckOptions) -> Vec<ccx_model::Diagnostic> {
+    use ccx_model::Diagnostic;
+
+    let mut diagnostics = Vec::new();
+    let mesh = match ccx_solver::MeshBuilder::build_from_deck(deck) {
+        Ok(mesh) => mesh,
+        Err(err) => return vec![Diagnostic::error(None, format!("mesh: {err}"))],
+    };
+    let lines = element_lines(deck);
+
+    let mut audit = mesh.audit();
+    let sets = ccx_solver::Sets::build_from_deck(deck).and_then(|mut sets| {
+        sets.add_card_sets(deck)?;
+        Ok(sets)
+    });
+    match sets {
+        Ok(sets) => audit = audit.with_unused_sets(&sets, deck),
+        Err(err) => diagnostics.push(Diagnostic::error(None, format!("sets: {err}"))),
+    }
+    for (element, nodes) in std::mem::take(&mut audit.missing_nodes) {
+        let nodes: Vec<String> = nodes.iter().map(i32::to_string).collect();
+        diagnostics.push(Diagnostic::error(
+            lines.get(&element).copied(),
+            format!(
+                "element {element} references undefined nodes {}",
+                nodes.join(", ")
+            ),
+        ));
+    }
+    for warning in audit.warnings() {
+        diagnostics.push(Diagnostic::warning(None, warning));
+    }
+    for element in mesh.check_jacobians() {
+        diagnostics.push(Diagnostic::error(
+            lines.get(&element.element).copied(),
+            element.describe(),
+        ));
+    }
+
+    let units = match options.units {
+        Some(units) => Ok(Some(units)),
+        None => ccx_solver::UnitSystem::from_deck(deck),
+    };
+    match (units, ccx_solver::MaterialLibrary::build_from_deck(deck)) {
+        (_, Err(err)) => diagnostics.push(Diagnostic::error(None, format!("materials: {err}"))),
+        (Err(err), _) => diagnostics.push(Diagnostic::error(None, err)),
+        (Ok(Some(units)), Ok(materials)) => {
+            let warnings = units
+                .check_materials(&materials)
+                .into_iter()
+                .chain(units.check_gravity(deck));
+            diagnostics.extend(warnings.map(|w| Diagnostic::warning(None, w)));
+        }
+        (Ok(None), Ok(_)) => {}
+    }
+
+    let bcs = match ccx_solver::BCBuilder::build_from_deck(deck) {
+        Ok(bcs) => bcs,
+        Err(err) => {
+            diagnostics.push(Diagnostic::error(None, err));
+            return diagnostics;
+        }
+    };
+    for bc in &bcs.displacement_bcs {
+        if !mesh.nodes.contains_key(&bc.node) {
+            diagnostics.push(Diagnostic::error(
+                None,
+                format!("*BOUNDARY on node {} which is not in the mesh", bc.node),
+            ));
+        } else if bc.first_dof == 0 || bc.last_dof < bc.first_dof {
+            diagnostics.push(Diagnostic::error(
+                None,
+                format!(
+                    "*BOUNDARY on node {} has invalid DOF range {}-{}",
+                    bc.node, bc.first_dof, bc.last_dof
+                ),
+            ));
+        }
+    }
+    let summary = ModelSummary::from_deck(deck);
+    if bcs.displacement_bcs.is_empty() && !summary.has_frequency && !summary.has_heat_transfer {
+        diagnostics.push(Diagnostic::warning(
+            None,
+            "no *BOUNDARY conditions; the model can move as a rigid body",
+        ));
+    }
+    let load_cards = [
+        "CLOAD",
+        "DLOAD",
+        "DSLOAD",
+        "CFLUX",
+        "DFLUX",
+        "FILM",
+        "TEMPERATURE",
+    ];
+    let loaded = deck
+        .cards
+        .iter()
+        .any(|card| load_cards.contains(&card.keyword.as_str()))
+        || bcs.displacement_bcs.iter().any(|bc| bc.value != 0.0);
+    if summary.has_static && !loaded {
+        diagnostics.push(Diagnostic::warning(
+            None,
+            "static step without loads or prescribed displacements",
+        ));
+    }
+    diagnostics
+}
+
+/// Run the parser, deck linter and model checks on a deck without solving it
+pub fn check_file(options: &CheckOptions) -> Vec<ccx_model::Diagnostic> {
+    let deck = match read_deck(&options.input, &options.overrides, &options.include_paths) {
+        Ok(deck) => deck,
+        Err(err) => {
+            let line = (err.line > 0).then_some(err.line);
+            return vec![ccx_model::Diagnostic::error(line, err.message)];
+        }
+    };
+    let mut diagnostics = ccx_model::lint_deck(&deck);
+    // Model checks on a deck with broken references only repeat the errors
+    if diagnostics
+        .iter()
+        .all(|d| d.severity == ccx_model::Severity::Warning)
+    {
+        diagnostics.extend(check_model(&deck, options));
+    }
+    diagnostics
+}
+
+fn write_check_report(
+    path: &Path,
+    input: &Path,
+    diagnostics: &[ccx_model::Diagnostic],
+) -> Result<(), String> {
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let entries: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "severity": d.severity.label(),
+                "line": d.line,
+                "message": d.message,
+            })
+        })
+        .collect();
+    let report = serde_json::json!({
+        "input": input.display().to_string(),
+        "errors": count(ccx_model::Severity::Error),
+        "warnings": count(ccx_model::Severity::Warning),
+        "diagnostics": entries,
+    });
+    let text = serde_json::to_string_pretty(&report).map_err(|err| err.to_string())?;
+    std::fs::write(path, text + "\n")
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// Check a deck and print its diagnostics, returning whether it passed
+pub fn run_check(options: &CheckOptions) -> Result<bool, String> {
+    let diagnostics = check_file(options);
+    let input = options.input.display();
+    for d in &diagnostics {
+        match d.line {
+            Some(line) => println!("{input}:{line}: {}: {}", d.severity.label(), d.message),
+            None => println!("{input}: {}: {}", d.severity.label(), d.message),
+        }
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == ccx_model::Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    println!("{input}: {errors} errors, {warnings} warnings");
+    if let Some(path) = &options.json {
+        write_check_report(path, &options.input, &diagnostics)?;
+    }
+    Ok(errors == 0 && (warnings == 0 || !options.strict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{CheckArgs, parse_args};
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn check_reports_model_problems() {
+        let root = unique_temp_dir("ccx_cli_check");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let deck = root.join("bar.inp");
+        fs::write(
+            &deck,
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n2,2,3\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("write deck");
+        let mut options = CheckOptions {
+            input: deck.clone(),
+            overrides: Vec::new(),
+            include_paths: Vec::new(),
+            units: None,
+            json: Some(root.join("check.json")),
+            strict: false,
+        };
+
+        let diagnostics = check_file(&options);
+        let errors: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.severity == ccx_model::Severity::Error)
+            .collect();
+        assert_eq!(errors.len(), 1, "{diagnostics:?}");
+        assert_eq!(errors[0].line, Some(7));
+        assert!(
+            errors[0]
+                .message
+                .starts_with("element 2 (T3D2) is degenerate")
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.message.starts_with("no *BOUNDARY conditions"))
+        );
+        assert_eq!(run_check(&options), Ok(false));
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join("check.json")).unwrap()).unwrap();
+        assert_eq!(json["errors"], 1);
+        let lines: Vec<_> = json["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|d| d["severity"] == "error")
+            .map(|d| d["line"].clone())
+            .collect();
+        assert_eq!(lines, vec![7]);
+
+        // Without the degenerate element only the warning is left
+        fs::write(
+            &deck,
+            fs::read_to_string(&deck).unwrap().replace("2,2,3\n", ""),
+        )
+        .expect("rewrite deck");
+        assert_eq!(run_check(&options), Ok(true));
+        options.strict = true;
+        assert_eq!(run_check(&options), Ok(false));
+    }
+
+    #[test]
+    fn check_searches_include_paths() {
+        let root = unique_temp_dir("ccx_cli_include_paths");
+        let lib = root.join("lib");
+        fs::create_dir_all(&lib).expect("create lib directory");
+        let deck = root.join("deck.inp");
+        fs::write(&deck, "*INCLUDE,INPUT=mesh.inc\n").expect("write deck");
+        fs::write(lib.join("mesh.inc"), "*NODE\n1,0,0,0\n").expect("write mesh");
+        let args = |extra: &[&str]| {
+            let mut args = vec![deck.display().to_string()];
+            args.extend(extra.iter().map(|s| s.to_string()));
+            parse_args::<CheckArgs>(&args).expect("valid arguments")
+        };
+
+        let without = check_file(&args(&[]));
+        assert!(without.iter().any(|d| d.message.contains("mesh.inc")));
+        let options = args(&["-I", lib.to_str().unwrap()]);
+        assert_eq!(options.include_paths, vec![lib.clone()]);
+        let with = check_file(&options);
+        assert!(with.iter().all(|d| !d.message.contains("mesh.inc")));
+        assert!(parse_args::<CheckArgs>(&["-I".to_string()]).is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
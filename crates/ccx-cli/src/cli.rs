@@ -0,0 +1,1182 @@
+//! Command-line grammar of `ccx-cli`.
+//!
+//! The subcommands and their flags are declared with clap, which supplies
+//! the help pages, flag validation and shell completions. Every `*Args`
+//! struct converts into the options the command itself works with, for the
+//! checks clap cannot express and for values that combine several flags.
+
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+
+use crate::DeckInput;
+use crate::bench::BenchOptions;
+use crate::check::CheckOptions;
+use crate::convert::{ConvertOptions, ExpandIncludesOptions, ImportOptions, PartitionOptions};
+use crate::error::ErrorFormat;
+use crate::results::{
+    Dat2VtuOptions, Frd2VtuOptions, FrdDiffOptions, FrfOptions, PathPlotOptions, ProbeOptions,
+};
+use crate::solve::{ModesOptions, SolveFormat, SolveOptions};
+use crate::validate::{ValidateOptions, XvalidateOptions};
+use crate::watch::{WatchMode, WatchOptions};
+
+const AFTER_HELP: &str = "\
+Exit codes: 0 success, 1 other failure, 2 usage, 3 parse error,
+            4 validation failure (check, validate, xvalidate, frd-diff,
+              inverted elements),
+            5 assembly error, 6 solver failure or non-convergence, 7 I/O error
+
+Unless --no-config is given, the nearest ccx.toml in the working directory or
+above (or the --config file) supplies defaults that command-line flags override.
+
+Examples:
+  ccx-cli analyze --json tests/fixtures/solver/ax6.inp
+  ccx-cli solve plate.inp -p thickness=0.02 --backend cg
+  ccx-cli solve plate.inp --output-dir results --job-name run1 --format frd,vtu
+  ccx-cli modes --num 10 --format frd,vtu bracket.inp
+  ccx-cli validate -j 8 --junit report.xml --json report.json fixtures
+  ccx-cli xvalidate --ccx /opt/ccx/ccx_2.23 --timeout 600 --json scoreboard.json fixtures
+  ccx-cli check --strict --json bracket_check.json bracket.inp
+  ccx-cli watch --run solve bracket.inp
+  ccx-cli frd2vtu --binary --step 2 job.frd step2.vtu
+  ccx-cli probe --node 123 --at 10,0,-5 --dataset STRESS job.frd
+  ccx-cli partition --parts 8 --decks --output-dir parts bracket.inp
+  ccx-cli expand-includes -p thickness=0.02 bracket.inp bracket_flat.inp
+  ccx-cli frd-diff --rtol 1e-5 --atol 1e-8 ccx/job.frd rust/job.frd
+  ccx-cli migration-report --json --history migration_history.jsonl
+  ccx-cli migration-hotspots --top 20 usage/*.tsv
+  ccx-cli keyword-coverage --json bracket.inp
+  ccx-cli --error-format json -q solve plate.inp
+  ccx-cli completions bash > /etc/bash_completion.d/ccx-cli";
+
+/// CalculiX model analysis, solving and result conversion
+#[derive(Debug, Parser)]
+#[command(
+    name = "ccx-cli",
+    version,
+    args_override_self = true,
+    after_help = AFTER_HELP
+)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Flags accepted before or after any subcommand
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Global options")]
+pub struct GlobalArgs {
+    /// Show debug messages; repeat for trace messages
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Show errors only
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub quiet: u8,
+    /// Log filter such as `warn,ccx_solver=debug`; overrides -v and -q
+    #[arg(long, value_name = "FILTER", global = true)]
+    pub log_level: Option<String>,
+    /// Project config to use instead of the nearest ccx.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+    /// Ignore ccx.toml files
+    #[arg(long, global = true, conflicts_with = "config")]
+    pub no_config: bool,
+    /// Format of error reports on stderr
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "text",
+        global = true
+    )]
+    pub error_format: ErrorFormat,
+}
+
+impl GlobalArgs {
+    /// Log filter selected by the flags
+    ///
+    /// `-q` shows errors only, the default is `info`, `-v` adds debug and
+    /// `-vv` trace messages.
+    pub fn log_filter(&self) -> String {
+        if let Some(filter) = &self.log_level {
+            return filter.clone();
+        }
+        match i32::from(self.verbose) - i32::from(self.quiet) {
+            ..0 => "error",
+            0 => "info",
+            1 => "debug",
+            _ => "trace",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Summarize the cards, mesh and mass properties of a deck
+    Analyze(InputArgs),
+    /// Parse every deck below a fixture directory
+    AnalyzeFixtures(AnalyzeFixturesArgs),
+    /// Solve a deck and write the result files
+    Solve(SolveArgs),
+    /// Extract natural frequencies, participation factors and mode shapes
+    Modes(ModesArgs),
+    /// Solve fixture decks and compare them with their reference results
+    Validate(ValidateArgs),
+    /// Run fixture decks through the legacy ccx binary and the Rust pipeline
+    /// and compare their results
+    Xvalidate(XvalidateArgs),
+    /// Lint a deck and check its model without solving it
+    Check(CheckArgs),
+    /// Time the parse, mesh, assembly, solve and output stages of a solve
+    Bench(BenchArgs),
+    /// Re-run checks or solves whenever a deck or its includes change
+    Watch(WatchArgs),
+    /// Print mesh statistics, element quality and sets of a deck
+    MeshInfo(MeshInfoArgs),
+    /// Print the result tables of a .dat file
+    Postprocess(PostprocessArgs),
+    /// Convert FRD results to legacy VTK
+    Frd2vtk(Frd2VtkArgs),
+    /// Convert FRD results to VTU or a PVD series
+    Frd2vtu(Frd2VtuArgs),
+    /// Write .dat result tables on the mesh of a deck as VTU
+    Dat2vtu(Dat2VtuArgs),
+    /// Compare two FRD files dataset by dataset
+    FrdDiff(FrdDiffArgs),
+    /// Sample a nodal result along a polyline into CSV
+    PathPlot(PathPlotArgs),
+    /// Print result values at nodes or points of FRD or .dat results
+    Probe(ProbeArgs),
+    /// Write a frequency response from steady-state dynamics results
+    Frf(FrfArgs),
+    /// Import a surface or Gmsh mesh as a deck
+    Import(ImportArgs),
+    /// Convert Nastran OP2 results to FRD
+    Op2frd(Op2FrdArgs),
+    /// Convert between deck, mesh and result formats by file extension
+    Convert(ConvertArgs),
+    /// Split the mesh of a deck into element parts
+    Partition(PartitionArgs),
+    /// Write a deck with its includes expanded and parameters resolved as one
+    /// self-contained deck
+    ExpandIncludes(ExpandIncludesArgs),
+    /// Report the porting status of the solver sources
+    MigrationReport(ReportArgs),
+    /// Report the porting status of the GUI sources
+    GuiMigrationReport(ReportArgs),
+    /// Rank the unported legacy routines by the calls recorded in usage logs
+    /// of `solve --usage-log` or the compatibility registry
+    MigrationHotspots(HotspotsArgs),
+    /// List which cards of a deck the Rust pipeline fully, partially or does
+    /// not support
+    KeywordCoverage(InputArgs),
+    /// Print a shell completion script
+    Completions(CompletionsArgs),
+}
+
+/// Index of the subcommand name in the raw command line `args`
+pub fn subcommand_position(args: &[String]) -> Option<usize> {
+    use clap::CommandFactory;
+
+    let command = Cli::command();
+    let mut index = 1;
+    while let Some(arg) = args.get(index) {
+        match arg.as_str() {
+            "--log-level" | "--config" | "--error-format" => index += 2,
+            flag if flag.starts_with('-') => index += 1,
+            name => return command.find_subcommand(name).map(|_| index),
+        }
+    }
+    None
+}
+
+/// Conversion of parsed arguments into the options of a command
+pub(crate) trait IntoOptions {
+    type Options;
+
+    fn into_options(self) -> Result<Self::Options, String>;
+}
+
+/// Deck and parameter arguments shared by the commands that read a deck
+#[derive(Debug, Args)]
+pub struct DeckArgs {
+    /// Input deck
+    #[arg(value_name = "INPUT.inp")]
+    pub input: PathBuf,
+    /// Override a *PARAMETER value
+    #[arg(short = 'p', long = "param", value_name = "NAME=VALUE",
+          value_parser = ccx_inp::parse_override)]
+    pub overrides: Vec<(String, String)>,
+    /// Directory searched for *INCLUDE files not found next to the deck
+    #[arg(short = 'I', long = "include-path", value_name = "DIR")]
+    pub include_paths: Vec<PathBuf>,
+}
+
+impl DeckArgs {
+    fn into_input(self) -> DeckInput {
+        DeckInput {
+            input: self.input,
+            overrides: self.overrides,
+            include_paths: self.include_paths,
+        }
+    }
+}
+
+/// Encoding flags of the VTU writers
+#[derive(Debug, Args)]
+pub struct VtuEncodingArgs {
+    /// Write appended raw binary data
+    #[arg(long, conflicts_with = "compressed")]
+    pub binary: bool,
+    /// Write zlib-compressed appended binary data
+    #[arg(long)]
+    pub compressed: bool,
+}
+
+impl VtuEncodingArgs {
+    fn format(&self) -> ccx_io::VtkFormat {
+        if self.compressed {
+            ccx_io::VtkFormat::Compressed
+        } else if self.binary {
+            ccx_io::VtkFormat::Binary
+        } else {
+            ccx_io::VtkFormat::Ascii
+        }
+    }
+}
+
+/// Result comparison tolerances of `validate`, `xvalidate` and `frd-diff`
+#[derive(Debug, Args)]
+pub struct ToleranceArgs {
+    /// Absolute tolerance [default: 1e-9 for validate and xvalidate, the
+    /// comparison default for frd-diff]
+    #[arg(long, value_name = "ABS", value_parser = non_negative)]
+    pub atol: Option<f64>,
+    /// Relative tolerance
+    #[arg(long, value_name = "REL", value_parser = non_negative)]
+    pub rtol: Option<f64>,
+    /// Tolerances of one dataset or quantity
+    #[arg(long, value_name = "NAME=ABS,REL", value_parser = crate::validate::parse_quantity_tolerance)]
+    pub tolerance: Vec<(String, f64, f64)>,
+}
+
+impl ToleranceArgs {
+    /// `defaults` with the given tolerances applied
+    fn apply(self, defaults: ccx_io::FrdTolerances) -> ccx_io::FrdTolerances {
+        let mut tolerances = defaults;
+        if let Some(atol) = self.atol {
+            tolerances.absolute = atol;
+        }
+        if let Some(rtol) = self.rtol {
+            tolerances.relative = rtol;
+        }
+        for (name, atol, rtol) in self.tolerance {
+            tolerances = tolerances.with_dataset(&name, atol, rtol);
+        }
+        tolerances
+    }
+}
+
+fn non_negative(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(v) if v >= 0.0 => Ok(v),
+        _ => Err(format!("expected a non-negative number, got {value}")),
+    }
+}
+
+fn positive(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("expected a positive integer, got {value}")),
+    }
+}
+
+fn point(value: &str) -> Result<[f64; 3], String> {
+    let coords: Vec<f64> = value
+        .split(',')
+        .map(|c| c.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid point {value}"))?;
+    <[f64; 3]>::try_from(coords).map_err(|_| format!("point {value} needs x,y,z"))
+}
+
+fn job_name(value: &str) -> Result<String, String> {
+    if value.is_empty() || value.contains(['/', '\\']) {
+        return Err(format!("invalid job name {value}"));
+    }
+    Ok(value.to_string())
+}
+
+#[derive(Debug, Args)]
+pub struct InputArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Print JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl IntoOptions for InputArgs {
+    type Options = (DeckInput, bool);
+
+    fn into_options(self) -> Result<Self::Options, String> {
+        Ok((self.deck.into_input(), self.json))
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct AnalyzeFixturesArgs {
+    /// Fixture directory [default: `fixtures` of ccx.toml]
+    #[arg(value_name = "FIXTURES_DIR")]
+    pub root: Option<PathBuf>,
+}
+
+impl IntoOptions for AnalyzeFixturesArgs {
+    type Options = PathBuf;
+
+    fn into_options(self) -> Result<Self::Options, String> {
+        self.root
+            .ok_or_else(|| "missing fixtures directory".to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Linear solver backend
+    #[arg(long, value_name = "SOLVER",
+          value_parser = ccx_solver::LinearSolverKind::from_name)]
+    pub backend: Option<ccx_solver::LinearSolverKind>,
+    /// Nodal stress and strain averaging: all, none, material or
+    /// elset:<name>,...
+    #[arg(long, value_name = "MODE", default_value = "all",
+          value_parser = ccx_solver::NodalAveraging::from_name)]
+    pub averaging: ccx_solver::NodalAveraging,
+    /// CSV or JSON file for the *NODE PRINT/*EL PRINT history table
+    #[arg(long, value_name = "FILE")]
+    pub history: Option<PathBuf>,
+    /// Unit system for the plausibility checks: m-kg-s, mm-t-s, mm-kg-ms or
+    /// in-lbf-s
+    #[arg(long, value_name = "SYSTEM", value_parser = ccx_solver::UnitSystem::from_name)]
+    pub units: Option<ccx_solver::UnitSystem>,
+    /// Solve despite inverted or degenerate elements
+    #[arg(long)]
+    pub warn_inverted: bool,
+    /// Directory for the result files [default: the deck's directory]
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+    /// Base name of the result files [default: the deck's file stem]
+    #[arg(long, value_name = "NAME", value_parser = job_name)]
+    pub job_name: Option<String>,
+    /// Comma-separated result files to write: dat, frd, vtu
+    #[arg(long = "format", value_name = "FORMATS", default_value = "dat,frd",
+          value_parser = crate::solve::parse_solve_formats)]
+    pub formats: ::std::vec::Vec<SolveFormat>,
+    /// Shared library whose e_<type>_ routines compute the stiffness of the
+    /// element types the solver has no formulation for
+    #[arg(long, value_name = "LIB")]
+    pub legacy_elements: Option<PathBuf>,
+    /// Usage log that counts the calls of the --legacy-elements routines,
+    /// for migration-hotspots; counts are added to those already in the file
+    #[arg(long, value_name = "FILE")]
+    pub usage_log: Option<PathBuf>,
+    /// Print the results as JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl IntoOptions for SolveArgs {
+    type Options = SolveOptions;
+
+    fn into_options(self) -> Result<SolveOptions, String> {
+        Ok(SolveOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            backend: self.backend,
+            averaging: self.averaging,
+            history: self.history,
+            units: self.units,
+            warn_inverted: self.warn_inverted,
+            output_dir: self.output_dir,
+            job_name: self.job_name,
+            formats: self.formats,
+            legacy_elements: self.legacy_elements,
+            usage_log: self.usage_log,
+            json: self.json,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ModesArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Number of modes [default: the *FREQUENCY card's, else 10]
+    #[arg(long = "num", value_name = "N", value_parser = positive)]
+    pub num_modes: Option<usize>,
+    /// Extract the modes closest to this eigenvalue ω², e.g. a small
+    /// negative value for an unsupported model
+    #[arg(
+        long,
+        value_name = "OMEGA2",
+        default_value_t = 0.0,
+        allow_negative_numbers = true
+    )]
+    pub shift: f64,
+    /// Linear solver backend factorizing K - σM
+    #[arg(long, value_name = "SOLVER",
+          value_parser = ccx_solver::LinearSolverKind::from_name)]
+    pub backend: Option<ccx_solver::LinearSolverKind>,
+    /// Directory for the mode shape files [default: the deck's directory]
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+    /// Base name of the mode shape files [default: the deck's file stem]
+    #[arg(long, value_name = "NAME", value_parser = job_name)]
+    pub job_name: Option<String>,
+    /// Comma-separated mode shape files to write: frd, vtu [default: none]
+    #[arg(long = "format", value_name = "FORMATS", value_parser = crate::solve::parse_solve_formats)]
+    pub formats: Option<::std::vec::Vec<SolveFormat>>,
+    /// Print the modes as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl IntoOptions for ModesArgs {
+    type Options = ModesOptions;
+
+    fn into_options(self) -> Result<ModesOptions, String> {
+        let formats = self.formats.unwrap_or_default();
+        if formats.contains(&SolveFormat::Dat) {
+            return Err("modes writes frd and vtu files only".to_string());
+        }
+        Ok(ModesOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            num_modes: self.num_modes,
+            shift: self.shift,
+            backend: self.backend,
+            output_dir: self.output_dir,
+            job_name: self.job_name,
+            formats,
+            json: self.json,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// Fixture directory [default: `fixtures` of ccx.toml]
+    #[arg(value_name = "FIXTURES_DIR")]
+    pub root: Option<PathBuf>,
+    #[command(flatten)]
+    pub tolerances: ToleranceArgs,
+    /// Worker threads [default: all cores]
+    #[arg(short, long, value_name = "THREADS", value_parser = positive)]
+    pub jobs: Option<usize>,
+    /// JUnit XML report
+    #[arg(long, value_name = "FILE")]
+    pub junit: Option<PathBuf>,
+    /// JSON summary; `-` prints it to stdout instead of the text report
+    #[arg(long, value_name = "FILE")]
+    pub json: Option<PathBuf>,
+}
+
+impl IntoOptions for ValidateArgs {
+    type Options = ValidateOptions;
+
+    fn into_options(self) -> Result<ValidateOptions, String> {
+        Ok(ValidateOptions {
+            root: self
+                .root
+                .ok_or_else(|| "missing fixtures directory".to_string())?,
+            tolerances: self
+                .tolerances
+                .apply(ccx_io::FrdTolerances::new(1e-9, 1e-4)),
+            jobs: self.jobs,
+            junit: self.junit,
+            json: self.json,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct XvalidateArgs {
+    /// Fixture directory [default: `fixtures` of ccx.toml]
+    #[arg(value_name = "FIXTURES_DIR")]
+    pub root: Option<PathBuf>,
+    /// Legacy ccx executable, run as `<PATH> -i <job>` [default: $CCX, else
+    /// `ccx_2.23`]
+    #[arg(long, value_name = "PATH")]
+    pub ccx: Option<PathBuf>,
+    #[command(flatten)]
+    pub tolerances: ToleranceArgs,
+    /// Worker threads [default: all cores]
+    #[arg(short, long, value_name = "THREADS", value_parser = positive)]
+    pub jobs: Option<usize>,
+    /// Seconds after which a ccx run is killed
+    #[arg(long, value_name = "SECONDS", value_parser = positive)]
+    pub timeout: Option<usize>,
+    /// Keep the ccx and Rust files of every deck below DIR
+    #[arg(long, value_name = "DIR")]
+    pub keep_files: Option<PathBuf>,
+    /// JSON scoreboard; `-` prints it to stdout instead of the text report
+    #[arg(long, value_name = "FILE")]
+    pub json: Option<PathBuf>,
+}
+
+impl IntoOptions for XvalidateArgs {
+    type Options = XvalidateOptions;
+
+    fn into_options(self) -> Result<XvalidateOptions, String> {
+        Ok(XvalidateOptions {
+            root: self
+                .root
+                .ok_or_else(|| "missing fixtures directory".to_string())?,
+            ccx: self
+                .ccx
+                .or_else(|| std::env::var_os("CCX").map(PathBuf::from))
+                .unwrap_or_else(|| PathBuf::from("ccx_2.23")),
+            tolerances: self
+                .tolerances
+                .apply(ccx_io::FrdTolerances::new(1e-9, 1e-4)),
+            jobs: self.jobs,
+            timeout: self
+                .timeout
+                .map(|seconds| std::time::Duration::from_secs(seconds as u64)),
+            keep_files: self.keep_files,
+            json: self.json,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Unit system for the plausibility checks [default: a `** UNITS:`
+    /// comment of the deck]
+    #[arg(long, value_name = "SYSTEM", value_parser = ccx_solver::UnitSystem::from_name)]
+    pub units: Option<ccx_solver::UnitSystem>,
+    /// JSON file for the diagnostics
+    #[arg(long, value_name = "FILE")]
+    pub json: Option<PathBuf>,
+    /// Fail on warnings as well as errors
+    #[arg(long)]
+    pub strict: bool,
+}
+
+impl IntoOptions for CheckArgs {
+    type Options = CheckOptions;
+
+    fn into_options(self) -> Result<CheckOptions, String> {
+        Ok(CheckOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            units: self.units,
+            json: self.json,
+            strict: self.strict,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Runs of every stage
+    #[arg(short = 'n', long, value_name = "RUNS", default_value = "3", value_parser = positive)]
+    pub repeat: usize,
+    /// Linear solver backend
+    #[arg(long, value_name = "SOLVER",
+          value_parser = ccx_solver::LinearSolverKind::from_name)]
+    pub backend: Option<ccx_solver::LinearSolverKind>,
+}
+
+impl IntoOptions for BenchArgs {
+    type Options = BenchOptions;
+
+    fn into_options(self) -> Result<BenchOptions, String> {
+        Ok(BenchOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            repeat: self.repeat,
+            backend: self.backend,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// What to run after every change
+    #[arg(long = "run", value_enum, default_value = "check")]
+    pub mode: WatchMode,
+    /// Quiet period in milliseconds after the last change before re-running
+    #[arg(long, value_name = "MS", default_value = "300")]
+    pub debounce: u64,
+}
+
+impl IntoOptions for WatchArgs {
+    type Options = WatchOptions;
+
+    fn into_options(self) -> Result<WatchOptions, String> {
+        Ok(WatchOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            mode: self.mode,
+            debounce: std::time::Duration::from_millis(self.debounce),
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct MeshInfoArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Number of worst-quality elements to list
+    #[arg(long, value_name = "N", default_value = "5")]
+    pub worst: usize,
+    /// Print JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl IntoOptions for MeshInfoArgs {
+    type Options = (DeckInput, usize, bool);
+
+    fn into_options(self) -> Result<Self::Options, String> {
+        Ok((self.deck.into_input(), self.worst, self.json))
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct PostprocessArgs {
+    /// Result tables
+    #[arg(value_name = "INPUT.dat")]
+    pub input: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct Frd2VtkArgs {
+    #[arg(value_name = "INPUT.frd")]
+    pub input: PathBuf,
+    #[arg(value_name = "OUTPUT.vtk")]
+    pub output: PathBuf,
+    /// Only export the results of this step
+    #[arg(long, value_name = "N")]
+    pub step: Option<i32>,
+}
+
+impl IntoOptions for Frd2VtkArgs {
+    type Options = (PathBuf, PathBuf, Option<i32>);
+
+    fn into_options(self) -> Result<Self::Options, String> {
+        Ok((self.input, self.output, self.step))
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct Frd2VtuArgs {
+    #[arg(value_name = "INPUT.frd")]
+    pub input: PathBuf,
+    /// VTU file, or PVD for a series with one VTU per result block
+    #[arg(value_name = "OUTPUT.(vtu|pvd)")]
+    pub output: PathBuf,
+    #[command(flatten)]
+    pub encoding: VtuEncodingArgs,
+    /// Write every eigenmode as its own point array
+    #[arg(long)]
+    pub modes: bool,
+    /// Add principal values and direction vectors of tensor results
+    #[arg(long)]
+    pub principal: bool,
+    /// Add max/min envelopes over all result blocks
+    #[arg(long)]
+    pub envelope: bool,
+    /// Yield stress for the SAFETY factor field, or a deck whose lowest
+    /// *PLASTIC yield stress is used
+    #[arg(long = "yield", value_name = "STRESS|DECK.inp",
+          value_parser = crate::results::parse_yield_stress)]
+    pub yield_stress: Option<f64>,
+    /// Only export the results of this step
+    #[arg(long, value_name = "N")]
+    pub step: Option<i32>,
+}
+
+impl IntoOptions for Frd2VtuArgs {
+    type Options = Frd2VtuOptions;
+
+    fn into_options(self) -> Result<Frd2VtuOptions, String> {
+        Ok(Frd2VtuOptions {
+            input: self.input,
+            output: self.output,
+            binary: self.encoding.binary,
+            compressed: self.encoding.compressed,
+            modes: self.modes,
+            principal: self.principal,
+            envelope: self.envelope,
+            yield_stress: self.yield_stress,
+            step: self.step,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct Dat2VtuArgs {
+    #[arg(value_name = "INPUT.dat")]
+    pub dat: PathBuf,
+    /// Deck with the mesh the .dat file was written for
+    #[arg(value_name = "MESH.inp")]
+    pub deck: PathBuf,
+    #[arg(value_name = "OUTPUT.(vtu|pvd)")]
+    pub output: PathBuf,
+    #[command(flatten)]
+    pub encoding: VtuEncodingArgs,
+}
+
+impl IntoOptions for Dat2VtuArgs {
+    type Options = Dat2VtuOptions;
+
+    fn into_options(self) -> Result<Dat2VtuOptions, String> {
+        Ok(Dat2VtuOptions {
+            format: self.encoding.format(),
+            dat: self.dat,
+            deck: self.deck,
+            output: self.output,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct FrdDiffArgs {
+    #[arg(value_name = "REFERENCE.frd")]
+    pub reference: PathBuf,
+    #[arg(value_name = "RESULT.frd")]
+    pub result: PathBuf,
+    #[command(flatten)]
+    pub tolerances: ToleranceArgs,
+    /// Step to compare in both files
+    #[arg(long, value_name = "N")]
+    pub step: Option<i32>,
+}
+
+impl IntoOptions for FrdDiffArgs {
+    type Options = FrdDiffOptions;
+
+    fn into_options(self) -> Result<FrdDiffOptions, String> {
+        Ok(FrdDiffOptions {
+            reference: self.reference,
+            result: self.result,
+            tolerances: self.tolerances.apply(ccx_io::FrdTolerances::default()),
+            step: self.step,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct PathPlotArgs {
+    #[arg(value_name = "INPUT.frd")]
+    pub input: PathBuf,
+    #[arg(value_name = "OUTPUT.csv")]
+    pub output: PathBuf,
+    /// Path points; coordinates may be negative, so options go before the
+    /// points
+    #[arg(value_name = "X,Y,Z", num_args = 2.., required = true, allow_hyphen_values = true)]
+    pub points: Vec<String>,
+    /// Nodal dataset to sample
+    #[arg(long, value_name = "NAME", default_value = "STRESS")]
+    pub dataset: String,
+    /// Samples per polyline segment
+    #[arg(long, value_name = "N", default_value = "20", value_parser = positive)]
+    pub samples: usize,
+    /// Step to sample
+    #[arg(long, value_name = "N")]
+    pub step: Option<i32>,
+}
+
+impl IntoOptions for PathPlotArgs {
+    type Options = PathPlotOptions;
+
+    fn into_options(self) -> Result<PathPlotOptions, String> {
+        let polyline = self
+            .points
+            .iter()
+            .map(|point| {
+                if point.starts_with("--") {
+                    return Err(format!("option {point} must precede the path points"));
+                }
+                let coords: Vec<f64> = point
+                    .split(',')
+                    .map(|c| c.trim().parse::<f64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("invalid path point {point}"))?;
+                <[f64; 3]>::try_from(coords).map_err(|_| format!("path point {point} needs x,y,z"))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(PathPlotOptions {
+            input: self.input,
+            output: self.output,
+            dataset: self.dataset,
+            samples: self.samples,
+            step: self.step,
+            polyline,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ProbeArgs {
+    #[arg(value_name = "RESULTS.(frd|dat)")]
+    pub input: PathBuf,
+    /// Node to print the nodal results of; may be repeated
+    #[arg(long = "node", value_name = "ID", required_unless_present = "points")]
+    pub nodes: Vec<i32>,
+    /// Point to interpolate the results at; may be repeated
+    #[arg(long = "at", value_name = "X,Y,Z", allow_hyphen_values = true, value_parser = point)]
+    pub points: Vec<[f64; 3]>,
+    /// Deck with the mesh the .dat results were written for, needed by --at
+    #[arg(long, value_name = "MESH.inp")]
+    pub mesh: Option<PathBuf>,
+    /// Dataset to print, e.g. DISP or STRESS; may be repeated [default: all]
+    #[arg(long = "dataset", value_name = "NAME")]
+    pub datasets: Vec<String>,
+    /// Step to probe [default: the last one]
+    #[arg(long, value_name = "N")]
+    pub step: Option<i32>,
+    /// Print the values as JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl IntoOptions for ProbeArgs {
+    type Options = ProbeOptions;
+
+    fn into_options(self) -> Result<ProbeOptions, String> {
+        Ok(ProbeOptions {
+            input: self.input,
+            mesh: self.mesh,
+            nodes: self.nodes,
+            points: self.points,
+            datasets: self.datasets,
+            step: self.step,
+            json: self.json,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct FrfArgs {
+    #[arg(value_name = "INPUT.frd")]
+    pub input: PathBuf,
+    #[arg(value_name = "OUTPUT.csv")]
+    pub output: PathBuf,
+    /// Response node and 1-based component
+    #[arg(value_name = "NODE:DOF")]
+    pub response: String,
+    /// Harmonic dataset of the response and excitation
+    #[arg(long, value_name = "NAME", default_value = "DISP")]
+    pub dataset: String,
+    /// Reference DOF to divide the response by
+    #[arg(long, value_name = "NODE:DOF")]
+    pub excitation: Option<String>,
+}
+
+impl IntoOptions for FrfArgs {
+    type Options = FrfOptions;
+
+    fn into_options(self) -> Result<FrfOptions, String> {
+        let dataset = self.dataset.to_uppercase();
+        Ok(FrfOptions {
+            input: self.input,
+            output: self.output,
+            response: crate::results::parse_harmonic_dof(&self.response, &dataset)?,
+            excitation: self
+                .excitation
+                .map(|dof| crate::results::parse_harmonic_dof(&dof, &dataset))
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Surface (STL, OBJ, PLY) or Gmsh mesh
+    #[arg(value_name = "MESH.(stl|obj|ply|msh)")]
+    pub input: PathBuf,
+    #[arg(value_name = "OUTPUT.inp")]
+    pub output: PathBuf,
+    /// Import surface facets as membranes instead of shells
+    #[arg(long)]
+    pub membrane: bool,
+    /// Merge nodes closer than this distance
+    #[arg(long = "merge-nodes", value_name = "TOL", value_parser = non_negative)]
+    pub merge_tolerance: Option<f64>,
+    /// Convert linear elements to quadratic ones
+    #[arg(long)]
+    pub quadratic: bool,
+}
+
+impl IntoOptions for ImportArgs {
+    type Options = ImportOptions;
+
+    fn into_options(self) -> Result<ImportOptions, String> {
+        Ok(ImportOptions {
+            input: self.input,
+            output: self.output,
+            membrane: self.membrane,
+            merge_tolerance: self.merge_tolerance,
+            quadratic: self.quadratic,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct Op2FrdArgs {
+    #[arg(value_name = "INPUT.op2")]
+    pub input: PathBuf,
+    #[arg(value_name = "OUTPUT.frd")]
+    pub output: PathBuf,
+    /// Also convert element stresses
+    #[arg(long)]
+    pub element_stress: bool,
+}
+
+impl IntoOptions for Op2FrdArgs {
+    type Options = (PathBuf, PathBuf, bool);
+
+    fn into_options(self) -> Result<Self::Options, String> {
+        Ok((self.input, self.output, self.element_stress))
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ConvertArgs {
+    #[arg(value_name = "INPUT.(inp|msh|bdf|frd|stl|obj|ply)")]
+    pub input: PathBuf,
+    #[arg(value_name = "OUTPUT.(inp|bdf|frd|vtu|vtk|stl)")]
+    pub output: PathBuf,
+    /// Import surface facets as membranes instead of shells
+    #[arg(long)]
+    pub membrane: bool,
+    /// Result step of an FRD input to keep
+    #[arg(long, value_name = "N")]
+    pub step: Option<i32>,
+    #[command(flatten)]
+    pub encoding: VtuEncodingArgs,
+}
+
+impl IntoOptions for ConvertArgs {
+    type Options = ConvertOptions;
+
+    fn into_options(self) -> Result<ConvertOptions, String> {
+        Ok(ConvertOptions {
+            vtu_format: self.encoding.format(),
+            input: self.input,
+            output: self.output,
+            membrane: self.membrane,
+            step: self.step,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct PartitionArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Number of parts
+    #[arg(long, value_name = "N", value_parser = positive)]
+    pub parts: usize,
+    /// Write a mesh deck per part instead of a file of element sets
+    #[arg(long)]
+    pub decks: bool,
+    /// Directory for the written files [default: the deck's directory]
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+    /// Base name of the written files [default: the deck's file stem]
+    #[arg(long, value_name = "NAME", value_parser = job_name)]
+    pub job_name: Option<String>,
+    /// Print the statistics as JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl IntoOptions for PartitionArgs {
+    type Options = PartitionOptions;
+
+    fn into_options(self) -> Result<PartitionOptions, String> {
+        Ok(PartitionOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            parts: self.parts,
+            decks: self.decks,
+            output_dir: self.output_dir,
+            job_name: self.job_name,
+            json: self.json,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ExpandIncludesArgs {
+    #[command(flatten)]
+    pub deck: DeckArgs,
+    /// Flattened deck [default: standard output]
+    #[arg(value_name = "OUTPUT.inp")]
+    pub output: Option<PathBuf>,
+}
+
+impl IntoOptions for ExpandIncludesArgs {
+    type Options = ExpandIncludesOptions;
+
+    fn into_options(self) -> Result<ExpandIncludesOptions, String> {
+        Ok(ExpandIncludesOptions {
+            input: self.deck.input,
+            overrides: self.deck.overrides,
+            include_paths: self.deck.include_paths,
+            output: self.output,
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Print JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+    /// Append the timestamped counts of the report to FILE as one JSON line
+    #[arg(long, value_name = "FILE")]
+    pub history: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct HotspotsArgs {
+    /// Usage logs written by `solve --usage-log` or the compatibility
+    /// registry
+    #[arg(value_name = "USAGE.tsv", required = true)]
+    pub logs: Vec<PathBuf>,
+    /// Show the N most called routines only
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+    /// Keep routines whose legacy unit is already ported
+    #[arg(long)]
+    pub include_ported: bool,
+    /// Print JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    pub shell: clap_complete::Shell,
+}
+
+/// Completion script of `shell`
+pub fn completion_script(shell: clap_complete::Shell) -> String {
+    use clap::CommandFactory;
+
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "ccx-cli", &mut script);
+    String::from_utf8_lossy(&script).into_owned()
+}
+
+/// Parse the arguments following a subcommand name as the CLI would
+#[cfg(test)]
+pub(crate) fn parse_args<A>(args: &[String]) -> Result<A::Options, String>
+where
+    A: Args + clap::FromArgMatches + IntoOptions,
+{
+    let command = A::augment_args(clap::Command::new("ccx-cli").args_override_self(true));
+    let matches = command
+        .try_get_matches_from(std::iter::once("ccx-cli".to_string()).chain(args.iter().cloned()))
+        .map_err(|err| err.to_string())?;
+    A::from_arg_matches(&matches)
+        .map_err(|err| err.to_string())?
+        .into_options()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("ccx-cli").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn cli_definition_is_consistent() {
+        use clap::CommandFactory;
+
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn global_flags_select_log_filter() {
+        let filter = |args: &[&str]| parse(args).unwrap().global.log_filter();
+        assert_eq!(filter(&["solve", "a.inp"]), "info");
+        assert_eq!(filter(&["-v", "solve", "a.inp"]), "debug");
+        assert_eq!(filter(&["solve", "a.inp", "-vv"]), "trace");
+        assert_eq!(filter(&["-q", "solve", "a.inp"]), "error");
+        assert_eq!(
+            filter(&["solve", "--log-level", "warn", "a.inp", "-v"]),
+            "warn"
+        );
+        assert!(parse(&["solve", "a.inp", "--log-level"]).is_err());
+    }
+
+    #[test]
+    fn rejects_conflicting_and_unknown_flags() {
+        assert!(parse(&["frd2vtu", "--binary", "--compressed", "a.frd", "b.vtu"]).is_err());
+        assert!(parse(&["frd2vtu", "--binary"]).is_err());
+        assert!(parse(&["frd2vtu", "a.frd", "b.vtu", "c.vtu"]).is_err());
+        assert!(parse(&["--config", "ccx.toml", "--no-config", "solve", "a.inp"]).is_err());
+        assert!(parse(&["solve", "a.inp", "--bogus"]).is_err());
+        assert!(parse(&["solve", "a.inp", "--error-format", "xml"]).is_err());
+        // Later occurrences override earlier ones, e.g. ccx.toml defaults
+        let cli = parse(&["solve", "--backend", "cg", "a.inp", "--backend", "dense"]).unwrap();
+        let Command::Solve(solve) = cli.command else {
+            panic!("expected solve");
+        };
+        assert_eq!(solve.backend, Some(ccx_solver::LinearSolverKind::DenseLu));
+    }
+
+    #[test]
+    fn finds_subcommand_after_global_flags() {
+        let position = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            subcommand_position(&args)
+        };
+        assert_eq!(
+            position(&["ccx-cli", "-v", "--config", "ci.toml", "frd-diff"]),
+            Some(4)
+        );
+        assert_eq!(
+            position(&["ccx-cli", "--log-level", "solve", "solve"]),
+            Some(3)
+        );
+        assert_eq!(position(&["ccx-cli", "frobnicate", "solve"]), None);
+        assert_eq!(position(&["ccx-cli", "-q"]), None);
+    }
+
+    #[test]
+    fn completions_cover_subcommands() {
+        let bash = completion_script(clap_complete::Shell::Bash);
+        assert!(bash.contains("frd2vtu"));
+        assert!(bash.contains("--include-path"));
+        let fish = completion_script(clap_complete::Shell::Fish);
+        assert!(fish.contains("path-plot"));
+    }
+}
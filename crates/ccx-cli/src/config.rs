@@ -0,0 +1,317 @@
+//! Per-project defaults from a `ccx.toml` file.
+//!
+//! The CLI looks for `ccx.toml` in the working directory and its ancestors,
+//! or reads the file given with `--config`; `--no-config` skips it. Every
+//! setting becomes a command-line flag placed before the user's own
+//! arguments, so flags given on the command line override the file.
+//! Relative paths are resolved against the directory of the file.
+//!
+//! ```toml
+//! include-paths = ["meshes", "/opt/ccx/materials"]
+//! fixtures = "tests/fixtures/solver"
+//!
+//! [solve]
+//! backend = "pcg"
+//! formats = ["dat", "frd", "vtu"]
+//! output-dir = "results"
+//!
+//! [validate]
+//! atol = 1e-9
+//! rtol = 1e-4
+//! jobs = 4
+//! tolerances = { STRESS = [1e-6, 1e-3] }
+//!
+//! [frd-diff]
+//! rtol = 1e-5
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// File name searched for in the working directory and its ancestors
+pub const CONFIG_FILE: &str = "ccx.toml";
+
+/// Flags of the fixture commands taking a value, besides the
+/// command-specific ones
+const VALUE_FLAGS: [&str; 7] = [
+    "--json",
+    "--atol",
+    "--rtol",
+    "--tolerance",
+    "--log-level",
+    "--config",
+    "--error-format",
+];
+
+/// Settings of a `ccx.toml` file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProjectConfig {
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    /// Fixture tree of `validate`, `xvalidate` and `analyze-fixtures` when
+    /// none is given
+    pub fixtures: Option<PathBuf>,
+    pub solve: SolveConfig,
+    /// Tolerances and threads of `validate` and `xvalidate`
+    pub validate: ToleranceConfig,
+    pub frd_diff: ToleranceConfig,
+    /// Directory of the file, against which relative paths resolve
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// Defaults of `solve`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SolveConfig {
+    pub backend: Option<String>,
+    pub formats: Vec<String>,
+    pub output_dir: Option<PathBuf>,
+}
+
+/// Result comparison tolerances of `validate` and `frd-diff`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ToleranceConfig {
+    pub atol: Option<f64>,
+    pub rtol: Option<f64>,
+    /// Worker threads; `validate` only
+    pub jobs: Option<usize>,
+    /// Absolute and relative tolerance per dataset or quantity
+    pub tolerances: BTreeMap<String, [f64; 2]>,
+}
+
+impl ProjectConfig {
+    /// Parse the TOML text of a config file in `root`
+    pub fn parse(text: &str, root: &Path) -> Result<Self, String> {
+        let mut config: Self = toml::from_str(text).map_err(|err| err.to_string())?;
+        config.root = root.to_path_buf();
+        Ok(config)
+    }
+
+    /// Read a config file
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&text, root).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    /// Read the nearest `ccx.toml` in `dir` or its ancestors, if any
+    pub fn find(dir: &Path) -> Result<Option<Self>, String> {
+        match dir
+            .ancestors()
+            .map(|d| d.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Self::from_file(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// `path` relative to the directory of the config file
+    fn resolve(&self, path: &Path) -> String {
+        self.root.join(path).display().to_string()
+    }
+
+    /// Flags for `command` to place before the command-line arguments `args`
+    ///
+    /// The fixture tree is appended instead, and only when `args` give none.
+    pub fn default_args(&self, command: &str, args: &[String]) -> Vec<String> {
+        let mut defaults = Vec::new();
+        match command {
+            "check" | "solve" | "bench" | "modes" | "watch" | "expand-includes" | "partition" => {
+                for dir in &self.include_paths {
+                    defaults.extend(["--include-path".to_string(), self.resolve(dir)]);
+                }
+            }
+            _ => {}
+        }
+        match command {
+            "solve" => {
+                let solve = &self.solve;
+                if let Some(backend) = &solve.backend {
+                    defaults.extend(["--backend".to_string(), backend.clone()]);
+                }
+                if !solve.formats.is_empty() {
+                    defaults.extend(["--format".to_string(), solve.formats.join(",")]);
+                }
+                if let Some(dir) = &solve.output_dir {
+                    defaults.extend(["--output-dir".to_string(), self.resolve(dir)]);
+                }
+            }
+            "bench" | "modes" => {
+                if let Some(backend) = &self.solve.backend {
+                    defaults.extend(["--backend".to_string(), backend.clone()]);
+                }
+            }
+            "validate" => {
+                defaults.extend(self.validate.flags(true));
+                if let Some(fixtures) = self.fixtures_for(args, &["-j", "--jobs", "--junit"]) {
+                    return [defaults, args.to_vec(), vec![fixtures]].concat();
+                }
+            }
+            "xvalidate" => {
+                defaults.extend(self.validate.flags(true));
+                let value_flags = ["-j", "--jobs", "--ccx", "--timeout", "--keep-files"];
+                if let Some(fixtures) = self.fixtures_for(args, &value_flags) {
+                    return [defaults, args.to_vec(), vec![fixtures]].concat();
+                }
+            }
+            "analyze-fixtures" => {
+                if let Some(fixtures) = self.fixtures_for(args, &[]) {
+                    return vec![fixtures];
+                }
+            }
+            "frd-diff" => defaults.extend(self.frd_diff.flags(false)),
+            _ => {}
+        }
+        [defaults, args.to_vec()].concat()
+    }
+
+    /// The configured fixture tree, unless `args` name a positional one
+    ///
+    /// `value_flags` are the command's flags besides the tolerance and global
+    /// ones that take a value.
+    fn fixtures_for(&self, args: &[String], value_flags: &[&str]) -> Option<String> {
+        let fixtures = self.fixtures.as_ref()?;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if value_flags.contains(&arg.as_str()) || VALUE_FLAGS.contains(&arg.as_str()) {
+                iter.next();
+            } else if !arg.starts_with('-') {
+                return None;
+            }
+        }
+        Some(self.resolve(fixtures))
+    }
+}
+
+impl ToleranceConfig {
+    /// `--atol`, `--rtol`, `--tolerance` and, with `jobs`, `--jobs` flags
+    fn flags(&self, jobs: bool) -> Vec<String> {
+        let mut flags = Vec::new();
+        if let Some(atol) = self.atol {
+            flags.extend(["--atol".to_string(), atol.to_string()]);
+        }
+        if let Some(rtol) = self.rtol {
+            flags.extend(["--rtol".to_string(), rtol.to_string()]);
+        }
+        for (name, [atol, rtol]) in &self.tolerances {
+            flags.extend(["--tolerance".to_string(), format!("{name}={atol},{rtol}")]);
+        }
+        if let Some(n) = self.jobs.filter(|_| jobs) {
+            flags.extend(["--jobs".to_string(), n.to_string()]);
+        }
+        flags
+    }
+}
+
+/// Load the project config selected by `--config <file>` and
+/// `--no-config`
+///
+/// Without either, the nearest `ccx.toml` above the working directory is
+/// used when there is one.
+pub fn load(path: Option<&Path>, disabled: bool) -> Result<Option<ProjectConfig>, String> {
+    match (disabled, path) {
+        (true, _) => Ok(None),
+        (false, Some(path)) => ProjectConfig::from_file(path).map(Some),
+        (false, None) => match std::env::current_dir() {
+            Ok(dir) => ProjectConfig::find(&dir),
+            Err(_) => Ok(None),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+include-paths = ["meshes"]
+fixtures = "tests/fixtures"
+
+[solve]
+backend = "pcg"
+formats = ["dat", "vtu"]
+
+[validate]
+rtol = 1e-3
+jobs = 2
+tolerances = { STRESS = [0.0, 0.01] }
+
+[frd-diff]
+atol = 1e-6
+"#;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn default_args_precede_command_line() {
+        let config = ProjectConfig::parse(EXAMPLE, Path::new("/proj")).unwrap();
+        assert_eq!(
+            config.default_args("solve", &to_args(&["--backend", "dense", "a.inp"])),
+            to_args(&[
+                "--include-path",
+                "/proj/meshes",
+                "--backend",
+                "pcg",
+                "--format",
+                "dat,vtu",
+                "--backend",
+                "dense",
+                "a.inp",
+            ])
+        );
+        assert_eq!(
+            config.default_args("frd-diff", &to_args(&["a.frd", "b.frd"])),
+            to_args(&["--atol", "0.000001", "a.frd", "b.frd"])
+        );
+        assert_eq!(
+            config.default_args("mesh-info", &to_args(&["a.inp"])),
+            to_args(&["a.inp"])
+        );
+    }
+
+    #[test]
+    fn fixtures_fill_in_missing_tree() {
+        let config = ProjectConfig::parse(EXAMPLE, Path::new("/proj")).unwrap();
+        let validate = config.default_args("validate", &to_args(&["--json", "-"]));
+        assert_eq!(
+            validate,
+            to_args(&[
+                "--rtol",
+                "0.001",
+                "--tolerance",
+                "STRESS=0,0.01",
+                "--jobs",
+                "2",
+                "--json",
+                "-",
+                "/proj/tests/fixtures",
+            ])
+        );
+        let explicit = config.default_args("validate", &to_args(&["other"]));
+        assert_eq!(explicit.last().map(String::as_str), Some("other"));
+        let xvalidate = config.default_args("xvalidate", &to_args(&["--ccx", "/opt/ccx"]));
+        assert_eq!(
+            xvalidate.last().map(String::as_str),
+            Some("/proj/tests/fixtures")
+        );
+        assert_eq!(
+            config.default_args("analyze-fixtures", &[]),
+            to_args(&["/proj/tests/fixtures"])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        let err = ProjectConfig::parse("[solve]\nsolver = \"pcg\"\n", Path::new(".")).unwrap_err();
+        assert!(err.contains("unknown field `solver`"), "{err}");
+    }
+}
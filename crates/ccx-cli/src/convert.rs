@@ -0,0 +1,757 @@
+//! Mesh and deck conversions of `import`, `convert`, `partition` and
+//! `expand-includes`.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::CliError;
+use crate::results::select_frd_step;
+use crate::solve::job_location;
+use crate::{print_json, read_deck};
+
+pub struct ImportOptions {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub membrane: bool,
+    /// Merge nodes closer than this distance
+    pub merge_tolerance: Option<f64>,
+    /// Convert linear elements to quadratic ones
+    pub quadratic: bool,
+}
+
+pub fn import_mesh_file(options: &ImportOptions) -> Result<(), CliError> {
+    use ccx_io::{SurfaceElement, read_gmsh, read_surface};
+
+    let (input_path, output_path) = (options.input.as_path(), options.output.as_path());
+
+    if !output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("inp"))
+    {
+        return Err(CliError::usage("Output file must have .inp extension"));
+    }
+
+    tracing::info!("Reading mesh: {}", input_path.display());
+    let is_gmsh = input_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("msh"));
+    let (mut mesh, mut sets) = if is_gmsh {
+        let gmsh = read_gmsh(input_path)
+            .map_err(|err| CliError::read(&err, format!("Failed to read mesh: {err}")))?;
+        (gmsh.mesh, Some(gmsh.sets))
+    } else {
+        let element = if options.membrane {
+            SurfaceElement::Membrane
+        } else {
+            SurfaceElement::Shell
+        };
+        let mesh = read_surface(input_path, element)
+            .map_err(|err| CliError::read(&err, format!("Failed to read mesh: {err}")))?;
+        (mesh, None)
+    };
+
+    tracing::info!("Nodes: {}", mesh.nodes.len());
+    tracing::info!("Elements: {}", mesh.elements.len());
+
+    if let Some(tolerance) = options.merge_tolerance {
+        let merged = mesh.merge_duplicate_nodes(tolerance)?;
+        if let Some(sets) = sets.as_mut() {
+            sets.apply_node_map(&merged);
+        }
+        tracing::info!(
+            "Merged {} duplicate nodes (tolerance {:e}), {} nodes left",
+            merged.len(),
+            tolerance,
+            mesh.nodes.len()
+        );
+    }
+    if options.quadratic {
+        let midside = mesh.convert_to_quadratic()?;
+        tracing::info!(
+            "Converted to quadratic elements: {} mid-side nodes, {} nodes",
+            midside.len(),
+            mesh.nodes.len()
+        );
+    }
+
+    tracing::info!("Writing input deck: {}", output_path.display());
+    mesh_to_deck(&mesh, sets.as_ref())
+        .write_file(output_path)
+        .map_err(|err| CliError::io(format!("Failed to write input deck: {err}")))?;
+
+    tracing::info!("Import complete");
+    Ok(())
+}
+
+/// File formats of the `convert` command, chosen by extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConvertFormat {
+    Inp,
+    Msh,
+    Bdf,
+    Frd,
+    Vtu,
+    Vtk,
+    Surface(ccx_io::SurfaceFormat),
+}
+
+impl ConvertFormat {
+    fn from_path(path: &Path) -> Result<Self, String> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        match ext.as_str() {
+            "inp" => Ok(Self::Inp),
+            "msh" => Ok(Self::Msh),
+            "bdf" | "nas" => Ok(Self::Bdf),
+            "frd" => Ok(Self::Frd),
+            "vtu" => Ok(Self::Vtu),
+            "vtk" => Ok(Self::Vtk),
+            _ => ccx_io::SurfaceFormat::from_path(path)
+                .map(Self::Surface)
+                .ok_or_else(|| format!("unsupported file format {}", path.display())),
+        }
+    }
+}
+
+/// Options of the `convert` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertOptions {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// Import surface facets as membranes instead of shells
+    pub membrane: bool,
+    /// Result step of an FRD input to keep
+    pub step: Option<i32>,
+    /// Encoding of VTU output
+    pub vtu_format: ccx_io::VtkFormat,
+}
+
+/// Model read by the `convert` command
+enum ConvertModel {
+    Deck(ccx_inp::Deck),
+    Mesh(ccx_solver::Mesh, Option<ccx_solver::Sets>),
+    Results(ccx_io::FrdFile),
+}
+
+impl ConvertModel {
+    fn read(options: &ConvertOptions) -> Result<Self, CliError> {
+        let path = options.input.as_path();
+        let read_error = |err: std::io::Error| {
+            CliError::read(&err, format!("Failed to read {}: {err}", path.display()))
+        };
+        let format = ConvertFormat::from_path(path).map_err(CliError::usage)?;
+        if options.step.is_some() && format != ConvertFormat::Frd {
+            return Err(CliError::usage("--step only applies to .frd input"));
+        }
+        let model = match format {
+            ConvertFormat::Inp => {
+                Self::Deck(read_deck(path, &[], &[]).map_err(|err| CliError::deck(path, &err))?)
+            }
+            ConvertFormat::Msh => {
+                let gmsh = ccx_io::read_gmsh(path).map_err(read_error)?;
+                Self::Mesh(gmsh.mesh, Some(gmsh.sets))
+            }
+            ConvertFormat::Bdf => {
+                let bdf = ccx_io::nastran::read_bdf(path).map_err(read_error)?;
+                Self::Mesh(bdf.mesh, Some(bdf.sets))
+            }
+            ConvertFormat::Frd => {
+                let frd = ccx_io::FrdFile::from_file(path).map_err(read_error)?;
+                Self::Results(select_frd_step(frd, options.step).map_err(CliError::usage)?)
+            }
+            ConvertFormat::Surface(_) => {
+                let element = if options.membrane {
+                    ccx_io::SurfaceElement::Membrane
+                } else {
+                    ccx_io::SurfaceElement::Shell
+                };
+                Self::Mesh(
+                    ccx_io::read_surface(path, element).map_err(read_error)?,
+                    None,
+                )
+            }
+            ConvertFormat::Vtu | ConvertFormat::Vtk => {
+                return Err(CliError::usage(format!(
+                    "no reader for {} files",
+                    path.display()
+                )));
+            }
+        };
+        Ok(model)
+    }
+
+    /// Solver mesh and sets of the model
+    fn mesh(&self) -> Result<(ccx_solver::Mesh, Option<ccx_solver::Sets>), CliError> {
+        match self {
+            Self::Deck(deck) => {
+                let mesh =
+                    ccx_solver::MeshBuilder::build_from_deck(deck).map_err(CliError::parse)?;
+                let mut sets = ccx_solver::Sets::build_from_deck(deck).map_err(CliError::parse)?;
+                sets.add_card_sets(deck).map_err(CliError::parse)?;
+                Ok((mesh, Some(sets)))
+            }
+            Self::Mesh(mesh, sets) => Ok((mesh.clone(), sets.clone())),
+            Self::Results(frd) => {
+                let (mesh, skipped) = ccx_io::frd_mesh(frd);
+                if !skipped.is_empty() {
+                    tracing::warn!("Skipped {} elements of unsupported types", skipped.len());
+                }
+                Ok((mesh, None))
+            }
+        }
+    }
+
+    /// Input deck of the model; everything but the mesh and sets is lost
+    /// unless the model was read from a deck
+    fn deck(&self) -> Result<ccx_inp::Deck, CliError> {
+        match self {
+            Self::Deck(deck) => Ok(deck.clone()),
+            _ => {
+                let (mesh, sets) = self.mesh()?;
+                Ok(mesh_to_deck(&mesh, sets.as_ref()))
+            }
+        }
+    }
+
+    /// FRD model with the results of an FRD input
+    fn frd(&self, job_name: &str) -> Result<ccx_io::FrdFile, CliError> {
+        match self {
+            Self::Results(frd) => Ok(frd.clone()),
+            _ => Ok(ccx_io::mesh_frd(&self.mesh()?.0, job_name)),
+        }
+    }
+}
+
+/// Convert a mesh or result file to the format given by the output extension
+pub fn convert_file(options: &ConvertOptions) -> Result<(), CliError> {
+    let output = options.output.as_path();
+    let format = ConvertFormat::from_path(output).map_err(CliError::usage)?;
+    let write_error = |err: std::io::Error| {
+        CliError::write(&err, format!("Failed to write {}: {err}", output.display()))
+    };
+    let job_name = output.file_stem().and_then(|s| s.to_str()).unwrap_or("job");
+
+    tracing::info!("Reading: {}", options.input.display());
+    let model = ConvertModel::read(options)?;
+    tracing::info!("Writing: {}", output.display());
+    match format {
+        ConvertFormat::Inp => model.deck()?.write_file(output).map_err(write_error)?,
+        ConvertFormat::Bdf => {
+            ccx_io::nastran::write_bdf(&model.deck()?, output).map_err(write_error)?
+        }
+        ConvertFormat::Frd => {
+            ccx_io::write_frd(output, &model.frd(job_name)?).map_err(write_error)?
+        }
+        ConvertFormat::Vtu => ccx_io::VtkWriter::new(&model.frd(job_name)?)
+            .write_vtu(output, options.vtu_format)
+            .map_err(write_error)?,
+        ConvertFormat::Vtk => ccx_io::VtkWriter::new(&model.frd(job_name)?)
+            .write_vtk(output)
+            .map_err(write_error)?,
+        ConvertFormat::Surface(ccx_io::SurfaceFormat::Stl) => {
+            ccx_io::write_stl(output, &model.mesh()?.0).map_err(write_error)?
+        }
+        ConvertFormat::Msh | ConvertFormat::Surface(_) => {
+            return Err(CliError::usage(format!(
+                "no writer for {} files",
+                output.display()
+            )));
+        }
+    }
+    tracing::info!("Conversion complete");
+    Ok(())
+}
+
+/// `*NODE`, `*ELEMENT` (one card per element type, all in `EALL`) and set cards
+fn mesh_to_deck(mesh: &ccx_solver::Mesh, sets: Option<&ccx_solver::Sets>) -> ccx_inp::Deck {
+    use ccx_inp::{Card, Deck, Parameter};
+    use std::collections::BTreeMap;
+
+    let param = |key: &str, value: &str| Parameter {
+        key: key.to_string(),
+        value: Some(value.to_string()),
+    };
+
+    let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+    node_ids.sort_unstable();
+    let node_lines = node_ids
+        .iter()
+        .map(|id| {
+            let node = &mesh.nodes[id];
+            format!("{}, {:e}, {:e}, {:e}", id, node.x, node.y, node.z)
+        })
+        .collect();
+    let mut cards = vec![Card::new("NODE", vec![param("NSET", "NALL")], node_lines)];
+
+    let mut by_type: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for element in mesh.elements.values() {
+        by_type
+            .entry(format!("{:?}", element.element_type))
+            .or_default()
+            .push(element.id);
+    }
+    for (element_type, mut ids) in by_type {
+        ids.sort_unstable();
+        let lines = ids
+            .iter()
+            .map(|id| {
+                let nodes: Vec<String> =
+                    mesh.elements[id].nodes.iter().map(i32::to_string).collect();
+                format!("{}, {}", id, nodes.join(", "))
+            })
+            .collect();
+        cards.push(Card::new(
+            "ELEMENT",
+            vec![param("TYPE", &element_type), param("ELSET", "EALL")],
+            lines,
+        ));
+    }
+
+    if let Some(sets) = sets {
+        cards.extend(set_cards(sets));
+    }
+
+    Deck {
+        cards,
+        trailing_trivia: Vec::new(),
+    }
+}
+
+/// `*NSET` and `*ELSET` cards of `sets`, sorted by name
+fn set_cards(sets: &ccx_solver::Sets) -> Vec<ccx_inp::Card> {
+    use ccx_inp::{Card, Parameter};
+
+    let param = |key: &str, value: &str| Parameter {
+        key: key.to_string(),
+        value: Some(value.to_string()),
+    };
+    let id_lines = |ids: &[i32]| -> Vec<String> {
+        ids.chunks(16)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(i32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect()
+    };
+    let mut cards = Vec::new();
+    let mut node_sets: Vec<_> = sets.node_sets.values().collect();
+    node_sets.sort_by(|a, b| a.name.cmp(&b.name));
+    for set in node_sets {
+        cards.push(Card::new(
+            "NSET",
+            vec![param("NSET", &set.name)],
+            id_lines(&set.nodes),
+        ));
+    }
+    let mut element_sets: Vec<_> = sets.element_sets.values().collect();
+    element_sets.sort_by(|a, b| a.name.cmp(&b.name));
+    for set in element_sets {
+        cards.push(Card::new(
+            "ELSET",
+            vec![param("ELSET", &set.name)],
+            id_lines(&set.elements),
+        ));
+    }
+    cards
+}
+
+/// Options of the `partition` command
+pub struct PartitionOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    pub parts: usize,
+    /// Write one mesh deck per part instead of the element sets
+    pub decks: bool,
+    /// Directory for the written files; the input's directory when absent
+    pub output_dir: Option<PathBuf>,
+    /// Base name of the written files; the input's file stem when absent
+    pub job_name: Option<String>,
+    /// Print the statistics as JSON instead of text
+    pub json: bool,
+}
+
+/// Split the mesh of a deck into parts and write `<job>_parts.inp`, with an
+/// element set `PART<n>` per part and the node set `INTERFACE`, or with
+/// `decks` a mesh deck `<job>_part<n>.inp` per part
+pub fn partition_file(options: &PartitionOptions) -> Result<(), CliError> {
+    use ccx_solver::{ElementSet, NodeSet, Sets};
+
+    let path = options.input.as_path();
+    let deck = read_deck(path, &options.overrides, &options.include_paths)
+        .map_err(|err| CliError::deck(path, &err))?;
+    let mesh = ccx_solver::MeshBuilder::build_from_deck(&deck)?;
+    let partition = ccx_solver::partition_mesh(&mesh, options.parts).map_err(CliError::usage)?;
+
+    let (dir, job_name) = job_location(
+        path,
+        options.output_dir.as_deref(),
+        options.job_name.as_deref(),
+    );
+    if !dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|err| CliError::io(format!("Failed to create {}: {}", dir.display(), err)))?;
+    }
+    let write = |name: String, deck: ccx_inp::Deck| -> Result<PathBuf, CliError> {
+        let out = dir.join(name);
+        deck.write_file(&out)
+            .map_err(|err| CliError::io(format!("Failed to write {}: {}", out.display(), err)))?;
+        tracing::info!("Wrote {}", out.display());
+        Ok(out)
+    };
+    let interface = |nodes: Vec<i32>| NodeSet {
+        name: "INTERFACE".to_string(),
+        nodes,
+    };
+
+    let mut written = Vec::new();
+    if options.decks {
+        for (i, part) in partition.parts.iter().enumerate() {
+            let mut part_mesh = ccx_solver::Mesh::new();
+            for id in &part.nodes {
+                if let Some(node) = mesh.nodes.get(id) {
+                    part_mesh.add_node(node.clone());
+                }
+            }
+            for id in &part.elements {
+                part_mesh.add_element(mesh.elements[id].clone())?;
+            }
+            let mut sets = Sets::new();
+            sets.add_node_set(interface(part.interface_nodes.clone()));
+            let part_deck = mesh_to_deck(&part_mesh, Some(&sets));
+            written.push(write(format!("{}_part{}.inp", job_name, i + 1), part_deck)?);
+        }
+    } else {
+        let mut sets = Sets::new();
+        for (i, part) in partition.parts.iter().enumerate() {
+            sets.add_element_set(ElementSet {
+                name: format!("PART{}", i + 1),
+                elements: part.elements.clone(),
+            });
+        }
+        sets.add_node_set(interface(partition.interface_nodes().into_iter().collect()));
+        let sets_deck = ccx_inp::Deck {
+            cards: set_cards(&sets),
+            trailing_trivia: Vec::new(),
+        };
+        written.push(write(format!("{}_parts.inp", job_name), sets_deck)?);
+    }
+
+    if options.json {
+        print_json(&partition_json(path, &partition, &written)).map_err(CliError::io)?;
+    } else {
+        println!(
+            "Partition of {} into {} parts",
+            path.display(),
+            options.parts
+        );
+        print!("{}", format_partition(&partition));
+    }
+    Ok(())
+}
+
+/// Element, node and interface node counts of every part
+fn format_partition(partition: &ccx_solver::MeshPartition) -> String {
+    let mut text = format!(
+        "{:>4} {:>10} {:>10} {:>10}\n",
+        "part", "elements", "nodes", "interface"
+    );
+    for (i, part) in partition.parts.iter().enumerate() {
+        text.push_str(&format!(
+            "{:>4} {:>10} {:>10} {:>10}\n",
+            i + 1,
+            part.elements.len(),
+            part.nodes.len(),
+            part.interface_nodes.len()
+        ));
+    }
+    text.push_str(&format!(
+        "Interface nodes: {}\nImbalance: {:.3}\n",
+        partition.interface_nodes().len(),
+        partition.imbalance()
+    ));
+    text
+}
+
+/// Statistics of `partition --json`
+fn partition_json(
+    path: &Path,
+    partition: &ccx_solver::MeshPartition,
+    written: &[PathBuf],
+) -> serde_json::Value {
+    let parts: Vec<serde_json::Value> = partition
+        .parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            serde_json::json!({
+                "part": i + 1,
+                "elements": part.elements.len(),
+                "nodes": part.nodes.len(),
+                "interface_nodes": part.interface_nodes.len(),
+            })
+        })
+        .collect();
+    let outputs: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+    serde_json::json!({
+        "input": path.display().to_string(),
+        "parts": parts,
+        "interface_nodes": partition.interface_nodes().len(),
+        "imbalance": partition.imbalance(),
+        "outputs": outputs,
+    })
+}
+
+/// Options of the `expand-includes` command
+pub struct ExpandIncludesOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    /// Flattened deck; standard output when absent
+    pub output: Option<PathBuf>,
+}
+
+/// Write the deck with its includes expanded and its parameters resolved as
+/// a single self-contained deck
+pub fn expand_includes_file(options: &ExpandIncludesOptions) -> Result<(), CliError> {
+    let path = options.input.as_path();
+    let deck_error = |err: ccx_inp::ParseError| CliError::deck(path, &err);
+    let mut deck = ccx_inp::Deck::parse_file_with_include_paths(path, &options.include_paths)
+        .map_err(deck_error)?;
+    let mut table = ccx_inp::ParameterTable::with_overrides(options.overrides.iter().cloned());
+    deck.apply_parameters(&mut table).map_err(deck_error)?;
+    deck.flatten(&table);
+    match &options.output {
+        Some(output) => {
+            deck.write_file(output).map_err(|err| {
+                CliError::io(format!("Failed to write {}: {}", output.display(), err))
+            })?;
+            tracing::info!(
+                "Wrote {} ({} cards, {} includes expanded)",
+                output.display(),
+                deck.cards.len(),
+                ccx_inp::Deck::include_files(path, &options.include_paths).len()
+            );
+        }
+        None => print!("{}", deck.to_inp_string()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::analyze_file;
+    use crate::cli::{ConvertArgs, ExpandIncludesArgs, ImportArgs, PartitionArgs, parse_args};
+    use crate::error::ErrorKind;
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn convert_routes_by_extension() {
+        let root = unique_temp_dir("ccx_cli_convert");
+        fs::create_dir_all(&root).expect("create temp dir");
+        fs::write(
+            root.join("cube.inp"),
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,1,0\n4,0,1,0\n5,0,0,1\n6,1,0,1\n7,1,1,1\n8,0,1,1\n\
+             *ELEMENT,TYPE=C3D8,ELSET=EALL\n1,1,2,3,4,5,6,7,8\n*NSET,NSET=BASE\n1,2,3,4\n",
+        )
+        .expect("write deck");
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let convert = |input: &str, output: &str| {
+            let args = [root.join(input), root.join(output)]
+                .map(|p| p.display().to_string())
+                .to_vec();
+            convert_file(&parse_args::<ConvertArgs>(&args)?)
+        };
+
+        convert("cube.inp", "cube.frd").unwrap();
+        convert("cube.frd", "cube.vtu").unwrap();
+        convert("cube.inp", "cube.stl").unwrap();
+        convert("cube.stl", "skin.inp").unwrap();
+        let frd = ccx_io::FrdFile::from_file(root.join("cube.frd")).unwrap();
+        assert_eq!((frd.nodes.len(), frd.elements.len()), (8, 1));
+        assert!(
+            fs::read_to_string(root.join("cube.vtu"))
+                .unwrap()
+                .contains("NumberOfCells=\"1\"")
+        );
+        let skin = ccx_inp::Deck::parse_file(root.join("skin.inp")).unwrap();
+        assert_eq!(skin.cards[1].keyword, "ELEMENT");
+        assert_eq!(skin.cards[1].data_lines.len(), 12);
+
+        assert_eq!(
+            convert("cube.inp", "cube.msh"),
+            Err(CliError::usage(format!(
+                "no writer for {} files",
+                root.join("cube.msh").display()
+            )))
+        );
+        let options = parse_args::<ConvertArgs>(&to_args(&["--step", "2", "a.inp", "b.vtu"]));
+        let err = convert_file(&options.unwrap()).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Usage);
+        assert!(err.message.contains("--step only applies"));
+
+        // *PARAMETERs are resolved before the deck is translated
+        fs::write(
+            root.join("loaded.inp"),
+            "*PARAMETER\nload=250.\n*NODE\n1,0,0,0\n2,1,0,0\n\
+             *ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n*STEP\n*STATIC\n\
+             *CLOAD\n2,1,<load>\n*END STEP\n",
+        )
+        .expect("write deck");
+        convert("loaded.inp", "loaded.bdf").unwrap();
+        let bdf = fs::read_to_string(root.join("loaded.bdf")).unwrap();
+        assert!(bdf.contains("FORCE") && bdf.contains("250."), "{bdf}");
+    }
+
+    #[test]
+    fn expand_includes_writes_self_contained_deck() {
+        let root = unique_temp_dir("ccx_cli_expand_includes");
+        let lib = root.join("lib");
+        fs::create_dir_all(&lib).expect("create temp dir");
+        let deck = root.join("root.inp");
+        fs::write(
+            &deck,
+            "*PARAMETER\nlength=1\n*INCLUDE,INPUT=mesh.inc\n*BOUNDARY\n1,1,3\n",
+        )
+        .expect("write root deck");
+        fs::write(lib.join("mesh.inc"), "*NODE\n1,0,0,0\n2,<length>,0,0\n").expect("write include");
+
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let flat = root.join("flat.inp");
+        let (deck_arg, lib_arg, flat_arg) = (
+            deck.display().to_string(),
+            lib.display().to_string(),
+            flat.display().to_string(),
+        );
+        let args = to_args(&["-I", &lib_arg, "-p", "length=2.5", &deck_arg, &flat_arg]);
+        let options = parse_args::<ExpandIncludesArgs>(&args).unwrap();
+        expand_includes_file(&options).unwrap();
+
+        // The flattened deck parses on its own, without include paths
+        let flattened = ccx_inp::Deck::parse_file_with_includes(&flat).unwrap();
+        let keywords: Vec<&str> = flattened.cards.iter().map(|c| c.keyword.as_str()).collect();
+        assert_eq!(keywords, ["NODE", "BOUNDARY"]);
+        assert_eq!(flattened.cards[0].data_lines[1], "2,2.5,0,0");
+        let text = fs::read_to_string(&flat).unwrap();
+        assert!(text.contains("** length=2.5\n"), "{text}");
+    }
+
+    #[test]
+    fn partition_writes_element_sets_or_part_decks() {
+        let root = unique_temp_dir("ccx_cli_partition");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let brick = ccx_solver::MeshBuilder::brick(
+            [4.0, 1.0, 1.0],
+            [
+                ccx_solver::Division::uniform(8),
+                ccx_solver::Division::uniform(1),
+                ccx_solver::Division::uniform(1),
+            ],
+            ccx_solver::ElementType::C3D8,
+        )
+        .unwrap();
+        let deck = root.join("bar.inp");
+        mesh_to_deck(&brick.mesh, None)
+            .write_file(&deck)
+            .expect("write deck");
+
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let deck_arg = deck.display().to_string();
+        assert!(parse_args::<PartitionArgs>(&to_args(&[&deck_arg])).is_err());
+        let options = parse_args::<PartitionArgs>(&to_args(&["--parts", "2", &deck_arg])).unwrap();
+        partition_file(&options).unwrap();
+        let sets = ccx_inp::Deck::parse_file(root.join("bar_parts.inp")).unwrap();
+        let names: Vec<String> = sets.cards.iter().map(|c| c.canonical_header()).collect();
+        assert_eq!(
+            names,
+            [
+                "*NSET, NSET=INTERFACE",
+                "*ELSET, ELSET=PART1",
+                "*ELSET, ELSET=PART2"
+            ]
+        );
+        // The nodes at x = 2
+        assert_eq!(sets.cards[0].data_lines, ["5, 14, 23, 32"]);
+
+        let args = to_args(&["--parts", "2", "--decks", "--job-name", "split", &deck_arg]);
+        partition_file(&parse_args::<PartitionArgs>(&args).unwrap()).unwrap();
+        let part = ccx_inp::Deck::parse_file(root.join("split_part2.inp")).unwrap();
+        let mesh = ccx_solver::MeshBuilder::build_from_deck(&part).unwrap();
+        assert_eq!((mesh.elements.len(), mesh.nodes.len()), (4, 20));
+
+        let too_many = to_args(&["--parts", "9", &deck_arg]);
+        let err = partition_file(&parse_args::<PartitionArgs>(&too_many).unwrap()).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Usage);
+    }
+
+    #[test]
+    fn import_surface_writes_parsable_deck() {
+        let root = unique_temp_dir("ccx_cli_import");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let obj = root.join("plate.obj");
+        let inp = root.join("plate.inp");
+        fs::write(&obj, "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n").expect("write obj");
+
+        let options = ImportOptions {
+            input: obj,
+            output: inp.clone(),
+            membrane: false,
+            merge_tolerance: None,
+            quadratic: false,
+        };
+        import_mesh_file(&options).expect("import should succeed");
+        let summary = analyze_file(&inp).expect("deck should parse");
+        assert_eq!(summary.node_rows, 4);
+        assert_eq!(summary.element_rows, 1);
+        let deck = ccx_inp::Deck::parse_file(&inp).expect("parse deck");
+        assert_eq!(deck.cards[1].parameters[0].value.as_deref(), Some("S4"));
+
+        let args: Vec<String> = vec!["--membrane".into(), "a.stl".into(), "a.inp".into()];
+        let options = parse_args::<ImportArgs>(&args).expect("valid arguments");
+        assert!(options.membrane);
+        assert_eq!(options.merge_tolerance, None);
+        assert!(!options.quadratic);
+    }
+
+    #[test]
+    fn import_merges_duplicate_nodes() {
+        let root = unique_temp_dir("ccx_cli_import_merge");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let obj = root.join("strip.obj");
+        let inp = root.join("strip.inp");
+        // Two quads with their own vertices along the shared edge x = 1
+        let vertices = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 1 0 0\nv 2 0 0\nv 2 1 0\nv 1 1 0\n";
+        fs::write(&obj, format!("{vertices}f 1 2 3 4\nf 5 6 7 8\n")).expect("write obj");
+
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options =
+            parse_args::<ImportArgs>(&to_args(&["--merge-nodes", "1e-6", "a", "b"])).unwrap();
+        assert_eq!(options.merge_tolerance, Some(1e-6));
+        assert!(parse_args::<ImportArgs>(&to_args(&["--merge-nodes", "-1", "a", "b"])).is_err());
+
+        let merge = to_args(&["--merge-nodes", "1e-6", "x", "y"]);
+        let mut options = parse_args::<ImportArgs>(&merge).unwrap();
+        (options.input, options.output) = (obj, inp.clone());
+        import_mesh_file(&options).expect("import should succeed");
+        let summary = analyze_file(&inp).expect("deck should parse");
+        assert_eq!(summary.node_rows, 6);
+        assert_eq!(summary.element_rows, 2);
+
+        // 7 distinct edges of the merged strip get one mid-side node each
+        options.quadratic = true;
+        import_mesh_file(&options).expect("import should succeed");
+        let summary = analyze_file(&inp).expect("deck should parse");
+        assert_eq!(summary.node_rows, 13);
+        let deck = ccx_inp::Deck::parse_file(&inp).expect("parse deck");
+        assert_eq!(deck.cards[1].parameters[0].value.as_deref(), Some("S8"));
+    }
+}
@@ -0,0 +1,269 @@
+//! Exit codes and error reports of the commands.
+//!
+//! Every failure carries an [`ErrorKind`] that selects the process exit
+//! code, so wrappers can branch on the kind of failure. With
+//! `--error-format json` the error is written to stderr as a single line
+//!
+//! ```json
+//! {"error":{"command":"solve","kind":"solver","code":6,"message":"..."}}
+//! ```
+//!
+//! instead of the `<command> error: <message>` text.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Class of a failure, selecting the exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Any failure not covered by the other kinds
+    Failure,
+    /// Invalid command-line arguments or configuration
+    Usage,
+    /// An input file could not be parsed
+    Parse,
+    /// The model, fixtures or results failed a check or comparison
+    Validation,
+    /// The element or global matrices could not be assembled
+    Assembly,
+    /// The linear solver failed or did not converge
+    Solver,
+    /// A file could not be read or written
+    Io,
+}
+
+impl ErrorKind {
+    /// Process exit code
+    pub fn code(self) -> u8 {
+        match self {
+            ErrorKind::Failure => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::Parse => 3,
+            ErrorKind::Validation => 4,
+            ErrorKind::Assembly => 5,
+            ErrorKind::Solver => 6,
+            ErrorKind::Io => 7,
+        }
+    }
+
+    /// Name in the JSON error envelope
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Failure => "failure",
+            ErrorKind::Usage => "usage",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Validation => "validation",
+            ErrorKind::Assembly => "assembly",
+            ErrorKind::Solver => "solver",
+            ErrorKind::Io => "io",
+        }
+    }
+}
+
+/// Failure of a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Usage, message)
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Io, message)
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Parse, message)
+    }
+
+    /// Error of reading or parsing the deck at `path`: an I/O error when the
+    /// deck or one of its includes could not be read, a parse error otherwise
+    pub fn deck(path: &Path, error: &ccx_inp::ParseError) -> Self {
+        let kind = match error.io_error {
+            Some(_) => ErrorKind::Io,
+            None => ErrorKind::Parse,
+        };
+        Self::new(kind, format!("{}: {error}", path.display()))
+    }
+
+    /// Error of reading a result or mesh file: a parse error when its
+    /// content is invalid, an I/O error otherwise
+    pub fn read(error: &std::io::Error, message: impl Into<String>) -> Self {
+        let kind = match error.kind() {
+            std::io::ErrorKind::InvalidData => ErrorKind::Parse,
+            _ => ErrorKind::Io,
+        };
+        Self::new(kind, message)
+    }
+
+    /// Error of writing a converted model: a parse error when part of the
+    /// model could not be translated, a validation error when the target
+    /// format cannot hold it, an I/O error otherwise
+    pub fn write(error: &std::io::Error, message: impl Into<String>) -> Self {
+        let kind = match error.kind() {
+            std::io::ErrorKind::InvalidData => ErrorKind::Parse,
+            std::io::ErrorKind::InvalidInput => ErrorKind::Validation,
+            _ => ErrorKind::Io,
+        };
+        Self::new(kind, message)
+    }
+
+    /// Error of a static solve that stopped at `failure`
+    pub fn solve(failure: &ccx_solver::SolveFailure) -> Self {
+        let kind = match failure {
+            ccx_solver::SolveFailure::InvertedElements(_) => ErrorKind::Validation,
+            ccx_solver::SolveFailure::Assembly(_) => ErrorKind::Assembly,
+            ccx_solver::SolveFailure::Solve(_) => ErrorKind::Solver,
+            ccx_solver::SolveFailure::StressRecovery(_) => ErrorKind::Failure,
+        };
+        Self::new(kind, failure.to_string())
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(self.kind.code())
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorKind::Failure, message)
+    }
+}
+
+/// How errors are written to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// `<command> error: <message>`
+    #[default]
+    Text,
+    /// A single-line JSON error envelope
+    Json,
+}
+
+impl ErrorFormat {
+    /// Format selected by `--error-format` in the raw command line `args`
+    ///
+    /// Scanned before the arguments are parsed, so that errors of the parsing
+    /// itself are reported in the requested format.
+    pub fn from_args(args: &[String]) -> Self {
+        let mut format = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let value = match arg.strip_prefix("--error-format") {
+                Some("") => iter.next().map(String::as_str),
+                Some(rest) => rest.strip_prefix('='),
+                None => continue,
+            };
+            if let Some(Ok(selected)) = value.map(|v| clap::ValueEnum::from_str(v, false)) {
+                format = selected;
+            }
+        }
+        format
+    }
+
+    /// Write `error` of `command` to stderr; `command` is empty when the
+    /// error precedes the command
+    pub fn report(self, command: &str, error: &CliError) {
+        match self {
+            ErrorFormat::Text if command.is_empty() => eprintln!("error: {}", error.message),
+            ErrorFormat::Text => eprintln!("{command} error: {}", error.message),
+            ErrorFormat::Json => eprintln!("{}", envelope(command, error)),
+        }
+    }
+}
+
+/// JSON error envelope of `error`
+pub fn envelope(command: &str, error: &CliError) -> serde_json::Value {
+    serde_json::json!({
+        "error": {
+            "command": command,
+            "kind": error.kind.name(),
+            "code": error.kind.code(),
+            "message": error.message,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_have_distinct_codes() {
+        let kinds = [
+            ErrorKind::Failure,
+            ErrorKind::Usage,
+            ErrorKind::Parse,
+            ErrorKind::Validation,
+            ErrorKind::Assembly,
+            ErrorKind::Solver,
+            ErrorKind::Io,
+        ];
+        let mut codes: Vec<u8> = kinds.iter().map(|k| k.code()).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes, vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn envelope_names_kind_and_code() {
+        let failure = ccx_solver::SolveFailure::Solve("CG did not converge".to_string());
+        let error = CliError::solve(&failure);
+        assert_eq!(
+            envelope("solve", &error),
+            serde_json::json!({"error": {
+                "command": "solve",
+                "kind": "solver",
+                "code": 6,
+                "message": "SOLVE FAILED: CG did not converge",
+            }})
+        );
+        let path = Path::new("/nonexistent/deck.inp");
+        let error = ccx_inp::Deck::parse_file(path).unwrap_err();
+        assert_eq!(CliError::deck(path, &error).kind, ErrorKind::Io);
+        let error = ccx_inp::Deck::parse_str("1,0,0,0\n").unwrap_err();
+        assert_eq!(CliError::deck(path, &error).kind, ErrorKind::Parse);
+        let invalid = std::io::Error::new(std::io::ErrorKind::InvalidData, "bad record");
+        assert_eq!(CliError::read(&invalid, "bad").kind, ErrorKind::Parse);
+        assert_eq!(CliError::write(&invalid, "bad").kind, ErrorKind::Parse);
+        let unsupported = std::io::Error::new(std::io::ErrorKind::InvalidInput, "no such card");
+        assert_eq!(
+            CliError::write(&unsupported, "bad").kind,
+            ErrorKind::Validation
+        );
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert_eq!(CliError::write(&denied, "bad").kind, ErrorKind::Io);
+    }
+
+    #[test]
+    fn finds_error_format_before_parsing() {
+        let format = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            ErrorFormat::from_args(&args)
+        };
+        assert_eq!(format(&["ccx-cli", "solve", "a.inp"]), ErrorFormat::Text);
+        assert_eq!(
+            format(&["ccx-cli", "solve", "--error-format", "json", "a.inp"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            format(&["ccx-cli", "--error-format=json", "solve"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            format(&["ccx-cli", "--error-format", "xml"]),
+            ErrorFormat::Text
+        );
+    }
+}
@@ -1,470 +1,467 @@
+mod analyze;
+mod bench;
+mod check;
+mod cli;
+mod config;
+mod convert;
+mod error;
+mod migration;
+mod results;
+mod solve;
+mod validate;
+mod watch;
+
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use calculix_gui::{LegacyGuiLanguage, PORTED_GUI_UNITS, gui_migration_report, legacy_gui_units};
 use ccx_model::ModelSummary;
-use ccx_solver::{LegacyLanguage, PORTED_UNITS, legacy_units, migration_report};
-
-fn usage() {
-    eprintln!("usage:");
-    eprintln!("  ccx-cli analyze <input.inp>");
-    eprintln!("  ccx-cli analyze-fixtures <fixtures_dir>");
-    eprintln!("  ccx-cli postprocess <input.dat>");
-    eprintln!("  ccx-cli frd2vtk <input.frd> <output.vtk>");
-    eprintln!("  ccx-cli frd2vtu [--binary] <input.frd> <output.vtu>");
-    eprintln!("  ccx-cli migration-report");
-    eprintln!("  ccx-cli gui-migration-report");
-    eprintln!("  ccx-cli --help");
-    eprintln!("  ccx-cli --version");
-    eprintln!();
-    eprintln!("examples:");
-    eprintln!("  ccx-cli analyze tests/fixtures/solver/ax6.inp");
-    eprintln!("  ccx-cli analyze-fixtures tests/fixtures/solver");
-    eprintln!("  ccx-cli postprocess results.dat");
-    eprintln!("  ccx-cli frd2vtk job.frd job.vtk");
-    eprintln!("  ccx-cli frd2vtu job.frd job.vtu");
-    eprintln!("  ccx-cli frd2vtu --binary job.frd job.vtu");
-    eprintln!("  ccx-cli migration-report");
-}
-
-fn print_summary(summary: &ModelSummary) {
-    println!("total_cards: {}", summary.total_cards);
-    println!("total_data_lines: {}", summary.total_data_lines);
-    println!("node_rows: {}", summary.node_rows);
-    println!("element_rows: {}", summary.element_rows);
-    println!("material_defs: {}", summary.material_defs);
-    println!("has_step: {}", summary.has_step);
-    println!("has_static: {}", summary.has_static);
-    println!("has_dynamic: {}", summary.has_dynamic);
-    println!("has_frequency: {}", summary.has_frequency);
-    println!("has_heat_transfer: {}", summary.has_heat_transfer);
-    if !summary.include_files.is_empty() {
-        println!("include_files: {}", summary.include_files.join(", "));
-    }
-    println!("unique_keywords: {}", summary.keyword_counts.len());
-}
-
-fn language_label(language: LegacyLanguage) -> &'static str {
-    match language {
-        LegacyLanguage::C => "C",
-        LegacyLanguage::Fortran => "Fortran",
-        LegacyLanguage::Header => "Header",
-        LegacyLanguage::Other => "Other",
-    }
+use clap::Parser;
+
+use analyze::{
+    analyze_fixture_tree, keyword_coverage_json, mass_properties_json, mesh_info_file,
+    print_mass_properties, print_summary, summary_json,
+};
+use bench::bench_file;
+use check::run_check;
+use convert::{convert_file, expand_includes_file, import_mesh_file, partition_file};
+use error::{CliError, ErrorFormat, ErrorKind};
+use migration::{
+    gui_migration_report_json, migration_hotspots_report, migration_report_json,
+    print_gui_migration_report, print_migration_report, report_command,
+};
+use results::{
+    dat2vtu_file, frd_diff_files, frd2vtk_file, frd2vtu_file, frf_file, op2_to_frd_file,
+    path_plot_file, postprocess_dat_file, probe_file,
+};
+use solve::{modes_file, solve_file};
+use validate::{validate_fixture_tree, xvalidate_fixture_tree};
+use watch::watch_file;
+
+/// Log `filter`ed messages to stderr
+fn init_logging(filter: &str) -> Result<(), String> {
+    let filter = tracing_subscriber::EnvFilter::try_new(filter)
+        .map_err(|err| format!("invalid log level {filter}: {err}"))?;
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stderr()))
+        .with_target(false)
+        .without_time()
+        .init();
+    Ok(())
 }
 
-fn print_migration_report() {
-    let report = migration_report();
-    println!("legacy_units_total: {}", report.total_units);
-    println!("ported_units: {}", report.ported_units);
-    println!(
-        "superseded_fortran_units: {}",
-        report.superseded_fortran_units
-    );
-    println!("pending_units: {}", report.pending_units);
-    for (language, count) in report.by_language {
-        println!("language_{}: {}", language_label(language), count);
-    }
-
-    if !PORTED_UNITS.is_empty() {
-        println!("ported_list: {}", PORTED_UNITS.join(", "));
-    }
-
-    let pending_preview: Vec<&str> = legacy_units()
-        .iter()
-        .map(|u| u.legacy_rel_path)
-        .filter(|path| !PORTED_UNITS.iter().any(|ported| ported == path))
-        .take(8)
-        .collect();
-    if !pending_preview.is_empty() {
-        println!("pending_preview: {}", pending_preview.join(", "));
-    }
+/// Print `value` to stdout as pretty JSON
+fn print_json(value: &serde_json::Value) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    println!("{text}");
+    Ok(())
 }
 
-fn gui_language_label(language: LegacyGuiLanguage) -> &'static str {
-    match language {
-        LegacyGuiLanguage::C => "C",
-        LegacyGuiLanguage::Cpp => "Cpp",
-        LegacyGuiLanguage::Header => "Header",
-        LegacyGuiLanguage::Other => "Other",
-    }
+/// Deck named on the command line with its `-p` overrides and `-I` search
+/// path
+#[derive(Debug, Clone, PartialEq)]
+struct DeckInput {
+    input: PathBuf,
+    overrides: Vec<(String, String)>,
+    include_paths: Vec<PathBuf>,
 }
 
-fn print_gui_migration_report() {
-    let report = gui_migration_report();
-    println!("legacy_gui_units_total: {}", report.total_units);
-    println!("ported_gui_units: {}", report.ported_units);
-    println!("pending_gui_units: {}", report.pending_units);
-    for (language, count) in report.by_language {
-        println!("gui_language_{}: {}", gui_language_label(language), count);
-    }
-
-    if !PORTED_GUI_UNITS.is_empty() {
-        println!("ported_gui_list: {}", PORTED_GUI_UNITS.join(", "));
-    }
-
-    let pending_preview: Vec<&str> = legacy_gui_units()
-        .iter()
-        .map(|u| u.legacy_rel_path)
-        .filter(|path| !PORTED_GUI_UNITS.iter().any(|ported| ported == path))
-        .take(8)
-        .collect();
-    if !pending_preview.is_empty() {
-        println!("pending_gui_preview: {}", pending_preview.join(", "));
+impl DeckInput {
+    /// The deck with its includes read and `*PARAMETER`s resolved
+    fn read(&self) -> Result<ccx_inp::Deck, CliError> {
+        read_deck(&self.input, &self.overrides, &self.include_paths)
+            .map_err(|err| CliError::deck(&self.input, &err))
     }
 }
 
-fn analyze_file(path: &Path) -> Result<ModelSummary, String> {
-    let deck = ccx_inp::Deck::parse_file_with_includes(path)
-        .map_err(|err| format!("{}: {}", path.display(), err))?;
-    Ok(ModelSummary::from_deck(&deck))
+/// Parse a deck, searching `include_paths` for includes not found next to
+/// it, and resolve its `*PARAMETER`s with the command-line overrides
+fn read_deck(
+    path: &Path,
+    overrides: &[(String, String)],
+    include_paths: &[PathBuf],
+) -> Result<ccx_inp::Deck, ccx_inp::ParseError> {
+    let mut deck = ccx_inp::Deck::parse_file_with_include_paths(path, include_paths)?;
+    let mut table = ccx_inp::ParameterTable::with_overrides(overrides.iter().cloned());
+    deck.apply_parameters(&mut table)?;
+    Ok(deck)
 }
 
-fn collect_inp_files(root: &Path) -> Result<Vec<PathBuf>, String> {
-    let mut out = Vec::<PathBuf>::new();
-    collect_inp_files_inner(root, &mut out)?;
-    out.sort();
-    Ok(out)
-}
-
-fn collect_inp_files_inner(root: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
-    let entries = std::fs::read_dir(root)
-        .map_err(|err| format!("failed to read directory {}: {err}", root.display()))?;
-    for entry in entries {
-        let entry = entry.map_err(|err| format!("failed to read dir entry: {err}"))?;
-        let path = entry.path();
-        if path.is_dir() {
-            collect_inp_files_inner(&path, out)?;
-            continue;
-        }
-        if path
-            .extension()
-            .is_some_and(|ext| ext.eq_ignore_ascii_case("inp"))
-        {
-            out.push(path);
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let format = ErrorFormat::from_args(&args);
+    let command = cli::subcommand_position(&args)
+        .map(|index| args[index].clone())
+        .unwrap_or_default();
+    let result = parse_command_line(&args).and_then(|cli| {
+        init_logging(&cli.global.log_filter()).map_err(CliError::usage)?;
+        run_command(cli.command)
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            format.report(&command, &err);
+            err.exit_code()
         }
     }
-    Ok(())
 }
 
-fn analyze_fixture_tree(root: &Path) -> Result<usize, String> {
-    let files = collect_inp_files(root)?;
-    if files.is_empty() {
-        println!("no .inp files found in {}", root.display());
-        return Ok(0);
-    }
-
-    let mut failures = 0usize;
-    for path in &files {
-        if let Err(err) = analyze_file(path) {
-            failures += 1;
-            eprintln!("parse_error: {err}");
+/// Parse the command line, with the defaults of the project config placed
+/// before the subcommand's own arguments
+fn parse_command_line(args: &[String]) -> Result<cli::Cli, CliError> {
+    let parse = |args: &[String]| cli::Cli::try_parse_from(args).map_err(usage_error);
+    let cli = parse(args)?;
+    let config = config::load(cli.global.config.as_deref(), cli.global.no_config)
+        .map_err(CliError::usage)?;
+    match (config, cli::subcommand_position(args)) {
+        (Some(config), Some(index)) => {
+            let defaults = config.default_args(&args[index], &args[index + 1..]);
+            parse(&[&args[..=index], &defaults[..]].concat())
         }
+        _ => Ok(cli),
     }
-
-    println!("fixtures_root: {}", root.display());
-    println!("total_inp: {}", files.len());
-    println!("parse_ok: {}", files.len().saturating_sub(failures));
-    println!("parse_failed: {}", failures);
-    Ok(failures)
 }
 
-fn postprocess_dat_file(path: &Path) -> Result<(), String> {
-    use ccx_solver::{read_dat_file, process_integration_points, compute_statistics, write_results};
-
-    // Validate file extension
-    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("dat")) {
-        return Err("File must have .dat extension".to_string());
-    }
-
-    // Read element variable output from .dat file
-    println!("Reading element variable output from: {}", path.display());
-    let data = read_dat_file(path)?;
-    println!("Found {} integration points", data.len());
-
-    // Process data and compute Mises stress, effective strain, PEEQ
-    let results = process_integration_points(&data);
-
-    // Compute statistics
-    let stats = compute_statistics(&results);
-
-    // Print summary
-    println!("\nStatistics:");
-    println!("  Mises stress:       min={:.4e}  max={:.4e}  mean={:.4e}",
-             stats.mises_min, stats.mises_max, stats.mises_mean);
-    println!("  Effective strain:   min={:.4e}  max={:.4e}  mean={:.4e}",
-             stats.eeq_min, stats.eeq_max, stats.eeq_mean);
-    println!("  Plastic strain:     min={:.4e}  max={:.4e}  mean={:.4e}",
-             stats.peeq_min, stats.peeq_max, stats.peeq_mean);
-
-    // Write results to file
-    write_results(path, &results, &stats)?;
-
-    Ok(())
+/// Usage error of a command line clap rejected; `--help` and `--version`
+/// print their text and exit instead
+fn usage_error(err: clap::Error) -> CliError {
+    use clap::error::ErrorKind as ClapErrorKind;
+
+    let text = match err.kind() {
+        ClapErrorKind::DisplayHelp | ClapErrorKind::DisplayVersion => err.exit(),
+        ClapErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => format!(
+            "missing command\n\n{}\n\nFor more information, try '--help'.",
+            <cli::Cli as clap::CommandFactory>::command().render_usage()
+        ),
+        _ => err.render().to_string(),
+    };
+    CliError::usage(text.trim_end().trim_start_matches("error: "))
 }
 
-fn frd2vtk_file(input_path: &Path, output_path: &Path) -> Result<(), String> {
-    use ccx_io::{FrdFile, VtkWriter};
-
-    // Validate file extensions
-    if !input_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("frd")) {
-        return Err("Input file must have .frd extension".to_string());
-    }
-    if !output_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("vtk")) {
-        return Err("Output file must have .vtk extension".to_string());
-    }
-
-    // Read FRD file
-    println!("Reading FRD file: {}", input_path.display());
-    let frd = FrdFile::from_file(input_path)
-        .map_err(|err| format!("Failed to read FRD file: {}", err))?;
-
-    println!("  Nodes: {}", frd.nodes.len());
-    println!("  Elements: {}", frd.elements.len());
-    println!("  Result blocks: {}", frd.result_blocks.len());
-
-    // Write VTK file
-    println!("Writing VTK file: {}", output_path.display());
-    let writer = VtkWriter::new(&frd);
-    writer.write_vtk(output_path)
-        .map_err(|err| format!("Failed to write VTK file: {}", err))?;
-
-    println!("Conversion complete!");
-    Ok(())
-}
-
-fn frd2vtu_file(input_path: &Path, output_path: &Path, binary: bool) -> Result<(), String> {
-    use ccx_io::{FrdFile, VtkWriter, VtkFormat};
-
-    // Validate file extensions
-    if !input_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("frd")) {
-        return Err("Input file must have .frd extension".to_string());
-    }
-    if !output_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("vtu")) {
-        return Err("Output file must have .vtu extension".to_string());
-    }
-
-    // Read FRD file
-    println!("Reading FRD file: {}", input_path.display());
-    let frd = FrdFile::from_file(input_path)
-        .map_err(|err| format!("Failed to read FRD file: {}", err))?;
-
-    println!("  Nodes: {}", frd.nodes.len());
-    println!("  Elements: {}", frd.elements.len());
-    println!("  Result blocks: {}", frd.result_blocks.len());
-
-    // Write VTU file
-    let format = if binary { VtkFormat::Binary } else { VtkFormat::Ascii };
-    println!("Writing VTU file ({}): {}",
-             if binary { "binary" } else { "ASCII" },
-             output_path.display());
-
-    let writer = VtkWriter::new(&frd);
-    writer.write_vtu(output_path, format)
-        .map_err(|err| format!("Failed to write VTU file: {}", err))?;
-
-    println!("Conversion complete!");
-    Ok(())
-}
-
-fn main() -> ExitCode {
-    let args: Vec<String> = std::env::args().collect();
-    match args.get(1).map(String::as_str) {
-        Some("help") | Some("-h") | Some("--help") => {
-            usage();
-            ExitCode::SUCCESS
+fn run_command(command: cli::Command) -> Result<(), CliError> {
+    use cli::{Command, IntoOptions};
+
+    let validation = |message: String| Err(CliError::new(ErrorKind::Validation, message));
+    match command {
+        Command::Analyze(args) => {
+            let (input, json) = args.into_options().map_err(CliError::usage)?;
+            let deck = input.read()?;
+            let summary = ModelSummary::from_deck(&deck);
+            if json {
+                let mut value = summary_json(&summary);
+                value["mass_properties"] = mass_properties_json(&deck);
+                print_json(&value).map_err(CliError::io)?;
+            } else {
+                print_summary(&summary);
+                print_mass_properties(&deck);
+            }
+            Ok(())
         }
-        Some("--version") | Some("-V") => {
-            println!("{}", env!("CARGO_PKG_VERSION"));
-            ExitCode::SUCCESS
+        Command::MeshInfo(args) => {
+            let (input, worst, json) = args.into_options().map_err(CliError::usage)?;
+            mesh_info_file(&input, worst, json)
         }
-        Some("analyze") => {
-            if args.len() != 3 {
-                usage();
-                return ExitCode::from(2);
+        Command::Check(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            match run_check(&options)? {
+                true => Ok(()),
+                false => validation(format!("{} failed the checks", options.input.display())),
             }
-
-            let path = Path::new(&args[2]);
-            let summary = match analyze_file(path) {
-                Ok(summary) => summary,
-                Err(err) => {
-                    eprintln!("parse error: {err}");
-                    return ExitCode::from(1);
-                }
-            };
-            print_summary(&summary);
-            ExitCode::SUCCESS
         }
-        Some("analyze-fixtures") => {
-            if args.len() != 3 {
-                usage();
-                return ExitCode::from(2);
-            }
-            let root = Path::new(&args[2]);
-            match analyze_fixture_tree(root) {
-                Ok(0) => ExitCode::SUCCESS,
-                Ok(_) => ExitCode::from(1),
-                Err(err) => {
-                    eprintln!("analyze_fixtures_error: {err}");
-                    ExitCode::from(1)
-                }
+        Command::AnalyzeFixtures(args) => {
+            let root = args.into_options().map_err(CliError::usage)?;
+            match analyze_fixture_tree(&root)? {
+                0 => Ok(()),
+                failures => Err(CliError::new(
+                    ErrorKind::Parse,
+                    format!("{failures} decks failed to parse"),
+                )),
             }
         }
-        Some("postprocess") => {
-            if args.len() != 3 {
-                usage();
-                return ExitCode::from(2);
-            }
-            let path = Path::new(&args[2]);
-            match postprocess_dat_file(path) {
-                Ok(()) => ExitCode::SUCCESS,
-                Err(err) => {
-                    eprintln!("postprocess error: {err}");
-                    ExitCode::from(1)
-                }
+        Command::Validate(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            match validate_fixture_tree(&options)? {
+                0 => Ok(()),
+                failed => validation(format!("{failed} fixtures failed")),
             }
         }
-        Some("frd2vtk") => {
-            if args.len() != 4 {
-                usage();
-                return ExitCode::from(2);
-            }
-            let input_path = Path::new(&args[2]);
-            let output_path = Path::new(&args[3]);
-            match frd2vtk_file(input_path, output_path) {
-                Ok(()) => ExitCode::SUCCESS,
-                Err(err) => {
-                    eprintln!("frd2vtk error: {err}");
-                    ExitCode::from(1)
-                }
+        Command::Xvalidate(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            match xvalidate_fixture_tree(&options)? {
+                0 => Ok(()),
+                failed => validation(format!("{failed} decks do not match ccx")),
             }
         }
-        Some("frd2vtu") => {
-            // Handle optional --binary flag
-            let (binary, input_idx, output_idx) = if args.get(2).map(String::as_str) == Some("--binary") {
-                if args.len() != 5 {
-                    usage();
-                    return ExitCode::from(2);
-                }
-                (true, 3, 4)
-            } else {
-                if args.len() != 4 {
-                    usage();
-                    return ExitCode::from(2);
-                }
-                (false, 2, 3)
-            };
-
-            let input_path = Path::new(&args[input_idx]);
-            let output_path = Path::new(&args[output_idx]);
-            match frd2vtu_file(input_path, output_path, binary) {
-                Ok(()) => ExitCode::SUCCESS,
-                Err(err) => {
-                    eprintln!("frd2vtu error: {err}");
-                    ExitCode::from(1)
-                }
-            }
+        Command::Solve(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            solve_file(&options)
+        }
+        Command::Modes(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            modes_file(&options)
+        }
+        Command::Bench(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            let report = bench_file(&options)?;
+            println!("Benchmark of {}", options.input.display());
+            print!("{}", report.format());
+            Ok(())
+        }
+        Command::Watch(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            watch_file(&options)
+        }
+        Command::Postprocess(args) => postprocess_dat_file(&args.input),
+        Command::Frd2vtk(args) => {
+            let (input, output, step) = args.into_options().map_err(CliError::usage)?;
+            frd2vtk_file(&input, &output, step)
+        }
+        Command::Frd2vtu(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            frd2vtu_file(&options)
         }
-        Some("migration-report") => {
-            if args.len() != 2 {
-                usage();
-                return ExitCode::from(2);
+        Command::Dat2vtu(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            dat2vtu_file(&options)
+        }
+        Command::FrdDiff(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            match frd_diff_files(&options)? {
+                true => Ok(()),
+                false => validation("results differ beyond the tolerances".to_string()),
             }
-            print_migration_report();
-            ExitCode::SUCCESS
         }
-        Some("gui-migration-report") => {
-            if args.len() != 2 {
-                usage();
-                return ExitCode::from(2);
+        Command::PathPlot(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            path_plot_file(&options)
+        }
+        Command::Probe(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            probe_file(&options)
+        }
+        Command::Frf(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            frf_file(&options)
+        }
+        Command::Import(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            import_mesh_file(&options)
+        }
+        Command::Convert(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            convert_file(&options)
+        }
+        Command::Partition(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            partition_file(&options)
+        }
+        Command::ExpandIncludes(args) => {
+            let options = args.into_options().map_err(CliError::usage)?;
+            expand_includes_file(&options)
+        }
+        Command::Op2frd(args) => {
+            let (input, output, element_stress) = args.into_options().map_err(CliError::usage)?;
+            op2_to_frd_file(&input, &output, element_stress)
+        }
+        Command::MigrationReport(args) => {
+            report_command(&args, migration_report_json(), print_migration_report)
+        }
+        Command::GuiMigrationReport(args) => report_command(
+            &args,
+            gui_migration_report_json(),
+            print_gui_migration_report,
+        ),
+        Command::MigrationHotspots(args) => migration_hotspots_report(&args),
+        Command::KeywordCoverage(args) => {
+            let (input, json) = args.into_options().map_err(CliError::usage)?;
+            let deck = input.read()?;
+            let coverage = ccx_solver::keyword_coverage(&deck);
+            if json {
+                print_json(&keyword_coverage_json(&coverage)).map_err(CliError::io)
+            } else {
+                println!("{}", coverage.format());
+                Ok(())
             }
-            print_gui_migration_report();
-            ExitCode::SUCCESS
         }
-        _ => {
-            usage();
-            ExitCode::from(2)
+        Command::Completions(args) => {
+            print!("{}", cli::completion_script(args.shell));
+            Ok(())
         }
     }
 }
 
+/// Fresh directory under the system temp directory, for the tests
+#[cfg(test)]
+fn unique_temp_dir(prefix: &str) -> PathBuf {
+    let pid = std::process::id();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("clock should be valid")
+        .as_nanos();
+    std::env::temp_dir().join(format!("{prefix}_{pid}_{nanos}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use analyze::{analyze_file, deck_mesh, mesh_info_json};
+    use ccx_solver::PORTED_UNITS;
+    use cli::{InputArgs, parse_args};
+    use solve::{solve_json, solve_warnings};
     use std::fs;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    #[test]
-    fn labels_match_expected_strings() {
-        assert_eq!(language_label(LegacyLanguage::C), "C");
-        assert_eq!(language_label(LegacyLanguage::Fortran), "Fortran");
-        assert_eq!(gui_language_label(LegacyGuiLanguage::Cpp), "Cpp");
-        assert_eq!(gui_language_label(LegacyGuiLanguage::Header), "Header");
-    }
-
-    #[test]
-    fn collect_inp_files_recurses_and_sorts() {
-        let root = unique_temp_dir("ccx_cli_collect_inp");
-        let nested = root.join("nested");
-        fs::create_dir_all(&nested).expect("create temp tree");
-
-        fs::write(root.join("b.INP"), "*NODE\n1,0,0,0\n").expect("write b");
-        fs::write(root.join("ignore.txt"), "x").expect("write ignore");
-        fs::write(nested.join("a.inp"), "*NODE\n1,0,0,0\n").expect("write a");
-
-        let files = collect_inp_files(&root).expect("collect should succeed");
-        let mut names: Vec<String> = files
-            .iter()
-            .filter_map(|p| p.file_name().and_then(|s| s.to_str()).map(str::to_string))
-            .collect();
-        names.sort();
-
-        assert_eq!(names, vec!["a.inp".to_string(), "b.INP".to_string()]);
-    }
 
     #[test]
-    fn analyze_file_expands_includes() {
-        let root = unique_temp_dir("ccx_cli_analyze_include");
-        fs::create_dir_all(&root).expect("create temp dir");
-        let deck = root.join("root.inp");
-        let inc = root.join("mesh.inc");
-
+    fn run_command_classifies_failures() {
+        let root = unique_temp_dir("ccx_cli_exit_codes");
+        fs::create_dir_all(&root).expect("create temp directory");
+        // Unsupported bar without any support: the stiffness matrix is singular
+        let free = root.join("free.inp");
         fs::write(
-            &deck,
-            "*NODE\n1,0,0,0\n*INCLUDE,INPUT=mesh.inc\n*ELEMENT\n1,1,1,1,1\n",
+            &free,
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
         )
-        .expect("write root deck");
-        fs::write(&inc, "*MATERIAL,NAME=STEEL\n").expect("write include");
-
-        let summary = analyze_file(&deck).expect("analysis should parse");
-        assert_eq!(summary.node_rows, 1);
-        assert_eq!(summary.element_rows, 1);
-        assert_eq!(summary.material_defs, 1);
-        assert_eq!(summary.include_files, vec!["mesh.inc".to_string()]);
+        .expect("write deck");
+        let bad = root.join("bad.inp");
+        fs::write(&bad, "*NODE\n1,0,0,0\n*INCLUDE\n").expect("write deck");
+        let kind = |args: &[&str]| {
+            let args: Vec<String> = ["ccx-cli"]
+                .iter()
+                .chain(args)
+                .map(|s| s.to_string())
+                .collect();
+            parse_command_line(&args)
+                .and_then(|cli| run_command(cli.command))
+                .map_err(|err| err.kind)
+        };
+        let output_dir = root.join("out");
+        let out = output_dir.to_str().unwrap();
+
+        assert_eq!(kind(&["solve", "--bogus"]), Err(ErrorKind::Usage));
+        assert_eq!(kind(&["frobnicate"]), Err(ErrorKind::Usage));
+        let missing = root.join("missing.inp");
+        assert_eq!(
+            kind(&["solve", missing.to_str().unwrap()]),
+            Err(ErrorKind::Io)
+        );
+        assert_eq!(
+            kind(&["analyze", bad.to_str().unwrap()]),
+            Err(ErrorKind::Parse)
+        );
+        assert_eq!(
+            kind(&["solve", "--output-dir", out, free.to_str().unwrap()]),
+            Err(ErrorKind::Solver)
+        );
+        assert_eq!(
+            kind(&["check", bad.to_str().unwrap()]),
+            Err(ErrorKind::Validation)
+        );
+        assert_eq!(
+            kind(&["mesh-info", missing.to_str().unwrap()]),
+            Err(ErrorKind::Io)
+        );
+        assert_eq!(
+            kind(&["mesh-info", bad.to_str().unwrap()]),
+            Err(ErrorKind::Parse)
+        );
+        let missing_frd = root.join("missing.frd");
+        let vtu = root.join("missing.vtu");
+        assert_eq!(
+            kind(&[
+                "frd2vtu",
+                missing_frd.to_str().unwrap(),
+                vtu.to_str().unwrap()
+            ]),
+            Err(ErrorKind::Io)
+        );
+        // A missing include is an I/O error even though the deck itself exists
+        let include = root.join("include.inp");
+        fs::write(&include, "*INCLUDE,INPUT=absent.inc\n").expect("write deck");
+        assert_eq!(
+            kind(&["analyze", include.to_str().unwrap()]),
+            Err(ErrorKind::Io)
+        );
+        let sta = fs::read_to_string(output_dir.join("free.sta")).expect("read sta");
+        let last = sta.lines().last().unwrap();
+        assert!(last.starts_with(" *ERROR: SOLVE FAILED"), "{sta}");
+
+        let _ = fs::remove_dir_all(root);
     }
 
     #[test]
-    fn analyze_fixture_tree_counts_failures() {
-        let root = unique_temp_dir("ccx_cli_fixture_tree");
+    fn json_reports_describe_model_and_solve() {
+        let root = unique_temp_dir("ccx_cli_json");
         fs::create_dir_all(&root).expect("create temp dir");
-
+        let path = root.join("truss.inp");
         fs::write(
-            root.join("ok.inp"),
-            "*NODE\n1,0,0,0\n*ELEMENT\n1,1,1,1,1\n*STEP\n*STATIC\n*END STEP\n",
+            &path,
+            "*NODE,NSET=NALL\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+             *NSET,NSET=SPARE\n2\n*MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
         )
-        .expect("write ok fixture");
-        fs::write(root.join("bad.inp"), "1,2,3\n*NODE\n1,0,0,0\n").expect("write bad fixture");
-
-        let failures = analyze_fixture_tree(&root).expect("scan should succeed");
-        assert_eq!(failures, 1);
-    }
-
-    fn unique_temp_dir(prefix: &str) -> PathBuf {
-        let pid = std::process::id();
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("clock should be valid")
-            .as_nanos();
-        std::env::temp_dir().join(format!("{prefix}_{pid}_{nanos}"))
+        .expect("write deck");
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (input, json) = parse_args::<InputArgs>(&to_args(&["--json", "a.inp"])).unwrap();
+        assert_eq!(input.input, PathBuf::from("a.inp"));
+        assert!(json);
+        assert!(parse_args::<InputArgs>(&to_args(&["--xml", "a.inp"])).is_err());
+
+        let summary = summary_json(&analyze_file(&path).unwrap());
+        assert_eq!(summary["node_rows"], 2);
+        assert_eq!(summary["has_static"], true);
+
+        let deck = ccx_inp::Deck::parse_file(&path).unwrap();
+        let mesh = ccx_solver::MeshBuilder::build_from_deck(&deck).unwrap();
+        let mut sets = ccx_solver::Sets::build_from_deck(&deck).unwrap();
+        sets.add_card_sets(&deck).unwrap();
+        let audit = mesh.audit().with_unused_sets(&sets, &deck);
+        let info = mesh_info_json(&mesh, &sets, &audit, 3);
+        assert_eq!(info["element_types"]["T3D2"], 1);
+        assert_eq!(info["node_id_range"], serde_json::json!([1, 2]));
+        assert_eq!(info["sets"]["node_sets"]["NALL"], 2);
+        // NALL and EALL are implicit, only SPARE goes unused
+        let unused = serde_json::json!(["sets not used by any card: SPARE"]);
+        assert_eq!(info["audit"]["warnings"], unused);
+
+        // mesh-info and analyze resolve *PARAMETERs with the -p overrides
+        let param = root.join("param.inp");
+        fs::write(
+            &param,
+            "*PARAMETER\nlen=1.\n*NODE\n1,0,0,0\n2,<len>,0,0\n\
+             *ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n",
+        )
+        .expect("write deck");
+        let input = DeckInput {
+            input: param,
+            overrides: vec![("len".to_string(), "3.".to_string())],
+            include_paths: Vec::new(),
+        };
+        let param_deck = input.read().unwrap();
+        let (param_mesh, _) = deck_mesh(&input.input, &param_deck).unwrap();
+        assert_eq!(
+            param_mesh.statistics().bounding_box,
+            Some(([0.0, 0.0, 0.0], [3.0, 0.0, 0.0]))
+        );
+        assert!(mesh_info_file(&input, 3, true).is_ok());
+
+        let results = ccx_solver::AnalysisPipeline::detect_from_deck(&deck)
+            .run(&deck)
+            .unwrap();
+        let written = [root.join("truss.dat")];
+        let solve = solve_json(&path, &results, &solve_warnings(&results), &written);
+        assert_eq!(solve["success"], true);
+        assert_eq!(solve["warnings"], unused);
+        assert_eq!(solve["max_displacement"]["node"], 2);
+        assert!(solve["solver"]["nnz"].as_u64().unwrap() > 0);
+        assert_eq!(solve["outputs"][0], written[0].display().to_string());
+
+        let report = migration_report_json();
+        assert_eq!(
+            report["ported"].as_array().unwrap().len(),
+            PORTED_UNITS.len()
+        );
     }
 }
@@ -0,0 +1,505 @@
+//! Migration reports of the legacy solver and GUI sources.
+//!
+//! `migration-report` and `gui-migration-report` count the ported and
+//! pending units, `migration-hotspots` ranks the pending ones by the calls
+//! recorded in usage logs such as those of `solve --usage-log`.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use calculix_gui::{LegacyGuiLanguage, PORTED_GUI_UNITS, gui_migration_report, legacy_gui_units};
+use ccx_compat::{Hotspot, UsageLog};
+use ccx_solver::{
+    LegacyLanguage, PORTED_UNITS, PORTING_MANIFEST, PortingStatus, legacy_units, migration_report,
+};
+
+use crate::cli::{HotspotsArgs, ReportArgs};
+use crate::error::CliError;
+use crate::print_json;
+
+fn language_label(language: LegacyLanguage) -> &'static str {
+    match language {
+        LegacyLanguage::C => "C",
+        LegacyLanguage::Fortran => "Fortran",
+        LegacyLanguage::Header => "Header",
+        LegacyLanguage::Other => "Other",
+    }
+}
+
+/// RFC 3339 UTC time of `time`, to the second
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date of a day count from 1970-01-01, in 400-year eras from March
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Unit of a migration report with its language and line count, which are
+/// null for ported units missing from the legacy catalog
+fn report_unit_json(path: &str, unit: Option<(&str, usize)>) -> serde_json::Value {
+    serde_json::json!({
+        "path": path,
+        "language": unit.map(|(language, _)| language),
+        "lines": unit.map(|(_, lines)| lines),
+    })
+}
+
+/// Total of the `lines` of report units
+fn report_lines(units: &[serde_json::Value]) -> u64 {
+    units.iter().filter_map(|unit| unit["lines"].as_u64()).sum()
+}
+
+pub fn migration_report_json() -> serde_json::Value {
+    let report = migration_report();
+    let by_language: serde_json::Map<String, serde_json::Value> = report
+        .by_language
+        .into_iter()
+        .map(|(language, count)| (language_label(language).to_string(), count.into()))
+        .collect();
+    let catalog = |path: &str| {
+        legacy_units()
+            .iter()
+            .find(|unit| unit.legacy_rel_path == path)
+            .map(|unit| (language_label(unit.language), unit.line_count))
+    };
+    let ported: Vec<serde_json::Value> = PORTING_MANIFEST
+        .iter()
+        .filter(|entry| entry.status == PortingStatus::Ported)
+        .map(|entry| {
+            let mut unit = report_unit_json(entry.legacy_rel_path, catalog(entry.legacy_rel_path));
+            unit["parity_test"] = entry.parity_test.into();
+            unit
+        })
+        .collect();
+    let pending: Vec<serde_json::Value> = legacy_units()
+        .iter()
+        .filter(|unit| !PORTED_UNITS.contains(&unit.legacy_rel_path))
+        .map(|unit| {
+            let language = language_label(unit.language);
+            report_unit_json(unit.legacy_rel_path, Some((language, unit.line_count)))
+        })
+        .collect();
+    serde_json::json!({
+        "timestamp": utc_timestamp(SystemTime::now()),
+        "legacy_units_total": report.total_units,
+        "ported_units": report.ported_units,
+        "in_progress_units": report.in_progress_units,
+        "superseded_fortran_units": report.superseded_fortran_units,
+        "pending_units": report.pending_units,
+        "by_language": by_language,
+        "ported_lines": report_lines(&ported),
+        "pending_lines": report_lines(&pending),
+        "ported": ported,
+        "pending": pending,
+    })
+}
+
+/// The report without its unit lists, one line of a `--history` file
+fn history_entry(report: &serde_json::Value) -> serde_json::Value {
+    let mut entry = report.clone();
+    if let Some(fields) = entry.as_object_mut() {
+        fields.remove("ported");
+        fields.remove("pending");
+    }
+    entry
+}
+
+/// Append the history entry of `report` to the JSON lines file at `path`
+fn append_history(path: &Path, report: &serde_json::Value) -> Result<(), String> {
+    use std::io::Write;
+
+    let line = serde_json::to_string(&history_entry(report)).map_err(|err| err.to_string())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("cannot open {}: {err}", path.display()))?;
+    writeln!(file, "{line}").map_err(|err| format!("cannot write {}: {err}", path.display()))
+}
+
+/// Print a migration report as JSON or text and append it to the
+/// `--history` file
+pub fn report_command(
+    args: &ReportArgs,
+    report: serde_json::Value,
+    print_text: fn(),
+) -> Result<(), CliError> {
+    if let Some(history) = &args.history {
+        append_history(history, &report).map_err(CliError::io)?;
+    }
+    if args.json {
+        return print_json(&report).map_err(CliError::io);
+    }
+    print_text();
+    Ok(())
+}
+
+pub fn print_migration_report() {
+    let report = migration_report();
+    println!("legacy_units_total: {}", report.total_units);
+    println!("ported_units: {}", report.ported_units);
+    println!("in_progress_units: {}", report.in_progress_units);
+    println!(
+        "superseded_fortran_units: {}",
+        report.superseded_fortran_units
+    );
+    println!("pending_units: {}", report.pending_units);
+    for (language, count) in report.by_language {
+        println!("language_{}: {}", language_label(language), count);
+    }
+
+    if !PORTED_UNITS.is_empty() {
+        println!("ported_list: {}", PORTED_UNITS.join(", "));
+    }
+
+    let pending_preview: Vec<&str> = legacy_units()
+        .iter()
+        .map(|u| u.legacy_rel_path)
+        .filter(|path| !PORTED_UNITS.iter().any(|ported| ported == path))
+        .take(8)
+        .collect();
+    if !pending_preview.is_empty() {
+        println!("pending_preview: {}", pending_preview.join(", "));
+    }
+}
+
+fn gui_language_label(language: LegacyGuiLanguage) -> &'static str {
+    match language {
+        LegacyGuiLanguage::C => "C",
+        LegacyGuiLanguage::Cpp => "Cpp",
+        LegacyGuiLanguage::Header => "Header",
+        LegacyGuiLanguage::Other => "Other",
+    }
+}
+
+pub fn gui_migration_report_json() -> serde_json::Value {
+    let report = gui_migration_report();
+    let by_language: serde_json::Map<String, serde_json::Value> = report
+        .by_language
+        .into_iter()
+        .map(|(language, count)| (gui_language_label(language).to_string(), count.into()))
+        .collect();
+    let catalog = |path: &str| {
+        legacy_gui_units()
+            .iter()
+            .find(|unit| unit.legacy_rel_path == path)
+            .map(|unit| (gui_language_label(unit.language), unit.line_count))
+    };
+    let ported: Vec<serde_json::Value> = PORTED_GUI_UNITS
+        .iter()
+        .map(|path| report_unit_json(path, catalog(path)))
+        .collect();
+    let pending: Vec<serde_json::Value> = legacy_gui_units()
+        .iter()
+        .filter(|unit| !PORTED_GUI_UNITS.contains(&unit.legacy_rel_path))
+        .map(|unit| {
+            let language = gui_language_label(unit.language);
+            report_unit_json(unit.legacy_rel_path, Some((language, unit.line_count)))
+        })
+        .collect();
+    serde_json::json!({
+        "timestamp": utc_timestamp(SystemTime::now()),
+        "legacy_gui_units_total": report.total_units,
+        "ported_gui_units": report.ported_units,
+        "pending_gui_units": report.pending_units,
+        "by_language": by_language,
+        "ported_lines": report_lines(&ported),
+        "pending_lines": report_lines(&pending),
+        "ported": ported,
+        "pending": pending,
+    })
+}
+
+pub fn print_gui_migration_report() {
+    let report = gui_migration_report();
+    println!("legacy_gui_units_total: {}", report.total_units);
+    println!("ported_gui_units: {}", report.ported_units);
+    println!("pending_gui_units: {}", report.pending_units);
+    for (language, count) in report.by_language {
+        println!("gui_language_{}: {}", gui_language_label(language), count);
+    }
+
+    if !PORTED_GUI_UNITS.is_empty() {
+        println!("ported_gui_list: {}", PORTED_GUI_UNITS.join(", "));
+    }
+
+    let pending_preview: Vec<&str> = legacy_gui_units()
+        .iter()
+        .map(|u| u.legacy_rel_path)
+        .filter(|path| !PORTED_GUI_UNITS.iter().any(|ported| ported == path))
+        .take(8)
+        .collect();
+    if !pending_preview.is_empty() {
+        println!("pending_gui_preview: {}", pending_preview.join(", "));
+    }
+}
+
+/// Routine of a usage log with the legacy unit it comes from
+#[derive(Debug)]
+struct MigrationHotspot {
+    hotspot: Hotspot,
+    legacy_unit: Option<&'static str>,
+    ported: bool,
+}
+
+/// File name of a legacy unit path without directory and extension
+fn unit_stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.split_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// Routines of the usage logs at `logs` with their legacy units, most called
+/// first, and the number of jobs in the logs
+fn migration_hotspots(logs: &[PathBuf]) -> Result<(Vec<MigrationHotspot>, usize), String> {
+    let mut usage = UsageLog::new();
+    for path in logs {
+        usage.merge(&UsageLog::load(path)?);
+    }
+    let hotspots = usage
+        .hotspots()
+        .into_iter()
+        .map(|hotspot| {
+            let stem = hotspot.symbol.trim_end_matches('_');
+            let legacy_unit = legacy_units()
+                .iter()
+                .map(|unit| unit.legacy_rel_path)
+                .find(|path| unit_stem(path) == stem);
+            let ported = PORTED_UNITS.iter().any(|path| unit_stem(path) == stem);
+            MigrationHotspot {
+                hotspot,
+                legacy_unit,
+                ported,
+            }
+        })
+        .collect();
+    Ok((hotspots, usage.jobs().count()))
+}
+
+pub fn migration_hotspots_report(args: &HotspotsArgs) -> Result<(), CliError> {
+    let (mut hotspots, jobs) = migration_hotspots(&args.logs).map_err(CliError::io)?;
+    let ported = hotspots.iter().filter(|h| h.ported).count();
+    if !args.include_ported {
+        hotspots.retain(|h| !h.ported);
+    }
+    if let Some(top) = args.top {
+        hotspots.truncate(top);
+    }
+
+    if args.json {
+        let routines: Vec<serde_json::Value> = hotspots
+            .iter()
+            .map(|h| {
+                serde_json::json!({
+                    "symbol": h.hotspot.symbol,
+                    "calls": h.hotspot.calls,
+                    "jobs": h.hotspot.jobs,
+                    "legacy_unit": h.legacy_unit,
+                    "ported": h.ported,
+                })
+            })
+            .collect();
+        let report = serde_json::json!({
+            "jobs": jobs,
+            "ported_routines": ported,
+            "routines": routines,
+        });
+        return print_json(&report).map_err(CliError::io);
+    }
+
+    println!("jobs: {jobs}");
+    println!("ported_routines: {ported}");
+    println!(
+        "{:>4} {:>10} {:>6}  routine (legacy unit)",
+        "rank", "calls", "jobs"
+    );
+    for (rank, h) in hotspots.iter().enumerate() {
+        let unit = h.legacy_unit.unwrap_or("unknown unit");
+        let ported = if h.ported { ", ported" } else { "" };
+        println!(
+            "{:>4} {:>10} {:>6}  {} ({unit}{ported})",
+            rank + 1,
+            h.hotspot.calls,
+            h.hotspot.jobs,
+            h.hotspot.symbol
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{SolveArgs, parse_args};
+    use crate::solve::{solve_file, solve_with_legacy};
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn labels_match_expected_strings() {
+        assert_eq!(language_label(LegacyLanguage::C), "C");
+        assert_eq!(language_label(LegacyLanguage::Fortran), "Fortran");
+        assert_eq!(gui_language_label(LegacyGuiLanguage::Cpp), "Cpp");
+        assert_eq!(gui_language_label(LegacyGuiLanguage::Header), "Header");
+    }
+
+    #[test]
+    fn migration_hotspots_merge_logs_and_flag_ported_units() {
+        let root = unique_temp_dir("ccx_cli_hotspots");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let first = root.join("first.tsv");
+        let second = root.join("second.tsv");
+        fs::write(&first, "beam\te_c3d_\t5\nbeam\tnident_\t9\n").expect("write first");
+        fs::write(&second, "plate\te_c3d_\t5\nplate\tumat_user_\t1\n").expect("write second");
+
+        let (hotspots, jobs) = migration_hotspots(&[first, second.clone()]).unwrap();
+        assert_eq!(jobs, 2);
+        let ranked: Vec<(&str, u64, usize, bool)> = hotspots
+            .iter()
+            .map(|h| {
+                (
+                    h.hotspot.symbol.as_str(),
+                    h.hotspot.calls,
+                    h.hotspot.jobs,
+                    h.ported,
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("e_c3d_", 10, 2, false),
+                ("nident_", 9, 1, true),
+                ("umat_user_", 1, 1, false),
+            ]
+        );
+        assert_eq!(unit_stem("superseded/nident.f"), "nident");
+
+        assert!(migration_hotspots(&[root.join("missing.tsv")]).is_err());
+        fs::write(&second, "plate\te_c3d_\n").expect("rewrite second");
+        assert!(
+            migration_hotspots(&[second])
+                .unwrap_err()
+                .contains("line 1")
+        );
+        fs::remove_dir_all(&root).expect("remove temp dir");
+    }
+
+    /// Axial spring along x between the first two nodes of an M3D3
+    /// element, standing in for a legacy element routine
+    unsafe extern "C" fn e_m3d3_(
+        _lakonl: *mut u8,
+        nope: *mut i32,
+        mdof: *mut i32,
+        xl: *mut f64,
+        elcon: *mut f64,
+        s: *mut f64,
+        _lakonl_len: usize,
+    ) {
+        let (nope, mdof) = unsafe { (*nope as usize, *mdof as usize) };
+        let xl = unsafe { std::slice::from_raw_parts(xl, 3 * nope) };
+        let n = nope * mdof;
+        let s = unsafe { std::slice::from_raw_parts_mut(s, n * n) };
+        let k = unsafe { *elcon } / (xl[3] - xl[0]).abs();
+        for (i, j, value) in [(0, 0, k), (mdof, mdof, k), (0, mdof, -k), (mdof, 0, -k)] {
+            s[j * n + i] = value;
+        }
+    }
+
+    #[test]
+    fn solve_usage_log_feeds_migration_hotspots() {
+        let root = unique_temp_dir("ccx_cli_usage");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let deck = root.join("membrane.inp");
+        fs::write(
+            &deck,
+            "*NODE\n1,0,0,0\n2,2,0,0\n3,0,1,0\n*ELEMENT,TYPE=M3D3\n1,1,2,3\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n10,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n3,1,3\n*STEP\n*STATIC\n*CLOAD\n2,1,10.\n*END STEP\n",
+        )
+        .expect("write deck");
+        let address = unsafe { ccx_compat::ForeignFn::from_ptr(e_m3d3_ as *const _) }.unwrap();
+        let routine = unsafe {
+            ccx_compat::ForeignRoutine::new(
+                "e_m3d3",
+                ccx_compat::CallingConvention::Fortran,
+                ccx_solver::ELEMENT_ROUTINE_ARGS.to_vec(),
+                address,
+            )
+        };
+        let mut registry = ccx_compat::CompatRegistry::new();
+        registry.register_legacy(routine);
+        let legacy = std::sync::Arc::new(ccx_solver::LegacyElements::new(registry));
+
+        let log = root.join("usage.tsv");
+        for job in ["first", "second"] {
+            let args: Vec<String> = ["--usage-log", &log.display().to_string()]
+                .into_iter()
+                .map(str::to_string)
+                .chain(["--job-name".to_string(), job.to_string()])
+                .chain([deck.display().to_string()])
+                .collect();
+            let options = parse_args::<SolveArgs>(&args).unwrap();
+            solve_with_legacy(&options, Some(legacy.clone())).unwrap();
+        }
+        // Without legacy routines no call reaches the log
+        let args = ["--usage-log".to_string(), log.display().to_string()];
+        let args = [&args[..], &[deck.display().to_string()]].concat();
+        solve_file(&parse_args::<SolveArgs>(&args).unwrap()).unwrap();
+
+        let (hotspots, jobs) = migration_hotspots(&[log]).unwrap();
+        assert_eq!(jobs, 2);
+        let [element] = &hotspots[..] else {
+            panic!("one routine called: {hotspots:?}");
+        };
+        assert_eq!(element.hotspot.symbol, "e_m3d3_");
+        assert_eq!((element.hotspot.calls, element.hotspot.jobs), (2, 2));
+        fs::remove_dir_all(&root).expect("remove temp dir");
+    }
+
+    #[test]
+    fn migration_history_appends_timestamped_counts() {
+        let at = |secs| utc_timestamp(UNIX_EPOCH + std::time::Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_700_000_000), "2023-11-14T22:13:20Z");
+
+        let report = migration_report_json();
+        let ported = report["ported"].as_array().unwrap();
+        assert!(ported.iter().all(|unit| unit["path"].is_string()));
+        let pending = report["pending"].as_array().unwrap();
+        assert_eq!(report["pending_lines"], report_lines(pending));
+
+        let root = unique_temp_dir("ccx_cli_migration_history");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let history = root.join("history.jsonl");
+        append_history(&history, &report).unwrap();
+        append_history(&history, &gui_migration_report_json()).unwrap();
+        let text = fs::read_to_string(&history).expect("read history");
+        let entries: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["timestamp"], report["timestamp"]);
+        assert_eq!(entries[0]["ported_units"], report["ported_units"]);
+        assert!(entries[0].get("pending").is_none());
+        assert!(entries[1]["ported_gui_units"].is_u64());
+        assert!(entries[1].get("ported").is_none());
+        assert!(append_history(&root.join("missing/history.jsonl"), &report).is_err());
+        fs::remove_dir_all(&root).expect("remove temp dir");
+    }
+}
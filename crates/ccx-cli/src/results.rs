@@ -0,0 +1,989 @@
+//! Post-processing of result files: `postprocess`, `frd2vtk`, `frd2vtu`,
+//! `dat2vtu`, `frd-diff`, `path-plot`, `probe`, `frf` and `op2frd`.
+
+use std::path::{Path, PathBuf};
+
+use crate::analyze::deck_mesh;
+use crate::error::CliError;
+use crate::{print_json, read_deck};
+
+pub fn postprocess_dat_file(path: &Path) -> Result<(), CliError> {
+    use ccx_solver::{
+        compute_statistics, process_integration_points, read_dat_file, write_results,
+    };
+
+    // Validate file extension
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dat"))
+    {
+        return Err(CliError::usage("File must have .dat extension"));
+    }
+
+    // Read element variable output from .dat file; once the file opens, the
+    // remaining failures are about its content
+    tracing::info!("Reading element variable output from: {}", path.display());
+    std::fs::File::open(path)
+        .map_err(|err| CliError::read(&err, format!("Failed to open {}: {err}", path.display())))?;
+    let data = read_dat_file(path).map_err(CliError::parse)?;
+    tracing::info!("Found {} integration points", data.len());
+
+    // Process data and compute Mises stress, effective strain, PEEQ
+    let results = process_integration_points(&data);
+
+    // Compute statistics
+    let stats = compute_statistics(&results);
+
+    // Print summary
+    println!("Statistics:");
+    println!(
+        "  Mises stress:       min={:.4e}  max={:.4e}  mean={:.4e}",
+        stats.mises_min, stats.mises_max, stats.mises_mean
+    );
+    println!(
+        "  Effective strain:   min={:.4e}  max={:.4e}  mean={:.4e}",
+        stats.eeq_min, stats.eeq_max, stats.eeq_mean
+    );
+    println!(
+        "  Plastic strain:     min={:.4e}  max={:.4e}  mean={:.4e}",
+        stats.peeq_min, stats.peeq_max, stats.peeq_mean
+    );
+
+    // Write results to file
+    write_results(path, &results, &stats).map_err(CliError::io)?;
+
+    Ok(())
+}
+
+/// Restrict `frd` to the results of `step`, if one was requested
+pub fn select_frd_step(frd: ccx_io::FrdFile, step: Option<i32>) -> Result<ccx_io::FrdFile, String> {
+    let Some(step) = step else {
+        return Ok(frd);
+    };
+    let selected = frd.select_step(step).ok_or_else(|| {
+        let available: Vec<String> = frd.steps().iter().map(i32::to_string).collect();
+        format!(
+            "step {step} not found (available: {})",
+            available.join(", ")
+        )
+    })?;
+    tracing::info!(
+        "Selected step {}: {} result block(s)",
+        step,
+        selected.result_blocks.len()
+    );
+    Ok(selected)
+}
+
+pub fn frd2vtk_file(
+    input_path: &Path,
+    output_path: &Path,
+    step: Option<i32>,
+) -> Result<(), CliError> {
+    use ccx_io::{FrdFile, VtkWriter};
+
+    // Validate file extensions
+    if !input_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("frd"))
+    {
+        return Err(CliError::usage("Input file must have .frd extension"));
+    }
+    if !output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("vtk"))
+    {
+        return Err(CliError::usage("Output file must have .vtk extension"));
+    }
+
+    // Read FRD file
+    tracing::info!("Reading FRD file: {}", input_path.display());
+    let frd = FrdFile::from_file(input_path)
+        .map_err(|err| CliError::read(&err, format!("Failed to read FRD file: {}", err)))?;
+
+    tracing::info!("Nodes: {}", frd.nodes.len());
+    tracing::info!("Elements: {}", frd.elements.len());
+    tracing::info!("Result blocks: {}", frd.result_blocks.len());
+    let frd = select_frd_step(frd, step).map_err(CliError::usage)?;
+
+    // Write VTK file
+    tracing::info!("Writing VTK file: {}", output_path.display());
+    let writer = VtkWriter::new(&frd);
+    writer
+        .write_vtk(output_path)
+        .map_err(|err| CliError::io(format!("Failed to write VTK file: {}", err)))?;
+
+    tracing::info!("Conversion complete");
+    Ok(())
+}
+
+/// Options of the `frd2vtu` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frd2VtuOptions {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub binary: bool,
+    /// zlib-compress the appended binary data
+    pub compressed: bool,
+    /// Write every eigenmode as its own point array
+    pub modes: bool,
+    /// Add principal values and direction vectors of tensor results
+    pub principal: bool,
+    /// Add max/min envelopes over all result blocks
+    pub envelope: bool,
+    /// Yield stress for the `SAFETY` factor field
+    pub yield_stress: Option<f64>,
+    /// Only export the results of this step
+    pub step: Option<i32>,
+}
+
+/// A yield stress value, or the lowest *PLASTIC yield stress of a deck
+pub fn parse_yield_stress(value: &str) -> Result<f64, String> {
+    if let Ok(stress) = value.parse::<f64>() {
+        return if stress > 0.0 {
+            Ok(stress)
+        } else {
+            Err(format!("invalid yield stress {value}"))
+        };
+    }
+    let deck = ccx_inp::Deck::parse_file(value).map_err(|err| format!("{}: {}", value, err))?;
+    let materials = ccx_solver::MaterialLibrary::build_from_deck(&deck)?;
+    materials
+        .material_names()
+        .iter()
+        .filter_map(|name| materials.get_material(name)?.yield_stress)
+        .reduce(f64::min)
+        .ok_or_else(|| format!("{value}: no material with a *PLASTIC yield stress"))
+}
+
+pub fn frd2vtu_file(options: &Frd2VtuOptions) -> Result<(), CliError> {
+    use ccx_io::{FrdFile, VtkFormat, VtkWriter};
+
+    let input_path = options.input.as_path();
+    let output_path = options.output.as_path();
+    let binary = options.binary;
+
+    // Validate file extensions
+    if !input_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("frd"))
+    {
+        return Err(CliError::usage("Input file must have .frd extension"));
+    }
+    let series = output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pvd"));
+    if !series
+        && !output_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("vtu"))
+    {
+        return Err(CliError::usage(
+            "Output file must have .vtu or .pvd extension",
+        ));
+    }
+
+    // Read FRD file
+    tracing::info!("Reading FRD file: {}", input_path.display());
+    let frd = FrdFile::from_file(input_path)
+        .map_err(|err| CliError::read(&err, format!("Failed to read FRD file: {}", err)))?;
+
+    tracing::info!("Nodes: {}", frd.nodes.len());
+    tracing::info!("Elements: {}", frd.elements.len());
+    tracing::info!("Result blocks: {}", frd.result_blocks.len());
+    let frd = select_frd_step(frd, options.step).map_err(CliError::usage)?;
+
+    // Write VTU file
+    let (format, label) = if options.compressed {
+        (VtkFormat::Compressed, "zlib-compressed binary")
+    } else if binary {
+        (VtkFormat::Binary, "binary")
+    } else {
+        (VtkFormat::Ascii, "ASCII")
+    };
+    tracing::info!("Writing VTU file ({}): {}", label, output_path.display());
+
+    let mut writer = VtkWriter::new(&frd);
+    if options.principal {
+        writer = writer.with_principal_directions();
+    }
+    if options.envelope {
+        writer = writer.with_envelopes();
+    }
+    if let Some(yield_stress) = options.yield_stress {
+        tracing::info!("Safety factor against yield stress {}", yield_stress);
+        writer = writer.with_safety_factor(yield_stress);
+    }
+    if options.modes {
+        if series {
+            return Err(CliError::usage(
+                "--modes writes a single .vtu; use a .pvd output without --modes for one file per mode",
+            ));
+        }
+        writer
+            .write_modes(output_path, format)
+            .map_err(|err| CliError::io(format!("Failed to write mode shapes: {}", err)))?;
+    } else if series {
+        let frames = writer
+            .write_series(output_path, format)
+            .map_err(|err| CliError::io(format!("Failed to write VTU series: {}", err)))?;
+        tracing::info!("Frames: {}", frames.len());
+    } else {
+        writer
+            .write_vtu(output_path, format)
+            .map_err(|err| CliError::io(format!("Failed to write VTU file: {}", err)))?;
+    }
+
+    tracing::info!("Conversion complete");
+    Ok(())
+}
+
+/// Options of the `dat2vtu` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dat2VtuOptions {
+    pub dat: PathBuf,
+    /// Deck with the mesh the `.dat` file was written for
+    pub deck: PathBuf,
+    pub output: PathBuf,
+    pub format: ccx_io::VtkFormat,
+}
+
+/// Write the `.dat` result tables on the mesh of a deck as VTU
+pub fn dat2vtu_file(options: &Dat2VtuOptions) -> Result<(), CliError> {
+    let output = options.output.as_path();
+    let series = output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pvd"));
+    if !series
+        && !output
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("vtu"))
+    {
+        return Err(CliError::usage(
+            "Output file must have .vtu or .pvd extension",
+        ));
+    }
+
+    tracing::info!("Reading mesh: {}", options.deck.display());
+    let deck =
+        read_deck(&options.deck, &[], &[]).map_err(|err| CliError::deck(&options.deck, &err))?;
+    let (mesh, _) = deck_mesh(&options.deck, &deck)?;
+    let job_name = options
+        .dat
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("job");
+    let mut frd = ccx_io::mesh_frd(&mesh, job_name);
+    tracing::info!("Nodes: {}", frd.nodes.len());
+    tracing::info!("Elements: {}", frd.elements.len());
+
+    tracing::info!("Reading .dat file: {}", options.dat.display());
+    let text = std::fs::read_to_string(&options.dat).map_err(|err| {
+        CliError::read(
+            &err,
+            format!("Failed to read {}: {}", options.dat.display(), err),
+        )
+    })?;
+    let tables = ccx_io::parse_dat_tables(&text);
+    if tables.is_empty() {
+        return Err(CliError::parse(format!(
+            "no result tables in {}",
+            options.dat.display()
+        )));
+    }
+    frd.result_blocks = ccx_io::dat_result_blocks(&tables, &frd);
+    tracing::info!("Tables: {}", tables.len());
+    tracing::info!("Result blocks: {}", frd.result_blocks.len());
+    if let Some(block) = frd.result_blocks.last() {
+        let names: Vec<String> = block
+            .datasets
+            .iter()
+            .map(|d| match d.location {
+                ccx_io::ResultLocation::Nodal => format!("{} (nodal)", d.name),
+                ccx_io::ResultLocation::Element => format!("{} (element)", d.name),
+            })
+            .collect();
+        tracing::info!("Fields: {}", names.join(", "));
+    }
+
+    tracing::info!("Writing VTU file: {}", output.display());
+    let writer = ccx_io::VtkWriter::new(&frd);
+    if series {
+        let frames = writer
+            .write_series(output, options.format)
+            .map_err(|err| CliError::io(format!("Failed to write VTU series: {}", err)))?;
+        tracing::info!("Frames: {}", frames.len());
+    } else {
+        writer
+            .write_vtu(output, options.format)
+            .map_err(|err| CliError::io(format!("Failed to write VTU file: {}", err)))?;
+    }
+
+    tracing::info!("Conversion complete");
+    Ok(())
+}
+
+/// Options of the `frd-diff` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrdDiffOptions {
+    pub reference: PathBuf,
+    pub result: PathBuf,
+    pub tolerances: ccx_io::FrdTolerances,
+    /// Step to compare in both files
+    pub step: Option<i32>,
+}
+
+/// Compare two FRD files and print per-dataset statistics, returning
+/// whether all datasets are within tolerance
+pub fn frd_diff_files(options: &FrdDiffOptions) -> Result<bool, String> {
+    let read = |path: &Path| {
+        let frd = ccx_io::FrdFile::from_file(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        select_frd_step(frd, options.step)
+    };
+    let reference = read(&options.reference)?;
+    let result = read(&options.result)?;
+    println!("reference: {}", options.reference.display());
+    println!("result:    {}", options.result.display());
+
+    let comparison = ccx_io::frd_compare(&reference, &result, &options.tolerances);
+    println!("{comparison}");
+    for failure in comparison.failures() {
+        if let Some((id, component)) = failure.worst {
+            println!(
+                "  {} step {}: largest difference at entity {} component {}",
+                failure.name,
+                failure.step,
+                id,
+                component + 1
+            );
+        }
+    }
+    Ok(comparison.passed())
+}
+
+pub struct PathPlotOptions {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// Nodal dataset to sample
+    pub dataset: String,
+    /// Samples per polyline segment
+    pub samples: usize,
+    pub step: Option<i32>,
+    pub polyline: Vec<[f64; 3]>,
+}
+
+/// Sample a nodal result along a polyline and write distance-vs-value CSV
+pub fn path_plot_file(options: &PathPlotOptions) -> Result<(), CliError> {
+    let input = options.input.as_path();
+    tracing::info!("Reading FRD file: {}", input.display());
+    let frd = ccx_io::FrdFile::from_file(input)
+        .map_err(|err| CliError::read(&err, format!("Failed to read FRD file: {}", err)))?;
+    let frd = select_frd_step(frd, options.step).map_err(CliError::usage)?;
+    let block = frd
+        .result_blocks
+        .last()
+        .ok_or_else(|| CliError::usage(format!("{} has no results", input.display())))?;
+    let samples = ccx_io::sample_path(
+        &frd,
+        block,
+        &options.dataset,
+        &options.polyline,
+        options.samples,
+    )
+    .map_err(CliError::usage)?;
+    let comp_names = block
+        .datasets
+        .iter()
+        .find(|d| d.name.eq_ignore_ascii_case(&options.dataset))
+        .map(|d| d.comp_names.clone())
+        .unwrap_or_default();
+
+    let output = options.output.as_path();
+    let file = std::fs::File::create(output)
+        .map_err(|err| CliError::io(format!("Failed to create {}: {}", output.display(), err)))?;
+    let mut out = std::io::BufWriter::new(file);
+    ccx_io::write_path_csv(&mut out, &samples, &comp_names)
+        .and_then(|()| std::io::Write::flush(&mut out))
+        .map_err(|err| CliError::io(format!("Failed to write {}: {}", output.display(), err)))?;
+    let outside = samples.iter().filter(|s| s.values.is_none()).count();
+    tracing::info!(
+        "Wrote {} ({} samples of {} at time {}, {} outside the mesh)",
+        output.display(),
+        samples.len(),
+        options.dataset,
+        block.time,
+        outside
+    );
+    Ok(())
+}
+
+/// Options of the `probe` command
+pub struct ProbeOptions {
+    /// FRD or `.dat` results
+    pub input: PathBuf,
+    /// Deck with the mesh the `.dat` results were written for
+    pub mesh: Option<PathBuf>,
+    pub nodes: Vec<i32>,
+    pub points: Vec<[f64; 3]>,
+    /// Datasets to print; all when empty
+    pub datasets: Vec<String>,
+    pub step: Option<i32>,
+    pub json: bool,
+}
+
+/// Values probed at a node or at a point
+#[derive(Debug)]
+pub enum Probe {
+    Node(i32, Vec<ccx_io::ProbeValue>),
+    Point([f64; 3], ccx_io::PointProbe),
+}
+
+/// Results of `probe`: the FRD file, or the `.dat` tables on the model of
+/// the mesh deck, if one was given
+fn probe_results(options: &ProbeOptions) -> Result<ccx_io::FrdFile, CliError> {
+    let input = options.input.as_path();
+    let read_error = |err: std::io::Error| {
+        CliError::read(&err, format!("Failed to read {}: {err}", input.display()))
+    };
+    if !input
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dat"))
+    {
+        if options.mesh.is_some() {
+            return Err(CliError::usage("--mesh only applies to .dat results"));
+        }
+        return ccx_io::FrdFile::from_file(input).map_err(read_error);
+    }
+
+    let mut frd = match &options.mesh {
+        Some(path) => {
+            let deck = read_deck(path, &[], &[]).map_err(|err| CliError::deck(path, &err))?;
+            let mesh = ccx_solver::MeshBuilder::build_from_deck(&deck)?;
+            let job_name = input.file_stem().and_then(|s| s.to_str()).unwrap_or("job");
+            ccx_io::mesh_frd(&mesh, job_name)
+        }
+        None if !options.points.is_empty() => {
+            return Err(CliError::usage(
+                "--at on .dat results needs the mesh deck (--mesh)",
+            ));
+        }
+        None => ccx_io::FrdFile::new(),
+    };
+    let text = std::fs::read_to_string(input).map_err(read_error)?;
+    frd.result_blocks = ccx_io::dat_result_blocks(&ccx_io::parse_dat_tables(&text), &frd);
+    Ok(frd)
+}
+
+/// Result block of the requested step and its values at the requested
+/// nodes and points
+fn probe_values(options: &ProbeOptions) -> Result<(ccx_io::ResultBlock, Vec<Probe>), CliError> {
+    let input = options.input.as_path();
+    let frd = select_frd_step(probe_results(options)?, options.step).map_err(CliError::usage)?;
+    let mut block = frd
+        .result_blocks
+        .last()
+        .cloned()
+        .ok_or_else(|| CliError::usage(format!("{} has no results", input.display())))?;
+    if !options.datasets.is_empty() {
+        for name in &options.datasets {
+            if !block
+                .datasets
+                .iter()
+                .any(|d| d.name.eq_ignore_ascii_case(name))
+            {
+                let available: Vec<&str> = block.datasets.iter().map(|d| d.name.as_str()).collect();
+                return Err(CliError::usage(format!(
+                    "dataset {name} not found (available: {})",
+                    available.join(", ")
+                )));
+            }
+        }
+        block.datasets.retain(|d| {
+            options
+                .datasets
+                .iter()
+                .any(|name| d.name.eq_ignore_ascii_case(name))
+        });
+    }
+
+    let mut probes = Vec::new();
+    for &node in &options.nodes {
+        let values = ccx_io::probe_node(&block, node);
+        if values.is_empty() {
+            return Err(CliError::usage(format!("node {node} has no nodal results")));
+        }
+        probes.push(Probe::Node(node, values));
+    }
+    if !options.points.is_empty() {
+        let (mesh, _) = ccx_io::frd_mesh(&frd);
+        for &point in &options.points {
+            let probe = ccx_io::probe_point(&mesh, &block, point).ok_or_else(|| {
+                let [x, y, z] = point;
+                let nearest = match mesh.spatial_index().nearest_node(point) {
+                    Some((id, distance)) => format!(" (nearest node {id} at {distance:.6e})"),
+                    None => String::new(),
+                };
+                CliError::usage(format!(
+                    "point ({x}, {y}, {z}) is not inside a solid element{nearest}"
+                ))
+            })?;
+            probes.push(Probe::Point(point, probe));
+        }
+    }
+    Ok((block, probes))
+}
+
+/// Print the results at the requested nodes and points
+pub fn probe_file(options: &ProbeOptions) -> Result<(), CliError> {
+    let input = options.input.as_path();
+    let (block, probes) = probe_values(options)?;
+    if options.json {
+        return print_json(&probe_json(input, &block, &probes)).map_err(CliError::io);
+    }
+    println!(
+        "{} (step {}, time {})",
+        input.display(),
+        block.step,
+        block.time
+    );
+    print!("{}", format_probes(&probes));
+    Ok(())
+}
+
+/// Text table of probed values, one line per dataset under each target
+fn format_probes(probes: &[Probe]) -> String {
+    let mut text = String::new();
+    for probe in probes {
+        let values = match probe {
+            Probe::Node(node, values) => {
+                text.push_str(&format!("node {node}\n"));
+                values
+            }
+            Probe::Point([x, y, z], probe) => {
+                text.push_str(&format!(
+                    "point ({x}, {y}, {z}) in element {}\n",
+                    probe.location.element
+                ));
+                &probe.values
+            }
+        };
+        for value in values {
+            let components: Vec<String> = value
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| match value.comp_names.get(i) {
+                    Some(name) => format!("{name}={v:.6e}"),
+                    None => format!("{}={v:.6e}", i + 1),
+                })
+                .collect();
+            text.push_str(&format!(
+                "  {:<10} {}\n",
+                value.dataset,
+                components.join(" ")
+            ));
+        }
+    }
+    text
+}
+
+/// Values of `probe --json`
+fn probe_json(path: &Path, block: &ccx_io::ResultBlock, probes: &[Probe]) -> serde_json::Value {
+    let datasets = |values: &[ccx_io::ProbeValue]| -> serde_json::Value {
+        values
+            .iter()
+            .map(|value| {
+                let components: serde_json::Map<String, serde_json::Value> = value
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        let name = value.comp_names.get(i).cloned();
+                        (
+                            name.unwrap_or_else(|| (i + 1).to_string()),
+                            serde_json::json!(v),
+                        )
+                    })
+                    .collect();
+                (value.dataset.clone(), serde_json::Value::Object(components))
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    };
+    let probes: Vec<serde_json::Value> = probes
+        .iter()
+        .map(|probe| match probe {
+            Probe::Node(node, values) => serde_json::json!({
+                "node": node,
+                "values": datasets(values),
+            }),
+            Probe::Point(point, probe) => serde_json::json!({
+                "point": point,
+                "element": probe.location.element,
+                "natural": probe.location.natural,
+                "values": datasets(&probe.values),
+            }),
+        })
+        .collect();
+    serde_json::json!({
+        "input": path.display().to_string(),
+        "step": block.step,
+        "time": block.time,
+        "probes": probes,
+    })
+}
+
+pub struct FrfOptions {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub response: ccx_io::HarmonicDof,
+    /// Reference DOF; the response is divided by it when given
+    pub excitation: Option<ccx_io::HarmonicDof>,
+}
+
+/// Parse a `<node>:<dof>` pair of a harmonic result dataset
+pub fn parse_harmonic_dof(value: &str, dataset: &str) -> Result<ccx_io::HarmonicDof, String> {
+    let (node, component) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected <node>:<dof>, got {value}"))?;
+    let node = node
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| format!("invalid node in {value}"))?;
+    let component = match component.trim().parse::<usize>() {
+        Ok(c) if c > 0 => c,
+        _ => return Err(format!("invalid dof in {value}")),
+    };
+    Ok(ccx_io::HarmonicDof {
+        dataset: dataset.to_string(),
+        node,
+        component,
+    })
+}
+
+/// Write the complex response of one DOF over frequency, or its transfer
+/// function to an excitation DOF, from steady-state dynamics results
+pub fn frf_file(options: &FrfOptions) -> Result<(), CliError> {
+    let input = options.input.as_path();
+    tracing::info!("Reading FRD file: {}", input.display());
+    let frd = ccx_io::FrdFile::from_file(input)
+        .map_err(|err| CliError::read(&err, format!("Failed to read FRD file: {}", err)))?;
+    let points = match &options.excitation {
+        Some(excitation) => {
+            ccx_io::frequency_response_function(&frd, excitation, &options.response)
+        }
+        None => ccx_io::harmonic_response(&frd, &options.response),
+    }
+    .map_err(CliError::usage)?;
+
+    let output = options.output.as_path();
+    let file = std::fs::File::create(output)
+        .map_err(|err| CliError::io(format!("Failed to create {}: {}", output.display(), err)))?;
+    let mut out = std::io::BufWriter::new(file);
+    ccx_io::write_harmonic_csv(&mut out, &points)
+        .and_then(|()| std::io::Write::flush(&mut out))
+        .map_err(|err| CliError::io(format!("Failed to write {}: {}", output.display(), err)))?;
+    let peak = points
+        .iter()
+        .max_by(|a, b| a.amplitude().total_cmp(&b.amplitude()))
+        .expect("response has at least one frequency");
+    tracing::info!(
+        "Wrote {} ({} frequencies, peak amplitude {:.6e} at {})",
+        output.display(),
+        points.len(),
+        peak.amplitude(),
+        peak.frequency
+    );
+    Ok(())
+}
+
+/// Convert Nastran OP2 results to an ASCII FRD file
+pub fn op2_to_frd_file(input: &Path, output: &Path, element_stress: bool) -> Result<(), CliError> {
+    use ccx_io::nastran::{Op2File, Op2ToFrdConverter};
+
+    tracing::info!("Reading OP2 file: {}", input.display());
+    let op2 = Op2File::from_file(input)
+        .map_err(|err| CliError::read(&err, format!("Failed to read OP2 file: {err}")))?;
+    tracing::info!("Grids: {}", op2.grids.len());
+    tracing::info!("Elements: {}", op2.elements.len());
+    tracing::info!("Displacement tables: {}", op2.displacements.len());
+    tracing::info!("Stress tables: {}", op2.stresses.len());
+
+    let frd = Op2ToFrdConverter::new(&op2)
+        .with_element_stresses(element_stress)
+        .convert();
+    tracing::info!("Writing FRD file: {}", output.display());
+    ccx_io::write_frd(output, &frd)
+        .map_err(|err| CliError::io(format!("Failed to write FRD file: {err}")))?;
+    tracing::info!("Conversion complete");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{
+        Dat2VtuArgs, Frd2VtkArgs, Frd2VtuArgs, FrdDiffArgs, FrfArgs, PathPlotArgs, ProbeArgs,
+        parse_args,
+    };
+    use crate::error::ErrorKind;
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn dat2vtu_writes_point_and_cell_fields() {
+        let root = unique_temp_dir("ccx_cli_dat2vtu");
+        fs::create_dir_all(&root).expect("create temp dir");
+        fs::write(
+            root.join("bar.inp"),
+            "*PARAMETER\nlen=1.\n*NODE\n1,0,0,0\n2,<len>,0,0\n\
+             *ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n",
+        )
+        .expect("write deck");
+        fs::write(
+            root.join("bar.dat"),
+            "\n displacements (vx,vy,vz) for set NALL and time  0.1000000E+01\n\n\
+             \x20        2  1.000000E-03  0.000000E+00  0.000000E+00\n\n\
+             \x20stresses (elem, integ.pnt.,sxx,syy,szz,sxy,sxz,syz) for set EALL and time  \
+             0.1000000E+01\n\n\
+             \x20        1   1  1.000000E+02  0.0  0.0  0.0  0.0  0.0\n",
+        )
+        .expect("write dat");
+        let args: Vec<String> = ["bar.dat", "bar.inp", "bar.vtu"]
+            .iter()
+            .map(|name| root.join(name).display().to_string())
+            .collect();
+
+        dat2vtu_file(&parse_args::<Dat2VtuArgs>(&args).unwrap()).unwrap();
+        let vtu = fs::read_to_string(root.join("bar.vtu")).unwrap();
+        assert!(
+            vtu.contains("Name=\"DISP\" NumberOfComponents=\"3\""),
+            "{vtu}"
+        );
+        assert!(vtu.contains("<CellData>"), "{vtu}");
+        assert_eq!(vtu.matches("Name=\"STRESS\"").count(), 2, "{vtu}");
+    }
+
+    #[test]
+    fn frd_diff_fails_outside_tolerance() {
+        let root = unique_temp_dir("ccx_cli_frd_diff");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let write = |name: &str, u: f64| {
+            let mut frd = ccx_io::FrdFile::new();
+            frd.nodes.insert(1, [0.0, 0.0, 0.0]);
+            frd.nodes.insert(2, [1.0, 0.0, 0.0]);
+            let values = [(1, vec![0.0; 3]), (2, vec![u, 0.0, 0.0])].into();
+            frd.result_blocks.push(ccx_io::ResultBlock {
+                step: 1,
+                time: 1.0,
+                mode: None,
+                datasets: vec![ccx_io::ResultDataset::nodal("DISP", values)],
+            });
+            ccx_io::write_frd(root.join(name), &frd).expect("write frd");
+        };
+        write("a.frd", 1.0e-3);
+        write("b.frd", 1.1e-3);
+        let args = |extra: &[&str]| {
+            let mut args: Vec<String> = extra.iter().map(|s| s.to_string()).collect();
+            args.extend(["a.frd", "b.frd"].map(|name| root.join(name).display().to_string()));
+            parse_args::<FrdDiffArgs>(&args).unwrap()
+        };
+
+        assert_eq!(frd_diff_files(&args(&["--rtol", "1e-5"])), Ok(false));
+        assert_eq!(frd_diff_files(&args(&["--rtol", "0.2"])), Ok(true));
+        let loose = args(&["--tolerance", "disp=0,0.2"]);
+        assert_eq!(loose.tolerances.datasets["DISP"], (0.0, 0.2));
+        assert_eq!(frd_diff_files(&loose), Ok(true));
+    }
+
+    #[test]
+    fn probe_prints_values_at_nodes_and_points() {
+        let root = unique_temp_dir("ccx_cli_probe");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let brick = ccx_solver::MeshBuilder::brick(
+            [2.0, 1.0, 1.0],
+            [2, 1, 1].map(ccx_solver::Division::uniform),
+            ccx_solver::ElementType::C3D8,
+        )
+        .unwrap();
+        let mut frd = ccx_io::mesh_frd(&brick.mesh, "bar");
+        let disp = brick
+            .mesh
+            .nodes
+            .values()
+            .map(|node| (node.id, vec![0.01 * node.x, 0.0, 0.02 * node.z]))
+            .collect();
+        frd.result_blocks.push(ccx_io::ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets: vec![ccx_io::ResultDataset::nodal("DISP", disp)],
+        });
+        let results = root.join("bar.frd");
+        ccx_io::write_frd(&results, &frd).expect("write frd");
+
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let results_arg = results.display().to_string();
+        assert!(parse_args::<ProbeArgs>(&to_args(&[&results_arg])).is_err());
+        assert!(parse_args::<ProbeArgs>(&to_args(&["--at", "1,0", &results_arg])).is_err());
+        let args = to_args(&[
+            "--node",
+            "2",
+            "--at",
+            "1.5,0.5,1",
+            "--dataset",
+            "disp",
+            &results_arg,
+        ]);
+        let options = parse_args::<ProbeArgs>(&args).unwrap();
+        assert_eq!(options.points, vec![[1.5, 0.5, 1.0]]);
+        let (block, probes) = probe_values(&options).unwrap();
+        assert_eq!(block.step, 1);
+        let text = format_probes(&probes);
+        assert!(
+            text.starts_with("node 2\n  DISP       D1=1.000000e-2 D2=0.000000e0 D3=0.000000e0\n"),
+            "{text}"
+        );
+        let point = "point (1.5, 0.5, 1) in element 2\n";
+        let disp = "  DISP       D1=1.500000e-2 D2=0.000000e0 D3=2.000000e-2\n";
+        assert!(text.ends_with(&format!("{point}{disp}")), "{text}");
+        let json = probe_json(&results, &block, &probes);
+        assert_eq!(json["probes"][1]["element"], 2);
+        assert_eq!(json["probes"][0]["values"]["DISP"]["D1"], 0.01);
+        probe_file(&options).unwrap();
+
+        let outside = to_args(&["--at", "-1,0,0", &results_arg]);
+        let err = probe_values(&parse_args::<ProbeArgs>(&outside).unwrap()).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Usage);
+        assert!(
+            err.message.contains("nearest node 1 at 1.000000e0"),
+            "{}",
+            err.message
+        );
+        let unknown = to_args(&["--node", "1", "--dataset", "STRESS", &results_arg]);
+        let err = probe_values(&parse_args::<ProbeArgs>(&unknown).unwrap()).unwrap_err();
+        assert!(err.message.contains("available: DISP"), "{}", err.message);
+
+        fs::write(root.join("bar.dat"), "").unwrap();
+        let dat = root.join("bar.dat").display().to_string();
+        let err =
+            probe_values(&parse_args::<ProbeArgs>(&to_args(&["--at", "0,0,0", &dat])).unwrap())
+                .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Usage);
+    }
+
+    #[test]
+    fn parse_path_plot_args_reads_polyline() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = parse_args::<PathPlotArgs>(&to_args(&[
+            "--dataset",
+            "DISP",
+            "--samples",
+            "5",
+            "a.frd",
+            "p.csv",
+            "0,0,0",
+            "-1,2.5,3",
+        ]))
+        .unwrap();
+        assert_eq!(options.dataset, "DISP");
+        assert_eq!(options.samples, 5);
+        assert_eq!(options.polyline, vec![[0.0, 0.0, 0.0], [-1.0, 2.5, 3.0]]);
+        assert_eq!(options.input, PathBuf::from("a.frd"));
+
+        let defaults = parse_args::<PathPlotArgs>(&to_args(&["a.frd", "p.csv", "0,0,0", "1,0,0"]));
+        assert_eq!(defaults.unwrap().dataset, "STRESS");
+        assert!(parse_args::<PathPlotArgs>(&to_args(&["a.frd", "p.csv", "0,0,0"])).is_err());
+        assert!(parse_args::<PathPlotArgs>(&to_args(&["a.frd", "p.csv", "0,0", "1,0,0"])).is_err());
+        let late_option = to_args(&["a.frd", "p.csv", "0,0,0", "1,0,0", "--step", "2"]);
+        assert!(parse_args::<PathPlotArgs>(&late_option).is_err());
+        let no_samples = to_args(&["--samples", "0", "a", "b", "0,0,0", "1,1,1"]);
+        assert!(parse_args::<PathPlotArgs>(&no_samples).is_err());
+    }
+
+    #[test]
+    fn parse_frf_args_reads_dofs() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options =
+            parse_args::<FrfArgs>(&to_args(&["--excitation", "1:3", "a.frd", "h.csv", "12:2"]))
+                .unwrap();
+        assert_eq!(options.response.dataset, "DISP");
+        assert_eq!((options.response.node, options.response.component), (12, 2));
+        assert_eq!(options.excitation.map(|dof| dof.node), Some(1));
+
+        let forces =
+            parse_args::<FrfArgs>(&to_args(&["--dataset", "forc", "a", "b", "4:1"])).unwrap();
+        assert_eq!(forces.response.dataset, "FORC");
+        assert!(forces.excitation.is_none());
+        assert!(parse_args::<FrfArgs>(&to_args(&["a.frd", "h.csv"])).is_err());
+        assert!(parse_args::<FrfArgs>(&to_args(&["a.frd", "h.csv", "12"])).is_err());
+        assert!(parse_args::<FrfArgs>(&to_args(&["a.frd", "h.csv", "12:0"])).is_err());
+    }
+
+    #[test]
+    fn parse_frd2vtu_args_accepts_flags_in_any_position() {
+        let args: Vec<String> = ["modal.frd", "--modes", "--binary", "modes.vtu"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = parse_args::<Frd2VtuArgs>(&args).expect("valid arguments");
+        assert_eq!(options.input, PathBuf::from("modal.frd"));
+        assert_eq!(options.output, PathBuf::from("modes.vtu"));
+        assert!(options.binary && options.modes);
+        assert!(!options.principal);
+        let principal: Vec<String> = vec!["--principal".into(), "a.frd".into(), "b.vtu".into()];
+        assert!(
+            parse_args::<Frd2VtuArgs>(&principal)
+                .expect("valid")
+                .principal
+        );
+        let envelope: Vec<String> = vec!["--envelope".into(), "a.frd".into(), "b.vtu".into()];
+        assert!(
+            parse_args::<Frd2VtuArgs>(&envelope)
+                .expect("valid")
+                .envelope
+        );
+        let with_yield = |value: &str| {
+            let args: Vec<String> = vec![
+                "--yield".into(),
+                value.into(),
+                "a.frd".into(),
+                "b.vtu".into(),
+            ];
+            parse_args::<Frd2VtuArgs>(&args)
+        };
+        assert_eq!(with_yield("235").expect("valid").yield_stress, Some(235.0));
+        assert!(with_yield("-1").is_err());
+
+        let unknown: Vec<String> = vec!["a.frd".into(), "b.vtu".into(), "--fast".into()];
+        assert!(parse_args::<Frd2VtuArgs>(&unknown).is_err());
+    }
+
+    #[test]
+    fn parse_step_flag_for_frd_conversions() {
+        let args: Vec<String> = ["--step", "2", "job.frd", "job.vtk"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (input, output, step) = parse_args::<Frd2VtkArgs>(&args).expect("valid arguments");
+        assert_eq!(
+            (input, output, step),
+            ("job.frd".into(), "job.vtk".into(), Some(2))
+        );
+
+        let args: Vec<String> = ["job.frd", "job.vtu", "--step", "3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            parse_args::<Frd2VtuArgs>(&args).expect("valid").step,
+            Some(3)
+        );
+
+        let missing: Vec<String> = vec!["job.frd".into(), "job.vtk".into(), "--step".into()];
+        assert!(parse_args::<Frd2VtkArgs>(&missing).is_err());
+        let invalid: Vec<String> = vec!["--step".into(), "x".into()];
+        assert!(parse_args::<Frd2VtkArgs>(&invalid).is_err());
+    }
+}
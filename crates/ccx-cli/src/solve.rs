@@ -0,0 +1,845 @@
+//! Static and modal analyses of `solve` and `modes`, with the result files
+//! they write.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::{CliError, ErrorKind};
+use crate::{print_json, read_deck};
+
+/// Result file written by `solve`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveFormat {
+    Dat,
+    Frd,
+    Vtu,
+}
+
+impl SolveFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            SolveFormat::Dat => "dat",
+            SolveFormat::Frd => "frd",
+            SolveFormat::Vtu => "vtu",
+        }
+    }
+}
+
+/// Parse a comma-separated `--format` list such as `dat,frd,vtu`
+pub fn parse_solve_formats(value: &str) -> Result<Vec<SolveFormat>, String> {
+    let mut formats = Vec::new();
+    for name in value.split(',').map(str::trim) {
+        let format = match name.to_ascii_lowercase().as_str() {
+            "dat" => SolveFormat::Dat,
+            "frd" => SolveFormat::Frd,
+            "vtu" => SolveFormat::Vtu,
+            _ => return Err(format!("unknown output format {name}")),
+        };
+        if !formats.contains(&format) {
+            formats.push(format);
+        }
+    }
+    Ok(formats)
+}
+
+pub struct SolveOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    /// Linear solver backend; the pipeline default when absent
+    pub backend: Option<ccx_solver::LinearSolverKind>,
+    /// Nodal stress and strain averaging
+    pub averaging: ccx_solver::NodalAveraging,
+    /// CSV or JSON file for the `*NODE PRINT`/`*EL PRINT` history table
+    pub history: Option<PathBuf>,
+    /// Unit system for the plausibility checks; a `** UNITS:` comment of the
+    /// deck when absent
+    pub units: Option<ccx_solver::UnitSystem>,
+    /// Solve despite inverted or degenerate elements
+    pub warn_inverted: bool,
+    /// Directory for the result files; the input's directory when absent
+    pub output_dir: Option<PathBuf>,
+    /// Base name of the result files; the input's file stem when absent
+    pub job_name: Option<String>,
+    /// Result files to write
+    pub formats: Vec<SolveFormat>,
+    /// Library with the legacy routines of the element types without a port
+    pub legacy_elements: Option<PathBuf>,
+    /// Usage log the legacy units reading the deck are counted in
+    pub usage_log: Option<PathBuf>,
+    /// Print the results as JSON instead of text
+    pub json: bool,
+}
+
+impl SolveOptions {
+    /// Directory and base name of the result files
+    fn job(&self) -> (PathBuf, String) {
+        job_location(
+            &self.input,
+            self.output_dir.as_deref(),
+            self.job_name.as_deref(),
+        )
+    }
+}
+
+/// Directory and base name of the result files of the deck `input`; its
+/// directory and file stem unless given
+pub fn job_location(
+    input: &Path,
+    output_dir: Option<&Path>,
+    job_name: Option<&str>,
+) -> (PathBuf, String) {
+    let dir = match output_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => input.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    let name = match job_name {
+        Some(name) => name.to_string(),
+        None => input
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("job")
+            .to_string(),
+    };
+    (dir, name)
+}
+
+pub fn solve_file(options: &SolveOptions) -> Result<(), CliError> {
+    let legacy = match &options.legacy_elements {
+        Some(path) => {
+            let library = ccx_compat::LegacyLibrary::open(path)
+                .map_err(|err| CliError::new(ErrorKind::Io, err.to_string()))?;
+            // SAFETY: --legacy-elements takes libraries whose element routines
+            // follow the interface of ccx_solver::legacy_elements
+            Some(Arc::new(unsafe {
+                ccx_solver::LegacyElements::bind(&library)
+            }))
+        }
+        None => None,
+    };
+    solve_with_legacy(options, legacy)
+}
+
+/// Solve with the element routines `legacy` for the element types the
+/// solver has no formulation for
+pub(crate) fn solve_with_legacy(
+    options: &SolveOptions,
+    legacy: Option<Arc<ccx_solver::LegacyElements>>,
+) -> Result<(), CliError> {
+    use ccx_solver::AnalysisPipeline;
+
+    let path = options.input.as_path();
+    let overrides = &options.overrides;
+    let deck = read_deck(path, overrides, &options.include_paths)
+        .map_err(|err| CliError::deck(path, &err))?;
+    // Print requests the .dat output cannot serve fail before the solve
+    ccx_io::HistoryRequest::from_deck(&deck)
+        .map_err(|err| CliError::new(ErrorKind::Validation, err))?;
+
+    tracing::info!("Initializing solver for: {}", path.display());
+    for (name, value) in overrides {
+        tracing::info!("Parameter {} = {}", name, value);
+    }
+
+    let mut pipeline = AnalysisPipeline::detect_from_deck(&deck);
+    if let Some(backend) = options.backend {
+        pipeline = pipeline.with_linear_solver(backend);
+    }
+    pipeline = pipeline.with_nodal_averaging(options.averaging.clone());
+    if let Some(units) = options.units {
+        pipeline = pipeline.with_unit_system(units);
+    }
+    if options.warn_inverted {
+        pipeline = pipeline.with_inverted_elements(ccx_solver::InvertedElementAction::Warn);
+    }
+    let (dir, job_name) = options.job();
+    if let Some(legacy) = &legacy {
+        legacy.set_job(&job_name);
+        tracing::info!("Legacy element routines: {:?}", legacy);
+        pipeline = pipeline.with_legacy_elements(legacy.clone());
+    }
+    tracing::info!(
+        "Detected analysis type: {:?}",
+        pipeline.config().analysis_type
+    );
+    tracing::info!(
+        "Linear solver: {}",
+        pipeline.config().linear_solver.create().name()
+    );
+
+    // The .sta file exists while the step runs, like for ccx
+    let mut monitor = ccx_io::ConvergenceMonitor::create(&dir, &job_name).map_err(|err| {
+        CliError::io(format!(
+            "Failed to create {}: {}",
+            dir.join(format!("{job_name}.sta")).display(),
+            err
+        ))
+    })?;
+    let record_error =
+        |err: std::io::Error| CliError::io(format!("Failed to write .sta file: {}", err));
+    let results = pipeline.run(&deck);
+    // Calls of failed runs count as well
+    if let Some(log) = &options.usage_log {
+        legacy
+            .as_ref()
+            .map(|legacy| legacy.take_usage())
+            .unwrap_or_default()
+            .save(log)
+            .map_err(CliError::io)?;
+    }
+    let results = match results {
+        Ok(results) => results,
+        Err(err) => {
+            monitor.record_failure(&err).map_err(record_error)?;
+            return Err(CliError::new(ErrorKind::Validation, err));
+        }
+    };
+    match &results.failure {
+        Some(failure) => monitor.record_failure(&failure.to_string()),
+        // A linear static step is one increment solved in one iteration
+        None if !results.displacements.is_empty() => {
+            monitor.record_increment(&ccx_io::IncrementRecord {
+                step: 1,
+                increment: 1,
+                attempts: 1,
+                iterations: 1,
+                total_time: 1.0,
+                step_time: 1.0,
+                increment_time: 1.0,
+            })
+        }
+        None => Ok(()),
+    }
+    .map_err(record_error)?;
+    let warnings = solve_warnings(&results);
+    for warning in &warnings {
+        tracing::warn!("{}", warning);
+    }
+    if !options.json {
+        println!("Analysis Results:");
+        println!(
+            "  Status: {}",
+            if results.success && results.failure.is_none() {
+                "SUCCESS"
+            } else {
+                "FAILED"
+            }
+        );
+        println!("  DOFs: {}", results.num_dofs);
+        println!("  Equations: {}", results.num_equations);
+        println!("  Message: {}", results.message);
+        if let Some(info) = &results.solve_info {
+            println!("  Solver: {}", info.summary());
+        }
+        if let Some(estimate) = &results.error_estimate {
+            println!(
+                "  Error estimate (ZZ): {:.2} % (element FRD dataset ERROR)",
+                estimate.global_relative_error
+            );
+        }
+    }
+    let mut written = vec![
+        monitor.sta_path().to_path_buf(),
+        monitor.cvg_path().to_path_buf(),
+    ];
+    if !results.displacements.is_empty() {
+        written = write_solve_outputs(
+            &dir,
+            &job_name,
+            &deck,
+            &results,
+            options.history.as_deref(),
+            &options.formats,
+        )
+        .map_err(CliError::io)?
+        .into_iter()
+        .chain(written)
+        .collect();
+    }
+    if options.json {
+        print_json(&solve_json(path, &results, &warnings, &written)).map_err(CliError::io)?;
+    }
+    match &results.failure {
+        Some(failure) => Err(CliError::solve(failure)),
+        None => Ok(()),
+    }
+}
+
+/// Audit, unit, element and solver warnings of a solve
+pub fn solve_warnings(results: &ccx_solver::AnalysisResults) -> Vec<String> {
+    let mut warnings = results.audit.warnings();
+    warnings.extend(results.unit_warnings.iter().cloned());
+    warnings.extend(results.inverted_elements.iter().map(|e| e.describe()));
+    if let Some(info) = &results.solve_info {
+        warnings.extend(info.warnings());
+    }
+    warnings
+}
+
+/// Results of `solve --json`
+pub fn solve_json(
+    path: &Path,
+    results: &ccx_solver::AnalysisResults,
+    warnings: &[String],
+    written: &[PathBuf],
+) -> serde_json::Value {
+    let solver = results.solve_info.as_ref().map(|info| {
+        serde_json::json!({
+            "backend": info.backend,
+            "equations": info.num_equations,
+            "nnz": info.nnz,
+            "factor_nnz": info.factor_nnz,
+            "peak_memory_bytes": info.peak_memory_bytes,
+            "factorization_seconds": info.factorization_time.as_secs_f64(),
+            "solve_seconds": info.solve_time.as_secs_f64(),
+            "condition_estimate": info.condition_estimate,
+        })
+    });
+    let max_displacement = results
+        .displacements
+        .iter()
+        .map(|(node, u)| (*node, u.iter().map(|c| c * c).sum::<f64>().sqrt()))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(node, magnitude)| serde_json::json!({ "node": node, "magnitude": magnitude }));
+    let outputs: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+    serde_json::json!({
+        "input": path.display().to_string(),
+        "analysis_type": format!("{:?}", results.analysis_type),
+        "success": results.success && results.failure.is_none(),
+        "failure": results.failure.as_ref().map(ToString::to_string),
+        "dofs": results.num_dofs,
+        "equations": results.num_equations,
+        "message": results.message,
+        "solver": solver,
+        "max_displacement": max_displacement,
+        "error_estimate_percent": results.error_estimate.as_ref().map(|e| e.global_relative_error),
+        "warnings": warnings,
+        "outputs": outputs,
+    })
+}
+
+/// Write `<job>.dat`, `<job>.frd` and `<job>.vtu` as requested into `dir`,
+/// returning the files written
+pub fn write_solve_outputs(
+    dir: &Path,
+    job_name: &str,
+    deck: &ccx_inp::Deck,
+    results: &ccx_solver::AnalysisResults,
+    history_path: Option<&Path>,
+    formats: &[SolveFormat],
+) -> Result<Vec<PathBuf>, String> {
+    let mut written = Vec::new();
+    let mesh = ccx_solver::MeshBuilder::build_from_deck(deck)?;
+    let output_path =
+        |format: SolveFormat| dir.join(format!("{}.{}", job_name, format.extension()));
+
+    let (history, dat_steps) = ccx_io::static_dat_steps(deck, results)?;
+    if formats.contains(&SolveFormat::Dat) {
+        let dat_path = output_path(SolveFormat::Dat);
+        ccx_io::write_dat_results(&dat_path, &dat_steps)
+            .map_err(|err| format!("Failed to write {}: {}", dat_path.display(), err))?;
+        tracing::info!("Wrote {}", dat_path.display());
+        written.push(dat_path);
+    }
+
+    if let Some(history_path) = history_path {
+        let file = std::fs::File::create(history_path)
+            .map_err(|err| format!("Failed to create {}: {}", history_path.display(), err))?;
+        let mut out = std::io::BufWriter::new(file);
+        let json = history_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if json {
+            history.write_json(&mut out)
+        } else {
+            history.write_csv(&mut out)
+        }
+        .and_then(|()| std::io::Write::flush(&mut out))
+        .map_err(|err| format!("Failed to write {}: {}", history_path.display(), err))?;
+        tracing::info!(
+            "Wrote {} ({} history values)",
+            history_path.display(),
+            history.records().len()
+        );
+        written.push(history_path.to_path_buf());
+    }
+
+    let frd = ccx_io::static_frd(&mesh, results, job_name);
+    if formats.contains(&SolveFormat::Frd) {
+        let frd_path = output_path(SolveFormat::Frd);
+        ccx_io::write_frd(&frd_path, &frd)
+            .map_err(|err| format!("Failed to write {}: {}", frd_path.display(), err))?;
+        tracing::info!("Wrote {}", frd_path.display());
+        written.push(frd_path);
+    }
+    if formats.contains(&SolveFormat::Vtu) {
+        let vtu_path = output_path(SolveFormat::Vtu);
+        ccx_io::VtkWriter::new(&frd)
+            .write_vtu(&vtu_path, ccx_io::VtkFormat::Ascii)
+            .map_err(|err| format!("Failed to write {}: {}", vtu_path.display(), err))?;
+        tracing::info!("Wrote {}", vtu_path.display());
+        written.push(vtu_path);
+    }
+    Ok(written)
+}
+
+pub struct ModesOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    /// Number of modes; the `*FREQUENCY` card's, else 10, when absent
+    pub num_modes: Option<usize>,
+    /// Shift of the eigenvalues ω²
+    pub shift: f64,
+    /// Linear solver backend; the Lanczos default when absent
+    pub backend: Option<ccx_solver::LinearSolverKind>,
+    /// Directory for the mode shape files; the input's directory when absent
+    pub output_dir: Option<PathBuf>,
+    /// Base name of the mode shape files; the input's file stem when absent
+    pub job_name: Option<String>,
+    /// Mode shape files to write, `frd` and `vtu`
+    pub formats: Vec<SolveFormat>,
+    /// Print the modes as JSON instead of a table
+    pub json: bool,
+}
+
+/// Modes extracted when neither `--num` nor a `*FREQUENCY` card give a count
+const DEFAULT_MODES: usize = 10;
+
+pub fn modes_file(options: &ModesOptions) -> Result<(), CliError> {
+    let path = options.input.as_path();
+    let deck = read_deck(path, &options.overrides, &options.include_paths)
+        .map_err(|err| CliError::deck(path, &err))?;
+    let num_modes = options
+        .num_modes
+        .or_else(|| ccx_solver::requested_modes(&deck))
+        .unwrap_or(DEFAULT_MODES);
+    let mut solver = ccx_solver::ModalSolver::new(num_modes).with_shift(options.shift);
+    if let Some(backend) = options.backend {
+        solver = solver.with_linear_solver(backend);
+    }
+    tracing::info!("Extracting {} modes of {}", num_modes, path.display());
+    let results = solver
+        .solve(&deck)
+        .map_err(|err| CliError::new(ErrorKind::Solver, err))?;
+    if !results.skipped.is_empty() {
+        tracing::warn!(
+            "{} elements carry no mass (no section, density, thickness or area)",
+            results.skipped.len()
+        );
+    }
+
+    let mut written = Vec::new();
+    if !options.formats.is_empty() {
+        let (dir, job_name) = job_location(
+            path,
+            options.output_dir.as_deref(),
+            options.job_name.as_deref(),
+        );
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(&dir).map_err(|err| {
+                CliError::io(format!("Failed to create {}: {}", dir.display(), err))
+            })?;
+        }
+        written = write_mode_shapes(&dir, &job_name, &deck, &results, &options.formats)
+            .map_err(CliError::io)?;
+    }
+    if options.json {
+        print_json(&modes_json(path, &results, &written)).map_err(CliError::io)?;
+    } else {
+        print!("{}", format_modes(&results));
+    }
+    Ok(())
+}
+
+/// Frequency table of `modes` with participation factors and effective
+/// mass fractions
+fn format_modes(results: &ccx_solver::ModalResults) -> String {
+    let mut text = format!(
+        "{:>4} {:>14} {:>12} {:>12} {:>12} {:>8} {:>8} {:>8}\n",
+        "mode", "frequency/Hz", "gamma_x", "gamma_y", "gamma_z", "meff_x", "meff_y", "meff_z"
+    );
+    let mut cumulative = [0.0; 3];
+    for mode in &results.modes {
+        let fractions: [f64; 3] = std::array::from_fn(|i| results.effective_mass_fraction(mode, i));
+        for (sum, fraction) in cumulative.iter_mut().zip(fractions) {
+            *sum += fraction;
+        }
+        let [gx, gy, gz] = mode.participation;
+        let [fx, fy, fz] = fractions.map(|f| f * 100.0);
+        text.push_str(&format!(
+            "{:>4} {:>14.6e} {:>12.4e} {:>12.4e} {:>12.4e} {:>7.2}% {:>7.2}% {:>7.2}%\n",
+            mode.number, mode.frequency, gx, gy, gz, fx, fy, fz
+        ));
+    }
+    let [cx, cy, cz] = cumulative.map(|f| f * 100.0);
+    text.push_str(&format!(
+        "{:<4} {:>53} {:>7.2}% {:>7.2}% {:>7.2}%\n",
+        "sum", "", cx, cy, cz
+    ));
+    text
+}
+
+/// Results of `modes --json`
+fn modes_json(
+    path: &Path,
+    results: &ccx_solver::ModalResults,
+    written: &[PathBuf],
+) -> serde_json::Value {
+    let modes: Vec<serde_json::Value> = results
+        .modes
+        .iter()
+        .map(|mode| {
+            let fractions: [f64; 3] =
+                std::array::from_fn(|i| results.effective_mass_fraction(mode, i));
+            serde_json::json!({
+                "mode": mode.number,
+                "eigenvalue": mode.eigenvalue,
+                "frequency": mode.frequency,
+                "participation": mode.participation,
+                "effective_mass": mode.effective_mass,
+                "effective_mass_fraction": fractions,
+            })
+        })
+        .collect();
+    let outputs: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+    serde_json::json!({
+        "input": path.display().to_string(),
+        "modes": modes,
+        "free_mass": results.free_mass,
+        "massless_elements": results.skipped.len(),
+        "lanczos_iterations": results.iterations,
+        "outputs": outputs,
+    })
+}
+
+/// Write the mode shapes as `<job>.frd` with one result block per mode and
+/// as `<job>.vtu` with one `DISP_MODE_<n>` array per mode, as requested,
+/// returning the files written
+fn write_mode_shapes(
+    dir: &Path,
+    job_name: &str,
+    deck: &ccx_inp::Deck,
+    results: &ccx_solver::ModalResults,
+    formats: &[SolveFormat],
+) -> Result<Vec<PathBuf>, String> {
+    let mesh = ccx_solver::MeshBuilder::build_from_deck(deck)?;
+    let mut frd = ccx_io::mesh_frd(&mesh, job_name);
+    frd.result_blocks = results
+        .modes
+        .iter()
+        .map(|mode| ccx_io::ResultBlock {
+            step: 1,
+            time: mode.frequency,
+            mode: Some(mode.number as i32),
+            datasets: vec![ccx_io::ResultDataset::nodal(
+                "DISP",
+                mode.shape.iter().map(|(id, u)| (*id, u.to_vec())).collect(),
+            )],
+        })
+        .collect();
+
+    let mut written = Vec::new();
+    let output_path =
+        |format: SolveFormat| dir.join(format!("{}.{}", job_name, format.extension()));
+    if formats.contains(&SolveFormat::Frd) {
+        let frd_path = output_path(SolveFormat::Frd);
+        ccx_io::write_frd(&frd_path, &frd)
+            .map_err(|err| format!("Failed to write {}: {}", frd_path.display(), err))?;
+        tracing::info!("Wrote {}", frd_path.display());
+        written.push(frd_path);
+    }
+    if formats.contains(&SolveFormat::Vtu) && !frd.result_blocks.is_empty() {
+        let vtu_path = output_path(SolveFormat::Vtu);
+        ccx_io::VtkWriter::new(&frd)
+            .write_modes(&vtu_path, ccx_io::VtkFormat::Ascii)
+            .map_err(|err| format!("Failed to write {}: {}", vtu_path.display(), err))?;
+        tracing::info!("Wrote {}", vtu_path.display());
+        written.push(vtu_path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{ModesArgs, SolveArgs, parse_args};
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn solve_writes_sta_and_cvg() {
+        let root = unique_temp_dir("ccx_cli_sta");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let path = root.join("truss.inp");
+        fs::write(
+            &path,
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("write deck");
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let out = root.join("out").display().to_string();
+        let deck_arg = path.display().to_string();
+        let options =
+            parse_args::<SolveArgs>(&to_args(&["--output-dir", &out, &deck_arg])).unwrap();
+        solve_file(&options).unwrap();
+
+        let sta = fs::read_to_string(root.join("out/truss.sta")).expect("read sta");
+        assert_eq!(
+            sta.lines().collect::<Vec<_>>()[1..],
+            [
+                "  STEP      INC     ATT   ITRS     TOT TIME     STEP TIME         INC TIME",
+                "     1        1      1      1  1.000000E+00  1.000000E+00     1.000000E+00",
+            ]
+        );
+        assert!(root.join("out/truss.cvg").exists());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn solve_opens_the_legacy_element_library() {
+        let root = unique_temp_dir("ccx_cli_legacy");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let path = root.join("truss.inp");
+        fs::write(
+            &path,
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("write deck");
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let deck_arg = path.display().to_string();
+        let missing = root.join("libccx_legacy.so").display().to_string();
+        let options =
+            parse_args::<SolveArgs>(&to_args(&["--legacy-elements", &missing, &deck_arg])).unwrap();
+        let err = solve_file(&options).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Io);
+        assert!(err.message.contains("libccx_legacy.so"), "{}", err.message);
+
+        // A library without element routines leaves the ported elements alone
+        #[cfg(target_os = "linux")]
+        {
+            let options =
+                parse_args::<SolveArgs>(&to_args(&["--legacy-elements", "libm.so.6", &deck_arg]))
+                    .unwrap();
+            solve_file(&options).unwrap();
+            assert!(root.join("truss.frd").exists());
+        }
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn solve_prints_reaction_totals_and_rejects_unsupported_variables() {
+        let root = unique_temp_dir("ccx_cli_rf");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let path = root.join("truss.inp");
+        let deck = |variables: &str| {
+            format!(
+                "*NODE,NSET=NALL\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+                 *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+                 *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+                 *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n\
+                 *NODE PRINT,NSET=NALL,TOTALS=YES\n{variables}\n*END STEP\n"
+            )
+        };
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let deck_arg = path.display().to_string();
+        let options = parse_args::<SolveArgs>(&to_args(&[&deck_arg])).unwrap();
+
+        fs::write(&path, deck("RF")).expect("write deck");
+        solve_file(&options).unwrap();
+        let dat = fs::read_to_string(root.join("truss.dat")).expect("read dat");
+        assert!(dat.contains(" forces (fx,fy,fz) for set NALL"), "{dat}");
+        assert!(
+            dat.contains(" total force (fx,fy,fz) for set NALL"),
+            "{dat}"
+        );
+        // Only the supports carry reactions; they balance the load
+        assert!(dat.contains("          2  0.000000E+00"), "{dat}");
+        let total = dat.lines().last().unwrap();
+        assert!(total.starts_with("       -1.000000E+02"), "{dat}");
+
+        fs::remove_file(root.join("truss.dat")).expect("remove dat");
+        fs::write(&path, deck("RF, NT")).expect("write deck");
+        let err = solve_file(&options).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Validation);
+        assert!(
+            err.message.contains("NT is not supported"),
+            "{}",
+            err.message
+        );
+        assert!(!root.join("truss.dat").exists());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn modes_tabulate_frequencies_and_write_shapes() {
+        let root = unique_temp_dir("ccx_cli_modes");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let path = root.join("bar.inp");
+        fs::write(
+            &path,
+            "*NODE\n1,0,0,0\n2,50,0,0\n3,100,0,0\n*ELEMENT,TYPE=T3D2,ELSET=BAR\n1,1,2\n2,2,3\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n*DENSITY\n7.85e-9\n\
+             *SOLID SECTION,ELSET=BAR,MATERIAL=STEEL\n0.001\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n3,2,3\n*STEP\n*FREQUENCY\n5\n*END STEP\n",
+        )
+        .expect("write deck");
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let deck_arg = path.display().to_string();
+        let out = root.join("out").display().to_string();
+        let options = parse_args::<ModesArgs>(&to_args(&[
+            "--shift",
+            "-100",
+            "--format",
+            "frd,vtu",
+            "--output-dir",
+            &out,
+            &deck_arg,
+        ]))
+        .unwrap();
+        assert_eq!((options.num_modes, options.shift), (None, -100.0));
+        assert!(parse_args::<ModesArgs>(&to_args(&["--format", "dat", "a.inp"])).is_err());
+        assert!(parse_args::<ModesArgs>(&to_args(&["--num", "0", "a.inp"])).is_err());
+        modes_file(&options).unwrap();
+
+        // The *FREQUENCY card asks for 5 modes, but only two DOFs are free
+        let frd = ccx_io::FrdFile::from_file(root.join("out/bar.frd")).unwrap();
+        let modes: Vec<_> = frd.result_blocks.iter().map(|b| b.mode).collect();
+        assert_eq!(modes, [Some(1), Some(2)]);
+        assert!(frd.result_blocks[0].time < frd.result_blocks[1].time);
+        assert!(root.join("out/bar.vtu").exists());
+
+        let deck = ccx_inp::Deck::parse_file(&path).unwrap();
+        let results = ccx_solver::ModalSolver::new(2).solve(&deck).unwrap();
+        let table = format_modes(&results);
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.lines().last().unwrap().contains("100.00%"), "{table}");
+        let json = modes_json(&path, &results, &[]);
+        assert_eq!(json["modes"][1]["mode"], 2);
+        assert!(json["free_mass"][0].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn parse_solve_args_collects_parameter_overrides() {
+        let args: Vec<String> = ["deck.inp", "-p", "thickness=0.02", "--param", "E=210000"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = parse_args::<SolveArgs>(&args).expect("valid arguments");
+        assert_eq!(options.input, PathBuf::from("deck.inp"));
+        assert_eq!(options.backend, None);
+        assert_eq!(
+            options.overrides,
+            vec![
+                ("thickness".to_string(), "0.02".to_string()),
+                ("E".to_string(), "210000".to_string())
+            ]
+        );
+
+        let missing: Vec<String> = vec!["deck.inp".to_string(), "-p".to_string()];
+        assert!(parse_args::<SolveArgs>(&missing).is_err());
+    }
+
+    #[test]
+    fn parse_solve_args_selects_backend() {
+        let args: Vec<String> = ["--backend", "dense", "deck.inp"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = parse_args::<SolveArgs>(&args).expect("valid arguments");
+        assert_eq!(options.backend, Some(ccx_solver::LinearSolverKind::DenseLu));
+
+        let unknown: Vec<String> = ["deck.inp", "--backend", "mumps"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(parse_args::<SolveArgs>(&unknown).is_err());
+    }
+
+    #[test]
+    fn parse_solve_args_selects_nodal_averaging() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = parse_args::<SolveArgs>(&to_args(&["deck.inp"])).unwrap();
+        assert_eq!(options.averaging, ccx_solver::NodalAveraging::All);
+
+        let options =
+            parse_args::<SolveArgs>(&to_args(&["deck.inp", "--averaging", "elset:Weld, PLATE"]))
+                .unwrap();
+        assert_eq!(
+            options.averaging,
+            ccx_solver::NodalAveraging::ElementSets(vec!["Weld".to_string(), "PLATE".to_string()])
+        );
+
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--averaging", "nodes"])).is_err());
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--averaging", "elset:"])).is_err());
+    }
+
+    #[test]
+    fn parse_solve_args_declares_unit_system() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            parse_args::<SolveArgs>(&to_args(&["deck.inp"]))
+                .unwrap()
+                .units,
+            None
+        );
+        let options =
+            parse_args::<SolveArgs>(&to_args(&["deck.inp", "--units", "mm-t-s"])).unwrap();
+        assert_eq!(options.units, Some(ccx_solver::UnitSystem::MmTonneSecond));
+
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--units", "furlong"])).is_err());
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--units"])).is_err());
+    }
+
+    #[test]
+    fn parse_solve_args_warn_inverted_keeps_solving() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(
+            !parse_args::<SolveArgs>(&to_args(&["deck.inp"]))
+                .unwrap()
+                .warn_inverted
+        );
+        let options = parse_args::<SolveArgs>(&to_args(&["deck.inp", "--warn-inverted"])).unwrap();
+        assert!(options.warn_inverted);
+    }
+
+    #[test]
+    fn parse_solve_args_sets_job_outputs() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = parse_args::<SolveArgs>(&to_args(&["models/deck.inp"])).unwrap();
+        assert_eq!(options.formats, vec![SolveFormat::Dat, SolveFormat::Frd]);
+        assert_eq!(options.job(), (PathBuf::from("models"), "deck".to_string()));
+
+        let options = parse_args::<SolveArgs>(&to_args(&[
+            "models/deck.inp",
+            "--output-dir",
+            "out",
+            "--job-name",
+            "run1",
+            "--format",
+            "VTU, frd,vtu",
+        ]))
+        .unwrap();
+        assert_eq!(options.formats, vec![SolveFormat::Vtu, SolveFormat::Frd]);
+        assert_eq!(options.job(), (PathBuf::from("out"), "run1".to_string()));
+
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--format", "dat,odb"])).is_err());
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--job-name", "a/b"])).is_err());
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--output-dir"])).is_err());
+    }
+
+    #[test]
+    fn parse_solve_args_takes_history_file() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options =
+            parse_args::<SolveArgs>(&to_args(&["--history", "h.csv", "deck.inp"])).unwrap();
+        assert_eq!(options.history, Some(PathBuf::from("h.csv")));
+        assert!(parse_args::<SolveArgs>(&to_args(&["deck.inp", "--history"])).is_err());
+    }
+}
@@ -0,0 +1,751 @@
+//! Fixture regression runs: `validate` compares with the reference
+//! results stored next to the decks, `xvalidate` with a ccx executable.
+
+use std::path::{Path, PathBuf};
+
+use crate::{print_json, read_deck};
+
+pub fn collect_inp_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut out = Vec::<PathBuf>::new();
+    collect_inp_files_inner(root, &mut out)?;
+    out.sort();
+    Ok(out)
+}
+
+fn collect_inp_files_inner(root: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(root)
+        .map_err(|err| format!("failed to read directory {}: {err}", root.display()))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("failed to read dir entry: {err}"))?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_inp_files_inner(&path, out)?;
+            continue;
+        }
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("inp"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Parse a `<quantity>=<atol>,<rtol>` tolerance override
+pub fn parse_quantity_tolerance(value: &str) -> Result<(String, f64, f64), String> {
+    let parsed = value.split_once('=').and_then(|(name, tolerances)| {
+        let (atol, rtol) = tolerances.split_once(',')?;
+        let (atol, rtol) = (atol.trim().parse().ok()?, rtol.trim().parse().ok()?);
+        Some((name.trim().to_string(), atol, rtol))
+    });
+    match parsed {
+        Some((name, atol, rtol)) if !name.is_empty() && atol >= 0.0 && rtol >= 0.0 => {
+            Ok((name, atol, rtol))
+        }
+        _ => Err(format!(
+            "invalid tolerance {value}, expected <quantity>=<atol>,<rtol>"
+        )),
+    }
+}
+
+/// Options of the `validate` command
+pub struct ValidateOptions {
+    pub root: PathBuf,
+    pub tolerances: ccx_io::FrdTolerances,
+    /// Worker threads; all cores when absent
+    pub jobs: Option<usize>,
+    /// JUnit XML report
+    pub junit: Option<PathBuf>,
+    /// JSON summary; `-` prints it to stdout instead of the text report
+    pub json: Option<PathBuf>,
+}
+
+/// Result of validating one deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValidationStatus {
+    Passed,
+    Failed,
+    /// The deck was not solved, e.g. for unsupported elements or analyses
+    Skipped,
+}
+
+impl ValidationStatus {
+    fn name(self) -> &'static str {
+        match self {
+            ValidationStatus::Passed => "passed",
+            ValidationStatus::Failed => "failed",
+            ValidationStatus::Skipped => "skipped",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ValidationStatus::Passed => "PASS",
+            ValidationStatus::Failed => "FAIL",
+            ValidationStatus::Skipped => "SKIP",
+        }
+    }
+}
+
+struct ValidationOutcome {
+    path: PathBuf,
+    status: ValidationStatus,
+    message: String,
+    /// Comparison with `<deck>.dat.ref`, when the deck has one
+    comparison: Option<ccx_io::DatComparison>,
+    /// Wall time of parsing, solving and comparing
+    seconds: f64,
+}
+
+/// Solve one deck and compare its `.dat` tables with `<deck>.dat.ref`
+///
+/// Decks without a reference pass when they solve.
+fn run_single_test(path: &Path, tolerances: &ccx_io::FrdTolerances) -> ValidationOutcome {
+    let start = std::time::Instant::now();
+    let outcome = |status, message: String, comparison| ValidationOutcome {
+        path: path.to_path_buf(),
+        status,
+        message,
+        comparison,
+        seconds: start.elapsed().as_secs_f64(),
+    };
+    let deck = match read_deck(path, &[], &[]) {
+        Ok(deck) => deck,
+        Err(err) => {
+            return outcome(
+                ValidationStatus::Failed,
+                format!("parse error: {err}"),
+                None,
+            );
+        }
+    };
+    let results = match ccx_solver::AnalysisPipeline::detect_from_deck(&deck).run(&deck) {
+        Ok(results) => results,
+        Err(err) => {
+            return outcome(
+                ValidationStatus::Failed,
+                format!("analysis error: {err}"),
+                None,
+            );
+        }
+    };
+    if results.displacements.is_empty() {
+        return outcome(ValidationStatus::Skipped, results.message, None);
+    }
+
+    let reference_path = path.with_extension("dat.ref");
+    let reference = match std::fs::read_to_string(&reference_path) {
+        Ok(text) => ccx_io::parse_dat_tables(&text),
+        Err(_) => {
+            let message = "solved, no reference".to_string();
+            return outcome(ValidationStatus::Passed, message, None);
+        }
+    };
+    let mut dat = Vec::new();
+    let written = ccx_io::static_dat_steps(&deck, &results).and_then(|(_, steps)| {
+        ccx_io::write_dat_results_to(&mut dat, &steps).map_err(|err| err.to_string())
+    });
+    if let Err(err) = written {
+        return outcome(
+            ValidationStatus::Failed,
+            format!("output error: {err}"),
+            None,
+        );
+    }
+    let actual = ccx_io::parse_dat_tables(&String::from_utf8_lossy(&dat));
+    let comparison = ccx_io::dat_compare(&reference, &actual, tolerances);
+    let (status, message) = if comparison.passed() {
+        (
+            ValidationStatus::Passed,
+            format!("matches {}", reference_path.display()),
+        )
+    } else if let Some(error) = comparison.errors.first() {
+        (ValidationStatus::Failed, error.clone())
+    } else {
+        let failed: Vec<&str> = comparison.failures().map(|q| q.quantity.as_str()).collect();
+        (
+            ValidationStatus::Failed,
+            format!("exceeds tolerance: {}", failed.join(", ")),
+        )
+    };
+    outcome(status, message, Some(comparison))
+}
+
+/// Run [`run_single_test`] on every deck in parallel, in the order of
+/// `files`; a panicking deck fails instead of aborting the run
+fn run_validation(files: &[PathBuf], options: &ValidateOptions) -> Vec<ValidationOutcome> {
+    use rayon::prelude::*;
+
+    let run = || {
+        files
+            .par_iter()
+            .map(|path| {
+                let start = std::time::Instant::now();
+                std::panic::catch_unwind(|| run_single_test(path, &options.tolerances))
+                    .unwrap_or_else(|_| ValidationOutcome {
+                        path: path.clone(),
+                        status: ValidationStatus::Failed,
+                        message: "panicked".to_string(),
+                        comparison: None,
+                        seconds: start.elapsed().as_secs_f64(),
+                    })
+            })
+            .collect()
+    };
+    match options
+        .jobs
+        .map(|jobs| rayon::ThreadPoolBuilder::new().num_threads(jobs).build())
+    {
+        Some(Ok(pool)) => pool.install(run),
+        _ => run(),
+    }
+}
+
+/// Write the outcomes as a JUnit XML test suite
+fn write_junit_report(
+    path: &Path,
+    root: &Path,
+    outcomes: &[ValidationOutcome],
+) -> Result<(), String> {
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+    let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        escape(&root.display().to_string()),
+        outcomes.len(),
+        count(ValidationStatus::Failed),
+        count(ValidationStatus::Skipped),
+        outcomes.iter().map(|o| o.seconds).sum::<f64>()
+    ));
+    for outcome in outcomes {
+        let relative = outcome.path.strip_prefix(root).unwrap_or(&outcome.path);
+        let classname = relative
+            .parent()
+            .map(|p| p.display().to_string().replace(['/', '\\'], "."))
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| "fixtures".to_string());
+        let name = relative.file_name().unwrap_or_default().to_string_lossy();
+        xml.push_str(&format!(
+            "  <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            escape(&classname),
+            escape(&name),
+            outcome.seconds
+        ));
+        let message = escape(&outcome.message);
+        match outcome.status {
+            ValidationStatus::Passed => xml.push_str("/>\n"),
+            ValidationStatus::Skipped => {
+                xml.push_str(&format!(
+                    ">\n    <skipped message=\"{message}\"/>\n  </testcase>\n"
+                ));
+            }
+            ValidationStatus::Failed => {
+                let details = outcome
+                    .comparison
+                    .as_ref()
+                    .map(|c| escape(&c.to_string()))
+                    .unwrap_or_default();
+                xml.push_str(&format!(
+                    ">\n    <failure message=\"{message}\">{details}</failure>\n  </testcase>\n"
+                ));
+            }
+        }
+    }
+    xml.push_str("</testsuite>\n");
+    std::fs::write(path, xml).map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// Outcomes with per-quantity errors as a JSON summary
+fn validation_json(root: &Path, outcomes: &[ValidationOutcome]) -> serde_json::Value {
+    let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+    let tests: Vec<serde_json::Value> = outcomes
+        .iter()
+        .map(|outcome| {
+            let quantities: Vec<serde_json::Value> = outcome
+                .comparison
+                .iter()
+                .flat_map(|c| &c.quantities)
+                .map(|q| {
+                    serde_json::json!({
+                        "quantity": q.quantity,
+                        "max_abs": q.max_abs,
+                        "max_rel": q.max_rel,
+                        "tolerance": q.tolerance,
+                        "missing": q.missing,
+                        "passed": q.passed,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "path": outcome.path.display().to_string(),
+                "status": outcome.status.name(),
+                "message": outcome.message,
+                "seconds": outcome.seconds,
+                "quantities": quantities,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "root": root.display().to_string(),
+        "total": outcomes.len(),
+        "passed": count(ValidationStatus::Passed),
+        "failed": count(ValidationStatus::Failed),
+        "skipped": count(ValidationStatus::Skipped),
+        "seconds": outcomes.iter().map(|o| o.seconds).sum::<f64>(),
+        "tests": tests,
+    })
+}
+
+/// Write [`validation_json`] to `path`
+fn write_json_report(
+    path: &Path,
+    root: &Path,
+    outcomes: &[ValidationOutcome],
+) -> Result<(), String> {
+    let summary = validation_json(root, outcomes);
+    let text = serde_json::to_string_pretty(&summary).map_err(|err| err.to_string())?;
+    std::fs::write(path, text + "\n")
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// Validate every deck under `root`, returning the number of failures
+pub fn validate_fixture_tree(options: &ValidateOptions) -> Result<usize, String> {
+    let stdout_json = options.json.as_deref() == Some(Path::new("-"));
+    let files = collect_inp_files(&options.root)?;
+    if files.is_empty() {
+        if stdout_json {
+            print_json(&validation_json(&options.root, &[]))?;
+        } else {
+            println!("no .inp files found in {}", options.root.display());
+        }
+        return Ok(0);
+    }
+
+    let start = std::time::Instant::now();
+    let outcomes = run_validation(&files, options);
+    let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+    let failed = count(ValidationStatus::Failed);
+    if stdout_json {
+        if let Some(path) = &options.junit {
+            write_junit_report(path, &options.root, &outcomes)?;
+        }
+        print_json(&validation_json(&options.root, &outcomes))?;
+        return Ok(failed);
+    }
+    for outcome in &outcomes {
+        println!(
+            "{}  {}  {} ({:.2} s)",
+            outcome.status.label(),
+            outcome.path.display(),
+            outcome.message,
+            outcome.seconds
+        );
+        for quantity in outcome.comparison.iter().flat_map(|c| &c.quantities) {
+            println!(
+                "      {}: max error {:.3e}, relative {:.3e}, tolerance {:.3e}{}",
+                quantity.quantity,
+                quantity.max_abs,
+                quantity.max_rel,
+                quantity.tolerance,
+                if quantity.missing > 0 {
+                    format!(", {} rows missing", quantity.missing)
+                } else {
+                    String::new()
+                }
+            );
+        }
+    }
+    if let Some(path) = &options.junit {
+        write_junit_report(path, &options.root, &outcomes)?;
+        println!("junit_report: {}", path.display());
+    }
+    if let Some(path) = &options.json {
+        write_json_report(path, &options.root, &outcomes)?;
+        println!("json_report: {}", path.display());
+    }
+
+    println!("fixtures_root: {}", options.root.display());
+    println!("total_inp: {}", files.len());
+    println!("passed: {}", count(ValidationStatus::Passed));
+    println!("failed: {}", failed);
+    println!("skipped: {}", count(ValidationStatus::Skipped));
+    println!("elapsed_seconds: {:.2}", start.elapsed().as_secs_f64());
+    Ok(failed)
+}
+
+/// Options of the `xvalidate` command
+pub struct XvalidateOptions {
+    pub root: PathBuf,
+    /// Legacy ccx executable
+    pub ccx: PathBuf,
+    pub tolerances: ccx_io::FrdTolerances,
+    /// Worker threads; all cores when absent
+    pub jobs: Option<usize>,
+    /// Time limit of a ccx run
+    pub timeout: Option<std::time::Duration>,
+    /// Directory keeping the ccx and Rust files of every deck
+    pub keep_files: Option<PathBuf>,
+    /// JSON scoreboard; `-` prints it to stdout instead of the text report
+    pub json: Option<PathBuf>,
+}
+
+/// Cross-validate every deck in parallel, in the order of `files`; a
+/// panicking deck counts as a Rust failure
+fn run_xvalidation(files: &[PathBuf], options: &XvalidateOptions) -> ccx_io::Scoreboard {
+    use rayon::prelude::*;
+
+    let mut validator = ccx_io::CrossValidator::new(&options.ccx)
+        .with_tolerances(options.tolerances.clone())
+        .with_keep_files(options.keep_files.is_some());
+    if let Some(dir) = &options.keep_files {
+        validator = validator.with_work_dir(dir);
+    }
+    if let Some(timeout) = options.timeout {
+        validator = validator.with_timeout(timeout);
+    }
+    let run = || {
+        files
+            .par_iter()
+            .map(|path| {
+                std::panic::catch_unwind(|| validator.run(path)).unwrap_or_else(|_| {
+                    ccx_io::XvalidateOutcome {
+                        deck: path.clone(),
+                        status: ccx_io::XvalidateStatus::RustFailed,
+                        message: "panicked".to_string(),
+                        dat: None,
+                        frd: None,
+                        legacy_seconds: 0.0,
+                        rust_seconds: 0.0,
+                    }
+                })
+            })
+            .collect()
+    };
+    let outcomes = match options
+        .jobs
+        .map(|jobs| rayon::ThreadPoolBuilder::new().num_threads(jobs).build())
+    {
+        Some(Ok(pool)) => pool.install(run),
+        _ => run(),
+    };
+    ccx_io::Scoreboard::new(outcomes)
+}
+
+/// Scoreboard with the per-quantity and per-dataset errors as JSON
+fn xvalidate_json(root: &Path, ccx: &Path, board: &ccx_io::Scoreboard) -> serde_json::Value {
+    let decks: Vec<serde_json::Value> = board
+        .outcomes
+        .iter()
+        .map(|outcome| {
+            let quantities: Vec<serde_json::Value> = outcome
+                .dat
+                .iter()
+                .flat_map(|c| &c.quantities)
+                .map(|q| {
+                    serde_json::json!({
+                        "quantity": q.quantity,
+                        "max_abs": q.max_abs,
+                        "max_rel": q.max_rel,
+                        "tolerance": q.tolerance,
+                        "passed": q.passed,
+                    })
+                })
+                .collect();
+            let datasets: Vec<serde_json::Value> = outcome
+                .frd
+                .iter()
+                .flat_map(|c| &c.datasets)
+                .map(|d| {
+                    serde_json::json!({
+                        "name": d.name,
+                        "step": d.step,
+                        "max_abs": d.max_abs,
+                        "tolerance": d.tolerance,
+                        "passed": d.passed,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "path": outcome.deck.display().to_string(),
+                "status": outcome.status.name(),
+                "message": outcome.message,
+                "legacy_seconds": outcome.legacy_seconds,
+                "rust_seconds": outcome.rust_seconds,
+                "quantities": quantities,
+                "datasets": datasets,
+            })
+        })
+        .collect();
+    let mut value = serde_json::json!({
+        "root": root.display().to_string(),
+        "ccx": ccx.display().to_string(),
+        "total": board.outcomes.len(),
+        "compatibility": board.compatibility(),
+        "decks": decks,
+    });
+    for status in ccx_io::XvalidateStatus::ALL {
+        value[status.name()] = board.count(status).into();
+    }
+    value
+}
+
+/// Cross-validate every deck under `root` against ccx, returning the number
+/// of decks that differ or that the Rust pipeline fails on
+pub fn xvalidate_fixture_tree(options: &XvalidateOptions) -> Result<usize, String> {
+    let files = collect_inp_files(&options.root)?;
+    let board = run_xvalidation(&files, options);
+    let incompatible = board.count(ccx_io::XvalidateStatus::Differs)
+        + board.count(ccx_io::XvalidateStatus::RustFailed);
+    let summary = xvalidate_json(&options.root, &options.ccx, &board);
+    match options.json.as_deref() {
+        Some(path) if path == Path::new("-") => {
+            print_json(&summary)?;
+            return Ok(incompatible);
+        }
+        Some(path) => {
+            let text = serde_json::to_string_pretty(&summary).map_err(|err| err.to_string())?;
+            std::fs::write(path, text + "\n")
+                .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+        }
+        None => {}
+    }
+    if files.is_empty() {
+        println!("no .inp files found in {}", options.root.display());
+        return Ok(0);
+    }
+    println!("{}", board.format());
+    if let Some(path) = &options.json {
+        println!("json_report: {}", path.display());
+    }
+    Ok(incompatible)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{ValidateArgs, parse_args};
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn collect_inp_files_recurses_and_sorts() {
+        let root = unique_temp_dir("ccx_cli_collect_inp");
+        let nested = root.join("nested");
+        fs::create_dir_all(&nested).expect("create temp tree");
+
+        fs::write(root.join("b.INP"), "*NODE\n1,0,0,0\n").expect("write b");
+        fs::write(root.join("ignore.txt"), "x").expect("write ignore");
+        fs::write(nested.join("a.inp"), "*NODE\n1,0,0,0\n").expect("write a");
+
+        let files = collect_inp_files(&root).expect("collect should succeed");
+        let mut names: Vec<String> = files
+            .iter()
+            .filter_map(|p| p.file_name().and_then(|s| s.to_str()).map(str::to_string))
+            .collect();
+        names.sort();
+
+        assert_eq!(names, vec!["a.inp".to_string(), "b.INP".to_string()]);
+    }
+
+    #[test]
+    fn parse_validate_args_reads_tolerances() {
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = parse_args::<ValidateArgs>(&to_args(&[
+            "--rtol",
+            "1e-3",
+            "fixtures",
+            "--tolerance",
+            "stresses=0,1e-2",
+        ]))
+        .unwrap();
+        assert_eq!(options.root, PathBuf::from("fixtures"));
+        assert_eq!(options.tolerances.relative, 1e-3);
+        assert_eq!(
+            options.tolerances,
+            ccx_io::FrdTolerances::new(1e-9, 1e-3).with_dataset("STRESSES", 0.0, 1e-2)
+        );
+
+        assert_eq!(
+            (options.jobs, options.junit, options.json),
+            (None, None, None)
+        );
+
+        let options = parse_args::<ValidateArgs>(&to_args(&[
+            "-j", "4", "--junit", "r.xml", "--json", "r.json", "fixtures",
+        ]))
+        .unwrap();
+        assert_eq!(options.jobs, Some(4));
+        assert_eq!(options.junit, Some(PathBuf::from("r.xml")));
+        assert_eq!(options.json, Some(PathBuf::from("r.json")));
+
+        assert!(parse_args::<ValidateArgs>(&to_args(&["-j", "0", "fixtures"])).is_err());
+        assert!(parse_args::<ValidateArgs>(&to_args(&[])).is_err());
+        assert!(parse_args::<ValidateArgs>(&to_args(&["--rtol", "-1", "fixtures"])).is_err());
+        assert!(parse_args::<ValidateArgs>(&to_args(&["--tolerance", "stresses=1", "f"])).is_err());
+    }
+
+    #[test]
+    fn run_single_test_compares_with_reference() {
+        let root = unique_temp_dir("ccx_cli_validate");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let deck = root.join("truss.inp");
+        fs::write(
+            &deck,
+            "*PARAMETER\nload=100.\n\
+             *NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,<load>\n*END STEP\n",
+        )
+        .expect("write deck");
+        let tolerances = ccx_io::FrdTolerances::new(1e-12, 1e-6);
+        let outcome = run_single_test(&deck, &tolerances);
+        assert_eq!(
+            outcome.status,
+            ValidationStatus::Passed,
+            "{}",
+            outcome.message
+        );
+        assert!(outcome.comparison.is_none());
+
+        // A reference written from the solve itself matches
+        let mut out = Vec::new();
+        let parsed = read_deck(&deck, &[], &[]).unwrap();
+        let results = ccx_solver::AnalysisPipeline::detect_from_deck(&parsed)
+            .run(&parsed)
+            .unwrap();
+        let (_, steps) = ccx_io::static_dat_steps(&parsed, &results).unwrap();
+        ccx_io::write_dat_results_to(&mut out, &steps).unwrap();
+        let reference = String::from_utf8(out).unwrap();
+        fs::write(root.join("truss.dat.ref"), &reference).expect("write reference");
+        let outcome = run_single_test(&deck, &tolerances);
+        assert_eq!(
+            outcome.status,
+            ValidationStatus::Passed,
+            "{}",
+            outcome.message
+        );
+        let comparison = outcome.comparison.expect("compared");
+        assert_eq!(comparison.quantities[0].quantity, "displacements");
+        assert_eq!(comparison.quantities[0].max_abs, 0.0);
+
+        // Doubling the reference displacement fails with the error reported
+        let tables = ccx_io::parse_dat_tables(&reference);
+        let ux = tables[0].rows.iter().find(|r| r.id == 2).unwrap().values[0];
+        let doubled = ccx_io::DatStep {
+            step: 1,
+            increment: 1,
+            time: tables[0].time,
+            sections: vec![ccx_io::DatSection::Displacements {
+                set: tables[0].set.clone(),
+                values: vec![(1, [0.0; 3]), (2, [2.0 * ux, 0.0, 0.0])],
+            }],
+        };
+        ccx_io::write_dat_results(root.join("truss.dat.ref"), &[doubled]).unwrap();
+        let outcome = run_single_test(&deck, &tolerances);
+        assert_eq!(outcome.status, ValidationStatus::Failed);
+        assert!(
+            outcome.message.contains("displacements"),
+            "{}",
+            outcome.message
+        );
+        let max_abs = outcome.comparison.unwrap().quantities[0].max_abs;
+        assert!((max_abs - ux.abs()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn validation_reports_list_every_deck() {
+        let root = unique_temp_dir("ccx_cli_validate_reports");
+        fs::create_dir_all(root.join("truss")).expect("create temp tree");
+        fs::write(
+            root.join("truss/bar.inp"),
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("write solved deck");
+        fs::write(root.join("broken.inp"), "*NODE\n").expect("write failing deck");
+        fs::write(root.join("broken.dat.ref"), "").expect("write reference");
+
+        let options = ValidateOptions {
+            root: root.clone(),
+            tolerances: ccx_io::FrdTolerances::default(),
+            jobs: Some(2),
+            junit: Some(root.join("report.xml")),
+            json: Some(root.join("report.json")),
+        };
+        assert_eq!(validate_fixture_tree(&options), Ok(1));
+
+        let xml = fs::read_to_string(root.join("report.xml")).unwrap();
+        assert!(
+            xml.contains("tests=\"2\" failures=\"1\" skipped=\"0\""),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("<testcase classname=\"truss\" name=\"bar.inp\""),
+            "{xml}"
+        );
+        assert!(xml.contains("<failure message="), "{xml}");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join("report.json")).unwrap()).unwrap();
+        assert_eq!(json["total"], 2);
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["tests"][0]["status"], "failed");
+        assert_eq!(json["tests"][1]["status"], "passed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn xvalidate_scores_every_deck() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = unique_temp_dir("ccx_cli_xvalidate");
+        fs::create_dir_all(&root).expect("create temp dir");
+        fs::write(
+            root.join("bar.inp"),
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("write deck");
+        fs::write(root.join("crash.inp"), "*NODE\n1,0,0,0\n").expect("write deck");
+        fs::write(root.join("missing.inp"), "*INCLUDE,INPUT=none.inp\n").expect("write deck");
+        let ccx = root.join("ccx");
+        fs::write(&ccx, "#!/bin/sh\n[ \"$2\" = crash ] && exit 1\nexit 0\n").expect("write ccx");
+        fs::set_permissions(&ccx, fs::Permissions::from_mode(0o755)).expect("chmod ccx");
+
+        let options = XvalidateOptions {
+            root: root.clone(),
+            ccx: ccx.clone(),
+            tolerances: ccx_io::FrdTolerances::default(),
+            jobs: Some(2),
+            timeout: Some(std::time::Duration::from_secs(10)),
+            keep_files: None,
+            json: Some(root.join("scoreboard.json")),
+        };
+        assert_eq!(xvalidate_fixture_tree(&options), Ok(1));
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join("scoreboard.json")).unwrap())
+                .unwrap();
+        assert_eq!(json["total"], 3);
+        assert_eq!(json["not_compared"], 1);
+        assert_eq!(json["legacy_failed"], 1);
+        assert_eq!(json["rust_failed"], 1);
+        assert_eq!(json["compatibility"], 0.0);
+        let statuses: Vec<&str> = json["decks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|deck| deck["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, ["not_compared", "legacy_failed", "rust_failed"]);
+    }
+}
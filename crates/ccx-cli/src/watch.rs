@@ -0,0 +1,361 @@
+//! `watch`: re-run the checks or the solve of a deck whenever it or one
+//! of its includes changes.
+
+use std::path::{Path, PathBuf};
+
+use ccx_model::ModelSummary;
+
+use crate::analyze::summary_json;
+use crate::check::{CheckOptions, check_file};
+use crate::error::CliError;
+use crate::read_deck;
+use crate::solve::{SolveFormat, solve_json, solve_warnings, write_solve_outputs};
+
+/// What `ccx-cli watch` runs after every change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WatchMode {
+    /// Lint and model checks only
+    Check,
+    /// Checks and the model summary
+    Analyze,
+    /// Checks, summary and a solve writing `<deck>.dat` and `<deck>.frd`
+    Solve,
+}
+
+pub struct WatchOptions {
+    pub input: PathBuf,
+    pub overrides: Vec<(String, String)>,
+    /// Directories searched for `*INCLUDE` files not found next to the deck
+    pub include_paths: Vec<PathBuf>,
+    pub mode: WatchMode,
+    /// Quiet period after the last change before re-running
+    pub debounce: std::time::Duration,
+}
+
+/// Outcome of one watch run, flattened for the diff with the previous run
+#[derive(Debug, Default)]
+struct WatchSnapshot {
+    /// Counts and results by dotted key, e.g. `model.node_rows`
+    values: std::collections::BTreeMap<String, String>,
+    diagnostics: std::collections::BTreeSet<String>,
+}
+
+impl WatchSnapshot {
+    /// Add the scalars of `value` under `prefix`; arrays are kept whole
+    fn insert_json(&mut self, prefix: &str, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    self.insert_json(&format!("{prefix}.{key}"), value);
+                }
+            }
+            serde_json::Value::String(text) => {
+                self.values.insert(prefix.to_string(), text.clone());
+            }
+            // Timings differ on every run
+            _ if prefix.ends_with("_seconds") => {}
+            _ => {
+                self.values.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+
+    fn status(&self) -> String {
+        let value = |key: &str| self.values.get(key).map_or("0", String::as_str);
+        let mut status = format!(
+            "{} errors, {} warnings",
+            value("check.errors"),
+            value("check.warnings")
+        );
+        if let Some(error) = self.values.get("solve.error") {
+            status.push_str(&format!(", solve failed: {error}"));
+        } else if let Some(message) = self.values.get("solve.message") {
+            status.push_str(&format!(", {message}"));
+        }
+        status
+    }
+
+    /// Sections with values: `check`, `model` and `solve`
+    fn sections(&self) -> std::collections::BTreeSet<&str> {
+        self.values
+            .keys()
+            .filter_map(|key| key.split('.').next())
+            .collect()
+    }
+
+    /// Changed values and new (`+`) and resolved (`-`) diagnostics since
+    /// `previous`; a section that was not run this time is one line
+    fn diff(&self, previous: &WatchSnapshot) -> Vec<String> {
+        let sections = self.sections();
+        let mut lines: Vec<String> = previous
+            .sections()
+            .difference(&sections)
+            .map(|section| format!("  {section}: not run"))
+            .collect();
+        let keys: std::collections::BTreeSet<&String> =
+            self.values.keys().chain(previous.values.keys()).collect();
+        for key in keys {
+            let section = key.split('.').next().unwrap_or_default();
+            match (previous.values.get(key), self.values.get(key)) {
+                (Some(old), Some(new)) if old != new => {
+                    lines.push(format!("  {key}: {old} -> {new}"))
+                }
+                (None, Some(new)) => lines.push(format!("  {key}: {new}")),
+                (Some(old), None) if sections.contains(section) => {
+                    lines.push(format!("  {key}: {old} -> (none)"))
+                }
+                _ => {}
+            }
+        }
+        for added in self.diagnostics.difference(&previous.diagnostics) {
+            lines.push(format!("  + {added}"));
+        }
+        for resolved in previous.diagnostics.difference(&self.diagnostics) {
+            lines.push(format!("  - {resolved}"));
+        }
+        lines
+    }
+}
+
+/// Check the deck and, as `options.mode` asks and the checks pass, summarize
+/// and solve it
+fn watch_run(options: &WatchOptions) -> WatchSnapshot {
+    let mut snapshot = WatchSnapshot::default();
+    let check = CheckOptions {
+        input: options.input.clone(),
+        overrides: options.overrides.clone(),
+        include_paths: options.include_paths.clone(),
+        units: None,
+        json: None,
+        strict: false,
+    };
+    let diagnostics = check_file(&check);
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == ccx_model::Severity::Error)
+        .count();
+    snapshot
+        .values
+        .insert("check.errors".to_string(), errors.to_string());
+    snapshot.values.insert(
+        "check.warnings".to_string(),
+        (diagnostics.len() - errors).to_string(),
+    );
+    snapshot.diagnostics = diagnostics.iter().map(ToString::to_string).collect();
+    if errors > 0 || options.mode == WatchMode::Check {
+        return snapshot;
+    }
+
+    let deck = match read_deck(&options.input, &options.overrides, &options.include_paths) {
+        Ok(deck) => deck,
+        Err(err) => {
+            snapshot
+                .values
+                .insert("model.error".to_string(), err.to_string());
+            return snapshot;
+        }
+    };
+    snapshot.insert_json("model", &summary_json(&ModelSummary::from_deck(&deck)));
+    if options.mode == WatchMode::Solve {
+        match watch_solve(&options.input, &deck) {
+            Ok(solve) => snapshot.insert_json("solve", &solve),
+            Err(err) => {
+                snapshot.values.insert("solve.error".to_string(), err);
+            }
+        }
+    }
+    snapshot
+}
+
+/// Solve `deck` and write `<deck>.dat` and `<deck>.frd` next to it
+fn watch_solve(path: &Path, deck: &ccx_inp::Deck) -> Result<serde_json::Value, String> {
+    let results = ccx_solver::AnalysisPipeline::detect_from_deck(deck).run(deck)?;
+    let mut written = Vec::new();
+    if !results.displacements.is_empty() {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let job_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("job");
+        let formats = [SolveFormat::Dat, SolveFormat::Frd];
+        written = write_solve_outputs(dir, job_name, deck, &results, None, &formats)?;
+    }
+    Ok(solve_json(
+        path,
+        &results,
+        &solve_warnings(&results),
+        &written,
+    ))
+}
+
+/// `path` made absolute, with symlinks resolved where it or its directory
+/// exists, to match the paths of file-system events
+fn watched_path(path: &Path) -> PathBuf {
+    if let Ok(path) = std::fs::canonicalize(path) {
+        return path;
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    match (std::fs::canonicalize(dir), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Block until the deck or one of its includes changes and no further change
+/// follows within `debounce`, returning the changed files
+///
+/// The directories are watched rather than the files, as editors often save
+/// by replacing the file.
+fn wait_for_change(
+    input: &Path,
+    include_paths: &[PathBuf],
+    debounce: std::time::Duration,
+) -> Result<Vec<PathBuf>, String> {
+    use notify_debouncer_mini::notify::RecursiveMode;
+
+    let mut files = vec![input.to_path_buf()];
+    files.extend(ccx_inp::Deck::include_files(input, include_paths));
+    let watched: std::collections::HashSet<PathBuf> =
+        files.iter().map(|f| watched_path(f)).collect();
+    let dirs: std::collections::BTreeSet<&Path> =
+        watched.iter().filter_map(|f| f.parent()).collect();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut debouncer = notify_debouncer_mini::new_debouncer(debounce, tx)
+        .map_err(|err| format!("Failed to start file watcher: {err}"))?;
+    for dir in dirs {
+        debouncer
+            .watcher()
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|err| format!("Failed to watch {}: {err}", dir.display()))?;
+    }
+    for result in rx {
+        let events = result.map_err(|err| format!("File watcher error: {err}"))?;
+        let mut changed: Vec<PathBuf> = events
+            .into_iter()
+            .map(|event| event.path)
+            .filter(|path| watched.contains(&watched_path(path)))
+            .collect();
+        changed.sort();
+        changed.dedup();
+        if !changed.is_empty() {
+            return Ok(changed);
+        }
+    }
+    Err("file watcher stopped".to_string())
+}
+
+/// Re-run [`watch_run`] whenever the deck or its includes change, printing
+/// what changed since the previous run; runs until interrupted
+pub fn watch_file(options: &WatchOptions) -> Result<(), CliError> {
+    println!("Watching {} (Ctrl-C to stop)", options.input.display());
+    let mut previous: Option<WatchSnapshot> = None;
+    let mut run = 1;
+    loop {
+        let start = std::time::Instant::now();
+        let snapshot = watch_run(options);
+        println!(
+            "Run {}: {} ({:.2} s)",
+            run,
+            snapshot.status(),
+            start.elapsed().as_secs_f64()
+        );
+        match &previous {
+            None => {
+                for diagnostic in &snapshot.diagnostics {
+                    println!("  {diagnostic}");
+                }
+            }
+            Some(previous) => {
+                let changes = snapshot.diff(previous);
+                if changes.is_empty() {
+                    println!("  no changes");
+                }
+                for change in changes {
+                    println!("{change}");
+                }
+            }
+        }
+        previous = Some(snapshot);
+        run += 1;
+
+        let changed = wait_for_change(&options.input, &options.include_paths, options.debounce)
+            .map_err(CliError::io)?;
+        let names: Vec<String> = changed.iter().map(|p| p.display().to_string()).collect();
+        tracing::info!("Changed: {}", names.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{WatchArgs, parse_args};
+    use crate::unique_temp_dir;
+    use std::fs;
+
+    #[test]
+    fn watch_reports_changes_between_runs() {
+        let root = unique_temp_dir("ccx_cli_watch");
+        fs::create_dir_all(&root).expect("create temp dir");
+        let deck = root.join("truss.inp");
+        let write_mesh = |length: &str| {
+            fs::write(
+                root.join("mesh.inc"),
+                format!("*NODE\n1,0,0,0\n2,{length},0,0\n"),
+            )
+            .expect("write mesh")
+        };
+        let write_deck = |load_node: &str| {
+            let text = format!(
+                "*INCLUDE,INPUT=mesh.inc\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+                 *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+                 *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+                 *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n{load_node},1,100.\n\
+                 *END STEP\n"
+            );
+            fs::write(&deck, text).expect("write deck")
+        };
+        write_mesh("1");
+        write_deck("2");
+        let to_args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let deck_arg = deck.display().to_string();
+        let options = parse_args::<WatchArgs>(&to_args(&["--run", "solve", &deck_arg])).unwrap();
+        assert_eq!(options.mode, WatchMode::Solve);
+        assert!(parse_args::<WatchArgs>(&to_args(&["--run", "mesh", &deck_arg])).is_err());
+
+        let first = watch_run(&options);
+        assert_eq!(first.values["check.errors"], "0");
+        assert_eq!(first.values["solve.success"], "true");
+        assert!(root.join("truss.frd").exists());
+        assert!(watch_run(&options).diff(&first).is_empty());
+
+        // A longer truss through the include changes the solution only
+        write_mesh("2");
+        let longer = watch_run(&options);
+        let changes = longer.diff(&first);
+        assert!(
+            changes
+                .iter()
+                .any(|c| c.starts_with("  solve.max_displacement.magnitude:"))
+        );
+        assert!(
+            changes
+                .iter()
+                .all(|c| !c.starts_with("  check.") && !c.contains("seconds"))
+        );
+
+        // A load on an undefined node fails the check and skips the solve
+        write_deck("3");
+        let broken = watch_run(&options);
+        let changes = broken.diff(&longer);
+        assert!(changes.contains(&"  check.errors: 0 -> 1".to_string()));
+        assert!(
+            changes
+                .iter()
+                .any(|c| c.starts_with("  + ") && c.contains("error"))
+        );
+        assert!(changes.contains(&"  solve: not run".to_string()));
+        assert!(!changes.iter().any(|c| c.starts_with("  solve.")));
+        assert!(broken.status().starts_with("1 errors"));
+    }
+}
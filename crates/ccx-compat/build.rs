@@ -0,0 +1,64 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Links the compiled legacy objects in `CCX_LEGACY_OBJ_DIR` into a shared
+/// library for `LegacyLibrary::bundled`, or points it at an existing one in
+/// `CCX_LEGACY_LIB`. Without either the crate builds without a bundled
+/// library.
+fn main() {
+    println!("cargo:rerun-if-env-changed=CCX_LEGACY_LIB");
+    println!("cargo:rerun-if-env-changed=CCX_LEGACY_OBJ_DIR");
+    println!("cargo:rerun-if-env-changed=CC");
+
+    if let Some(lib) = env::var_os("CCX_LEGACY_LIB") {
+        println!(
+            "cargo:rustc-env=CCX_LEGACY_LIB={}",
+            PathBuf::from(lib).display()
+        );
+        return;
+    }
+    let Some(dir) = env::var_os("CCX_LEGACY_OBJ_DIR").map(PathBuf::from) else {
+        return;
+    };
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let mut objects: Vec<PathBuf> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "o"))
+            .collect(),
+        Err(err) => {
+            println!("cargo:warning=cannot read {}: {err}", dir.display());
+            return;
+        }
+    };
+    if objects.is_empty() {
+        println!("cargo:warning=no legacy objects in {}", dir.display());
+        return;
+    }
+    objects.sort();
+
+    let extension = match env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("macos") => "dylib",
+        _ => "so",
+    };
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
+    let library = out_dir.join(format!("libccx_legacy.{extension}"));
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .arg("-shared")
+        .arg("-o")
+        .arg(&library)
+        .args(&objects)
+        .args(["-lgfortran", "-lm"])
+        .status();
+    match status {
+        Ok(status) if status.success() => {
+            println!("cargo:rustc-env=CCX_LEGACY_LIB={}", library.display());
+        }
+        Ok(status) => println!("cargo:warning=linking legacy objects failed: {status}"),
+        Err(err) => println!("cargo:warning=cannot run {cc}: {err}"),
+    }
+}
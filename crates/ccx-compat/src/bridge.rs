@@ -1,8 +1,10 @@
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
+use crate::ffi::{ArgKind, ForeignRoutine, LegacyArg};
 use crate::symbols::{LegacyLanguage, canonical_symbol, fortran_symbol};
+use crate::usage::UsageLog;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CallingConvention {
@@ -32,10 +34,24 @@ pub enum CompatError {
         expected: usize,
         got: usize,
     },
+    InvalidArgumentKind {
+        symbol: String,
+        index: usize,
+        expected: ArgKind,
+        got: ArgKind,
+    },
     InvocationFailed {
         symbol: String,
         message: String,
     },
+    LibraryUnavailable {
+        library: String,
+        message: String,
+    },
+    SymbolNotFound {
+        symbol: String,
+        library: String,
+    },
 }
 
 impl Display for CompatError {
@@ -52,9 +68,24 @@ impl Display for CompatError {
                 f,
                 "invalid argument count for {symbol}: expected {expected}, got {got}"
             ),
+            CompatError::InvalidArgumentKind {
+                symbol,
+                index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "invalid argument {index} for {symbol}: expected {expected}, got {got}"
+            ),
             CompatError::InvocationFailed { symbol, message } => {
                 write!(f, "routine invocation failed for {symbol}: {message}")
             }
+            CompatError::LibraryUnavailable { library, message } => {
+                write!(f, "legacy library {library} unavailable: {message}")
+            }
+            CompatError::SymbolNotFound { symbol, library } => {
+                write!(f, "symbol {symbol} not found in {library}")
+            }
         }
     }
 }
@@ -63,9 +94,25 @@ impl std::error::Error for CompatError {}
 
 pub type ScalarRoutine = Arc<dyn Fn(&[f64]) -> Result<f64, CompatError> + Send + Sync + 'static>;
 
-#[derive(Default)]
+/// Job calls are counted for until [`CompatRegistry::set_job`]
+pub const DEFAULT_JOB: &str = "default";
+
 pub struct CompatRegistry {
     routines: BTreeMap<String, (RoutineSpec, ScalarRoutine)>,
+    legacy: BTreeMap<String, ForeignRoutine>,
+    job: String,
+    usage: Mutex<UsageLog>,
+}
+
+impl Default for CompatRegistry {
+    fn default() -> Self {
+        Self {
+            routines: BTreeMap::new(),
+            legacy: BTreeMap::new(),
+            job: DEFAULT_JOB.to_string(),
+            usage: Mutex::default(),
+        }
+    }
 }
 
 impl CompatRegistry {
@@ -73,6 +120,29 @@ impl CompatRegistry {
         Self::default()
     }
 
+    /// Count the following calls for `job`
+    pub fn set_job(&mut self, job: &str) {
+        self.job = job.to_string();
+    }
+
+    pub fn job(&self) -> &str {
+        &self.job
+    }
+
+    /// Calls routed so far, per job and routine
+    pub fn usage(&self) -> UsageLog {
+        self.usage_log().clone()
+    }
+
+    /// Calls routed so far, resetting the counts
+    pub fn take_usage(&self) -> UsageLog {
+        std::mem::take(&mut *self.usage_log())
+    }
+
+    fn usage_log(&self) -> std::sync::MutexGuard<'_, UsageLog> {
+        self.usage.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
     pub fn register_c(
         &mut self,
         symbol: &str,
@@ -91,8 +161,56 @@ impl CompatRegistry {
         self.register_internal(symbol, CallingConvention::Fortran, expected_args, routine)
     }
 
+    /// Register a legacy routine bound from a compiled library, to be
+    /// called with [`CompatRegistry::call_legacy`]
+    pub fn register_legacy(&mut self, mut routine: ForeignRoutine) -> RoutineHandle {
+        let language = match routine.spec.convention {
+            CallingConvention::C => LegacyLanguage::C,
+            CallingConvention::Fortran => LegacyLanguage::Fortran,
+        };
+        let canonical = canonical_symbol(&routine.spec.symbol, language);
+        routine.spec.symbol = canonical.clone();
+        self.legacy.insert(canonical.clone(), routine);
+        RoutineHandle { symbol: canonical }
+    }
+
     pub fn spec(&self, symbol: &str) -> Option<&RoutineSpec> {
-        self.routines.get(symbol).map(|entry| &entry.0)
+        self.routines
+            .get(symbol)
+            .map(|entry| &entry.0)
+            .or_else(|| self.legacy.get(symbol).map(|routine| &routine.spec))
+    }
+
+    /// Call the legacy routine `symbol` with `args`, which it may write to
+    ///
+    /// # Safety
+    ///
+    /// As for [`ForeignRoutine::call`]: every array argument must be as long
+    /// as the routine accesses for the given scalar arguments.
+    pub unsafe fn call_legacy(
+        &self,
+        symbol: &str,
+        args: &mut [LegacyArg<'_>],
+    ) -> Result<(), CompatError> {
+        let resolved = [
+            symbol.to_string(),
+            canonical_symbol(symbol, LegacyLanguage::C),
+            fortran_symbol(symbol),
+        ]
+        .into_iter()
+        .find(|candidate| self.legacy.contains_key(candidate))
+        .ok_or_else(|| CompatError::RoutineNotRegistered {
+            symbol: symbol.to_string(),
+        })?;
+        let result = unsafe { self.legacy[&resolved].call(args) };
+        let rejected = matches!(
+            result,
+            Err(CompatError::InvalidArgumentCount { .. } | CompatError::InvalidArgumentKind { .. })
+        );
+        if !rejected {
+            self.usage_log().record(&self.job, &resolved);
+        }
+        result
     }
 
     pub fn call(&self, symbol: &str, args: &[f64]) -> Result<f64, CompatError> {
@@ -113,6 +231,7 @@ impl CompatRegistry {
                 got: args.len(),
             });
         }
+        self.usage_log().record(&self.job, &resolved);
         routine(args)
     }
 
@@ -218,6 +337,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn routes_calls_to_legacy_routines() {
+        use crate::ffi::ForeignFn;
+
+        unsafe extern "C" fn nident_(x: *mut i32, px: *mut i32, n: *mut i32, id: *mut i32) {
+            let x = unsafe { std::slice::from_raw_parts(x, *n as usize) };
+            unsafe { *id = x.partition_point(|&v| v <= *px) as i32 };
+        }
+
+        let address = unsafe { ForeignFn::from_ptr(nident_ as *const _) }.unwrap();
+        let kinds = vec![ArgKind::IntArray, ArgKind::Int, ArgKind::Int, ArgKind::Int];
+        let routine =
+            unsafe { ForeignRoutine::new("NIDENT", CallingConvention::Fortran, kinds, address) };
+        let mut registry = CompatRegistry::new();
+        let handle = registry.register_legacy(routine);
+        assert_eq!(handle.symbol, "nident_");
+        assert_eq!(registry.spec("nident_").unwrap().expected_args, 4);
+
+        let mut x = [2, 4, 6, 8];
+        let (mut px, mut n, mut id) = (5, 4, 0);
+        // x holds the n = 4 values nident_ searches
+        unsafe {
+            registry.call_legacy(
+                "nident",
+                &mut [
+                    LegacyArg::IntArray(&mut x),
+                    LegacyArg::Int(&mut px),
+                    LegacyArg::Int(&mut n),
+                    LegacyArg::Int(&mut id),
+                ],
+            )
+        }
+        .unwrap();
+        assert_eq!(id, 2);
+
+        let err =
+            unsafe { registry.call_legacy("nident", &mut [LegacyArg::Int(&mut id)]) }.unwrap_err();
+        assert!(matches!(
+            err,
+            CompatError::InvalidArgumentCount {
+                expected: 4,
+                got: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            unsafe { registry.call_legacy("e_c3d", &mut []) },
+            Err(CompatError::RoutineNotRegistered { .. })
+        ));
+    }
+
+    #[test]
+    fn counts_routed_calls_per_job() {
+        let mut registry = CompatRegistry::new();
+        registry.register_fortran("calc", 2, Arc::new(|args| Ok(args[0] + args[1])));
+        registry.call("calc", &[1.0, 2.0]).unwrap();
+        registry.set_job("beam");
+        for _ in 0..3 {
+            registry.call("calc_", &[1.0, 2.0]).unwrap();
+        }
+        assert!(registry.call("calc", &[1.0]).is_err());
+        assert!(registry.call("missing", &[]).is_err());
+
+        let usage = registry.take_usage();
+        assert_eq!(usage.calls(DEFAULT_JOB, "calc_"), 1);
+        assert_eq!(usage.calls("beam", "calc_"), 3);
+        assert_eq!(usage.jobs().collect::<Vec<_>>(), ["beam", DEFAULT_JOB]);
+        assert!(registry.usage().is_empty());
+    }
+
     #[test]
     fn exposes_registered_specs() {
         let mut registry = CompatRegistry::new();
@@ -0,0 +1,475 @@
+//! Dispatch of legacy routines through raw symbols.
+//!
+//! Legacy Fortran subroutines take every argument by reference, followed by
+//! the lengths of their `CHARACTER` arguments by value. A [`ForeignRoutine`]
+//! describes such a routine by the kinds of its arguments; calling it
+//! checks the arguments against those kinds, passes each as a pointer to the
+//! caller's storage, so the routine writes its outputs in place, and appends
+//! the hidden string lengths for the Fortran convention. Only the kinds are
+//! checked: the routine learns the extent of an array from other arguments
+//! or from its own logic, so calls are `unsafe` and the caller guarantees
+//! the array lengths. Symbols come from a
+//! [`LegacyLibrary`] opened at run time, by default the library the build
+//! script linked from `CCX_LEGACY_OBJ_DIR` or found at `CCX_LEGACY_LIB`.
+
+use std::ffi::{CString, c_void};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use crate::bridge::{CallingConvention, CompatError, RoutineSpec};
+
+/// Most arguments of a legacy routine, hidden string lengths included
+pub const MAX_ARGS: usize = 64;
+
+/// Kind of a legacy routine argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArgKind {
+    /// `INTEGER` / `int*`
+    Int,
+    /// `REAL*8` / `double*`
+    Real,
+    IntArray,
+    RealArray,
+    /// `CHARACTER*(*)` / `char*`
+    Chars,
+}
+
+impl Display for ArgKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ArgKind::Int => "integer",
+            ArgKind::Real => "real",
+            ArgKind::IntArray => "integer array",
+            ArgKind::RealArray => "real array",
+            ArgKind::Chars => "character",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Argument of a legacy call, borrowed mutably since the routine may write
+/// to any of them
+#[derive(Debug)]
+pub enum LegacyArg<'a> {
+    Int(&'a mut i32),
+    Real(&'a mut f64),
+    IntArray(&'a mut [i32]),
+    RealArray(&'a mut [f64]),
+    Chars(&'a mut [u8]),
+}
+
+impl LegacyArg<'_> {
+    pub fn kind(&self) -> ArgKind {
+        match self {
+            LegacyArg::Int(_) => ArgKind::Int,
+            LegacyArg::Real(_) => ArgKind::Real,
+            LegacyArg::IntArray(_) => ArgKind::IntArray,
+            LegacyArg::RealArray(_) => ArgKind::RealArray,
+            LegacyArg::Chars(_) => ArgKind::Chars,
+        }
+    }
+
+    fn as_ptr(&mut self) -> *mut c_void {
+        match self {
+            LegacyArg::Int(value) => std::ptr::from_mut(*value).cast(),
+            LegacyArg::Real(value) => std::ptr::from_mut(*value).cast(),
+            LegacyArg::IntArray(values) => values.as_mut_ptr().cast(),
+            LegacyArg::RealArray(values) => values.as_mut_ptr().cast(),
+            LegacyArg::Chars(chars) => chars.as_mut_ptr().cast(),
+        }
+    }
+}
+
+/// Address of a routine in a loaded library
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForeignFn(NonNull<c_void>);
+
+// Code addresses are immutable and valid on every thread
+unsafe impl Send for ForeignFn {}
+unsafe impl Sync for ForeignFn {}
+
+impl ForeignFn {
+    /// # Safety
+    ///
+    /// `address` must point to a function taking only pointer-sized
+    /// arguments, for as long as the routine built from it is called.
+    pub unsafe fn from_ptr(address: *const c_void) -> Option<Self> {
+        NonNull::new(address.cast_mut()).map(Self)
+    }
+}
+
+/// Shared library of compiled legacy objects
+#[derive(Debug, Clone)]
+pub struct LegacyLibrary {
+    path: PathBuf,
+    handle: Arc<dl::Handle>,
+}
+
+impl LegacyLibrary {
+    pub fn open(path: &Path) -> Result<Self, CompatError> {
+        let handle = dl::Handle::open(path).map_err(|message| CompatError::LibraryUnavailable {
+            library: path.display().to_string(),
+            message,
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            handle: Arc::new(handle),
+        })
+    }
+
+    /// Library set up by the build script; unavailable when the crate was
+    /// built without legacy objects
+    pub fn bundled() -> Result<Self, CompatError> {
+        match option_env!("CCX_LEGACY_LIB") {
+            Some(path) => Self::open(Path::new(path)),
+            None => Err(CompatError::LibraryUnavailable {
+                library: "bundled".to_string(),
+                message: "built without CCX_LEGACY_OBJ_DIR or CCX_LEGACY_LIB".to_string(),
+            }),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Address of `symbol`, which must be the exact linker name
+    pub fn symbol(&self, symbol: &str) -> Result<ForeignFn, CompatError> {
+        let not_found = || CompatError::SymbolNotFound {
+            symbol: symbol.to_string(),
+            library: self.path.display().to_string(),
+        };
+        let name = CString::new(symbol).map_err(|_| not_found())?;
+        let address = self.handle.symbol(&name).ok_or_else(not_found)?;
+        // The caller of `bind` vouches for the signature
+        unsafe { ForeignFn::from_ptr(address) }.ok_or_else(not_found)
+    }
+}
+
+/// Legacy routine with the kinds of its arguments
+#[derive(Debug, Clone)]
+pub struct ForeignRoutine {
+    pub spec: RoutineSpec,
+    pub args: Vec<ArgKind>,
+    address: ForeignFn,
+    /// Library the routine was bound from, kept loaded with it
+    library: Option<LegacyLibrary>,
+}
+
+impl ForeignRoutine {
+    /// # Safety
+    ///
+    /// `address` must be a routine of `convention` whose arguments are
+    /// pointers to the kinds `args`, followed for the Fortran convention by
+    /// one length per [`ArgKind::Chars`] argument.
+    pub unsafe fn new(
+        symbol: &str,
+        convention: CallingConvention,
+        args: Vec<ArgKind>,
+        address: ForeignFn,
+    ) -> Self {
+        Self {
+            spec: RoutineSpec {
+                symbol: symbol.to_string(),
+                convention,
+                expected_args: args.len(),
+            },
+            args,
+            address,
+            library: None,
+        }
+    }
+
+    /// Routine `symbol` of `library`
+    ///
+    /// # Safety
+    ///
+    /// As for [`ForeignRoutine::new`], for the routine `symbol` resolves to.
+    pub unsafe fn bind(
+        library: &LegacyLibrary,
+        symbol: &str,
+        convention: CallingConvention,
+        args: Vec<ArgKind>,
+    ) -> Result<Self, CompatError> {
+        let address = library.symbol(symbol)?;
+        let mut routine = unsafe { Self::new(symbol, convention, args, address) };
+        routine.library = Some(library.clone());
+        Ok(routine)
+    }
+
+    pub fn library(&self) -> Option<&LegacyLibrary> {
+        self.library.as_ref()
+    }
+
+    /// Call the routine with `args`, after checking them against its
+    /// argument kinds
+    ///
+    /// # Safety
+    ///
+    /// Every [`LegacyArg::IntArray`] and [`LegacyArg::RealArray`] must hold
+    /// at least as many elements as the routine reads or writes for the
+    /// values of the other arguments, e.g. `n` for `x(n)` or `3*nk` for
+    /// `co(3,*)`. Scalars and character lengths are checked.
+    pub unsafe fn call(&self, args: &mut [LegacyArg<'_>]) -> Result<(), CompatError> {
+        let symbol = &self.spec.symbol;
+        if args.len() != self.args.len() {
+            return Err(CompatError::InvalidArgumentCount {
+                symbol: symbol.clone(),
+                expected: self.args.len(),
+                got: args.len(),
+            });
+        }
+        for (index, (arg, &expected)) in args.iter().zip(&self.args).enumerate() {
+            if arg.kind() != expected {
+                return Err(CompatError::InvalidArgumentKind {
+                    symbol: symbol.clone(),
+                    index,
+                    expected,
+                    got: arg.kind(),
+                });
+            }
+        }
+
+        let mut pointers: Vec<*mut c_void> = args.iter_mut().map(LegacyArg::as_ptr).collect();
+        if self.spec.convention == CallingConvention::Fortran {
+            // Hidden lengths are passed by value in the registers and slots
+            // of pointers
+            let lengths = args.iter().filter_map(|arg| match arg {
+                LegacyArg::Chars(chars) => Some(chars.len() as *mut c_void),
+                _ => None,
+            });
+            pointers.extend(lengths);
+        }
+        // Kinds checked against the signature the routine was built with,
+        // array extents vouched for by the caller
+        unsafe { dispatch(self.address, &pointers) }.ok_or_else(|| CompatError::InvocationFailed {
+            symbol: symbol.clone(),
+            message: format!("{} arguments, at most {MAX_ARGS} supported", pointers.len()),
+        })
+    }
+}
+
+/// Call `f(p[0], p[1], ...)` for the first slice pattern matching the
+/// length of `p`, growing by one identifier per step
+macro_rules! dispatch_arity {
+    ($f:ident, $p:ident; [$($done:ident)*]; $next:ident $($rest:ident)*) => {
+        if let &[$($done),*] = $p {
+            let f: unsafe extern "C" fn($(dispatch_arity!(@ptr $done)),*) =
+                unsafe { std::mem::transmute($f.0.as_ptr()) };
+            unsafe { f($($done),*) };
+            Some(())
+        } else {
+            dispatch_arity!($f, $p; [$($done)* $next]; $($rest)*)
+        }
+    };
+    ($f:ident, $p:ident; [$($done:ident)*];) => {
+        if let &[$($done),*] = $p {
+            let f: unsafe extern "C" fn($(dispatch_arity!(@ptr $done)),*) =
+                unsafe { std::mem::transmute($f.0.as_ptr()) };
+            unsafe { f($($done),*) };
+            Some(())
+        } else {
+            None
+        }
+    };
+    (@ptr $a:ident) => { *mut c_void };
+}
+
+/// # Safety
+///
+/// `f` must take exactly `p.len()` pointer-sized arguments.
+unsafe fn dispatch(f: ForeignFn, p: &[*mut c_void]) -> Option<()> {
+    dispatch_arity!(f, p; [];
+        a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15
+        a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26 a27 a28 a29 a30 a31
+        a32 a33 a34 a35 a36 a37 a38 a39 a40 a41 a42 a43 a44 a45 a46 a47
+        a48 a49 a50 a51 a52 a53 a54 a55 a56 a57 a58 a59 a60 a61 a62 a63
+    )
+}
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{CStr, CString, c_char, c_int, c_void};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    const RTLD_NOW: c_int = 2;
+
+    unsafe extern "C" {
+        fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        fn dlerror() -> *mut c_char;
+        fn dlclose(handle: *mut c_void) -> c_int;
+    }
+
+    #[derive(Debug)]
+    pub struct Handle(*mut c_void);
+
+    // SAFETY: the handle is an opaque token owned by the dynamic loader;
+    // dlsym and dlclose may be called with it from any thread
+    unsafe impl Send for Handle {}
+    unsafe impl Sync for Handle {}
+
+    impl Handle {
+        pub fn open(path: &Path) -> Result<Self, String> {
+            let name = CString::new(path.as_os_str().as_bytes())
+                .map_err(|_| "path contains a NUL byte".to_string())?;
+            // SAFETY: `name` is a NUL-terminated string that outlives the call
+            let handle = unsafe { dlopen(name.as_ptr(), RTLD_NOW) };
+            if handle.is_null() {
+                return Err(last_error());
+            }
+            Ok(Self(handle))
+        }
+
+        pub fn symbol(&self, name: &CStr) -> Option<*const c_void> {
+            // SAFETY: `self.0` is a handle from dlopen that is only closed on
+            // drop, and `name` is NUL-terminated
+            let address = unsafe { dlsym(self.0, name.as_ptr()) };
+            (!address.is_null()).then_some(address.cast_const())
+        }
+    }
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            // SAFETY: `self.0` came from a successful dlopen and is closed
+            // exactly once; routines bound from it hold a clone of the
+            // library, so none outlives the handle
+            unsafe { dlclose(self.0) };
+        }
+    }
+
+    fn last_error() -> String {
+        // SAFETY: dlerror takes no arguments and returns null or a string
+        // owned by the loader
+        let message = unsafe { dlerror() };
+        if message.is_null() {
+            return "unknown dlopen error".to_string();
+        }
+        // SAFETY: `message` is a non-null NUL-terminated string, copied
+        // before any further dl* call can overwrite it
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(not(unix))]
+mod dl {
+    use std::ffi::{CStr, c_void};
+    use std::path::Path;
+
+    #[derive(Debug)]
+    pub struct Handle;
+
+    impl Handle {
+        pub fn open(_path: &Path) -> Result<Self, String> {
+            Err("loading legacy libraries is only supported on unix".to_string())
+        }
+
+        pub fn symbol(&self, _name: &CStr) -> Option<*const c_void> {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `y = a*x + y` over `n` values, as a Fortran subroutine
+    unsafe extern "C" fn daxpy_(n: *mut i32, a: *mut f64, x: *mut f64, y: *mut f64) {
+        let n = unsafe { *n } as usize;
+        let x = unsafe { std::slice::from_raw_parts(x, n) };
+        let y = unsafe { std::slice::from_raw_parts_mut(y, n) };
+        for (y, x) in y.iter_mut().zip(x) {
+            *y += unsafe { *a } * x;
+        }
+    }
+
+    /// Upper-cases a `CHARACTER*(*)` label and counts its blanks
+    unsafe extern "C" fn upcase_(label: *mut u8, blanks: *mut i32, length: usize) {
+        let label = unsafe { std::slice::from_raw_parts_mut(label, length) };
+        label.make_ascii_uppercase();
+        unsafe { *blanks = label.iter().filter(|&&c| c == b' ').count() as i32 };
+    }
+
+    fn routine(symbol: &str, args: Vec<ArgKind>, address: *const c_void) -> ForeignRoutine {
+        let address = unsafe { ForeignFn::from_ptr(address) }.unwrap();
+        unsafe { ForeignRoutine::new(symbol, CallingConvention::Fortran, args, address) }
+    }
+
+    #[test]
+    fn marshals_scalars_arrays_and_string_lengths() {
+        let kinds = vec![
+            ArgKind::Int,
+            ArgKind::Real,
+            ArgKind::RealArray,
+            ArgKind::RealArray,
+        ];
+        let axpy = routine("daxpy_", kinds, daxpy_ as *const c_void);
+        let (mut n, mut a) = (3, 2.0);
+        let mut x = [1.0, 2.0, 3.0];
+        let mut y = [10.0, 20.0, 30.0];
+        // x and y hold the n = 3 values daxpy_ reads
+        unsafe {
+            axpy.call(&mut [
+                LegacyArg::Int(&mut n),
+                LegacyArg::Real(&mut a),
+                LegacyArg::RealArray(&mut x),
+                LegacyArg::RealArray(&mut y),
+            ])
+        }
+        .unwrap();
+        assert_eq!(y, [12.0, 24.0, 36.0]);
+
+        let upcase = routine(
+            "upcase_",
+            vec![ArgKind::Chars, ArgKind::Int],
+            upcase_ as *const c_void,
+        );
+        let mut label = *b"nall  ";
+        let mut blanks = 0;
+        unsafe { upcase.call(&mut [LegacyArg::Chars(&mut label), LegacyArg::Int(&mut blanks)]) }
+            .unwrap();
+        assert_eq!(&label, b"NALL  ");
+        assert_eq!(blanks, 2);
+
+        let err =
+            unsafe { upcase.call(&mut [LegacyArg::Int(&mut blanks), LegacyArg::Int(&mut n)]) }
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid argument 0 for upcase_: expected character, got integer"
+        );
+    }
+
+    #[test]
+    fn reports_missing_libraries_and_symbols() {
+        let err = LegacyLibrary::open(Path::new("/nonexistent/libccx_legacy.so")).unwrap_err();
+        assert!(
+            matches!(err, CompatError::LibraryUnavailable { .. }),
+            "{err}"
+        );
+
+        #[cfg(target_os = "linux")]
+        {
+            let libm = LegacyLibrary::open(Path::new("libm.so.6")).unwrap();
+            assert!(libm.symbol("cos").is_ok());
+            let err = libm.symbol("e_c3d_").unwrap_err();
+            assert_eq!(
+                err,
+                CompatError::SymbolNotFound {
+                    symbol: "e_c3d_".to_string(),
+                    library: "libm.so.6".to_string(),
+                }
+            );
+            let bound = unsafe {
+                ForeignRoutine::bind(&libm, "missing_", CallingConvention::Fortran, vec![])
+            };
+            assert!(bound.is_err());
+        }
+    }
+}
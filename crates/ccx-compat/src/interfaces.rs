@@ -0,0 +1,530 @@
+//! Routine descriptions read from legacy Fortran sources.
+//!
+//! The argument list of every `subroutine` and the declarations of its
+//! body give the kinds a [`ForeignRoutine`] needs, so legacy routines can be
+//! bound by name instead of by hand-written descriptions. Sources are read
+//! in the fixed form of CalculiX: comments start in column 1, continuations
+//! have a character in column 6, and `!` starts a trailing comment.
+//! Arguments are typed by their declarations, `dimension` statements and
+//! the `implicit` rules of the routine; routines with arguments of other
+//! types than the [`ArgKind`]s, such as `real*4` or `complex`, are reported
+//! instead of described.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::bridge::{CallingConvention, CompatError, CompatRegistry, RoutineHandle, RoutineSpec};
+use crate::ffi::{ArgKind, ForeignRoutine, LegacyLibrary};
+use crate::symbols::fortran_symbol;
+
+/// Argument of a legacy subroutine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FortranArgument {
+    pub name: String,
+    pub kind: ArgKind,
+}
+
+/// Interface of a legacy subroutine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FortranInterface {
+    /// Linker symbol, with the trailing underscore
+    pub spec: RoutineSpec,
+    pub arguments: Vec<FortranArgument>,
+}
+
+impl FortranInterface {
+    pub fn kinds(&self) -> Vec<ArgKind> {
+        self.arguments.iter().map(|arg| arg.kind).collect()
+    }
+}
+
+/// Interfaces of the subroutines in `source`, and a message for every
+/// subroutine that cannot be described
+pub fn parse_fortran_interfaces(source: &str) -> (Vec<FortranInterface>, Vec<String>) {
+    let mut interfaces = Vec::new();
+    let mut errors = Vec::new();
+    let mut unit: Option<Unit> = None;
+    for (line, statement) in statements(source) {
+        let text = statement.to_ascii_lowercase();
+        match unit.as_mut() {
+            None => {
+                if let Some(header) = subroutine_header(&text) {
+                    unit = Some(Unit::new(line, header));
+                }
+            }
+            Some(current) => {
+                if text == "end" || text.starts_with("end subroutine") {
+                    match unit.take().unwrap().finish() {
+                        Ok(interface) => interfaces.push(interface),
+                        Err(message) => errors.push(message),
+                    }
+                } else if let Err(message) = current.declare(&text) {
+                    errors.push(format!("line {line}: {message}"));
+                }
+            }
+        }
+    }
+    if let Some(unit) = unit {
+        errors.push(format!(
+            "line {}: subroutine {} has no end",
+            unit.line, unit.name
+        ));
+    }
+    (interfaces, errors)
+}
+
+/// Interfaces of the `.f` sources under `dir`, in path order; messages are
+/// prefixed with the file they come from
+pub fn scan_fortran_dir(dir: &Path) -> Result<(Vec<FortranInterface>, Vec<String>), String> {
+    let mut files = Vec::new();
+    collect_sources(dir, &mut files)?;
+    files.sort();
+    let mut interfaces = Vec::new();
+    let mut errors = Vec::new();
+    for file in files {
+        let source = fs::read(&file).map_err(|e| format!("cannot read {}: {e}", file.display()))?;
+        let (found, failed) = parse_fortran_interfaces(&String::from_utf8_lossy(&source));
+        interfaces.extend(found);
+        let name = file
+            .strip_prefix(dir)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        errors.extend(
+            failed
+                .into_iter()
+                .map(|message| format!("{name}: {message}")),
+        );
+    }
+    Ok((interfaces, errors))
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("cannot read {}: {e}", dir.display()))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("cannot read {}: {e}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            collect_sources(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("f"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+impl CompatRegistry {
+    /// Bind every interface to its symbol in `library`; returns the handles
+    /// of the bound routines and the errors of the missing ones
+    ///
+    /// # Safety
+    ///
+    /// The interfaces must describe the routines compiled into `library`.
+    pub unsafe fn bind_interfaces(
+        &mut self,
+        library: &LegacyLibrary,
+        interfaces: &[FortranInterface],
+    ) -> (Vec<RoutineHandle>, Vec<CompatError>) {
+        let mut handles = Vec::new();
+        let mut errors = Vec::new();
+        for interface in interfaces {
+            let spec = &interface.spec;
+            let bound = unsafe {
+                ForeignRoutine::bind(library, &spec.symbol, spec.convention, interface.kinds())
+            };
+            match bound {
+                Ok(routine) => handles.push(self.register_legacy(routine)),
+                Err(err) => errors.push(err),
+            }
+        }
+        (handles, errors)
+    }
+}
+
+/// Statements of fixed-form `source` with the line they start on,
+/// continuations joined and comments removed
+fn statements(source: &str) -> Vec<(usize, String)> {
+    let mut statements: Vec<(usize, String)> = Vec::new();
+    for (index, raw) in source.lines().enumerate() {
+        if raw.starts_with(['c', 'C', '*', '!']) || raw.trim().is_empty() {
+            continue;
+        }
+        let code = match raw.find('!') {
+            Some(bang) => &raw[..bang],
+            None => raw,
+        };
+        let continued = code.len() > 5
+            && !code.starts_with('\t')
+            && !matches!(code.as_bytes()[5], b' ' | b'0')
+            && code[..5].trim().is_empty();
+        match statements.last_mut() {
+            Some((_, statement)) if continued => statement.push_str(code[6..].trim()),
+            _ => {
+                let text = code.trim();
+                if !text.is_empty() {
+                    statements.push((index + 1, text.to_string()));
+                }
+            }
+        }
+    }
+    statements
+}
+
+/// Name and argument names of a `subroutine` statement
+fn subroutine_header(text: &str) -> Option<(String, Vec<String>)> {
+    let rest = text
+        .strip_prefix("subroutine")
+        .or_else(|| text.strip_prefix("recursive subroutine"))?;
+    if !rest.starts_with([' ', '(']) {
+        return None;
+    }
+    let (name, args) = match rest.split_once('(') {
+        Some((name, args)) => (name, args.trim_end().strip_suffix(')')?),
+        None => (rest, ""),
+    };
+    let args = args
+        .split(',')
+        .map(|arg| arg.trim().to_string())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    Some((name.trim().to_string(), args))
+}
+
+/// Type of a Fortran declaration, by the kind of a scalar of that type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Scalar(ArgKind),
+    Unsupported,
+}
+
+/// Subroutine read up to its `end`
+struct Unit {
+    line: usize,
+    name: String,
+    args: Vec<String>,
+    types: Vec<Option<Type>>,
+    arrays: Vec<bool>,
+    /// Type of names starting with `a` to `z` without declaration
+    implicit: [Option<Type>; 26],
+}
+
+impl Unit {
+    fn new(line: usize, (name, args): (String, Vec<String>)) -> Self {
+        let mut implicit = [Some(Type::Unsupported); 26];
+        for letter in b'i'..=b'n' {
+            implicit[(letter - b'a') as usize] = Some(Type::Scalar(ArgKind::Int));
+        }
+        let count = args.len();
+        Self {
+            line,
+            name,
+            args,
+            types: vec![None; count],
+            arrays: vec![false; count],
+            implicit,
+        }
+    }
+
+    fn argument(&self, name: &str) -> Option<usize> {
+        self.args.iter().position(|arg| arg == name)
+    }
+
+    /// Take the types and dimensions of a statement of the body
+    fn declare(&mut self, text: &str) -> Result<(), String> {
+        if let Some(rules) = text.strip_prefix("implicit") {
+            return self.implicit(rules.trim());
+        }
+        if let Some(entities) = text.strip_prefix("dimension") {
+            for entity in split_top_level(entities) {
+                let (name, dimensions) = entity_name(&entity);
+                if let (Some(index), true) = (self.argument(&name), dimensions) {
+                    self.arrays[index] = true;
+                }
+            }
+            return Ok(());
+        }
+        let Some((ty, entities)) = declaration(text) else {
+            return Ok(());
+        };
+        for entity in split_top_level(entities) {
+            let (name, dimensions) = entity_name(&entity);
+            if let Some(index) = self.argument(&name) {
+                self.types[index] = Some(ty);
+                self.arrays[index] |= dimensions;
+            }
+        }
+        Ok(())
+    }
+
+    fn implicit(&mut self, rules: &str) -> Result<(), String> {
+        if rules == "none" {
+            self.implicit = [None; 26];
+            return Ok(());
+        }
+        let Some((ty, ranges)) = declaration(rules) else {
+            return Err(format!("unsupported implicit statement in {}", self.name));
+        };
+        let ranges = ranges
+            .trim()
+            .strip_prefix('(')
+            .and_then(|r| r.strip_suffix(')'))
+            .ok_or_else(|| format!("unsupported implicit statement in {}", self.name))?;
+        for range in ranges.split(',') {
+            let letters: Vec<u8> = range.bytes().filter(u8::is_ascii_lowercase).collect();
+            let (first, last) = match letters[..] {
+                [letter] => (letter, letter),
+                [first, last] => (first, last),
+                _ => {
+                    return Err(format!(
+                        "unsupported implicit range {range} in {}",
+                        self.name
+                    ));
+                }
+            };
+            for letter in first..=last {
+                self.implicit[(letter - b'a') as usize] = Some(ty);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<FortranInterface, String> {
+        let mut arguments = Vec::with_capacity(self.args.len());
+        for (index, name) in self.args.iter().enumerate() {
+            let ty = self.types[index].or_else(|| {
+                let first = name.bytes().next().filter(u8::is_ascii_lowercase)?;
+                self.implicit[(first - b'a') as usize]
+            });
+            let kind = match (ty, self.arrays[index]) {
+                (Some(Type::Scalar(ArgKind::Int)), true) => ArgKind::IntArray,
+                (Some(Type::Scalar(ArgKind::Real)), true) => ArgKind::RealArray,
+                (Some(Type::Scalar(kind)), _) => kind,
+                (Some(Type::Unsupported), _) => {
+                    return Err(format!(
+                        "line {}: argument {name} of {} has an unsupported type",
+                        self.line, self.name
+                    ));
+                }
+                (None, _) => {
+                    return Err(format!(
+                        "line {}: argument {name} of {} is not declared",
+                        self.line, self.name
+                    ));
+                }
+            };
+            arguments.push(FortranArgument {
+                name: name.clone(),
+                kind,
+            });
+        }
+        Ok(FortranInterface {
+            spec: RoutineSpec {
+                symbol: fortran_symbol(&self.name),
+                convention: CallingConvention::Fortran,
+                expected_args: arguments.len(),
+            },
+            arguments,
+        })
+    }
+}
+
+/// Type and entity list of a type declaration statement
+fn declaration(text: &str) -> Option<(Type, &str)> {
+    const TYPES: [(&str, Type); 10] = [
+        ("double precision", Type::Scalar(ArgKind::Real)),
+        ("real*8", Type::Scalar(ArgKind::Real)),
+        ("real(8)", Type::Scalar(ArgKind::Real)),
+        ("real(kind=8)", Type::Scalar(ArgKind::Real)),
+        ("integer*8", Type::Unsupported),
+        ("integer", Type::Scalar(ArgKind::Int)),
+        ("logical", Type::Scalar(ArgKind::Int)),
+        ("character", Type::Scalar(ArgKind::Chars)),
+        ("real", Type::Unsupported),
+        ("complex", Type::Unsupported),
+    ];
+    let (ty, rest) = TYPES
+        .iter()
+        .find_map(|(name, ty)| Some((*ty, text.strip_prefix(name)?)))?;
+    let rest = match rest.split_once("::") {
+        Some((_, entities)) => entities,
+        None => {
+            let rest =
+                rest.trim_start_matches(['*', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9']);
+            // Length of a character declaration
+            match rest.trim_start().strip_prefix('(') {
+                Some(inner) if ty == Type::Scalar(ArgKind::Chars) => {
+                    inner.split_once(')').map_or(inner, |(_, after)| after)
+                }
+                _ => rest,
+            }
+        }
+    };
+    if rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+        // A statement starting with a type name, such as `integral = 1`
+        return None;
+    }
+    Some((ty, rest))
+}
+
+/// Entities of a declaration, split at the commas outside of parentheses
+fn split_top_level(entities: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut depth = 0usize;
+    for c in entities.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        parts.last_mut().unwrap().push(c);
+    }
+    parts.retain(|part| !part.trim().is_empty());
+    parts
+}
+
+/// Name of an entity and whether it has dimensions
+fn entity_name(entity: &str) -> (String, bool) {
+    let entity = entity.trim();
+    let end = entity
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(entity.len());
+    let rest = entity[end..].trim_start();
+    // `amat*80` and `s*(*)` set a length, `x(3,*)` dimensions
+    let rest = match rest.strip_prefix('*').map(str::trim_start) {
+        Some(length) if length.starts_with('(') => length
+            .split_once(')')
+            .map_or("", |(_, after)| after.trim_start()),
+        Some(length) => length.trim_start_matches(|c: char| c.is_ascii_digit() || c == ' '),
+        None => rest,
+    };
+    (entity[..end].to_string(), rest.starts_with('('))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NIDENT: &str = "\
+!
+!     CalculiX - A 3-dimensional finite element program
+!
+      subroutine nident(x,px,n,id)
+!
+!     identifies the position id of px in an array x of
+!     dimension n
+!
+      implicit none
+!
+      integer x,px,n,id,n2,m
+!
+      dimension x(n)
+!
+      id=0
+      if(n.eq.0) return
+      end
+";
+
+    const MATERIAL: &str = "\
+      subroutine umat_user(amat,iel,kode,elconloc,emec,
+     &  stiff,nstate_,xstate,pnewdt)  ! user material
+C
+      implicit none
+      character*80 amat
+      integer iel,kode,nstate_
+      real*8 elconloc(*),emec(6),stiff(21),xstate(nstate_,*),
+     &  pnewdt
+      return
+      end subroutine umat_user
+      real*8 function area(x)
+      real*8 x
+      area=x
+      end
+      subroutine old(i,a,s)
+      implicit real*8(a-h,o-z)
+      character s*(*)
+      dimension a(3)
+      end
+      subroutine single(x)
+      real x
+      end
+";
+
+    #[test]
+    fn reads_arguments_of_fixed_form_subroutines() {
+        let (interfaces, errors) = parse_fortran_interfaces(NIDENT);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(interfaces.len(), 1);
+        let nident = &interfaces[0];
+        assert_eq!(nident.spec.symbol, "nident_");
+        assert_eq!(nident.spec.convention, CallingConvention::Fortran);
+        assert_eq!(nident.spec.expected_args, 4);
+        assert_eq!(
+            nident.kinds(),
+            [ArgKind::IntArray, ArgKind::Int, ArgKind::Int, ArgKind::Int]
+        );
+
+        let (interfaces, errors) = parse_fortran_interfaces(MATERIAL);
+        let names: Vec<&str> = interfaces.iter().map(|i| i.spec.symbol.as_str()).collect();
+        assert_eq!(names, ["umat_user_", "old_"]);
+        use ArgKind::*;
+        assert_eq!(
+            interfaces[0].kinds(),
+            [
+                Chars, Int, Int, RealArray, RealArray, RealArray, Int, RealArray, Real
+            ]
+        );
+        assert_eq!(interfaces[1].kinds(), [Int, RealArray, Chars]);
+        assert_eq!(
+            errors,
+            ["line 20: argument x of single has an unsupported type"]
+        );
+    }
+
+    #[test]
+    fn scans_source_trees_and_binds_interfaces() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock should be valid")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "ccx_compat_interfaces_{}_{nanos}",
+            std::process::id()
+        ));
+        fs::create_dir_all(dir.join("materials")).unwrap();
+        fs::write(dir.join("nident.f"), NIDENT).unwrap();
+        fs::write(dir.join("materials/umat_user.f"), MATERIAL).unwrap();
+        fs::write(dir.join("notes.txt"), "subroutine skipped(x)").unwrap();
+        fs::write(dir.join("broken.f"), "      subroutine open(x)\n").unwrap();
+
+        let (interfaces, errors) = scan_fortran_dir(&dir).unwrap();
+        let names: Vec<&str> = interfaces.iter().map(|i| i.spec.symbol.as_str()).collect();
+        assert_eq!(names, ["umat_user_", "old_", "nident_"]);
+        assert_eq!(
+            errors,
+            [
+                "broken.f: line 1: subroutine open has no end",
+                "materials/umat_user.f: line 20: argument x of single has an unsupported type",
+            ]
+        );
+        assert!(scan_fortran_dir(&dir.join("missing")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+
+        #[cfg(target_os = "linux")]
+        {
+            let libm = LegacyLibrary::open(Path::new("libm.so.6")).unwrap();
+            let mut registry = CompatRegistry::new();
+            let (handles, errors) = unsafe { registry.bind_interfaces(&libm, &interfaces) };
+            assert!(handles.is_empty());
+            assert_eq!(errors.len(), 3);
+            assert!(matches!(errors[0], CompatError::SymbolNotFound { .. }));
+        }
+    }
+}
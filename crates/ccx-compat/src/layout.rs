@@ -0,0 +1,326 @@
+//! Column-major array views for legacy routine arguments.
+//!
+//! Fortran stores `a(i,j)` at offset `(i-1) + (j-1)*n` for `a(n,*)`: the
+//! first index runs fastest and indices start at 1. [`FortranArray`] and
+//! [`FortranArrayMut`] view a slice with those rules, so shims index arrays
+//! exactly as the legacy source does. A row-major Rust array has the same
+//! memory as the column-major array with its dimensions reversed;
+//! [`FortranArray::from_row_major`] views it that way without copying, with
+//! the indices reversed, and [`to_column_major`] / [`to_row_major`] copy
+//! between the layouts when a routine needs the same index order.
+
+use std::ops::{Index, IndexMut};
+
+use crate::ffi::LegacyArg;
+
+/// Offset of the 1-based `index` in a column-major array of `dims`; `None`
+/// for an index outside the array
+pub fn column_major_offset<const N: usize>(dims: [usize; N], index: [usize; N]) -> Option<usize> {
+    let mut offset = 0;
+    let mut stride = 1;
+    for (dim, i) in dims.into_iter().zip(index) {
+        if i == 0 || i > dim {
+            return None;
+        }
+        offset += (i - 1) * stride;
+        stride *= dim;
+    }
+    Some(offset)
+}
+
+fn check_len(len: usize, dims: &[usize]) -> Result<(), String> {
+    let expected: usize = dims.iter().product();
+    match len == expected {
+        true => Ok(()),
+        false => Err(format!(
+            "array of {len} values does not match dimensions {dims:?} ({expected} values)"
+        )),
+    }
+}
+
+/// Dimensions with the last one taken from `len`, like the `*` of an
+/// assumed-size array
+fn assumed<const N: usize>(len: usize, mut dims: [usize; N]) -> Result<[usize; N], String> {
+    let leading: usize = dims[..N.saturating_sub(1)].iter().product();
+    if N == 0 || leading == 0 || !len.is_multiple_of(leading) {
+        return Err(format!(
+            "array of {len} values does not fill leading dimensions {:?}",
+            &dims[..N.saturating_sub(1)]
+        ));
+    }
+    dims[N - 1] = len / leading;
+    Ok(dims)
+}
+
+fn reversed<const N: usize>(mut dims: [usize; N]) -> [usize; N] {
+    dims.reverse();
+    dims
+}
+
+/// Column-major view of a slice, indexed from 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FortranArray<'a, T, const N: usize> {
+    data: &'a [T],
+    dims: [usize; N],
+}
+
+impl<'a, T, const N: usize> FortranArray<'a, T, N> {
+    /// View of `data` as a column-major array of `dims`
+    pub fn new(data: &'a [T], dims: [usize; N]) -> Result<Self, String> {
+        check_len(data.len(), &dims)?;
+        Ok(Self { data, dims })
+    }
+
+    /// View of `data` with the last dimension taken from its length; the
+    /// last entry of `dims` is ignored
+    pub fn assumed_size(data: &'a [T], dims: [usize; N]) -> Result<Self, String> {
+        let dims = assumed(data.len(), dims)?;
+        Ok(Self { data, dims })
+    }
+
+    /// View of the row-major array `data` of `dims` without copying: the
+    /// column-major array of the reversed dimensions, so element
+    /// `[i][j]` of the row-major array is `(j+1, i+1)` of the view
+    pub fn from_row_major(data: &'a [T], dims: [usize; N]) -> Result<Self, String> {
+        Self::new(data, reversed(dims))
+    }
+
+    pub fn dims(&self) -> [usize; N] {
+        self.dims
+    }
+
+    pub fn as_slice(&self) -> &'a [T] {
+        self.data
+    }
+
+    /// Element at the 1-based `index`
+    pub fn get(&self, index: [usize; N]) -> Option<&'a T> {
+        column_major_offset(self.dims, index).map(|offset| &self.data[offset])
+    }
+}
+
+impl<T, const N: usize> Index<[usize; N]> for FortranArray<'_, T, N> {
+    type Output = T;
+
+    fn index(&self, index: [usize; N]) -> &T {
+        match self.get(index) {
+            Some(value) => value,
+            None => panic!("index {index:?} out of bounds {:?}", self.dims),
+        }
+    }
+}
+
+/// Mutable column-major view of a slice, indexed from 1
+#[derive(Debug, PartialEq)]
+pub struct FortranArrayMut<'a, T, const N: usize> {
+    data: &'a mut [T],
+    dims: [usize; N],
+}
+
+impl<'a, T, const N: usize> FortranArrayMut<'a, T, N> {
+    pub fn new(data: &'a mut [T], dims: [usize; N]) -> Result<Self, String> {
+        check_len(data.len(), &dims)?;
+        Ok(Self { data, dims })
+    }
+
+    pub fn assumed_size(data: &'a mut [T], dims: [usize; N]) -> Result<Self, String> {
+        let dims = assumed(data.len(), dims)?;
+        Ok(Self { data, dims })
+    }
+
+    /// As [`FortranArray::from_row_major`]
+    pub fn from_row_major(data: &'a mut [T], dims: [usize; N]) -> Result<Self, String> {
+        Self::new(data, reversed(dims))
+    }
+
+    pub fn dims(&self) -> [usize; N] {
+        self.dims
+    }
+
+    pub fn as_view(&self) -> FortranArray<'_, T, N> {
+        FortranArray {
+            data: self.data,
+            dims: self.dims,
+        }
+    }
+
+    pub fn get(&self, index: [usize; N]) -> Option<&T> {
+        column_major_offset(self.dims, index).map(|offset| &self.data[offset])
+    }
+
+    pub fn get_mut(&mut self, index: [usize; N]) -> Option<&mut T> {
+        column_major_offset(self.dims, index).map(|offset| &mut self.data[offset])
+    }
+}
+
+impl<const N: usize> FortranArrayMut<'_, f64, N> {
+    /// The array as a `REAL*8` array argument of a legacy call
+    pub fn as_arg(&mut self) -> LegacyArg<'_> {
+        LegacyArg::RealArray(self.data)
+    }
+}
+
+impl<const N: usize> FortranArrayMut<'_, i32, N> {
+    /// The array as an `INTEGER` array argument of a legacy call
+    pub fn as_arg(&mut self) -> LegacyArg<'_> {
+        LegacyArg::IntArray(self.data)
+    }
+}
+
+impl<T, const N: usize> Index<[usize; N]> for FortranArrayMut<'_, T, N> {
+    type Output = T;
+
+    fn index(&self, index: [usize; N]) -> &T {
+        match self.get(index) {
+            Some(value) => value,
+            None => panic!("index {index:?} out of bounds {:?}", self.dims),
+        }
+    }
+}
+
+impl<T, const N: usize> IndexMut<[usize; N]> for FortranArrayMut<'_, T, N> {
+    fn index_mut(&mut self, index: [usize; N]) -> &mut T {
+        let dims = self.dims;
+        match self.get_mut(index) {
+            Some(value) => value,
+            None => panic!("index {index:?} out of bounds {dims:?}"),
+        }
+    }
+}
+
+/// Copy of the row-major array `data` of `dims` in column-major order, with
+/// the same index order
+pub fn to_column_major<T: Copy, const N: usize>(
+    data: &[T],
+    dims: [usize; N],
+) -> Result<Vec<T>, String> {
+    check_len(data.len(), &dims)?;
+    let reversed_dims = reversed(dims);
+    let mut out = Vec::with_capacity(data.len());
+    for_each_index(dims, |index| {
+        // Row-major offsets are column-major offsets of the reversed indices
+        let offset = column_major_offset(reversed_dims, reversed(index)).expect("index in bounds");
+        out.push(data[offset]);
+    });
+    Ok(out)
+}
+
+/// Copy of the column-major array `data` of `dims` in row-major order, with
+/// the same index order
+pub fn to_row_major<T: Copy, const N: usize>(
+    data: &[T],
+    dims: [usize; N],
+) -> Result<Vec<T>, String> {
+    check_len(data.len(), &dims)?;
+    let reversed_dims = reversed(dims);
+    let mut out = Vec::with_capacity(data.len());
+    // Walk the row-major order: the last index fastest
+    for_each_index(reversed_dims, |index| {
+        let offset = column_major_offset(dims, reversed(index)).expect("index in bounds");
+        out.push(data[offset]);
+    });
+    Ok(out)
+}
+
+/// Every 1-based index of `dims` in column-major order
+fn for_each_index<const N: usize>(dims: [usize; N], mut f: impl FnMut([usize; N])) {
+    if dims.contains(&0) {
+        return;
+    }
+    let mut index = [1; N];
+    loop {
+        f(index);
+        let mut k = 0;
+        while k < N {
+            if index[k] < dims[k] {
+                index[k] += 1;
+                break;
+            }
+            index[k] = 1;
+            k += 1;
+        }
+        if k == N {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::CallingConvention;
+    use crate::ffi::{ArgKind, ForeignFn, ForeignRoutine};
+
+    #[test]
+    fn indexes_like_fortran_and_views_row_major_data() {
+        // a(2,3) = [[11, 12, 13], [21, 22, 23]] stored by columns
+        let column_major = [11, 21, 12, 22, 13, 23];
+        let a = FortranArray::new(&column_major, [2, 3]).unwrap();
+        assert_eq!(a[[1, 1]], 11);
+        assert_eq!(a[[2, 1]], 21);
+        assert_eq!(a[[1, 3]], 13);
+        assert_eq!(a.get([3, 1]), None);
+        assert_eq!(a.get([0, 1]), None);
+        assert_eq!(column_major_offset([2, 3, 4], [2, 3, 4]), Some(23));
+
+        let rows = [[11, 12, 13], [21, 22, 23]];
+        let flat = rows.as_flattened();
+        let view = FortranArray::from_row_major(flat, [2, 3]).unwrap();
+        assert_eq!(view.dims(), [3, 2]);
+        for (i, row) in rows.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert_eq!(view[[j + 1, i + 1]], *value);
+            }
+        }
+        assert!(std::ptr::eq(view.as_slice(), flat));
+
+        let xstate = [0.0; 12];
+        let assumed = FortranArray::assumed_size(&xstate, [3, 0]).unwrap();
+        assert_eq!(assumed.dims(), [3, 4]);
+        assert!(FortranArray::assumed_size(&xstate, [5, 0]).is_err());
+        assert_eq!(
+            FortranArray::new(&column_major, [4, 2]).unwrap_err(),
+            "array of 6 values does not match dimensions [4, 2] (8 values)"
+        );
+    }
+
+    #[test]
+    fn copies_between_layouts_and_passes_arrays_to_routines() {
+        let rows = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let columns = to_column_major(&rows, [2, 3]).unwrap();
+        assert_eq!(columns, [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert_eq!(to_row_major(&columns, [2, 3]).unwrap(), rows);
+        let cube: Vec<i32> = (0..24).collect();
+        let back = to_row_major(&to_column_major(&cube, [2, 3, 4]).unwrap(), [2, 3, 4]).unwrap();
+        assert_eq!(back, cube);
+
+        // Fortran: a(i,j) = a(i,j) + i*10 + j for a(2,3)
+        unsafe extern "C" fn mark_(a: *mut f64) {
+            let a = unsafe { std::slice::from_raw_parts_mut(a, 6) };
+            for j in 1..=3 {
+                for i in 1..=2 {
+                    a[(i - 1) + (j - 1) * 2] += (i * 10 + j) as f64;
+                }
+            }
+        }
+        let address = unsafe { ForeignFn::from_ptr(mark_ as *const _) }.unwrap();
+        let mark = unsafe {
+            ForeignRoutine::new(
+                "mark_",
+                CallingConvention::Fortran,
+                vec![ArgKind::RealArray],
+                address,
+            )
+        };
+        let mut values = to_column_major(&[0.0; 6], [2, 3]).unwrap();
+        let mut a = FortranArrayMut::new(&mut values, [2, 3]).unwrap();
+        // a holds the 2x3 values mark_ updates
+        unsafe { mark.call(&mut [a.as_arg()]) }.unwrap();
+        assert_eq!(a[[2, 3]], 23.0);
+        a[[1, 2]] = -1.0;
+        assert_eq!(
+            to_row_major(&values, [2, 3]).unwrap(),
+            [11.0, -1.0, 13.0, 21.0, 22.0, 23.0]
+        );
+    }
+}
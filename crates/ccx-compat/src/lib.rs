@@ -3,11 +3,28 @@
 //! This crate provides:
 //! - symbol normalization helpers for legacy C/Fortran routines
 //! - a runtime registry to route calls through temporary compatibility shims
+//! - dispatch of unported legacy routines loaded from a compiled library
+//! - routine descriptions read from the argument lists of Fortran sources
+//! - counts of the routed calls per job, to rank routines for porting
+//! - column-major, 1-based views of arrays passed to legacy routines
 
 mod bridge;
+mod ffi;
+mod interfaces;
+mod layout;
 mod symbols;
+mod usage;
 
 pub use bridge::{
-    CallingConvention, CompatError, CompatRegistry, RoutineHandle, RoutineSpec, ScalarRoutine,
+    CallingConvention, CompatError, CompatRegistry, DEFAULT_JOB, RoutineHandle, RoutineSpec,
+    ScalarRoutine,
+};
+pub use ffi::{ArgKind, ForeignFn, ForeignRoutine, LegacyArg, LegacyLibrary, MAX_ARGS};
+pub use interfaces::{
+    FortranArgument, FortranInterface, parse_fortran_interfaces, scan_fortran_dir,
+};
+pub use layout::{
+    FortranArray, FortranArrayMut, column_major_offset, to_column_major, to_row_major,
 };
 pub use symbols::{LegacyLanguage, canonical_symbol, fortran_symbol, rust_module_from_legacy_path};
+pub use usage::{Hotspot, UsageLog};
@@ -0,0 +1,216 @@
+//! Counts of the calls routed through compatibility shims.
+//!
+//! A [`CompatRegistry`](crate::CompatRegistry) counts every call it routes
+//! to a shim or legacy routine, per job and routine. The counts are saved
+//! as tab-separated `job, symbol, count` lines and merged with the counts
+//! already in the file, so a log collects the calls of many runs; the
+//! [`UsageLog::hotspots`] of such a log rank the routines by how often real
+//! jobs need them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+const HEADER: &str = "# ccx-compat usage v1: job, symbol, calls";
+
+/// Calls per job and routine symbol
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageLog {
+    calls: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+/// Calls of one routine over all jobs of a log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotspot {
+    pub symbol: String,
+    pub calls: u64,
+    /// Number of jobs that called the routine
+    pub jobs: usize,
+}
+
+impl UsageLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    pub fn record(&mut self, job: &str, symbol: &str) {
+        self.add(job, symbol, 1);
+    }
+
+    fn add(&mut self, job: &str, symbol: &str, calls: u64) {
+        let count = self
+            .calls
+            .entry(job.to_string())
+            .or_default()
+            .entry(symbol.to_string())
+            .or_insert(0);
+        *count += calls;
+    }
+
+    pub fn calls(&self, job: &str, symbol: &str) -> u64 {
+        self.calls
+            .get(job)
+            .and_then(|symbols| symbols.get(symbol))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Names of the jobs, sorted
+    pub fn jobs(&self) -> impl Iterator<Item = &str> {
+        self.calls.keys().map(String::as_str)
+    }
+
+    /// Add the counts of `other`
+    pub fn merge(&mut self, other: &UsageLog) {
+        for (job, symbols) in &other.calls {
+            for (symbol, calls) in symbols {
+                self.add(job, symbol, *calls);
+            }
+        }
+    }
+
+    /// Routines by calls, most called first; ties by number of jobs, then
+    /// by symbol
+    pub fn hotspots(&self) -> Vec<Hotspot> {
+        let mut totals: BTreeMap<&str, (u64, BTreeSet<&str>)> = BTreeMap::new();
+        for (job, symbols) in &self.calls {
+            for (symbol, calls) in symbols {
+                let total = totals.entry(symbol).or_default();
+                total.0 += calls;
+                total.1.insert(job);
+            }
+        }
+        let mut hotspots: Vec<Hotspot> = totals
+            .into_iter()
+            .map(|(symbol, (calls, jobs))| Hotspot {
+                symbol: symbol.to_string(),
+                calls,
+                jobs: jobs.len(),
+            })
+            .collect();
+        hotspots.sort_by(|a, b| {
+            b.calls
+                .cmp(&a.calls)
+                .then(b.jobs.cmp(&a.jobs))
+                .then(a.symbol.cmp(&b.symbol))
+        });
+        hotspots
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\n");
+        for (job, symbols) in &self.calls {
+            for (symbol, calls) in symbols {
+                text.push_str(&format!("{job}\t{symbol}\t{calls}\n"));
+            }
+        }
+        text
+    }
+
+    /// Counts of the text of a usage log; lines starting with `#` are
+    /// comments
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut log = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let (job, symbol, calls) = match fields[..] {
+                [job, symbol, calls] => (job, symbol, calls),
+                _ => {
+                    return Err(format!(
+                        "line {}: expected job, symbol and calls",
+                        index + 1
+                    ));
+                }
+            };
+            let calls = calls
+                .trim()
+                .parse()
+                .map_err(|_| format!("line {}: invalid call count {calls}", index + 1))?;
+            log.add(job, symbol, calls);
+        }
+        Ok(log)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Add the counts to those of the log at `path`, creating it
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut merged = match path.exists() {
+            true => Self::load(path)?,
+            false => Self::new(),
+        };
+        merged.merge(self);
+        fs::write(path, merged.to_text())
+            .map_err(|e| format!("cannot write {}: {e}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_routines_by_calls_and_jobs() {
+        let mut log = UsageLog::new();
+        for _ in 0..3 {
+            log.record("beam", "e_c3d_");
+        }
+        log.record("beam", "umat_user_");
+        log.record("plate", "umat_user_");
+        log.record("plate", "e_c3d_");
+        log.record("plate", "shape8h_");
+        assert_eq!(log.calls("beam", "e_c3d_"), 3);
+        assert_eq!(log.calls("beam", "shape8h_"), 0);
+        assert_eq!(log.jobs().collect::<Vec<_>>(), ["beam", "plate"]);
+
+        let hotspots = log.hotspots();
+        let ranked: Vec<(&str, u64, usize)> = hotspots
+            .iter()
+            .map(|h| (h.symbol.as_str(), h.calls, h.jobs))
+            .collect();
+        assert_eq!(
+            ranked,
+            [("e_c3d_", 4, 2), ("umat_user_", 2, 2), ("shape8h_", 1, 1)]
+        );
+    }
+
+    #[test]
+    fn saves_logs_merged_with_earlier_runs() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock should be valid")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "ccx_compat_usage_{}_{nanos}.tsv",
+            std::process::id()
+        ));
+        let mut first = UsageLog::new();
+        first.record("beam", "e_c3d_");
+        first.save(&path).unwrap();
+        first.save(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text, format!("{HEADER}\nbeam\te_c3d_\t2\n"));
+        assert_eq!(UsageLog::load(&path).unwrap().calls("beam", "e_c3d_"), 2);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            UsageLog::parse("beam\te_c3d_").unwrap_err(),
+            "line 1: expected job, symbol and calls"
+        );
+        assert_eq!(
+            UsageLog::parse("# comment\nbeam\te_c3d_\tmany").unwrap_err(),
+            "line 2: invalid call count many"
+        );
+    }
+}
@@ -5,9 +5,20 @@ use std::fmt::{Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use span::{field_spans, line_offset};
+
+mod params;
+mod span;
+mod writer;
+
+pub use params::{ParameterTable, parse_override};
+pub use span::{CardSpans, Span};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Deck {
     pub cards: Vec<Card>,
+    /// Comment and blank lines after the last card, kept for round-tripping.
+    pub trailing_trivia: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -16,6 +27,53 @@ pub struct Card {
     pub parameters: Vec<Parameter>,
     pub data_lines: Vec<String>,
     pub line_start: usize,
+    /// Original layout of the card, used by the deck writer.
+    pub format: CardFormat,
+    /// Source positions of the keyword, parameters and data fields.
+    pub spans: CardSpans,
+}
+
+impl Card {
+    /// Source line of the data line at `index`, falling back to the header line.
+    pub fn data_line_number(&self, index: usize) -> usize {
+        self.spans
+            .data_fields
+            .get(index)
+            .and_then(|fields| fields.first())
+            .map(|span| span.line)
+            .unwrap_or(self.line_start)
+    }
+
+    /// Span of comma-separated field `field` on data line `line`.
+    pub fn data_field_span(&self, line: usize, field: usize) -> Option<Span> {
+        self.spans.data_fields.get(line)?.get(field).copied()
+    }
+
+    /// Span of the first parameter named `key` (case-insensitive).
+    pub fn parameter_span(&self, key: &str) -> Option<Span> {
+        let index = self
+            .parameters
+            .iter()
+            .position(|p| p.key.eq_ignore_ascii_case(key))?;
+        self.spans.parameters.get(index).copied()
+    }
+}
+
+/// Source layout of a card as it appeared in the deck.
+///
+/// The parser normalizes keywords and trims data lines; this keeps the
+/// verbatim text so unchanged cards can be written back byte-for-byte.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardFormat {
+    /// Comment, blank and separator lines immediately preceding the header.
+    pub leading: Vec<String>,
+    /// Header lines as written, including `,`-continuation lines.
+    pub header_lines: Vec<String>,
+    /// Data lines as written, parallel to `Card::data_lines`.
+    pub raw_data_lines: Vec<String>,
+    /// Comment/blank lines inside the data block, keyed by the index of the
+    /// data line they precede.
+    pub interleaved: Vec<(usize, String)>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,11 +86,30 @@ pub struct Parameter {
 pub struct ParseError {
     pub line: usize,
     pub message: String,
+    /// Exact token location, when the error can be pinned to one.
+    pub span: Option<Span>,
+    /// Kind of the I/O failure, when the deck or one of its includes could
+    /// not be read.
+    pub io_error: Option<std::io::ErrorKind>,
+}
+
+impl ParseError {
+    pub fn at(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            line: span.line,
+            message: message.into(),
+            span: Some(span),
+            io_error: None,
+        }
+    }
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "line {}: {}", self.line, self.message)
+        match self.span {
+            Some(span) => write!(f, "line {}:{}: {}", span.line, span.column, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
     }
 }
 
@@ -41,38 +118,38 @@ impl std::error::Error for ParseError {}
 impl Deck {
     pub fn parse_file(path: impl AsRef<Path>) -> Result<Self, ParseError> {
         let path = path.as_ref();
-        let raw = fs::read_to_string(path).map_err(|e| ParseError {
-            line: 0,
-            message: format!("failed to read {}: {e}", path.display()),
-        })?;
+        let raw = read_deck_text(path)?;
         Self::parse_str(&raw)
     }
 
     pub fn parse_str(raw: &str) -> Result<Self, ParseError> {
         let lines: Vec<&str> = raw.lines().collect();
         let mut cards = Vec::new();
+        let mut trivia = Vec::<String>::new();
         let mut i = 0usize;
 
         while i < lines.len() {
             let trimmed = lines[i].trim();
 
             if trimmed.is_empty() || is_comment(trimmed) {
+                trivia.push(lines[i].to_string());
                 i += 1;
                 continue;
             }
 
             if !trimmed.starts_with('*') {
-                return Err(ParseError {
-                    line: i + 1,
-                    message: "expected card starting with '*'".to_string(),
-                });
+                let fields = field_spans(lines[i], i + 1, line_offset(raw, lines[i]));
+                return Err(ParseError::at(fields[0], "expected card starting with '*'"));
             }
 
             let line_start = i + 1;
             let mut header = trimmed.trim_start_matches('*').trim().to_string();
+            let mut header_lines = vec![lines[i].to_string()];
+            let mut header_spans = header_field_spans(raw, lines[i], line_start);
             i += 1;
             if header.is_empty() {
                 // Legacy decks sometimes use a bare "*" as a visual separator.
+                trivia.push(lines[i - 1].to_string());
                 continue;
             }
 
@@ -81,6 +158,8 @@ impl Deck {
                 let next = lines[i].trim();
                 if next.starts_with(',') {
                     header.push_str(next);
+                    header_lines.push(lines[i].to_string());
+                    header_spans.extend(header_field_spans(raw, lines[i], i + 1));
                     i += 1;
                     continue;
                 }
@@ -88,40 +167,162 @@ impl Deck {
             }
 
             let (keyword, parameters) = parse_header(&header, line_start)?;
+            let mut header_spans = header_spans.into_iter().filter(|span| span.len > 0);
+            let mut spans = CardSpans {
+                keyword: header_spans.next().unwrap_or_default(),
+                parameters: header_spans.collect(),
+                data_fields: Vec::new(),
+            };
 
             let mut data_lines = Vec::new();
+            let mut format = CardFormat {
+                leading: std::mem::take(&mut trivia),
+                header_lines,
+                ..CardFormat::default()
+            };
+            let mut pending = Vec::<String>::new();
             while i < lines.len() {
                 let candidate = lines[i].trim();
                 if candidate.is_empty() || is_comment(candidate) {
+                    pending.push(lines[i].to_string());
                     i += 1;
                     continue;
                 }
                 if candidate.starts_with('*') {
                     break;
                 }
+                let index = data_lines.len();
+                format
+                    .interleaved
+                    .extend(pending.drain(..).map(|line| (index, line)));
                 data_lines.push(candidate.to_string());
+                format.raw_data_lines.push(lines[i].to_string());
+                spans
+                    .data_fields
+                    .push(field_spans(lines[i], i + 1, line_offset(raw, lines[i])));
                 i += 1;
             }
+            // Comments after the last data line introduce the next card.
+            trivia = pending;
 
             cards.push(Card {
                 keyword,
                 parameters,
                 data_lines,
                 line_start,
+                format,
+                spans,
             });
         }
 
-        Ok(Deck { cards })
+        Ok(Deck {
+            cards,
+            trailing_trivia: trivia,
+        })
     }
 
     pub fn parse_file_with_includes(path: impl AsRef<Path>) -> Result<Self, ParseError> {
+        Self::parse_file_with_include_paths(path, &[])
+    }
+
+    /// Parse a deck file, expanding includes; an include that does not exist
+    /// relative to the including file is looked up in `include_paths` in order
+    pub fn parse_file_with_include_paths(
+        path: impl AsRef<Path>,
+        include_paths: &[PathBuf],
+    ) -> Result<Self, ParseError> {
         let mut include_stack = Vec::<PathBuf>::new();
         let mut active = HashSet::<PathBuf>::new();
-        Self::parse_file_with_includes_inner(path.as_ref(), &mut include_stack, &mut active)
+        Self::parse_file_with_includes_inner(
+            path.as_ref(),
+            include_paths,
+            &mut include_stack,
+            &mut active,
+        )
+    }
+
+    /// Files `*INCLUDE`d by the deck at `path`, directly or through other
+    /// includes, in the order they are first included
+    ///
+    /// Includes are resolved as by [`Deck::parse_file_with_include_paths`].
+    /// Files that cannot be read or parsed are listed but not followed.
+    pub fn include_files(path: impl AsRef<Path>, include_paths: &[PathBuf]) -> Vec<PathBuf> {
+        fn collect(
+            path: &Path,
+            include_paths: &[PathBuf],
+            seen: &mut HashSet<PathBuf>,
+            out: &mut Vec<PathBuf>,
+        ) {
+            let Ok(deck) = read_deck_text(path).and_then(|raw| Deck::parse_str(&raw)) else {
+                return;
+            };
+            let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+            for card in &deck.cards {
+                if normalized_keyword(&card.keyword) != "INCLUDE" {
+                    continue;
+                }
+                let Some(raw_include) = include_input_path(card) else {
+                    continue;
+                };
+                let include_path = find_include(base_dir, &raw_include, include_paths);
+                if seen.insert(normalize_path(&include_path)) {
+                    out.push(include_path.clone());
+                    collect(&include_path, include_paths, seen, out);
+                }
+            }
+        }
+
+        let path = path.as_ref();
+        let mut seen = HashSet::from([normalize_path(path)]);
+        let mut out = Vec::new();
+        collect(path, include_paths, &mut seen, &mut out);
+        out
+    }
+
+    /// Make a deck parsed with its includes expanded and its parameters
+    /// applied self-contained
+    ///
+    /// Every `*INCLUDE` card, whose cards follow it in the deck, is replaced
+    /// by a comment naming the included file. The `*PARAMETER` cards are
+    /// replaced by comments listing the values of `parameters`, overrides
+    /// included, that were substituted into the deck.
+    pub fn flatten(&mut self, parameters: &ParameterTable) {
+        let mut pending = Vec::new();
+        let mut cards = Vec::with_capacity(self.cards.len());
+        for mut card in std::mem::take(&mut self.cards) {
+            match normalized_keyword(&card.keyword).as_str() {
+                "INCLUDE" => {
+                    pending.append(&mut card.format.leading);
+                    let input = include_input_path(&card).unwrap_or_default();
+                    pending.push(format!("** *INCLUDE, INPUT={input} (expanded)"));
+                }
+                "PARAMETER" => {
+                    pending.append(&mut card.format.leading);
+                    pending.push("** *PARAMETER (resolved)".to_string());
+                    for line in &card.data_lines {
+                        let name = line
+                            .split_once('=')
+                            .map_or(line.as_str(), |(n, _)| n)
+                            .trim();
+                        match parameters.get(name) {
+                            Some(value) => pending.push(format!("** {name}={value}")),
+                            None => pending.push(format!("** {line}")),
+                        }
+                    }
+                }
+                _ => {
+                    card.format.leading.splice(0..0, pending.drain(..));
+                    cards.push(card);
+                }
+            }
+        }
+        self.trailing_trivia.splice(0..0, pending);
+        self.cards = cards;
     }
 
     fn parse_file_with_includes_inner(
         path: &Path,
+        include_paths: &[PathBuf],
         include_stack: &mut Vec<PathBuf>,
         active: &mut HashSet<PathBuf>,
     ) -> Result<Self, ParseError> {
@@ -135,6 +336,8 @@ impl Deck {
             return Err(ParseError {
                 line: 0,
                 message: format!("include cycle detected: {}", chain.join(" -> ")),
+                span: None,
+                io_error: None,
             });
         }
 
@@ -142,10 +345,7 @@ impl Deck {
         active.insert(normalized_path);
 
         let result = (|| -> Result<Self, ParseError> {
-            let raw = fs::read_to_string(path).map_err(|e| ParseError {
-                line: 0,
-                message: format!("failed to read {}: {e}", path.display()),
-            })?;
+            let raw = read_deck_text(path)?;
             let parsed = Self::parse_str(&raw)?;
             let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
             let mut expanded_cards = Vec::<Card>::new();
@@ -155,6 +355,8 @@ impl Deck {
                     Some(include_input_path(&card).ok_or(ParseError {
                         line: card.line_start,
                         message: "missing INPUT parameter in *INCLUDE card".to_string(),
+                        span: Some(card.spans.keyword),
+                        io_error: None,
                     })?)
                 } else {
                     None
@@ -162,23 +364,30 @@ impl Deck {
 
                 expanded_cards.push(card);
                 if let Some(raw_include) = include_target {
-                    let include_path = resolve_include_path(base_dir, &raw_include);
-                    let included =
-                        Self::parse_file_with_includes_inner(&include_path, include_stack, active)
-                            .map_err(|err| ParseError {
-                                line: err.line,
-                                message: format!(
-                                    "{} (while expanding include {})",
-                                    err.message,
-                                    include_path.display()
-                                ),
-                            })?;
+                    let include_path = find_include(base_dir, &raw_include, include_paths);
+                    let included = Self::parse_file_with_includes_inner(
+                        &include_path,
+                        include_paths,
+                        include_stack,
+                        active,
+                    )
+                    .map_err(|err| ParseError {
+                        line: err.line,
+                        message: format!(
+                            "{} (while expanding include {})",
+                            err.message,
+                            include_path.display()
+                        ),
+                        span: err.span,
+                        io_error: err.io_error,
+                    })?;
                     expanded_cards.extend(included.cards);
                 }
             }
 
             Ok(Self {
                 cards: expanded_cards,
+                trailing_trivia: parsed.trailing_trivia,
             })
         })();
 
@@ -191,11 +400,45 @@ impl Deck {
     }
 }
 
+/// Decode raw deck bytes, tolerating legacy encodings.
+///
+/// A leading UTF-8 byte order mark is dropped. Input that is not valid UTF-8
+/// is decoded as Latin-1, which maps every byte to a character, so decks with
+/// `°` or umlauts in comments still parse.
+pub fn decode_deck_bytes(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+fn read_deck_text(path: &Path) -> Result<String, ParseError> {
+    let bytes = fs::read(path).map_err(|e| ParseError {
+        line: 0,
+        message: format!("failed to read {}: {e}", path.display()),
+        span: None,
+        io_error: Some(e.kind()),
+    })?;
+    Ok(decode_deck_bytes(&bytes))
+}
+
 fn is_comment(line: &str) -> bool {
     // Some legacy fixtures prefix comment lines with `>`, e.g. `>** ...`.
     line.trim_start_matches('>').trim_start().starts_with("**")
 }
 
+/// Field spans of a header line, excluding the leading `*`.
+fn header_field_spans(raw: &str, line: &str, line_no: usize) -> Vec<Span> {
+    let base = line_offset(raw, line);
+    let star = line.find('*').map(|idx| idx + 1).unwrap_or(0);
+    let mut spans = field_spans(&line[star..], line_no, base + star);
+    for span in &mut spans {
+        span.column += star;
+    }
+    spans
+}
+
 fn parse_header(header: &str, line: usize) -> Result<(String, Vec<Parameter>), ParseError> {
     let fields = split_header_fields(header);
     let keyword_raw = fields.first().map(|s| s.as_str()).unwrap_or("").trim();
@@ -203,6 +446,8 @@ fn parse_header(header: &str, line: usize) -> Result<(String, Vec<Parameter>), P
         return Err(ParseError {
             line,
             message: "empty card keyword".to_string(),
+            span: None,
+            io_error: None,
         });
     }
     let keyword = keyword_raw.to_ascii_uppercase();
@@ -282,6 +527,20 @@ fn resolve_include_path(base_dir: &Path, include: &str) -> PathBuf {
     normalize_path(&joined)
 }
 
+/// Resolve an include next to the including file or, when it does not exist
+/// there, in the first of `include_paths` that has it
+fn find_include(base_dir: &Path, include: &str, include_paths: &[PathBuf]) -> PathBuf {
+    let local = resolve_include_path(base_dir, include);
+    if local.exists() {
+        return local;
+    }
+    include_paths
+        .iter()
+        .map(|dir| resolve_include_path(dir, include))
+        .find(|candidate| candidate.exists())
+        .unwrap_or(local)
+}
+
 fn normalize_path(path: &Path) -> PathBuf {
     fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
 }
@@ -333,11 +592,30 @@ My model
         );
     }
 
+    #[test]
+    fn records_spans_for_keyword_parameters_and_fields() {
+        let src = "** c\n*STEP, INC=100\n , NLGEOM\n*NODE\n  7, 1.5,  x\n";
+        let deck = Deck::parse_str(src).expect("parser should succeed");
+
+        let step = &deck.cards[0];
+        assert_eq!((step.spans.keyword.line, step.spans.keyword.column), (2, 2));
+        assert_eq!(step.spans.keyword.len, 4);
+        let nlgeom = step.parameter_span("nlgeom").expect("NLGEOM span");
+        assert_eq!((nlgeom.line, nlgeom.column, nlgeom.len), (3, 4, 6));
+
+        let node = &deck.cards[1];
+        assert_eq!(node.data_line_number(0), 5);
+        let bad = node.data_field_span(0, 2).expect("third field");
+        assert_eq!((bad.column, bad.len), (12, 1));
+        assert_eq!(&src[bad.offset..bad.offset + bad.len], "x");
+    }
+
     #[test]
     fn fails_on_orphan_data_before_first_card() {
         let src = "1,2,3\n*NODE\n1,0,0,0\n";
         let err = Deck::parse_str(src).expect_err("should fail");
         assert_eq!(err.line, 1);
+        assert_eq!(err.to_string(), "line 1:1: expected card starting with '*'");
     }
 
     #[test]
@@ -419,6 +697,101 @@ My model
         );
     }
 
+    #[test]
+    fn include_files_lists_nested_includes_once() {
+        let tmp = unique_temp_dir("ccx_inp_include_files");
+        fs::create_dir_all(&tmp).expect("create temp directory");
+        let root = tmp.join("root.inp");
+        fs::write(
+            &root,
+            "*INCLUDE,INPUT=mid.inc\n*INCLUDE,INPUT=missing.inc\n*INCLUDE,INPUT=leaf.inc\n",
+        )
+        .expect("write root");
+        fs::write(tmp.join("mid.inc"), "*INCLUDE,INPUT=leaf.inc\n").expect("write mid");
+        fs::write(tmp.join("leaf.inc"), "*INCLUDE,INPUT=root.inp\n").expect("write leaf");
+
+        let names: Vec<String> = Deck::include_files(&root, &[])
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["mid.inc", "leaf.inc", "missing.inc"]);
+    }
+
+    #[test]
+    fn flatten_replaces_includes_and_parameters_with_comments() {
+        let tmp = unique_temp_dir("ccx_inp_flatten");
+        fs::create_dir_all(&tmp).expect("create temp directory");
+        let root = tmp.join("root.inp");
+        fs::write(
+            &root,
+            "*PARAMETER\nx=1.5\ny=2\n** mesh\n*INCLUDE,INPUT=mesh.inc\n\
+             *BOUNDARY\n1,1,3\n*INCLUDE,INPUT=tail.inc\n",
+        )
+        .expect("write root");
+        fs::write(tmp.join("mesh.inc"), "*NODE\n1,<x>,<y>,0\n").expect("write mesh");
+        fs::write(tmp.join("tail.inc"), "** empty\n").expect("write tail");
+
+        let mut deck = Deck::parse_file_with_includes(&root).expect("parse with includes");
+        let mut table = ParameterTable::with_overrides([("y", "3")]);
+        deck.apply_parameters(&mut table).expect("apply parameters");
+        deck.flatten(&table);
+        let text = deck.to_inp_string();
+        assert_eq!(
+            text,
+            "** *PARAMETER (resolved)\n** x=1.5\n** y=3\n** mesh\n\
+             ** *INCLUDE, INPUT=mesh.inc (expanded)\n*NODE\n1,1.5,3,0\n\
+             *BOUNDARY\n1,1,3\n** *INCLUDE, INPUT=tail.inc (expanded)\n"
+        );
+        let reparsed = Deck::parse_str(&text).expect("reparse");
+        assert_eq!(reparsed.cards.len(), 2);
+    }
+
+    #[test]
+    fn parse_file_with_include_paths_searches_after_local_directory() {
+        let tmp = unique_temp_dir("ccx_inp_include_paths");
+        let lib = tmp.join("lib");
+        let other = tmp.join("other");
+        fs::create_dir_all(&lib).expect("create lib directory");
+        fs::create_dir_all(&other).expect("create other directory");
+        let root = tmp.join("root.inp");
+        fs::write(&root, "*INCLUDE,INPUT=mat.inc\n*INCLUDE,INPUT=mesh.inc\n").expect("write root");
+        fs::write(tmp.join("mesh.inc"), "*NODE\n1,0,0,0\n").expect("write local mesh");
+        fs::write(other.join("mesh.inc"), "*NODE\n2,0,0,0\n").expect("write other mesh");
+        fs::write(lib.join("mat.inc"), "*MATERIAL,NAME=STEEL\n").expect("write material");
+
+        assert!(Deck::parse_file_with_includes(&root).is_err());
+        let deck = Deck::parse_file_with_include_paths(&root, &[other, lib]).expect("parse");
+        let keywords: Vec<&str> = deck.cards.iter().map(|c| c.keyword.as_str()).collect();
+        assert_eq!(keywords, ["INCLUDE", "MATERIAL", "INCLUDE", "NODE"]);
+        // The deck's own directory comes first
+        assert_eq!(deck.cards[3].data_lines, ["1,0,0,0"]);
+    }
+
+    #[test]
+    fn parse_file_accepts_bom_and_latin1_comments() {
+        let tmp = unique_temp_dir("ccx_inp_latin1");
+        fs::create_dir_all(&tmp).expect("create temp directory");
+        let bom = tmp.join("bom.inp");
+        let latin1 = tmp.join("latin1.inp");
+
+        fs::write(&bom, b"\xEF\xBB\xBF*NODE\n1,0,0,0\n").expect("write bom deck");
+        fs::write(
+            &latin1,
+            b"** Temperatur 20 \xB0C, Gr\xF6\xDFe\n*NODE\n1,0,0,0\n",
+        )
+        .expect("write latin1 deck");
+
+        let deck = Deck::parse_file(&bom).expect("BOM deck should parse");
+        assert_eq!(deck.cards[0].keyword, "NODE");
+
+        let deck = Deck::parse_file_with_includes(&latin1).expect("Latin-1 deck should parse");
+        assert_eq!(deck.cards[0].keyword, "NODE");
+        assert_eq!(
+            deck.cards[0].format.leading,
+            vec!["** Temperatur 20 °C, Größe".to_string()]
+        );
+    }
+
     #[test]
     fn parse_file_with_includes_detects_cycles() {
         let tmp = unique_temp_dir("ccx_inp_include_cycle");
@@ -474,6 +847,7 @@ My model
             err.message
         );
         assert_eq!(err.line, 1);
+        assert_eq!(err.io_error, None);
     }
 
     #[test]
@@ -491,6 +865,7 @@ My model
             err.message
         );
         assert_eq!(err.line, 0);
+        assert_eq!(err.io_error, Some(std::io::ErrorKind::NotFound));
     }
 
     #[test]
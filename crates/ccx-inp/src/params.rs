@@ -0,0 +1,272 @@
+//! `*PARAMETER` definitions and `<name>` substitution for parametric decks.
+
+use std::collections::BTreeMap;
+
+use crate::span::split_fields;
+use crate::{Card, Deck, ParseError, Span};
+
+/// Parameter values keyed by uppercase name.
+///
+/// Values defined with `*PARAMETER` cards are collected in deck order;
+/// overrides (e.g. from the command line) always take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParameterTable {
+    values: BTreeMap<String, String>,
+    overrides: BTreeMap<String, String>,
+}
+
+impl ParameterTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a table from `(name, value)` override pairs.
+    pub fn with_overrides<I, K, V>(overrides: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut table = Self::new();
+        for (key, value) in overrides {
+            table.set_override(key.as_ref(), value.as_ref());
+        }
+        table
+    }
+
+    /// Set a value that deck-level `*PARAMETER` definitions cannot replace.
+    pub fn set_override(&mut self, name: &str, value: &str) {
+        self.overrides
+            .insert(name.trim().to_ascii_uppercase(), value.trim().to_string());
+    }
+
+    /// Define a deck-level value (ignored if an override exists).
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.values
+            .insert(name.trim().to_ascii_uppercase(), value.trim().to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let key = name.trim().to_ascii_uppercase();
+        self.overrides
+            .get(&key)
+            .or_else(|| self.values.get(&key))
+            .map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.overrides.is_empty()
+    }
+
+    /// Replace every `<name>` token in `text`; unknown names are an error.
+    pub fn substitute(&self, text: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(open) = rest.find('<') {
+            out.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let Some(close) = after.find('>') else {
+                out.push_str(&rest[open..]);
+                return Ok(out);
+            };
+            let name = &after[..close];
+            if !is_parameter_name(name) {
+                out.push('<');
+                rest = after;
+                continue;
+            }
+            match self.get(name) {
+                Some(value) => out.push_str(value),
+                None => return Err(format!("undefined parameter <{name}>")),
+            }
+            rest = &after[close + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+/// Parse a `name=value` override as given on the command line.
+pub fn parse_override(raw: &str) -> Result<(String, String), String> {
+    let (name, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("invalid parameter override '{raw}' (expected name=value)"))?;
+    let name = name.trim();
+    if !is_parameter_name(name) {
+        return Err(format!("invalid parameter name '{name}'"));
+    }
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+fn is_parameter_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Deck {
+    /// Apply `*PARAMETER` definitions and `<name>` substitution in place.
+    ///
+    /// Definitions are processed in card order, so a parameter is only
+    /// visible to cards after the `*PARAMETER` card that defines it.
+    /// The `*PARAMETER` cards themselves are kept in the deck.
+    pub fn apply_parameters(&mut self, table: &mut ParameterTable) -> Result<(), ParseError> {
+        for card in &mut self.cards {
+            if card.keyword == "PARAMETER" {
+                collect_definitions(card, table)?;
+                continue;
+            }
+            substitute_card(card, table)?;
+        }
+        Ok(())
+    }
+
+    /// Parse a deck string and resolve its parameters using `overrides`.
+    pub fn parse_str_with_parameters(
+        raw: &str,
+        overrides: &[(String, String)],
+    ) -> Result<Self, ParseError> {
+        let mut deck = Self::parse_str(raw)?;
+        deck.apply_parameters(&mut ParameterTable::with_overrides(
+            overrides.iter().cloned(),
+        ))?;
+        Ok(deck)
+    }
+
+    /// Parse a deck file, expanding includes, and resolve its parameters.
+    pub fn parse_file_with_parameters(
+        path: impl AsRef<std::path::Path>,
+        overrides: &[(String, String)],
+    ) -> Result<Self, ParseError> {
+        let mut deck = Self::parse_file_with_includes(path)?;
+        deck.apply_parameters(&mut ParameterTable::with_overrides(
+            overrides.iter().cloned(),
+        ))?;
+        Ok(deck)
+    }
+}
+
+fn collect_definitions(card: &Card, table: &mut ParameterTable) -> Result<(), ParseError> {
+    for (index, line) in card.data_lines.iter().enumerate() {
+        let error = |message: String| match card.data_field_span(index, 0) {
+            Some(span) => ParseError::at(span, message),
+            None => ParseError {
+                line: card.data_line_number(index),
+                message,
+                span: None,
+                io_error: None,
+            },
+        };
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("expected name=value in *PARAMETER, got '{line}'")))?;
+        let name = name.trim();
+        if !is_parameter_name(name) {
+            return Err(error(format!("invalid parameter name '{name}'")));
+        }
+        let value = table.substitute(value).map_err(error)?;
+        table.define(name, &value);
+    }
+    Ok(())
+}
+
+fn substitute_card(card: &mut Card, table: &ParameterTable) -> Result<(), ParseError> {
+    let error = |span: Option<Span>, line: usize, message: String| match span {
+        Some(span) => ParseError::at(span, message),
+        None => ParseError {
+            line,
+            message,
+            span: None,
+            io_error: None,
+        },
+    };
+    for (index, param) in card.parameters.iter_mut().enumerate() {
+        if let Some(value) = param.value.as_mut() {
+            *value = table.substitute(value).map_err(|message| {
+                error(
+                    card.spans.parameters.get(index).copied(),
+                    card.line_start,
+                    message,
+                )
+            })?;
+        }
+    }
+    for index in 0..card.data_lines.len() {
+        let mut fields = Vec::new();
+        for (field, text) in split_fields(&card.data_lines[index])
+            .into_iter()
+            .enumerate()
+        {
+            let substituted = table.substitute(text).map_err(|message| {
+                error(
+                    card.data_field_span(index, field),
+                    card.data_line_number(index),
+                    message,
+                )
+            })?;
+            fields.push(substituted);
+        }
+        card.data_lines[index] = fields.join(",");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_deck_defined_parameters() {
+        let src =
+            "*PARAMETER\nt = 0.01\nmat = STEEL\n*SHELL SECTION, ELSET=EALL, MATERIAL=<mat>\n<t>\n";
+        let deck = Deck::parse_str_with_parameters(src, &[]).expect("substitution");
+        let section = &deck.cards[1];
+        assert_eq!(section.parameters[1].value.as_deref(), Some("STEEL"));
+        assert_eq!(section.data_lines, vec!["0.01".to_string()]);
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_deck_values() {
+        let src = "*PARAMETER\nthickness=0.01\n*SHELL SECTION, ELSET=E1\n<thickness>\n";
+        let overrides = vec![parse_override("thickness=0.02").expect("valid override")];
+        let deck = Deck::parse_str_with_parameters(src, &overrides).expect("substitution");
+        assert_eq!(deck.cards[1].data_lines, vec!["0.02".to_string()]);
+    }
+
+    #[test]
+    fn undefined_parameter_reports_field_position() {
+        let src = "*NODE\n1, <x>, 0, 0\n";
+        let err = Deck::parse_str_with_parameters(src, &[]).expect_err("should fail");
+        assert_eq!(err.line, 2);
+        assert_eq!(err.span.map(|span| span.column), Some(4));
+        assert!(
+            err.message.contains("<x>"),
+            "unexpected message: {}",
+            err.message
+        );
+    }
+
+    #[test]
+    fn quoted_commas_do_not_split_fields() {
+        let src = "*PARAMETER\nt=2\n*NODE PRINT, NSET=N1\n\"a,b\", <t>, <y>\n";
+        let err = Deck::parse_str_with_parameters(src, &[]).expect_err("should fail");
+        assert_eq!(err.line, 4);
+        assert_eq!(err.span.map(|span| span.column), Some(13));
+
+        let src = "*PARAMETER\nt=2\n*NODE PRINT, NSET=N1\n\"a,<t>\", <t>\n";
+        let deck = Deck::parse_str_with_parameters(src, &[]).expect("substitution");
+        assert_eq!(deck.cards[1].data_lines, vec!["\"a,2\", 2".to_string()]);
+    }
+
+    #[test]
+    fn parameters_can_reference_earlier_parameters() {
+        let mut table = ParameterTable::new();
+        table.define("a", "2.5");
+        assert_eq!(table.substitute("<a>,<A>").expect("known"), "2.5,2.5");
+        assert_eq!(
+            table.substitute("x < 3 > y").expect("not a name"),
+            "x < 3 > y"
+        );
+        assert!(parse_override("no_equals").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Source positions for cards, parameters and data fields.
+
+/// Location of a token in the deck source.
+///
+/// `line` and `column` are 1-based; `column` counts bytes from the start of
+/// the line. `offset` is the byte offset from the start of the parsed text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// Spans of every token in a card, parallel to the parsed card fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardSpans {
+    pub keyword: Span,
+    /// One span per entry in `Card::parameters` covering `KEY[=VALUE]`.
+    pub parameters: Vec<Span>,
+    /// Per data line, one span per comma-separated field outside quotes
+    /// (empty fields included so indices match the split fields).
+    pub data_fields: Vec<Vec<Span>>,
+}
+
+/// Byte offset of `line` within `source`, given that `line` borrows from it.
+pub(crate) fn line_offset(source: &str, line: &str) -> usize {
+    line.as_ptr() as usize - source.as_ptr() as usize
+}
+
+/// Split `text` on commas outside quotes, returning trimmed token spans.
+///
+/// `line` is the 1-based line number and `base` the byte offset of `text`.
+pub(crate) fn field_spans(text: &str, line: usize, base: usize) -> Vec<Span> {
+    field_ranges(text)
+        .into_iter()
+        .map(|(start, end)| trimmed_span(text, start, end, line, base))
+        .collect()
+}
+
+/// Split `text` on commas outside quotes, keeping the fields untrimmed.
+pub(crate) fn split_fields(text: &str) -> Vec<&str> {
+    field_ranges(text)
+        .into_iter()
+        .map(|(start, end)| &text[start..end])
+        .collect()
+}
+
+/// Byte ranges of the comma-separated fields of `text`, ignoring commas
+/// inside single or double quotes.
+fn field_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = 0usize;
+    let mut in_single = false;
+    let mut in_double = false;
+    for (idx, ch) in text.char_indices() {
+        match ch {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            ',' if !in_single && !in_double => {
+                ranges.push((start, idx));
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    ranges.push((start, text.len()));
+    ranges
+}
+
+fn trimmed_span(text: &str, start: usize, end: usize, line: usize, base: usize) -> Span {
+    let raw = &text[start..end];
+    let lead = raw.len() - raw.trim_start().len();
+    let len = raw.trim().len();
+    Span {
+        line,
+        column: start + lead + 1,
+        offset: base + start + lead,
+        len,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_spans_skip_whitespace_and_respect_quotes() {
+        let spans = field_spans("  1, 2.5 ,\"a,b\",", 3, 100);
+        assert_eq!(spans.len(), 4);
+        assert_eq!(
+            spans[0],
+            Span {
+                line: 3,
+                column: 3,
+                offset: 102,
+                len: 1
+            }
+        );
+        assert_eq!((spans[1].column, spans[1].len), (6, 3));
+        assert_eq!((spans[2].column, spans[2].len), (11, 5));
+        assert_eq!(spans[3].len, 0);
+    }
+}
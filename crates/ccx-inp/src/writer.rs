@@ -0,0 +1,150 @@
+//! Deck writer that round-trips parsed decks.
+//!
+//! Cards whose keyword, parameters and data are unchanged since parsing are
+//! written with their original text (comments, spacing, continuation lines).
+//! Edited or programmatically created cards fall back to a canonical layout.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{Card, Deck, Parameter, parse_header};
+
+impl Deck {
+    /// Render the deck as `.inp` text.
+    pub fn to_inp_string(&self) -> String {
+        let mut out = String::new();
+        for card in &self.cards {
+            write_card(card, &mut out);
+        }
+        for line in &self.trailing_trivia {
+            push_line(&mut out, line);
+        }
+        out
+    }
+
+    /// Write the deck to `path`, preserving original formatting where possible.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        fs::write(path, self.to_inp_string())
+    }
+}
+
+impl Card {
+    /// Build a card with canonical formatting and no source position.
+    pub fn new(
+        keyword: impl Into<String>,
+        parameters: Vec<Parameter>,
+        data_lines: Vec<String>,
+    ) -> Self {
+        Self {
+            keyword: keyword.into().to_ascii_uppercase(),
+            parameters,
+            data_lines,
+            line_start: 0,
+            format: Default::default(),
+            spans: Default::default(),
+        }
+    }
+
+    /// Canonical `*KEYWORD, KEY=VALUE, FLAG` header text.
+    pub fn canonical_header(&self) -> String {
+        let mut header = format!("*{}", self.keyword);
+        for param in &self.parameters {
+            header.push_str(", ");
+            header.push_str(&param.key);
+            if let Some(value) = &param.value {
+                header.push('=');
+                header.push_str(value);
+            }
+        }
+        header
+    }
+
+    fn header_unchanged(&self) -> bool {
+        if self.format.header_lines.is_empty() {
+            return false;
+        }
+        let joined = self
+            .format
+            .header_lines
+            .iter()
+            .map(|line| line.trim())
+            .collect::<String>();
+        let header = joined.trim_start_matches('*').trim();
+        match parse_header(header, self.line_start) {
+            Ok((keyword, parameters)) => keyword == self.keyword && parameters == self.parameters,
+            Err(_) => false,
+        }
+    }
+}
+
+fn write_card(card: &Card, out: &mut String) {
+    let format = &card.format;
+    for line in &format.leading {
+        push_line(out, line);
+    }
+
+    if card.header_unchanged() {
+        for line in &format.header_lines {
+            push_line(out, line);
+        }
+    } else {
+        push_line(out, &card.canonical_header());
+    }
+
+    let mut interleaved = format.interleaved.iter().peekable();
+    for (index, data) in card.data_lines.iter().enumerate() {
+        while let Some((_, line)) = interleaved.next_if(|(at, _)| *at == index) {
+            push_line(out, line);
+        }
+        match format.raw_data_lines.get(index) {
+            Some(raw) if raw.trim() == data => push_line(out, raw),
+            _ => push_line(out, data),
+        }
+    }
+    for (_, line) in interleaved {
+        push_line(out, line);
+    }
+}
+
+fn push_line(out: &mut String, line: &str) {
+    out.push_str(line);
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_deck_round_trips_verbatim() {
+        let src = "** header comment\n*HEADING\nMy model\n\n*node,  nset=NALL\n  1, 0.0, 0.0, 0.0\n** mid-block\n  2, 1.0, 0.0, 0.0\n*\n*STEP, INC=100\n, NLGEOM\n*STATIC\n1., 1.\n** end of deck\n";
+        let deck = Deck::parse_str(src).expect("parse");
+        assert_eq!(deck.to_inp_string(), src);
+        assert_eq!(deck.cards[1].format.leading, vec!["".to_string()]);
+        assert_eq!(deck.trailing_trivia, vec!["** end of deck".to_string()]);
+    }
+
+    #[test]
+    fn edited_cards_fall_back_to_canonical_layout() {
+        let src = "** nodes\n*NODE, NSET=NALL\n  1, 0.0, 0.0, 0.0\n  2, 1.0, 0.0, 0.0\n";
+        let mut deck = Deck::parse_str(src).expect("parse");
+        deck.cards[0].parameters[0].value = Some("NEW".to_string());
+        deck.cards[0].data_lines[1] = "2, 2.0, 0.0, 0.0".to_string();
+        deck.cards.push(Card::new(
+            "elset",
+            vec![Parameter {
+                key: "ELSET".to_string(),
+                value: Some("E1".to_string()),
+            }],
+            vec!["1".to_string()],
+        ));
+
+        let written = deck.to_inp_string();
+        assert_eq!(
+            written,
+            "** nodes\n*NODE, NSET=NEW\n  1, 0.0, 0.0, 0.0\n2, 2.0, 0.0, 0.0\n*ELSET, ELSET=E1\n1\n"
+        );
+        let reparsed = Deck::parse_str(&written).expect("reparse");
+        assert_eq!(reparsed.cards.len(), 2);
+    }
+}
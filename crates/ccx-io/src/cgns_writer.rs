@@ -0,0 +1,825 @@
+//! CGNS export of meshes and nodal fields (feature `cgns`).
+//!
+//! Maps FRD data onto the CGNS SIDS layout of one unstructured zone:
+//!
+//! ```text
+//! Base "CalculiX" (cell dim 3, phys dim 3)
+//! └── Zone "Mesh" (vertices, cells)
+//!     ├── GridCoordinates: CoordinateX/Y/Z
+//!     ├── Elements_<TYPE>: one section per element type, 1-based ranges
+//!     └── FlowSolution "Step<n>_<i>" (Vertex): one array per component
+//! ```
+//!
+//! The writer is stream-based: coordinates are sent one component at a time
+//! and element sections and fields in chunks of at most `chunk_size`
+//! entities, mirroring `cg_section_partial_write` / `cg_field_partial_write`,
+//! so large meshes never need a second full copy in memory. The nodes go to a
+//! [`CgnsSink`]: [`CgnsFile`] writes a CGNS/HDF5 file readable by the CGNS
+//! library and ParaView, [`CgnsTree`] records the node hierarchy for
+//! inspection and testing.
+//!
+//! ```no_run
+//! use ccx_io::{CgnsFile, CgnsWriter, FrdFile};
+//!
+//! let frd = FrdFile::from_file("job.frd")?;
+//! let mut file = CgnsFile::create("job.cgns")?;
+//! CgnsWriter::new(&frd).write(&mut file)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+
+use crate::frd_reader::{FrdFile, ResultLocation};
+use crate::hdf5_file::{H5Dataset, H5File, H5Group, H5Type, library_version};
+
+/// CGNS `ElementType_t` values used for CalculiX elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CgnsElementType {
+    Bar2 = 3,
+    Bar3 = 4,
+    Tri3 = 5,
+    Tri6 = 6,
+    Quad4 = 7,
+    Quad8 = 8,
+    Tetra4 = 10,
+    Tetra10 = 11,
+    Penta6 = 14,
+    Penta15 = 15,
+    Hexa8 = 17,
+    Hexa20 = 18,
+}
+
+impl CgnsElementType {
+    /// CGNS type for an FRD element type code
+    pub fn from_frd(frd_type: i32) -> Option<Self> {
+        let element_type = match frd_type {
+            1 => Self::Hexa8,
+            2 => Self::Penta6,
+            3 => Self::Tetra4,
+            4 => Self::Hexa20,
+            5 => Self::Penta15,
+            6 => Self::Tetra10,
+            7 => Self::Tri3,
+            8 => Self::Tri6,
+            9 => Self::Quad4,
+            10 => Self::Quad8,
+            11 => Self::Bar2,
+            12 => Self::Bar3,
+            _ => return None,
+        };
+        Some(element_type)
+    }
+
+    /// SIDS name, e.g. `HEXA_20`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bar2 => "BAR_2",
+            Self::Bar3 => "BAR_3",
+            Self::Tri3 => "TRI_3",
+            Self::Tri6 => "TRI_6",
+            Self::Quad4 => "QUAD_4",
+            Self::Quad8 => "QUAD_8",
+            Self::Tetra4 => "TETRA_4",
+            Self::Tetra10 => "TETRA_10",
+            Self::Penta6 => "PENTA_6",
+            Self::Penta15 => "PENTA_15",
+            Self::Hexa8 => "HEXA_8",
+            Self::Hexa20 => "HEXA_20",
+        }
+    }
+
+    /// Nodes per element
+    pub fn node_count(self) -> usize {
+        match self {
+            Self::Bar2 => 2,
+            Self::Bar3 | Self::Tri3 => 3,
+            Self::Quad4 | Self::Tetra4 => 4,
+            Self::Tri6 | Self::Penta6 => 6,
+            Self::Quad8 | Self::Hexa8 => 8,
+            Self::Tetra10 => 10,
+            Self::Penta15 => 15,
+            Self::Hexa20 => 20,
+        }
+    }
+
+    /// CalculiX node index for each CGNS node position
+    fn node_order(self) -> Option<&'static [usize]> {
+        // CGNS puts the vertical (corner-to-corner) edges before the top
+        // edges; CalculiX lists them last.
+        const HEXA20: &[usize] = &[
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 16, 17, 18, 19, 12, 13, 14, 15,
+        ];
+        const PENTA15: &[usize] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 13, 14, 9, 10, 11];
+        match self {
+            Self::Hexa20 => Some(HEXA20),
+            Self::Penta15 => Some(PENTA15),
+            _ => None,
+        }
+    }
+}
+
+/// Receiver of the CGNS node stream, one call per mid-level library call.
+pub trait CgnsSink {
+    type Error;
+
+    fn base(&mut self, name: &str, cell_dim: u32, phys_dim: u32) -> Result<(), Self::Error>;
+    fn zone(&mut self, name: &str, vertices: usize, cells: usize) -> Result<(), Self::Error>;
+    fn coordinates(&mut self, name: &str, values: &[f64]) -> Result<(), Self::Error>;
+    /// Start a section covering 1-based cells `start..=end`
+    fn section(
+        &mut self,
+        name: &str,
+        element_type: CgnsElementType,
+        start: usize,
+        end: usize,
+    ) -> Result<(), Self::Error>;
+    /// Connectivity (1-based vertex indices) for cells `start..=end`
+    fn section_partial(
+        &mut self,
+        start: usize,
+        end: usize,
+        connectivity: &[i64],
+    ) -> Result<(), Self::Error>;
+    fn solution(&mut self, name: &str, time: f64) -> Result<(), Self::Error>;
+    /// Field values for vertices `start..=end` (1-based)
+    fn field_partial(
+        &mut self,
+        name: &str,
+        start: usize,
+        end: usize,
+        values: &[f64],
+    ) -> Result<(), Self::Error>;
+    /// Called once after the last field
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Streams FRD data into a [`CgnsSink`].
+pub struct CgnsWriter<'a> {
+    frd: &'a FrdFile,
+    chunk_size: usize,
+}
+
+impl<'a> CgnsWriter<'a> {
+    pub fn new(frd: &'a FrdFile) -> Self {
+        Self {
+            frd,
+            chunk_size: 65_536,
+        }
+    }
+
+    /// Maximum number of cells or vertices sent per partial write
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn write<S: CgnsSink>(&self, sink: &mut S) -> Result<(), S::Error> {
+        let mut node_ids: Vec<i32> = self.frd.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        let index: HashMap<i32, i64> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, &id)| (id, idx as i64 + 1))
+            .collect();
+
+        let mut sections: BTreeMap<CgnsElementType, Vec<i32>> = BTreeMap::new();
+        for (id, element) in &self.frd.elements {
+            if let Some(element_type) = CgnsElementType::from_frd(element.element_type) {
+                sections.entry(element_type).or_default().push(*id);
+            }
+        }
+        let cells: usize = sections.values().map(Vec::len).sum();
+
+        sink.base("CalculiX", 3, 3)?;
+        sink.zone("Mesh", node_ids.len(), cells)?;
+        for (axis, name) in ["CoordinateX", "CoordinateY", "CoordinateZ"]
+            .into_iter()
+            .enumerate()
+        {
+            let values: Vec<f64> = node_ids.iter().map(|id| self.frd.nodes[id][axis]).collect();
+            sink.coordinates(name, &values)?;
+        }
+
+        let mut next = 1;
+        for (element_type, mut ids) in sections {
+            ids.sort_unstable();
+            let end = next + ids.len() - 1;
+            sink.section(
+                &format!("Elements_{}", element_type.name()),
+                element_type,
+                next,
+                end,
+            )?;
+            for chunk in ids.chunks(self.chunk_size) {
+                let mut connectivity = Vec::new();
+                for id in chunk {
+                    let nodes = &self.frd.elements[id].nodes;
+                    let vertex = |i: usize| index.get(&nodes[i]).copied().unwrap_or(0);
+                    match element_type.node_order() {
+                        Some(order) if order.len() == nodes.len() => {
+                            connectivity.extend(order.iter().map(|&i| vertex(i)))
+                        }
+                        _ => connectivity.extend((0..nodes.len()).map(vertex)),
+                    }
+                }
+                sink.section_partial(next, next + chunk.len() - 1, &connectivity)?;
+                next += chunk.len();
+            }
+        }
+
+        for (idx, block) in self.frd.result_blocks.iter().enumerate() {
+            sink.solution(&format!("Step{}_{}", block.step, idx + 1), block.time)?;
+            for dataset in &block.datasets {
+                if dataset.location != ResultLocation::Nodal {
+                    continue;
+                }
+                for comp in 0..dataset.ncomps {
+                    let name = match dataset.comp_names.get(comp) {
+                        Some(comp_name) => format!("{}_{comp_name}", dataset.name),
+                        None => format!("{}_{}", dataset.name, comp + 1),
+                    };
+                    let mut start = 1;
+                    for chunk in node_ids.chunks(self.chunk_size) {
+                        let values: Vec<f64> = chunk
+                            .iter()
+                            .map(|id| {
+                                dataset
+                                    .values
+                                    .get(id)
+                                    .and_then(|v| v.get(comp))
+                                    .copied()
+                                    .unwrap_or(0.0)
+                            })
+                            .collect();
+                        sink.field_partial(&name, start, start + chunk.len() - 1, &values)?;
+                        start += chunk.len();
+                    }
+                }
+            }
+        }
+        sink.finish()
+    }
+}
+
+/// In-memory sink recording the CGNS node tree as `cgnslist`-style lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CgnsTree {
+    pub lines: Vec<String>,
+}
+
+impl CgnsSink for CgnsTree {
+    type Error = std::convert::Infallible;
+
+    fn base(&mut self, name: &str, cell_dim: u32, phys_dim: u32) -> Result<(), Self::Error> {
+        self.lines
+            .push(format!("{name} CGNSBase_t [{cell_dim} {phys_dim}]"));
+        Ok(())
+    }
+
+    fn zone(&mut self, name: &str, vertices: usize, cells: usize) -> Result<(), Self::Error> {
+        self.lines.push(format!(
+            "  {name} Zone_t Unstructured [{vertices} {cells} 0]"
+        ));
+        Ok(())
+    }
+
+    fn coordinates(&mut self, name: &str, values: &[f64]) -> Result<(), Self::Error> {
+        self.lines
+            .push(format!("    GridCoordinates/{name} [{}]", values.len()));
+        Ok(())
+    }
+
+    fn section(
+        &mut self,
+        name: &str,
+        element_type: CgnsElementType,
+        start: usize,
+        end: usize,
+    ) -> Result<(), Self::Error> {
+        self.lines.push(format!(
+            "    {name} Elements_t {} [{start}..{end}]",
+            element_type.name()
+        ));
+        Ok(())
+    }
+
+    fn section_partial(
+        &mut self,
+        start: usize,
+        end: usize,
+        connectivity: &[i64],
+    ) -> Result<(), Self::Error> {
+        self.lines.push(format!(
+            "      ElementConnectivity [{start}..{end}] {connectivity:?}"
+        ));
+        Ok(())
+    }
+
+    fn solution(&mut self, name: &str, time: f64) -> Result<(), Self::Error> {
+        self.lines
+            .push(format!("    {name} FlowSolution_t Vertex t={time}"));
+        Ok(())
+    }
+
+    fn field_partial(
+        &mut self,
+        name: &str,
+        start: usize,
+        end: usize,
+        values: &[f64],
+    ) -> Result<(), Self::Error> {
+        self.lines
+            .push(format!("      {name} [{start}..{end}] {values:?}"));
+        Ok(())
+    }
+}
+
+/// Name of the dataset holding the data of a CGNS/HDF5 node
+const DATA: &str = " data";
+
+/// Version recorded in the `CGNSLibraryVersion` node
+const CGNS_VERSION: f32 = 3.4;
+
+/// Sink writing a CGNS/HDF5 file.
+///
+/// Uses the node layout of the CGNS library's HDF5 back end: every node is a
+/// group carrying its `name`, SIDS `label`, data `type` and `flags` as
+/// attributes and its data in the dataset `" data"` (extents reversed from
+/// the Fortran order of SIDS). Index arrays are stored as `I4` unless the
+/// zone needs `I8`. Connectivity and field arrays are allocated once and
+/// filled slice by slice; the time-step bookkeeping
+/// (`BaseIterativeData`/`ZoneIterativeData`) is written by `finish`.
+pub struct CgnsFile {
+    /// Connectivity of the open section, its first cell and nodes per cell
+    section: Option<(H5Dataset, usize, usize)>,
+    /// Open flow solution and its field arrays
+    solution: Option<(H5Group, HashMap<String, H5Dataset>)>,
+    grid: Option<H5Group>,
+    zone: Option<H5Group>,
+    base: Option<H5Group>,
+    root: H5Group,
+    // Declared last so that every node is closed before the file
+    _file: H5File,
+    solutions: Vec<(String, f64)>,
+    vertices: usize,
+    wide: bool,
+}
+
+impl CgnsFile {
+    /// Create or truncate the CGNS file at `path`
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = H5File::create(path.as_ref())?;
+        let root = file.root()?;
+        root.string_attr("name", "HDF5 MotherNode", 33)?;
+        root.string_attr("label", "Root Node of HDF5 File", 33)?;
+        root.string_attr("type", "MT", 3)?;
+        let format = if cfg!(target_endian = "big") {
+            "IEEE_BIG_32"
+        } else {
+            "IEEE_LITTLE_32"
+        };
+        root.write_dataset(" format", &[format.len()], &chars(format))?;
+        let mut version = chars(&format!("HDF5 Version {}", library_version()?));
+        version.resize(33, 0);
+        root.write_dataset(" hdf5version", &[33], &version)?;
+        data_node(
+            &root,
+            "CGNSLibraryVersion",
+            "CGNSLibraryVersion_t",
+            "R4",
+            &[1],
+            &[CGNS_VERSION],
+        )?;
+        Ok(Self {
+            section: None,
+            solution: None,
+            grid: None,
+            zone: None,
+            base: None,
+            root,
+            _file: file,
+            solutions: Vec::new(),
+            vertices: 0,
+            wide: false,
+        })
+    }
+
+    /// Node of 1-based indices, `I8` if the zone is too large for `I4`
+    fn index_node(
+        &self,
+        parent: &H5Group,
+        name: &str,
+        label: &str,
+        dims: &[usize],
+        values: &[usize],
+    ) -> io::Result<H5Group> {
+        if self.wide {
+            let values: Vec<i64> = values.iter().map(|&v| v as i64).collect();
+            data_node(parent, name, label, "I8", dims, &values)
+        } else {
+            let values: Vec<i32> = values.iter().map(|&v| v as i32).collect();
+            data_node(parent, name, label, "I4", dims, &values)
+        }
+    }
+}
+
+/// Create the node `name` under `parent`
+fn node(parent: &H5Group, name: &str, label: &str, data_type: &str) -> io::Result<H5Group> {
+    let group = parent.create_group(name)?;
+    group.string_attr("name", name, 33)?;
+    group.string_attr("label", label, 33)?;
+    group.string_attr("type", data_type, 3)?;
+    group.int_attr("flags", &[1])?;
+    Ok(group)
+}
+
+/// Create the node `name` holding `values` with SIDS extents `dims`
+fn data_node<T: H5Type>(
+    parent: &H5Group,
+    name: &str,
+    label: &str,
+    data_type: &str,
+    dims: &[usize],
+    values: &[T],
+) -> io::Result<H5Group> {
+    let group = node(parent, name, label, data_type)?;
+    let dims: Vec<usize> = dims.iter().rev().copied().collect();
+    group.write_dataset(DATA, &dims, values)?;
+    Ok(group)
+}
+
+/// Create the `C1` node `name` holding `value`
+fn string_node(parent: &H5Group, name: &str, label: &str, value: &str) -> io::Result<H5Group> {
+    data_node(parent, name, label, "C1", &[value.len()], &chars(value))
+}
+
+fn chars(text: &str) -> Vec<i8> {
+    text.bytes().map(|b| b as i8).collect()
+}
+
+fn not_open(label: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("CGNS: no {label} node is open"),
+    )
+}
+
+fn open<'a>(node: &'a Option<H5Group>, label: &str) -> io::Result<&'a H5Group> {
+    node.as_ref().ok_or_else(|| not_open(label))
+}
+
+fn check_count(what: &str, expected: usize, actual: usize) -> io::Result<()> {
+    if expected != actual {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("CGNS {what}: {actual} values, expected {expected}"),
+        ));
+    }
+    Ok(())
+}
+
+impl CgnsSink for CgnsFile {
+    type Error = io::Error;
+
+    fn base(&mut self, name: &str, cell_dim: u32, phys_dim: u32) -> io::Result<()> {
+        let dims = [cell_dim as i32, phys_dim as i32];
+        self.base = Some(data_node(
+            &self.root,
+            name,
+            "CGNSBase_t",
+            "I4",
+            &[2],
+            &dims,
+        )?);
+        Ok(())
+    }
+
+    fn zone(&mut self, name: &str, vertices: usize, cells: usize) -> io::Result<()> {
+        self.wide = vertices.max(cells) > i32::MAX as usize;
+        self.vertices = vertices;
+        let base = open(&self.base, "CGNSBase_t")?;
+        let zone = self.index_node(base, name, "Zone_t", &[1, 3], &[vertices, cells, 0])?;
+        string_node(&zone, "ZoneType", "ZoneType_t", "Unstructured")?;
+        self.grid = Some(node(&zone, "GridCoordinates", "GridCoordinates_t", "MT")?);
+        self.zone = Some(zone);
+        Ok(())
+    }
+
+    fn coordinates(&mut self, name: &str, values: &[f64]) -> io::Result<()> {
+        check_count(name, self.vertices, values.len())?;
+        let grid = open(&self.grid, "GridCoordinates_t")?;
+        data_node(grid, name, "DataArray_t", "R8", &[values.len()], values)?;
+        Ok(())
+    }
+
+    fn section(
+        &mut self,
+        name: &str,
+        element_type: CgnsElementType,
+        start: usize,
+        end: usize,
+    ) -> io::Result<()> {
+        let zone = open(&self.zone, "Zone_t")?;
+        let section = data_node(
+            zone,
+            name,
+            "Elements_t",
+            "I4",
+            &[2],
+            &[element_type as i32, 0],
+        )?;
+        self.index_node(
+            &section,
+            "ElementRange",
+            "IndexRange_t",
+            &[2],
+            &[start, end],
+        )?;
+        let nodes = element_type.node_count();
+        let len = (end + 1 - start) * nodes;
+        let connectivity = if self.wide {
+            node(&section, "ElementConnectivity", "DataArray_t", "I8")?
+                .create_vector::<i64>(DATA, len)?
+        } else {
+            node(&section, "ElementConnectivity", "DataArray_t", "I4")?
+                .create_vector::<i32>(DATA, len)?
+        };
+        self.section = Some((connectivity, start, nodes));
+        Ok(())
+    }
+
+    fn section_partial(
+        &mut self,
+        start: usize,
+        end: usize,
+        connectivity: &[i64],
+    ) -> io::Result<()> {
+        let Some((dataset, first, nodes)) = &self.section else {
+            return Err(not_open("Elements_t"));
+        };
+        check_count(
+            "ElementConnectivity",
+            (end + 1 - start) * nodes,
+            connectivity.len(),
+        )?;
+        let offset = (start - first) * nodes;
+        if self.wide {
+            dataset.write_slice(offset, connectivity)
+        } else {
+            let values: Vec<i32> = connectivity.iter().map(|&v| v as i32).collect();
+            dataset.write_slice(offset, &values)
+        }
+    }
+
+    fn solution(&mut self, name: &str, time: f64) -> io::Result<()> {
+        let zone = open(&self.zone, "Zone_t")?;
+        let solution = node(zone, name, "FlowSolution_t", "MT")?;
+        string_node(&solution, "GridLocation", "GridLocation_t", "Vertex")?;
+        self.solution = Some((solution, HashMap::new()));
+        self.solutions.push((name.to_string(), time));
+        Ok(())
+    }
+
+    fn field_partial(
+        &mut self,
+        name: &str,
+        start: usize,
+        end: usize,
+        values: &[f64],
+    ) -> io::Result<()> {
+        check_count(name, end + 1 - start, values.len())?;
+        let vertices = self.vertices;
+        let Some((solution, fields)) = &mut self.solution else {
+            return Err(not_open("FlowSolution_t"));
+        };
+        if !fields.contains_key(name) {
+            let field = node(solution, name, "DataArray_t", "R8")?;
+            fields.insert(
+                name.to_string(),
+                field.create_vector::<f64>(DATA, vertices)?,
+            );
+        }
+        fields[name].write_slice(start - 1, values)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.section = None;
+        self.solution = None;
+        if self.solutions.is_empty() {
+            return Ok(());
+        }
+        let steps = self.solutions.len();
+        let base = open(&self.base, "CGNSBase_t")?;
+        let iterative = data_node(
+            base,
+            "BaseIterativeData",
+            "BaseIterativeData_t",
+            "I4",
+            &[1],
+            &[steps as i32],
+        )?;
+        let times: Vec<f64> = self.solutions.iter().map(|(_, time)| *time).collect();
+        data_node(
+            &iterative,
+            "TimeValues",
+            "DataArray_t",
+            "R8",
+            &[steps],
+            &times,
+        )?;
+
+        let zone = open(&self.zone, "Zone_t")?;
+        let zone_iterative = node(zone, "ZoneIterativeData", "ZoneIterativeData_t", "MT")?;
+        let mut pointers = vec![0i8; 32 * steps];
+        for ((name, _), pointer) in self.solutions.iter().zip(pointers.chunks_mut(32)) {
+            let name = chars(name);
+            let len = name.len().min(32);
+            pointer[..len].copy_from_slice(&name[..len]);
+        }
+        data_node(
+            &zone_iterative,
+            "FlowSolutionPointers",
+            "DataArray_t",
+            "C1",
+            &[32, steps],
+            &pointers,
+        )?;
+        string_node(base, "SimulationType", "SimulationType_t", "TimeAccurate")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frd_reader::{FrdElement, ResultBlock, ResultDataset};
+
+    fn bar_model() -> FrdFile {
+        let mut frd = FrdFile::new();
+        for id in 1..=3 {
+            frd.nodes.insert(id, [id as f64, 0.0, 0.0]);
+        }
+        for id in 1..=2 {
+            frd.elements.insert(
+                id,
+                FrdElement {
+                    id,
+                    element_type: 11,
+                    nodes: vec![id, id + 1],
+                },
+            );
+        }
+        let mut temp = HashMap::new();
+        temp.insert(2, vec![20.0]);
+        frd.result_blocks.push(ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets: vec![ResultDataset::nodal("NDTEMP", temp)],
+        });
+        frd
+    }
+
+    #[test]
+    fn streams_sections_and_fields_in_chunks() {
+        let frd = bar_model();
+        let mut tree = CgnsTree::default();
+        let Ok(()) = CgnsWriter::new(&frd).with_chunk_size(2).write(&mut tree);
+        assert_eq!(
+            tree.lines,
+            vec![
+                "CalculiX CGNSBase_t [3 3]",
+                "  Mesh Zone_t Unstructured [3 2 0]",
+                "    GridCoordinates/CoordinateX [3]",
+                "    GridCoordinates/CoordinateY [3]",
+                "    GridCoordinates/CoordinateZ [3]",
+                "    Elements_BAR_2 Elements_t BAR_2 [1..2]",
+                "      ElementConnectivity [1..2] [1, 2, 2, 3]",
+                "    Step1_1 FlowSolution_t Vertex t=1",
+                "      NDTEMP_T [1..2] [0.0, 20.0]",
+                "      NDTEMP_T [3..3] [0.0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_cgns_hdf5_file() {
+        let path = std::env::temp_dir().join(format!("ccx_io_cgns_{}.cgns", std::process::id()));
+        let mut file = CgnsFile::create(&path).expect("create cgns");
+        CgnsWriter::new(&bar_model())
+            .with_chunk_size(2)
+            .write(&mut file)
+            .expect("write cgns");
+        drop(file);
+
+        let bytes = std::fs::read(&path).expect("read cgns");
+        assert_eq!(&bytes[..8], b"\x89HDF\r\n\x1a\n");
+
+        let file = H5File::open(&path).expect("open cgns");
+        let root = file.root().expect("root");
+        assert_eq!(
+            root.read_string_attr("label").expect("root label").0,
+            "Root Node of HDF5 File"
+        );
+        let (_, format) = root.read_dataset::<i8>(" format").expect("format");
+        assert!(text(&format).starts_with("IEEE_"));
+        let (dims, version) = root.read_dataset::<i8>(" hdf5version").expect("version");
+        assert_eq!(dims, [33]);
+        assert!(text(&version).starts_with("HDF5 Version "));
+
+        let mut lines = Vec::new();
+        dump(&root, "", &mut lines);
+        assert_eq!(
+            lines,
+            [
+                "CGNSLibraryVersion CGNSLibraryVersion_t R4 [1] [3.4]",
+                "CalculiX CGNSBase_t I4 [2] [3, 3]",
+                "  Mesh Zone_t I4 [3, 1] [3, 2, 0]",
+                "    ZoneType ZoneType_t C1 [12] Unstructured",
+                "    GridCoordinates GridCoordinates_t MT",
+                "      CoordinateX DataArray_t R8 [3] [1, 2, 3]",
+                "      CoordinateY DataArray_t R8 [3] [0, 0, 0]",
+                "      CoordinateZ DataArray_t R8 [3] [0, 0, 0]",
+                "    Elements_BAR_2 Elements_t I4 [2] [3, 0]",
+                "      ElementRange IndexRange_t I4 [2] [1, 2]",
+                "      ElementConnectivity DataArray_t I4 [4] [1, 2, 2, 3]",
+                "    Step1_1 FlowSolution_t MT",
+                "      GridLocation GridLocation_t C1 [6] Vertex",
+                "      NDTEMP_T DataArray_t R8 [3] [0, 20, 0]",
+                "    ZoneIterativeData ZoneIterativeData_t MT",
+                "      FlowSolutionPointers DataArray_t C1 [1, 32] Step1_1",
+                "  BaseIterativeData BaseIterativeData_t I4 [1] [1]",
+                "    TimeValues DataArray_t R8 [1] [1]",
+                "  SimulationType SimulationType_t C1 [12] TimeAccurate",
+            ]
+        );
+        drop((root, file));
+        match std::env::var_os("CCX_IO_TEST_OUTPUT") {
+            // Kept for h5dump and cgnscheck in CI
+            Some(dir) => {
+                std::fs::create_dir_all(&dir).expect("output dir");
+                std::fs::rename(&path, Path::new(&dir).join("bar.cgns")).expect("keep cgns");
+            }
+            None => {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn text(chars: &[i8]) -> String {
+        let bytes: Vec<u8> = chars
+            .iter()
+            .map(|&c| c as u8)
+            .take_while(|&b| b != 0)
+            .collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// One line per CGNS node below `group`: name, label, data type and the
+    /// HDF5 extents and values of its data; checks the node attributes that
+    /// the CGNS library requires on the way
+    fn dump(group: &H5Group, indent: &str, lines: &mut Vec<String>) {
+        for name in group.children().expect("children") {
+            if name.starts_with(' ') {
+                continue;
+            }
+            let node = group.open_group(&name).expect("node");
+            let (node_name, size) = node.read_string_attr("name").expect("name");
+            assert_eq!((node_name.as_str(), size), (name.as_str(), 33));
+            let (label, size) = node.read_string_attr("label").expect("label");
+            assert_eq!(size, 33);
+            let (data_type, size) = node.read_string_attr("type").expect("type");
+            assert_eq!(size, 3);
+            assert_eq!(node.read_int_attr("flags").expect("flags"), [1]);
+
+            let mut line = format!("{indent}{name} {label} {data_type}");
+            let has_data = node.children().expect("children").iter().any(|c| c == DATA);
+            assert_eq!(has_data, data_type != "MT", "{name}");
+            if has_data {
+                let (dims, values) = node.read_dataset::<f64>(DATA).expect("data");
+                line.push_str(&format!(" {dims:?} "));
+                if data_type == "C1" {
+                    let (_, chars) = node.read_dataset::<i8>(DATA).expect("chars");
+                    line.push_str(&text(&chars));
+                } else {
+                    let values: Vec<f32> = values.iter().map(|&v| v as f32).collect();
+                    line.push_str(&format!("{values:?}").replace(".0", ""));
+                }
+            }
+            lines.push(line);
+            dump(&node, &format!("{indent}  "), lines);
+        }
+    }
+
+    #[test]
+    fn reorders_quadratic_hexahedron_edges() {
+        let order = CgnsElementType::Hexa20.node_order().expect("hexa20 order");
+        assert_eq!(&order[12..16], &[16, 17, 18, 19]);
+        assert_eq!(order.len(), CgnsElementType::Hexa20.node_count());
+        assert_eq!(CgnsElementType::from_frd(6), Some(CgnsElementType::Tetra10));
+    }
+}
@@ -0,0 +1,209 @@
+//! Live `.sta` and `.cvg` convergence monitoring files.
+//!
+//! Incremental solvers (nonlinear static, dynamic) report every converged
+//! increment to `job.sta` and every equilibrium iteration to `job.cvg`,
+//! using the same column layout as upstream ccx. Lines are flushed as they
+//! are written so `tail -f job.sta` works while the job runs.
+//!
+//! Only the linear static path of `ccx-cli solve` uses the monitor so far:
+//! it writes the single increment of the step, or the failure, to `job.sta`
+//! and leaves `job.cvg` with its header only.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::fortran::format_e_1p;
+
+/// A converged (or abandoned) increment, as listed in `job.sta`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IncrementRecord {
+    pub step: i32,
+    pub increment: i32,
+    /// Number of attempts (cutbacks + 1) needed for this increment
+    pub attempts: i32,
+    /// Equilibrium iterations of the successful attempt
+    pub iterations: i32,
+    pub total_time: f64,
+    pub step_time: f64,
+    pub increment_time: f64,
+}
+
+/// One equilibrium iteration, as listed in `job.cvg`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationRecord {
+    pub step: i32,
+    pub increment: i32,
+    pub attempt: i32,
+    pub iteration: i32,
+    /// Number of active contact elements
+    pub contact_elements: usize,
+    /// Largest residual force relative to the average force (%)
+    pub residual_force: f64,
+    /// Largest displacement correction relative to the increment (%)
+    pub correction_disp: f64,
+    /// Largest residual flux relative to the average flux (%)
+    pub residual_flux: f64,
+    /// Largest temperature correction relative to the increment (%)
+    pub correction_temp: f64,
+}
+
+/// Writer for the `job.sta` / `job.cvg` pair of a running job.
+pub struct ConvergenceMonitor {
+    sta_path: PathBuf,
+    cvg_path: PathBuf,
+    sta: BufWriter<File>,
+    cvg: BufWriter<File>,
+}
+
+impl ConvergenceMonitor {
+    /// Create `<dir>/<job_name>.sta` and `.cvg` and write their headers.
+    pub fn create(dir: impl AsRef<Path>, job_name: &str) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let sta_path = dir.join(format!("{job_name}.sta"));
+        let cvg_path = dir.join(format!("{job_name}.cvg"));
+
+        let mut sta = BufWriter::new(File::create(&sta_path)?);
+        writeln!(sta, "SUMMARY OF JOB INFORMATION")?;
+        writeln!(
+            sta,
+            "  STEP      INC     ATT   ITRS     TOT TIME     STEP TIME         INC TIME"
+        )?;
+        sta.flush()?;
+
+        let mut cvg = BufWriter::new(File::create(&cvg_path)?);
+        writeln!(cvg, "   SUMMARY OF C0NVERGENCE INFORMATION")?;
+        writeln!(
+            cvg,
+            "  STEP   INC  ATT  ITER     CONT.   RESID.        CORR.      RESID.      CORR."
+        )?;
+        writeln!(
+            cvg,
+            "                             EL.    FORCE         DISP       FLUX        TEMP."
+        )?;
+        writeln!(
+            cvg,
+            "                             (#)     (%)           (%)        (%)         (%)"
+        )?;
+        cvg.flush()?;
+
+        Ok(Self {
+            sta_path,
+            cvg_path,
+            sta,
+            cvg,
+        })
+    }
+
+    /// Append an increment line to `job.sta`.
+    pub fn record_increment(&mut self, record: &IncrementRecord) -> io::Result<()> {
+        writeln!(
+            self.sta,
+            "{:6}{:9}{:7}{:7}{}{}{}",
+            record.step,
+            record.increment,
+            record.attempts,
+            record.iterations,
+            format_e_1p(record.total_time, 6, 14),
+            format_e_1p(record.step_time, 6, 14),
+            format_e_1p(record.increment_time, 6, 17)
+        )?;
+        self.sta.flush()
+    }
+
+    /// Append an iteration line to `job.cvg`.
+    pub fn record_iteration(&mut self, record: &IterationRecord) -> io::Result<()> {
+        writeln!(
+            self.cvg,
+            "{:6}{:6}{:5}{:6}{:8}{}{}{}{}",
+            record.step,
+            record.increment,
+            record.attempt,
+            record.iteration,
+            record.contact_elements,
+            format_e_1p(record.residual_force, 4, 12),
+            format_e_1p(record.correction_disp, 4, 12),
+            format_e_1p(record.residual_flux, 4, 12),
+            format_e_1p(record.correction_temp, 4, 12)
+        )?;
+        self.cvg.flush()
+    }
+
+    /// Record that a step ended without converging (written to `job.sta`).
+    pub fn record_failure(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.sta, " *ERROR: {message}")?;
+        self.sta.flush()
+    }
+
+    pub fn sta_path(&self) -> &Path {
+        &self.sta_path
+    }
+
+    pub fn cvg_path(&self) -> &Path {
+        &self.cvg_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn writes_sta_and_cvg_lines() {
+        let dir = unique_temp_dir("ccx_io_convergence");
+        let mut monitor = ConvergenceMonitor::create(&dir, "job").expect("create monitor");
+
+        for iteration in 1..=2 {
+            monitor
+                .record_iteration(&IterationRecord {
+                    step: 1,
+                    increment: 1,
+                    attempt: 1,
+                    iteration,
+                    contact_elements: 0,
+                    residual_force: 10.0 / iteration as f64,
+                    correction_disp: 1.0e-3,
+                    residual_flux: 0.0,
+                    correction_temp: 0.0,
+                })
+                .expect("record iteration");
+        }
+        monitor
+            .record_increment(&IncrementRecord {
+                step: 1,
+                increment: 1,
+                attempts: 1,
+                iterations: 2,
+                total_time: 0.1,
+                step_time: 0.1,
+                increment_time: 0.1,
+            })
+            .expect("record increment");
+
+        let sta = fs::read_to_string(monitor.sta_path()).expect("read sta");
+        let last = sta.lines().last().expect("increment line");
+        assert_eq!(
+            last,
+            "     1        1      1      2  1.000000E-01  1.000000E-01     1.000000E-01"
+        );
+
+        let cvg = fs::read_to_string(monitor.cvg_path()).expect("read cvg");
+        let rows: Vec<&str> = cvg.lines().skip(4).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[1],
+            "     1     1    1     2       0  5.0000E+00  1.0000E-03  0.0000E+00  0.0000E+00"
+        );
+    }
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let pid = std::process::id();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock should be valid")
+            .as_nanos();
+        std::env::temp_dir().join(format!("{prefix}_{pid}_{nanos}"))
+    }
+}
@@ -0,0 +1,375 @@
+//! Numerical comparison of `.dat` result tables against a reference.
+//!
+//! ccx writes `*NODE PRINT` / `*EL PRINT` output as tables headed
+//!
+//! ```text
+//!  displacements (vx,vy,vz) for set NALL and time  0.1000000E+01
+//! ```
+//!
+//! followed by one row per node, or per element integration point when the
+//! column list starts with `elem`. Tables are paired by title and set, in
+//! file order, and the differences are collected per quantity (the title
+//! up to the column list, e.g. `displacements`). A quantity passes when
+//!
+//! ```text
+//! max |a - b| <= absolute + relative * max |a|
+//! ```
+//!
+//! over all its tables, with `a` taken as the reference. Reference tables
+//! without a counterpart are listed as unmatched rather than failing, since
+//! a solver may not produce every quantity ccx does.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::frd_compare::FrdTolerances;
+
+/// One row of a `.dat` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatRow {
+    /// Node or element ID; 0 for rows without one, such as total forces
+    pub id: i32,
+    /// Integration point of element tables
+    pub point: Option<i32>,
+    pub values: Vec<f64>,
+}
+
+/// A result table read from a `.dat` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatTable {
+    /// Title up to the column list, e.g. `displacements`
+    pub quantity: String,
+    /// Column list, e.g. `vx,vy,vz`
+    pub columns: String,
+    pub set: String,
+    pub time: f64,
+    pub rows: Vec<DatRow>,
+}
+
+impl DatTable {
+    fn key(&self) -> (String, String, String) {
+        (
+            self.quantity.clone(),
+            self.columns.clone(),
+            self.set.to_ascii_uppercase(),
+        )
+    }
+}
+
+/// Parse a table header into quantity, columns, set and time
+fn parse_header(line: &str) -> Option<(String, String, String, f64)> {
+    let (title, rest) = line.split_once(" for set ")?;
+    let (set, time) = rest.split_once(" and time")?;
+    let time = time.trim().parse().ok()?;
+    let title = title.trim();
+    let (quantity, columns) = match title.split_once('(') {
+        Some((quantity, columns)) => (quantity.trim(), columns.trim_end_matches(')').trim()),
+        None => (title, ""),
+    };
+    Some((
+        quantity.to_string(),
+        columns.to_string(),
+        set.trim().to_string(),
+        time,
+    ))
+}
+
+/// Parse a table row, `None` for lines that are not one
+fn parse_row(line: &str, per_point: bool) -> Option<DatRow> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let leading = if per_point { 2 } else { 1 };
+    let (id, point, values) = match tokens.first()?.parse::<i32>() {
+        Ok(id) if tokens.len() > leading => {
+            let point = if per_point {
+                Some(tokens[1].parse().ok()?)
+            } else {
+                None
+            };
+            (id, point, &tokens[leading..])
+        }
+        _ => (0, None, &tokens[..]),
+    };
+    let values: Vec<f64> = values
+        .iter()
+        .map(|t| t.replace(['D', 'd'], "E").parse().ok())
+        .collect::<Option<_>>()?;
+    Some(DatRow { id, point, values })
+}
+
+/// Read all result tables of a `.dat` file, skipping other output
+pub fn parse_dat_tables(text: &str) -> Vec<DatTable> {
+    let mut tables: Vec<DatTable> = Vec::new();
+    let mut open = false;
+    for line in text.lines() {
+        if let Some((quantity, columns, set, time)) = parse_header(line) {
+            tables.push(DatTable {
+                quantity,
+                columns,
+                set,
+                time,
+                rows: Vec::new(),
+            });
+            open = true;
+            continue;
+        }
+        if line.trim().is_empty() || !open {
+            continue;
+        }
+        let table = tables.last_mut().expect("open table");
+        let per_point = table.columns.starts_with("elem");
+        match parse_row(line, per_point) {
+            Some(row) => table.rows.push(row),
+            None => open = false,
+        }
+    }
+    tables
+}
+
+/// Differences of one quantity over all its tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantityComparison {
+    pub quantity: String,
+    /// Tables paired with a reference table
+    pub tables: usize,
+    /// Rows present in both files
+    pub compared: usize,
+    /// Rows present in only one of the files
+    pub missing: usize,
+    pub max_abs: f64,
+    /// `max_abs` relative to `max_reference`
+    pub max_rel: f64,
+    /// Largest magnitude in the reference tables
+    pub max_reference: f64,
+    /// ID, integration point and column of the largest difference
+    pub worst: Option<(i32, Option<i32>, usize)>,
+    /// Allowed maximum difference
+    pub tolerance: f64,
+    pub passed: bool,
+}
+
+/// Result of [`dat_compare`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DatComparison {
+    pub quantities: Vec<QuantityComparison>,
+    /// Reference tables without a counterpart
+    pub unmatched: Vec<String>,
+    /// Mismatches that prevent a table-by-table comparison
+    pub errors: Vec<String>,
+}
+
+impl DatComparison {
+    /// Whether something was compared and every quantity is within tolerance
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+            && !self.quantities.is_empty()
+            && self.quantities.iter().all(|q| q.passed)
+    }
+
+    /// Quantities exceeding their tolerance
+    pub fn failures(&self) -> impl Iterator<Item = &QuantityComparison> {
+        self.quantities.iter().filter(|q| !q.passed)
+    }
+}
+
+impl fmt::Display for DatComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for error in &self.errors {
+            writeln!(f, "ERROR {error}")?;
+        }
+        for q in &self.quantities {
+            writeln!(
+                f,
+                "{} {:<16} max {:.3e} rel {:.3e} tol {:.3e} ({} tables, {} compared, {} missing)",
+                if q.passed { "PASS " } else { "FAIL " },
+                q.quantity,
+                q.max_abs,
+                q.max_rel,
+                q.tolerance,
+                q.tables,
+                q.compared,
+                q.missing
+            )?;
+        }
+        for table in &self.unmatched {
+            writeln!(f, "SKIP  {table}")?;
+        }
+        write!(f, "{}", if self.passed() { "PASSED" } else { "FAILED" })
+    }
+}
+
+/// Compare the tables of `actual` against the reference `reference`.
+pub fn dat_compare(
+    reference: &[DatTable],
+    actual: &[DatTable],
+    tolerances: &FrdTolerances,
+) -> DatComparison {
+    let mut comparison = DatComparison::default();
+    if reference.is_empty() {
+        comparison
+            .errors
+            .push("reference has no result tables".to_string());
+        return comparison;
+    }
+
+    // n-th table of a title and set pairs with the n-th of the same key
+    let mut counterparts: HashMap<(String, String, String), Vec<&DatTable>> = HashMap::new();
+    for table in actual {
+        counterparts.entry(table.key()).or_default().push(table);
+    }
+    let mut seen: HashMap<(String, String, String), usize> = HashMap::new();
+    let mut quantities: BTreeMap<String, QuantityComparison> = BTreeMap::new();
+    for table in reference {
+        let key = table.key();
+        let index = seen.entry(key.clone()).or_default();
+        let other = counterparts.get(&key).and_then(|t| t.get(*index));
+        *index += 1;
+        let Some(other) = other else {
+            comparison.unmatched.push(format!(
+                "{} for set {} and time {:.6e}",
+                table.quantity, table.set, table.time
+            ));
+            continue;
+        };
+        if (table.time - other.time).abs() > 1e-6 * table.time.abs().max(1.0) {
+            comparison.errors.push(format!(
+                "{} for set {}: time {:.6e} vs {:.6e}",
+                table.quantity, table.set, table.time, other.time
+            ));
+        }
+        let entry =
+            quantities
+                .entry(table.quantity.clone())
+                .or_insert_with(|| QuantityComparison {
+                    quantity: table.quantity.clone(),
+                    tables: 0,
+                    compared: 0,
+                    missing: 0,
+                    max_abs: 0.0,
+                    max_rel: 0.0,
+                    max_reference: 0.0,
+                    worst: None,
+                    tolerance: 0.0,
+                    passed: false,
+                });
+        compare_table(table, other, entry);
+    }
+
+    for (name, mut quantity) in quantities {
+        let (absolute, relative) = tolerances.for_dataset(&name);
+        quantity.tolerance = absolute + relative * quantity.max_reference;
+        quantity.max_rel = if quantity.max_reference > 0.0 {
+            quantity.max_abs / quantity.max_reference
+        } else {
+            0.0
+        };
+        quantity.passed = quantity.missing == 0 && quantity.max_abs <= quantity.tolerance;
+        comparison.quantities.push(quantity);
+    }
+    comparison
+}
+
+fn compare_table(a: &DatTable, b: &DatTable, result: &mut QuantityComparison) {
+    result.tables += 1;
+    let rows_b: HashMap<(i32, Option<i32>), &DatRow> = b
+        .rows
+        .iter()
+        .map(|row| ((row.id, row.point), row))
+        .collect();
+    let mut matched = 0;
+    for row_a in &a.rows {
+        let Some(row_b) = rows_b.get(&(row_a.id, row_a.point)) else {
+            result.missing += 1;
+            continue;
+        };
+        matched += 1;
+        result.compared += 1;
+        for column in 0..row_a.values.len().max(row_b.values.len()) {
+            let va = row_a.values.get(column).copied().unwrap_or(0.0);
+            let vb = row_b.values.get(column).copied().unwrap_or(0.0);
+            let diff = (va - vb).abs();
+            result.max_reference = result.max_reference.max(va.abs());
+            // NaN never compares greater, so force it to be reported
+            if diff > result.max_abs || (diff.is_nan() && !result.max_abs.is_nan()) {
+                result.max_abs = diff;
+                result.worst = Some((row_a.id, row_a.point, column));
+            }
+        }
+    }
+    result.missing += b.rows.len() - matched;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dat_writer::{DatSection, DatStep, write_dat_results_to};
+
+    fn dat(displacement: f64, stress: f64) -> String {
+        let steps = vec![DatStep {
+            step: 1,
+            increment: 1,
+            time: 1.0,
+            sections: vec![
+                DatSection::Displacements {
+                    set: "NALL".to_string(),
+                    values: vec![(1, [0.0, 0.0, 0.0]), (2, [displacement, 0.0, -1e-4])],
+                },
+                DatSection::Stresses {
+                    set: "EALL".to_string(),
+                    values: vec![(1, 1, [stress, 0.0, 0.0, 0.0, 0.0, 0.0])],
+                },
+                DatSection::total_force("FIX", &[(1, [-5.0, 0.0, 0.0])]),
+            ],
+        }];
+        let mut out = Vec::new();
+        write_dat_results_to(&mut out, &steps).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn parses_node_element_and_total_tables() {
+        let tables = parse_dat_tables(&dat(1.5e-3, 200.0));
+        assert_eq!(tables.len(), 3);
+        assert_eq!(tables[0].quantity, "displacements");
+        assert_eq!(tables[0].columns, "vx,vy,vz");
+        assert_eq!(tables[0].set, "NALL");
+        assert_eq!(tables[0].time, 1.0);
+        assert_eq!(tables[0].rows[1].values, vec![1.5e-3, 0.0, -1e-4]);
+        assert_eq!(tables[1].rows[0].point, Some(1));
+        assert_eq!(tables[1].rows[0].values[0], 200.0);
+        assert_eq!(tables[2].quantity, "total force");
+        assert_eq!(tables[2].rows[0].id, 0);
+        assert_eq!(tables[2].rows[0].values, vec![-5.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn reports_max_error_per_quantity() {
+        let reference = parse_dat_tables(&dat(1.5e-3, 200.0));
+        let same = dat_compare(&reference, &reference, &FrdTolerances::default());
+        assert!(same.passed(), "{same}");
+        assert_eq!(same.quantities.len(), 3);
+
+        let actual = parse_dat_tables(&dat(1.5e-3, 201.0));
+        let comparison = dat_compare(&reference, &actual, &FrdTolerances::new(0.0, 1e-3));
+        assert!(!comparison.passed());
+        let failures: Vec<&str> = comparison.failures().map(|q| q.quantity.as_str()).collect();
+        assert_eq!(failures, vec!["stresses"]);
+        let stresses = &comparison.quantities[1];
+        assert!((stresses.max_abs - 1.0).abs() < 1e-9);
+        assert!((stresses.max_rel - 0.005).abs() < 1e-9);
+        assert_eq!(stresses.worst, Some((1, Some(1), 0)));
+
+        let loose = FrdTolerances::new(0.0, 1e-3).with_dataset("stresses", 0.0, 1e-2);
+        assert!(dat_compare(&reference, &actual, &loose).passed());
+    }
+
+    #[test]
+    fn unmatched_reference_tables_do_not_fail() {
+        let reference = parse_dat_tables(&dat(1.5e-3, 200.0));
+        let comparison = dat_compare(&reference, &reference[..1], &FrdTolerances::default());
+        assert!(comparison.passed(), "{comparison}");
+        assert_eq!(comparison.unmatched.len(), 2);
+
+        let comparison = dat_compare(&reference, &[], &FrdTolerances::default());
+        assert!(!comparison.passed());
+    }
+}
@@ -0,0 +1,240 @@
+//! Result fields of `.dat` tables on an FRD model.
+//!
+//! [`dat_result_blocks`] turns the tables read by [`parse_dat_tables`] into
+//! FRD result blocks, one per output time, so `*NODE PRINT` / `*EL PRINT`
+//! output can be visualized without an FRD file:
+//!
+//! - nodal tables become nodal datasets
+//! - element tables become element datasets with the mean over the
+//!   integration points of each element, plus a nodal dataset of the same
+//!   name averaging the element means at every node, unless the block
+//!   already has nodal values of that quantity
+//!
+//! Datasets are named like their FRD counterparts (`displacements` →
+//! `DISP`, `stresses` → `STRESS`, ...) and tensor columns are reordered
+//! from the `.dat` order `xy,xz,yz` to the FRD order `xy,yz,zx`. Tables of
+//! several sets with the same quantity and time are merged.
+//!
+//! [`parse_dat_tables`]: crate::parse_dat_tables
+
+use std::collections::HashMap;
+
+use crate::dat_compare::DatTable;
+use crate::frd_reader::{FrdFile, ResultBlock, ResultDataset, ResultLocation};
+
+/// FRD dataset name of a `.dat` quantity
+fn frd_name(quantity: &str) -> String {
+    let name = match quantity {
+        "displacements" => "DISP",
+        "velocities" => "VELO",
+        "forces" => "FORC",
+        "stresses" => "STRESS",
+        "strains" => "TOSTRAIN",
+        "mechanical strains" => "MESTRAIN",
+        "temperatures" => "NDTEMP",
+        "equivalent plastic strain" => "PE",
+        other => return other.to_ascii_uppercase().replace(' ', "_"),
+    };
+    name.to_string()
+}
+
+/// Whether the last six columns are a symmetric tensor in `.dat` order
+fn is_tensor(columns: &str) -> bool {
+    let names: Vec<&str> = columns.split(',').map(str::trim).collect();
+    names.len() >= 6
+        && names[names.len() - 3..]
+            .iter()
+            .zip(["xy", "xz", "yz"])
+            .all(|(name, suffix)| name.ends_with(suffix))
+}
+
+/// Values of a row in FRD component order
+fn frd_values(values: &[f64], tensor: bool) -> Vec<f64> {
+    let mut values = values.to_vec();
+    if tensor && values.len() == 6 {
+        values.swap(4, 5);
+    }
+    values
+}
+
+/// Merge `values` into the dataset `name` at `location`, creating it
+fn merge(
+    block: &mut ResultBlock,
+    name: &str,
+    location: ResultLocation,
+    values: HashMap<i32, Vec<f64>>,
+) {
+    match block
+        .datasets
+        .iter_mut()
+        .find(|d| d.name == name && d.location == location)
+    {
+        Some(dataset) => dataset.values.extend(values),
+        None => block.datasets.push(match location {
+            ResultLocation::Nodal => ResultDataset::nodal(name, values),
+            ResultLocation::Element => ResultDataset::element(name, values),
+        }),
+    }
+}
+
+/// Average of the element values at every node of `model`
+fn nodal_average(values: &HashMap<i32, Vec<f64>>, model: &FrdFile) -> HashMap<i32, Vec<f64>> {
+    let mut sums: HashMap<i32, (Vec<f64>, usize)> = HashMap::new();
+    for (element, value) in values {
+        let Some(element) = model.elements.get(element) else {
+            continue;
+        };
+        for node in &element.nodes {
+            let (sum, count) = sums
+                .entry(*node)
+                .or_insert_with(|| (vec![0.0; value.len()], 0));
+            for (s, v) in sum.iter_mut().zip(value) {
+                *s += v;
+            }
+            *count += 1;
+        }
+    }
+    sums.into_iter()
+        .map(|(node, (sum, count))| (node, sum.iter().map(|s| s / count as f64).collect()))
+        .collect()
+}
+
+/// Result blocks of the `.dat` tables on the nodes and elements of `model`
+///
+/// Blocks are in order of first appearance of their time and all belong to
+/// step 1. Rows without a node or element ID, such as total forces, are
+/// skipped.
+pub fn dat_result_blocks(tables: &[DatTable], model: &FrdFile) -> Vec<ResultBlock> {
+    let mut blocks: Vec<ResultBlock> = Vec::new();
+    for table in tables {
+        let tensor = is_tensor(&table.columns);
+        let per_point = table.columns.starts_with("elem");
+        let mut values: HashMap<i32, Vec<f64>> = HashMap::new();
+        let mut points: HashMap<i32, usize> = HashMap::new();
+        for row in table.rows.iter().filter(|row| row.id != 0) {
+            let row_values = frd_values(&row.values, tensor);
+            let sum = values
+                .entry(row.id)
+                .or_insert_with(|| vec![0.0; row_values.len()]);
+            for (s, v) in sum.iter_mut().zip(&row_values) {
+                *s += v;
+            }
+            *points.entry(row.id).or_insert(0) += 1;
+        }
+        if values.is_empty() {
+            continue;
+        }
+        for (id, value) in values.iter_mut() {
+            let count = points[id] as f64;
+            value.iter_mut().for_each(|v| *v /= count);
+        }
+
+        let index = match blocks.iter().position(|b| b.time == table.time) {
+            Some(index) => index,
+            None => {
+                blocks.push(ResultBlock {
+                    step: 1,
+                    time: table.time,
+                    mode: None,
+                    datasets: Vec::new(),
+                });
+                blocks.len() - 1
+            }
+        };
+        let location = if per_point {
+            ResultLocation::Element
+        } else {
+            ResultLocation::Nodal
+        };
+        merge(
+            &mut blocks[index],
+            &frd_name(&table.quantity),
+            location,
+            values,
+        );
+    }
+
+    for block in &mut blocks {
+        let averaged: Vec<(String, HashMap<i32, Vec<f64>>)> = block
+            .datasets
+            .iter()
+            .filter(|d| d.location == ResultLocation::Element)
+            .filter(|d| {
+                !block
+                    .datasets
+                    .iter()
+                    .any(|n| n.location == ResultLocation::Nodal && n.name == d.name)
+            })
+            .map(|d| (d.name.clone(), nodal_average(&d.values, model)))
+            .collect();
+        for (name, values) in averaged {
+            merge(block, &name, ResultLocation::Nodal, values);
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dat_compare::parse_dat_tables;
+    use crate::frd_reader::FrdElement;
+
+    fn two_bars() -> FrdFile {
+        let mut model = FrdFile::new();
+        for id in 1..=3 {
+            model.nodes.insert(id, [id as f64, 0.0, 0.0]);
+        }
+        for (id, nodes) in [(1, vec![1, 2]), (2, vec![2, 3])] {
+            model.elements.insert(
+                id,
+                FrdElement {
+                    id,
+                    element_type: 11,
+                    nodes,
+                },
+            );
+        }
+        model
+    }
+
+    const DAT: &str = "
+ displacements (vx,vy,vz) for set NALL and time  0.1000000E+01
+
+         1  0.000000E+00  0.000000E+00  0.000000E+00
+         3  2.000000E-03  0.000000E+00  0.000000E+00
+
+ stresses (elem, integ.pnt.,sxx,syy,szz,sxy,sxz,syz) for set EALL and time  0.1000000E+01
+
+         1   1  1.000000E+02  0.000000E+00  0.000000E+00  0.000000E+00  5.000000E+00  7.000000E+00
+         1   2  3.000000E+02  0.000000E+00  0.000000E+00  0.000000E+00  5.000000E+00  7.000000E+00
+         2   1  4.000000E+02  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00
+";
+
+    #[test]
+    fn builds_nodal_and_element_fields() {
+        let blocks = dat_result_blocks(&parse_dat_tables(DAT), &two_bars());
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].time, 1.0);
+        let dataset = |name: &str, location| {
+            blocks[0]
+                .datasets
+                .iter()
+                .find(|d| d.name == name && d.location == location)
+                .unwrap()
+        };
+
+        let disp = dataset("DISP", ResultLocation::Nodal);
+        assert_eq!(disp.comp_names, vec!["D1", "D2", "D3"]);
+        assert_eq!(disp.values[&3], vec![2e-3, 0.0, 0.0]);
+
+        // Mean over the integration points, shear in FRD order
+        let stress = dataset("STRESS", ResultLocation::Element);
+        assert_eq!(stress.values[&1], vec![200.0, 0.0, 0.0, 0.0, 7.0, 5.0]);
+
+        let nodal = dataset("STRESS", ResultLocation::Nodal);
+        assert_eq!(nodal.values[&1][0], 200.0);
+        assert_eq!(nodal.values[&2][0], 300.0);
+        assert_eq!(nodal.values[&3][0], 400.0);
+    }
+}
@@ -0,0 +1,248 @@
+//! ccx-compatible `.dat` result tables.
+//!
+//! Emits the `*NODE PRINT` / `*EL PRINT` sections of a CalculiX `.dat` file
+//! with the same headers and column layout as ccx, so `.dat.ref` comparison
+//! tooling can diff Rust-solver output numerically:
+//!
+//! - node rows: `(1X,I10,1P,3(1X,E13.6))`
+//! - integration point rows: `(1X,I10,1X,I3,1P,6(1X,E13.6))`
+//! - section headers end with `and time` followed by an `E14.7` time value
+
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::fortran::{format_e, format_e_1p};
+
+/// One output increment of a `.dat` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatStep {
+    pub step: i32,
+    pub increment: i32,
+    /// Total time of the increment
+    pub time: f64,
+    pub sections: Vec<DatSection>,
+}
+
+/// A printed result table.
+///
+/// Tensor components use the `.dat` ordering `xx, yy, zz, xy, xz, yz`
+/// (note: FRD files use `xy, yz, zx`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DatSection {
+    /// `U`: nodal displacements
+    Displacements {
+        set: String,
+        values: Vec<(i32, [f64; 3])>,
+    },
+    /// `RF`: nodal reaction/external forces
+    Forces {
+        set: String,
+        values: Vec<(i32, [f64; 3])>,
+    },
+    /// `TOTALS=ONLY` style summed forces over a node set
+    TotalForce { set: String, total: [f64; 3] },
+    /// Nodal (extrapolated and averaged) stresses
+    NodalStresses {
+        set: String,
+        values: Vec<(i32, [f64; 6])>,
+    },
+    /// `S`: stresses per element integration point
+    Stresses {
+        set: String,
+        values: Vec<(i32, i32, [f64; 6])>,
+    },
+    /// `E`: strains per element integration point
+    Strains {
+        set: String,
+        values: Vec<(i32, i32, [f64; 6])>,
+    },
+    /// `ME`: mechanical (total minus thermal) strains per integration point
+    MechanicalStrains {
+        set: String,
+        values: Vec<(i32, i32, [f64; 6])>,
+    },
+    /// `SF`: beam section forces `n, vy, vz, mt, my, mz` in the local beam
+    /// axes, per element end (point 1 and 2)
+    SectionForces {
+        set: String,
+        values: Vec<(i32, i32, [f64; 6])>,
+    },
+}
+
+impl DatSection {
+    /// Sum nodal forces into a total-force section for `set`.
+    pub fn total_force(set: impl Into<String>, forces: &[(i32, [f64; 3])]) -> Self {
+        let mut total = [0.0; 3];
+        for (_, f) in forces {
+            for (t, v) in total.iter_mut().zip(f) {
+                *t += v;
+            }
+        }
+        DatSection::TotalForce {
+            set: set.into(),
+            total,
+        }
+    }
+
+    fn header(&self) -> (&'static str, &str) {
+        match self {
+            DatSection::Displacements { set, .. } => ("displacements (vx,vy,vz)", set),
+            DatSection::Forces { set, .. } => ("forces (fx,fy,fz)", set),
+            DatSection::TotalForce { set, .. } => ("total force (fx,fy,fz)", set),
+            DatSection::NodalStresses { set, .. } => ("stresses (sxx,syy,szz,sxy,sxz,syz)", set),
+            DatSection::Stresses { set, .. } => {
+                ("stresses (elem, integ.pnt.,sxx,syy,szz,sxy,sxz,syz)", set)
+            }
+            DatSection::Strains { set, .. } => {
+                ("strains (elem, integ.pnt.,exx,eyy,ezz,exy,exz,eyz)", set)
+            }
+            DatSection::MechanicalStrains { set, .. } => (
+                "mechanical strains (elem, integ.pnt.,exx,eyy,ezz,exy,exz,eyz)",
+                set,
+            ),
+            DatSection::SectionForces { set, .. } => {
+                ("section forces (elem, integ.pnt.,n,vy,vz,mt,my,mz)", set)
+            }
+        }
+    }
+}
+
+/// Write `.dat` result tables for all increments to `path`.
+pub fn write_dat_results(path: impl AsRef<Path>, steps: &[DatStep]) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    write_dat_results_to(&mut out, steps)?;
+    out.flush()
+}
+
+/// Write `.dat` result tables for all increments to any writer.
+pub fn write_dat_results_to<W: Write>(out: &mut W, steps: &[DatStep]) -> io::Result<()> {
+    for step in steps {
+        writeln!(out)?;
+        writeln!(out, "{:24}S T E P {:5}", "", step.step)?;
+        writeln!(out)?;
+        writeln!(out)?;
+        writeln!(out, "{:32}INCREMENT {:5}", "", step.increment)?;
+        writeln!(out)?;
+
+        for section in &step.sections {
+            let (title, set) = section.header();
+            writeln!(out)?;
+            writeln!(
+                out,
+                " {title} for set {set} and time {}",
+                format_e(step.time, 7, 14)
+            )?;
+            writeln!(out)?;
+            write_rows(out, section)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_rows<W: Write>(out: &mut W, section: &DatSection) -> io::Result<()> {
+    match section {
+        DatSection::Displacements { values, .. } | DatSection::Forces { values, .. } => {
+            for (node, v) in values {
+                writeln!(out, " {node:10}{}", row(v))?;
+            }
+        }
+        DatSection::NodalStresses { values, .. } => {
+            for (node, v) in values {
+                writeln!(out, " {node:10}{}", row(v))?;
+            }
+        }
+        DatSection::TotalForce { total, .. } => {
+            writeln!(out, "{:6}{}", "", row(total))?;
+        }
+        DatSection::Stresses { values, .. }
+        | DatSection::Strains { values, .. }
+        | DatSection::MechanicalStrains { values, .. }
+        | DatSection::SectionForces { values, .. } => {
+            for (element, point, v) in values {
+                writeln!(out, " {element:10} {point:3}{}", row(v))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn row(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(|v| format!(" {}", format_e_1p(*v, 6, 13)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_ccx_column_layout() {
+        let forces = vec![(1, [-5.0e2, 0.0, 1.0]), (2, [-5.0e2, 0.0, -1.0])];
+        let steps = vec![DatStep {
+            step: 1,
+            increment: 1,
+            time: 1.0,
+            sections: vec![
+                DatSection::Displacements {
+                    set: "NALL".to_string(),
+                    values: vec![(12, [1.5e-3, -2.0e-4, 0.0])],
+                },
+                DatSection::Forces {
+                    set: "FIX".to_string(),
+                    values: forces.clone(),
+                },
+                DatSection::total_force("FIX", &forces),
+                DatSection::Stresses {
+                    set: "EALL".to_string(),
+                    values: vec![(3, 1, [100.0, -50.0, 0.0, 1.0, 2.0, 3.0])],
+                },
+            ],
+        }];
+
+        let mut buffer = Vec::new();
+        write_dat_results_to(&mut buffer, &steps).expect("write dat");
+        let text = String::from_utf8(buffer).expect("ascii output");
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(lines.contains(&"                        S T E P     1"));
+        assert!(lines.contains(&" displacements (vx,vy,vz) for set NALL and time  0.1000000E+01"));
+        assert!(lines.contains(&"         12  1.500000E-03 -2.000000E-04  0.000000E+00"));
+        assert!(lines.contains(&"       -1.000000E+03  0.000000E+00  0.000000E+00"));
+        assert!(lines.contains(
+            &"          3   1  1.000000E+02 -5.000000E+01  0.000000E+00  1.000000E+00  2.000000E+00  3.000000E+00"
+        ));
+    }
+
+    #[test]
+    fn stress_section_is_readable_by_solver_style_parsers() {
+        let steps = vec![DatStep {
+            step: 1,
+            increment: 2,
+            time: 0.5,
+            sections: vec![DatSection::Strains {
+                set: "EALL".to_string(),
+                values: vec![(1, 8, [1.0e-3; 6])],
+            }],
+        }];
+        let mut buffer = Vec::new();
+        write_dat_results_to(&mut buffer, &steps).expect("write dat");
+        let text = String::from_utf8(buffer).expect("ascii output");
+        let header = text
+            .lines()
+            .find(|l| l.contains("strains"))
+            .expect("strain header");
+        assert!(header.contains("elem") && header.contains("integ"));
+        assert!(header.ends_with("and time  0.5000000E+00"));
+        let row = text.lines().last().expect("row");
+        assert_eq!(row.split_whitespace().count(), 8);
+    }
+}
@@ -0,0 +1,59 @@
+//! Fortran-style number formatting shared by the ccx-compatible writers.
+
+/// `1P,Ew.d` edit descriptor: one digit before the point, e.g. `-1.234567E-03`.
+///
+/// Magnitudes below 1e-99 are flushed to zero so the exponent always fits
+/// two digits and the field keeps its width.
+pub(crate) fn format_e_1p(value: f64, decimals: usize, width: usize) -> String {
+    let value = flush_tiny(value);
+    let formatted = format!("{:.*E}", decimals, value);
+    let (mantissa, exponent) = formatted.split_once('E').unwrap_or((&formatted, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    format!(
+        "{:>width$}",
+        format!("{mantissa}{}", exponent_suffix(exponent))
+    )
+}
+
+/// Plain `Ew.d` edit descriptor: mantissa in [0.1, 1), e.g. `0.1000000E+01`.
+pub(crate) fn format_e(value: f64, decimals: usize, width: usize) -> String {
+    let value = flush_tiny(value);
+    if value == 0.0 {
+        let zero = format!("0.{}{}", "0".repeat(decimals), exponent_suffix(0));
+        return format!("{zero:>width$}");
+    }
+    // Round to `decimals` significant digits first, then shift the point.
+    let formatted = format!("{:.*E}", decimals.saturating_sub(1), value);
+    let (mantissa, exponent) = formatted.split_once('E').unwrap_or((&formatted, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let negative = mantissa.starts_with('-');
+    let digits: String = mantissa.chars().filter(|c| c.is_ascii_digit()).collect();
+    let sign = if negative { "-" } else { "" };
+    format!(
+        "{:>width$}",
+        format!("{sign}0.{digits}{}", exponent_suffix(exponent + 1))
+    )
+}
+
+fn flush_tiny(value: f64) -> f64 {
+    if value.abs() < 1.0e-99 { 0.0 } else { value }
+}
+
+fn exponent_suffix(exponent: i32) -> String {
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("E{sign}{:02}", exponent.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_scaled_and_unscaled_exponents() {
+        assert_eq!(format_e_1p(-1.234567e-3, 6, 13), "-1.234567E-03");
+        assert_eq!(format_e_1p(0.0, 6, 13), " 0.000000E+00");
+        assert_eq!(format_e(1.0, 7, 14), " 0.1000000E+01");
+        assert_eq!(format_e(-0.0123, 7, 14), "-0.1230000E-01");
+        assert_eq!(format_e(0.0, 7, 14), " 0.0000000E+00");
+    }
+}
@@ -0,0 +1,322 @@
+//! Numerical comparison of two FRD result files.
+//!
+//! Result blocks are paired in file order and datasets by name within each
+//! block. For every dataset the largest absolute difference and the RMS
+//! difference over all shared entities and components are reported, and a
+//! dataset passes when
+//!
+//! ```text
+//! max |a - b| <= absolute + relative * max |a|
+//! ```
+//!
+//! with `a` taken as the reference. Entities present in only one file, and
+//! blocks or datasets without a counterpart, fail the comparison.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::frd_reader::{FrdFile, ResultDataset};
+
+/// Pass/fail tolerances, optionally overridden per dataset name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrdTolerances {
+    pub absolute: f64,
+    pub relative: f64,
+    /// Dataset name (upper case) → (absolute, relative)
+    pub datasets: HashMap<String, (f64, f64)>,
+}
+
+impl Default for FrdTolerances {
+    fn default() -> Self {
+        Self {
+            absolute: 1e-12,
+            relative: 1e-6,
+            datasets: HashMap::new(),
+        }
+    }
+}
+
+impl FrdTolerances {
+    pub fn new(absolute: f64, relative: f64) -> Self {
+        Self {
+            absolute,
+            relative,
+            datasets: HashMap::new(),
+        }
+    }
+
+    /// Override the tolerances of one dataset, e.g. looser for `STRESS`
+    pub fn with_dataset(mut self, name: &str, absolute: f64, relative: f64) -> Self {
+        self.datasets
+            .insert(name.to_ascii_uppercase(), (absolute, relative));
+        self
+    }
+
+    pub(crate) fn for_dataset(&self, name: &str) -> (f64, f64) {
+        self.datasets
+            .get(&name.to_ascii_uppercase())
+            .copied()
+            .unwrap_or((self.absolute, self.relative))
+    }
+}
+
+/// Differences of one dataset in one result block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetComparison {
+    /// Index of the result block in file order
+    pub block: usize,
+    pub step: i32,
+    pub time: f64,
+    pub name: String,
+    /// Entities (nodes or elements) present in both files
+    pub compared: usize,
+    /// Entities present in only one of the files
+    pub missing: usize,
+    pub max_abs: f64,
+    pub rms: f64,
+    /// Largest magnitude in the reference file
+    pub max_reference: f64,
+    /// Entity id and component of the largest difference
+    pub worst: Option<(i32, usize)>,
+    /// Allowed maximum difference
+    pub tolerance: f64,
+    pub passed: bool,
+}
+
+/// Result of [`frd_compare`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FrdComparison {
+    pub datasets: Vec<DatasetComparison>,
+    /// Mismatches that prevent a dataset-by-dataset comparison
+    pub errors: Vec<String>,
+}
+
+impl FrdComparison {
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty() && self.datasets.iter().all(|d| d.passed)
+    }
+
+    /// Datasets exceeding their tolerance
+    pub fn failures(&self) -> impl Iterator<Item = &DatasetComparison> {
+        self.datasets.iter().filter(|d| !d.passed)
+    }
+}
+
+impl fmt::Display for FrdComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for error in &self.errors {
+            writeln!(f, "ERROR {error}")?;
+        }
+        for d in &self.datasets {
+            writeln!(
+                f,
+                "{} step {} time {:.6e} {:<8} max {:.3e} rms {:.3e} tol {:.3e} ({} compared, {} missing)",
+                if d.passed { "PASS " } else { "FAIL " },
+                d.step,
+                d.time,
+                d.name,
+                d.max_abs,
+                d.rms,
+                d.tolerance,
+                d.compared,
+                d.missing
+            )?;
+        }
+        write!(f, "{}", if self.passed() { "PASSED" } else { "FAILED" })
+    }
+}
+
+/// Compare the results of `b` against the reference `a`.
+pub fn frd_compare(a: &FrdFile, b: &FrdFile, tolerances: &FrdTolerances) -> FrdComparison {
+    let mut comparison = FrdComparison::default();
+    if a.nodes.len() != b.nodes.len() {
+        comparison.errors.push(format!(
+            "node count differs: {} vs {}",
+            a.nodes.len(),
+            b.nodes.len()
+        ));
+    }
+    if a.elements.len() != b.elements.len() {
+        comparison.errors.push(format!(
+            "element count differs: {} vs {}",
+            a.elements.len(),
+            b.elements.len()
+        ));
+    }
+    if a.result_blocks.len() != b.result_blocks.len() {
+        comparison.errors.push(format!(
+            "result block count differs: {} vs {}",
+            a.result_blocks.len(),
+            b.result_blocks.len()
+        ));
+    }
+
+    for (index, (block_a, block_b)) in a.result_blocks.iter().zip(&b.result_blocks).enumerate() {
+        if block_a.step != block_b.step {
+            comparison.errors.push(format!(
+                "block {index}: step {} vs {}",
+                block_a.step, block_b.step
+            ));
+        }
+        for dataset_a in &block_a.datasets {
+            let (absolute, relative) = tolerances.for_dataset(&dataset_a.name);
+            match block_b.datasets.iter().find(|d| d.name == dataset_a.name) {
+                Some(dataset_b) => {
+                    let mut result = compare_dataset(dataset_a, dataset_b, absolute, relative);
+                    result.block = index;
+                    result.step = block_a.step;
+                    result.time = block_a.time;
+                    comparison.datasets.push(result);
+                }
+                None => comparison.errors.push(format!(
+                    "block {index}: dataset {} missing in second file",
+                    dataset_a.name
+                )),
+            }
+        }
+        for dataset_b in &block_b.datasets {
+            if !block_a.datasets.iter().any(|d| d.name == dataset_b.name) {
+                comparison.errors.push(format!(
+                    "block {index}: dataset {} missing in first file",
+                    dataset_b.name
+                ));
+            }
+        }
+    }
+    comparison
+}
+
+fn compare_dataset(
+    a: &ResultDataset,
+    b: &ResultDataset,
+    absolute: f64,
+    relative: f64,
+) -> DatasetComparison {
+    let ids: BTreeSet<i32> = a.values.keys().chain(b.values.keys()).copied().collect();
+    let mut compared = 0;
+    let mut missing = 0;
+    let mut max_abs = 0.0f64;
+    let mut max_reference = 0.0f64;
+    let mut sum_sq = 0.0;
+    let mut count = 0usize;
+    let mut worst = None;
+
+    for id in ids {
+        let (Some(values_a), Some(values_b)) = (a.values.get(&id), b.values.get(&id)) else {
+            missing += 1;
+            continue;
+        };
+        compared += 1;
+        for comp in 0..values_a.len().max(values_b.len()) {
+            let va = values_a.get(comp).copied().unwrap_or(0.0);
+            let vb = values_b.get(comp).copied().unwrap_or(0.0);
+            let diff = (va - vb).abs();
+            max_reference = max_reference.max(va.abs());
+            sum_sq += diff * diff;
+            count += 1;
+            // NaN never compares greater, so force it to be reported
+            if diff > max_abs || (diff.is_nan() && !max_abs.is_nan()) {
+                max_abs = diff;
+                worst = Some((id, comp));
+            }
+        }
+    }
+
+    let rms = if count > 0 {
+        (sum_sq / count as f64).sqrt()
+    } else {
+        0.0
+    };
+    let tolerance = absolute + relative * max_reference;
+    DatasetComparison {
+        block: 0,
+        step: 0,
+        time: 0.0,
+        name: a.name.clone(),
+        compared,
+        missing,
+        max_abs,
+        rms,
+        max_reference,
+        worst,
+        tolerance,
+        passed: missing == 0 && max_abs <= tolerance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frd_reader::ResultBlock;
+
+    fn frd(disp: &[(i32, [f64; 3])], temp: Option<f64>) -> FrdFile {
+        let mut frd = FrdFile::new();
+        for &(id, _) in disp {
+            frd.nodes.insert(id, [id as f64, 0.0, 0.0]);
+        }
+        let mut datasets = vec![ResultDataset::nodal(
+            "DISP",
+            disp.iter().map(|(id, v)| (*id, v.to_vec())).collect(),
+        )];
+        if let Some(t) = temp {
+            datasets.push(ResultDataset::nodal(
+                "NDTEMP",
+                disp.iter().map(|(id, _)| (*id, vec![t])).collect(),
+            ));
+        }
+        frd.result_blocks.push(ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets,
+        });
+        frd
+    }
+
+    #[test]
+    fn reports_max_and_rms_differences() {
+        let a = frd(&[(1, [1.0, 0.0, 0.0]), (2, [2.0, 0.0, 0.0])], None);
+        let b = frd(&[(1, [1.0, 0.0, 0.0]), (2, [2.0, 0.0, 0.003])], None);
+
+        let result = frd_compare(&a, &b, &FrdTolerances::new(0.0, 1e-3));
+        assert_eq!(result.datasets.len(), 1);
+        let disp = &result.datasets[0];
+        assert_eq!(disp.compared, 2);
+        assert!((disp.max_abs - 0.003).abs() < 1e-15);
+        assert!((disp.rms - (0.003f64 * 0.003 / 6.0).sqrt()).abs() < 1e-15);
+        assert_eq!(disp.worst, Some((2, 2)));
+        assert!((disp.tolerance - 2e-3).abs() < 1e-15);
+        assert!(!result.passed());
+
+        let loose = FrdTolerances::new(0.0, 1e-3).with_dataset("disp", 0.01, 0.0);
+        assert!(frd_compare(&a, &b, &loose).passed());
+        assert!(frd_compare(&a, &a, &FrdTolerances::default()).passed());
+    }
+
+    #[test]
+    fn missing_entities_and_datasets_fail() {
+        let a = frd(&[(1, [1.0, 0.0, 0.0]), (2, [2.0, 0.0, 0.0])], Some(20.0));
+        let b = frd(&[(1, [1.0, 0.0, 0.0])], None);
+
+        let result = frd_compare(&a, &b, &FrdTolerances::default());
+        assert!(!result.passed());
+        assert_eq!(result.datasets[0].missing, 1);
+        assert!(result.errors.iter().any(|e| e.contains("node count")));
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|e| e.contains("NDTEMP missing in second file"))
+        );
+        assert!(result.to_string().ends_with("FAILED"));
+    }
+
+    #[test]
+    fn nan_differences_fail() {
+        let a = frd(&[(1, [1.0, 0.0, 0.0])], None);
+        let b = frd(&[(1, [f64::NAN, 0.0, 0.0])], None);
+        let result = frd_compare(&a, &b, &FrdTolerances::new(1.0, 1.0));
+        assert!(result.datasets[0].max_abs.is_nan());
+        assert!(!result.passed());
+    }
+}
@@ -0,0 +1,141 @@
+//! Conversion between solver meshes and FRD models.
+//!
+//! [`mesh_frd`] writes the nodes and elements of a [`Mesh`] as an FRD model
+//! without result blocks, e.g. to view a mesh in CGX or export it to VTK.
+//! [`frd_mesh`] builds a solver mesh back from the model part of an FRD
+//! file. Both sides hold CalculiX connectivity; the FRD writer and reader
+//! apply and undo the midside node order ccx uses for C3D20 and C3D15.
+
+use ccx_solver::{Element, ElementType, Mesh, Node};
+
+use crate::frd_reader::{FrdElement, FrdFile, FrdHeader};
+use crate::output::frd_element_type;
+
+/// Solver element type of an FRD element type code
+///
+/// Code 11 is shared by trusses and beams and maps to `B31`, code 12 to
+/// `B32`.
+fn solver_element_type(code: i32) -> Option<ElementType> {
+    let element_type = match code {
+        1 => ElementType::C3D8,
+        2 => ElementType::C3D6,
+        3 => ElementType::C3D4,
+        4 => ElementType::C3D20,
+        5 => ElementType::C3D15,
+        6 => ElementType::C3D10,
+        7 => ElementType::S3,
+        8 => ElementType::S6,
+        9 => ElementType::S4,
+        10 => ElementType::S8,
+        11 => ElementType::B31,
+        12 => ElementType::B32,
+        _ => return None,
+    };
+    Some(element_type)
+}
+
+/// FRD model of `mesh` without results
+///
+/// Elements without an FRD type code are left out.
+pub fn mesh_frd(mesh: &Mesh, job_name: &str) -> FrdFile {
+    let nodes = mesh
+        .nodes
+        .values()
+        .map(|node| (node.id, node.coords()))
+        .collect();
+    let elements = mesh
+        .elements
+        .values()
+        .filter_map(|element| {
+            let code = frd_element_type(&format!("{:?}", element.element_type))?;
+            Some((
+                element.id,
+                FrdElement {
+                    id: element.id,
+                    element_type: code,
+                    nodes: element.nodes.clone(),
+                },
+            ))
+        })
+        .collect();
+    FrdFile {
+        header: FrdHeader {
+            job_name: job_name.to_string(),
+            ..Default::default()
+        },
+        nodes,
+        elements,
+        result_blocks: Vec::new(),
+    }
+}
+
+/// Solver mesh of the model in `frd`
+///
+/// Returns the mesh and the IDs of the elements skipped because their type
+/// code has no solver element type.
+pub fn frd_mesh(frd: &FrdFile) -> (Mesh, Vec<i32>) {
+    let mut mesh = Mesh::new();
+    for (&id, &[x, y, z]) in &frd.nodes {
+        mesh.add_node(Node::new(id, x, y, z));
+    }
+    let mut ids: Vec<i32> = frd.elements.keys().copied().collect();
+    ids.sort_unstable();
+    let mut skipped = Vec::new();
+    for id in ids {
+        let element = &frd.elements[&id];
+        let added = solver_element_type(element.element_type).is_some_and(|element_type| {
+            mesh.add_element(Element::new(id, element_type, element.nodes.clone()))
+                .is_ok()
+        });
+        if !added {
+            skipped.push(id);
+        }
+    }
+    (mesh, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mesh_round_trips_through_frd() {
+        let mut mesh = Mesh::new();
+        for (id, [x, y, z]) in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            mesh.add_node(Node::new(id as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(7, ElementType::C3D4, vec![1, 2, 3, 4]))
+            .unwrap();
+        mesh.add_element(Element::new(8, ElementType::T3D2, vec![1, 2]))
+            .unwrap();
+
+        let frd = mesh_frd(&mesh, "tet");
+        assert_eq!(frd.header.job_name, "tet");
+        assert_eq!(frd.elements[&7].element_type, 3);
+        assert!(frd.result_blocks.is_empty());
+
+        let mut frd = frd;
+        frd.elements.insert(
+            9,
+            FrdElement {
+                id: 9,
+                element_type: 99,
+                nodes: vec![1],
+            },
+        );
+        let (back, skipped) = frd_mesh(&frd);
+        assert_eq!(skipped, vec![9]);
+        assert_eq!(back.nodes.len(), 4);
+        assert_eq!(back.elements[&7].element_type, ElementType::C3D4);
+        assert_eq!(back.elements[&7].nodes, vec![1, 2, 3, 4]);
+        assert_eq!(back.elements[&8].element_type, ElementType::B31);
+    }
+}
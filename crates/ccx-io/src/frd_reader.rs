@@ -62,7 +62,8 @@ pub struct FrdElement {
     pub id: i32,
     /// Element type code (FRD format)
     pub element_type: i32,
-    /// Node connectivity
+    /// Node connectivity in CalculiX order; the FRD midside order is
+    /// undone on reading and applied again on writing
     pub nodes: Vec<i32>,
 }
 
@@ -71,8 +72,10 @@ pub struct FrdElement {
 pub struct ResultBlock {
     /// Step number
     pub step: i32,
-    /// Increment/time value
+    /// Increment/time value; the eigenfrequency for modal results
     pub time: f64,
+    /// Eigenmode number for frequency results (analysis type 2)
+    pub mode: Option<i32>,
     /// Result datasets in this block
     pub datasets: Vec<ResultDataset>,
 }
@@ -92,6 +95,47 @@ pub struct ResultDataset {
     pub values: HashMap<i32, Vec<f64>>,
 }
 
+impl ResultDataset {
+    /// Nodal dataset with the standard CalculiX component names for `name`
+    pub fn nodal(name: &str, values: HashMap<i32, Vec<f64>>) -> Self {
+        let comp_names = standard_components(name);
+        let ncomps = if comp_names.is_empty() {
+            values.values().map(Vec::len).max().unwrap_or(0)
+        } else {
+            comp_names.len()
+        };
+        Self {
+            name: name.to_string(),
+            ncomps,
+            comp_names: comp_names.iter().map(|c| c.to_string()).collect(),
+            location: ResultLocation::Nodal,
+            values,
+        }
+    }
+
+    /// Element dataset with the standard CalculiX component names for `name`
+    pub fn element(name: &str, values: HashMap<i32, Vec<f64>>) -> Self {
+        Self {
+            location: ResultLocation::Element,
+            ..Self::nodal(name, values)
+        }
+    }
+}
+
+/// Component names CalculiX uses for its standard output variables
+pub fn standard_components(name: &str) -> &'static [&'static str] {
+    match name.to_ascii_uppercase().as_str() {
+        "DISP" | "VELO" | "ACC" => &["D1", "D2", "D3"],
+        "FORC" | "RF" => &["F1", "F2", "F3"],
+        "STRESS" | "ZZSTR" => &["SXX", "SYY", "SZZ", "SXY", "SYZ", "SZX"],
+        "TOSTRAIN" | "MESTRAIN" | "STRAIN" => &["EXX", "EYY", "EZZ", "EXY", "EYZ", "EZX"],
+        "NDTEMP" => &["T"],
+        "PE" => &["PE"],
+        "ERROR" => &["STR(%)"],
+        _ => &[],
+    }
+}
+
 /// Location of result data
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResultLocation {
@@ -102,6 +146,57 @@ pub enum ResultLocation {
 }
 
 impl FrdFile {
+    /// Create an empty FRD model
+    pub fn new() -> Self {
+        Self {
+            header: FrdHeader::default(),
+            nodes: HashMap::new(),
+            elements: HashMap::new(),
+            result_blocks: Vec::new(),
+        }
+    }
+
+    /// Distinct step numbers in file order
+    pub fn steps(&self) -> Vec<i32> {
+        let mut steps: Vec<i32> = Vec::new();
+        for block in &self.result_blocks {
+            if !steps.contains(&block.step) {
+                steps.push(block.step);
+            }
+        }
+        steps
+    }
+
+    /// Time (or frequency) values of the result blocks of `step`
+    pub fn step_times(&self, step: i32) -> Vec<f64> {
+        self.step_blocks(step).map(|block| block.time).collect()
+    }
+
+    /// Result blocks belonging to `step`
+    pub fn step_blocks(&self, step: i32) -> impl Iterator<Item = &ResultBlock> {
+        self.result_blocks
+            .iter()
+            .filter(move |block| block.step == step)
+    }
+
+    /// Copy of the model restricted to the results of `step`.
+    ///
+    /// Returns `None` if the file has no results for that step. Writers that
+    /// use the last block (legacy VTK, single VTU) then export the final
+    /// increment of the selected step.
+    pub fn select_step(&self, step: i32) -> Option<FrdFile> {
+        let blocks: Vec<ResultBlock> = self.step_blocks(step).cloned().collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        Some(FrdFile {
+            header: self.header.clone(),
+            nodes: self.nodes.clone(),
+            elements: self.elements.clone(),
+            result_blocks: blocks,
+        })
+    }
+
     /// Read FRD file from path
     pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
         let file = File::open(path)?;
@@ -111,13 +206,8 @@ impl FrdFile {
 
     /// Read FRD file from a buffered reader
     pub fn from_reader<R: BufRead>(mut reader: R) -> io::Result<Self> {
-        let mut frd = FrdFile {
-            header: FrdHeader::default(),
-            nodes: HashMap::new(),
-            elements: HashMap::new(),
-            result_blocks: Vec::new(),
-        };
-
+        let mut frd = Self::new();
+        let mut pending_step: Option<i32> = None;
         let mut line = String::new();
 
         // Read file line by line
@@ -128,40 +218,72 @@ impl FrdFile {
                 break; // EOF
             }
 
-            let trimmed = line.trim();
+            let record = line.trim_end_matches(['\r', '\n']);
+            let key = record.trim_start();
 
             // Skip empty lines
-            if trimmed.is_empty() {
+            if key.is_empty() {
                 continue;
             }
 
             // Parse based on record type marker
-            match &trimmed[0..std::cmp::min(5, trimmed.len())] {
-                // Header record (1PSTEP, 1U or similar)
-                s if s.starts_with('1') => {
-                    frd.header.info.push(trimmed.to_string());
+            if key.starts_with("100C") {
+                // Result dataset (one variable at one step)
+                let (step, time, mode, dataset) =
+                    Self::read_result_dataset(&mut reader, record)?;
+                let step = pending_step.take().unwrap_or(step);
+                match frd.result_blocks.last_mut() {
+                    Some(block) if block.step == step && block.time == time && block.mode == mode => {
+                        block.datasets.push(dataset);
+                    }
+                    _ => frd.result_blocks.push(ResultBlock {
+                        step,
+                        time,
+                        mode,
+                        datasets: vec![dataset],
+                    }),
                 }
+            } else if key.starts_with("2C") {
                 // Node coordinates block
-                "    2" | "   2C" => {
-                    Self::read_node_block(&mut reader, &mut frd.nodes)?;
+                let format = block_format(record);
+                if format >= FORMAT_BINARY {
+                    let count = fixed_int(record, 24, 12).unwrap_or(0).max(0) as usize;
+                    Self::read_node_block_binary(&mut reader, &mut frd.nodes, count, format)?;
+                } else {
+                    Self::read_node_block(&mut reader, &mut frd.nodes, format)?;
                 }
+            } else if key.starts_with("3C") {
                 // Element block
-                "    3" | "   3C" => {
-                    Self::read_element_block(&mut reader, &mut frd.elements)?;
-                }
-                // Result block (100C for nodal, 100CL for element)
-                "  100" => {
-                    let result_block = Self::read_result_block(&mut reader, trimmed)?;
-                    frd.result_blocks.push(result_block);
+                let format = block_format(record);
+                if format >= FORMAT_BINARY {
+                    let count = fixed_int(record, 24, 12).unwrap_or(0).max(0) as usize;
+                    Self::read_element_block_binary(&mut reader, &mut frd.elements, count)?;
+                } else {
+                    Self::read_element_block(&mut reader, &mut frd.elements, format)?;
                 }
-                // End markers (-3, 9999)
-                "   -3" | " 9999" => {
-                    // Block end, continue
+            } else if let Some(text) = key.strip_prefix("1C") {
+                // Model header record carrying the job name
+                frd.header.job_name = text.trim().to_string();
+                frd.header.info.push(key.to_string());
+            } else if let Some(text) = key.strip_prefix("1P") {
+                // Parameter record; 1PSTEP announces the step of the next dataset
+                // ccx writes: output counter, increment, step number
+                if let Some(values) = text.strip_prefix("STEP") {
+                    let fields: Vec<&str> = values.split_whitespace().collect();
+                    pending_step = fields
+                        .get(2)
+                        .or(fields.first())
+                        .and_then(|v| v.parse().ok());
                 }
-                _ => {
-                    // Unknown or comment line, skip
+                frd.header.info.push(key.to_string());
+            } else if key.starts_with('1') {
+                // Other header records (1U user info)
+                if let Some(version) = key.strip_prefix("1UVERSION") {
+                    frd.header.version = version.trim().to_string();
                 }
+                frd.header.info.push(key.to_string());
             }
+            // End markers (-3, 9999) and unknown lines are skipped
         }
 
         Ok(frd)
@@ -171,7 +293,9 @@ impl FrdFile {
     fn read_node_block<R: BufRead>(
         reader: &mut R,
         nodes: &mut HashMap<i32, [f64; 3]>,
+        format: u8,
     ) -> io::Result<()> {
+        let id_width = id_width(format);
         let mut line = String::new();
 
         loop {
@@ -181,47 +305,96 @@ impl FrdFile {
                 break;
             }
 
-            let trimmed = line.trim();
+            let record = line.trim_end_matches(['\r', '\n']);
 
             // End of block
-            if trimmed == "-3" || trimmed.is_empty() {
+            if is_block_end(record) {
                 break;
             }
 
-            // Node line format: -1<node_id:10><x:12><y:12><z:12>
-            if !trimmed.starts_with("-1") {
+            // Node line format: (1X,'-1',I10,3E12.5)
+            if !record.trim_start().starts_with("-1") {
                 continue;
             }
 
-            // Parse fixed-width fields (FRD format specification)
-            if line.len() < 2 + 10 + 12 * 3 {
-                continue; // Line too short
+            let Some(node_id) = fixed_int(record, 3, id_width) else {
+                continue;
+            };
+            let values = fixed_floats(record, 3 + id_width, 3);
+            if values.len() == 3 {
+                nodes.insert(node_id, [values[0], values[1], values[2]]);
             }
+        }
+
+        Ok(())
+    }
 
-            let node_id_str = &line[2..12].trim();
-            let x_str = &line[12..24].trim();
-            let y_str = &line[24..36].trim();
-            let z_str = &line[36..48].trim();
-
-            if let (Ok(node_id), Ok(x), Ok(y), Ok(z)) = (
-                node_id_str.parse::<i32>(),
-                x_str.parse::<f64>(),
-                y_str.parse::<f64>(),
-                z_str.parse::<f64>(),
-            ) {
-                nodes.insert(node_id, [x, y, z]);
+    /// Read a binary node block: per node an `i32` id and three coordinates
+    /// (`f64` for format 2 as written by ccx, `f32` for format 3)
+    fn read_node_block_binary<R: BufRead>(
+        reader: &mut R,
+        nodes: &mut HashMap<i32, [f64; 3]>,
+        count: usize,
+        format: u8,
+    ) -> io::Result<()> {
+        for _ in 0..count {
+            let id = read_i32(reader)?;
+            let mut coords = [0.0; 3];
+            for c in &mut coords {
+                *c = if format == FORMAT_BINARY {
+                    read_f64(reader)?
+                } else {
+                    read_f32(reader)? as f64
+                };
             }
+            nodes.insert(id, coords);
         }
+        skip_block_end(reader)
+    }
 
-        Ok(())
+    /// Read a binary element block: per element `i32` id, type, group and
+    /// material followed by the node ids implied by the type
+    fn read_element_block_binary<R: BufRead>(
+        reader: &mut R,
+        elements: &mut HashMap<i32, FrdElement>,
+        count: usize,
+    ) -> io::Result<()> {
+        for _ in 0..count {
+            let id = read_i32(reader)?;
+            let element_type = read_i32(reader)?;
+            let _group = read_i32(reader)?;
+            let _material = read_i32(reader)?;
+            let num_nodes = frd_element_node_count(element_type).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown FRD element type {element_type} in binary block"),
+                )
+            })?;
+            let nodes = (0..num_nodes)
+                .map(|_| read_i32(reader))
+                .collect::<io::Result<Vec<_>>>()?;
+            let nodes = calculix_node_order(element_type, &nodes);
+            elements.insert(
+                id,
+                FrdElement {
+                    id,
+                    element_type,
+                    nodes,
+                },
+            );
+        }
+        skip_block_end(reader)
     }
 
     /// Read element connectivity block (record type 3)
     fn read_element_block<R: BufRead>(
         reader: &mut R,
         elements: &mut HashMap<i32, FrdElement>,
+        format: u8,
     ) -> io::Result<()> {
+        let id_width = id_width(format);
         let mut line = String::new();
+        let mut current: Option<FrdElement> = None;
 
         loop {
             line.clear();
@@ -230,44 +403,74 @@ impl FrdFile {
                 break;
             }
 
-            let trimmed = line.trim();
+            let record = line.trim_end_matches(['\r', '\n']);
 
             // End of block
-            if trimmed == "-3" || trimmed.is_empty() {
+            if is_block_end(record) {
                 break;
             }
 
-            // Element header line: -1<elem_id><elem_type>
-            if trimmed.starts_with("-1") && line.len() >= 2 + 10 + 5 {
-                let elem_id_str = &line[2..12].trim();
-                let elem_type_str = &line[12..17].trim();
-
-                if let (Ok(elem_id), Ok(elem_type)) = (
-                    elem_id_str.parse::<i32>(),
-                    elem_type_str.parse::<i32>(),
+            let marker = record.trim_start();
+            if marker.starts_with("-1") {
+                // Element header line: (1X,'-1',I10,3I5) id, type, group, material
+                if let Some(element) = current.take() {
+                    insert_element(elements, element);
+                }
+                if let (Some(id), Some(element_type)) = (
+                    fixed_int(record, 3, id_width),
+                    fixed_int(record, 3 + id_width, 5),
                 ) {
-                    // Read element nodes
-                    let nodes = Self::read_element_nodes(reader)?;
-
-                    elements.insert(
-                        elem_id,
-                        FrdElement {
-                            id: elem_id,
-                            element_type: elem_type,
-                            nodes,
-                        },
-                    );
+                    current = Some(FrdElement {
+                        id,
+                        element_type,
+                        nodes: Vec::new(),
+                    });
+                }
+            } else if marker.starts_with("-2") {
+                // Node continuation line: (1X,'-2',10I10)
+                if let Some(element) = current.as_mut() {
+                    let mut start = 3;
+                    while let Some(node_id) = fixed_int(record, start, id_width) {
+                        element.nodes.push(node_id);
+                        start += id_width;
+                    }
                 }
             }
         }
 
+        if let Some(element) = current.take() {
+            insert_element(elements, element);
+        }
+
         Ok(())
     }
 
-    /// Read element node connectivity lines
-    fn read_element_nodes<R: BufRead>(reader: &mut R) -> io::Result<Vec<i32>> {
-        let mut nodes = Vec::new();
+    /// Read one result dataset (record type 100) and return it with its step and time
+    fn read_result_dataset<R: BufRead>(
+        reader: &mut R,
+        header_line: &str,
+    ) -> io::Result<(i32, f64, Option<i32>, ResultDataset)> {
+        // Header: (1X,' 100','C',6A1,E12.5,I12,20A1,I2,I5,10A1,I2)
+        let time = fixed_floats(header_line, 12, 1).first().copied().unwrap_or(0.0);
+        let step = fixed_int(header_line, 58, 5).unwrap_or(1);
+        // Analysis type 2 (frequency): numstp is the mode, time the frequency
+        let mode = (fixed_int(header_line, 56, 2) == Some(2)).then_some(step);
+        let format = fixed_int(header_line, 73, 2).unwrap_or(1) as u8;
+        let count = fixed_int(header_line, 24, 12).unwrap_or(0).max(0) as usize;
+        let id_width = id_width(format);
+        let mut declared_comps = 0usize;
+        let mut component_lines = 0usize;
+
+        let mut dataset = ResultDataset {
+            name: String::new(),
+            ncomps: 0,
+            comp_names: Vec::new(),
+            location: ResultLocation::Nodal,
+            values: HashMap::new(),
+        };
+
         let mut line = String::new();
+        let mut current: Option<i32> = None;
 
         loop {
             line.clear();
@@ -276,77 +479,198 @@ impl FrdFile {
                 break;
             }
 
-            let trimmed = line.trim();
+            let record = line.trim_end_matches(['\r', '\n']);
 
-            // Node continuation line: -2<node1><node2>...
-            if !trimmed.starts_with("-2") {
-                // Not a continuation line, put it back (conceptually)
+            // End of result block
+            if is_block_end(record) {
                 break;
             }
 
-            // Parse node IDs (10 chars each after -2)
-            let node_data = &line[2..];
-            for chunk in node_data.as_bytes().chunks(10) {
-                if let Ok(s) = std::str::from_utf8(chunk) {
-                    if let Ok(node_id) = s.trim().parse::<i32>() {
-                        nodes.push(node_id);
+            let marker = record.trim_start();
+            if marker.starts_with("-4") {
+                // (1X,' -4',2X,8A1,2I5) name, ncomps, irtype
+                dataset.name = fixed_str(record, 5, 8).to_string();
+                declared_comps = fixed_int(record, 13, 5).unwrap_or(0).max(0) as usize;
+                if fixed_int(record, 18, 5) == Some(2) {
+                    dataset.location = ResultLocation::Element;
+                }
+            } else if marker.starts_with("-5") {
+                // (1X,' -5',2X,8A1,5I5,8A1) name, menu, ictype, icind1, icind2, iexist
+                // Entries with iexist=1 (e.g. ALL) are computed, not stored.
+                if fixed_int(record, 33, 5) != Some(1) {
+                    dataset.comp_names.push(fixed_str(record, 5, 8).to_string());
+                    dataset.ncomps += 1;
+                }
+                component_lines += 1;
+                if format >= FORMAT_BINARY && component_lines == declared_comps {
+                    // Binary values follow the last component record directly:
+                    // per entity an i32 id and ncomps f32 values.
+                    for _ in 0..count {
+                        let id = read_i32(reader)?;
+                        let values = (0..dataset.ncomps)
+                            .map(|_| read_f32(reader).map(f64::from))
+                            .collect::<io::Result<Vec<_>>>()?;
+                        dataset.values.insert(id, values);
                     }
+                    skip_block_end(reader)?;
+                    break;
+                }
+            } else if marker.starts_with("-1") {
+                // Result value line: (1X,I2,I10,6E12.5)
+                current = fixed_int(record, 3, id_width);
+                if let Some(id) = current {
+                    let values = fixed_floats(record, 3 + id_width, 6);
+                    dataset.values.insert(id, values);
+                }
+            } else if marker.starts_with("-2") {
+                // Continuation of the previous entity's values
+                if let Some(values) = current.and_then(|id| dataset.values.get_mut(&id)) {
+                    values.extend(fixed_floats(record, 3 + id_width, 6));
                 }
             }
         }
 
-        Ok(nodes)
+        Ok((step, time, mode, dataset))
     }
+}
 
-    /// Read result data block (record type 100)
-    fn read_result_block<R: BufRead>(reader: &mut R, _header_line: &str) -> io::Result<ResultBlock> {
-        // Parse result block header
-        // Format: 100C<step><time><dataset_name><ncomps>...
+impl Default for FrdFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        let mut result_block = ResultBlock {
-            step: 1,
-            time: 0.0,
-            datasets: Vec::new(),
-        };
+/// Block format flag for binary records
+const FORMAT_BINARY: u8 = 2;
+
+/// Number of nodes stored per element for an FRD element type code
+pub fn frd_element_node_count(element_type: i32) -> Option<usize> {
+    let count = match element_type {
+        1 => 8,
+        2 => 6,
+        3 => 4,
+        4 => 20,
+        5 => 15,
+        6 => 10,
+        7 => 3,
+        8 => 6,
+        9 => 4,
+        10 => 8,
+        11 => 2,
+        12 => 3,
+        _ => return None,
+    };
+    Some(count)
+}
 
-        // TODO: Parse header line to extract step, time, dataset info
-        // This is a simplified implementation
+/// Midside node blocks that ccx (frd.c) swaps in the FRD connectivity
+///
+/// C3D20 (type 4) is written as nodes 1–12, 17–20, 13–16 and C3D15
+/// (type 5) as 1–9, 13–15, 10–12; all other types keep the CalculiX order.
+fn frd_swapped_blocks(element_type: i32) -> Option<(usize, usize, usize)> {
+    match element_type {
+        4 => Some((12, 16, 20)),
+        5 => Some((9, 12, 15)),
+        _ => None,
+    }
+}
 
-        let mut line = String::new();
-        let mut current_dataset: Option<ResultDataset> = None;
+/// FRD node order of an element given in CalculiX order
+pub fn frd_node_order(element_type: i32, nodes: &[i32]) -> Vec<i32> {
+    match frd_swapped_blocks(element_type) {
+        Some((a, b, end)) if nodes.len() == end => {
+            [&nodes[..a], &nodes[b..end], &nodes[a..b]].concat()
+        }
+        _ => nodes.to_vec(),
+    }
+}
 
-        loop {
-            line.clear();
-            let bytes_read = reader.read_line(&mut line)?;
-            if bytes_read == 0 {
-                break;
-            }
+/// CalculiX node order of an element read in FRD order
+///
+/// Inverse of [`frd_node_order`].
+pub fn calculix_node_order(element_type: i32, nodes: &[i32]) -> Vec<i32> {
+    match frd_swapped_blocks(element_type) {
+        Some((a, b, end)) if nodes.len() == end => {
+            let split = a + end - b;
+            [&nodes[..a], &nodes[split..end], &nodes[a..split]].concat()
+        }
+        _ => nodes.to_vec(),
+    }
+}
 
-            let trimmed = line.trim();
+fn insert_element(elements: &mut HashMap<i32, FrdElement>, mut element: FrdElement) {
+    element.nodes = calculix_node_order(element.element_type, &element.nodes);
+    elements.insert(element.id, element);
+}
 
-            // End of result block
-            if trimmed == "-3" || trimmed.starts_with("  100") {
-                if let Some(dataset) = current_dataset.take() {
-                    result_block.datasets.push(dataset);
-                }
+fn read_bytes<R: BufRead, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
 
-                if trimmed.starts_with("  100") {
-                    // Another dataset in same block, continue
-                    continue;
-                } else {
-                    break;
-                }
-            }
+fn read_i32<R: BufRead>(reader: &mut R) -> io::Result<i32> {
+    read_bytes(reader).map(i32::from_le_bytes)
+}
 
-            // Result value line: -1<node_id><value1><value2>...
-            if trimmed.starts_with("-1") {
-                // TODO: Parse result values
-                // This requires knowledge of the dataset format
-            }
-        }
+fn read_f32<R: BufRead>(reader: &mut R) -> io::Result<f32> {
+    read_bytes(reader).map(f32::from_le_bytes)
+}
+
+fn read_f64<R: BufRead>(reader: &mut R) -> io::Result<f64> {
+    read_bytes(reader).map(f64::from_le_bytes)
+}
 
-        Ok(result_block)
+/// Consume the ` -3` record that terminates a binary block
+fn skip_block_end<R: BufRead>(reader: &mut R) -> io::Result<()> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim().is_empty() {
+        // Binary data may be followed by a newline before the terminator
+        line.clear();
+        reader.read_line(&mut line)?;
     }
+    if line.trim() != "-3" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected -3 after binary block, found {:?}", line.trim()),
+        ));
+    }
+    Ok(())
+}
+
+/// ASCII format flag from a block header: 0 = short (I5 ids), 1 = long (I10 ids)
+fn block_format(header: &str) -> u8 {
+    header
+        .get(73..)
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+fn id_width(format: u8) -> usize {
+    if format == 0 { 5 } else { 10 }
+}
+
+fn is_block_end(record: &str) -> bool {
+    let trimmed = record.trim();
+    trimmed == "-3" || trimmed.is_empty()
+}
+
+/// Fixed-width text field, clipped to the line length
+fn fixed_str(line: &str, start: usize, width: usize) -> &str {
+    let end = (start + width).min(line.len());
+    line.get(start..end).unwrap_or("").trim()
+}
+
+fn fixed_int(line: &str, start: usize, width: usize) -> Option<i32> {
+    fixed_str(line, start, width).parse().ok()
+}
+
+/// Up to `max` consecutive E12.5 fields starting at `start`
+fn fixed_floats(line: &str, start: usize, max: usize) -> Vec<f64> {
+    (0..max)
+        .map_while(|i| fixed_str(line, start + 12 * i, 12).parse().ok())
+        .collect()
 }
 
 #[cfg(test)]
@@ -367,6 +691,94 @@ mod tests {
         assert_eq!(frd.result_blocks.len(), 0);
     }
 
+    #[test]
+    fn parses_fixed_width_ascii_blocks() {
+        let text = concat!(
+            "    1CJOB\n",
+            "    2C                             2                                     1\n",
+            " -1         1 0.00000E+00 0.00000E+00 0.00000E+00\n",
+            " -1        25 1.00000E+00-2.50000E-01 0.00000E+00\n",
+            " -3\n",
+            "    3C                             1                                     1\n",
+            " -1         1   11    0    1\n",
+            " -2         1        25\n",
+            " -3\n",
+            "    1PSTEP                 1           1           1\n",
+            "  100CL  101 1.00000E+00           2                     0    1           1\n",
+            " -4  DISP        4    1\n",
+            " -5  D1          1    2    1    0\n",
+            " -5  D2          1    2    2    0\n",
+            " -5  D3          1    2    3    0\n",
+            " -5  ALL         1    2    0    0    1ALL\n",
+            " -1         1 0.00000E+00 0.00000E+00 0.00000E+00\n",
+            " -1        25 1.00000E-03-2.00000E-03 0.00000E+00\n",
+            " -3\n",
+            " 9999\n",
+        );
+
+        let frd = FrdFile::from_reader(text.as_bytes()).expect("parse");
+        assert_eq!(frd.header.job_name, "JOB");
+        assert_eq!(frd.nodes.get(&25), Some(&[1.0, -0.25, 0.0]));
+        assert_eq!(frd.elements[&1].element_type, 11);
+        assert_eq!(frd.elements[&1].nodes, vec![1, 25]);
+
+        assert_eq!(frd.result_blocks.len(), 1);
+        let disp = &frd.result_blocks[0].datasets[0];
+        assert_eq!(disp.name, "DISP");
+        assert_eq!(disp.ncomps, 3);
+        assert_eq!(disp.comp_names, vec!["D1", "D2", "D3"]);
+        assert_eq!(disp.values[&25], vec![1.0e-3, -2.0e-3, 0.0]);
+    }
+
+    #[test]
+    fn parses_binary_node_element_and_result_blocks() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"    1CBIN\n");
+        bytes.extend_from_slice(
+            format!("    2C{:18}{:12}{:37}2\n", "", 2, "").as_bytes(),
+        );
+        for (id, coords) in [(1i32, [0.0f64, 0.0, 0.0]), (2, [1.5, -2.0, 0.25])] {
+            bytes.extend_from_slice(&id.to_le_bytes());
+            for c in coords {
+                bytes.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(b" -3\n");
+        bytes.extend_from_slice(
+            format!("    3C{:18}{:12}{:37}2\n", "", 1, "").as_bytes(),
+        );
+        for value in [7i32, 11, 0, 1, 1, 2] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(b" -3\n");
+        bytes.extend_from_slice(b"    1PSTEP                 1           1           3\n");
+        bytes.extend_from_slice(
+            format!(
+                "  100CL  101 5.00000E-01{:12}{:20} 0    1{:10} 2\n",
+                2, "", ""
+            )
+            .as_bytes(),
+        );
+        bytes.extend_from_slice(b" -4  NDTEMP      1    1\n");
+        bytes.extend_from_slice(b" -5  T           1    1    1    0\n");
+        for (id, temp) in [(1i32, 20.0f32), (2, 80.5)] {
+            bytes.extend_from_slice(&id.to_le_bytes());
+            bytes.extend_from_slice(&temp.to_le_bytes());
+        }
+        bytes.extend_from_slice(b" -3\n 9999\n");
+
+        let frd = FrdFile::from_reader(bytes.as_slice()).expect("parse binary frd");
+        assert_eq!(frd.nodes.get(&2), Some(&[1.5, -2.0, 0.25]));
+        assert_eq!(frd.elements[&7].element_type, 11);
+        assert_eq!(frd.elements[&7].nodes, vec![1, 2]);
+        assert_eq!(frd.result_blocks.len(), 1);
+        assert_eq!(frd.result_blocks[0].step, 3);
+        assert_eq!(frd.result_blocks[0].time, 0.5);
+        let temp = &frd.result_blocks[0].datasets[0];
+        assert_eq!(temp.name, "NDTEMP");
+        assert_eq!(temp.values[&2], vec![80.5]);
+    }
+
     #[test]
     fn test_node_parsing() {
         // Test basic node structure creation
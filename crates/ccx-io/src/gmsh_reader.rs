@@ -0,0 +1,529 @@
+//! Gmsh `.msh` reader (ASCII formats 2.2 and 4.1).
+//!
+//! Builds a solver [`Mesh`] directly from a Gmsh file:
+//! - elements of the highest dimension present become mesh elements
+//!   (lines → B31/B32, triangles/quads → S3/S6/S4/S8, volumes → C3D*)
+//! - every physical group becomes a node set with the nodes of its elements
+//! - physical groups of the mesh dimension also become element sets
+//!
+//! Set names are the uppercase physical names, or `PHYSICAL<dim>_<tag>` for
+//! unnamed groups. Node orderings are converted to the CalculiX convention.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ccx_solver::{Element, ElementSet, ElementType, Mesh, Node, NodeSet, Sets};
+
+/// Mesh and sets read from a Gmsh file.
+#[derive(Debug, Clone)]
+pub struct GmshMesh {
+    /// Value of the `$MeshFormat` version field (e.g. `2.2`, `4.1`)
+    pub version: String,
+    pub mesh: Mesh,
+    pub sets: Sets,
+}
+
+#[derive(Debug)]
+struct RawElement {
+    id: i32,
+    gmsh_type: i32,
+    physicals: Vec<i32>,
+    nodes: Vec<i32>,
+}
+
+#[derive(Default)]
+struct RawMesh {
+    version: String,
+    physical_names: HashMap<(usize, i32), String>,
+    /// Physical tags per `(dimension, entity tag)` (format 4 only)
+    entity_physicals: HashMap<(usize, i32), Vec<i32>>,
+    nodes: Vec<Node>,
+    elements: Vec<RawElement>,
+}
+
+impl GmshMesh {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let text = String::from_utf8(bytes)
+            .map_err(|_| invalid(0, "binary .msh files are not supported; export as ASCII"))?;
+        Self::parse_str(&text)
+    }
+
+    pub fn parse_str(text: &str) -> io::Result<Self> {
+        let raw = parse_sections(text)?;
+        build(raw)
+    }
+}
+
+/// Read a Gmsh `.msh` file into a solver mesh with physical-group sets.
+pub fn read_gmsh<P: AsRef<Path>>(path: P) -> io::Result<GmshMesh> {
+    GmshMesh::from_file(path)
+}
+
+/// Gmsh element type → (dimension, CalculiX type, Gmsh index per CalculiX node).
+fn gmsh_element(gmsh_type: i32) -> Option<(usize, Option<ElementType>, &'static [usize])> {
+    const LINE3: &[usize] = &[0, 2, 1];
+    const TET10: &[usize] = &[0, 1, 2, 3, 4, 5, 6, 7, 9, 8];
+    const HEX20: &[usize] = &[
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 11, 13, 9, 16, 18, 19, 17, 10, 12, 14, 15,
+    ];
+    const PRISM15: &[usize] = &[0, 1, 2, 3, 4, 5, 6, 9, 7, 12, 14, 13, 8, 10, 11];
+    const IDENTITY: &[usize] = &[0, 1, 2, 3, 4, 5, 6, 7];
+    let entry = match gmsh_type {
+        15 => (0, None, &IDENTITY[..1]),
+        1 => (1, Some(ElementType::B31), &IDENTITY[..2]),
+        8 => (1, Some(ElementType::B32), LINE3),
+        2 => (2, Some(ElementType::S3), &IDENTITY[..3]),
+        3 => (2, Some(ElementType::S4), &IDENTITY[..4]),
+        9 => (2, Some(ElementType::S6), &IDENTITY[..6]),
+        16 => (2, Some(ElementType::S8), &IDENTITY[..8]),
+        4 => (3, Some(ElementType::C3D4), &IDENTITY[..4]),
+        5 => (3, Some(ElementType::C3D8), IDENTITY),
+        6 => (3, Some(ElementType::C3D6), &IDENTITY[..6]),
+        11 => (3, Some(ElementType::C3D10), TET10),
+        17 => (3, Some(ElementType::C3D20), HEX20),
+        18 => (3, Some(ElementType::C3D15), PRISM15),
+        _ => return None,
+    };
+    Some(entry)
+}
+
+struct Lines<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    number: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        for (idx, line) in self.lines.by_ref() {
+            self.number = idx + 1;
+            let line = line.trim();
+            if !line.is_empty() {
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    fn expect(&mut self, what: &str) -> io::Result<&'a str> {
+        self.next()
+            .ok_or_else(|| invalid(self.number, &format!("unexpected end of file in {what}")))
+    }
+
+    fn numbers<T: std::str::FromStr>(&mut self, what: &str) -> io::Result<Vec<T>> {
+        let line = self.expect(what)?;
+        parse_numbers(line, self.number, what)
+    }
+
+    fn skip_to_end(&mut self, section: &str) -> io::Result<()> {
+        let end = format!("$End{section}");
+        while let Some(line) = self.next() {
+            if line == end {
+                return Ok(());
+            }
+        }
+        Err(invalid(self.number, &format!("missing {end}")))
+    }
+}
+
+fn parse_numbers<T: std::str::FromStr>(
+    line: &str,
+    number: usize,
+    what: &str,
+) -> io::Result<Vec<T>> {
+    line.split_whitespace()
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| invalid(number, &format!("invalid value '{token}' in {what}")))
+        })
+        .collect()
+}
+
+fn parse_sections(text: &str) -> io::Result<RawMesh> {
+    let mut lines = Lines {
+        lines: text.lines().enumerate(),
+        number: 0,
+    };
+    let mut raw = RawMesh::default();
+    while let Some(line) = lines.next() {
+        let Some(section) = line.strip_prefix('$') else {
+            continue;
+        };
+        match section {
+            "MeshFormat" => {
+                let header = lines.expect("$MeshFormat")?;
+                let fields: Vec<&str> = header.split_whitespace().collect();
+                if fields.len() < 3 {
+                    return Err(invalid(lines.number, "malformed $MeshFormat header"));
+                }
+                if fields[1] != "0" {
+                    return Err(invalid(
+                        lines.number,
+                        "binary .msh files are not supported; export as ASCII",
+                    ));
+                }
+                if !matches!(fields[0], "2" | "2.0" | "2.1" | "2.2" | "4.1") {
+                    return Err(invalid(
+                        lines.number,
+                        &format!(
+                            "unsupported .msh version {} (expected 2.2 or 4.1)",
+                            fields[0]
+                        ),
+                    ));
+                }
+                raw.version = fields[0].to_string();
+                lines.skip_to_end("MeshFormat")?;
+            }
+            "PhysicalNames" => parse_physical_names(&mut lines, &mut raw)?,
+            "Entities" => parse_entities(&mut lines, &mut raw)?,
+            "Nodes" if raw.is_v4() => parse_nodes_v4(&mut lines, &mut raw)?,
+            "Nodes" => parse_nodes_v2(&mut lines, &mut raw)?,
+            "Elements" if raw.is_v4() => parse_elements_v4(&mut lines, &mut raw)?,
+            "Elements" => parse_elements_v2(&mut lines, &mut raw)?,
+            other if !other.starts_with("End") => lines.skip_to_end(other)?,
+            _ => {}
+        }
+    }
+    if raw.version.is_empty() {
+        return Err(invalid(0, "missing $MeshFormat section"));
+    }
+    Ok(raw)
+}
+
+impl RawMesh {
+    fn is_v4(&self) -> bool {
+        self.version.starts_with('4')
+    }
+}
+
+fn parse_physical_names(lines: &mut Lines, raw: &mut RawMesh) -> io::Result<()> {
+    let count: Vec<usize> = lines.numbers("$PhysicalNames")?;
+    for _ in 0..count.first().copied().unwrap_or(0) {
+        let line = lines.expect("$PhysicalNames")?;
+        let mut parts = line.splitn(3, char::is_whitespace);
+        let dim = parts.next().and_then(|s| s.parse::<usize>().ok());
+        let tag = parts.next().and_then(|s| s.trim().parse::<i32>().ok());
+        let (Some(dim), Some(tag)) = (dim, tag) else {
+            return Err(invalid(lines.number, "malformed physical name"));
+        };
+        let name = parts.next().unwrap_or("").trim().trim_matches('"');
+        raw.physical_names.insert((dim, tag), name.to_string());
+    }
+    lines.skip_to_end("PhysicalNames")
+}
+
+fn parse_entities(lines: &mut Lines, raw: &mut RawMesh) -> io::Result<()> {
+    let counts: Vec<usize> = lines.numbers("$Entities")?;
+    if counts.len() < 4 {
+        return Err(invalid(lines.number, "malformed $Entities header"));
+    }
+    for (dim, &count) in counts.iter().take(4).enumerate() {
+        for _ in 0..count {
+            let values: Vec<f64> = lines.numbers("$Entities")?;
+            // Points: tag x y z; others: tag and a bounding box.
+            let physicals_at = if dim == 0 { 4 } else { 7 };
+            let Some(&num) = values.get(physicals_at) else {
+                return Err(invalid(lines.number, "truncated entity record"));
+            };
+            let start = physicals_at + 1;
+            let end = start + num as usize;
+            if values.len() < end {
+                return Err(invalid(lines.number, "truncated entity physical tags"));
+            }
+            let tags = values[start..end].iter().map(|v| *v as i32).collect();
+            raw.entity_physicals.insert((dim, values[0] as i32), tags);
+        }
+    }
+    lines.skip_to_end("Entities")
+}
+
+fn parse_nodes_v2(lines: &mut Lines, raw: &mut RawMesh) -> io::Result<()> {
+    let count: Vec<usize> = lines.numbers("$Nodes")?;
+    for _ in 0..count.first().copied().unwrap_or(0) {
+        let line = lines.expect("$Nodes")?;
+        let values: Vec<f64> = parse_numbers(line, lines.number, "$Nodes")?;
+        if values.len() < 4 {
+            return Err(invalid(lines.number, "node record needs id x y z"));
+        }
+        raw.nodes
+            .push(Node::new(values[0] as i32, values[1], values[2], values[3]));
+    }
+    lines.skip_to_end("Nodes")
+}
+
+fn parse_nodes_v4(lines: &mut Lines, raw: &mut RawMesh) -> io::Result<()> {
+    let header: Vec<usize> = lines.numbers("$Nodes")?;
+    let blocks = header.first().copied().unwrap_or(0);
+    for _ in 0..blocks {
+        let block: Vec<usize> = lines.numbers("$Nodes block")?;
+        if block.len() < 4 {
+            return Err(invalid(lines.number, "malformed node block header"));
+        }
+        let count = block[3];
+        let mut tags = Vec::with_capacity(count);
+        for _ in 0..count {
+            let tag: Vec<i32> = lines.numbers("$Nodes tags")?;
+            tags.push(tag[0]);
+        }
+        for tag in tags {
+            let coords: Vec<f64> = lines.numbers("$Nodes coordinates")?;
+            if coords.len() < 3 {
+                return Err(invalid(lines.number, "node coordinates need x y z"));
+            }
+            raw.nodes
+                .push(Node::new(tag, coords[0], coords[1], coords[2]));
+        }
+    }
+    lines.skip_to_end("Nodes")
+}
+
+fn parse_elements_v2(lines: &mut Lines, raw: &mut RawMesh) -> io::Result<()> {
+    let count: Vec<usize> = lines.numbers("$Elements")?;
+    for _ in 0..count.first().copied().unwrap_or(0) {
+        let values: Vec<i32> = lines.numbers("$Elements")?;
+        if values.len() < 3 || values.len() < 3 + values[2] as usize {
+            return Err(invalid(lines.number, "truncated element record"));
+        }
+        let num_tags = values[2] as usize;
+        // The first tag is the physical group (0 = none).
+        let physicals = values
+            .get(3)
+            .filter(|&&tag| num_tags > 0 && tag != 0)
+            .map(|&tag| vec![tag])
+            .unwrap_or_default();
+        raw.elements.push(RawElement {
+            id: values[0],
+            gmsh_type: values[1],
+            physicals,
+            nodes: values[3 + num_tags..].to_vec(),
+        });
+    }
+    lines.skip_to_end("Elements")
+}
+
+fn parse_elements_v4(lines: &mut Lines, raw: &mut RawMesh) -> io::Result<()> {
+    let header: Vec<usize> = lines.numbers("$Elements")?;
+    let blocks = header.first().copied().unwrap_or(0);
+    for _ in 0..blocks {
+        let block: Vec<i32> = lines.numbers("$Elements block")?;
+        if block.len() < 4 {
+            return Err(invalid(lines.number, "malformed element block header"));
+        }
+        let (dim, entity, gmsh_type, count) = (block[0] as usize, block[1], block[2], block[3]);
+        let physicals = raw
+            .entity_physicals
+            .get(&(dim, entity))
+            .cloned()
+            .unwrap_or_default();
+        for _ in 0..count {
+            let values: Vec<i32> = lines.numbers("$Elements")?;
+            if values.len() < 2 {
+                return Err(invalid(lines.number, "truncated element record"));
+            }
+            raw.elements.push(RawElement {
+                id: values[0],
+                gmsh_type,
+                physicals: physicals.clone(),
+                nodes: values[1..].to_vec(),
+            });
+        }
+    }
+    lines.skip_to_end("Elements")
+}
+
+fn build(raw: RawMesh) -> io::Result<GmshMesh> {
+    let mut mesh = Mesh::new();
+    for node in raw.nodes {
+        mesh.add_node(node);
+    }
+
+    let mut mesh_dim = 0;
+    for element in &raw.elements {
+        let (dim, _, _) = gmsh_element(element.gmsh_type).ok_or_else(|| {
+            invalid(
+                0,
+                &format!(
+                    "unsupported Gmsh element type {} (element {})",
+                    element.gmsh_type, element.id
+                ),
+            )
+        })?;
+        mesh_dim = mesh_dim.max(dim);
+    }
+
+    let mut node_groups: BTreeMap<(usize, i32), BTreeSet<i32>> = BTreeMap::new();
+    let mut element_groups: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for element in &raw.elements {
+        let (dim, element_type, order) =
+            gmsh_element(element.gmsh_type).expect("types checked above");
+        if element.nodes.len() != order.len() {
+            return Err(invalid(
+                0,
+                &format!(
+                    "element {} has {} nodes, expected {} for Gmsh type {}",
+                    element.id,
+                    element.nodes.len(),
+                    order.len(),
+                    element.gmsh_type
+                ),
+            ));
+        }
+        for &physical in &element.physicals {
+            node_groups
+                .entry((dim, physical))
+                .or_default()
+                .extend(&element.nodes);
+        }
+        if dim != mesh_dim {
+            continue;
+        }
+        if let Some(element_type) = element_type {
+            let nodes = order.iter().map(|&idx| element.nodes[idx]).collect();
+            mesh.add_element(Element::new(element.id, element_type, nodes))
+                .map_err(|err| invalid(0, &err))?;
+            for &physical in &element.physicals {
+                element_groups.entry(physical).or_default().push(element.id);
+            }
+        }
+    }
+    mesh.calculate_dofs();
+
+    let set_name = |dim: usize, tag: i32| match raw.physical_names.get(&(dim, tag)) {
+        Some(name) if !name.is_empty() => name.to_uppercase(),
+        _ => format!("PHYSICAL{dim}_{tag}"),
+    };
+    let mut sets = Sets::new();
+    for ((dim, tag), nodes) in node_groups {
+        sets.add_node_set(NodeSet {
+            name: set_name(dim, tag),
+            nodes: nodes.into_iter().collect(),
+        });
+    }
+    for (tag, elements) in element_groups {
+        sets.add_element_set(ElementSet {
+            name: set_name(mesh_dim, tag),
+            elements,
+        });
+    }
+
+    Ok(GmshMesh {
+        version: raw.version,
+        mesh,
+        sets,
+    })
+}
+
+fn invalid(line: usize, message: &str) -> io::Error {
+    let message = if line > 0 {
+        format!("line {line}: {message}")
+    } else {
+        message.to_string()
+    };
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2: &str = "\
+$MeshFormat
+2.2 0 8
+$EndMeshFormat
+$PhysicalNames
+2
+2 1 \"fixed\"
+3 2 \"solid\"
+$EndPhysicalNames
+$Nodes
+5
+1 0 0 0
+2 1 0 0
+3 0 1 0
+4 0 0 1
+5 0.5 0 0
+$EndNodes
+$Elements
+2
+1 2 2 1 10 1 2 3
+2 4 2 2 20 1 2 3 4
+$EndElements
+";
+
+    const V4: &str = "\
+$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$PhysicalNames
+1
+2 5 \"Shell\"
+$EndPhysicalNames
+$Entities
+0 0 1 0
+1 0 0 0 1 1 0 1 5 0
+$EndEntities
+$Nodes
+1 4 1 4
+2 1 0 4
+1
+2
+3
+4
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+$EndNodes
+$Elements
+1 1 1 1
+2 1 3 1
+7 1 2 3 4
+$EndElements
+";
+
+    #[test]
+    fn reads_v2_mesh_with_physical_groups() {
+        let gmsh = GmshMesh::parse_str(V2).expect("parse v2");
+        assert_eq!(gmsh.version, "2.2");
+        assert_eq!(gmsh.mesh.nodes.len(), 5);
+        assert_eq!(gmsh.mesh.elements.len(), 1);
+        let tet = gmsh.mesh.get_element(2).expect("tet");
+        assert_eq!(tet.element_type, ElementType::C3D4);
+        assert_eq!(gmsh.sets.get_nodes("FIXED"), Some(&[1, 2, 3][..]));
+        assert_eq!(gmsh.sets.get_elements("SOLID"), Some(&[2][..]));
+        assert!(gmsh.sets.get_elements("FIXED").is_none());
+    }
+
+    #[test]
+    fn reads_v4_mesh_using_entity_physicals() {
+        let gmsh = GmshMesh::parse_str(V4).expect("parse v4");
+        let quad = gmsh.mesh.get_element(7).expect("quad");
+        assert_eq!(quad.element_type, ElementType::S4);
+        assert_eq!(quad.nodes, vec![1, 2, 3, 4]);
+        assert_eq!(gmsh.sets.get_elements("SHELL"), Some(&[7][..]));
+        assert_eq!(
+            gmsh.mesh.get_node(3).map(|n| n.coords()),
+            Some([1.0, 1.0, 0.0])
+        );
+    }
+
+    #[test]
+    fn reorders_second_order_tetrahedra() {
+        let order = gmsh_element(11).expect("tet10").2;
+        let gmsh_nodes: Vec<i32> = (1..=10).collect();
+        let ccx: Vec<i32> = order.iter().map(|&i| gmsh_nodes[i]).collect();
+        assert_eq!(ccx, vec![1, 2, 3, 4, 5, 6, 7, 8, 10, 9]);
+    }
+
+    #[test]
+    fn rejects_binary_and_unknown_versions() {
+        let binary = "$MeshFormat\n4.1 1 8\n$EndMeshFormat\n";
+        let err = GmshMesh::parse_str(binary).expect_err("binary");
+        assert!(err.to_string().contains("binary"));
+        let old = "$MeshFormat\n4.0 0 8\n$EndMeshFormat\n";
+        assert!(GmshMesh::parse_str(old).is_err());
+    }
+}
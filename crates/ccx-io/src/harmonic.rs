@@ -0,0 +1,288 @@
+//! Postprocessing of steady-state dynamics (harmonic response) results.
+//!
+//! CalculiX writes the complex response of `*STEADY STATE DYNAMICS` as one
+//! result block per excitation frequency (the block time), with the real and
+//! imaginary parts in the datasets `<NAME>` and `<NAME>I` (e.g. `DISP` and
+//! `DISPI`), or as magnitudes and phases in degrees in `P<NAME>` (e.g.
+//! `PDISP` with `MAG1..3, PHA1..3`). Both layouts are read here to give the
+//! response of single DOFs over frequency, frequency response functions
+//! between two DOFs, and amplitude/phase datasets for field output.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::frd_reader::{FrdFile, ResultBlock, ResultDataset, ResultLocation};
+
+/// A nodal degree of freedom of a result dataset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HarmonicDof {
+    /// Real part dataset name, e.g. `DISP`
+    pub dataset: String,
+    pub node: i32,
+    /// Component, counted from 1
+    pub component: usize,
+}
+
+/// Complex value of a response at one frequency
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HarmonicPoint {
+    pub frequency: f64,
+    pub re: f64,
+    pub im: f64,
+}
+
+impl HarmonicPoint {
+    pub fn amplitude(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Phase angle in degrees, in (-180, 180]
+    pub fn phase(&self) -> f64 {
+        self.im.atan2(self.re).to_degrees()
+    }
+}
+
+/// Complex value of `dof` in one result block, if the block holds it
+fn complex_value(block: &ResultBlock, dof: &HarmonicDof) -> Option<(f64, f64)> {
+    let index = dof.component.checked_sub(1)?;
+    let nodal = |name: &str| {
+        block
+            .datasets
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name) && d.location == ResultLocation::Nodal)
+    };
+    if let (Some(real), Some(imag)) = (nodal(&dof.dataset), nodal(&format!("{}I", dof.dataset))) {
+        let re = *real.values.get(&dof.node)?.get(index)?;
+        let im = *imag.values.get(&dof.node)?.get(index)?;
+        return Some((re, im));
+    }
+    // Magnitudes followed by phases in degrees
+    let polar = nodal(&format!("P{}", dof.dataset))?;
+    let values = polar.values.get(&dof.node)?;
+    let components = values.len() / 2;
+    if index >= components {
+        return None;
+    }
+    let (magnitude, phase) = (values[index], values[components + index].to_radians());
+    Some((magnitude * phase.cos(), magnitude * phase.sin()))
+}
+
+/// Response of `dof` over the frequencies of `frd`, in block order
+///
+/// Blocks without the DOF are skipped; an error is returned when no block
+/// has it.
+pub fn harmonic_response(frd: &FrdFile, dof: &HarmonicDof) -> Result<Vec<HarmonicPoint>, String> {
+    let points: Vec<HarmonicPoint> = frd
+        .result_blocks
+        .iter()
+        .filter_map(|block| {
+            let (re, im) = complex_value(block, dof)?;
+            Some(HarmonicPoint {
+                frequency: block.time,
+                re,
+                im,
+            })
+        })
+        .collect();
+    if points.is_empty() {
+        return Err(format!(
+            "no complex {} results for node {} component {}",
+            dof.dataset, dof.node, dof.component
+        ));
+    }
+    Ok(points)
+}
+
+/// Frequency response function `response / excitation` over the
+/// frequencies at which both DOFs have results
+///
+/// Frequencies with a zero excitation are left out.
+pub fn frequency_response_function(
+    frd: &FrdFile,
+    excitation: &HarmonicDof,
+    response: &HarmonicDof,
+) -> Result<Vec<HarmonicPoint>, String> {
+    let points: Vec<HarmonicPoint> = frd
+        .result_blocks
+        .iter()
+        .filter_map(|block| {
+            let (a, b) = complex_value(block, response)?;
+            let (c, d) = complex_value(block, excitation)?;
+            let norm = c * c + d * d;
+            (norm > 0.0).then(|| HarmonicPoint {
+                frequency: block.time,
+                re: (a * c + b * d) / norm,
+                im: (b * c - a * d) / norm,
+            })
+        })
+        .collect();
+    if points.is_empty() {
+        return Err(format!(
+            "no common frequencies with a nonzero excitation at node {} component {}",
+            excitation.node, excitation.component
+        ));
+    }
+    Ok(points)
+}
+
+/// Amplitude/phase dataset `P<NAME>` from the real and imaginary datasets
+/// `<NAME>` and `<NAME>I` of a block, with components `MAG1.., PHA1..`
+pub fn amplitude_phase_dataset(block: &ResultBlock, name: &str) -> Option<ResultDataset> {
+    let find = |name: &str| {
+        block
+            .datasets
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+    };
+    let real = find(name)?;
+    let imag = find(&format!("{}I", name))?;
+    let values: HashMap<i32, Vec<f64>> = real
+        .values
+        .iter()
+        .filter_map(|(id, re)| {
+            let im = imag.values.get(id)?;
+            let points: Vec<HarmonicPoint> = re
+                .iter()
+                .zip(im)
+                .map(|(&re, &im)| HarmonicPoint {
+                    frequency: block.time,
+                    re,
+                    im,
+                })
+                .collect();
+            let magnitudes = points.iter().map(HarmonicPoint::amplitude);
+            let phases = points.iter().map(HarmonicPoint::phase);
+            Some((*id, magnitudes.chain(phases).collect()))
+        })
+        .collect();
+    let ncomps = real.ncomps.min(imag.ncomps);
+    let mut dataset = ResultDataset::nodal(&format!("P{}", real.name), values);
+    dataset.location = real.location;
+    dataset.ncomps = 2 * ncomps;
+    dataset.comp_names = (1..=ncomps)
+        .map(|c| format!("MAG{c}"))
+        .chain((1..=ncomps).map(|c| format!("PHA{c}")))
+        .collect();
+    Some(dataset)
+}
+
+/// Write a response as CSV with the columns
+/// `frequency, real, imag, amplitude, phase_deg`
+pub fn write_harmonic_csv<W: Write>(out: &mut W, points: &[HarmonicPoint]) -> io::Result<()> {
+    writeln!(out, "frequency,real,imag,amplitude,phase_deg")?;
+    for point in points {
+        writeln!(
+            out,
+            "{:e},{:e},{:e},{:e},{}",
+            point.frequency,
+            point.re,
+            point.im,
+            point.amplitude(),
+            point.phase()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(frequency: f64, datasets: Vec<ResultDataset>) -> ResultBlock {
+        ResultBlock {
+            step: 1,
+            time: frequency,
+            mode: None,
+            datasets,
+        }
+    }
+
+    fn nodal(name: &str, values: &[(i32, [f64; 3])]) -> ResultDataset {
+        ResultDataset::nodal(
+            name,
+            values.iter().map(|(id, v)| (*id, v.to_vec())).collect(),
+        )
+    }
+
+    fn dof(node: i32, component: usize) -> HarmonicDof {
+        HarmonicDof {
+            dataset: "DISP".to_string(),
+            node,
+            component,
+        }
+    }
+
+    /// Base node 1 moves with unit amplitude, node 2 responds with
+    /// 2 (1 + i) at 10 Hz and -3 i at 20 Hz (given as PDISP)
+    fn frd() -> FrdFile {
+        let mut frd = FrdFile::new();
+        frd.result_blocks.push(block(
+            10.0,
+            vec![
+                nodal("DISP", &[(1, [1.0, 0.0, 0.0]), (2, [2.0, 0.0, 0.0])]),
+                nodal("DISPI", &[(1, [0.0; 3]), (2, [2.0, 0.0, 0.0])]),
+            ],
+        ));
+        let mut polar = ResultDataset::nodal(
+            "PDISP",
+            [
+                (1, vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+                (2, vec![3.0, 0.0, 0.0, -90.0, 0.0, 0.0]),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        polar.comp_names = ["MAG1", "MAG2", "MAG3", "PHA1", "PHA2", "PHA3"]
+            .map(String::from)
+            .to_vec();
+        frd.result_blocks.push(block(20.0, vec![polar]));
+        frd
+    }
+
+    #[test]
+    fn reads_real_imaginary_and_polar_results() {
+        let response = harmonic_response(&frd(), &dof(2, 1)).unwrap();
+        assert_eq!(response.len(), 2);
+        assert_eq!((response[0].re, response[0].im), (2.0, 2.0));
+        assert!((response[0].amplitude() - 8.0_f64.sqrt()).abs() < 1e-12);
+        assert!((response[0].phase() - 45.0).abs() < 1e-12);
+        assert!(response[1].re.abs() < 1e-12 && (response[1].im + 3.0).abs() < 1e-12);
+        assert!((response[1].phase() + 90.0).abs() < 1e-12);
+
+        assert!(harmonic_response(&frd(), &dof(7, 1)).is_err());
+        assert!(harmonic_response(&frd(), &dof(2, 0)).is_err());
+    }
+
+    #[test]
+    fn transfer_function_divides_response_by_excitation() {
+        let mut frd = frd();
+        // Excitation 1 + i at 10 Hz: H = 2 (1 + i) / (1 + i) = 2
+        frd.result_blocks[0].datasets[1]
+            .values
+            .insert(1, vec![1.0, 0.0, 0.0]);
+        let frf = frequency_response_function(&frd, &dof(1, 1), &dof(2, 1)).unwrap();
+        assert_eq!(frf.len(), 2);
+        assert!((frf[0].re - 2.0).abs() < 1e-12 && frf[0].im.abs() < 1e-12);
+        assert!((frf[1].amplitude() - 3.0).abs() < 1e-12);
+
+        let mut csv = Vec::new();
+        write_harmonic_csv(&mut csv, &frf[..1]).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv,
+            "frequency,real,imag,amplitude,phase_deg\n1e1,2e0,0e0,2e0,0\n"
+        );
+    }
+
+    #[test]
+    fn converts_to_amplitude_and_phase() {
+        let frd = frd();
+        let polar = amplitude_phase_dataset(&frd.result_blocks[0], "DISP").unwrap();
+        assert_eq!(polar.name, "PDISP");
+        assert_eq!(polar.comp_names[3], "PHA1");
+        let node = &polar.values[&2];
+        assert!((node[0] - 8.0_f64.sqrt()).abs() < 1e-12);
+        assert!((node[3] - 45.0).abs() < 1e-12);
+        assert!(amplitude_phase_dataset(&frd.result_blocks[1], "DISP").is_none());
+    }
+}
@@ -0,0 +1,624 @@
+//! Minimal safe wrapper over the HDF5 C library (feature `hdf5`).
+//!
+//! Covers what the XDMF and CGNS writers need: files and groups that track
+//! link creation order, fixed-length string and integer attributes, and
+//! contiguous datasets written whole or in 1-D slices. Every handle is closed
+//! when its wrapper is dropped.
+
+// Groups, attributes and sliced writes are only used by the CGNS writer
+#![cfg_attr(not(feature = "cgns"), allow(dead_code))]
+
+use std::ffi::CString;
+use std::io;
+use std::os::raw::c_void;
+use std::path::Path;
+
+use hdf5_sys::h5::{herr_t, hsize_t};
+use hdf5_sys::h5a::{H5Aclose, H5Acreate2, H5Awrite};
+use hdf5_sys::h5d::{H5Dclose, H5Dcreate2, H5Dget_space, H5Dwrite};
+use hdf5_sys::h5f::{H5F_ACC_TRUNC, H5Fclose, H5Fcreate};
+use hdf5_sys::h5g::{H5Gclose, H5Gcreate2, H5Gopen2};
+use hdf5_sys::h5i::hid_t;
+use hdf5_sys::h5p::{
+    H5P_CLS_FILE_CREATE, H5P_CLS_GROUP_CREATE, H5P_CLS_LINK_CREATE, H5P_CRT_ORDER_INDEXED,
+    H5P_CRT_ORDER_TRACKED, H5P_DEFAULT, H5Pclose, H5Pcreate, H5Pset_create_intermediate_group,
+    H5Pset_link_creation_order,
+};
+use hdf5_sys::h5s::{
+    H5S_ALL, H5S_class_t, H5S_seloper_t, H5Sclose, H5Screate, H5Screate_simple, H5Sselect_hyperslab,
+};
+use hdf5_sys::h5t::{
+    H5T_C_S1, H5T_NATIVE_DOUBLE, H5T_NATIVE_FLOAT, H5T_NATIVE_INT, H5T_NATIVE_INT64,
+    H5T_NATIVE_SCHAR, H5Tclose, H5Tcopy, H5Tset_size,
+};
+
+/// Element type of a dataset or attribute
+pub(crate) trait H5Type: Copy {
+    /// Native HDF5 type; valid once the library is open
+    fn type_id() -> hid_t;
+}
+
+impl H5Type for f64 {
+    fn type_id() -> hid_t {
+        *H5T_NATIVE_DOUBLE
+    }
+}
+
+impl H5Type for f32 {
+    fn type_id() -> hid_t {
+        *H5T_NATIVE_FLOAT
+    }
+}
+
+impl H5Type for i32 {
+    fn type_id() -> hid_t {
+        *H5T_NATIVE_INT
+    }
+}
+
+impl H5Type for i64 {
+    fn type_id() -> hid_t {
+        *H5T_NATIVE_INT64
+    }
+}
+
+impl H5Type for i8 {
+    fn type_id() -> hid_t {
+        *H5T_NATIVE_SCHAR
+    }
+}
+
+/// Identifier closed with `close` on drop
+struct Handle {
+    id: hid_t,
+    close: unsafe extern "C" fn(hid_t) -> herr_t,
+}
+
+impl Handle {
+    fn new(
+        id: hid_t,
+        close: unsafe extern "C" fn(hid_t) -> herr_t,
+        what: &str,
+    ) -> io::Result<Self> {
+        if id < 0 {
+            return Err(io::Error::other(format!("HDF5: {what} failed")));
+        }
+        Ok(Self { id, close })
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        // SAFETY: `id` was returned by the library and is closed only here
+        unsafe {
+            (self.close)(self.id);
+        }
+    }
+}
+
+fn check(status: herr_t, what: &str) -> io::Result<()> {
+    if status < 0 {
+        return Err(io::Error::other(format!("HDF5: {what} failed")));
+    }
+    Ok(())
+}
+
+fn c_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("HDF5 name {name:?} contains a NUL byte"),
+        )
+    })
+}
+
+fn dims_of(dims: &[usize]) -> Vec<hsize_t> {
+    dims.iter().map(|&d| d as hsize_t).collect()
+}
+
+/// Simple dataspace of `dims`
+fn simple_space(dims: &[usize]) -> io::Result<Handle> {
+    let dims = dims_of(dims);
+    // SAFETY: `dims` holds `dims.len()` extents; no maximum extents
+    let id = unsafe { H5Screate_simple(dims.len() as _, dims.as_ptr(), std::ptr::null()) };
+    Handle::new(id, H5Sclose, "dataspace creation")
+}
+
+/// Property list of class `class` with link creation order tracked and
+/// indexed, as CGNS readers list children in creation order
+fn creation_order_plist(class: hid_t) -> io::Result<Handle> {
+    // SAFETY: plain library calls on a property list owned by the handle
+    let plist = Handle::new(unsafe { H5Pcreate(class) }, H5Pclose, "property list")?;
+    check(
+        unsafe {
+            H5Pset_link_creation_order(plist.id, H5P_CRT_ORDER_TRACKED | H5P_CRT_ORDER_INDEXED)
+        },
+        "link creation order",
+    )?;
+    Ok(plist)
+}
+
+/// Version of the linked library as `major.minor.release`
+pub(crate) fn library_version() -> io::Result<String> {
+    let (mut major, mut minor, mut release) = (0, 0, 0);
+    // SAFETY: the three pointers refer to live locals
+    check(
+        unsafe { hdf5_sys::h5::H5get_libversion(&mut major, &mut minor, &mut release) },
+        "library version",
+    )?;
+    Ok(format!("{major}.{minor}.{release}"))
+}
+
+/// An HDF5 file open for writing
+pub(crate) struct H5File {
+    handle: Handle,
+}
+
+impl H5File {
+    /// Create or truncate `path`
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        let name = path.to_str().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("HDF5 path {} is not valid UTF-8", path.display()),
+            )
+        })?;
+        let name = c_name(name)?;
+        // SAFETY: initializes the library and its type globals; idempotent
+        check(unsafe { hdf5_sys::h5::H5open() }, "library initialization")?;
+        let fcpl = creation_order_plist(*H5P_CLS_FILE_CREATE)?;
+        // SAFETY: `name` is NUL terminated and `fcpl` is a file creation list
+        let id = unsafe { H5Fcreate(name.as_ptr(), H5F_ACC_TRUNC, fcpl.id, H5P_DEFAULT) };
+        Ok(Self {
+            handle: Handle::new(id, H5Fclose, &format!("creating {}", path.display()))?,
+        })
+    }
+
+    /// The root group `/`
+    pub(crate) fn root(&self) -> io::Result<H5Group> {
+        let name = c_name("/")?;
+        // SAFETY: the file handle is open and `name` is NUL terminated
+        let id = unsafe { H5Gopen2(self.handle.id, name.as_ptr(), H5P_DEFAULT) };
+        Ok(H5Group {
+            handle: Handle::new(id, H5Gclose, "opening /")?,
+        })
+    }
+}
+
+/// A group of an open [`H5File`]
+pub(crate) struct H5Group {
+    handle: Handle,
+}
+
+impl H5Group {
+    /// Create the child group `name`
+    pub(crate) fn create_group(&self, name: &str) -> io::Result<H5Group> {
+        let c = c_name(name)?;
+        let gcpl = creation_order_plist(*H5P_CLS_GROUP_CREATE)?;
+        // SAFETY: the group handle is open and `c` is NUL terminated
+        let id = unsafe {
+            H5Gcreate2(
+                self.handle.id,
+                c.as_ptr(),
+                H5P_DEFAULT,
+                gcpl.id,
+                H5P_DEFAULT,
+            )
+        };
+        Ok(H5Group {
+            handle: Handle::new(id, H5Gclose, &format!("creating group {name}"))?,
+        })
+    }
+
+    /// Scalar attribute holding `value` as a NUL-padded string of `size`
+    /// bytes, the last of which is always NUL
+    pub(crate) fn string_attr(&self, name: &str, value: &str, size: usize) -> io::Result<()> {
+        let mut buffer = vec![0u8; size];
+        let len = value.len().min(size.saturating_sub(1));
+        buffer[..len].copy_from_slice(&value.as_bytes()[..len]);
+
+        // SAFETY: the copied type and scalar space are owned by their handles
+        let string_type = Handle::new(unsafe { H5Tcopy(*H5T_C_S1) }, H5Tclose, "string type")?;
+        check(unsafe { H5Tset_size(string_type.id, size) }, "string size")?;
+        let space = Handle::new(
+            unsafe { H5Screate(H5S_class_t::H5S_SCALAR) },
+            H5Sclose,
+            "scalar dataspace",
+        )?;
+        self.attr(name, string_type.id, &space, buffer.as_ptr().cast())
+    }
+
+    /// 1-D attribute holding `values`
+    pub(crate) fn int_attr(&self, name: &str, values: &[i32]) -> io::Result<()> {
+        let space = simple_space(&[values.len()])?;
+        self.attr(name, i32::type_id(), &space, values.as_ptr().cast())
+    }
+
+    fn attr(
+        &self,
+        name: &str,
+        type_id: hid_t,
+        space: &Handle,
+        data: *const c_void,
+    ) -> io::Result<()> {
+        let c = c_name(name)?;
+        // SAFETY: `data` points to one element of `type_id` per point of `space`
+        let attr = Handle::new(
+            unsafe {
+                H5Acreate2(
+                    self.handle.id,
+                    c.as_ptr(),
+                    type_id,
+                    space.id,
+                    H5P_DEFAULT,
+                    H5P_DEFAULT,
+                )
+            },
+            H5Aclose,
+            &format!("creating attribute {name}"),
+        )?;
+        check(
+            unsafe { H5Awrite(attr.id, type_id, data) },
+            &format!("writing attribute {name}"),
+        )
+    }
+
+    /// Write `values` as the dataset `name` with row-major extents `dims`;
+    /// missing intermediate groups of `name` are created
+    pub(crate) fn write_dataset<T: H5Type>(
+        &self,
+        name: &str,
+        dims: &[usize],
+        values: &[T],
+    ) -> io::Result<()> {
+        if dims.iter().product::<usize>() != values.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "dataset {name}: {} values for extents {dims:?}",
+                    values.len()
+                ),
+            ));
+        }
+        let dataset = self.create_dataset::<T>(name, dims)?;
+        // SAFETY: `values` holds exactly one element per point of the dataset
+        check(
+            unsafe {
+                H5Dwrite(
+                    dataset.handle.id,
+                    T::type_id(),
+                    H5S_ALL,
+                    H5S_ALL,
+                    H5P_DEFAULT,
+                    values.as_ptr().cast(),
+                )
+            },
+            &format!("writing dataset {name}"),
+        )
+    }
+
+    /// Create the 1-D dataset `name` of `len` elements, to be filled with
+    /// [`H5Dataset::write_slice`]
+    pub(crate) fn create_vector<T: H5Type>(&self, name: &str, len: usize) -> io::Result<H5Dataset> {
+        self.create_dataset::<T>(name, &[len])
+    }
+
+    fn create_dataset<T: H5Type>(&self, name: &str, dims: &[usize]) -> io::Result<H5Dataset> {
+        let c = c_name(name)?;
+        let space = simple_space(dims)?;
+        // SAFETY: plain library calls on handles owned here
+        let lcpl = Handle::new(
+            unsafe { H5Pcreate(*H5P_CLS_LINK_CREATE) },
+            H5Pclose,
+            "link creation list",
+        )?;
+        check(
+            unsafe { H5Pset_create_intermediate_group(lcpl.id, 1) },
+            "intermediate groups",
+        )?;
+        let id = unsafe {
+            H5Dcreate2(
+                self.handle.id,
+                c.as_ptr(),
+                T::type_id(),
+                space.id,
+                lcpl.id,
+                H5P_DEFAULT,
+                H5P_DEFAULT,
+            )
+        };
+        Ok(H5Dataset {
+            handle: Handle::new(id, H5Dclose, &format!("creating dataset {name}"))?,
+            len: dims.iter().product(),
+            type_id: T::type_id(),
+        })
+    }
+}
+
+/// A 1-D dataset written in slices
+pub(crate) struct H5Dataset {
+    handle: Handle,
+    len: usize,
+    type_id: hid_t,
+}
+
+impl H5Dataset {
+    /// Write `values` to elements `offset..offset + values.len()`
+    pub(crate) fn write_slice<T: H5Type>(&self, offset: usize, values: &[T]) -> io::Result<()> {
+        if T::type_id() != self.type_id {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "slice type differs from the dataset type",
+            ));
+        }
+        if offset + values.len() > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "slice {offset}..{} does not fit a dataset of {} elements",
+                    offset + values.len(),
+                    self.len
+                ),
+            ));
+        }
+        if values.is_empty() {
+            return Ok(());
+        }
+        let memory = simple_space(&[values.len()])?;
+        // SAFETY: the selected hyperslab has as many points as `values`
+        let file = Handle::new(
+            unsafe { H5Dget_space(self.handle.id) },
+            H5Sclose,
+            "dataset dataspace",
+        )?;
+        let start = [offset as hsize_t];
+        let count = [values.len() as hsize_t];
+        check(
+            unsafe {
+                H5Sselect_hyperslab(
+                    file.id,
+                    H5S_seloper_t::H5S_SELECT_SET,
+                    start.as_ptr(),
+                    std::ptr::null(),
+                    count.as_ptr(),
+                    std::ptr::null(),
+                )
+            },
+            "hyperslab selection",
+        )?;
+        check(
+            unsafe {
+                H5Dwrite(
+                    self.handle.id,
+                    self.type_id,
+                    memory.id,
+                    file.id,
+                    H5P_DEFAULT,
+                    values.as_ptr().cast(),
+                )
+            },
+            "writing dataset slice",
+        )
+    }
+}
+
+// Read-back used by the round-trip tests of the writers
+#[cfg(test)]
+mod read {
+    use std::ffi::{CStr, CString};
+    use std::io;
+    use std::path::Path;
+
+    use hdf5_sys::h5::{H5_index_t, H5_iter_order_t, hsize_t};
+    use hdf5_sys::h5a::{H5Aclose, H5Aget_space, H5Aget_type, H5Aopen, H5Aread};
+    use hdf5_sys::h5d::{H5Dclose, H5Dget_space, H5Dopen2, H5Dread};
+    use hdf5_sys::h5f::{H5F_ACC_RDONLY, H5Fclose, H5Fopen};
+    use hdf5_sys::h5g::{H5G_info_t, H5Gclose, H5Gget_info, H5Gopen2};
+    use hdf5_sys::h5l::H5Lget_name_by_idx;
+    use hdf5_sys::h5p::H5P_DEFAULT;
+    use hdf5_sys::h5s::{H5S_ALL, H5Sclose, H5Sget_simple_extent_dims, H5Sget_simple_extent_ndims};
+    use hdf5_sys::h5t::{H5Tclose, H5Tget_size};
+
+    use super::{H5File, H5Group, H5Type, Handle, c_name, check};
+
+    /// Extents of the dataspace `space`
+    fn extents(space: &Handle) -> io::Result<Vec<usize>> {
+        // SAFETY: `dims` has room for every dimension of the space
+        let rank = unsafe { H5Sget_simple_extent_ndims(space.id) };
+        check(rank, "dataspace rank")?;
+        let mut dims = vec![0 as hsize_t; rank as usize];
+        check(
+            unsafe { H5Sget_simple_extent_dims(space.id, dims.as_mut_ptr(), std::ptr::null_mut()) },
+            "dataspace extents",
+        )?;
+        Ok(dims.into_iter().map(|d| d as usize).collect())
+    }
+
+    impl H5File {
+        /// Open `path` read-only
+        pub(crate) fn open(path: &Path) -> io::Result<Self> {
+            let name = c_name(&path.to_string_lossy())?;
+            // SAFETY: `name` is NUL terminated
+            let id = unsafe { H5Fopen(name.as_ptr(), H5F_ACC_RDONLY, H5P_DEFAULT) };
+            Ok(Self {
+                handle: Handle::new(id, H5Fclose, &format!("opening {}", path.display()))?,
+            })
+        }
+    }
+
+    impl H5Group {
+        /// Open the child group `name`
+        pub(crate) fn open_group(&self, name: &str) -> io::Result<H5Group> {
+            let c = c_name(name)?;
+            // SAFETY: the group handle is open and `c` is NUL terminated
+            let id = unsafe { H5Gopen2(self.handle.id, c.as_ptr(), H5P_DEFAULT) };
+            Ok(H5Group {
+                handle: Handle::new(id, H5Gclose, &format!("opening group {name}"))?,
+            })
+        }
+
+        /// Names of the links of the group in creation order
+        pub(crate) fn children(&self) -> io::Result<Vec<String>> {
+            // SAFETY: `info` is plain data filled by the library
+            let mut info: H5G_info_t = unsafe { std::mem::zeroed() };
+            check(
+                unsafe { H5Gget_info(self.handle.id, &mut info) },
+                "group info",
+            )?;
+            let dot = CString::new(".").expect("no NUL byte");
+            (0..info.nlinks)
+                .map(|n| {
+                    let mut buffer = [0 as std::os::raw::c_char; 256];
+                    // SAFETY: the name is truncated to the buffer and NUL terminated
+                    let len = unsafe {
+                        H5Lget_name_by_idx(
+                            self.handle.id,
+                            dot.as_ptr(),
+                            H5_index_t::H5_INDEX_CRT_ORDER,
+                            H5_iter_order_t::H5_ITER_INC,
+                            n,
+                            buffer.as_mut_ptr(),
+                            buffer.len(),
+                            H5P_DEFAULT,
+                        )
+                    };
+                    check(len as i32, "link name")?;
+                    // SAFETY: the library NUL terminated the name
+                    let name = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+                    Ok(name.to_string_lossy().into_owned())
+                })
+                .collect()
+        }
+
+        /// Extents and row-major values of the dataset `name`
+        pub(crate) fn read_dataset<T: H5Type + Default>(
+            &self,
+            name: &str,
+        ) -> io::Result<(Vec<usize>, Vec<T>)> {
+            let c = c_name(name)?;
+            // SAFETY: the dataset is read into a buffer of one element per point
+            let dataset = Handle::new(
+                unsafe { H5Dopen2(self.handle.id, c.as_ptr(), H5P_DEFAULT) },
+                H5Dclose,
+                &format!("opening dataset {name}"),
+            )?;
+            let space = Handle::new(
+                unsafe { H5Dget_space(dataset.id) },
+                H5Sclose,
+                "dataset dataspace",
+            )?;
+            let dims = extents(&space)?;
+            let mut values = vec![T::default(); dims.iter().product()];
+            check(
+                unsafe {
+                    H5Dread(
+                        dataset.id,
+                        T::type_id(),
+                        H5S_ALL,
+                        H5S_ALL,
+                        H5P_DEFAULT,
+                        values.as_mut_ptr().cast(),
+                    )
+                },
+                &format!("reading dataset {name}"),
+            )?;
+            Ok((dims, values))
+        }
+
+        /// Value of the string attribute `name` up to its first NUL, and the
+        /// declared size of the string
+        pub(crate) fn read_string_attr(&self, name: &str) -> io::Result<(String, usize)> {
+            let c = c_name(name)?;
+            // SAFETY: the attribute is read with its own fixed-size string type
+            let attr = Handle::new(
+                unsafe { H5Aopen(self.handle.id, c.as_ptr(), H5P_DEFAULT) },
+                H5Aclose,
+                &format!("opening attribute {name}"),
+            )?;
+            let string_type =
+                Handle::new(unsafe { H5Aget_type(attr.id) }, H5Tclose, "attribute type")?;
+            let size = unsafe { H5Tget_size(string_type.id) };
+            let mut buffer = vec![0u8; size];
+            check(
+                unsafe { H5Aread(attr.id, string_type.id, buffer.as_mut_ptr().cast()) },
+                &format!("reading attribute {name}"),
+            )?;
+            let len = buffer.iter().position(|&b| b == 0).unwrap_or(size);
+            Ok((String::from_utf8_lossy(&buffer[..len]).into_owned(), size))
+        }
+
+        /// Values of the integer attribute `name`
+        pub(crate) fn read_int_attr(&self, name: &str) -> io::Result<Vec<i32>> {
+            let c = c_name(name)?;
+            // SAFETY: the attribute is read into one `i32` per point
+            let attr = Handle::new(
+                unsafe { H5Aopen(self.handle.id, c.as_ptr(), H5P_DEFAULT) },
+                H5Aclose,
+                &format!("opening attribute {name}"),
+            )?;
+            let space = Handle::new(
+                unsafe { H5Aget_space(attr.id) },
+                H5Sclose,
+                "attribute dataspace",
+            )?;
+            let mut values = vec![0i32; extents(&space)?.iter().product()];
+            check(
+                unsafe { H5Aread(attr.id, i32::type_id(), values.as_mut_ptr().cast()) },
+                &format!("reading attribute {name}"),
+            )?;
+            Ok(values)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_datasets_slices_and_attributes() {
+        let path = std::env::temp_dir().join(format!("ccx_io_h5_{}.h5", std::process::id()));
+        {
+            let file = H5File::create(&path).expect("create h5");
+            let root = file.root().expect("root");
+            let group = root.create_group("zone").expect("group");
+            group
+                .string_attr("label", "Zone_t", 33)
+                .expect("string attr");
+            group.int_attr("flags", &[1, 2]).expect("int attr");
+            root.write_dataset("mesh/XYZ", &[2, 3], &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0])
+                .expect("dataset");
+            let vector = group.create_vector::<i64>("ids", 5).expect("vector");
+            vector.write_slice(0, &[10i64, 20]).expect("first slice");
+            vector
+                .write_slice(2, &[30i64, 40, 50])
+                .expect("second slice");
+            assert!(vector.write_slice(4, &[60i64, 70]).is_err());
+            assert!(vector.write_slice(0, &[1.0f64]).is_err());
+            root.create_group("after").expect("second group");
+        }
+
+        let file = H5File::open(&path).expect("open h5");
+        let root = file.root().expect("root");
+        assert_eq!(
+            root.children().expect("children"),
+            ["zone", "mesh", "after"]
+        );
+        let (dims, xyz) = root.read_dataset::<f64>("mesh/XYZ").expect("read xyz");
+        assert_eq!(dims, [2, 3]);
+        assert_eq!(xyz, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        let zone = root.open_group("zone").expect("zone");
+        assert_eq!(
+            zone.read_string_attr("label").expect("label"),
+            ("Zone_t".to_string(), 33)
+        );
+        assert_eq!(zone.read_int_attr("flags").expect("flags"), [1, 2]);
+        let (dims, ids) = zone.read_dataset::<i64>("ids").expect("read ids");
+        assert_eq!(dims, [5]);
+        assert_eq!(ids, [10, 20, 30, 40, 50]);
+        assert!(library_version().expect("version").contains('.'));
+        drop((zone, root, file));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,526 @@
+//! Time-history output driven by `*NODE PRINT` and `*EL PRINT`.
+//!
+//! Each print card of a deck becomes a [`HistoryRequest`]: a node or element
+//! set, the requested variables and the `FREQUENCY` of the output. A
+//! [`HistoryOutput`] is fed the full `.dat` tables of every increment and
+//! keeps only the requested entities and variables of the due increments,
+//! both as `.dat` sections for the `.dat` file and as a compact table that
+//! can be written as CSV or JSON.
+//!
+//! Supported variables are the ones of [`DatSection`]: `U` and `RF` for
+//! nodes, `S`, `E`, `ME` and `SF` for elements; other variables are
+//! rejected. `TOTALS=YES` adds the total `RF` of the set after its table and
+//! `TOTALS=ONLY` prints the total alone. The sets `NALL` and `EALL` select
+//! all nodes or elements unless the deck defines them.
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use ccx_inp::{Card, Deck};
+use ccx_solver::Sets;
+use serde::{Deserialize, Serialize};
+
+use crate::dat_writer::{DatSection, DatStep};
+
+/// Whether a print request selects nodes or elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryTarget {
+    /// `*NODE PRINT, NSET=...`
+    Nodes,
+    /// `*EL PRINT, ELSET=...`
+    Elements,
+}
+
+/// `TOTALS` parameter of a print request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrintTotals {
+    /// Only the table (`TOTALS=NO`, the default)
+    #[default]
+    No,
+    /// The table followed by the sum over the set
+    Yes,
+    /// Only the sum over the set
+    Only,
+}
+
+/// One `*NODE PRINT` or `*EL PRINT` request
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRequest {
+    pub target: HistoryTarget,
+    /// Set name as written in the deck
+    pub set: String,
+    /// Members of the set; `None` selects all nodes or elements
+    pub ids: Option<BTreeSet<i32>>,
+    /// Requested variables, e.g. `U`, `RF`, `S`
+    pub variables: Vec<String>,
+    /// Output every `frequency`-th increment of a step; 0 switches it off
+    pub frequency: i32,
+    /// Whether `RF` is printed per node, summed over the set, or both
+    pub totals: PrintTotals,
+}
+
+impl HistoryRequest {
+    /// Collect the print requests of `deck`, resolving their sets
+    pub fn from_deck(deck: &Deck) -> Result<Vec<Self>, String> {
+        let mut sets = Sets::build_from_deck(deck)?;
+        sets.add_card_sets(deck)?;
+        deck.cards
+            .iter()
+            .filter_map(|card| match card.keyword.as_str() {
+                "NODE PRINT" => Some(Self::from_card(card, HistoryTarget::Nodes, &sets)),
+                "EL PRINT" => Some(Self::from_card(card, HistoryTarget::Elements, &sets)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn from_card(card: &Card, target: HistoryTarget, sets: &Sets) -> Result<Self, String> {
+        let param = |key: &str| {
+            card.parameters
+                .iter()
+                .find(|p| p.key.eq_ignore_ascii_case(key))
+                .and_then(|p| p.value.clone())
+        };
+        let (set_key, all, supported): (_, _, &[&str]) = match target {
+            HistoryTarget::Nodes => ("NSET", "NALL", &["U", "RF"]),
+            HistoryTarget::Elements => ("ELSET", "EALL", &["S", "E", "ME", "SF"]),
+        };
+        let set = param(set_key).ok_or(format!(
+            "*{} requires the {} parameter",
+            card.keyword, set_key
+        ))?;
+        let members = match target {
+            HistoryTarget::Nodes => sets
+                .node_sets
+                .values()
+                .find(|s| s.name.eq_ignore_ascii_case(&set))
+                .map(|s| &s.nodes),
+            HistoryTarget::Elements => sets
+                .element_sets
+                .values()
+                .find(|s| s.name.eq_ignore_ascii_case(&set))
+                .map(|s| &s.elements),
+        };
+        let ids = match members {
+            Some(members) => Some(members.iter().copied().collect()),
+            None if set.eq_ignore_ascii_case(all) => None,
+            None => return Err(format!("*{}: set {} not found", card.keyword, set)),
+        };
+        let frequency = match param("FREQUENCY") {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map_err(|_| format!("*{}: invalid FREQUENCY {}", card.keyword, value))?,
+            None => 1,
+        };
+        let totals = match param("TOTALS").map(|v| v.trim().to_ascii_uppercase()) {
+            None => PrintTotals::No,
+            Some(value) => match value.as_str() {
+                "NO" => PrintTotals::No,
+                "YES" => PrintTotals::Yes,
+                "ONLY" => PrintTotals::Only,
+                _ => return Err(format!("*{}: invalid TOTALS {}", card.keyword, value)),
+            },
+        };
+        let variables: Vec<String> = card
+            .data_lines
+            .iter()
+            .flat_map(|line| line.split(','))
+            .map(|v| v.trim().to_ascii_uppercase())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if let Some(variable) = variables.iter().find(|v| !supported.contains(&v.as_str())) {
+            return Err(format!(
+                "*{}: output variable {} is not supported (supported: {})",
+                card.keyword,
+                variable,
+                supported.join(", ")
+            ));
+        }
+        Ok(Self {
+            target,
+            set,
+            ids,
+            variables,
+            frequency,
+            totals,
+        })
+    }
+
+    /// Whether this request prints in `increment` (counted from 1 per step)
+    pub fn is_due(&self, increment: i32) -> bool {
+        self.frequency > 0 && increment % self.frequency == 0
+    }
+
+    fn selects(&self, id: i32) -> bool {
+        self.ids.as_ref().is_none_or(|ids| ids.contains(&id))
+    }
+
+    /// The parts of `section` this request asks for, relabelled with its set
+    fn filter(&self, section: &DatSection) -> Vec<DatSection> {
+        let Some((variable, target)) = section_variable(section) else {
+            return Vec::new();
+        };
+        if target != self.target || !self.variables.iter().any(|v| v == variable) {
+            return Vec::new();
+        }
+        if let DatSection::Forces { values, .. } = section {
+            let values: Vec<_> = values
+                .iter()
+                .filter(|(id, _)| self.selects(*id))
+                .copied()
+                .collect();
+            let total = DatSection::total_force(self.set.clone(), &values);
+            let table = DatSection::Forces {
+                set: self.set.clone(),
+                values,
+            };
+            return match self.totals {
+                PrintTotals::No => vec![table],
+                PrintTotals::Yes => vec![table, total],
+                PrintTotals::Only => vec![total],
+            };
+        }
+        let set = self.set.clone();
+        let nodal = |values: &Vec<(i32, [f64; 3])>| {
+            values
+                .iter()
+                .filter(|(id, _)| self.selects(*id))
+                .copied()
+                .collect()
+        };
+        let points = |values: &Vec<(i32, i32, [f64; 6])>| {
+            values
+                .iter()
+                .filter(|(id, _, _)| self.selects(*id))
+                .copied()
+                .collect()
+        };
+        vec![match section {
+            DatSection::Displacements { values, .. } => DatSection::Displacements {
+                set,
+                values: nodal(values),
+            },
+            DatSection::Stresses { values, .. } => DatSection::Stresses {
+                set,
+                values: points(values),
+            },
+            DatSection::Strains { values, .. } => DatSection::Strains {
+                set,
+                values: points(values),
+            },
+            DatSection::MechanicalStrains { values, .. } => DatSection::MechanicalStrains {
+                set,
+                values: points(values),
+            },
+            DatSection::SectionForces { values, .. } => DatSection::SectionForces {
+                set,
+                values: points(values),
+            },
+            DatSection::Forces { .. }
+            | DatSection::TotalForce { .. }
+            | DatSection::NodalStresses { .. } => return Vec::new(),
+        }]
+    }
+}
+
+/// Print variable and target of a `.dat` section
+fn section_variable(section: &DatSection) -> Option<(&'static str, HistoryTarget)> {
+    match section {
+        DatSection::Displacements { .. } => Some(("U", HistoryTarget::Nodes)),
+        DatSection::Forces { .. } => Some(("RF", HistoryTarget::Nodes)),
+        DatSection::Stresses { .. } => Some(("S", HistoryTarget::Elements)),
+        DatSection::Strains { .. } => Some(("E", HistoryTarget::Elements)),
+        DatSection::MechanicalStrains { .. } => Some(("ME", HistoryTarget::Elements)),
+        DatSection::SectionForces { .. } => Some(("SF", HistoryTarget::Elements)),
+        DatSection::TotalForce { .. } | DatSection::NodalStresses { .. } => None,
+    }
+}
+
+/// One recorded value of the history table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub step: i32,
+    pub increment: i32,
+    pub time: f64,
+    /// Print variable, e.g. `U` or `S`
+    pub variable: String,
+    /// Node or element ID
+    pub id: i32,
+    /// Integration point or beam end for element variables
+    pub point: Option<i32>,
+    /// Components in `.dat` order
+    pub values: Vec<f64>,
+}
+
+/// History output collected over the increments of an analysis
+#[derive(Debug, Clone, Default)]
+pub struct HistoryOutput {
+    requests: Vec<HistoryRequest>,
+    steps: Vec<DatStep>,
+    records: Vec<HistoryRecord>,
+}
+
+impl HistoryOutput {
+    pub fn new(requests: Vec<HistoryRequest>) -> Self {
+        Self {
+            requests,
+            ..Default::default()
+        }
+    }
+
+    pub fn requests(&self) -> &[HistoryRequest] {
+        &self.requests
+    }
+
+    /// Record the requested parts of the full `.dat` tables of one increment
+    ///
+    /// Returns whether any request was due in this increment.
+    pub fn record(&mut self, increment: &DatStep) -> bool {
+        let sections: Vec<DatSection> = self
+            .requests
+            .iter()
+            .filter(|request| request.is_due(increment.increment))
+            .flat_map(|request| {
+                // Sections in the order of the requested variables
+                request.variables.iter().flat_map(move |variable| {
+                    increment
+                        .sections
+                        .iter()
+                        .filter(move |s| section_variable(s).is_some_and(|(v, _)| v == variable))
+                        .flat_map(|section| request.filter(section))
+                })
+            })
+            .collect();
+        if sections.is_empty() {
+            return false;
+        }
+
+        for section in &sections {
+            let Some((variable, _)) = section_variable(section) else {
+                continue;
+            };
+            let record = |id, point, values: &[f64]| HistoryRecord {
+                step: increment.step,
+                increment: increment.increment,
+                time: increment.time,
+                variable: variable.to_string(),
+                id,
+                point,
+                values: values.to_vec(),
+            };
+            match section {
+                DatSection::Displacements { values, .. } | DatSection::Forces { values, .. } => {
+                    for (id, v) in values {
+                        self.records.push(record(*id, None, v));
+                    }
+                }
+                DatSection::Stresses { values, .. }
+                | DatSection::Strains { values, .. }
+                | DatSection::MechanicalStrains { values, .. }
+                | DatSection::SectionForces { values, .. } => {
+                    for (id, point, v) in values {
+                        self.records.push(record(*id, Some(*point), v));
+                    }
+                }
+                DatSection::TotalForce { .. } | DatSection::NodalStresses { .. } => {}
+            }
+        }
+        self.steps.push(DatStep {
+            sections,
+            ..increment.clone()
+        });
+        true
+    }
+
+    /// `.dat` tables of the recorded increments
+    pub fn dat_steps(&self) -> &[DatStep] {
+        &self.steps
+    }
+
+    /// Recorded values in increment order
+    pub fn records(&self) -> &[HistoryRecord] {
+        &self.records
+    }
+
+    /// Write the table as CSV, one row per node or integration point and
+    /// variable, with up to six value columns
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "step,increment,time,variable,id,point,v1,v2,v3,v4,v5,v6"
+        )?;
+        for record in &self.records {
+            let point = record.point.map(|p| p.to_string()).unwrap_or_default();
+            let mut values: Vec<String> = record.values.iter().map(|v| format!("{v:e}")).collect();
+            values.resize(6, String::new());
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                record.step,
+                record.increment,
+                record.time,
+                record.variable,
+                record.id,
+                point,
+                values.join(",")
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the table as a JSON array of [`HistoryRecord`]s
+    pub fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, &self.records)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        writeln!(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK: &str = "\
+*NODE, NSET=Nall
+1, 0, 0, 0
+2, 1, 0, 0
+*NSET, NSET=TIP
+2
+*ELEMENT, TYPE=T3D2, ELSET=BAR
+1, 1, 2
+*STEP
+*STATIC
+*NODE PRINT, NSET=TIP, FREQUENCY=2
+U
+*EL PRINT, ELSET=EALL
+S, SF
+*END STEP
+";
+
+    fn increment(increment: i32) -> DatStep {
+        let u = increment as f64;
+        DatStep {
+            step: 1,
+            increment,
+            time: 0.25 * u,
+            sections: vec![
+                DatSection::Displacements {
+                    set: "NALL".to_string(),
+                    values: vec![(1, [0.0; 3]), (2, [u, 0.0, 0.0])],
+                },
+                DatSection::Forces {
+                    set: "NALL".to_string(),
+                    values: vec![(1, [-u, 0.0, 0.0])],
+                },
+                DatSection::Stresses {
+                    set: "EALL".to_string(),
+                    values: vec![(1, 1, [10.0 * u, 0.0, 0.0, 0.0, 0.0, 0.0])],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn reads_print_requests() {
+        let deck = Deck::parse_str(DECK).unwrap();
+        let requests = HistoryRequest::from_deck(&deck).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].target, HistoryTarget::Nodes);
+        assert_eq!(requests[0].ids, Some(BTreeSet::from([2])));
+        assert_eq!(requests[0].variables, vec!["U"]);
+        assert_eq!(requests[0].frequency, 2);
+        assert!(!requests[0].is_due(1) && requests[0].is_due(4));
+        assert_eq!(requests[1].ids, None);
+        assert_eq!(requests[1].variables, vec!["S", "SF"]);
+        assert_eq!(requests[1].frequency, 1);
+
+        let missing = Deck::parse_str("*NODE PRINT, NSET=FIX\nU\n").unwrap();
+        assert!(HistoryRequest::from_deck(&missing).is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_variables_and_totals() {
+        let err = HistoryRequest::from_deck(
+            &Deck::parse_str("*NODE\n1,0,0,0\n*NODE PRINT, NSET=NALL\nU, NT\n").unwrap(),
+        )
+        .unwrap_err();
+        assert!(err.contains("variable NT is not supported"), "{err}");
+        let err = HistoryRequest::from_deck(
+            &Deck::parse_str("*NODE\n1,0,0,0\n*EL PRINT, ELSET=EALL\nENER\n").unwrap(),
+        )
+        .unwrap_err();
+        assert!(err.contains("variable ENER is not supported"), "{err}");
+        assert!(
+            HistoryRequest::from_deck(
+                &Deck::parse_str("*NODE PRINT, NSET=NALL, TOTALS=SOME\nRF\n").unwrap()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn prints_reaction_totals_over_the_set() {
+        let forces = |totals: &str| {
+            let deck = Deck::parse_str(&format!(
+                "*NODE, NSET=NALL\n1, 0, 0, 0\n2, 1, 0, 0\n*NSET, NSET=FIX\n1\n\
+                 *NODE PRINT, NSET=FIX{totals}\nRF\n"
+            ))
+            .unwrap();
+            let mut history = HistoryOutput::new(HistoryRequest::from_deck(&deck).unwrap());
+            history.record(&increment(2));
+            history.dat_steps()[0].sections.clone()
+        };
+        let table = DatSection::Forces {
+            set: "FIX".to_string(),
+            values: vec![(1, [-2.0, 0.0, 0.0])],
+        };
+        let total = DatSection::TotalForce {
+            set: "FIX".to_string(),
+            total: [-2.0, 0.0, 0.0],
+        };
+        assert_eq!(forces(""), vec![table.clone()]);
+        assert_eq!(forces(", TOTALS=YES"), vec![table, total.clone()]);
+        assert_eq!(forces(", TOTALS=ONLY"), vec![total]);
+    }
+
+    #[test]
+    fn records_requested_values_at_their_frequency() {
+        let deck = Deck::parse_str(DECK).unwrap();
+        let mut history = HistoryOutput::new(HistoryRequest::from_deck(&deck).unwrap());
+        for i in 1..=4 {
+            assert!(history.record(&increment(i)));
+        }
+
+        // Stresses every increment, tip displacement every second one
+        assert_eq!(history.dat_steps().len(), 4);
+        assert_eq!(history.dat_steps()[0].sections.len(), 1);
+        assert_eq!(
+            history.dat_steps()[1].sections[0],
+            DatSection::Displacements {
+                set: "TIP".to_string(),
+                values: vec![(2, [2.0, 0.0, 0.0])],
+            }
+        );
+        let displacements: Vec<_> = history
+            .records()
+            .iter()
+            .filter(|r| r.variable == "U")
+            .map(|r| (r.increment, r.id, r.values[0]))
+            .collect();
+        assert_eq!(displacements, vec![(2, 2, 2.0), (4, 2, 4.0)]);
+        assert_eq!(history.records().len(), 6);
+
+        let mut csv = Vec::new();
+        history.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("step,increment,time,variable,id,point,v1"));
+        assert!(csv.contains("\n1,1,0.25,S,1,1,1e1,0e0,0e0,0e0,0e0,0e0\n"));
+        assert!(csv.contains("\n1,2,0.5,U,2,,2e0,0e0,0e0,,,\n"));
+
+        let mut json = Vec::new();
+        history.write_json(&mut json).unwrap();
+        let parsed: Vec<HistoryRecord> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed, history.records());
+    }
+}
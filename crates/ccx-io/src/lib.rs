@@ -1,24 +1,100 @@
 //! Output and restart I/O support for the CalculiX Rust migration.
 //!
 //! This crate provides:
-//! - lightweight DAT/STA/FRD output writers for migration-stage runs
+//! - lightweight DAT/STA output writers, live `.sta`/`.cvg` convergence logs,
+//!   and a complete FRD writer that can append steps to an existing file
+//! - ccx-compatible `.dat` result tables (displacements, forces, stresses, strains)
+//! - `.dat`/FRD output of the Rust solver's static displacements and stresses
 //! - JSON-based restart state persistence/loading
-//! - FRD (result file) reader for postprocessing
+//! - FRD (result file) reader for postprocessing, with step selection
+//! - Time-history output of `*NODE PRINT` / `*EL PRINT` requests as `.dat`
+//!   sections, CSV or JSON
+//! - Result fields of `.dat` tables on an FRD model, for visualizing `.dat`
+//!   output without an FRD file
+//! - Numerical FRD comparison with per-dataset tolerances, and of `.dat`
+//!   result tables against ccx `.dat.ref` files
+//! - Cross-validation of the Rust pipeline against an external ccx binary,
+//!   with a compatibility scoreboard over a fixture suite
+//! - Gmsh `.msh` (v2.2/v4.1) reader building solver meshes and sets
+//! - STL/OBJ/PLY surface import as shell or membrane meshes, and STL export
+//!   of mesh surfaces
+//! - FRD models of solver meshes and solver meshes of FRD models
 //! - VTK/VTU export for ParaView visualization
-//! - Postprocessing utilities (von Mises, principal stresses/strains)
+//! - XDMF time-series export with shared geometry and binary or HDF5 heavy
+//!   data (HDF5 with feature `hdf5`)
+//! - CGNS/HDF5 mesh and nodal field export (feature `cgns`)
+//! - Nastran OP2 result reading, OP2 → FRD conversion, BDF import and
+//!   INP → BDF export (feature `nastran`, on by default)
+//! - Path plots: nodal results sampled along a polyline, written as CSV
+//! - Result values at nodes and at points interpolated inside solid elements
+//! - Postprocessing utilities (von Mises, principal stresses/strains,
+//!   safety factors against yield, max/min envelopes over time)
 
+#[cfg(feature = "cgns")]
+pub mod cgns_writer;
+mod convergence;
+mod dat_compare;
+mod dat_fields;
+mod dat_writer;
+mod fortran;
+mod frd_compare;
+mod frd_mesh;
 pub mod frd_reader;
+pub mod gmsh_reader;
+mod harmonic;
+#[cfg(feature = "hdf5")]
+mod hdf5_file;
+mod history;
+#[cfg(feature = "nastran")]
+pub mod nastran;
 mod output;
+mod path_plot;
 pub mod postprocess;
+mod probe;
 mod restart;
+mod solver_results;
+mod stl_writer;
+pub mod surface_reader;
 pub mod vtk_writer;
+pub mod xdmf_writer;
+mod xvalidate;
 
+#[cfg(feature = "cgns")]
+pub use cgns_writer::{CgnsElementType, CgnsFile, CgnsSink, CgnsTree, CgnsWriter};
+pub use convergence::{ConvergenceMonitor, IncrementRecord, IterationRecord};
+pub use dat_compare::{
+    DatComparison, DatRow, DatTable, QuantityComparison, dat_compare, parse_dat_tables,
+};
+pub use dat_fields::dat_result_blocks;
+pub use dat_writer::{DatSection, DatStep, write_dat_results, write_dat_results_to};
+pub use frd_compare::{DatasetComparison, FrdComparison, FrdTolerances, frd_compare};
+pub use frd_mesh::{frd_mesh, mesh_frd};
 pub use frd_reader::{
     FrdElement, FrdFile, FrdHeader, ResultBlock, ResultDataset, ResultLocation,
+    calculix_node_order, frd_element_node_count, frd_node_order, standard_components,
+};
+pub use gmsh_reader::{GmshMesh, read_gmsh};
+pub use harmonic::{
+    HarmonicDof, HarmonicPoint, amplitude_phase_dataset, frequency_response_function,
+    harmonic_response, write_harmonic_csv,
 };
+pub use history::{HistoryOutput, HistoryRecord, HistoryRequest, HistoryTarget, PrintTotals};
 pub use output::{
-    JobReport, JobStatus, OutputBundle, write_dat, write_frd_stub, write_output_bundle, write_sta,
+    FrdFormat, JobReport, JobStatus, OutputBundle, append_frd_step, frd_element_type, write_dat,
+    write_frd, write_frd_stub, write_frd_to, write_frd_to_with_format, write_frd_with_format,
+    write_output_bundle, write_sta,
+};
+pub use path_plot::{PathSample, sample_path, write_path_csv};
+pub use postprocess::{
+    compute_mises_stress, compute_principal_axes, compute_principal_stresses, envelope,
+    envelope_datasets, principal_datasets, safety_factor_dataset, EnvelopeValue, PrincipalAxes,
+    TensorComponents, MAX_SAFETY_FACTOR,
 };
-pub use postprocess::{compute_mises_stress, compute_principal_stresses, TensorComponents};
+pub use probe::{PointProbe, ProbeValue, probe_node, probe_point};
 pub use restart::{RestartState, load_restart, save_restart};
+pub use solver_results::{static_dat_step, static_dat_steps, static_frd};
+pub use stl_writer::{stl_text, write_stl};
+pub use surface_reader::{SurfaceElement, SurfaceFormat, parse_surface, read_surface};
 pub use vtk_writer::{VtkFormat, VtkWriter};
+pub use xdmf_writer::{XdmfStorage, XdmfWriter};
+pub use xvalidate::{CrossValidator, Scoreboard, XvalidateOutcome, XvalidateStatus};
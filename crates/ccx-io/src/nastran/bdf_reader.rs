@@ -0,0 +1,839 @@
+//! Nastran bulk data (BDF) import into solver data structures.
+//!
+//! Builds [`Mesh`], [`Sets`], [`MaterialLibrary`] and
+//! [`BoundaryConditions`] directly from a BDF file, so Nastran models can be
+//! solved natively without going through an `.inp` deck.
+//!
+//! Input format: small-field (8 columns), large-field (`*`, 16 columns)
+//! and free-field (comma separated) cards with explicit or implicit
+//! continuations, Nastran-style reals (`7.85-9`, `1.D3`) and `INCLUDE`
+//! statements (resolved by [`BdfModel::from_file`]).
+//!
+//! Supported cards:
+//! - GRID (basic coordinate system only)
+//! - CROD, CBAR, CBEAM, CTRIA3, CTRIA6, CQUAD4, CQUAD8, CTETRA, CPENTA, CHEXA
+//! - PROD, PBAR, PBEAM, PSHELL, PSOLID (element → material assignment;
+//!   a PSHELL without bending material makes membranes)
+//! - MAT1
+//! - SPC, SPC1, SPCADD
+//! - FORCE, MOMENT, PLOAD4, GRAV, LOAD
+//!
+//! The `SPC =` and `LOAD =` case control requests select the constraint and
+//! load sets; without them every SPC and every directly defined load is
+//! applied unscaled. Elements are also collected into the element sets
+//! `EALL` and `PID<n>`, and all grids into the node set `NALL`.
+//!
+//! `PLOAD4` becomes a [`DistributedLoadType::Pressure`] load on the element
+//! id. For solids `parameters[0]` is the CalculiX face number; shell
+//! pressures are negated to the CalculiX sign convention and carry no
+//! parameters (see `bdf_writer` for the inverse mapping). `GRAV` becomes a
+//! [`DistributedLoadType::Gravity`] load on `EALL` with the direction in
+//! `parameters`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ccx_solver::boundary_conditions::{DistributedLoad, DistributedLoadType};
+use ccx_solver::{
+    BoundaryConditions, ConcentratedLoad, DisplacementBC, Element, ElementSet, ElementType,
+    Material, MaterialLibrary, Mesh, Node, NodeSet, Sets,
+};
+
+/// A property card, kept with its numeric fields after the material id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BdfProperty {
+    pub id: i32,
+    /// Card name, e.g. `PSHELL`
+    pub card: String,
+    pub material: Option<i32>,
+    /// Remaining fields (e.g. thickness, area), blank fields as 0.0
+    pub values: Vec<f64>,
+}
+
+/// Model built from a BDF file.
+#[derive(Debug, Clone)]
+pub struct BdfModel {
+    pub mesh: Mesh,
+    pub sets: Sets,
+    /// Materials named `MAT<id>`, assigned to elements through properties
+    pub materials: MaterialLibrary,
+    pub boundary_conditions: BoundaryConditions,
+    pub properties: BTreeMap<i32, BdfProperty>,
+}
+
+/// Read a BDF file, following `INCLUDE` statements.
+pub fn read_bdf<P: AsRef<Path>>(path: P) -> io::Result<BdfModel> {
+    BdfModel::from_file(path)
+}
+
+/// One bulk data card with its fields (continuations joined)
+#[derive(Debug, Clone, PartialEq)]
+struct BulkCard {
+    name: String,
+    fields: Vec<String>,
+}
+
+impl BulkCard {
+    fn field(&self, index: usize) -> &str {
+        self.fields.get(index).map(String::as_str).unwrap_or("")
+    }
+
+    fn int(&self, index: usize) -> Result<Option<i32>, String> {
+        let field = self.field(index);
+        if field.is_empty() {
+            return Ok(None);
+        }
+        field
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{}: invalid integer '{field}'", self.name))
+    }
+
+    fn required_int(&self, index: usize) -> Result<i32, String> {
+        self.int(index)?
+            .ok_or_else(|| format!("{}: missing integer field {}", self.name, index + 2))
+    }
+
+    fn real(&self, index: usize) -> Result<Option<f64>, String> {
+        let field = self.field(index);
+        if field.is_empty() {
+            return Ok(None);
+        }
+        parse_real(field)
+            .map(Some)
+            .ok_or_else(|| format!("{}: invalid real '{field}'", self.name))
+    }
+
+    fn real_or(&self, index: usize, default: f64) -> Result<f64, String> {
+        Ok(self.real(index)?.unwrap_or(default))
+    }
+
+    /// Non-blank integer fields from `start`
+    fn ints_from(&self, start: usize) -> Result<Vec<i32>, String> {
+        (start..self.fields.len())
+            .filter_map(|i| self.int(i).transpose())
+            .collect()
+    }
+}
+
+/// Load entries of one load set
+#[derive(Debug, Clone, Default)]
+struct LoadSet {
+    concentrated: Vec<ConcentratedLoad>,
+    distributed: Vec<DistributedLoad>,
+}
+
+impl BdfModel {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = read_with_includes(path.as_ref(), 0)?;
+        Self::parse_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn parse_str(text: &str) -> Result<Self, String> {
+        let (case_control, cards) = split_deck(text);
+        let spc_request = case_control_request(&case_control, "SPC");
+        let load_request = case_control_request(&case_control, "LOAD");
+
+        let mut mesh = Mesh::new();
+        let mut sets = Sets::new();
+        let mut materials = MaterialLibrary::new();
+        let mut properties = BTreeMap::new();
+        let mut element_cards = Vec::new();
+        let mut spcs: BTreeMap<i32, Vec<DisplacementBC>> = BTreeMap::new();
+        let mut spc_adds: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut loads: BTreeMap<i32, LoadSet> = BTreeMap::new();
+        let mut load_combinations: HashMap<i32, (f64, Vec<(f64, i32)>)> = HashMap::new();
+        let mut pressures = Vec::new();
+
+        for card in &cards {
+            match card.name.as_str() {
+                "GRID" => {
+                    let id = card.required_int(0)?;
+                    if card.int(1)?.unwrap_or(0) != 0 {
+                        return Err(format!(
+                            "GRID {id}: only the basic coordinate system is supported"
+                        ));
+                    }
+                    mesh.add_node(Node::new(
+                        id,
+                        card.real_or(2, 0.0)?,
+                        card.real_or(3, 0.0)?,
+                        card.real_or(4, 0.0)?,
+                    ));
+                }
+                "CROD" | "CBAR" | "CBEAM" | "CTRIA3" | "CTRIA6" | "CQUAD4" | "CQUAD8"
+                | "CTETRA" | "CPENTA" | "CHEXA" => element_cards.push(card),
+                "PROD" | "PBAR" | "PBEAM" | "PSHELL" | "PSOLID" => {
+                    let id = card.required_int(0)?;
+                    // Keyword fields such as PSOLID's `SMECH` count as blank.
+                    let values = card.fields[2.min(card.fields.len())..]
+                        .iter()
+                        .map(|f| parse_real(f).unwrap_or(0.0))
+                        .collect();
+                    properties.insert(
+                        id,
+                        BdfProperty {
+                            id,
+                            card: card.name.clone(),
+                            material: card.int(1)?,
+                            values,
+                        },
+                    );
+                }
+                "MAT1" => materials.add_material(mat1(card)?),
+                "SPC1" => {
+                    let sid = card.required_int(0)?;
+                    let dofs = dof_ranges(card.field(1));
+                    let nodes = thru_list(card, 2)?;
+                    let entry = spcs.entry(sid).or_default();
+                    for node in nodes {
+                        for &(first, last) in &dofs {
+                            entry.push(DisplacementBC::new(node, first, last, 0.0));
+                        }
+                    }
+                }
+                "SPC" => {
+                    let sid = card.required_int(0)?;
+                    let entry = spcs.entry(sid).or_default();
+                    // Up to two (grid, components, value) triples
+                    for start in [1, 4] {
+                        let Some(node) = card.int(start)? else {
+                            continue;
+                        };
+                        let value = card.real_or(start + 2, 0.0)?;
+                        for (first, last) in dof_ranges(card.field(start + 1)) {
+                            entry.push(DisplacementBC::new(node, first, last, value));
+                        }
+                    }
+                }
+                "SPCADD" => {
+                    spc_adds.insert(card.required_int(0)?, card.ints_from(1)?);
+                }
+                "FORCE" | "MOMENT" => {
+                    let sid = card.required_int(0)?;
+                    let node = card.required_int(1)?;
+                    if card.int(2)?.unwrap_or(0) != 0 {
+                        return Err(format!(
+                            "{} on grid {node}: only CID 0 is supported",
+                            card.name
+                        ));
+                    }
+                    let scale = card.real_or(3, 0.0)?;
+                    let offset = if card.name == "FORCE" { 1 } else { 4 };
+                    let entry = loads.entry(sid).or_default();
+                    for axis in 0..3 {
+                        let component = scale * card.real_or(4 + axis, 0.0)?;
+                        if component != 0.0 {
+                            entry.concentrated.push(ConcentratedLoad::new(
+                                node,
+                                offset + axis,
+                                component,
+                            ));
+                        }
+                    }
+                }
+                "PLOAD4" => pressures.push(card),
+                "GRAV" => {
+                    let sid = card.required_int(0)?;
+                    if card.int(1)?.unwrap_or(0) != 0 {
+                        return Err("GRAV: only CID 0 is supported".to_string());
+                    }
+                    let direction = [
+                        card.real_or(3, 0.0)?,
+                        card.real_or(4, 0.0)?,
+                        card.real_or(5, 0.0)?,
+                    ];
+                    loads
+                        .entry(sid)
+                        .or_default()
+                        .distributed
+                        .push(DistributedLoad {
+                            element: "EALL".to_string(),
+                            load_type: DistributedLoadType::Gravity,
+                            magnitude: card.real_or(2, 0.0)?,
+                            parameters: direction.to_vec(),
+                        });
+                }
+                "LOAD" => {
+                    let sid = card.required_int(0)?;
+                    let scale = card.real_or(1, 1.0)?;
+                    let mut terms = Vec::new();
+                    let mut i = 2;
+                    while i + 1 < card.fields.len() {
+                        if let (Some(factor), Some(set)) = (card.real(i)?, card.int(i + 1)?) {
+                            terms.push((factor, set));
+                        }
+                        i += 2;
+                    }
+                    load_combinations.insert(sid, (scale, terms));
+                }
+                _ => {}
+            }
+        }
+
+        // Elements need their properties to pick shell vs. membrane types.
+        let mut pid_sets: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+        for card in element_cards {
+            let id = card.required_int(0)?;
+            let pid = card.int(1)?.unwrap_or(id);
+            let property = properties.get(&pid);
+            let membrane = property.is_some_and(is_membrane);
+            let (element_type, nodes) = element_from_card(card, membrane)?;
+            mesh.add_element(Element::new(id, element_type, nodes))
+                .map_err(|e| format!("{} {id}: {e}", card.name))?;
+            pid_sets.entry(pid).or_default().push(id);
+            if let Some(mid) = property.and_then(|p| p.material) {
+                materials.assign_material(id, format!("MAT{mid}"));
+            }
+        }
+        mesh.validate()?;
+        mesh.calculate_dofs();
+
+        for card in pressures {
+            let sid = card.required_int(0)?;
+            let magnitude = card.real_or(2, 0.0)?;
+            let mut ids = vec![card.required_int(1)?];
+            if card.field(6).eq_ignore_ascii_case("THRU") {
+                let last = card.required_int(7)?;
+                ids = (ids[0]..=last).collect();
+            }
+            for id in ids {
+                let Some(element) = mesh.get_element(id) else {
+                    return Err(format!("PLOAD4 references missing element {id}"));
+                };
+                let load = pressure_load(element, magnitude, card)?;
+                loads.entry(sid).or_default().distributed.push(load);
+            }
+        }
+
+        let mut element_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        element_ids.sort_unstable();
+        let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        sets.add_element_set(ElementSet {
+            name: "EALL".to_string(),
+            elements: element_ids,
+        });
+        sets.add_node_set(NodeSet {
+            name: "NALL".to_string(),
+            nodes: node_ids,
+        });
+        for (pid, elements) in pid_sets {
+            sets.add_element_set(ElementSet {
+                name: format!("PID{pid}"),
+                elements,
+            });
+        }
+
+        let mut boundary_conditions = BoundaryConditions::new();
+        let spc_sets = match spc_request {
+            Some(sid) => spc_adds.get(&sid).cloned().unwrap_or_else(|| vec![sid]),
+            None => spcs.keys().copied().collect(),
+        };
+        for sid in spc_sets {
+            for bc in spcs.get(&sid).into_iter().flatten() {
+                boundary_conditions.add_displacement_bc(bc.clone());
+            }
+        }
+
+        let load_terms: Vec<(f64, i32)> = match load_request {
+            Some(sid) => match load_combinations.get(&sid) {
+                Some((scale, terms)) => terms.iter().map(|&(f, set)| (scale * f, set)).collect(),
+                None => vec![(1.0, sid)],
+            },
+            None => loads.keys().map(|&sid| (1.0, sid)).collect(),
+        };
+        for (factor, sid) in load_terms {
+            let Some(set) = loads.get(&sid) else {
+                continue;
+            };
+            for load in &set.concentrated {
+                boundary_conditions.add_concentrated_load(ConcentratedLoad::new(
+                    load.node,
+                    load.dof,
+                    factor * load.magnitude,
+                ));
+            }
+            for load in &set.distributed {
+                let mut load = load.clone();
+                load.magnitude *= factor;
+                boundary_conditions.add_distributed_load(load);
+            }
+        }
+
+        Ok(Self {
+            mesh,
+            sets,
+            materials,
+            boundary_conditions,
+            properties,
+        })
+    }
+}
+
+fn is_membrane(property: &BdfProperty) -> bool {
+    // PSHELL fields after MID1: T, MID2, ...; no MID2 means membrane only
+    property.card == "PSHELL" && property.values.get(1).is_none_or(|&mid2| mid2 == 0.0)
+}
+
+fn mat1(card: &BulkCard) -> Result<Material, String> {
+    let id = card.required_int(0)?;
+    let mut material = Material::new(format!("MAT{id}"));
+    let e = card.real(1)?;
+    let g = card.real(2)?;
+    let nu = card.real(3)?;
+    material.elastic_modulus = e.or_else(|| Some(2.0 * g? * (1.0 + nu?)));
+    material.poissons_ratio = nu.or_else(|| Some(e? / (2.0 * g?) - 1.0));
+    material.density = card.real(4)?;
+    material.thermal_expansion = card.real(5)?;
+    Ok(material)
+}
+
+/// Element type and CalculiX-ordered nodes of an element card
+fn element_from_card(card: &BulkCard, membrane: bool) -> Result<(ElementType, Vec<i32>), String> {
+    let node_fields = match card.name.as_str() {
+        "CROD" | "CBAR" | "CBEAM" => 2,
+        "CTRIA3" => 3,
+        "CQUAD4" => 4,
+        "CTRIA6" => 6,
+        "CQUAD8" => 8,
+        "CTETRA" => 10,
+        "CPENTA" => 15,
+        _ => 20,
+    };
+    // Later fields (orientation, thickness, ...) are not nodes.
+    let nodes: Vec<i32> = (2..card.fields.len().min(2 + node_fields))
+        .filter_map(|i| card.int(i).transpose())
+        .collect::<Result<_, _>>()?;
+    let shell = |regular, membrane_type| if membrane { membrane_type } else { regular };
+    let element_type = match (card.name.as_str(), nodes.len()) {
+        ("CROD", 2) => ElementType::T3D2,
+        ("CBAR" | "CBEAM", 2) => ElementType::B31,
+        ("CTRIA3", 3) => shell(ElementType::S3, ElementType::M3D3),
+        ("CTRIA6", 6) => shell(ElementType::S6, ElementType::M3D6),
+        ("CQUAD4", 4) => shell(ElementType::S4, ElementType::M3D4),
+        ("CQUAD8", 8) => shell(ElementType::S8, ElementType::M3D8),
+        ("CTETRA", 4) => ElementType::C3D4,
+        ("CTETRA", 10) => ElementType::C3D10,
+        ("CPENTA", 6) => ElementType::C3D6,
+        ("CPENTA", 15) => {
+            let mut order = nodes[..9].to_vec();
+            order.extend_from_slice(&nodes[12..15]);
+            order.extend_from_slice(&nodes[9..12]);
+            return Ok((ElementType::C3D15, order));
+        }
+        ("CHEXA", 8) => ElementType::C3D8,
+        ("CHEXA", 20) => {
+            // Nastran lists the vertical edges before the top mid-side nodes.
+            let mut order = nodes[..12].to_vec();
+            order.extend_from_slice(&nodes[16..20]);
+            order.extend_from_slice(&nodes[12..16]);
+            return Ok((ElementType::C3D20, order));
+        }
+        (name, count) => return Err(format!("{name} with {count} grids is not supported")),
+    };
+    Ok((element_type, nodes))
+}
+
+/// CalculiX face corners (0-based node indices) of solid elements
+const HEX_FACES: [&[usize]; 6] = [
+    &[0, 1, 2, 3],
+    &[4, 7, 6, 5],
+    &[0, 4, 5, 1],
+    &[1, 5, 6, 2],
+    &[2, 6, 7, 3],
+    &[3, 7, 4, 0],
+];
+const TET_FACES: [&[usize]; 4] = [&[0, 1, 2], &[0, 3, 1], &[1, 3, 2], &[2, 3, 0]];
+const PENTA_FACES: [&[usize]; 5] = [
+    &[0, 1, 2],
+    &[3, 4, 5],
+    &[0, 1, 4, 3],
+    &[1, 2, 5, 4],
+    &[2, 0, 3, 5],
+];
+
+fn pressure_load(
+    element: &Element,
+    magnitude: f64,
+    card: &BulkCard,
+) -> Result<DistributedLoad, String> {
+    let faces: &[&[usize]] = match element.element_type {
+        ElementType::C3D8 | ElementType::C3D20 => &HEX_FACES,
+        ElementType::C3D4 | ElementType::C3D10 => &TET_FACES,
+        ElementType::C3D6 | ElementType::C3D15 => &PENTA_FACES,
+        ElementType::T3D2 | ElementType::B31 | ElementType::B32 => {
+            return Err(format!("PLOAD4 on line element {}", element.id));
+        }
+        // Shells: PLOAD4 acts along the normal, CalculiX against it.
+        _ => {
+            return Ok(DistributedLoad {
+                element: element.id.to_string(),
+                load_type: DistributedLoadType::Pressure,
+                magnitude: -magnitude,
+                parameters: Vec::new(),
+            });
+        }
+    };
+    let g1 = card.int(6)?;
+    let g34 = card.int(7)?;
+    let position =
+        |grid: Option<i32>| grid.and_then(|g| element.nodes.iter().position(|&n| n == g));
+    let (g1, g34) = (position(g1), position(g34));
+    let tet = faces.len() == 4;
+    let face = faces.iter().position(|corners| match (g1, g34) {
+        // Tetrahedra: G1 on the face, G4 the vertex opposite to it
+        (Some(a), Some(b)) if tet => corners.contains(&a) && !corners.contains(&b),
+        // Quadrilateral faces: G1 and G3 diagonally opposite
+        (Some(a), Some(b)) => {
+            corners.len() == 4
+                && corners[(corners.iter().position(|&c| c == a).unwrap_or(0) + 2) % 4] == b
+                && corners.contains(&a)
+        }
+        // Triangular pentahedron faces: G1 alone
+        (Some(a), None) => corners.len() == 3 && corners.contains(&a),
+        _ => false,
+    });
+    let face =
+        face.ok_or_else(|| format!("PLOAD4 on element {}: cannot identify the face", element.id))?;
+    Ok(DistributedLoad {
+        element: element.id.to_string(),
+        load_type: DistributedLoadType::Pressure,
+        magnitude,
+        parameters: vec![(face + 1) as f64],
+    })
+}
+
+/// Contiguous DOF ranges of a component string such as `1235`
+fn dof_ranges(components: &str) -> Vec<(usize, usize)> {
+    let mut digits: Vec<usize> = components
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as usize)
+        .filter(|d| (1..=6).contains(d))
+        .collect();
+    digits.sort_unstable();
+    digits.dedup();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for d in digits {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == d => *last = d,
+            _ => ranges.push((d, d)),
+        }
+    }
+    ranges
+}
+
+/// Grid list starting at `start`, accepting the `G1 THRU G2` form
+fn thru_list(card: &BulkCard, start: usize) -> Result<Vec<i32>, String> {
+    if card.field(start + 1).eq_ignore_ascii_case("THRU") {
+        let first = card.required_int(start)?;
+        let last = card.required_int(start + 2)?;
+        return Ok((first..=last).collect());
+    }
+    card.ints_from(start)
+}
+
+/// Nastran real: accepts `1.5+3`, `-2.-4`, `1.D3` and plain integers
+fn parse_real(field: &str) -> Option<f64> {
+    let text = field.trim().to_ascii_uppercase().replace('D', "E");
+    if let Ok(value) = text.parse::<f64>() {
+        return Some(value);
+    }
+    // Implicit exponent: sign after the mantissa without an `E`
+    let bytes = text.as_bytes();
+    let split = (1..bytes.len())
+        .rev()
+        .find(|&i| (bytes[i] == b'+' || bytes[i] == b'-') && bytes[i - 1] != b'E')?;
+    format!("{}E{}", &text[..split], &text[split..])
+        .parse()
+        .ok()
+}
+
+/// Split a BDF into case control lines and bulk data cards
+fn split_deck(text: &str) -> (Vec<String>, Vec<BulkCard>) {
+    let has_bulk = text.lines().any(|l| {
+        l.trim_start()
+            .to_ascii_uppercase()
+            .starts_with("BEGIN BULK")
+    });
+    let mut in_bulk = !has_bulk;
+    let mut case_control = Vec::new();
+    let mut cards: Vec<BulkCard> = Vec::new();
+
+    for raw in text.lines() {
+        let line = raw.split('$').next().unwrap_or("").trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        let upper = line.trim_start().to_ascii_uppercase();
+        if !in_bulk {
+            if upper.starts_with("BEGIN BULK") {
+                in_bulk = true;
+            } else {
+                case_control.push(upper);
+            }
+            continue;
+        }
+        if upper.starts_with("ENDDATA") {
+            break;
+        }
+        if upper.starts_with("BEGIN BULK") {
+            continue;
+        }
+
+        let (first, fields) = split_line(line);
+        let continuation = first.is_empty() || first.starts_with('+') || first.starts_with('*');
+        match cards.last_mut() {
+            Some(card) if continuation => {
+                card.fields.extend(fields);
+            }
+            _ => cards.push(BulkCard {
+                name: first.trim_end_matches('*').to_ascii_uppercase(),
+                fields,
+            }),
+        }
+    }
+
+    for card in &mut cards {
+        while card.fields.last().is_some_and(|f| f.is_empty()) {
+            card.fields.pop();
+        }
+    }
+    (case_control, cards)
+}
+
+/// First field and the 8 data fields of one line, in any field format
+fn split_line(line: &str) -> (String, Vec<String>) {
+    let line = line.replace('\t', "        ");
+    let (first, mut fields) = if line.contains(',') {
+        let mut parts = line.split(',').map(|f| f.trim().to_string());
+        let first = parts.next().unwrap_or_default();
+        (first, parts.collect::<Vec<_>>())
+    } else {
+        let first = line
+            .get(..8.min(line.len()))
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let width = if first.ends_with('*') { 16 } else { 8 };
+        let count = if width == 16 { 4 } else { 8 };
+        let fields = (0..count)
+            .map(|i| {
+                let start = 8 + i * width;
+                line.get(start..(start + width).min(line.len()))
+                    .unwrap_or("")
+                    .trim()
+                    .to_string()
+            })
+            .collect();
+        (first, fields)
+    };
+    // Free-field lines may end with a continuation marker in field 10.
+    if fields.len() > 8 {
+        fields.truncate(8);
+    }
+    // A large-field line carries half a card line; pad to keep alignment.
+    let target = if first.ends_with('*') && !line.contains(',') {
+        4
+    } else {
+        8
+    };
+    fields.resize(target, String::new());
+    (first, fields)
+}
+
+/// Set id of a case control request such as `SPC = 1`
+fn case_control_request(lines: &[String], key: &str) -> Option<i32> {
+    lines.iter().find_map(|line| {
+        let (name, value) = line.split_once('=')?;
+        (name.trim() == key).then(|| value.trim().parse().ok())?
+    })
+}
+
+fn read_with_includes(path: &Path, depth: usize) -> io::Result<String> {
+    if depth > 16 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("INCLUDE nesting too deep at {}", path.display()),
+        ));
+    }
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.to_ascii_uppercase().starts_with("INCLUDE") {
+            let name = trimmed[7..].trim().trim_matches(['\'', '"']);
+            out.push_str(&read_with_includes(&base.join(name), depth + 1)?);
+        } else {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLATE: &str = "\
+SOL 101
+CEND
+SUBCASE 1
+  SPC = 1
+  LOAD = 10
+BEGIN BULK
+$ small-field, free-field and large-field cards
+GRID           1              0.      0.      0.
+GRID,2,,1.,0.,0.
+GRID*                  3                             1.0             1.0
+*                     0.
+GRID,4,,0.,1.,0.
+GRID,5,,0.,0.,1.
+CQUAD4,1,1,1,2,3,4
+CBAR,2,2,1,5,1.,0.,0.
+PSHELL,1,1,0.5,1,,1
+PBAR,2,1,0.01,1.-5,1.-5
+MAT1,1,2.1+5,,0.3,7.85-9
+SPC1,1,123456,1,THRU,2
+SPC,1,4,3,0.25
+SPC,2,3,1
+FORCE,7,3,,10.,0.,0.,-1.
+MOMENT,7,5,,2.,1.,0.,0.
+PLOAD4,7,1,3.
+LOAD,10,2.,0.5,7
+ENDDATA
+";
+
+    #[test]
+    fn builds_mesh_materials_and_sets() {
+        let model = BdfModel::parse_str(PLATE).expect("parse bdf");
+        assert_eq!(model.mesh.nodes.len(), 5);
+        assert_eq!(model.mesh.get_node(3).unwrap().coords(), [1.0, 1.0, 0.0]);
+        assert_eq!(
+            model.mesh.get_element(1).unwrap().element_type,
+            ElementType::S4
+        );
+        assert_eq!(
+            model.mesh.get_element(2).unwrap().element_type,
+            ElementType::B31
+        );
+
+        let steel = model.materials.get_material("MAT1").expect("MAT1");
+        assert_eq!(steel.elastic_modulus, Some(2.1e5));
+        assert_eq!(steel.poissons_ratio, Some(0.3));
+        assert_eq!(steel.density, Some(7.85e-9));
+        assert_eq!(
+            model
+                .materials
+                .get_element_material(2)
+                .map(|m| m.name.as_str()),
+            Some("MAT1")
+        );
+
+        assert_eq!(model.sets.get_elements("EALL"), Some(&[1, 2][..]));
+        assert_eq!(model.sets.get_elements("PID2"), Some(&[2][..]));
+        assert_eq!(model.properties[&1].values[0], 0.5);
+    }
+
+    #[test]
+    fn applies_selected_constraints_and_scaled_loads() {
+        let model = BdfModel::parse_str(PLATE).expect("parse bdf");
+        let bcs = &model.boundary_conditions;
+
+        // SPC set 2 is not requested.
+        assert_eq!(bcs.displacement_bcs.len(), 3);
+        assert_eq!(bcs.displacement_bcs[0], DisplacementBC::new(1, 1, 6, 0.0));
+        assert_eq!(bcs.displacement_bcs[2], DisplacementBC::new(4, 3, 3, 0.25));
+
+        // LOAD 10 = 2.0 * 0.5 * set 7
+        assert_eq!(
+            bcs.concentrated_loads[0],
+            ConcentratedLoad::new(3, 3, -10.0)
+        );
+        assert_eq!(bcs.concentrated_loads[1], ConcentratedLoad::new(5, 4, 2.0));
+        let pressure = &bcs.distributed_loads[0];
+        assert_eq!(pressure.load_type, DistributedLoadType::Pressure);
+        assert_eq!((pressure.element.as_str(), pressure.magnitude), ("1", -3.0));
+    }
+
+    #[test]
+    fn maps_solid_pressure_faces_and_quadratic_nodes() {
+        let mut bdf = String::from("BEGIN BULK\n");
+        for id in 1..=20 {
+            bdf.push_str(&format!("GRID,{id},,{}.,0.,0.\n", id));
+        }
+        bdf.push_str("CHEXA,1,1,1,2,3,4,5,6\n,7,8,9,10,11,12,13,14\n,15,16,17,18,19,20\n");
+        bdf.push_str("PSOLID,1,1\nPLOAD4,1,1,5.,,,,5,7\nGRAV,1,,9.81,0.,0.,-1.\nENDDATA\n");
+        let model = BdfModel::parse_str(&bdf).expect("parse bdf");
+
+        let hex = model.mesh.get_element(1).unwrap();
+        assert_eq!(hex.element_type, ElementType::C3D20);
+        assert_eq!(&hex.nodes[12..], &[17, 18, 19, 20, 13, 14, 15, 16]);
+
+        let loads = &model.boundary_conditions.distributed_loads;
+        let pressure = loads
+            .iter()
+            .find(|l| l.load_type == DistributedLoadType::Pressure)
+            .unwrap();
+        assert_eq!(
+            (pressure.magnitude, pressure.parameters.clone()),
+            (5.0, vec![2.0])
+        );
+        let gravity = loads
+            .iter()
+            .find(|l| l.load_type == DistributedLoadType::Gravity)
+            .unwrap();
+        assert_eq!(gravity.element, "EALL");
+        assert_eq!(gravity.parameters, vec![0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn parses_nastran_reals() {
+        assert_eq!(parse_real("1.5+3"), Some(1500.0));
+        assert_eq!(parse_real("-2.-4"), Some(-2e-4));
+        assert_eq!(parse_real("1.D3"), Some(1000.0));
+        assert_eq!(parse_real("7"), Some(7.0));
+        assert_eq!(parse_real("1.E-2"), Some(0.01));
+        assert_eq!(parse_real("abc"), None);
+    }
+
+    #[test]
+    fn round_trips_through_bdf_writer() {
+        use super::super::InpToBdfConverter;
+
+        let deck = ccx_inp::Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,1,0\n4,0,1,0\n5,0,0,1\n6,1,0,1\n7,1,1,1\n8,0,1,1\n\
+             *ELEMENT,TYPE=C3D8,ELSET=EALL\n1,1,2,3,4,5,6,7,8\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000.,0.3\n\
+             *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+             *STEP\n*STATIC\n*BOUNDARY\n1,1,3\n*DLOAD\n1,P4,2.5\n*CLOAD\n7,2,4.\n*END STEP\n",
+        )
+        .expect("deck");
+        let bdf = InpToBdfConverter::new(&deck).convert().expect("to bdf");
+        let model = BdfModel::parse_str(&bdf).expect("read back");
+
+        assert_eq!(
+            model.mesh.get_element(1).unwrap().nodes,
+            (1..=8).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            model.boundary_conditions.displacement_bcs,
+            vec![DisplacementBC::new(1, 1, 3, 0.0)]
+        );
+        assert_eq!(
+            model.boundary_conditions.concentrated_loads,
+            vec![ConcentratedLoad::new(7, 2, 4.0)]
+        );
+        let pressure = &model.boundary_conditions.distributed_loads[0];
+        assert_eq!(
+            (pressure.magnitude, pressure.parameters.clone()),
+            (2.5, vec![4.0])
+        );
+    }
+}
@@ -0,0 +1,958 @@
+//! CalculiX input deck → Nastran bulk data (BDF) export.
+//!
+//! The reverse of the BDF → INP conversion: a parsed [`Deck`] is written as
+//! a free-field `SOL 101` (or `SOL 103` for `*FREQUENCY`) input file so the
+//! same model can be run through Nastran for cross-solver verification.
+//!
+//! Translated cards:
+//! - `*NODE` → `GRID`
+//! - `*ELEMENT` → `CTETRA`, `CPENTA`, `CHEXA`, `CTRIA3/6`, `CQUAD4/8`,
+//!   `CBAR`, `CROD` (second-order node order converted where it differs)
+//! - `*SOLID/SHELL/MEMBRANE/BEAM SECTION` → `PSOLID`, `PSHELL`, `PBAR`,
+//!   `PROD`
+//! - `*MATERIAL` + `*ELASTIC/*DENSITY/*EXPANSION` → `MAT1`
+//! - `*BOUNDARY` → `SPC1` (zero) / `SPC` (prescribed value)
+//! - `*CLOAD` → `FORCE` / `MOMENT`
+//! - `*DLOAD` pressure → `PLOAD4`, `GRAV` → `GRAV`
+//!
+//! Everything else is skipped; cards that cannot be represented are listed
+//! as `$` comments in the bulk data section.
+//!
+//! Sign conventions: a positive solid-face pressure acts into the element in
+//! both codes. For shells, CalculiX pressure acts against the element normal
+//! while `PLOAD4` acts along it, so shell pressures are negated.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ccx_inp::{Card, Deck};
+use ccx_solver::{ElementType, MaterialLibrary, MeshBuilder};
+
+/// Load and constraint set id used for `SPC`, `LOAD` and `METHOD`.
+const SET_ID: i32 = 1;
+
+/// Converts a CalculiX input deck to Nastran bulk data.
+pub struct InpToBdfConverter<'a> {
+    deck: &'a Deck,
+}
+
+/// A property card derived from one section definition.
+struct Property {
+    id: i32,
+    elset: String,
+    kind: PropertyKind,
+    material: Option<i32>,
+}
+
+enum PropertyKind {
+    Solid,
+    /// Shell or membrane thickness; membranes carry no bending material.
+    Shell {
+        thickness: f64,
+        membrane: bool,
+    },
+    Rod {
+        area: f64,
+    },
+    Bar {
+        values: [f64; 4],
+        orientation: [f64; 3],
+    },
+}
+
+impl<'a> InpToBdfConverter<'a> {
+    pub fn new(deck: &'a Deck) -> Self {
+        Self { deck }
+    }
+
+    /// Write the converted model to `path`.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let text = self
+            .convert()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// Convert the deck to BDF text.
+    pub fn convert(&self) -> Result<String, String> {
+        let mesh = MeshBuilder::build_from_deck(self.deck)?;
+        let materials = MaterialLibrary::build_from_deck(self.deck)?;
+        let (node_sets, element_sets) = collect_sets(self.deck)?;
+
+        let mut notes = Vec::new();
+        let mut bulk = String::new();
+
+        // Materials, numbered in deck order.
+        let mut material_ids = HashMap::new();
+        for name in material_names(self.deck) {
+            let id = material_ids.len() as i32 + 1;
+            material_ids.insert(name.to_uppercase(), id);
+            let Some(material) = materials.get_material(&name) else {
+                continue;
+            };
+            let Some(e) = material.elastic_modulus else {
+                notes.push(format!("material {name} has no *ELASTIC data"));
+                continue;
+            };
+            push_comment(&mut bulk, &format!("MATERIAL {name}"));
+            push_card(
+                &mut bulk,
+                "MAT1",
+                &[
+                    int(id),
+                    real(e),
+                    String::new(),
+                    opt_real(material.poissons_ratio),
+                    opt_real(material.density),
+                    opt_real(material.thermal_expansion),
+                ],
+            );
+        }
+
+        // Properties and element → property assignment.
+        let properties = self.properties(&material_ids, &mut notes)?;
+        let mut element_property = HashMap::new();
+        for property in &properties {
+            let elements = element_sets
+                .get(&property.elset.to_uppercase())
+                .ok_or_else(|| format!("Unknown element set in section: {}", property.elset))?;
+            for &id in elements {
+                element_property.insert(id, property.id);
+            }
+        }
+        for property in &properties {
+            write_property(&mut bulk, property);
+        }
+
+        // Nodes.
+        let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        for id in &node_ids {
+            let node = &mesh.nodes[id];
+            push_card(
+                &mut bulk,
+                "GRID",
+                &[
+                    int(*id),
+                    String::new(),
+                    real(node.x),
+                    real(node.y),
+                    real(node.z),
+                ],
+            );
+        }
+
+        // Elements.
+        let fallback_pid = properties.len() as i32 + 1;
+        let mut unassigned = 0usize;
+        let mut element_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        element_ids.sort_unstable();
+        for id in &element_ids {
+            let element = &mesh.elements[id];
+            let pid = match element_property.get(id) {
+                Some(&pid) => pid,
+                None => {
+                    unassigned += 1;
+                    fallback_pid
+                }
+            };
+            let orientation = properties.iter().find_map(|p| match p.kind {
+                PropertyKind::Bar { orientation, .. } if p.id == pid => Some(orientation),
+                _ => None,
+            });
+            write_element(
+                &mut bulk,
+                element.element_type,
+                *id,
+                pid,
+                &element.nodes,
+                orientation,
+            )
+            .unwrap_or_else(|| {
+                notes.push(format!(
+                    "element {id} of type {:?} has no Nastran equivalent",
+                    element.element_type
+                ))
+            });
+        }
+        if unassigned > 0 {
+            notes.push(format!(
+                "{unassigned} element(s) without section reference PID {fallback_pid}"
+            ));
+        }
+
+        // Constraints and loads.
+        let resolve_nodes = |field: &str| -> Result<Vec<i32>, String> {
+            match field.parse::<i32>() {
+                Ok(id) => Ok(vec![id]),
+                Err(_) => node_sets
+                    .get(&field.to_uppercase())
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown node or node set: {field}")),
+            }
+        };
+        let resolve_elements = |field: &str| -> Result<Vec<i32>, String> {
+            match field.parse::<i32>() {
+                Ok(id) => Ok(vec![id]),
+                Err(_) => element_sets
+                    .get(&field.to_uppercase())
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown element or element set: {field}")),
+            }
+        };
+
+        let mut has_spc = false;
+        let mut has_load = false;
+        let mut fixed: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+        for card in self.cards("BOUNDARY") {
+            for line in &card.data_lines {
+                let fields = split_fields(line);
+                if fields.len() < 2 {
+                    return Err(format!("Invalid BOUNDARY line: {line}"));
+                }
+                let first = parse_dof(&fields[1], line)?;
+                let last = match fields.get(2).filter(|f| !f.is_empty()) {
+                    Some(field) => parse_dof(field, line)?,
+                    None => first,
+                };
+                let value = match fields.get(3).filter(|f| !f.is_empty()) {
+                    Some(field) => parse_real(field, line)?,
+                    None => 0.0,
+                };
+                let dofs: String = (first..=last.min(6)).map(|d| d.to_string()).collect();
+                if dofs.is_empty() {
+                    notes.push(format!("non-structural BOUNDARY skipped: {line}"));
+                    continue;
+                }
+                for node in resolve_nodes(&fields[0])? {
+                    has_spc = true;
+                    if value == 0.0 {
+                        fixed.entry(dofs.clone()).or_default().push(node);
+                    } else {
+                        push_card(
+                            &mut bulk,
+                            "SPC",
+                            &[int(SET_ID), int(node), dofs.clone(), real(value)],
+                        );
+                    }
+                }
+            }
+        }
+        for (dofs, nodes) in fixed {
+            let mut fields = vec![int(SET_ID), dofs];
+            fields.extend(nodes.into_iter().map(int));
+            push_card(&mut bulk, "SPC1", &fields);
+        }
+
+        for card in self.cards("CLOAD") {
+            for line in &card.data_lines {
+                let fields = split_fields(line);
+                if fields.len() < 3 {
+                    return Err(format!("Invalid CLOAD line: {line}"));
+                }
+                let dof = parse_dof(&fields[1], line)?;
+                let magnitude = parse_real(&fields[2], line)?;
+                let (name, axis) = match dof {
+                    1..=3 => ("FORCE", dof - 1),
+                    4..=6 => ("MOMENT", dof - 4),
+                    _ => {
+                        notes.push(format!("non-structural CLOAD skipped: {line}"));
+                        continue;
+                    }
+                };
+                let mut direction = [0.0; 3];
+                direction[axis] = 1.0;
+                for node in resolve_nodes(&fields[0])? {
+                    has_load = true;
+                    push_card(
+                        &mut bulk,
+                        name,
+                        &[
+                            int(SET_ID),
+                            int(node),
+                            String::new(),
+                            real(magnitude),
+                            real(direction[0]),
+                            real(direction[1]),
+                            real(direction[2]),
+                        ],
+                    );
+                }
+            }
+        }
+
+        for card in self.cards("DLOAD") {
+            for line in &card.data_lines {
+                let fields = split_fields(line);
+                if fields.len() < 3 {
+                    return Err(format!("Invalid DLOAD line: {line}"));
+                }
+                let label = fields[1].to_uppercase();
+                let magnitude = parse_real(&fields[2], line)?;
+                if label == "GRAV" {
+                    let direction: Vec<f64> = fields[3..]
+                        .iter()
+                        .take(3)
+                        .map(|f| parse_real(f, line))
+                        .collect::<Result<_, _>>()?;
+                    if direction.len() < 3 {
+                        return Err(format!("GRAV load needs a direction: {line}"));
+                    }
+                    has_load = true;
+                    let mut values = vec![int(SET_ID), String::new(), real(magnitude)];
+                    values.extend(direction.into_iter().map(real));
+                    push_card(&mut bulk, "GRAV", &values);
+                    continue;
+                }
+                for id in resolve_elements(&fields[0])? {
+                    let element = mesh
+                        .get_element(id)
+                        .ok_or_else(|| format!("DLOAD references missing element {id}"))?;
+                    match pressure_fields(element.element_type, &element.nodes, &label) {
+                        Some(PressureFace::Shell) => {
+                            has_load = true;
+                            push_card(
+                                &mut bulk,
+                                "PLOAD4",
+                                &[int(SET_ID), int(id), real(-magnitude)],
+                            );
+                        }
+                        Some(PressureFace::Solid { g1, g34 }) => {
+                            has_load = true;
+                            push_card(
+                                &mut bulk,
+                                "PLOAD4",
+                                &[
+                                    int(SET_ID),
+                                    int(id),
+                                    real(magnitude),
+                                    String::new(),
+                                    String::new(),
+                                    String::new(),
+                                    int(g1),
+                                    g34.map(int).unwrap_or_default(),
+                                ],
+                            );
+                        }
+                        None => notes.push(format!("DLOAD skipped: {line} (element {id})")),
+                    }
+                }
+            }
+        }
+
+        let mut out = String::new();
+        out.push_str("$ Nastran bulk data exported from a CalculiX input deck\n");
+        let frequency = self.cards("FREQUENCY").next();
+        out.push_str(if frequency.is_some() {
+            "SOL 103\n"
+        } else {
+            "SOL 101\n"
+        });
+        out.push_str("CEND\n");
+        if let Some(title) = self
+            .cards("HEADING")
+            .next()
+            .and_then(|card| card.data_lines.first())
+        {
+            out.push_str(&format!("TITLE = {}\n", title.trim()));
+        }
+        out.push_str("ECHO = NONE\n");
+        out.push_str("SUBCASE 1\n");
+        if has_spc {
+            out.push_str(&format!("  SPC = {SET_ID}\n"));
+        }
+        if has_load {
+            out.push_str(&format!("  LOAD = {SET_ID}\n"));
+        }
+        if frequency.is_some() {
+            out.push_str(&format!("  METHOD = {SET_ID}\n"));
+        }
+        out.push_str("  DISPLACEMENT = ALL\n");
+        out.push_str("  STRESS = ALL\n");
+        out.push_str("BEGIN BULK\n");
+        out.push_str("PARAM,POST,-1\n");
+        if let Some(card) = frequency {
+            let modes = card
+                .data_lines
+                .first()
+                .and_then(|line| {
+                    split_fields(line)
+                        .first()
+                        .and_then(|f| f.parse::<i32>().ok())
+                })
+                .unwrap_or(10);
+            push_card(
+                &mut out,
+                "EIGRL",
+                &[int(SET_ID), String::new(), String::new(), int(modes)],
+            );
+        }
+        for note in notes {
+            push_comment(&mut out, &format!("WARNING: {note}"));
+        }
+        out.push_str(&bulk);
+        out.push_str("ENDDATA\n");
+        Ok(out)
+    }
+
+    fn cards<'b>(&'b self, keyword: &'b str) -> impl Iterator<Item = &'a Card> + 'b {
+        self.deck
+            .cards
+            .iter()
+            .filter(move |card| card.keyword.eq_ignore_ascii_case(keyword))
+    }
+
+    /// One property per section card, numbered in deck order.
+    fn properties(
+        &self,
+        material_ids: &HashMap<String, i32>,
+        notes: &mut Vec<String>,
+    ) -> Result<Vec<Property>, String> {
+        let mut properties = Vec::new();
+        for card in &self.deck.cards {
+            let keyword = card.keyword.to_uppercase();
+            if !keyword.ends_with(" SECTION") {
+                continue;
+            }
+            let Some(elset) = parameter(card, "ELSET") else {
+                continue;
+            };
+            let material = parameter(card, "MATERIAL")
+                .and_then(|name| material_ids.get(&name.to_uppercase()).copied());
+            let first_line: Vec<f64> = card
+                .data_lines
+                .first()
+                .map(|line| {
+                    split_fields(line)
+                        .iter()
+                        .filter_map(|f| f.parse::<f64>().ok())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let kind = match keyword.as_str() {
+                "SOLID SECTION" => match first_line.first() {
+                    // A solid section with an area belongs to truss elements.
+                    Some(&area) => PropertyKind::Rod { area },
+                    None => PropertyKind::Solid,
+                },
+                "SHELL SECTION" | "MEMBRANE SECTION" => PropertyKind::Shell {
+                    thickness: first_line.first().copied().unwrap_or(1.0),
+                    membrane: keyword == "MEMBRANE SECTION",
+                },
+                "BEAM SECTION" => {
+                    let shape = parameter(card, "SECTION")
+                        .unwrap_or_default()
+                        .to_uppercase();
+                    let Some(values) = beam_properties(&shape, &first_line) else {
+                        notes.push(format!("beam section {shape} on {elset} is not supported"));
+                        continue;
+                    };
+                    let orientation = card
+                        .data_lines
+                        .get(1)
+                        .map(|line| {
+                            split_fields(line)
+                                .iter()
+                                .filter_map(|f| f.parse::<f64>().ok())
+                                .collect::<Vec<_>>()
+                        })
+                        .filter(|v| v.len() >= 3)
+                        .map(|v| [v[0], v[1], v[2]])
+                        .unwrap_or([0.0, 0.0, -1.0]);
+                    PropertyKind::Bar {
+                        values,
+                        orientation,
+                    }
+                }
+                _ => {
+                    notes.push(format!("*{keyword} on {elset} is not supported"));
+                    continue;
+                }
+            };
+            properties.push(Property {
+                id: properties.len() as i32 + 1,
+                elset,
+                kind,
+                material,
+            });
+        }
+        Ok(properties)
+    }
+}
+
+/// Convert `deck` and write it to `path`.
+pub fn write_bdf<P: AsRef<Path>>(deck: &Deck, path: P) -> io::Result<()> {
+    InpToBdfConverter::new(deck).write(path)
+}
+
+/// Area, I1, I2 and J of a CalculiX beam cross-section.
+fn beam_properties(shape: &str, dims: &[f64]) -> Option<[f64; 4]> {
+    use std::f64::consts::PI;
+    match (shape, dims) {
+        ("RECT", [a, b, ..]) => {
+            let (long, short) = if a >= b { (a, b) } else { (b, a) };
+            let ratio = short / long;
+            let j =
+                long * short.powi(3) * (1.0 / 3.0 - 0.21 * ratio * (1.0 - ratio.powi(4) / 12.0));
+            Some([a * b, b * a.powi(3) / 12.0, a * b.powi(3) / 12.0, j])
+        }
+        ("CIRC", [r, ..]) => {
+            let i = PI * r.powi(4) / 4.0;
+            Some([PI * r * r, i, i, 2.0 * i])
+        }
+        ("PIPE", [r, t, ..]) => {
+            let inner = r - t;
+            let i = PI * (r.powi(4) - inner.powi(4)) / 4.0;
+            Some([PI * (r * r - inner * inner), i, i, 2.0 * i])
+        }
+        _ => None,
+    }
+}
+
+fn write_property(out: &mut String, property: &Property) {
+    let pid = int(property.id);
+    let mid = property.material.map(int).unwrap_or_default();
+    push_comment(out, &format!("SECTION {}", property.elset));
+    match &property.kind {
+        PropertyKind::Solid => push_card(out, "PSOLID", &[pid, mid]),
+        PropertyKind::Shell {
+            thickness,
+            membrane,
+        } => {
+            let mut fields = vec![pid, mid.clone(), real(*thickness)];
+            if !membrane {
+                fields.extend([mid.clone(), String::new(), mid]);
+            }
+            push_card(out, "PSHELL", &fields);
+        }
+        PropertyKind::Rod { area } => push_card(out, "PROD", &[pid, mid, real(*area)]),
+        PropertyKind::Bar { values, .. } => {
+            let mut fields = vec![pid, mid];
+            fields.extend(values.iter().copied().map(real));
+            push_card(out, "PBAR", &fields);
+        }
+    }
+}
+
+/// Write one element card, or return `None` for unsupported types.
+fn write_element(
+    out: &mut String,
+    element_type: ElementType,
+    id: i32,
+    pid: i32,
+    nodes: &[i32],
+    orientation: Option<[f64; 3]>,
+) -> Option<()> {
+    let (name, nodes): (&str, Vec<i32>) = match element_type {
+        ElementType::C3D4 | ElementType::C3D10 => ("CTETRA", nodes.to_vec()),
+        ElementType::C3D8 => ("CHEXA", nodes.to_vec()),
+        ElementType::C3D20 => {
+            // CalculiX lists the top mid-side nodes before the vertical
+            // edges; Nastran lists the vertical edges first.
+            let mut order = nodes[..12].to_vec();
+            order.extend_from_slice(&nodes[16..20]);
+            order.extend_from_slice(&nodes[12..16]);
+            ("CHEXA", order)
+        }
+        ElementType::C3D6 => ("CPENTA", nodes.to_vec()),
+        ElementType::C3D15 => {
+            let mut order = nodes[..9].to_vec();
+            order.extend_from_slice(&nodes[12..15]);
+            order.extend_from_slice(&nodes[9..12]);
+            ("CPENTA", order)
+        }
+        ElementType::S3 | ElementType::M3D3 => ("CTRIA3", nodes.to_vec()),
+        ElementType::S6 | ElementType::M3D6 => ("CTRIA6", nodes.to_vec()),
+        ElementType::S4 | ElementType::M3D4 => ("CQUAD4", nodes.to_vec()),
+        ElementType::S8 | ElementType::M3D8 => ("CQUAD8", nodes.to_vec()),
+        ElementType::T3D2 => ("CROD", nodes.to_vec()),
+        ElementType::B31 => {
+            let v = orientation.unwrap_or([0.0, 0.0, -1.0]);
+            let fields = [
+                int(id),
+                int(pid),
+                int(nodes[0]),
+                int(nodes[1]),
+                real(v[0]),
+                real(v[1]),
+                real(v[2]),
+            ];
+            push_card(out, "CBAR", &fields);
+            return Some(());
+        }
+        ElementType::B32 => return None,
+    };
+    let mut fields = vec![int(id), int(pid)];
+    fields.extend(nodes.into_iter().map(int));
+    push_card(out, name, &fields);
+    Some(())
+}
+
+enum PressureFace {
+    Shell,
+    /// `PLOAD4` G1 / G3-G4 grid selection of a solid face
+    Solid {
+        g1: i32,
+        g34: Option<i32>,
+    },
+}
+
+/// Map a CalculiX pressure label (`P`, `P1`..`P6`) to `PLOAD4` fields.
+fn pressure_fields(element_type: ElementType, nodes: &[i32], label: &str) -> Option<PressureFace> {
+    // Per face: a corner on the face, then the diagonally opposite corner
+    // of a quad face or, for tetrahedra, the vertex off the face.
+    const HEX: [(usize, Option<usize>); 6] = [
+        (0, Some(2)),
+        (4, Some(6)),
+        (0, Some(5)),
+        (1, Some(6)),
+        (2, Some(7)),
+        (3, Some(4)),
+    ];
+    const TET: [(usize, Option<usize>); 4] =
+        [(0, Some(3)), (0, Some(2)), (1, Some(0)), (2, Some(1))];
+    const PENTA: [(usize, Option<usize>); 5] = [
+        (0, None),
+        (3, None),
+        (0, Some(4)),
+        (1, Some(5)),
+        (2, Some(3)),
+    ];
+
+    let faces: &[(usize, Option<usize>)] = match element_type {
+        ElementType::C3D8 | ElementType::C3D20 => &HEX,
+        ElementType::C3D4 | ElementType::C3D10 => &TET,
+        ElementType::C3D6 | ElementType::C3D15 => &PENTA,
+        ElementType::S3
+        | ElementType::S4
+        | ElementType::S6
+        | ElementType::S8
+        | ElementType::M3D3
+        | ElementType::M3D4
+        | ElementType::M3D6
+        | ElementType::M3D8 => {
+            return (label == "P").then_some(PressureFace::Shell);
+        }
+        _ => return None,
+    };
+    let face: usize = label.strip_prefix('P')?.parse().ok()?;
+    let &(g1, g34) = faces.get(face.checked_sub(1)?)?;
+    Some(PressureFace::Solid {
+        g1: nodes[g1],
+        g34: g34.map(|i| nodes[i]),
+    })
+}
+
+/// Node and element sets by upper-case name, including the `NSET`/`ELSET`
+/// parameters of `*NODE` and `*ELEMENT` cards.
+type SetMap = HashMap<String, Vec<i32>>;
+
+fn collect_sets(deck: &Deck) -> Result<(SetMap, SetMap), String> {
+    let mut node_sets: SetMap = HashMap::new();
+    let mut element_sets: SetMap = HashMap::new();
+    for card in &deck.cards {
+        match card.keyword.to_uppercase().as_str() {
+            "NODE" => {
+                if let Some(name) = parameter(card, "NSET") {
+                    let ids = card
+                        .data_lines
+                        .iter()
+                        .filter_map(|line| split_fields(line).first()?.parse::<i32>().ok());
+                    node_sets
+                        .entry(name.to_uppercase())
+                        .or_default()
+                        .extend(ids);
+                }
+            }
+            "ELEMENT" => {
+                let Some(name) = parameter(card, "ELSET") else {
+                    continue;
+                };
+                let Some(element_type) =
+                    parameter(card, "TYPE").and_then(|t| ElementType::from_calculix_type(&t))
+                else {
+                    continue;
+                };
+                let fields: Vec<i32> = card
+                    .data_lines
+                    .iter()
+                    .flat_map(|line| split_fields(line))
+                    .filter_map(|f| f.parse().ok())
+                    .collect();
+                let ids = fields
+                    .chunks(element_type.num_nodes() + 1)
+                    .map(|record| record[0]);
+                element_sets
+                    .entry(name.to_uppercase())
+                    .or_default()
+                    .extend(ids);
+            }
+            "NSET" | "ELSET" => {
+                let key = if card.keyword.eq_ignore_ascii_case("NSET") {
+                    "NSET"
+                } else {
+                    "ELSET"
+                };
+                let Some(name) = parameter(card, key) else {
+                    continue;
+                };
+                let generate = card
+                    .parameters
+                    .iter()
+                    .any(|p| p.key.eq_ignore_ascii_case("GENERATE"));
+                let sets = if key == "NSET" {
+                    &mut node_sets
+                } else {
+                    &mut element_sets
+                };
+                let mut ids = Vec::new();
+                for line in &card.data_lines {
+                    let fields = split_fields(line);
+                    if generate {
+                        let range: Vec<i32> = fields
+                            .iter()
+                            .map(|f| f.parse())
+                            .collect::<Result<_, _>>()
+                            .map_err(|_| format!("Invalid GENERATE line in *{key}: {line}"))?;
+                        let (start, end) = match range[..] {
+                            [start, end, ..] => (start, end),
+                            _ => return Err(format!("Invalid GENERATE line in *{key}: {line}")),
+                        };
+                        let step = range.get(2).copied().unwrap_or(1).max(1);
+                        ids.extend((start..=end).step_by(step as usize));
+                        continue;
+                    }
+                    for field in fields {
+                        match field.parse::<i32>() {
+                            Ok(id) => ids.push(id),
+                            Err(_) => match sets.get(&field.to_uppercase()) {
+                                Some(members) => ids.extend_from_slice(members),
+                                None => return Err(format!("Unknown set in *{key}: {field}")),
+                            },
+                        }
+                    }
+                }
+                sets.entry(name.to_uppercase()).or_default().extend(ids);
+            }
+            _ => {}
+        }
+    }
+    Ok((node_sets, element_sets))
+}
+
+fn material_names(deck: &Deck) -> Vec<String> {
+    deck.cards
+        .iter()
+        .filter(|card| card.keyword.eq_ignore_ascii_case("MATERIAL"))
+        .filter_map(|card| parameter(card, "NAME"))
+        .collect()
+}
+
+fn parameter(card: &Card, key: &str) -> Option<String> {
+    card.parameters
+        .iter()
+        .find(|p| p.key.eq_ignore_ascii_case(key))
+        .and_then(|p| p.value.clone())
+}
+
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields: Vec<String> = line.split(',').map(|f| f.trim().to_string()).collect();
+    while fields.last().is_some_and(|f| f.is_empty()) {
+        fields.pop();
+    }
+    fields
+}
+
+fn parse_dof(field: &str, line: &str) -> Result<usize, String> {
+    field
+        .parse()
+        .map_err(|_| format!("Invalid degree of freedom '{field}' in: {line}"))
+}
+
+fn parse_real(field: &str, line: &str) -> Result<f64, String> {
+    field
+        .parse()
+        .map_err(|_| format!("Invalid number '{field}' in: {line}"))
+}
+
+fn int(value: i32) -> String {
+    value.to_string()
+}
+
+fn opt_real(value: Option<f64>) -> String {
+    value.map(real).unwrap_or_default()
+}
+
+/// Nastran real: always carries a decimal point.
+fn real(value: f64) -> String {
+    if value == 0.0 {
+        return "0.".to_string();
+    }
+    let magnitude = value.abs();
+    let text = if (1e-4..1e8).contains(&magnitude) {
+        format!("{value}")
+    } else {
+        format!("{value:E}")
+    };
+    match text.find(['.', 'E']) {
+        Some(i) if text.as_bytes()[i] == b'.' => text,
+        Some(i) => format!("{}.{}", &text[..i], &text[i..]),
+        None => format!("{text}."),
+    }
+}
+
+fn push_comment(out: &mut String, text: &str) {
+    out.push_str("$ ");
+    out.push_str(text);
+    out.push('\n');
+}
+
+/// Free-field card with 8 data fields per line; continuations start with `,`.
+fn push_card(out: &mut String, name: &str, fields: &[String]) {
+    out.push_str(name);
+    for (i, chunk) in fields.chunks(8).enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        for field in chunk {
+            out.push(',');
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE: &str = "\
+*HEADING
+Cube under pressure
+*NODE, NSET=NALL
+1, 0, 0, 0
+2, 1, 0, 0
+3, 1, 1, 0
+4, 0, 1, 0
+5, 0, 0, 1
+6, 1, 0, 1
+7, 1, 1, 1
+8, 0, 1, 1
+*ELEMENT, TYPE=C3D8, ELSET=EALL
+1, 1, 2, 3, 4, 5, 6, 7, 8
+*NSET, NSET=BASE
+1, 2, 3, 4
+*MATERIAL, NAME=STEEL
+*ELASTIC
+210000, 0.3
+*DENSITY
+7.85e-9
+*SOLID SECTION, ELSET=EALL, MATERIAL=STEEL
+*STEP
+*STATIC
+*BOUNDARY
+BASE, 1, 3
+7, 1, 1, 0.01
+*CLOAD
+8, 3, -100.
+*DLOAD
+1, P2, 5.0
+EALL, GRAV, 9810., 0, 0, -1
+*END STEP
+";
+
+    fn convert(source: &str) -> String {
+        let deck = Deck::parse_str(source).expect("deck parses");
+        InpToBdfConverter::new(&deck).convert().expect("converts")
+    }
+
+    #[test]
+    fn writes_case_control_and_bulk_data() {
+        let bdf = convert(CUBE);
+        assert!(bdf.contains("SOL 101\nCEND\nTITLE = Cube under pressure\n"));
+        assert!(bdf.contains("  SPC = 1\n  LOAD = 1\n"));
+        assert!(bdf.contains("MAT1,1,210000.,,0.3,7.85E-9,\n"));
+        assert!(bdf.contains("PSOLID,1,1\n"));
+        assert!(bdf.contains("GRID,7,,1.,1.,1.\n"));
+        assert!(bdf.contains("CHEXA,1,1,1,2,3,4,5,6\n,7,8\n"));
+        assert!(bdf.ends_with("ENDDATA\n"));
+    }
+
+    #[test]
+    fn translates_constraints_and_loads() {
+        let bdf = convert(CUBE);
+        assert!(bdf.contains("SPC1,1,123,1,2,3,4\n"));
+        assert!(bdf.contains("SPC,1,7,1,0.01\n"));
+        assert!(bdf.contains("FORCE,1,8,,-100.,0.,0.,1.\n"));
+        // Face 2 of a hexahedron is 5-8-7-6: G1 = 5, diagonal G34 = 7.
+        assert!(bdf.contains("PLOAD4,1,1,5.,,,,5,7\n"));
+        assert!(bdf.contains("GRAV,1,,9810.,0.,0.,-1.\n"));
+    }
+
+    #[test]
+    fn reorders_quadratic_hexahedron_nodes() {
+        let mut out = String::new();
+        let nodes: Vec<i32> = (1..=20).collect();
+        write_element(&mut out, ElementType::C3D20, 9, 2, &nodes, None).unwrap();
+        assert_eq!(
+            out,
+            "CHEXA,9,2,1,2,3,4,5,6\n,7,8,9,10,11,12,17,18\n,19,20,13,14,15,16\n"
+        );
+    }
+
+    #[test]
+    fn writes_shell_and_beam_properties() {
+        let source = "\
+*NODE, NSET=NALL
+1, 0, 0, 0
+2, 1, 0, 0
+3, 1, 1, 0
+4, 0, 1, 0
+*ELEMENT, TYPE=S4, ELSET=PLATE
+1, 1, 2, 3, 4
+*ELEMENT, TYPE=B31, ELSET=BEAM
+2, 1, 2
+*MATERIAL, NAME=Alu
+*ELASTIC
+70000, 0.33
+*SHELL SECTION, ELSET=PLATE, MATERIAL=ALU
+2.5
+*BEAM SECTION, ELSET=BEAM, MATERIAL=ALU, SECTION=CIRC
+1.
+0, 1, 0
+*STEP
+*FREQUENCY
+6
+*DLOAD
+PLATE, P, 1.5
+*END STEP
+";
+        let bdf = convert(source);
+        assert!(bdf.contains("SOL 103\n"));
+        assert!(bdf.contains("  METHOD = 1\n"));
+        assert!(bdf.contains("EIGRL,1,,,6\n"));
+        assert!(bdf.contains("PSHELL,1,1,2.5,1,,1\n"));
+        assert!(bdf.contains("PBAR,2,1,3.141592653589793,0.7853981633974483,"));
+        assert!(bdf.contains("CQUAD4,1,1,1,2,3,4\n"));
+        assert!(bdf.contains("CBAR,2,2,1,2,0.,1.,0.\n"));
+        assert!(bdf.contains("PLOAD4,1,1,-1.5\n"));
+    }
+
+    #[test]
+    fn formats_reals_with_decimal_point() {
+        assert_eq!(real(0.0), "0.");
+        assert_eq!(real(2.0), "2.");
+        assert_eq!(real(-1.25), "-1.25");
+        assert_eq!(real(1e20), "1.E20");
+        assert_eq!(real(2.5e-9), "2.5E-9");
+    }
+
+    #[test]
+    fn reports_unknown_sets() {
+        let deck = Deck::parse_str("*NODE\n1, 0, 0, 0\n*BOUNDARY\nMISSING, 1, 3\n").unwrap();
+        let err = InpToBdfConverter::new(&deck).convert().unwrap_err();
+        assert!(err.contains("MISSING"));
+    }
+}
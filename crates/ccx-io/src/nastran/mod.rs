@@ -0,0 +1,20 @@
+//! Native Nastran interoperability (feature `nastran`).
+//!
+//! Pure Rust replacements for the pyNastran-based tooling, so Nastran
+//! models and results can be converted and compared without a Python
+//! runtime.
+//!
+//! - [`op2`]: OP2 result tables (geometry, displacements, stresses)
+//! - [`bdf_reader`]: BDF bulk data → solver mesh, materials and loads
+//! - [`bdf_writer`]: INP deck → BDF bulk data export
+//! - [`op2_to_frd`]: OP2 results → FRD datasets
+
+pub mod bdf_reader;
+pub mod bdf_writer;
+pub mod op2;
+pub mod op2_to_frd;
+
+pub use bdf_reader::{BdfModel, BdfProperty, read_bdf};
+pub use bdf_writer::{InpToBdfConverter, write_bdf};
+pub use op2::{Op2Displacements, Op2Element, Op2File, Op2Stresses};
+pub use op2_to_frd::{Op2ToFrdConverter, op2_to_frd};
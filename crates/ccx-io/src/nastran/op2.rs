@@ -0,0 +1,584 @@
+//! Nastran OP2 (32-bit, `PARAM,POST,-1`) table reader.
+//!
+//! OP2 files are Fortran unformatted sequential files. Every value below is
+//! its own record framed by 4-byte length markers:
+//!
+//! ```text
+//! [3] date [7] "NASTRAN FORT TAPE ID CODE - " [2] label [-1] [0]   (optional header)
+//! per table:
+//!   [2] name [-1] [7] trailer [-2 1 0] header-record
+//!   [-3 1 0] record [-4 1 0] record ... [-n 1 0] [0]
+//! ```
+//!
+//! where each `record` is a word-count marker followed by one or more data
+//! blocks. Byte order is detected from the first marker.
+//!
+//! Supported tables:
+//! - `GEOM1*`: GRID
+//! - `GEOM2*`: CROD, CBAR, CTRIA3, CQUAD4, CTETRA, CPENTA, CHEXA
+//! - `OUG*` / `BOUGV1`: real displacements (table code 1)
+//! - `OES1*`: real stresses of CTRIA3/CQUAD4 (fibre 1) and solid centroids
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Element connectivity from a `GEOM2` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Op2Element {
+    pub id: i32,
+    /// Bulk data card name, e.g. `CQUAD4`
+    pub card: &'static str,
+    pub property: i32,
+    pub nodes: Vec<i32>,
+}
+
+/// Nodal displacements of one subcase / time / mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Op2Displacements {
+    pub subcase: i32,
+    /// Analysis approach code (1 static, 2 modes, 6 transient, ...)
+    pub approach: i32,
+    /// Time, frequency or mode number, depending on `approach`
+    pub time: f64,
+    pub title: String,
+    /// node id → (tx, ty, tz, rx, ry, rz)
+    pub values: BTreeMap<i32, [f64; 6]>,
+}
+
+/// Element stresses of one subcase / time / mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Op2Stresses {
+    pub subcase: i32,
+    pub approach: i32,
+    pub time: f64,
+    /// Nastran element type code (33 CQUAD4, 39 CTETRA, ...)
+    pub element_type: i32,
+    /// element id → (sxx, syy, szz, sxy, syz, szx)
+    pub values: BTreeMap<i32, [f64; 6]>,
+    /// element id → von Mises stress as reported by Nastran
+    pub von_mises: BTreeMap<i32, f64>,
+}
+
+/// Geometry and results read from an OP2 file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Op2File {
+    /// Names of all tables in file order
+    pub tables: Vec<String>,
+    pub grids: BTreeMap<i32, [f64; 3]>,
+    pub elements: BTreeMap<i32, Op2Element>,
+    pub displacements: Vec<Op2Displacements>,
+    pub stresses: Vec<Op2Stresses>,
+}
+
+impl Op2File {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = RecordReader::new(bytes)?;
+        let mut op2 = Op2File::default();
+
+        if reader.peek_marker()? == Some(3) {
+            reader.expect_markers(&[3])?;
+            reader.block()?;
+            reader.expect_markers(&[7])?;
+            reader.block()?;
+            reader.expect_markers(&[2])?;
+            reader.block()?;
+            reader.expect_markers(&[-1, 0])?;
+        }
+
+        while let Some(marker) = reader.peek_marker()? {
+            if marker == 0 {
+                reader.marker()?;
+                continue;
+            }
+            reader.expect_markers(&[2])?;
+            let name = String::from_utf8_lossy(&reader.block()?).trim().to_string();
+            reader.expect_markers(&[-1, 7])?;
+            reader.block()?;
+            reader.expect_markers(&[-2, 1, 0])?;
+            reader.record()?;
+
+            let mut records = Vec::new();
+            let mut subtable = -3;
+            loop {
+                reader.expect_markers(&[subtable, 1, 0])?;
+                if reader.peek_marker()? == Some(0) {
+                    reader.marker()?;
+                    break;
+                }
+                records.push(reader.record()?);
+                subtable -= 1;
+            }
+            op2.read_table(&name, &records, reader.big_endian);
+            op2.tables.push(name);
+        }
+        Ok(op2)
+    }
+
+    fn read_table(&mut self, name: &str, records: &[Vec<u8>], big_endian: bool) {
+        let words = |record: &Vec<u8>| Words {
+            bytes: record.clone(),
+            big_endian,
+        };
+        if name.starts_with("GEOM1") || name.starts_with("GEOM2") {
+            for record in records {
+                self.read_geometry(&words(record));
+            }
+        } else if name.starts_with("OUG") || name == "BOUGV1" || name.starts_with("OES1") {
+            for pair in records.chunks(2) {
+                if let [ident, data] = pair {
+                    self.read_result(&words(ident), &words(data));
+                }
+            }
+        }
+    }
+
+    fn read_geometry(&mut self, words: &Words) {
+        let mut pos = 0;
+        while pos + 3 <= words.len() {
+            let key = (words.int(pos), words.int(pos + 1), words.int(pos + 2));
+            pos += 3;
+            let Some((card, size, first_node, num_nodes)) = geometry_card(key) else {
+                // Unknown card: its record size is unknown, skip the rest.
+                return;
+            };
+            while pos + size <= words.len() && !is_key_at(words, pos) {
+                if card == "GRID" {
+                    self.grids.insert(
+                        words.int(pos),
+                        [
+                            words.float(pos + 2),
+                            words.float(pos + 3),
+                            words.float(pos + 4),
+                        ],
+                    );
+                } else {
+                    let nodes: Vec<i32> = (0..num_nodes)
+                        .map(|i| words.int(pos + first_node + i))
+                        .take_while(|&node| node > 0)
+                        .collect();
+                    let id = words.int(pos);
+                    self.elements.insert(
+                        id,
+                        Op2Element {
+                            id,
+                            card,
+                            property: words.int(pos + 1),
+                            nodes,
+                        },
+                    );
+                }
+                pos += size;
+            }
+        }
+    }
+
+    fn read_result(&mut self, ident: &Words, data: &Words) {
+        if ident.len() < 10 {
+            return;
+        }
+        let approach = ident.int(0) / 10;
+        let table_code = ident.int(1) % 1000;
+        let element_type = ident.int(2);
+        let subcase = ident.int(3);
+        let time = match approach {
+            2 => ident.int(4) as f64,
+            _ if ident.len() > 4 && approach != 1 => ident.float(4),
+            _ => 0.0,
+        };
+        let num_wide = ident.int(9).max(1) as usize;
+        let title = if ident.len() >= 82 {
+            ident.text(50, 32)
+        } else {
+            String::new()
+        };
+
+        match table_code {
+            1 if num_wide == 8 => {
+                let mut values = BTreeMap::new();
+                for entry in data.entries(num_wide) {
+                    let mut dof = [0.0; 6];
+                    for (i, value) in dof.iter_mut().enumerate() {
+                        *value = data.float(entry + 2 + i);
+                    }
+                    values.insert(data.int(entry) / 10, dof);
+                }
+                self.displacements.push(Op2Displacements {
+                    subcase,
+                    approach,
+                    time,
+                    title,
+                    values,
+                });
+            }
+            5 => {
+                let Some(layout) = stress_layout(element_type, num_wide) else {
+                    return;
+                };
+                let mut stresses = Op2Stresses {
+                    subcase,
+                    approach,
+                    time,
+                    element_type,
+                    values: BTreeMap::new(),
+                    von_mises: BTreeMap::new(),
+                };
+                for entry in data.entries(num_wide) {
+                    let id = data.int(entry) / 10;
+                    let at = |offset: Option<usize>| offset.map_or(0.0, |o| data.float(entry + o));
+                    stresses.values.insert(id, layout.components.map(at));
+                    stresses.von_mises.insert(id, at(Some(layout.von_mises)));
+                }
+                self.stresses.push(stresses);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Word offsets of stress components within one element entry.
+struct StressLayout {
+    /// sxx, syy, szz, sxy, syz, szx (`None` = not reported, zero)
+    components: [Option<usize>; 6],
+    von_mises: usize,
+}
+
+fn stress_layout(element_type: i32, num_wide: usize) -> Option<StressLayout> {
+    match (element_type, num_wide) {
+        // CQUAD4 / CTRIA3: eid, fd1, sx1, sy1, txy1, angle, major, minor, vm1, ...
+        (33 | 74, 17) => Some(StressLayout {
+            components: [Some(2), Some(3), None, Some(4), None, None],
+            von_mises: 8,
+        }),
+        // Solids: eid, cid, "CEN/", nnodes, then 21 words for the centroid:
+        // grid, sx, txy, s1, l1(3), sy, tyz, s2, l2(3), sz, tzx, s3, l3(3), p, vm
+        (39 | 67 | 68, n) if n >= 25 && (n - 4) % 21 == 0 => Some(StressLayout {
+            components: [Some(5), Some(11), Some(17), Some(6), Some(12), Some(18)],
+            von_mises: 24,
+        }),
+        _ => None,
+    }
+}
+
+/// GEOM record key → (card, words per entry, first node word, max nodes)
+fn geometry_card(key: (i32, i32, i32)) -> Option<(&'static str, usize, usize, usize)> {
+    let card = match key {
+        (4501, 45, 1) => ("GRID", 8, 0, 0),
+        (3001, 30, 48) => ("CROD", 4, 2, 2),
+        (2408, 24, 180) => ("CBAR", 16, 2, 2),
+        (5959, 59, 282) => ("CTRIA3", 13, 2, 3),
+        (2958, 51, 177) => ("CQUAD4", 14, 2, 4),
+        (5508, 55, 217) => ("CTETRA", 12, 2, 10),
+        (4108, 41, 280) => ("CPENTA", 17, 2, 15),
+        (7308, 73, 253) => ("CHEXA", 22, 2, 20),
+        _ => return None,
+    };
+    Some(card)
+}
+
+fn is_key_at(words: &Words, pos: usize) -> bool {
+    pos + 3 <= words.len()
+        && geometry_card((words.int(pos), words.int(pos + 1), words.int(pos + 2))).is_some()
+}
+
+/// 32-bit word view of a record.
+struct Words {
+    bytes: Vec<u8>,
+    big_endian: bool,
+}
+
+impl Words {
+    fn len(&self) -> usize {
+        self.bytes.len() / 4
+    }
+
+    fn raw(&self, index: usize) -> [u8; 4] {
+        let mut word: [u8; 4] = self.bytes[index * 4..index * 4 + 4]
+            .try_into()
+            .expect("4-byte word");
+        if self.big_endian {
+            word.reverse();
+        }
+        word
+    }
+
+    fn int(&self, index: usize) -> i32 {
+        i32::from_le_bytes(self.raw(index))
+    }
+
+    fn float(&self, index: usize) -> f64 {
+        f32::from_le_bytes(self.raw(index)) as f64
+    }
+
+    fn text(&self, start: usize, words: usize) -> String {
+        let end = ((start + words) * 4).min(self.bytes.len());
+        String::from_utf8_lossy(&self.bytes[start * 4..end])
+            .trim()
+            .to_string()
+    }
+
+    /// Start word of every complete `num_wide` entry
+    fn entries(&self, num_wide: usize) -> impl Iterator<Item = usize> {
+        (0..self.len() / num_wide).map(move |i| i * num_wide)
+    }
+}
+
+/// Reader of Fortran records with OP2 marker conventions.
+struct RecordReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> RecordReader<'a> {
+    fn new(bytes: &'a [u8]) -> io::Result<Self> {
+        let first = bytes
+            .get(0..4)
+            .ok_or_else(|| invalid("OP2 file is empty".to_string()))?;
+        let big_endian = match first {
+            [4, 0, 0, 0] => false,
+            [0, 0, 0, 4] => true,
+            _ => {
+                return Err(invalid(
+                    "not a 32-bit OP2 file (first record marker is not 4)".to_string(),
+                ));
+            }
+        };
+        Ok(Self {
+            bytes,
+            pos: 0,
+            big_endian,
+        })
+    }
+
+    fn i32_at(&self, pos: usize) -> io::Result<i32> {
+        let raw: [u8; 4] = self
+            .bytes
+            .get(pos..pos + 4)
+            .ok_or_else(|| invalid(format!("OP2 file truncated at byte {pos}")))?
+            .try_into()
+            .expect("4 bytes");
+        Ok(if self.big_endian {
+            i32::from_be_bytes(raw)
+        } else {
+            i32::from_le_bytes(raw)
+        })
+    }
+
+    /// Next Fortran record payload
+    fn block(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.i32_at(self.pos)?;
+        if len < 0 {
+            return Err(invalid(format!(
+                "negative record length at byte {}",
+                self.pos
+            )));
+        }
+        let start = self.pos + 4;
+        let end = start + len as usize;
+        if self.i32_at(end)? != len {
+            return Err(invalid(format!(
+                "record length markers disagree at byte {}",
+                self.pos
+            )));
+        }
+        self.pos = end + 4;
+        Ok(self.bytes[start..end].to_vec())
+    }
+
+    fn peek_marker(&self) -> io::Result<Option<i32>> {
+        if self.pos >= self.bytes.len() {
+            return Ok(None);
+        }
+        if self.i32_at(self.pos)? != 4 {
+            return Ok(None);
+        }
+        self.i32_at(self.pos + 4).map(Some)
+    }
+
+    fn marker(&mut self) -> io::Result<i32> {
+        let at = self.pos;
+        let block = self.block()?;
+        if block.len() != 4 {
+            return Err(invalid(format!("expected marker record at byte {at}")));
+        }
+        self.i32_at(at + 4)
+    }
+
+    fn expect_markers(&mut self, expected: &[i32]) -> io::Result<()> {
+        for &value in expected {
+            let at = self.pos;
+            let found = self.marker()?;
+            if found != value {
+                return Err(invalid(format!(
+                    "expected OP2 marker {value} at byte {at}, found {found}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// A word-count marker followed by data blocks until the next marker
+    fn record(&mut self) -> io::Result<Vec<u8>> {
+        self.marker()?;
+        let mut data = self.block()?;
+        // Long records are continued as further (count, block) pairs.
+        while let Some(count) = self.peek_marker()? {
+            if count <= 0 {
+                break;
+            }
+            self.marker()?;
+            data.extend(self.block()?);
+        }
+        Ok(data)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Minimal OP2 writer for tests.
+    #[derive(Default)]
+    pub(crate) struct Op2Builder {
+        pub(crate) bytes: Vec<u8>,
+    }
+
+    pub(crate) enum Word {
+        I(i32),
+        F(f32),
+    }
+
+    impl Op2Builder {
+        fn block(&mut self, payload: &[u8]) {
+            let len = (payload.len() as i32).to_le_bytes();
+            self.bytes.extend_from_slice(&len);
+            self.bytes.extend_from_slice(payload);
+            self.bytes.extend_from_slice(&len);
+        }
+
+        fn markers(&mut self, values: &[i32]) {
+            for value in values {
+                self.block(&value.to_le_bytes());
+            }
+        }
+
+        fn record(&mut self, words: &[Word]) {
+            let payload: Vec<u8> = words
+                .iter()
+                .flat_map(|w| match w {
+                    Word::I(v) => v.to_le_bytes(),
+                    Word::F(v) => v.to_le_bytes(),
+                })
+                .collect();
+            self.markers(&[words.len() as i32]);
+            self.block(&payload);
+        }
+
+        pub(crate) fn header(&mut self) {
+            self.markers(&[3]);
+            self.block(&[0; 12]);
+            self.markers(&[7]);
+            self.block(b"NASTRAN FORT TAPE ID CODE - ");
+            self.markers(&[2]);
+            self.block(b"XXXXXXXX");
+            self.markers(&[-1, 0]);
+        }
+
+        pub(crate) fn table(&mut self, name: &str, records: &[Vec<Word>]) {
+            self.markers(&[2]);
+            self.block(format!("{name:<8}").as_bytes());
+            self.markers(&[-1, 7]);
+            self.block(&[0; 28]);
+            self.markers(&[-2, 1, 0]);
+            self.record(&[Word::I(0), Word::I(0)]);
+            let mut subtable = -3;
+            for record in records {
+                self.markers(&[subtable, 1, 0]);
+                self.record(record);
+                subtable -= 1;
+            }
+            self.markers(&[subtable, 1, 0, 0]);
+        }
+
+        pub(crate) fn ident(
+            approach: i32,
+            table_code: i32,
+            element_type: i32,
+            num_wide: i32,
+        ) -> Vec<Word> {
+            let mut words: Vec<Word> = (0..146).map(|_| Word::I(0)).collect();
+            words[0] = Word::I(approach * 10 + 1);
+            words[1] = Word::I(table_code);
+            words[2] = Word::I(element_type);
+            words[3] = Word::I(1);
+            words[9] = Word::I(num_wide);
+            words
+        }
+    }
+
+    pub(crate) fn sample_op2() -> Vec<u8> {
+        use Word::{F, I};
+        let mut op2 = Op2Builder::default();
+        op2.header();
+        let mut grids = vec![I(4501), I(45), I(1)];
+        for (id, x) in [(1, 0.0), (2, 1.0), (3, 1.0), (4, 0.0)] {
+            let y = if id > 2 { 1.0 } else { 0.0 };
+            grids.extend([I(id), I(0), F(x), F(y), F(0.0), I(0), I(0), I(0)]);
+        }
+        op2.table("GEOM1S", &[grids]);
+        let mut quad = vec![I(2958), I(51), I(177), I(10), I(1), I(1), I(2), I(3), I(4)];
+        quad.extend((0..8).map(|_| I(0)));
+        op2.table("GEOM2S", &[quad]);
+
+        let mut disp = Vec::new();
+        for node in 1..=4 {
+            disp.extend([I(node * 10 + 1), I(1)]);
+            disp.extend((0..6).map(|c| F(if c == 2 { node as f32 * 0.5 } else { 0.0 })));
+        }
+        op2.table("OUGV1", &[Op2Builder::ident(1, 1, 0, 8), disp]);
+
+        let mut stress = vec![I(101), F(-0.5), F(10.0), F(20.0), F(5.0)];
+        stress.extend((0..3).map(|_| F(0.0)));
+        stress.push(F(19.0));
+        stress.extend((0..8).map(|_| F(0.0)));
+        op2.table("OES1X1", &[Op2Builder::ident(1, 5, 33, 17), stress]);
+        op2.bytes
+    }
+
+    #[test]
+    fn reads_geometry_displacements_and_shell_stresses() {
+        let op2 = Op2File::from_bytes(&sample_op2()).expect("parse op2");
+        assert_eq!(op2.tables, vec!["GEOM1S", "GEOM2S", "OUGV1", "OES1X1"]);
+        assert_eq!(op2.grids.get(&3), Some(&[1.0, 1.0, 0.0]));
+        let quad = &op2.elements[&10];
+        assert_eq!(
+            (quad.card, quad.nodes.clone()),
+            ("CQUAD4", vec![1, 2, 3, 4])
+        );
+
+        let disp = &op2.displacements[0];
+        assert_eq!(disp.subcase, 1);
+        assert_eq!(disp.values[&4][2], 2.0);
+
+        let stress = &op2.stresses[0];
+        assert_eq!(stress.element_type, 33);
+        assert_eq!(stress.values[&10], [10.0, 20.0, 0.0, 5.0, 0.0, 0.0]);
+        assert_eq!(stress.von_mises[&10], 19.0);
+    }
+
+    #[test]
+    fn rejects_non_op2_input() {
+        let err = Op2File::from_bytes(b"$ bulk data\n").expect_err("not op2");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
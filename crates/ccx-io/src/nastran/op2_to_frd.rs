@@ -0,0 +1,250 @@
+//! Nastran OP2 results → FRD conversion.
+//!
+//! Maps the geometry and results of an [`Op2File`] onto an [`FrdFile`] so a
+//! Nastran reference solution can be exported, viewed and diffed
+//! (see [`crate::frd_compare`]) with the same tooling as CalculiX results.
+//! Grid and element ids are kept, so the comparison is only meaningful when
+//! both models share their numbering.
+//!
+//! - GRID → FRD nodes; elements → FRD element types, with second-order
+//!   CHEXA/CPENTA nodes reordered to the CalculiX convention
+//! - displacements → `DISP` (translations only; rotations have no FRD
+//!   counterpart)
+//! - element stresses → `STRESS`, averaged at the nodes by default to match
+//!   the nodal stresses CalculiX writes, or kept per element
+//!
+//! Results sharing subcase, approach and time end up in one result block
+//! whose step is the subcase. Modal results carry the mode number both as
+//! block time and as [`ResultBlock::mode`].
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::op2::{Op2Element, Op2File};
+use crate::frd_reader::{
+    FrdElement, FrdFile, ResultBlock, ResultDataset, ResultLocation, calculix_node_order,
+};
+
+/// Converts OP2 results to an FRD model.
+pub struct Op2ToFrdConverter<'a> {
+    op2: &'a Op2File,
+    element_stresses: bool,
+}
+
+impl<'a> Op2ToFrdConverter<'a> {
+    pub fn new(op2: &'a Op2File) -> Self {
+        Self {
+            op2,
+            element_stresses: false,
+        }
+    }
+
+    /// Keep stresses per element instead of averaging them at the nodes
+    pub fn with_element_stresses(mut self, element_stresses: bool) -> Self {
+        self.element_stresses = element_stresses;
+        self
+    }
+
+    pub fn convert(&self) -> FrdFile {
+        let mut frd = FrdFile::new();
+        frd.header.job_name = "OP2".to_string();
+        frd.nodes = self.op2.grids.iter().map(|(&id, &xyz)| (id, xyz)).collect();
+        for element in self.op2.elements.values() {
+            if let Some((element_type, nodes)) = frd_element(element) {
+                frd.elements.insert(
+                    element.id,
+                    FrdElement {
+                        id: element.id,
+                        element_type,
+                        nodes,
+                    },
+                );
+            }
+        }
+
+        // (subcase, approach, time bits) → datasets, in first-seen order
+        let mut blocks: Vec<((i32, i32, u64), ResultBlock)> = Vec::new();
+        let mut block_for = |subcase: i32, approach: i32, time: f64| -> usize {
+            let key = (subcase, approach, time.to_bits());
+            if let Some(index) = blocks.iter().position(|(k, _)| *k == key) {
+                return index;
+            }
+            blocks.push((
+                key,
+                ResultBlock {
+                    step: subcase,
+                    time,
+                    mode: (approach == 2).then_some(time as i32),
+                    datasets: Vec::new(),
+                },
+            ));
+            blocks.len() - 1
+        };
+
+        let mut block_datasets: Vec<(usize, ResultDataset)> = Vec::new();
+        for disp in &self.op2.displacements {
+            let index = block_for(disp.subcase, disp.approach, disp.time);
+            let values = disp
+                .values
+                .iter()
+                .map(|(&id, dof)| (id, dof[..3].to_vec()))
+                .collect();
+            block_datasets.push((index, ResultDataset::nodal("DISP", values)));
+        }
+
+        // Stress tables are split per element type; merge them per block.
+        let mut stresses: BTreeMap<usize, BTreeMap<i32, [f64; 6]>> = BTreeMap::new();
+        for table in &self.op2.stresses {
+            let index = block_for(table.subcase, table.approach, table.time);
+            stresses
+                .entry(index)
+                .or_default()
+                .extend(table.values.iter().map(|(&id, v)| (id, *v)));
+        }
+        for (index, values) in stresses {
+            let dataset = if self.element_stresses {
+                let mut dataset = ResultDataset::nodal(
+                    "STRESS",
+                    values.iter().map(|(&id, v)| (id, v.to_vec())).collect(),
+                );
+                dataset.location = ResultLocation::Element;
+                dataset
+            } else {
+                ResultDataset::nodal("STRESS", self.average_at_nodes(&values))
+            };
+            block_datasets.push((index, dataset));
+        }
+
+        for (index, dataset) in block_datasets {
+            blocks[index].1.datasets.push(dataset);
+        }
+        frd.result_blocks = blocks.into_iter().map(|(_, block)| block).collect();
+        frd
+    }
+
+    /// Unweighted mean of the adjacent element values at every node
+    fn average_at_nodes(&self, values: &BTreeMap<i32, [f64; 6]>) -> HashMap<i32, Vec<f64>> {
+        let mut sums: HashMap<i32, ([f64; 6], usize)> = HashMap::new();
+        for (id, stress) in values {
+            let Some(element) = self.op2.elements.get(id) else {
+                continue;
+            };
+            for node in &element.nodes {
+                let (sum, count) = sums.entry(*node).or_insert(([0.0; 6], 0));
+                for (s, v) in sum.iter_mut().zip(stress) {
+                    *s += v;
+                }
+                *count += 1;
+            }
+        }
+        sums.into_iter()
+            .map(|(node, (sum, count))| (node, sum.iter().map(|s| s / count as f64).collect()))
+            .collect()
+    }
+}
+
+/// Convert an OP2 file with nodal-averaged stresses.
+pub fn op2_to_frd(op2: &Op2File) -> FrdFile {
+    Op2ToFrdConverter::new(op2).convert()
+}
+
+/// FRD element type code and CalculiX-ordered nodes of an OP2 element
+///
+/// Nastran lists the midside nodes of CHEXA20 and CPENTA15 in the order ccx
+/// writes to FRD files (vertical edges before the top face), so they are
+/// brought into CalculiX order with the FRD helper; [`crate::write_frd_to`]
+/// swaps them back when writing.
+fn frd_element(element: &Op2Element) -> Option<(i32, Vec<i32>)> {
+    let nodes = &element.nodes;
+    let frd_type = match (element.card, nodes.len()) {
+        ("CHEXA", 8) => 1,
+        ("CPENTA", 6) => 2,
+        ("CTETRA", 4) => 3,
+        ("CHEXA", 20) => 4,
+        ("CPENTA", 15) => 5,
+        ("CTETRA", 10) => 6,
+        ("CTRIA3", 3) => 7,
+        ("CQUAD4", 4) => 9,
+        ("CROD" | "CBAR", 2) => 11,
+        _ => return None,
+    };
+    Some((frd_type, calculix_node_order(frd_type, nodes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nastran::op2::tests::sample_op2;
+
+    #[test]
+    fn converts_geometry_displacements_and_averaged_stresses() {
+        let op2 = Op2File::from_bytes(&sample_op2()).expect("parse op2");
+        let frd = op2_to_frd(&op2);
+
+        assert_eq!(frd.nodes[&3], [1.0, 1.0, 0.0]);
+        assert_eq!(frd.elements[&10].element_type, 9);
+        assert_eq!(frd.result_blocks.len(), 1);
+
+        let block = &frd.result_blocks[0];
+        assert_eq!((block.step, block.mode), (1, None));
+        let disp = &block.datasets[0];
+        assert_eq!((disp.name.as_str(), disp.ncomps), ("DISP", 3));
+        assert_eq!(disp.values[&4], vec![0.0, 0.0, 2.0]);
+
+        // A single quad: every corner node gets its centroid stress.
+        let stress = &block.datasets[1];
+        assert_eq!(stress.location, ResultLocation::Nodal);
+        assert_eq!(stress.values[&2], vec![10.0, 20.0, 0.0, 5.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn keeps_element_stresses_and_survives_frd_round_trip() {
+        let op2 = Op2File::from_bytes(&sample_op2()).expect("parse op2");
+        let frd = Op2ToFrdConverter::new(&op2)
+            .with_element_stresses(true)
+            .convert();
+        let stress = &frd.result_blocks[0].datasets[1];
+        assert_eq!(stress.location, ResultLocation::Element);
+        assert_eq!(stress.values[&10][1], 20.0);
+
+        let mut bytes = Vec::new();
+        crate::write_frd_to(&mut bytes, &frd).expect("write frd");
+        let read = FrdFile::from_reader(bytes.as_slice()).expect("read frd");
+        let tolerances = crate::FrdTolerances::new(1e-6, 1e-5);
+        assert!(crate::frd_compare(&frd, &read, &tolerances).passed());
+    }
+
+    #[test]
+    fn reorders_quadratic_hexahedra() {
+        let element = Op2Element {
+            id: 1,
+            card: "CHEXA",
+            property: 1,
+            nodes: (1..=20).collect(),
+        };
+        let (frd_type, nodes) = frd_element(&element).unwrap();
+        assert_eq!(frd_type, 4);
+        assert_eq!(&nodes[12..], &[17, 18, 19, 20, 13, 14, 15, 16]);
+
+        // The FRD file carries the Nastran midside order again.
+        let mut frd = FrdFile::new();
+        frd.elements.insert(
+            1,
+            FrdElement {
+                id: 1,
+                element_type: frd_type,
+                nodes,
+            },
+        );
+        let mut bytes = Vec::new();
+        crate::write_frd_to(&mut bytes, &frd).expect("write frd");
+        let text = String::from_utf8(bytes).expect("ascii frd");
+        let connectivity: Vec<&str> = text.lines().filter(|l| l.starts_with(" -2")).collect();
+        assert_eq!(
+            connectivity,
+            [
+                " -2         1         2         3         4         5         6         7         8         9        10",
+                " -2        11        12        13        14        15        16        17        18        19        20",
+            ]
+        );
+    }
+}
@@ -1,9 +1,14 @@
 use std::fs;
-use std::io;
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 use ccx_model::ModelSummary;
 
+use crate::fortran::format_e_1p;
+use crate::frd_reader::{
+    FrdFile, ResultBlock, ResultDataset, ResultLocation, frd_element_node_count, frd_node_order,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JobStatus {
     Success,
@@ -143,6 +148,390 @@ pub fn write_frd_stub(path: impl AsRef<Path>, report: &JobReport) -> io::Result<
     fs::write(path, body)
 }
 
+/// Encoding of FRD node, element and result records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrdFormat {
+    /// Long fixed-width ASCII (format flag 1)
+    #[default]
+    Ascii,
+    /// Binary records (format flag 2): `f64` coordinates, `f32` results,
+    /// as written by ccx for large models
+    Binary,
+}
+
+impl FrdFormat {
+    fn flag(self) -> u8 {
+        match self {
+            FrdFormat::Ascii => 1,
+            FrdFormat::Binary => 2,
+        }
+    }
+}
+
+/// Write a complete ASCII FRD results file (long format).
+///
+/// Emits the `1C` model header, the `2C` node block, the `3C` element block
+/// and one `100C` dataset per entry of every result block, each preceded by
+/// a `1PSTEP` record so readers can recover the step number.
+pub fn write_frd(path: impl AsRef<Path>, frd: &FrdFile) -> io::Result<()> {
+    write_frd_with_format(path, frd, FrdFormat::Ascii)
+}
+
+/// Write a complete FRD results file in the requested encoding.
+pub fn write_frd_with_format(
+    path: impl AsRef<Path>,
+    frd: &FrdFile,
+    format: FrdFormat,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    ensure_parent_dir(path)?;
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    write_frd_to_with_format(&mut out, frd, format)?;
+    out.flush()
+}
+
+/// Write a complete ASCII FRD results file to any writer.
+pub fn write_frd_to<W: Write>(out: &mut W, frd: &FrdFile) -> io::Result<()> {
+    write_frd_to_with_format(out, frd, FrdFormat::Ascii)
+}
+
+/// Write a complete FRD results file to any writer in the requested encoding.
+///
+/// Block headers and `-4`/`-5` records are always ASCII; in binary mode the
+/// node, element and value records are raw little-endian data.
+pub fn write_frd_to_with_format<W: Write>(
+    out: &mut W,
+    frd: &FrdFile,
+    format: FrdFormat,
+) -> io::Result<()> {
+    let flag = format.flag();
+    writeln!(out, "    1C{}", frd.header.job_name)?;
+    for info in &frd.header.info {
+        if info.starts_with("1U") {
+            writeln!(out, "    {info}")?;
+        }
+    }
+
+    let mut node_ids: Vec<i32> = frd.nodes.keys().copied().collect();
+    node_ids.sort_unstable();
+    writeln!(out, "    2C{:18}{:12}{:37}{flag}", "", node_ids.len(), "")?;
+    for id in &node_ids {
+        let [x, y, z] = frd.nodes[id];
+        match format {
+            FrdFormat::Ascii => writeln!(
+                out,
+                " -1{:10}{}{}{}",
+                id,
+                frd_float(x),
+                frd_float(y),
+                frd_float(z)
+            )?,
+            FrdFormat::Binary => {
+                out.write_all(&id.to_le_bytes())?;
+                for c in [x, y, z] {
+                    out.write_all(&c.to_le_bytes())?;
+                }
+            }
+        }
+    }
+    writeln!(out, " -3")?;
+
+    let mut element_ids: Vec<i32> = frd.elements.keys().copied().collect();
+    element_ids.sort_unstable();
+    writeln!(
+        out,
+        "    3C{:18}{:12}{:37}{flag}",
+        "",
+        element_ids.len(),
+        ""
+    )?;
+    for id in &element_ids {
+        let element = &frd.elements[id];
+        let nodes = frd_node_order(element.element_type, &element.nodes);
+        match format {
+            FrdFormat::Ascii => {
+                writeln!(out, " -1{:10}{:5}{:5}{:5}", id, element.element_type, 0, 1)?;
+                for chunk in nodes.chunks(10) {
+                    write!(out, " -2")?;
+                    for node in chunk {
+                        write!(out, "{node:10}")?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+            FrdFormat::Binary => {
+                // Binary records carry no node count, so it must match the type.
+                if frd_element_node_count(element.element_type) != Some(nodes.len()) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "element {} has {} nodes, not valid for FRD type {}",
+                            id,
+                            nodes.len(),
+                            element.element_type
+                        ),
+                    ));
+                }
+                for value in [*id, element.element_type, 0, 1] {
+                    out.write_all(&value.to_le_bytes())?;
+                }
+                for node in &nodes {
+                    out.write_all(&node.to_le_bytes())?;
+                }
+            }
+        }
+    }
+    writeln!(out, " -3")?;
+
+    let mut counters = FrdCounters::default();
+    for block in &frd.result_blocks {
+        write_frd_block(out, block, &mut counters, format)?;
+    }
+
+    writeln!(out, " 9999")
+}
+
+/// Append the results of one more step or increment to an existing FRD file.
+///
+/// The trailing ` 9999` record is replaced by the new datasets (numbered
+/// after the ones already present) and written again, so a running
+/// analysis can extend a single FRD step by step. `format` only affects the
+/// value records of the new datasets.
+pub fn append_frd_step(
+    path: impl AsRef<Path>,
+    block: &ResultBlock,
+    format: FrdFormat,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut bytes = fs::read(path)?;
+    let end = frd_end_marker(&bytes).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has no closing 9999 record", path.display()),
+        )
+    })?;
+    bytes.truncate(end);
+    let mut counters = FrdCounters::after(&bytes);
+
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    out.write_all(&bytes)?;
+    write_frd_block(&mut out, block, &mut counters, format)?;
+    writeln!(out, " 9999")?;
+    out.flush()
+}
+
+/// Byte offset of the final ` 9999` record line
+fn frd_end_marker(bytes: &[u8]) -> Option<usize> {
+    let trimmed = bytes.trim_ascii_end();
+    let start = trimmed
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    (trimmed[start..].trim_ascii() == b"9999").then_some(start)
+}
+
+/// Running FRD numbering as kept by ccx's `frdheader`: `1PSTEP` counts
+/// datasets, while the `100CL` set name counts output increments so that
+/// CGX groups the datasets of one increment.
+#[derive(Debug, Default)]
+struct FrdCounters {
+    datasets: i32,
+    kode: i32,
+    step: i32,
+    increment: i32,
+}
+
+impl FrdCounters {
+    /// Counters continuing after the datasets already in `bytes`
+    fn after(bytes: &[u8]) -> Self {
+        let mut counters = Self::default();
+        for line in bytes.split(|&b| b == b'\n') {
+            let line = line.trim_ascii_start();
+            if let Some(fields) = line.strip_prefix(b"1PSTEP") {
+                counters.datasets += 1;
+                let fields: Vec<i32> = String::from_utf8_lossy(fields)
+                    .split_whitespace()
+                    .filter_map(|field| field.parse().ok())
+                    .collect();
+                if let [_, increment, step] = fields[..] {
+                    counters.increment = increment;
+                    counters.step = step;
+                }
+            } else if let Some(record) = line.strip_prefix(b"100CL") {
+                let set = record.get(..5).unwrap_or(record);
+                if let Ok(set) = String::from_utf8_lossy(set).trim().parse::<i32>() {
+                    counters.kode = counters.kode.max(set - 100);
+                }
+            }
+        }
+        counters
+    }
+
+    /// Start the next increment of `step`
+    fn begin(&mut self, step: i32) {
+        self.kode += 1;
+        self.increment = if step == self.step {
+            self.increment + 1
+        } else {
+            1
+        };
+        self.step = step;
+    }
+}
+
+fn write_frd_block<W: Write>(
+    out: &mut W,
+    block: &ResultBlock,
+    counters: &mut FrdCounters,
+    format: FrdFormat,
+) -> io::Result<()> {
+    counters.begin(block.step);
+    for dataset in &block.datasets {
+        counters.datasets += 1;
+        writeln!(
+            out,
+            "    1PSTEP{:25}{:12}{:12}",
+            counters.datasets, counters.increment, block.step
+        )?;
+        write_frd_dataset(out, dataset, block.time, block.mode, counters.kode, format)?;
+    }
+    Ok(())
+}
+
+fn write_frd_dataset<W: Write>(
+    out: &mut W,
+    dataset: &ResultDataset,
+    time: f64,
+    mode: Option<i32>,
+    kode: i32,
+    format: FrdFormat,
+) -> io::Result<()> {
+    let mut ids: Vec<i32> = dataset.values.keys().copied().collect();
+    ids.sort_unstable();
+
+    writeln!(
+        out,
+        "  100CL{:5}{}{:12}{:20}{:2}{:5}{:10}{:2}",
+        100 + kode,
+        frd_float(time),
+        ids.len(),
+        "",
+        if mode.is_some() { 2 } else { 0 },
+        mode.unwrap_or(kode),
+        if mode.is_some() { "MODAL" } else { "" },
+        format.flag()
+    )?;
+
+    let irtype = match dataset.location {
+        ResultLocation::Nodal => 1,
+        ResultLocation::Element => 2,
+    };
+    let components = frd_components(dataset);
+    writeln!(
+        out,
+        " -4  {:<8}{:5}{:5}",
+        dataset.name,
+        components.len(),
+        irtype
+    )?;
+    for (name, ictype, icind1, icind2, iexist) in &components {
+        write!(out, " -5  {name:<8}{:5}{ictype:5}{icind1:5}{icind2:5}", 1)?;
+        if *iexist == 1 {
+            write!(out, "{iexist:5}{name}")?;
+        }
+        writeln!(out)?;
+    }
+
+    for id in &ids {
+        let values = &dataset.values[id];
+        if format == FrdFormat::Binary {
+            out.write_all(&id.to_le_bytes())?;
+            for i in 0..dataset.ncomps {
+                let value = values.get(i).copied().unwrap_or(0.0) as f32;
+                out.write_all(&value.to_le_bytes())?;
+            }
+            continue;
+        }
+        for (row, chunk) in values.chunks(6).enumerate() {
+            if row == 0 {
+                write!(out, " -1{id:10}")?;
+            } else {
+                write!(out, " -2{:10}", "")?;
+            }
+            for value in chunk {
+                write!(out, "{}", frd_float(*value))?;
+            }
+            writeln!(out)?;
+        }
+    }
+    writeln!(out, " -3")
+}
+
+/// `-5` component records: name, ictype, icind1, icind2, iexist.
+fn frd_components(dataset: &ResultDataset) -> Vec<(String, i32, i32, i32, i32)> {
+    const TENSOR_INDICES: [(i32, i32); 6] = [(1, 1), (2, 2), (3, 3), (1, 2), (2, 3), (3, 1)];
+
+    let names: Vec<String> = (0..dataset.ncomps)
+        .map(|i| {
+            dataset
+                .comp_names
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| format!("C{}", i + 1))
+        })
+        .collect();
+
+    match dataset.ncomps {
+        3 => {
+            let mut comps: Vec<_> = names
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| (name, 2, i as i32 + 1, 0, 0))
+                .collect();
+            comps.push(("ALL".to_string(), 2, 0, 0, 1));
+            comps
+        }
+        6 => names
+            .into_iter()
+            .zip(TENSOR_INDICES)
+            .map(|(name, (i, j))| (name, 4, i, j, 0))
+            .collect(),
+        _ => names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name, 1, i as i32 + 1, 0, 0))
+            .collect(),
+    }
+}
+
+/// Fortran-style `E12.5` field, e.g. ` 1.00000E-03`.
+fn frd_float(value: f64) -> String {
+    format_e_1p(value, 5, 12)
+}
+
+/// FRD element type code for a CalculiX element type name.
+pub fn frd_element_type(calculix_type: &str) -> Option<i32> {
+    let upper = calculix_type.trim().to_ascii_uppercase();
+    let code = match upper.as_str() {
+        t if t.starts_with("C3D8") => 1,
+        t if t.starts_with("C3D6") => 2,
+        t if t.starts_with("C3D4") => 3,
+        t if t.starts_with("C3D20") => 4,
+        t if t.starts_with("C3D15") => 5,
+        t if t.starts_with("C3D10") => 6,
+        "S3" | "M3D3" | "CPS3" | "CPE3" | "CAX3" => 7,
+        "S6" | "M3D6" | "CPS6" | "CPE6" | "CAX6" => 8,
+        "S4" | "S4R" | "M3D4" | "M3D4R" | "CPS4" | "CPS4R" | "CPE4" | "CPE4R" | "CAX4"
+        | "CAX4R" => 9,
+        "S8" | "S8R" | "M3D8" | "M3D8R" | "CPS8" | "CPS8R" | "CPE8" | "CPE8R" | "CAX8"
+        | "CAX8R" => 10,
+        "B31" | "B31R" | "T3D2" => 11,
+        "B32" | "B32R" | "T3D3" => 12,
+        _ => return None,
+    };
+    Some(code)
+}
+
 fn ensure_parent_dir(path: &Path) -> io::Result<()> {
     if let Some(parent) = path.parent()
         && !parent.as_os_str().is_empty()
@@ -201,6 +590,259 @@ mod tests {
         assert!(content.contains("Diverged"));
     }
 
+    #[test]
+    fn formats_fortran_e12_5_fields() {
+        assert_eq!(frd_float(0.0), " 0.00000E+00");
+        assert_eq!(frd_float(1.0e-3), " 1.00000E-03");
+        assert_eq!(frd_float(-2.5e10), "-2.50000E+10");
+        assert_eq!(frd_float(1.0e-120), " 0.00000E+00");
+    }
+
+    #[test]
+    fn frd_writer_round_trips_through_reader() {
+        use crate::frd_reader::{FrdElement, ResultBlock};
+        use std::collections::HashMap;
+
+        let mut frd = FrdFile::new();
+        frd.header.job_name = "cube".to_string();
+        for (id, coords) in [
+            (1, [0.0, 0.0, 0.0]),
+            (2, [1.0, 0.0, 0.0]),
+            (3, [0.0, 1.0, 0.0]),
+            (4, [0.0, 0.0, -1.0]),
+        ] {
+            frd.nodes.insert(id, coords);
+        }
+        frd.elements.insert(
+            1,
+            FrdElement {
+                id: 1,
+                element_type: frd_element_type("C3D4").expect("known type"),
+                nodes: vec![1, 2, 3, 4],
+            },
+        );
+
+        let disp: HashMap<i32, Vec<f64>> = (1..=4)
+            .map(|id| (id, vec![0.0, -1.0e-3 * id as f64, 0.0]))
+            .collect();
+        let stress: HashMap<i32, Vec<f64>> = (1..=4)
+            .map(|id| (id, vec![100.0, -50.0, 0.0, 1.5, 0.0, -2.0e-4 * id as f64]))
+            .collect();
+        let temp: HashMap<i32, Vec<f64>> = (1..=4).map(|id| (id, vec![293.15])).collect();
+        for step in 1..=2 {
+            frd.result_blocks.push(ResultBlock {
+                step,
+                time: step as f64,
+                mode: None,
+                datasets: vec![
+                    ResultDataset::nodal("DISP", disp.clone()),
+                    ResultDataset::nodal("STRESS", stress.clone()),
+                    ResultDataset::nodal("NDTEMP", temp.clone()),
+                ],
+            });
+        }
+
+        let mut buffer = Vec::new();
+        write_frd_to(&mut buffer, &frd).expect("write frd");
+        let text = String::from_utf8(buffer).expect("ascii output");
+        assert!(text.contains("    2C                             4"));
+        assert!(text.contains(" -4  STRESS      6    1"));
+        let sets: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("  100CL"))
+            .map(|record| record[..5].trim())
+            .collect();
+        assert_eq!(sets, ["101", "101", "101", "102", "102", "102"]);
+        assert!(text.contains(&format!("    1PSTEP{:25}{:12}{:12}\n", 4, 1, 2)));
+
+        let read = FrdFile::from_reader(text.as_bytes()).expect("read back");
+        assert_eq!(read.header.job_name, "cube");
+        assert_eq!(read.nodes, frd.nodes);
+        assert_eq!(read.elements[&1].nodes, vec![1, 2, 3, 4]);
+        assert_eq!(read.elements[&1].element_type, 3);
+        assert_eq!(read.result_blocks.len(), 2);
+
+        let block = &read.result_blocks[1];
+        assert_eq!(block.step, 2);
+        assert_eq!(block.time, 2.0);
+        let names: Vec<&str> = block.datasets.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["DISP", "STRESS", "NDTEMP"]);
+        assert_eq!(block.datasets[0].comp_names, vec!["D1", "D2", "D3"]);
+        assert_eq!(block.datasets[1].ncomps, 6);
+        assert_eq!(block.datasets[0].values[&3], vec![0.0, -3.0e-3, 0.0]);
+        assert_eq!(block.datasets[1].values[&4], stress[&4]);
+        assert_eq!(block.datasets[2].values[&2], vec![293.15]);
+    }
+
+    #[test]
+    fn binary_frd_round_trips_and_is_smaller() {
+        use crate::frd_reader::{FrdElement, ResultBlock};
+        use std::collections::HashMap;
+
+        let mut frd = FrdFile::new();
+        frd.header.job_name = "bin".to_string();
+        for id in 1..=8 {
+            frd.nodes
+                .insert(id, [id as f64 * 0.125, -(id as f64), 1.0e-3 * id as f64]);
+        }
+        frd.elements.insert(
+            1,
+            FrdElement {
+                id: 1,
+                element_type: 1,
+                nodes: (1..=8).collect(),
+            },
+        );
+        let disp: HashMap<i32, Vec<f64>> = (1..=8)
+            .map(|id| (id, vec![0.5, -0.25, id as f64]))
+            .collect();
+        frd.result_blocks.push(ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets: vec![ResultDataset::nodal("DISP", disp.clone())],
+        });
+
+        let root = unique_temp_dir("ccx_io_frd_binary");
+        let binary_path = root.join("job.frd");
+        let ascii_path = root.join("job_ascii.frd");
+        write_frd_with_format(&binary_path, &frd, FrdFormat::Binary).expect("binary frd");
+        write_frd(&ascii_path, &frd).expect("ascii frd");
+
+        let read = FrdFile::from_file(&binary_path).expect("read binary frd");
+        assert_eq!(read.nodes, frd.nodes);
+        assert_eq!(read.elements[&1].nodes, frd.elements[&1].nodes);
+        let values = &read.result_blocks[0].datasets[0].values;
+        assert_eq!(values[&8], disp[&8]);
+
+        let binary_len = fs::metadata(&binary_path).expect("binary size").len();
+        let ascii_len = fs::metadata(&ascii_path).expect("ascii size").len();
+        assert!(binary_len < ascii_len, "{binary_len} >= {ascii_len}");
+
+        frd.elements.get_mut(&1).expect("element").nodes.pop();
+        let err = write_frd_to_with_format(&mut Vec::new(), &frd, FrdFormat::Binary)
+            .expect_err("node count must match type");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn writes_quadratic_solids_in_the_ccx_node_order() {
+        use crate::frd_reader::FrdElement;
+
+        let mut frd = FrdFile::new();
+        for (id, element_type, count) in [(1, 4, 20), (2, 5, 15)] {
+            frd.elements.insert(
+                id,
+                FrdElement {
+                    id,
+                    element_type,
+                    nodes: (1..=count).collect(),
+                },
+            );
+        }
+
+        let mut buffer = Vec::new();
+        write_frd_to(&mut buffer, &frd).expect("write frd");
+        let text = String::from_utf8(buffer).expect("ascii frd");
+        let connectivity: Vec<&str> = text.lines().filter(|l| l.starts_with(" -2")).collect();
+        assert_eq!(
+            connectivity,
+            [
+                " -2         1         2         3         4         5         6         7         8         9        10",
+                " -2        11        12        17        18        19        20        13        14        15        16",
+                " -2         1         2         3         4         5         6         7         8         9        13",
+                " -2        14        15        10        11        12",
+            ]
+        );
+
+        let read = FrdFile::from_reader(text.as_bytes()).expect("read frd");
+        assert_eq!(read.elements[&1].nodes, (1..=20).collect::<Vec<_>>());
+        assert_eq!(read.elements[&2].nodes, (1..=15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn binary_frd_uses_the_same_quadratic_node_order() {
+        use crate::frd_reader::FrdElement;
+
+        let mut frd = FrdFile::new();
+        frd.elements.insert(
+            1,
+            FrdElement {
+                id: 1,
+                element_type: 4,
+                nodes: (1..=20).collect(),
+            },
+        );
+
+        let mut buffer = Vec::new();
+        write_frd_to_with_format(&mut buffer, &frd, FrdFormat::Binary).expect("write frd");
+        let header = buffer
+            .windows(3)
+            .position(|w| w == b"3C ")
+            .expect("element block");
+        let start = header + buffer[header..].iter().position(|&b| b == b'\n').unwrap() + 1;
+        let record: Vec<i32> = buffer[start..start + 24 * 4]
+            .chunks(4)
+            .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let mut expected = vec![1, 4, 0, 1];
+        expected.extend((1..=12).chain(17..=20).chain(13..=16));
+        assert_eq!(record, expected);
+
+        let read = FrdFile::from_reader(buffer.as_slice()).expect("read frd");
+        assert_eq!(read.elements[&1].nodes, (1..=20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn appends_steps_and_selects_them_on_read() {
+        use std::collections::HashMap;
+
+        let mut frd = FrdFile::new();
+        frd.nodes.insert(1, [0.0, 0.0, 0.0]);
+        frd.nodes.insert(2, [1.0, 0.0, 0.0]);
+        let block = |step: i32, time: f64| ResultBlock {
+            step,
+            time,
+            mode: None,
+            datasets: vec![ResultDataset::nodal(
+                "DISP",
+                HashMap::from([(2, vec![time, 0.0, 0.0])]),
+            )],
+        };
+        frd.result_blocks.push(block(1, 1.0));
+
+        let root = unique_temp_dir("ccx_io_frd_append");
+        let path = root.join("job.frd");
+        write_frd(&path, &frd).expect("write frd");
+        append_frd_step(&path, &block(2, 1.5), FrdFormat::Ascii).expect("append step 2");
+        append_frd_step(&path, &block(2, 2.0), FrdFormat::Binary).expect("append binary");
+
+        let read = FrdFile::from_file(&path).expect("read appended frd");
+        assert_eq!(read.steps(), vec![1, 2]);
+        assert_eq!(read.step_times(2), vec![1.5, 2.0]);
+        let step2 = read.select_step(2).expect("step 2 present");
+        assert_eq!(step2.result_blocks.len(), 2);
+        assert_eq!(
+            step2.result_blocks[1].datasets[0].values[&2],
+            vec![2.0, 0.0, 0.0]
+        );
+        assert!(read.select_step(3).is_none());
+
+        let text = fs::read(&path).expect("frd bytes");
+        assert!(text.ends_with(b" 9999\n"));
+        let ascii = String::from_utf8_lossy(&text);
+        let step = 2;
+        for (dataset, increment, set) in [(2, 1, 102), (3, 2, 103)] {
+            let header = format!("    1PSTEP{dataset:25}{increment:12}{step:12}\n  100CL{set:5}");
+            assert!(ascii.contains(&header), "missing {header:?}");
+        }
+
+        fs::write(root.join("open.frd"), "    1Cjob\n").expect("truncated frd");
+        let err = append_frd_step(root.join("open.frd"), &block(3, 3.0), FrdFormat::Ascii)
+            .expect_err("missing end record");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
     fn unique_temp_dir(prefix: &str) -> PathBuf {
         let pid = std::process::id();
         let nanos = SystemTime::now()
@@ -0,0 +1,396 @@
+//! Sampling of nodal results along a polyline.
+//!
+//! A path plot evaluates one nodal dataset at equally spaced points on every
+//! segment of a user-defined polyline, e.g. through the thickness of a
+//! vessel wall for a stress linearization check. Each point is located in
+//! the solid element that contains it and the nodal values are interpolated
+//! linearly between the element's corner nodes; midside nodes of quadratic
+//! elements are not used. Points outside the mesh have no values.
+
+use std::io::{self, Write};
+
+use crate::frd_reader::{FrdElement, FrdFile, ResultBlock, ResultLocation};
+
+/// One sample of a path plot
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSample {
+    /// Distance along the polyline from its first point
+    pub distance: f64,
+    pub point: [f64; 3],
+    /// Interpolated components, `None` outside the mesh
+    pub values: Option<Vec<f64>>,
+}
+
+/// Corner-node shapes of the supported solid elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CornerShape {
+    Hexahedron,
+    Wedge,
+    Tetrahedron,
+}
+
+impl CornerShape {
+    fn of(element: &FrdElement) -> Option<(Self, &[i32])> {
+        let (shape, corners) = match element.element_type {
+            1 | 4 => (Self::Hexahedron, 8),
+            2 | 5 => (Self::Wedge, 6),
+            3 | 6 => (Self::Tetrahedron, 4),
+            _ => return None,
+        };
+        element.nodes.get(..corners).map(|nodes| (shape, nodes))
+    }
+
+    /// Shape functions and their natural derivatives at `xi`
+    fn evaluate(self, [r, s, t]: [f64; 3]) -> (Vec<f64>, Vec<[f64; 3]>) {
+        match self {
+            Self::Hexahedron => {
+                const SIGNS: [[f64; 3]; 8] = [
+                    [-1.0, -1.0, -1.0],
+                    [1.0, -1.0, -1.0],
+                    [1.0, 1.0, -1.0],
+                    [-1.0, 1.0, -1.0],
+                    [-1.0, -1.0, 1.0],
+                    [1.0, -1.0, 1.0],
+                    [1.0, 1.0, 1.0],
+                    [-1.0, 1.0, 1.0],
+                ];
+                SIGNS
+                    .iter()
+                    .map(|[a, b, c]| {
+                        let (fr, fs, ft) = (1.0 + a * r, 1.0 + b * s, 1.0 + c * t);
+                        (
+                            fr * fs * ft / 8.0,
+                            [a * fs * ft / 8.0, b * fr * ft / 8.0, c * fr * fs / 8.0],
+                        )
+                    })
+                    .unzip()
+            }
+            Self::Wedge => {
+                let triangle = [
+                    (1.0 - r - s, [-1.0, -1.0]),
+                    (r, [1.0, 0.0]),
+                    (s, [0.0, 1.0]),
+                ];
+                [-1.0, 1.0]
+                    .iter()
+                    .flat_map(|c| {
+                        triangle.iter().map(move |(l, [dr, ds])| {
+                            let h = (1.0 + c * t) / 2.0;
+                            (l * h, [dr * h, ds * h, l * c / 2.0])
+                        })
+                    })
+                    .unzip()
+            }
+            Self::Tetrahedron => (
+                vec![1.0 - r - s - t, r, s, t],
+                vec![
+                    [-1.0, -1.0, -1.0],
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                ],
+            ),
+        }
+    }
+
+    fn contains(self, [r, s, t]: [f64; 3]) -> bool {
+        const TOL: f64 = 1e-6;
+        match self {
+            Self::Hexahedron => [r, s, t].iter().all(|x| x.abs() <= 1.0 + TOL),
+            Self::Wedge => r >= -TOL && s >= -TOL && r + s <= 1.0 + TOL && t.abs() <= 1.0 + TOL,
+            Self::Tetrahedron => r >= -TOL && s >= -TOL && t >= -TOL && r + s + t <= 1.0 + TOL,
+        }
+    }
+
+    fn start(self) -> [f64; 3] {
+        match self {
+            Self::Hexahedron => [0.0; 3],
+            Self::Wedge => [1.0 / 3.0, 1.0 / 3.0, 0.0],
+            Self::Tetrahedron => [0.25; 3],
+        }
+    }
+}
+
+/// Natural coordinates of `point` in an element with corners `coords`, by
+/// Newton iteration
+fn natural_coordinates(
+    shape: CornerShape,
+    coords: &[[f64; 3]],
+    point: [f64; 3],
+) -> Option<[f64; 3]> {
+    let size = coords
+        .iter()
+        .flat_map(|c| coords.iter().map(move |d| distance(*c, *d)))
+        .fold(0.0, f64::max);
+    let mut xi = shape.start();
+    for _ in 0..25 {
+        let (n, dn) = shape.evaluate(xi);
+        let mut residual = point;
+        let mut jacobian = [[0.0; 3]; 3];
+        for ((x, ni), dni) in coords.iter().zip(&n).zip(&dn) {
+            for i in 0..3 {
+                residual[i] -= ni * x[i];
+                for j in 0..3 {
+                    jacobian[i][j] += x[i] * dni[j];
+                }
+            }
+        }
+        if residual.iter().map(|r| r * r).sum::<f64>().sqrt() <= 1e-10 * size {
+            return Some(xi);
+        }
+        let step = solve3(jacobian, residual)?;
+        for (x, d) in xi.iter_mut().zip(step) {
+            *x += d;
+        }
+        // Far outside: stop early, the point is in another element
+        if xi.iter().any(|x| x.abs() > 10.0) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Solve the 3 × 3 system `a x = b` by Cramer's rule
+fn solve3(a: [[f64; 3]; 3], b: [f64; 3]) -> Option<[f64; 3]> {
+    let det = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let d = det(a);
+    if d.abs() < f64::MIN_POSITIVE {
+        return None;
+    }
+    Some(std::array::from_fn(|col| {
+        let mut m = a;
+        for (row, value) in m.iter_mut().zip(b) {
+            row[col] = value;
+        }
+        det(m) / d
+    }))
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Sample the nodal dataset `dataset` of `block` along `polyline`
+///
+/// Every segment is divided into `samples_per_segment` equal parts, so the
+/// result has `samples_per_segment` points per segment plus the end point.
+pub fn sample_path(
+    frd: &FrdFile,
+    block: &ResultBlock,
+    dataset: &str,
+    polyline: &[[f64; 3]],
+    samples_per_segment: usize,
+) -> Result<Vec<PathSample>, String> {
+    if polyline.len() < 2 {
+        return Err("a path needs at least two points".to_string());
+    }
+    if samples_per_segment == 0 {
+        return Err("a path needs at least one sample per segment".to_string());
+    }
+    let dataset = block
+        .datasets
+        .iter()
+        .find(|d| d.name.eq_ignore_ascii_case(dataset) && d.location == ResultLocation::Nodal)
+        .ok_or(format!("no nodal dataset {} in the result block", dataset))?;
+
+    // Solid elements with their corner coordinates and bounding boxes
+    let mut ids: Vec<i32> = frd.elements.keys().copied().collect();
+    ids.sort_unstable();
+    let elements: Vec<_> = ids
+        .iter()
+        .filter_map(|id| {
+            let (shape, nodes) = CornerShape::of(&frd.elements[id])?;
+            let coords: Vec<[f64; 3]> = nodes
+                .iter()
+                .map(|node| frd.nodes.get(node).copied())
+                .collect::<Option<_>>()?;
+            let mut bounds = [[f64::INFINITY; 3], [f64::NEG_INFINITY; 3]];
+            for c in &coords {
+                for i in 0..3 {
+                    bounds[0][i] = bounds[0][i].min(c[i]);
+                    bounds[1][i] = bounds[1][i].max(c[i]);
+                }
+            }
+            Some((shape, nodes, coords, bounds))
+        })
+        .collect();
+
+    let interpolate = |point: [f64; 3]| {
+        elements
+            .iter()
+            .find_map(|(shape, nodes, coords, [low, high])| {
+                let slack = 1e-6 * distance(*low, *high);
+                if (0..3).any(|i| point[i] < low[i] - slack || point[i] > high[i] + slack) {
+                    return None;
+                }
+                let xi = natural_coordinates(*shape, coords, point)?;
+                if !shape.contains(xi) {
+                    return None;
+                }
+                let (n, _) = shape.evaluate(xi);
+                let mut values = vec![0.0; dataset.ncomps];
+                for (node, ni) in nodes.iter().zip(n) {
+                    let nodal = dataset.values.get(node)?;
+                    for (v, x) in values.iter_mut().zip(nodal) {
+                        *v += ni * x;
+                    }
+                }
+                Some(values)
+            })
+    };
+
+    let mut samples = Vec::new();
+    let mut start_distance = 0.0;
+    for (index, segment) in polyline.windows(2).enumerate() {
+        let [a, b] = [segment[0], segment[1]];
+        let length = distance(a, b);
+        let first = if index == 0 { 0 } else { 1 };
+        for k in first..=samples_per_segment {
+            let f = k as f64 / samples_per_segment as f64;
+            let point = std::array::from_fn(|i| a[i] + f * (b[i] - a[i]));
+            samples.push(PathSample {
+                distance: start_distance + f * length,
+                point,
+                values: interpolate(point),
+            });
+        }
+        start_distance += length;
+    }
+    Ok(samples)
+}
+
+/// Write path samples as CSV with the columns `distance, x, y, z` and one
+/// column per component name
+pub fn write_path_csv<W: Write>(
+    out: &mut W,
+    samples: &[PathSample],
+    comp_names: &[String],
+) -> io::Result<()> {
+    write!(out, "distance,x,y,z")?;
+    for name in comp_names {
+        write!(out, ",{}", name)?;
+    }
+    writeln!(out)?;
+    for sample in samples {
+        let [x, y, z] = sample.point;
+        write!(out, "{:e},{:e},{:e},{:e}", sample.distance, x, y, z)?;
+        match &sample.values {
+            Some(values) => {
+                for v in values {
+                    write!(out, ",{:e}", v)?;
+                }
+            }
+            None => write!(out, "{}", ",".repeat(comp_names.len()))?,
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frd_reader::ResultDataset;
+
+    /// Two unit hexahedra along x and a tetrahedron in 2 ≤ x ≤ 3, with the
+    /// linear field `S = (10 x, y, z)` at their nodes
+    fn model() -> (FrdFile, ResultBlock) {
+        let mut frd = FrdFile::new();
+        let mut id = 0;
+        for x in 0..=2 {
+            for (y, z) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                id += 1;
+                frd.nodes.insert(id, [x as f64, y, z]);
+            }
+        }
+        frd.nodes.insert(13, [3.0, 0.0, 0.0]);
+        for e in 0..2 {
+            let a = 4 * e + 1;
+            let nodes = vec![a, a + 4, a + 5, a + 1, a + 3, a + 7, a + 6, a + 2];
+            let element_type = 1;
+            frd.elements.insert(
+                e + 1,
+                FrdElement {
+                    id: e + 1,
+                    element_type,
+                    nodes,
+                },
+            );
+        }
+        let nodes = vec![9, 13, 10, 12];
+        frd.elements.insert(
+            3,
+            FrdElement {
+                id: 3,
+                element_type: 3,
+                nodes,
+            },
+        );
+
+        let values = frd
+            .nodes
+            .iter()
+            .map(|(id, [x, y, z])| (*id, vec![10.0 * x, *y, *z]))
+            .collect();
+        let block = ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets: vec![ResultDataset::nodal("DISP", values)],
+        };
+        (frd, block)
+    }
+
+    #[test]
+    fn interpolates_linear_fields_exactly() {
+        let (frd, block) = model();
+        let path = [
+            [0.0, 0.5, 0.5],
+            [2.0, 0.5, 0.5],
+            [2.0, 0.2, 0.1],
+            [2.5, 0.1, 0.1],
+        ];
+        let samples = sample_path(&frd, &block, "disp", &path, 4).unwrap();
+        assert_eq!(samples.len(), 13);
+        assert!((samples[4].distance - 2.0).abs() < 1e-12);
+        for sample in &samples {
+            let values = sample.values.as_ref().expect("inside the mesh");
+            let [x, y, z] = sample.point;
+            for (v, expected) in values.iter().zip([10.0 * x, y, z]) {
+                assert!(
+                    (v - expected).abs() < 1e-9,
+                    "{:?}: {:?}",
+                    sample.point,
+                    values
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn points_outside_the_mesh_have_no_values() {
+        let (frd, block) = model();
+        let samples =
+            sample_path(&frd, &block, "DISP", &[[1.5, 0.5, 0.5], [1.5, 0.5, 3.0]], 5).unwrap();
+        assert!(samples[0].values.is_some());
+        assert!(samples[5].values.is_none());
+
+        let mut csv = Vec::new();
+        let names = ["D1", "D2", "D3"].map(String::from);
+        write_path_csv(&mut csv, &samples, &names).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("distance,x,y,z,D1,D2,D3\n0e0,1.5e0,5e-1,5e-1,"));
+        assert!(csv.ends_with(",3e0,,,\n"));
+
+        assert!(sample_path(&frd, &block, "STRESS", &[[0.0; 3], [1.0; 3]], 2).is_err());
+        assert!(sample_path(&frd, &block, "DISP", &[[0.0; 3]], 2).is_err());
+    }
+}
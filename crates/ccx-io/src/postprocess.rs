@@ -2,8 +2,10 @@
 ///!
 ///! Provides computations for derived quantities like:
 ///! - von Mises stress and strain
-///! - Principal stresses and strains
+///! - Principal stresses and strains, with their directions
 ///! - Effective stress and strain
+///! - Safety factors against yield (yield stress / von Mises)
+///! - Envelopes (max/min over time) of von Mises stress and displacement
 ///!
 ///! ## Usage
 ///!
@@ -23,6 +25,10 @@
 ///! println!("von Mises stress: {}", mises);
 ///! ```
 
+use std::collections::HashMap;
+
+use crate::frd_reader::{FrdFile, ResultBlock, ResultDataset, ResultLocation, standard_components};
+
 /// Stress or strain tensor components (Voigt notation)
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TensorComponents {
@@ -106,7 +112,7 @@ pub fn compute_mises_strain(strain: &TensorComponents) -> f64 {
 /// Compute principal stresses (eigenvalues of stress tensor)
 ///
 /// For a 3D symmetric tensor, computes the three principal values.
-/// Uses characteristic equation for 3×3 symmetric matrix.
+/// See [`compute_principal_axes`] for the directions.
 ///
 /// # Arguments
 ///
@@ -125,76 +131,349 @@ pub fn compute_principal_strains(strain: &TensorComponents) -> PrincipalValues {
 }
 
 /// Generic principal value computation for symmetric 3×3 tensor
-///
-/// Solves the characteristic equation: det(T - λI) = 0
-/// where λ are the eigenvalues (principal values)
 fn compute_principal_values(tensor: &TensorComponents) -> PrincipalValues {
-    // Special case: diagonal or nearly diagonal tensor
-    let shear_norm = tensor.xy.abs() + tensor.yz.abs() + tensor.xz.abs();
-    if shear_norm < 1e-10 {
-        // Diagonal tensor - eigenvalues are just the diagonal entries
-        let mut principals = [tensor.xx, tensor.yy, tensor.zz];
-        principals.sort_by(|a, b| b.partial_cmp(a).unwrap());
-        return PrincipalValues {
-            max: principals[0],
-            mid: principals[1],
-            min: principals[2],
-        };
+    compute_principal_axes(tensor).values
+}
+
+/// Principal values with their unit direction vectors
+#[derive(Debug, Clone, Copy)]
+pub struct PrincipalAxes {
+    /// Principal values sorted as (max, mid, min)
+    pub values: PrincipalValues,
+    /// Direction of the maximum principal value
+    pub max_direction: [f64; 3],
+    /// Direction of the middle principal value
+    pub mid_direction: [f64; 3],
+    /// Direction of the minimum principal value (max × mid)
+    pub min_direction: [f64; 3],
+}
+
+/// Compute principal values and directions of a symmetric tensor
+///
+/// Uses cyclic Jacobi rotations, which keep orthogonal directions for
+/// repeated principal values. Directions are unit vectors with their
+/// largest component positive, except the minimum direction, which
+/// completes a right-handed triad.
+pub fn compute_principal_axes(tensor: &TensorComponents) -> PrincipalAxes {
+    let t = tensor;
+    let mut a = [[t.xx, t.xy, t.xz], [t.xy, t.yy, t.yz], [t.xz, t.yz, t.zz]];
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    let norm: f64 = a.iter().flatten().map(|x| x * x).sum();
+    for _sweep in 0..50 {
+        let off = a[0][1].powi(2) + a[0][2].powi(2) + a[1][2].powi(2);
+        if off <= 1e-30 * norm {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            // Rotation J with tan(φ) = t zeroing a[p][q]: A ← Jᵀ A J, V ← V J
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            let rotate_columns = |m: &mut [[f64; 3]; 3]| {
+                for row in m.iter_mut() {
+                    let (xp, xq) = (row[p], row[q]);
+                    row[p] = c * xp - s * xq;
+                    row[q] = s * xp + c * xq;
+                }
+            };
+            rotate_columns(&mut a);
+            let (ap, aq) = (a[p], a[q]);
+            for k in 0..3 {
+                a[p][k] = c * ap[k] - s * aq[k];
+                a[q][k] = s * ap[k] + c * aq[k];
+            }
+            rotate_columns(&mut v);
+        }
     }
 
-    // Tensor in matrix form (symmetric):
-    // | xx  xy  xz |
-    // | xy  yy  yz |
-    // | xz  yz  zz |
-
-    // Invariants of the stress tensor
-    let i1 = tensor.xx + tensor.yy + tensor.zz; // First invariant (trace)
-
-    let i2 = tensor.xx * tensor.yy + tensor.yy * tensor.zz + tensor.zz * tensor.xx
-        - tensor.xy.powi(2)
-        - tensor.yz.powi(2)
-        - tensor.xz.powi(2); // Second invariant
-
-    let i3 = tensor.xx * tensor.yy * tensor.zz
-        + 2.0 * tensor.xy * tensor.yz * tensor.xz
-        - tensor.xx * tensor.yz.powi(2)
-        - tensor.yy * tensor.xz.powi(2)
-        - tensor.zz * tensor.xy.powi(2); // Third invariant (determinant)
-
-    // Solve cubic equation: λ³ - I₁λ² + I₂λ - I₃ = 0
-    // Using trigonometric method for three real roots
-
-    let p = i2 - i1.powi(2) / 3.0;
-    let q = 2.0 * i1.powi(3) / 27.0 - i1 * i2 / 3.0 + i3;
-
-    // For symmetric tensor, always use trigonometric method
-    let eps = 1e-14;
-    if p.abs() < eps {
-        // Degenerate case: all eigenvalues equal
-        let lambda = i1 / 3.0;
-        return PrincipalValues {
-            max: lambda,
-            mid: lambda,
-            min: lambda,
-        };
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| a[j][j].total_cmp(&a[i][i]));
+    let direction = |i: usize| {
+        let d = [v[0][i], v[1][i], v[2][i]];
+        let largest = d.iter().copied().fold(0.0_f64, |m, x| if x.abs() > m.abs() { x } else { m });
+        d.map(|x| if largest < 0.0 { -x } else { x })
+    };
+    let max_direction = direction(order[0]);
+    let mid_direction = direction(order[1]);
+    let min_direction = [
+        max_direction[1] * mid_direction[2] - max_direction[2] * mid_direction[1],
+        max_direction[2] * mid_direction[0] - max_direction[0] * mid_direction[2],
+        max_direction[0] * mid_direction[1] - max_direction[1] * mid_direction[0],
+    ];
+
+    PrincipalAxes {
+        values: PrincipalValues {
+            max: a[order[0]][order[0]],
+            mid: a[order[1]][order[1]],
+            min: a[order[2]][order[2]],
+        },
+        max_direction,
+        mid_direction,
+        min_direction,
     }
+}
 
-    let theta = ((-q / 2.0) / ((-p / 3.0).powf(1.5))).acos();
-    let k = 2.0 * (-p / 3.0).sqrt();
+/// Principal value and direction datasets of an FRD tensor dataset
+///
+/// For six-component tensor results such as `STRESS` or `TOSTRAIN`
+/// (components `xx, yy, zz, xy, yz, zx`) this returns, at the same location:
+///
+/// - `<NAME>_PRINCIPAL`: max, mid and min principal value
+/// - `<NAME>_PMAX`, `<NAME>_PMID`, `<NAME>_PMIN`: principal directions
+///   scaled by their principal value, for glyphs and stress trajectories
+///
+/// Other datasets yield no datasets.
+pub fn principal_datasets(dataset: &ResultDataset) -> Vec<ResultDataset> {
+    if dataset.ncomps != 6 || standard_components(&dataset.name).len() != 6 {
+        return Vec::new();
+    }
+    let axes: Vec<(i32, PrincipalAxes)> = dataset
+        .values
+        .iter()
+        .filter(|(_, v)| v.len() >= 6)
+        .map(|(&id, v)| {
+            let tensor = TensorComponents {
+                xx: v[0],
+                yy: v[1],
+                zz: v[2],
+                xy: v[3],
+                yz: v[4],
+                xz: v[5],
+            };
+            (id, compute_principal_axes(&tensor))
+        })
+        .collect();
+
+    let derived = |suffix: &str, value: &dyn Fn(&PrincipalAxes) -> Vec<f64>| {
+        let mut result = ResultDataset::nodal(
+            &format!("{}_{}", dataset.name, suffix),
+            axes.iter().map(|(id, a)| (*id, value(a))).collect(),
+        );
+        result.location = dataset.location;
+        result
+    };
+    let scaled = |d: [f64; 3], s: f64| d.iter().map(|x| x * s).collect::<Vec<f64>>();
+    vec![
+        derived("PRINCIPAL", &|a| vec![a.values.max, a.values.mid, a.values.min]),
+        derived("PMAX", &|a| scaled(a.max_direction, a.values.max)),
+        derived("PMID", &|a| scaled(a.mid_direction, a.values.mid)),
+        derived("PMIN", &|a| scaled(a.min_direction, a.values.min)),
+    ]
+}
 
-    let lambda1 = k * (theta / 3.0).cos() + i1 / 3.0;
-    let lambda2 = k * ((theta + 2.0 * std::f64::consts::PI) / 3.0).cos() + i1 / 3.0;
-    let lambda3 = k * ((theta + 4.0 * std::f64::consts::PI) / 3.0).cos() + i1 / 3.0;
+/// Upper bound of [`safety_factor_dataset`] values, reported where the
+/// von Mises stress vanishes
+pub const MAX_SAFETY_FACTOR: f64 = 1.0e3;
 
-    // Sort in descending order
-    let mut principals = [lambda1, lambda2, lambda3];
-    principals.sort_by(|a, b| b.partial_cmp(a).unwrap());
+/// Safety factor against yield `σ_y / σ_v` of a `STRESS` dataset
+///
+/// `yield_stress` gives the yield stress of each node or element of the
+/// dataset; entities without one are left out. Returns the dataset `SAFETY`
+/// with the single component `FOS`, at the location of `stress`, or `None`
+/// for datasets other than `STRESS`.
+pub fn safety_factor_dataset(
+    stress: &ResultDataset,
+    yield_stress: impl Fn(i32) -> Option<f64>,
+) -> Option<ResultDataset> {
+    if stress.name != "STRESS" {
+        return None;
+    }
+    let values = stress
+        .values
+        .iter()
+        .filter(|(_, v)| v.len() >= 6)
+        .filter_map(|(&id, v)| {
+            let mises = compute_mises_stress(&TensorComponents {
+                xx: v[0],
+                yy: v[1],
+                zz: v[2],
+                xy: v[3],
+                yz: v[4],
+                xz: v[5],
+            });
+            let factor = if mises > 0.0 {
+                (yield_stress(id)? / mises).min(MAX_SAFETY_FACTOR)
+            } else {
+                yield_stress(id).map(|_| MAX_SAFETY_FACTOR)?
+            };
+            Some((id, vec![factor]))
+        })
+        .collect();
+    let mut dataset = ResultDataset::nodal("SAFETY", values);
+    dataset.location = stress.location;
+    dataset.ncomps = 1;
+    dataset.comp_names = vec!["FOS".to_string()];
+    Some(dataset)
+}
+
+/// Extremes of a scalar result over time, with the step and time at which
+/// they occur
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeValue {
+    pub max: f64,
+    pub max_step: i32,
+    pub max_time: f64,
+    pub min: f64,
+    pub min_step: i32,
+    pub min_time: f64,
+}
+
+impl EnvelopeValue {
+    fn new(value: f64, block: &ResultBlock) -> Self {
+        Self {
+            max: value,
+            max_step: block.step,
+            max_time: block.time,
+            min: value,
+            min_step: block.step,
+            min_time: block.time,
+        }
+    }
+
+    fn update(&mut self, value: f64, block: &ResultBlock) {
+        if value > self.max {
+            (self.max, self.max_step, self.max_time) = (value, block.step, block.time);
+        }
+        if value < self.min {
+            (self.min, self.min_step, self.min_time) = (value, block.step, block.time);
+        }
+    }
+
+    fn components(&self) -> Vec<f64> {
+        vec![
+            self.max,
+            self.max_step as f64,
+            self.max_time,
+            self.min,
+            self.min_step as f64,
+            self.min_time,
+        ]
+    }
+}
 
-    PrincipalValues {
-        max: principals[0],
-        mid: principals[1],
-        min: principals[2],
+/// Envelope of a scalar derived from dataset `name` over the non-modal
+/// result blocks of `frd`, per entity
+pub fn envelope(
+    frd: &FrdFile,
+    name: &str,
+    location: ResultLocation,
+    scalar: impl Fn(&[f64]) -> Option<f64>,
+) -> HashMap<i32, EnvelopeValue> {
+    let mut envelope: HashMap<i32, EnvelopeValue> = HashMap::new();
+    for block in frd.result_blocks.iter().filter(|b| b.mode.is_none()) {
+        let Some(dataset) = block
+            .datasets
+            .iter()
+            .find(|d| d.name == name && d.location == location)
+        else {
+            continue;
+        };
+        for (&id, values) in &dataset.values {
+            let Some(value) = scalar(values) else {
+                continue;
+            };
+            envelope
+                .entry(id)
+                .and_modify(|e| e.update(value, block))
+                .or_insert_with(|| EnvelopeValue::new(value, block));
+        }
     }
+    envelope
+}
+
+/// Envelope datasets of the transient or multi-step results in `frd`
+///
+/// - `MISES_ENV`: von Mises stress of the nodal `STRESS` results
+/// - `MISES_ENV` (element): peak von Mises stress of the element nodes
+/// - `DISP_ENV`: displacement magnitude of the `DISP` results
+///
+/// Each has the components `MAX, MAXSTEP, MAXTIME, MIN, MINSTEP, MINTIME`.
+/// Modal result blocks are ignored; missing results yield no dataset.
+pub fn envelope_datasets(frd: &FrdFile) -> Vec<ResultDataset> {
+    let mises = |v: &[f64]| {
+        (v.len() >= 6).then(|| {
+            compute_mises_stress(&TensorComponents {
+                xx: v[0],
+                yy: v[1],
+                zz: v[2],
+                xy: v[3],
+                yz: v[4],
+                xz: v[5],
+            })
+        })
+    };
+    let magnitude =
+        |v: &[f64]| (v.len() >= 3).then(|| v[..3].iter().map(|x| x * x).sum::<f64>().sqrt());
+
+    // Element peaks: the highest nodal value of each element per block
+    let mut element_peaks = FrdFile::new();
+    for block in frd.result_blocks.iter().filter(|b| b.mode.is_none()) {
+        let Some(stress) = block
+            .datasets
+            .iter()
+            .find(|d| d.name == "STRESS" && d.location == ResultLocation::Nodal)
+        else {
+            continue;
+        };
+        let peaks = frd
+            .elements
+            .iter()
+            .filter_map(|(&id, element)| {
+                let peak = element
+                    .nodes
+                    .iter()
+                    .filter_map(|node| mises(stress.values.get(node)?))
+                    .reduce(f64::max)?;
+                Some((id, vec![peak]))
+            })
+            .collect();
+        element_peaks.result_blocks.push(ResultBlock {
+            step: block.step,
+            time: block.time,
+            mode: None,
+            datasets: vec![ResultDataset::element("MISES", peaks)],
+        });
+    }
+
+    let dataset = |name: &str, location, envelope: HashMap<i32, EnvelopeValue>| {
+        if envelope.is_empty() {
+            return None;
+        }
+        let values = envelope
+            .into_iter()
+            .map(|(id, e)| (id, e.components()))
+            .collect();
+        let mut dataset = ResultDataset::nodal(name, values);
+        dataset.location = location;
+        dataset.comp_names = ["MAX", "MAXSTEP", "MAXTIME", "MIN", "MINSTEP", "MINTIME"]
+            .map(String::from)
+            .to_vec();
+        Some(dataset)
+    };
+    let element_envelope = envelope(&element_peaks, "MISES", ResultLocation::Element, |v| {
+        v.first().copied()
+    });
+    [
+        dataset(
+            "MISES_ENV",
+            ResultLocation::Nodal,
+            envelope(frd, "STRESS", ResultLocation::Nodal, mises),
+        ),
+        dataset("MISES_ENV", ResultLocation::Element, element_envelope),
+        dataset(
+            "DISP_ENV",
+            ResultLocation::Nodal,
+            envelope(frd, "DISP", ResultLocation::Nodal, magnitude),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
 }
 
 /// Compute hydrostatic (mean) stress
@@ -283,6 +562,147 @@ mod tests {
         assert!((sum - 100.0).abs() < 1e-3, "Sum of principals should equal trace (100)");
     }
 
+    #[test]
+    fn test_principal_axes_diagonalize_tensor() {
+        let stress = TensorComponents {
+            xx: 100.0,
+            yy: 50.0,
+            zz: 25.0,
+            xy: 10.0,
+            yz: 5.0,
+            xz: 2.0,
+        };
+        let axes = compute_principal_axes(&stress);
+        let values = compute_principal_stresses(&stress);
+        assert_eq!(axes.values.max, values.max);
+        assert_eq!(axes.values.mid, values.mid);
+        assert_eq!(axes.values.min, values.min);
+        // Invariants are preserved
+        let sum = values.max + values.mid + values.min;
+        assert!((sum - 175.0).abs() < 1e-9);
+
+        let t = [
+            [stress.xx, stress.xy, stress.xz],
+            [stress.xy, stress.yy, stress.yz],
+            [stress.xz, stress.yz, stress.zz],
+        ];
+        for (d, lambda) in [
+            (axes.max_direction, axes.values.max),
+            (axes.mid_direction, axes.values.mid),
+            (axes.min_direction, axes.values.min),
+        ] {
+            let norm: f64 = d.iter().map(|x| x * x).sum();
+            assert!((norm - 1.0).abs() < 1e-12);
+            for i in 0..3 {
+                let td: f64 = (0..3).map(|j| t[i][j] * d[j]).sum();
+                assert!((td - lambda * d[i]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_principal_axes_of_pure_shear() {
+        // τ_xy = 100: principal values ±100 along (1, ±1, 0)/√2, 0 along z
+        let stress = TensorComponents {
+            xy: 100.0,
+            ..Default::default()
+        };
+        let axes = compute_principal_axes(&stress);
+        let r = 0.5_f64.sqrt();
+        assert!((axes.values.max - 100.0).abs() < 1e-9);
+        assert!(axes.values.mid.abs() < 1e-9);
+        assert!((axes.values.min + 100.0).abs() < 1e-9);
+        for (a, b) in axes.max_direction.iter().zip([r, r, 0.0]) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert!((axes.mid_direction[2] - 1.0).abs() < 1e-12);
+        assert!((axes.min_direction[0].abs() - r).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_principal_datasets_of_stress() {
+        let values = [(1, vec![0.0, 0.0, 0.0, 100.0, 0.0, 0.0])].into_iter().collect();
+        let stress = ResultDataset::nodal("STRESS", values);
+        let derived = principal_datasets(&stress);
+        let names: Vec<&str> = derived.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["STRESS_PRINCIPAL", "STRESS_PMAX", "STRESS_PMID", "STRESS_PMIN"]);
+        assert_eq!(derived[1].ncomps, 3);
+        let pmax = &derived[1].values[&1];
+        assert!((pmax[0] - 100.0 * 0.5_f64.sqrt()).abs() < 1e-9);
+
+        let disp = ResultDataset::nodal("DISP", [(1, vec![0.0; 3])].into_iter().collect());
+        assert!(principal_datasets(&disp).is_empty());
+    }
+
+    #[test]
+    fn test_safety_factor_against_yield() {
+        let values = [
+            (1, vec![100.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+            (2, vec![0.0; 6]),
+            (3, vec![400.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        ];
+        let stress = ResultDataset::nodal("STRESS", values.into_iter().collect());
+        let safety = safety_factor_dataset(&stress, |id| (id != 3).then_some(250.0)).unwrap();
+        assert_eq!(safety.comp_names, ["FOS"]);
+        assert_eq!(safety.values[&1], vec![2.5]);
+        assert_eq!(safety.values[&2], vec![MAX_SAFETY_FACTOR]);
+        assert!(!safety.values.contains_key(&3));
+
+        let disp = ResultDataset::nodal("DISP", [(1, vec![0.0; 3])].into_iter().collect());
+        assert!(safety_factor_dataset(&disp, |_| Some(250.0)).is_none());
+    }
+
+    #[test]
+    fn test_envelope_datasets_track_extremes_over_time() {
+        let mut frd = FrdFile::new();
+        frd.elements.insert(
+            1,
+            crate::frd_reader::FrdElement {
+                id: 1,
+                element_type: 11,
+                nodes: vec![1, 2],
+            },
+        );
+        for (step, sxx, ux) in [(1, 50.0, 3.0), (2, 200.0, -4.0), (3, 100.0, 1.0)] {
+            let stress = [(1, vec![sxx, 0.0, 0.0, 0.0, 0.0, 0.0]), (2, vec![0.0; 6])];
+            let disp = [(1, vec![ux, 0.0, 0.0]), (2, vec![0.0; 3])];
+            frd.result_blocks.push(ResultBlock {
+                step,
+                time: step as f64 * 0.5,
+                mode: None,
+                datasets: vec![
+                    ResultDataset::nodal("STRESS", stress.into_iter().collect()),
+                    ResultDataset::nodal("DISP", disp.into_iter().collect()),
+                ],
+            });
+        }
+        // Modal blocks do not count
+        frd.result_blocks.push(ResultBlock {
+            step: 4,
+            time: 10.0,
+            mode: Some(1),
+            datasets: vec![ResultDataset::nodal("DISP", [(1, vec![99.0, 0.0, 0.0])].into())],
+        });
+
+        let datasets = envelope_datasets(&frd);
+        let names: Vec<_> = datasets.iter().map(|d| (d.name.as_str(), d.location)).collect();
+        assert_eq!(
+            names,
+            [
+                ("MISES_ENV", ResultLocation::Nodal),
+                ("MISES_ENV", ResultLocation::Element),
+                ("DISP_ENV", ResultLocation::Nodal)
+            ]
+        );
+        assert_eq!(datasets[0].comp_names[1], "MAXSTEP");
+        assert_eq!(datasets[0].values[&1], vec![200.0, 2.0, 1.0, 50.0, 1.0, 0.5]);
+        assert_eq!(datasets[1].values[&1], vec![200.0, 2.0, 1.0, 50.0, 1.0, 0.5]);
+        assert_eq!(datasets[2].values[&1], vec![4.0, 2.0, 1.0, 1.0, 3.0, 1.5]);
+        assert_eq!(datasets[2].values[&2][0], 0.0);
+
+        assert!(envelope_datasets(&FrdFile::new()).is_empty());
+    }
+
     #[test]
     fn test_hydrostatic_stress() {
         let stress = TensorComponents {
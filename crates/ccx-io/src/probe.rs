@@ -0,0 +1,142 @@
+//! Spot values of results at nodes and points.
+//!
+//! [`probe_node`] reads every dataset of a result block at one node.
+//! [`probe_point`] locates a point in the solid elements of the model with
+//! the spatial index of the solver mesh and interpolates the nodal datasets
+//! with the shape functions of the containing element, midside nodes
+//! included; element datasets give the value of that element.
+
+use ccx_solver::{Mesh, PointLocation, SolidElement};
+
+use crate::frd_reader::{ResultBlock, ResultDataset, ResultLocation};
+
+/// Tolerance of [`probe_point`] in natural coordinates, so points on faces
+/// and edges are found
+const LOCATE_TOLERANCE: f64 = 1e-6;
+
+/// Value of one dataset at a probe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeValue {
+    pub dataset: String,
+    pub location: ResultLocation,
+    pub comp_names: Vec<String>,
+    pub values: Vec<f64>,
+}
+
+impl ProbeValue {
+    fn new(dataset: &ResultDataset, values: Vec<f64>) -> Self {
+        Self {
+            dataset: dataset.name.clone(),
+            location: dataset.location,
+            comp_names: dataset.comp_names.clone(),
+            values,
+        }
+    }
+}
+
+/// Values at a point, with the element containing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointProbe {
+    pub location: PointLocation,
+    pub values: Vec<ProbeValue>,
+}
+
+/// Values of the nodal datasets of `block` at `node`, in dataset order
+pub fn probe_node(block: &ResultBlock, node: i32) -> Vec<ProbeValue> {
+    block
+        .datasets
+        .iter()
+        .filter(|dataset| dataset.location == ResultLocation::Nodal)
+        .filter_map(|dataset| Some(ProbeValue::new(dataset, dataset.values.get(&node)?.clone())))
+        .collect()
+}
+
+/// Values of the datasets of `block` at `point` of `mesh`; `None` when no
+/// solid element contains the point
+///
+/// Nodal datasets missing a node of the element are left out.
+pub fn probe_point(mesh: &Mesh, block: &ResultBlock, point: [f64; 3]) -> Option<PointProbe> {
+    let location = mesh.spatial_index().locate(point, LOCATE_TOLERANCE)?;
+    let element = &mesh.elements[&location.element];
+    let solid = SolidElement::new(element.id, element.element_type, element.nodes.clone());
+    let shape = solid.shape_functions(location.natural);
+
+    let values = block
+        .datasets
+        .iter()
+        .filter_map(|dataset| match dataset.location {
+            ResultLocation::Nodal => {
+                let mut values = vec![0.0; dataset.ncomps];
+                for (node, n) in element.nodes.iter().zip(&shape) {
+                    for (v, x) in values.iter_mut().zip(dataset.values.get(node)?) {
+                        *v += n * x;
+                    }
+                }
+                Some(ProbeValue::new(dataset, values))
+            }
+            ResultLocation::Element => {
+                let values = dataset.values.get(&element.id)?.clone();
+                Some(ProbeValue::new(dataset, values))
+            }
+        })
+        .collect();
+    Some(PointProbe { location, values })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ccx_solver::{Division, ElementType, MeshBuilder};
+    use std::collections::HashMap;
+
+    fn block(mesh: &Mesh, field: impl Fn([f64; 3]) -> Vec<f64>) -> ResultBlock {
+        let values: HashMap<i32, Vec<f64>> = mesh
+            .nodes
+            .values()
+            .map(|node| (node.id, field(node.coords())))
+            .collect();
+        let elements = mesh
+            .elements
+            .keys()
+            .map(|&id| (id, vec![id as f64]))
+            .collect();
+        ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets: vec![
+                ResultDataset::nodal("DISP", values),
+                ResultDataset::element("ERROR", elements),
+            ],
+        }
+    }
+
+    #[test]
+    fn interpolates_quadratic_fields_exactly() {
+        let divisions = [2, 2, 1].map(Division::uniform);
+        let mesh = MeshBuilder::brick([2.0, 2.0, 1.0], divisions, ElementType::C3D20)
+            .unwrap()
+            .mesh;
+        let field = |[x, y, z]: [f64; 3]| vec![x * x, x * y + z, 3.0];
+        let block = block(&mesh, field);
+
+        let at = [1.3, 0.4, 0.7];
+        let probe = probe_point(&mesh, &block, at).unwrap();
+        let disp = &probe.values[0];
+        assert_eq!(
+            (disp.dataset.as_str(), disp.location),
+            ("DISP", ResultLocation::Nodal)
+        );
+        for (value, expected) in disp.values.iter().zip(field(at)) {
+            assert!((value - expected).abs() < 1e-12, "{value} != {expected}");
+        }
+        assert_eq!(probe.values[1].values, [probe.location.element as f64]);
+        assert!(probe_point(&mesh, &block, [3.0, 0.0, 0.0]).is_none());
+
+        let node = mesh.nodes.values().next().unwrap();
+        let values = probe_node(&block, node.id);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].values, field(node.coords()));
+        assert!(probe_node(&block, -1).is_empty());
+    }
+}
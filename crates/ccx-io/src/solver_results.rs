@@ -0,0 +1,337 @@
+//! `.dat` and FRD output of the Rust solver's static results.
+//!
+//! Converts the displacements and recovered solid stresses of an
+//! [`AnalysisResults`] into the ccx result structures, so a solved deck
+//! produces the same files as a ccx run:
+//!
+//! - `.dat`: `U` and reaction forces `RF` for all nodes (`NALL`), `S`, `E`
+//!   and `ME` per integration point and beam section forces `SF` per element
+//!   end (`EALL`)
+//! - FRD: `DISP` and extrapolated, averaged nodal `STRESS`, `TOSTRAIN` and
+//!   `MESTRAIN`, plus the ZZ error estimate as the element dataset `ERROR`
+//!
+//! Nodes split between averaging regions (see
+//! [`ccx_solver::NodalAveraging`]) are written as extra nodes at the
+//! position and with the displacement of the node they duplicate.
+//!
+//! The elastic strains `EE` are written under the CalculiX mechanical strain
+//! names `ME`/`MESTRAIN`: without thermal and plastic strains, which the
+//! linear static solver does not have, the two coincide.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ccx_inp::Deck;
+use ccx_solver::{AnalysisResults, ElementStresses, Mesh};
+
+use crate::dat_writer::{DatSection, DatStep};
+use crate::frd_reader::{FrdElement, FrdFile, FrdHeader, ResultBlock, ResultDataset};
+use crate::history::{HistoryOutput, HistoryRequest};
+use crate::output::frd_element_type;
+
+/// `.dat` increments of a static solve with the history of the deck's
+/// print requests
+pub fn static_dat_steps(
+    deck: &Deck,
+    results: &AnalysisResults,
+) -> Result<(HistoryOutput, Vec<DatStep>), String> {
+    // Decks with print requests get only the requested tables, like ccx
+    let requests = HistoryRequest::from_deck(deck)?;
+    let mut history = HistoryOutput::new(requests);
+    let increment = static_dat_step(results);
+    history.record(&increment);
+    let dat_steps = if history.requests().is_empty() {
+        vec![increment]
+    } else {
+        history.dat_steps().to_vec()
+    };
+    Ok((history, dat_steps))
+}
+
+/// `.dat` tables of a solved static step
+pub fn static_dat_step(results: &AnalysisResults) -> DatStep {
+    let mut sections = vec![DatSection::Displacements {
+        set: "NALL".to_string(),
+        values: results.displacements.clone(),
+    }];
+    if !results.reaction_forces.is_empty() {
+        sections.push(DatSection::Forces {
+            set: "NALL".to_string(),
+            values: results.reaction_forces.clone(),
+        });
+    }
+    if let Some(stresses) = &results.stresses {
+        let set = || "EALL".to_string();
+        let elements = &stresses.elements;
+        sections.push(DatSection::Stresses {
+            set: set(),
+            values: integration_point_rows(elements, |e| &e.integration_points),
+        });
+        sections.push(DatSection::Strains {
+            set: set(),
+            values: integration_point_rows(elements, |e| &e.strains),
+        });
+        sections.push(DatSection::MechanicalStrains {
+            set: set(),
+            values: integration_point_rows(elements, |e| &e.elastic_strains),
+        });
+    }
+    if !results.section_forces.is_empty() {
+        sections.push(DatSection::SectionForces {
+            set: "EALL".to_string(),
+            values: results
+                .section_forces
+                .iter()
+                .flat_map(|beam| {
+                    (1..)
+                        .zip(beam.ends)
+                        .map(|(end, f)| (beam.element_id, end, f))
+                })
+                .collect(),
+        });
+    }
+    DatStep {
+        step: 1,
+        increment: 1,
+        time: 1.0,
+        sections,
+    }
+}
+
+/// `(element, point, values)` rows of one integration point field
+fn integration_point_rows(
+    elements: &[ElementStresses],
+    field: impl Fn(&ElementStresses) -> &Vec<[f64; 6]>,
+) -> Vec<(i32, i32, [f64; 6])> {
+    elements
+        .iter()
+        .flat_map(|element| {
+            field(element)
+                .iter()
+                .enumerate()
+                .map(|(point, v)| (element.element_id, point as i32 + 1, *v))
+        })
+        .collect()
+}
+
+/// Nodal tensor values reordered from `.dat` (xx, yy, zz, xy, xz, yz) to
+/// FRD (xx, yy, zz, xy, yz, zx) component order
+fn frd_tensor(nodal: &BTreeMap<i32, [f64; 6]>) -> HashMap<i32, Vec<f64>> {
+    nodal
+        .iter()
+        .map(|(node, t)| (*node, vec![t[0], t[1], t[2], t[3], t[5], t[4]]))
+        .collect()
+}
+
+/// FRD model and nodal results of a solved static step
+///
+/// Elements without an FRD type code are left out of the element block.
+pub fn static_frd(mesh: &Mesh, results: &AnalysisResults, job_name: &str) -> FrdFile {
+    let split_nodes = results
+        .stresses
+        .as_ref()
+        .map(|stresses| &stresses.split_nodes);
+    let split = || split_nodes.into_iter().flatten();
+    let mut nodes: HashMap<_, _> = mesh
+        .nodes
+        .values()
+        .map(|node| (node.id, node.coords()))
+        .collect();
+    for (&id, origin) in split() {
+        if let Some(coords) = nodes.get(origin).copied() {
+            nodes.insert(id, coords);
+        }
+    }
+    let elements = mesh
+        .elements
+        .values()
+        .filter_map(|element| {
+            let code = frd_element_type(&format!("{:?}", element.element_type))?;
+            Some((
+                element.id,
+                FrdElement {
+                    id: element.id,
+                    element_type: code,
+                    nodes: match &results.stresses {
+                        Some(stresses) => stresses.element_node_ids(element.id, &element.nodes),
+                        None => &element.nodes,
+                    }
+                    .to_vec(),
+                },
+            ))
+        })
+        .collect();
+
+    let mut displacements: HashMap<i32, Vec<f64>> = results
+        .displacements
+        .iter()
+        .map(|(node, u)| (*node, u.to_vec()))
+        .collect();
+    for (&id, origin) in split() {
+        if let Some(u) = displacements.get(origin).cloned() {
+            displacements.insert(id, u);
+        }
+    }
+    let mut datasets = vec![ResultDataset::nodal("DISP", displacements)];
+    if let Some(stresses) = &results.stresses {
+        datasets.push(ResultDataset::nodal("STRESS", frd_tensor(&stresses.nodal)));
+        datasets.push(ResultDataset::nodal(
+            "TOSTRAIN",
+            frd_tensor(&stresses.nodal_strains),
+        ));
+        datasets.push(ResultDataset::nodal(
+            "MESTRAIN",
+            frd_tensor(&stresses.nodal_elastic_strains),
+        ));
+    }
+    if let Some(estimate) = &results.error_estimate {
+        let values = estimate
+            .elements
+            .iter()
+            .map(|element| (element.element_id, vec![element.relative_error]))
+            .collect();
+        datasets.push(ResultDataset::element("ERROR", values));
+    }
+
+    FrdFile {
+        header: FrdHeader {
+            job_name: job_name.to_string(),
+            ..Default::default()
+        },
+        nodes,
+        elements,
+        result_blocks: vec![ResultBlock {
+            step: 1,
+            time: 1.0,
+            mode: None,
+            datasets,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frd_reader::ResultLocation;
+    use ccx_solver::{
+        AnalysisType, BeamSectionForces, Element, ElementError, ElementType, ErrorEstimate, Node,
+        StressField,
+    };
+
+    #[test]
+    fn converts_displacements_and_stresses() {
+        let mut mesh = Mesh::new();
+        for (id, [x, y, z]) in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            mesh.add_node(Node::new(id as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D4, vec![1, 2, 3, 4]))
+            .unwrap();
+        let stress = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let strain = [1e-3, 2e-3, 3e-3, 4e-3, 5e-3, 6e-3];
+        let results = AnalysisResults {
+            success: true,
+            num_dofs: 12,
+            num_equations: 12,
+            analysis_type: AnalysisType::LinearStatic,
+            message: String::new(),
+            solve_info: None,
+            displacements: (1..=4).map(|id| (id, [0.1, 0.0, 0.0])).collect(),
+            reaction_forces: vec![(1, [-2.0, 0.0, 0.0])],
+            stresses: Some(StressField {
+                elements: vec![ElementStresses {
+                    element_id: 1,
+                    integration_points: vec![stress],
+                    strains: vec![strain],
+                    elastic_strains: vec![strain],
+                }],
+                nodal: (1..=4).map(|id| (id, stress)).collect::<BTreeMap<_, _>>(),
+                nodal_strains: (1..=4).map(|id| (id, strain)).collect(),
+                nodal_elastic_strains: (1..=4).map(|id| (id, strain)).collect(),
+                ..Default::default()
+            }),
+            section_forces: vec![BeamSectionForces {
+                element_id: 2,
+                ends: [
+                    [10.0, 1.0, 0.0, 0.0, 0.0, 5.0],
+                    [10.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+                ],
+            }],
+            error_estimate: Some(ErrorEstimate {
+                elements: vec![ElementError {
+                    element_id: 1,
+                    energy_error: 0.5,
+                    relative_error: 4.0,
+                }],
+                global_relative_error: 4.0,
+            }),
+            audit: Default::default(),
+            unit_warnings: Vec::new(),
+            inverted_elements: Vec::new(),
+            failure: None,
+        };
+
+        let dat = static_dat_step(&results);
+        assert_eq!(dat.sections.len(), 6);
+        assert_eq!(
+            dat.sections[1],
+            DatSection::Forces {
+                set: "NALL".to_string(),
+                values: vec![(1, [-2.0, 0.0, 0.0])],
+            }
+        );
+        assert_eq!(
+            dat.sections[2],
+            DatSection::Stresses {
+                set: "EALL".to_string(),
+                values: vec![(1, 1, stress)],
+            }
+        );
+        assert_eq!(
+            dat.sections[3],
+            DatSection::Strains {
+                set: "EALL".to_string(),
+                values: vec![(1, 1, strain)],
+            }
+        );
+        assert!(matches!(
+            dat.sections[4],
+            DatSection::MechanicalStrains { .. }
+        ));
+        let DatSection::SectionForces { values, .. } = &dat.sections[5] else {
+            panic!("expected section forces, got {:?}", dat.sections[5]);
+        };
+        assert_eq!(values[1], (2, 2, [10.0, 1.0, 0.0, 0.0, 0.0, 0.0]));
+
+        let frd = static_frd(&mesh, &results, "job");
+        assert_eq!(frd.elements[&1].element_type, 3);
+        let datasets = &frd.result_blocks[0].datasets;
+        assert_eq!(datasets[0].name, "DISP");
+        assert_eq!(datasets[1].comp_names[4], "SYZ");
+        assert_eq!(datasets[1].values[&2], vec![1.0, 2.0, 3.0, 4.0, 6.0, 5.0]);
+        assert_eq!(datasets[2].name, "TOSTRAIN");
+        assert_eq!(datasets[2].comp_names[5], "EZX");
+        assert_eq!(datasets[2].values[&1][4], 6e-3);
+        assert_eq!(datasets[3].name, "MESTRAIN");
+        assert_eq!(datasets[4].name, "ERROR");
+        assert_eq!(datasets[4].location, ResultLocation::Element);
+        assert_eq!(datasets[4].values[&1], vec![4.0]);
+
+        // Node 5 splits node 1 off element 1
+        let mut split = results;
+        let stresses = split.stresses.as_mut().unwrap();
+        stresses.split_nodes.insert(5, 1);
+        stresses.element_nodes.insert(1, vec![5, 2, 3, 4]);
+        let frd = static_frd(&mesh, &split, "job");
+        assert_eq!(frd.nodes[&5], frd.nodes[&1]);
+        assert_eq!(frd.elements[&1].nodes, vec![5, 2, 3, 4]);
+        let displacements = &frd.result_blocks[0].datasets[0].values;
+        assert_eq!(displacements[&5], displacements[&1]);
+    }
+}
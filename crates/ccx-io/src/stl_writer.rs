@@ -0,0 +1,134 @@
+//! ASCII STL export of the surface of a mesh.
+//!
+//! Solid elements contribute their free faces (see [`Mesh::free_faces`]),
+//! shells and membranes their mid-surface. Quadrilaterals are split into two
+//! triangles along the diagonal from their first corner; mid-side nodes of
+//! quadratic elements are dropped. Trusses and beams have no surface and are
+//! left out.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ccx_solver::{ElementType, Mesh};
+
+/// Corner node IDs of the facets of `mesh`, in element ID order
+fn facets(mesh: &Mesh) -> Vec<Vec<i32>> {
+    let mut facets: Vec<Vec<i32>> = Vec::new();
+    let mut ids: Vec<i32> = mesh.elements.keys().copied().collect();
+    ids.sort_unstable();
+    for id in ids {
+        let element = &mesh.elements[&id];
+        let corners = match element.element_type {
+            ElementType::S3 | ElementType::S6 | ElementType::M3D3 | ElementType::M3D6 => 3,
+            ElementType::S4 | ElementType::S8 | ElementType::M3D4 | ElementType::M3D8 => 4,
+            _ => continue,
+        };
+        facets.push(element.nodes.iter().take(corners).copied().collect());
+    }
+    for face in mesh.free_faces() {
+        let corners = if face.nodes.len() == 3 || face.nodes.len() == 6 {
+            3
+        } else {
+            4
+        };
+        facets.push(face.nodes[..corners].to_vec());
+    }
+    facets
+}
+
+/// STL text of the surface of `mesh`, with `name` on the `solid` line
+pub fn stl_text(mesh: &Mesh, name: &str) -> io::Result<String> {
+    let point = |id: &i32| {
+        mesh.nodes
+            .get(id)
+            .map(|node| node.coords())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("missing node {id}")))
+    };
+    let mut text = format!("solid {name}\n");
+    for facet in facets(mesh) {
+        let triangles: &[[usize; 3]] = if facet.len() == 4 {
+            &[[0, 1, 2], [0, 2, 3]]
+        } else {
+            &[[0, 1, 2]]
+        };
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|i| point(&facet[i]));
+            let (a, b, c) = (a?, b?, c?);
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let mut n = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            if length > 0.0 {
+                n = n.map(|x| x / length);
+            }
+            let _ = writeln!(text, "  facet normal {:e} {:e} {:e}", n[0], n[1], n[2]);
+            text.push_str("    outer loop\n");
+            for p in [a, b, c] {
+                let _ = writeln!(text, "      vertex {:e} {:e} {:e}", p[0], p[1], p[2]);
+            }
+            text.push_str("    endloop\n  endfacet\n");
+        }
+    }
+    let _ = writeln!(text, "endsolid {name}");
+    Ok(text)
+}
+
+/// Write the surface of `mesh` as an ASCII STL file
+pub fn write_stl(path: impl AsRef<Path>, mesh: &Mesh) -> io::Result<()> {
+    let path = path.as_ref();
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("mesh");
+    fs::write(path, stl_text(mesh, name)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surface_reader::{SurfaceElement, SurfaceFormat, parse_surface};
+    use ccx_solver::{Element, Node};
+
+    #[test]
+    fn writes_brick_skin_readable_as_stl() {
+        let mut mesh = Mesh::new();
+        for (i, [x, y, z]) in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D8, (1..=8).collect()))
+            .unwrap();
+
+        let text = stl_text(&mesh, "cube").unwrap();
+        assert!(text.starts_with("solid cube\n"));
+        assert_eq!(text.matches("endfacet").count(), 12);
+        // Face 1 of the brick is the bottom, pointing down
+        let normal: Vec<f64> = text.lines().nth(1).unwrap()["  facet normal".len()..]
+            .split_whitespace()
+            .map(|x| x.parse().unwrap())
+            .collect();
+        assert_eq!(normal, vec![0.0, 0.0, -1.0]);
+
+        let back =
+            parse_surface(text.as_bytes(), SurfaceFormat::Stl, SurfaceElement::Shell).unwrap();
+        assert_eq!(back.nodes.len(), 8);
+        assert_eq!(back.elements.len(), 12);
+    }
+}
@@ -0,0 +1,517 @@
+//! STL, OBJ and PLY surface mesh import.
+//!
+//! Reads triangulated or polygonal surfaces into a solver [`Mesh`] of
+//! shell (S3/S4) or membrane (M3D3/M3D4) elements, for contact surfaces
+//! and visual checks. Supported variants:
+//! - STL: ASCII and binary; coincident facet vertices are merged
+//! - OBJ: `v` and `f` records (including `v/vt/vn` and negative indices)
+//! - PLY: ASCII, binary little- and big-endian
+//!
+//! Triangles and quads map one-to-one; larger polygons are fan-triangulated.
+//! Node and element ids are 1-based in file order.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ccx_solver::{Element, ElementType, Mesh, Node};
+
+/// Surface file format, usually derived from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceFormat {
+    Stl,
+    Obj,
+    Ply,
+}
+
+impl SurfaceFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "stl" => Some(Self::Stl),
+            "obj" => Some(Self::Obj),
+            "ply" => Some(Self::Ply),
+            _ => None,
+        }
+    }
+}
+
+/// Element family used for imported facets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceElement {
+    /// S3 / S4 shells
+    #[default]
+    Shell,
+    /// M3D3 / M3D4 membranes
+    Membrane,
+}
+
+impl SurfaceElement {
+    fn element_type(self, corners: usize) -> ElementType {
+        match (self, corners) {
+            (Self::Shell, 4) => ElementType::S4,
+            (Self::Shell, _) => ElementType::S3,
+            (Self::Membrane, 4) => ElementType::M3D4,
+            (Self::Membrane, _) => ElementType::M3D3,
+        }
+    }
+}
+
+/// Read a surface file, choosing the format from its extension.
+pub fn read_surface(path: impl AsRef<Path>, element: SurfaceElement) -> io::Result<Mesh> {
+    let path = path.as_ref();
+    let format = SurfaceFormat::from_path(path).ok_or_else(|| {
+        invalid(format!(
+            "unsupported surface format '{}' (expected .stl, .obj or .ply)",
+            path.display()
+        ))
+    })?;
+    parse_surface(&fs::read(path)?, format, element)
+}
+
+/// Parse surface file contents of a known format.
+pub fn parse_surface(
+    bytes: &[u8],
+    format: SurfaceFormat,
+    element: SurfaceElement,
+) -> io::Result<Mesh> {
+    let (points, faces) = match format {
+        SurfaceFormat::Stl => parse_stl(bytes)?,
+        SurfaceFormat::Obj => parse_obj(&text(bytes)?)?,
+        SurfaceFormat::Ply => parse_ply(bytes)?,
+    };
+    build_mesh(points, faces, element)
+}
+
+type Polygons = (Vec<[f64; 3]>, Vec<Vec<usize>>);
+
+fn build_mesh(
+    points: Vec<[f64; 3]>,
+    faces: Vec<Vec<usize>>,
+    element: SurfaceElement,
+) -> io::Result<Mesh> {
+    let mut mesh = Mesh::new();
+    for (idx, [x, y, z]) in points.iter().copied().enumerate() {
+        mesh.add_node(Node::new(idx as i32 + 1, x, y, z));
+    }
+    let mut next_id = 1;
+    for face in faces {
+        if let Some(&bad) = face.iter().find(|&&v| v >= points.len()) {
+            return Err(invalid(format!(
+                "face references vertex {} but only {} vertices exist",
+                bad + 1,
+                points.len()
+            )));
+        }
+        let polygons: Vec<Vec<usize>> = match face.len() {
+            0..=2 => continue,
+            3 | 4 => vec![face],
+            _ => (1..face.len() - 1)
+                .map(|i| vec![face[0], face[i], face[i + 1]])
+                .collect(),
+        };
+        for polygon in polygons {
+            let nodes = polygon.iter().map(|&v| v as i32 + 1).collect();
+            mesh.add_element(Element::new(
+                next_id,
+                element.element_type(polygon.len()),
+                nodes,
+            ))
+            .map_err(invalid)?;
+            next_id += 1;
+        }
+    }
+    mesh.calculate_dofs();
+    Ok(mesh)
+}
+
+fn parse_stl(bytes: &[u8]) -> io::Result<Polygons> {
+    let is_binary = bytes.len() >= 84 && {
+        let count = u32::from_le_bytes(bytes[80..84].try_into().expect("4 bytes")) as usize;
+        bytes.len() == 84 + count * 50
+    };
+    let mut triangles: Vec<[[f64; 3]; 3]> = Vec::new();
+    if is_binary {
+        for record in bytes[84..].chunks_exact(50) {
+            let float = |offset: usize| {
+                f32::from_le_bytes(record[offset..offset + 4].try_into().expect("4 bytes")) as f64
+            };
+            // Skip the 12-byte normal; three vertices follow.
+            let vertex = |v: usize| {
+                let base = 12 + v * 12;
+                [float(base), float(base + 4), float(base + 8)]
+            };
+            triangles.push([vertex(0), vertex(1), vertex(2)]);
+        }
+    } else {
+        let mut current = Vec::new();
+        for (line_no, line) in text(bytes)?.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("vertex") => {
+                    current.push(parse_xyz(tokens, line_no + 1)?);
+                }
+                Some("endfacet") => {
+                    let [a, b, c] = current[..] else {
+                        return Err(invalid(format!(
+                            "line {}: facet has {} vertices, expected 3",
+                            line_no + 1,
+                            current.len()
+                        )));
+                    };
+                    triangles.push([a, b, c]);
+                    current.clear();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut points = Vec::new();
+    let mut lookup: HashMap<[u64; 3], usize> = HashMap::new();
+    let faces = triangles
+        .into_iter()
+        .map(|triangle| {
+            triangle
+                .iter()
+                .map(|p| {
+                    let key = p.map(f64::to_bits);
+                    *lookup.entry(key).or_insert_with(|| {
+                        points.push(*p);
+                        points.len() - 1
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    Ok((points, faces))
+}
+
+fn parse_obj(text: &str) -> io::Result<Polygons> {
+    let mut points = Vec::new();
+    let mut faces = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => points.push(parse_xyz(tokens, line_no + 1)?),
+            Some("f") => {
+                let face = tokens
+                    .map(|token| {
+                        let index = token.split('/').next().unwrap_or("");
+                        let index: i64 = index.parse().map_err(|_| {
+                            invalid(format!(
+                                "line {}: invalid face index '{token}'",
+                                line_no + 1
+                            ))
+                        })?;
+                        // Negative indices count back from the latest vertex.
+                        let resolved = if index < 0 {
+                            points.len() as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        usize::try_from(resolved).map_err(|_| {
+                            invalid(format!(
+                                "line {}: face index {index} out of range",
+                                line_no + 1
+                            ))
+                        })
+                    })
+                    .collect::<io::Result<Vec<usize>>>()?;
+                faces.push(face);
+            }
+            _ => {}
+        }
+    }
+    Ok((points, faces))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyEncoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Debug, Clone)]
+enum PlyProperty {
+    Scalar { name: String, kind: String },
+    List { count: String, item: String },
+}
+
+#[derive(Debug, Clone)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+fn parse_ply(bytes: &[u8]) -> io::Result<Polygons> {
+    let end_marker = b"end_header";
+    let header_end = bytes
+        .windows(end_marker.len())
+        .position(|w| w == end_marker)
+        .ok_or_else(|| invalid("PLY file has no end_header".to_string()))?;
+    let mut body_start = header_end + end_marker.len();
+    while bytes
+        .get(body_start)
+        .is_some_and(|b| *b == b'\r' || *b == b' ')
+    {
+        body_start += 1;
+    }
+    if bytes.get(body_start) == Some(&b'\n') {
+        body_start += 1;
+    }
+
+    let header = text(&bytes[..header_end])?;
+    let mut encoding = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in header.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["format", kind, ..] => {
+                encoding = Some(match *kind {
+                    "ascii" => PlyEncoding::Ascii,
+                    "binary_little_endian" => PlyEncoding::LittleEndian,
+                    "binary_big_endian" => PlyEncoding::BigEndian,
+                    other => return Err(invalid(format!("unknown PLY format '{other}'"))),
+                });
+            }
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid PLY element count '{count}'")))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, _name] => {
+                if let Some(element) = elements.last_mut() {
+                    element.properties.push(PlyProperty::List {
+                        count: count.to_string(),
+                        item: item.to_string(),
+                    });
+                }
+            }
+            ["property", kind, name] => {
+                if let Some(element) = elements.last_mut() {
+                    element.properties.push(PlyProperty::Scalar {
+                        name: name.to_string(),
+                        kind: kind.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    let encoding = encoding.ok_or_else(|| invalid("PLY header has no format line".to_string()))?;
+
+    let mut reader = PlyReader {
+        encoding,
+        bytes: &bytes[body_start..],
+        pos: 0,
+        tokens: Vec::new(),
+    };
+    if encoding == PlyEncoding::Ascii {
+        reader.tokens = text(reader.bytes)?
+            .split_whitespace()
+            .rev()
+            .map(str::to_string)
+            .collect();
+    }
+
+    let mut points = Vec::new();
+    let mut faces = Vec::new();
+    for element in &elements {
+        for _ in 0..element.count {
+            let mut xyz = [0.0; 3];
+            let mut indices = Vec::new();
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar { name, kind } => {
+                        let value = reader.scalar(kind)?;
+                        match name.as_str() {
+                            "x" => xyz[0] = value,
+                            "y" => xyz[1] = value,
+                            "z" => xyz[2] = value,
+                            _ => {}
+                        }
+                    }
+                    PlyProperty::List { count, item } => {
+                        let n = reader.scalar(count)? as usize;
+                        indices = (0..n)
+                            .map(|_| reader.scalar(item).map(|v| v as usize))
+                            .collect::<io::Result<_>>()?;
+                    }
+                }
+            }
+            match element.name.as_str() {
+                "vertex" => points.push(xyz),
+                "face" => faces.push(indices),
+                _ => {}
+            }
+        }
+    }
+    Ok((points, faces))
+}
+
+struct PlyReader<'a> {
+    encoding: PlyEncoding,
+    bytes: &'a [u8],
+    pos: usize,
+    /// Remaining ASCII tokens, reversed so `pop` yields the next one
+    tokens: Vec<String>,
+}
+
+impl PlyReader<'_> {
+    fn scalar(&mut self, kind: &str) -> io::Result<f64> {
+        if self.encoding == PlyEncoding::Ascii {
+            let token = self
+                .tokens
+                .pop()
+                .ok_or_else(|| invalid("PLY body ends early".to_string()))?;
+            return token
+                .parse()
+                .map_err(|_| invalid(format!("invalid PLY value '{token}'")));
+        }
+        let size = match kind {
+            "char" | "int8" | "uchar" | "uint8" => 1,
+            "short" | "int16" | "ushort" | "uint16" => 2,
+            "int" | "int32" | "uint" | "uint32" | "float" | "float32" => 4,
+            "double" | "float64" => 8,
+            other => return Err(invalid(format!("unknown PLY property type '{other}'"))),
+        };
+        let raw = self
+            .bytes
+            .get(self.pos..self.pos + size)
+            .ok_or_else(|| invalid("PLY body ends early".to_string()))?;
+        self.pos += size;
+        let mut buf = [0u8; 8];
+        buf[..size].copy_from_slice(raw);
+        if self.encoding == PlyEncoding::BigEndian {
+            buf[..size].reverse();
+        }
+        let value = match kind {
+            "char" | "int8" => buf[0] as i8 as f64,
+            "uchar" | "uint8" => buf[0] as f64,
+            "short" | "int16" => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            "ushort" | "uint16" => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            "int" | "int32" => i32::from_le_bytes(buf[..4].try_into().expect("4 bytes")) as f64,
+            "uint" | "uint32" => u32::from_le_bytes(buf[..4].try_into().expect("4 bytes")) as f64,
+            "float" | "float32" => f32::from_le_bytes(buf[..4].try_into().expect("4 bytes")) as f64,
+            _ => f64::from_le_bytes(buf),
+        };
+        Ok(value)
+    }
+}
+
+fn parse_xyz<'a>(mut tokens: impl Iterator<Item = &'a str>, line: usize) -> io::Result<[f64; 3]> {
+    let mut xyz = [0.0; 3];
+    for value in &mut xyz {
+        let token = tokens
+            .next()
+            .ok_or_else(|| invalid(format!("line {line}: expected three coordinates")))?;
+        *value = token
+            .parse()
+            .map_err(|_| invalid(format!("line {line}: invalid coordinate '{token}'")))?;
+    }
+    Ok(xyz)
+}
+
+fn text(bytes: &[u8]) -> io::Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|err| invalid(err.to_string()))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_stl_merges_shared_vertices() {
+        let stl = "solid t
+facet normal 0 0 1
+ outer loop
+  vertex 0 0 0
+  vertex 1 0 0
+  vertex 1 1 0
+ endloop
+endfacet
+facet normal 0 0 1
+ outer loop
+  vertex 0 0 0
+  vertex 1 1 0
+  vertex 0 1 0
+ endloop
+endfacet
+endsolid t
+";
+        let mesh = parse_surface(stl.as_bytes(), SurfaceFormat::Stl, SurfaceElement::Shell)
+            .expect("parse stl");
+        assert_eq!(mesh.nodes.len(), 4);
+        assert_eq!(mesh.elements.len(), 2);
+        assert_eq!(mesh.get_element(2).expect("element").nodes, vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn binary_stl_is_detected_by_size() {
+        let mut bytes = vec![0u8; 80];
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        for value in [
+            0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 2.0, 0.0,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        let mesh = parse_surface(&bytes, SurfaceFormat::Stl, SurfaceElement::Membrane)
+            .expect("parse binary stl");
+        assert_eq!(mesh.get_node(2).map(|n| n.coords()), Some([2.0, 0.0, 0.0]));
+        assert_eq!(
+            mesh.get_element(1).map(|e| e.element_type),
+            Some(ElementType::M3D3)
+        );
+    }
+
+    #[test]
+    fn obj_quads_and_pentagons() {
+        let obj =
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 0.5 0\nf 1/1 2/2 3/3 4/4\nf 1 4 -1 2 3\n";
+        let mesh = parse_surface(obj.as_bytes(), SurfaceFormat::Obj, SurfaceElement::Shell)
+            .expect("parse obj");
+        assert_eq!(
+            mesh.get_element(1).map(|e| e.element_type),
+            Some(ElementType::S4)
+        );
+        // The pentagon is split into three triangles.
+        assert_eq!(mesh.elements.len(), 4);
+        assert_eq!(mesh.get_element(2).expect("fan").nodes, vec![1, 4, 5]);
+    }
+
+    #[test]
+    fn ply_ascii_and_binary_agree() {
+        let header = |format: &str| {
+            format!(
+                "ply\nformat {format} 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n"
+            )
+        };
+        let ascii = format!("{}0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n", header("ascii"));
+        let mut binary = header("binary_big_endian").into_bytes();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            binary.extend_from_slice(&value.to_be_bytes());
+        }
+        binary.push(3);
+        for index in [0i32, 1, 2] {
+            binary.extend_from_slice(&index.to_be_bytes());
+        }
+
+        let a = parse_surface(ascii.as_bytes(), SurfaceFormat::Ply, SurfaceElement::Shell)
+            .expect("ascii ply");
+        let b =
+            parse_surface(&binary, SurfaceFormat::Ply, SurfaceElement::Shell).expect("binary ply");
+        assert_eq!(a.get_element(1), b.get_element(1));
+        assert_eq!(a.get_node(2), b.get_node(2));
+    }
+}
@@ -6,7 +6,18 @@
 ///! ## Supported Formats
 ///!
 ///! - **VTK Legacy**: ASCII text format (.vtk) - human-readable, larger files
-///! - **VTU XML**: Binary or ASCII XML format (.vtu) - compressed, efficient
+///! - **VTU XML**: ASCII, raw appended or zlib-compressed appended XML format
+///!   (.vtu) with 64-bit block headers
+///! - **PVD collection**: one VTU per result block plus a `.pvd` index (.pvd) -
+///!   time series that animate in ParaView
+///! - **Mode shapes**: every eigenmode as its own point array with `MODE` /
+///!   `FREQUENCY` field data
+///! - **Principal directions**: optional principal value and direction
+///!   vector arrays of the tensor results (see
+///!   [`crate::postprocess::principal_datasets`])
+///! - **Safety factor**: optional yield stress / von Mises field `SAFETY`
+///! - **Envelopes**: optional max/min over time of von Mises stress and
+///!   displacement magnitude, with step and time of occurrence
 ///!
 ///! ## Usage
 ///!
@@ -17,22 +28,29 @@
 ///! let writer = VtkWriter::new(&frd);
 ///! writer.write_vtk("output.vtk")?;
 ///! writer.write_vtu("output.vtu", VtkFormat::Binary)?;
+///! writer.write_vtu("small.vtu", VtkFormat::Compressed)?;
+///! writer.write_series("output.pvd", VtkFormat::Ascii)?;
 ///! # Ok::<(), Box<dyn std::error::Error>>(())
 ///! ```
 
-use crate::frd_reader::{FrdFile, FrdElement, ResultLocation};
+use crate::frd_reader::{FrdElement, FrdFile, ResultBlock, ResultDataset, ResultLocation};
+use crate::postprocess::{envelope_datasets, principal_datasets, safety_factor_dataset};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, Write};
-use std::path::Path;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 /// VTK output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VtkFormat {
     /// ASCII text format
     Ascii,
-    /// Binary format (compressed)
+    /// Raw little-endian appended data
     Binary,
+    /// zlib-compressed appended data
+    Compressed,
 }
 
 /// VTK element type codes
@@ -58,12 +76,43 @@ enum VtkCellType {
 /// VTK writer for FRD data
 pub struct VtkWriter<'a> {
     frd: &'a FrdFile,
+    principal_directions: bool,
+    /// Envelope datasets appended to every VTU frame
+    envelopes: Vec<ResultDataset>,
+    /// Yield stress for the `SAFETY` factor of stress results
+    yield_stress: Option<f64>,
 }
 
 impl<'a> VtkWriter<'a> {
     /// Create a new VTK writer for the given FRD file
     pub fn new(frd: &'a FrdFile) -> Self {
-        Self { frd }
+        Self {
+            frd,
+            principal_directions: false,
+            envelopes: Vec::new(),
+            yield_stress: None,
+        }
+    }
+
+    /// Also write principal values and direction vectors of tensor results
+    /// to VTU files
+    pub fn with_principal_directions(mut self) -> Self {
+        self.principal_directions = true;
+        self
+    }
+
+    /// Also write the max/min envelopes over all result blocks (see
+    /// [`crate::postprocess::envelope_datasets`]) to VTU files
+    pub fn with_envelopes(mut self) -> Self {
+        self.envelopes = envelope_datasets(self.frd);
+        self
+    }
+
+    /// Also write the safety factor against `yield_stress` of the stress
+    /// results to VTU files (see [`crate::postprocess::safety_factor_dataset`])
+    pub fn with_safety_factor(mut self, yield_stress: f64) -> Self {
+        self.yield_stress = Some(yield_stress);
+        self
     }
 
     /// Write VTK legacy format file
@@ -78,11 +127,157 @@ impl<'a> VtkWriter<'a> {
 
     /// Write VTU XML format file
     pub fn write_vtu<P: AsRef<Path>>(&self, path: P, format: VtkFormat) -> io::Result<()> {
-        let mut file = File::create(path)?;
+        self.write_vtu_frame(path, format, self.frd.result_blocks.last())
+    }
+
+    /// Write one VTU per result block plus a ParaView `.pvd` collection.
+    ///
+    /// Frames are written next to `pvd_path` as `<stem>_0000.vtu`,
+    /// `<stem>_0001.vtu`, ... and referenced with their block time, so
+    /// transient and multi-step results animate directly in ParaView. A file
+    /// without results yields a single geometry-only frame at time 0.
+    /// Returns the paths of the written frames.
+    pub fn write_series<P: AsRef<Path>>(
+        &self,
+        pvd_path: P,
+        format: VtkFormat,
+    ) -> io::Result<Vec<PathBuf>> {
+        let pvd_path = pvd_path.as_ref();
+        let dir = pvd_path.parent().unwrap_or_else(|| Path::new(""));
+        let stem = pvd_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid .pvd path"))?;
+
+        let frames: Vec<(f64, Option<&ResultBlock>)> = if self.frd.result_blocks.is_empty() {
+            vec![(0.0, None)]
+        } else {
+            self.frd
+                .result_blocks
+                .iter()
+                .map(|block| (block.time, Some(block)))
+                .collect()
+        };
+
+        let mut written = Vec::with_capacity(frames.len());
+        let mut pvd = BufWriter::new(File::create(pvd_path)?);
+        writeln!(pvd, "<?xml version=\"1.0\"?>")?;
+        writeln!(
+            pvd,
+            "<VTKFile type=\"Collection\" version=\"0.1\" byte_order=\"LittleEndian\">"
+        )?;
+        writeln!(pvd, "  <Collection>")?;
+        for (index, (time, block)) in frames.into_iter().enumerate() {
+            let name = format!("{stem}_{index:04}.vtu");
+            let path = dir.join(&name);
+            self.write_vtu_frame(&path, format, block)?;
+            writeln!(
+                pvd,
+                "    <DataSet timestep=\"{}\" group=\"\" part=\"0\" file=\"{}\"/>",
+                time, name
+            )?;
+            written.push(path);
+        }
+        writeln!(pvd, "  </Collection>")?;
+        writeln!(pvd, "</VTKFile>")?;
+        pvd.flush()?;
+        Ok(written)
+    }
+
+    /// Write a VTU file with the results of `block`
+    fn write_vtu_frame<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: VtkFormat,
+        block: Option<&ResultBlock>,
+    ) -> io::Result<()> {
+        let datasets = block.map(|block| block.datasets.as_slice()).unwrap_or_default();
+        let mut derived: Vec<ResultDataset> = if self.principal_directions {
+            datasets.iter().flat_map(principal_datasets).collect()
+        } else {
+            Vec::new()
+        };
+        if let Some(yield_stress) = self.yield_stress {
+            derived.extend(
+                datasets
+                    .iter()
+                    .filter_map(|dataset| safety_factor_dataset(dataset, |_| Some(yield_stress))),
+            );
+        }
+        let arrays: Vec<_> = datasets
+            .iter()
+            .chain(&derived)
+            .chain(&self.envelopes)
+            .map(|dataset| (dataset.name.clone(), dataset))
+            .collect();
+        self.write_vtu_file(path, format, &arrays, &[])
+    }
+
+    /// Write all eigenmodes into a single VTU.
+    ///
+    /// Every nodal dataset of a modal result block becomes its own point
+    /// array named `<NAME>_MODE_<n>` (e.g. `DISP_MODE_3`), and the mode
+    /// numbers and eigenfrequencies are stored as `MODE` / `FREQUENCY`
+    /// field data in the same order. Blocks without a mode number are
+    /// ignored; use [`VtkWriter::write_series`] to get one file per mode.
+    pub fn write_modes<P: AsRef<Path>>(&self, path: P, format: VtkFormat) -> io::Result<()> {
+        let modes: Vec<&ResultBlock> = self
+            .frd
+            .result_blocks
+            .iter()
+            .filter(|block| block.mode.is_some())
+            .collect();
+        if modes.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "FRD file contains no eigenmode results",
+            ));
+        }
+
+        let mut arrays = Vec::new();
+        let mut metadata = Vec::with_capacity(modes.len());
+        for block in modes {
+            let mode = block.mode.unwrap_or_default();
+            metadata.push((mode, block.time));
+            for dataset in &block.datasets {
+                if dataset.location == ResultLocation::Nodal {
+                    arrays.push((format!("{}_MODE_{}", dataset.name, mode), dataset));
+                }
+            }
+        }
+        self.write_vtu_file(path, format, &arrays, &metadata)
+    }
+
+    /// Write a VTU with the given named result arrays and mode metadata
+    fn write_vtu_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: VtkFormat,
+        arrays: &[(String, &ResultDataset)],
+        modes: &[(i32, f64)],
+    ) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut encoder = VtuEncoder::new(format);
         self.write_vtu_header(&mut file, format)?;
-        self.write_vtu_piece(&mut file)?;
-        self.write_vtu_footer(&mut file)?;
-        Ok(())
+        if !modes.is_empty() {
+            writeln!(file, "    <FieldData>")?;
+            let mode_numbers = ArrayValues::Int32(modes.iter().map(|m| m.0).collect());
+            let frequencies = ArrayValues::Float64(modes.iter().map(|m| m.1).collect());
+            for (name, values) in [("MODE", mode_numbers), ("FREQUENCY", frequencies)] {
+                let attributes = format!("Name=\"{}\" NumberOfTuples=\"{}\"", name, modes.len());
+                encoder.write_array(&mut file, 6, &attributes, &values, 1)?;
+            }
+            writeln!(file, "    </FieldData>")?;
+        }
+        writeln!(
+            file,
+            "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+            self.frd.nodes.len(),
+            self.frd.elements.len()
+        )?;
+        self.write_vtu_piece(&mut file, &mut encoder, arrays)?;
+        self.write_vtu_footer(&mut file, encoder)?;
+        file.flush()
     }
 
     /// Write VTK header
@@ -125,11 +320,11 @@ impl<'a> VtkWriter<'a> {
 
         writeln!(file, "CELLS {} {}", num_elements, total_size)?;
 
-        // Create node ID mapping for indexing
-        let node_id_to_index: HashMap<i32, usize> = self
-            .frd
-            .nodes
-            .keys()
+        // Create node ID mapping for indexing (same sorted order as POINTS)
+        let mut sorted_node_ids: Vec<_> = self.frd.nodes.keys().copied().collect();
+        sorted_node_ids.sort();
+        let node_id_to_index: HashMap<i32, usize> = sorted_node_ids
+            .iter()
             .enumerate()
             .map(|(idx, &node_id)| (node_id, idx))
             .collect();
@@ -270,81 +465,145 @@ impl<'a> VtkWriter<'a> {
     }
 
     /// Write VTU XML header
-    fn write_vtu_header(&self, file: &mut File, format: VtkFormat) -> io::Result<()> {
+    fn write_vtu_header<W: Write>(&self, file: &mut W, format: VtkFormat) -> io::Result<()> {
         writeln!(file, "<?xml version=\"1.0\"?>")?;
-        writeln!(file, "<VTKFile type=\"UnstructuredGrid\" version=\"1.0\" byte_order=\"LittleEndian\">")?;
-
-        let format_str = match format {
-            VtkFormat::Ascii => "ascii",
-            VtkFormat::Binary => "binary",
+        let attributes = match format {
+            VtkFormat::Ascii => "",
+            VtkFormat::Binary => " header_type=\"UInt64\"",
+            VtkFormat::Compressed => " header_type=\"UInt64\" compressor=\"vtkZLibDataCompressor\"",
         };
-        writeln!(file, "  <UnstructuredGrid>")?;
         writeln!(
             file,
-            "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
-            self.frd.nodes.len(),
-            self.frd.elements.len()
+            "<VTKFile type=\"UnstructuredGrid\" version=\"1.0\" byte_order=\"LittleEndian\"{}>",
+            attributes
         )?;
+        writeln!(file, "  <UnstructuredGrid>")?;
 
         Ok(())
     }
 
     /// Write VTU piece data
-    fn write_vtu_piece(&self, file: &mut File) -> io::Result<()> {
+    fn write_vtu_piece<W: Write>(
+        &self,
+        file: &mut W,
+        encoder: &mut VtuEncoder,
+        arrays: &[(String, &ResultDataset)],
+    ) -> io::Result<()> {
+        let mut node_ids: Vec<_> = self.frd.nodes.keys().copied().collect();
+        node_ids.sort();
+
         // Points
         writeln!(file, "      <Points>")?;
-        writeln!(
-            file,
-            "        <DataArray type=\"Float32\" NumberOfComponents=\"3\" format=\"ascii\">"
-        )?;
+        let points = ArrayValues::Float64(
+            node_ids
+                .iter()
+                .flat_map(|id| self.frd.nodes[id])
+                .collect(),
+        );
+        encoder.write_array(file, 8, "NumberOfComponents=\"3\"", &points, 3)?;
+        writeln!(file, "      </Points>")?;
 
-        let mut node_ids: Vec<_> = self.frd.nodes.keys().copied().collect();
-        node_ids.sort();
+        // Cells
+        let node_id_to_index: HashMap<i32, i64> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, &node_id)| (node_id, idx as i64))
+            .collect();
+        let mut element_ids: Vec<_> = self.frd.elements.keys().copied().collect();
+        element_ids.sort();
 
-        for node_id in &node_ids {
-            if let Some(coords) = self.frd.nodes.get(node_id) {
-                writeln!(file, "          {} {} {}", coords[0], coords[1], coords[2])?;
-            }
+        let mut connectivity = Vec::new();
+        let mut offsets = Vec::with_capacity(element_ids.len());
+        let mut types = Vec::with_capacity(element_ids.len());
+        for elem_id in &element_ids {
+            let element = &self.frd.elements[elem_id];
+            connectivity.extend(
+                element
+                    .nodes
+                    .iter()
+                    .filter_map(|node_id| node_id_to_index.get(node_id)),
+            );
+            offsets.push(connectivity.len() as i64);
+            types.push(Self::frd_to_vtk_cell_type(element) as u8);
         }
 
-        writeln!(file, "        </DataArray>")?;
-        writeln!(file, "      </Points>")?;
-
-        // TODO: Cells, PointData, CellData sections
-        // This is a simplified implementation
+        writeln!(file, "      <Cells>")?;
+        encoder.write_array(
+            file,
+            8,
+            "Name=\"connectivity\"",
+            &ArrayValues::Int64(connectivity),
+            8,
+        )?;
+        encoder.write_array(file, 8, "Name=\"offsets\"", &ArrayValues::Int64(offsets), 1)?;
+        encoder.write_array(file, 8, "Name=\"types\"", &ArrayValues::UInt8(types), 1)?;
+        writeln!(file, "      </Cells>")?;
+
+        // Results
+        for (tag, location, ids) in [
+            ("PointData", ResultLocation::Nodal, &node_ids),
+            ("CellData", ResultLocation::Element, &element_ids),
+        ] {
+            let selected: Vec<_> = arrays
+                .iter()
+                .filter(|(_, d)| d.location == location && d.ncomps > 0)
+                .collect();
+            if selected.is_empty() {
+                continue;
+            }
+            writeln!(file, "      <{}>", tag)?;
+            for (name, dataset) in selected {
+                // Missing ids are zero-filled
+                let values = ArrayValues::Float64(
+                    ids.iter()
+                        .flat_map(|id| {
+                            let values = dataset.values.get(id);
+                            (0..dataset.ncomps).map(move |comp| {
+                                values.and_then(|v| v.get(comp)).copied().unwrap_or(0.0)
+                            })
+                        })
+                        .collect(),
+                );
+                let attributes = format!(
+                    "Name=\"{}\" NumberOfComponents=\"{}\"",
+                    name, dataset.ncomps
+                );
+                encoder.write_array(file, 8, &attributes, &values, dataset.ncomps)?;
+            }
+            writeln!(file, "      </{}>", tag)?;
+        }
 
         Ok(())
     }
 
-    /// Write VTU footer
-    fn write_vtu_footer(&self, file: &mut File) -> io::Result<()> {
+    /// Write VTU footer, including the appended data section if any
+    fn write_vtu_footer<W: Write>(&self, file: &mut W, encoder: VtuEncoder) -> io::Result<()> {
         writeln!(file, "    </Piece>")?;
         writeln!(file, "  </UnstructuredGrid>")?;
+        encoder.finish(file)?;
         writeln!(file, "</VTKFile>")?;
         Ok(())
     }
 
     /// Convert FRD element type to VTK cell type
     fn frd_to_vtk_cell_type(element: &FrdElement) -> VtkCellType {
-        // FRD element type codes (from cgx manual)
-        // 1 = C3D8 (8-node brick) -> VTK_HEXAHEDRON
-        // 2 = C3D6 (6-node wedge) -> VTK_WEDGE
-        // 3 = C3D4 (4-node tet) -> VTK_TETRA
-        // 4 = C3D20 (20-node brick) -> VTK_QUADRATIC_HEXAHEDRON
-        // etc.
+        // FRD element type codes (cgx manual, § 11 element block)
+        // 1 = he8, 2 = pe6, 3 = te4, 4 = he20, 5 = pe15, 6 = te10,
+        // 7 = tr3, 8 = tr6, 9 = qu4, 10 = qu8, 11 = be2, 12 = be3
 
         match element.element_type {
-            1 => VtkCellType::Hexahedron,        // C3D8
-            2 => VtkCellType::Wedge,              // C3D6
-            3 => VtkCellType::Tetra,              // C3D4
+            1 => VtkCellType::Hexahedron,          // C3D8
+            2 => VtkCellType::Wedge,               // C3D6
+            3 => VtkCellType::Tetra,               // C3D4
             4 => VtkCellType::QuadraticHexahedron, // C3D20
-            5 => VtkCellType::QuadraticWedge,     // C3D15
-            6 => VtkCellType::Pyramid,            // C3D5?
-            7 => VtkCellType::Line,               // B31, T3D2
-            8 => VtkCellType::QuadraticEdge,      // B32
-            9 => VtkCellType::Triangle,           // S3
-            10 => VtkCellType::Quad,              // S4, S8
-            11 => VtkCellType::QuadraticTetra,    // C3D10
+            5 => VtkCellType::QuadraticWedge,      // C3D15
+            6 => VtkCellType::QuadraticTetra,      // C3D10
+            7 => VtkCellType::Triangle,            // S3
+            8 => VtkCellType::QuadraticTriangle,   // S6
+            9 => VtkCellType::Quad,                // S4
+            10 => VtkCellType::QuadraticQuad,      // S8
+            11 => VtkCellType::Line,               // B31, T3D2
+            12 => VtkCellType::QuadraticEdge,      // B32
             _ => {
                 // Default based on node count
                 match element.nodes.len() {
@@ -361,6 +620,135 @@ impl<'a> VtkWriter<'a> {
     }
 }
 
+/// Uncompressed bytes per zlib block, as used by VTK itself
+const COMPRESSION_BLOCK_SIZE: usize = 1 << 15;
+
+/// Typed payload of a VTU data array
+enum ArrayValues {
+    Float64(Vec<f64>),
+    Int64(Vec<i64>),
+    Int32(Vec<i32>),
+    UInt8(Vec<u8>),
+}
+
+impl ArrayValues {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Float64(_) => "Float64",
+            Self::Int64(_) => "Int64",
+            Self::Int32(_) => "Int32",
+            Self::UInt8(_) => "UInt8",
+        }
+    }
+
+    fn to_le_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Float64(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            Self::Int64(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            Self::Int32(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            Self::UInt8(v) => v.clone(),
+        }
+    }
+
+    fn to_strings(&self) -> Vec<String> {
+        match self {
+            Self::Float64(v) => v.iter().map(f64::to_string).collect(),
+            Self::Int64(v) => v.iter().map(i64::to_string).collect(),
+            Self::Int32(v) => v.iter().map(i32::to_string).collect(),
+            Self::UInt8(v) => v.iter().map(u8::to_string).collect(),
+        }
+    }
+}
+
+/// Writes data arrays inline (ASCII) or collects them for `<AppendedData>`.
+///
+/// Appended blocks use 64-bit (`UInt64`) headers so arrays and offsets
+/// beyond 4 GiB stay addressable. Compressed blocks follow VTK's zlib
+/// layout: `[nblocks, block size, last block size, compressed sizes...]`
+/// followed by the compressed blocks.
+struct VtuEncoder {
+    format: VtkFormat,
+    appended: Vec<u8>,
+}
+
+impl VtuEncoder {
+    fn new(format: VtkFormat) -> Self {
+        Self {
+            format,
+            appended: Vec::new(),
+        }
+    }
+
+    /// Write one `<DataArray>`; ASCII values go `per_line` to a row
+    fn write_array<W: Write>(
+        &mut self,
+        file: &mut W,
+        indent: usize,
+        attributes: &str,
+        values: &ArrayValues,
+        per_line: usize,
+    ) -> io::Result<()> {
+        let pad = " ".repeat(indent);
+        let open = format!("{}<DataArray type=\"{}\" {}", pad, values.type_name(), attributes);
+        if self.format == VtkFormat::Ascii {
+            writeln!(file, "{} format=\"ascii\">", open)?;
+            for row in values.to_strings().chunks(per_line.max(1)) {
+                writeln!(file, "{}  {}", pad, row.join(" "))?;
+            }
+            writeln!(file, "{}</DataArray>", pad)?;
+            return Ok(());
+        }
+
+        let offset = self.appended.len();
+        let bytes = values.to_le_bytes();
+        if self.format == VtkFormat::Compressed {
+            self.append_compressed(&bytes)?;
+        } else {
+            self.appended
+                .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            self.appended.extend_from_slice(&bytes);
+        }
+        writeln!(file, "{} format=\"appended\" offset=\"{}\"/>", open, offset)
+    }
+
+    fn append_compressed(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let blocks: Vec<Vec<u8>> = bytes
+            .chunks(COMPRESSION_BLOCK_SIZE)
+            .map(|block| {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(block)?;
+                encoder.finish()
+            })
+            .collect::<io::Result<_>>()?;
+        let header = [
+            blocks.len() as u64,
+            COMPRESSION_BLOCK_SIZE as u64,
+            (bytes.len() % COMPRESSION_BLOCK_SIZE) as u64,
+        ];
+        for value in header
+            .into_iter()
+            .chain(blocks.iter().map(|b| b.len() as u64))
+        {
+            self.appended.extend_from_slice(&value.to_le_bytes());
+        }
+        for block in &blocks {
+            self.appended.extend_from_slice(block);
+        }
+        Ok(())
+    }
+
+    /// Write the `<AppendedData>` section (nothing for ASCII output)
+    fn finish<W: Write>(self, file: &mut W) -> io::Result<()> {
+        if self.format == VtkFormat::Ascii {
+            return Ok(());
+        }
+        write!(file, "  <AppendedData encoding=\"raw\">\n   _")?;
+        file.write_all(&self.appended)?;
+        writeln!(file, "\n  </AppendedData>")?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,4 +791,213 @@ mod tests {
         let vtk_type = VtkWriter::frd_to_vtk_cell_type(&elem);
         assert_eq!(vtk_type as i32, VtkCellType::Tetra as i32);
     }
+
+    #[test]
+    fn write_series_emits_one_vtu_per_block_and_pvd() {
+        use crate::frd_reader::FrdElement;
+
+        let mut frd = FrdFile {
+            header: FrdHeader::default(),
+            nodes: HashMap::from([(1, [0.0, 0.0, 0.0]), (2, [1.0, 0.0, 0.0])]),
+            elements: HashMap::from([(
+                1,
+                FrdElement {
+                    id: 1,
+                    element_type: 11,
+                    nodes: vec![1, 2],
+                },
+            )]),
+            result_blocks: Vec::new(),
+        };
+        for (step, time) in [(1, 0.5), (1, 1.0)] {
+            frd.result_blocks.push(ResultBlock {
+                step,
+                time,
+                mode: None,
+                datasets: vec![ResultDataset::nodal(
+                    "DISP",
+                    HashMap::from([(2, vec![time, 0.0, 0.0])]),
+                )],
+            });
+        }
+
+        let dir = std::env::temp_dir().join(format!("ccx_pvd_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pvd = dir.join("job.pvd");
+        let frames = VtkWriter::new(&frd)
+            .write_series(&pvd, VtkFormat::Ascii)
+            .unwrap();
+
+        assert_eq!(frames, vec![dir.join("job_0000.vtu"), dir.join("job_0001.vtu")]);
+        let collection = std::fs::read_to_string(&pvd).unwrap();
+        assert!(collection.contains("timestep=\"0.5\" group=\"\" part=\"0\" file=\"job_0000.vtu\""));
+        assert!(collection.contains("timestep=\"1\" group=\"\" part=\"0\" file=\"job_0001.vtu\""));
+
+        let last = std::fs::read_to_string(&frames[1]).unwrap();
+        assert!(last.contains("Name=\"connectivity\""));
+        assert!(last.contains("Name=\"DISP\" NumberOfComponents=\"3\""));
+        assert!(last.contains("          1 0 0\n"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn principal_directions_are_written_as_vector_arrays() {
+        use crate::frd_reader::FrdElement;
+
+        let stress = HashMap::from([(1, vec![100.0, 0.0, 0.0, 0.0, 0.0, 0.0]), (2, vec![0.0; 6])]);
+        let frd = FrdFile {
+            header: FrdHeader::default(),
+            nodes: HashMap::from([(1, [0.0, 0.0, 0.0]), (2, [1.0, 0.0, 0.0])]),
+            elements: HashMap::from([(
+                1,
+                FrdElement {
+                    id: 1,
+                    element_type: 11,
+                    nodes: vec![1, 2],
+                },
+            )]),
+            result_blocks: vec![ResultBlock {
+                step: 1,
+                time: 1.0,
+                mode: None,
+                datasets: vec![ResultDataset::nodal("STRESS", stress)],
+            }],
+        };
+
+        let path = std::env::temp_dir().join(format!("ccx_principal_{}.vtu", std::process::id()));
+        VtkWriter::new(&frd)
+            .with_principal_directions()
+            .write_vtu(&path, VtkFormat::Ascii)
+            .unwrap();
+        let vtu = std::fs::read_to_string(&path).unwrap();
+        VtkWriter::new(&frd).write_vtu(&path, VtkFormat::Ascii).unwrap();
+        let plain = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(vtu.contains("Name=\"STRESS_PRINCIPAL\" NumberOfComponents=\"3\""));
+        assert!(vtu.contains("Name=\"STRESS_PMAX\" NumberOfComponents=\"3\""));
+        assert!(vtu.contains("Name=\"STRESS_PMIN\" NumberOfComponents=\"3\""));
+        assert!(vtu.contains("          100 0 0\n"));
+        assert!(!plain.contains("STRESS_PMAX"));
+    }
+
+    #[test]
+    fn write_modes_annotates_each_mode_with_its_frequency() {
+        use crate::frd_reader::FrdElement;
+
+        let mut frd = FrdFile {
+            header: FrdHeader::default(),
+            nodes: HashMap::from([(1, [0.0, 0.0, 0.0]), (2, [1.0, 0.0, 0.0])]),
+            elements: HashMap::from([(
+                1,
+                FrdElement {
+                    id: 1,
+                    element_type: 11,
+                    nodes: vec![1, 2],
+                },
+            )]),
+            result_blocks: Vec::new(),
+        };
+        for (mode, frequency) in [(1, 12.5), (2, 48.0)] {
+            frd.result_blocks.push(ResultBlock {
+                step: 1,
+                time: frequency,
+                mode: Some(mode),
+                datasets: vec![ResultDataset::nodal(
+                    "DISP",
+                    HashMap::from([(2, vec![0.0, mode as f64, 0.0])]),
+                )],
+            });
+        }
+
+        // Modes survive an FRD round trip.
+        let mut bytes = Vec::new();
+        crate::write_frd_to(&mut bytes, &frd).unwrap();
+        let read = FrdFile::from_reader(bytes.as_slice()).unwrap();
+        let modes: Vec<_> = read.result_blocks.iter().map(|b| (b.mode, b.time)).collect();
+        assert_eq!(modes, vec![(Some(1), 12.5), (Some(2), 48.0)]);
+
+        let path = std::env::temp_dir().join(format!("ccx_modes_{}.vtu", std::process::id()));
+        VtkWriter::new(&read)
+            .write_modes(&path, VtkFormat::Ascii)
+            .unwrap();
+        let vtu = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(vtu.contains("Name=\"DISP_MODE_1\" NumberOfComponents=\"3\""));
+        assert!(vtu.contains("Name=\"DISP_MODE_2\" NumberOfComponents=\"3\""));
+        assert!(vtu.contains("Name=\"MODE\" NumberOfTuples=\"2\" format=\"ascii\">\n        1\n        2\n"));
+        assert!(vtu.contains("Name=\"FREQUENCY\" NumberOfTuples=\"2\" format=\"ascii\">\n        12.5\n        48\n"));
+
+        frd.result_blocks.iter_mut().for_each(|b| b.mode = None);
+        let err = VtkWriter::new(&frd)
+            .write_modes(&path, VtkFormat::Ascii)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn appended_vtu_uses_64_bit_headers_and_zlib_blocks() {
+        use crate::frd_reader::FrdElement;
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let nodes: HashMap<i32, [f64; 3]> = (1..=2000)
+            .map(|id| (id, [id as f64, 0.5 * id as f64, 0.0]))
+            .collect();
+        let frd = FrdFile {
+            header: FrdHeader::default(),
+            nodes,
+            elements: HashMap::from([(
+                1,
+                FrdElement {
+                    id: 1,
+                    element_type: 11,
+                    nodes: vec![1, 2],
+                },
+            )]),
+            result_blocks: Vec::new(),
+        };
+        let dir = std::env::temp_dir().join(format!("ccx_vtu_appended_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let raw_path = dir.join("raw.vtu");
+        let zlib_path = dir.join("zlib.vtu");
+        let writer = VtkWriter::new(&frd);
+        writer.write_vtu(&raw_path, VtkFormat::Binary).unwrap();
+        writer.write_vtu(&zlib_path, VtkFormat::Compressed).unwrap();
+        let raw = std::fs::read(&raw_path).unwrap();
+        let zlib = std::fs::read(&zlib_path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let appended = |bytes: &[u8]| {
+            let marker = b"<AppendedData encoding=\"raw\">\n   _";
+            let start = bytes.windows(marker.len()).position(|w| w == marker).unwrap();
+            bytes[start + marker.len()..].to_vec()
+        };
+        let u64_at = |bytes: &[u8], at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+
+        // Raw: points are the first array, prefixed by a UInt64 byte count.
+        let text = String::from_utf8_lossy(&raw);
+        assert!(text.contains("header_type=\"UInt64\""));
+        assert!(text.contains("NumberOfComponents=\"3\" format=\"appended\" offset=\"0\"/>"));
+        let data = appended(&raw);
+        assert_eq!(u64_at(&data, 0), 2000 * 3 * 8);
+        assert_eq!(f64::from_le_bytes(data[8 + 24..8 + 32].try_into().unwrap()), 2.0);
+
+        // Compressed: 48000 bytes of points are two 32 KiB blocks.
+        let text = String::from_utf8_lossy(&zlib);
+        assert!(text.contains("compressor=\"vtkZLibDataCompressor\""));
+        let data = appended(&zlib);
+        assert_eq!(u64_at(&data, 0), 2);
+        assert_eq!(u64_at(&data, 8), COMPRESSION_BLOCK_SIZE as u64);
+        assert_eq!(u64_at(&data, 16), (48000 % COMPRESSION_BLOCK_SIZE) as u64);
+        let first = u64_at(&data, 24) as usize;
+        let mut block = Vec::new();
+        ZlibDecoder::new(&data[40..40 + first])
+            .read_to_end(&mut block)
+            .unwrap();
+        assert_eq!(block.len(), COMPRESSION_BLOCK_SIZE);
+        assert_eq!(f64::from_le_bytes(block[24..32].try_into().unwrap()), 2.0);
+        assert!(zlib.len() < raw.len());
+    }
 }
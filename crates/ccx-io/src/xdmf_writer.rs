@@ -0,0 +1,464 @@
+//! XDMF time-series writer for ParaView.
+//!
+//! Writes an `.xmf` file holding the light XML metadata and, with
+//! [`XdmfStorage::Binary`], a sidecar `.bin` file holding the heavy arrays,
+//! or with `XdmfStorage::Hdf5` (feature `hdf5`) an `.h5` file holding them
+//! as HDF5 datasets.
+//! Geometry and topology are stored once and shared by every time step via
+//! `xi:include`, so long transient runs do not repeat the mesh per step.
+//!
+//! Each FRD result block becomes one grid of a temporal collection; nodal
+//! datasets become `Scalar`, `Vector`, `Tensor6` or `Matrix` attributes.
+//!
+//! ```rust,no_run
+//! use ccx_io::{FrdFile, XdmfStorage, XdmfWriter};
+//!
+//! let frd = FrdFile::from_file("job.frd")?;
+//! XdmfWriter::new(&frd)
+//!     .with_storage(XdmfStorage::Binary)
+//!     .write("job.xmf")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::frd_reader::{FrdFile, ResultDataset, ResultLocation};
+
+/// Where heavy arrays are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum XdmfStorage {
+    /// Raw little-endian arrays in a `.bin` file next to the `.xmf`
+    #[default]
+    Binary,
+    /// Arrays inlined in the XML (small models, debugging)
+    Xml,
+    /// HDF5 datasets in a `.h5` file next to the `.xmf`, one per array
+    #[cfg(feature = "hdf5")]
+    Hdf5,
+}
+
+/// XDMF writer for FRD data.
+pub struct XdmfWriter<'a> {
+    frd: &'a FrdFile,
+    storage: XdmfStorage,
+}
+
+/// Destination of heavy data while the XML is assembled.
+struct HeavyData {
+    storage: XdmfStorage,
+    file_name: String,
+    out: Option<BufWriter<File>>,
+    offset: u64,
+    #[cfg(feature = "hdf5")]
+    h5: Option<(crate::hdf5_file::H5File, crate::hdf5_file::H5Group)>,
+}
+
+impl HeavyData {
+    /// Heavy data inlined in the XML
+    fn inline() -> Self {
+        Self {
+            storage: XdmfStorage::Xml,
+            file_name: String::new(),
+            out: None,
+            offset: 0,
+            #[cfg(feature = "hdf5")]
+            h5: None,
+        }
+    }
+
+    /// Data item of `values`; `path` names its HDF5 dataset
+    #[cfg_attr(not(feature = "hdf5"), allow(unused_variables))]
+    fn data_item(&mut self, path: &str, dims: &[usize], values: &[f64]) -> io::Result<String> {
+        #[cfg(feature = "hdf5")]
+        if let Some(item) = self.hdf5_item(path, dims, "Float", values)? {
+            return Ok(item);
+        }
+        self.item(
+            dims,
+            "Float",
+            values.iter().map(|v| v.to_le_bytes()),
+            || join(values.iter().map(|v| v.to_string())),
+        )
+    }
+
+    #[cfg_attr(not(feature = "hdf5"), allow(unused_variables))]
+    fn int_item(&mut self, path: &str, dims: &[usize], values: &[i64]) -> io::Result<String> {
+        #[cfg(feature = "hdf5")]
+        if let Some(item) = self.hdf5_item(path, dims, "Int", values)? {
+            return Ok(item);
+        }
+        self.item(dims, "Int", values.iter().map(|v| v.to_le_bytes()), || {
+            join(values.iter().map(|v| v.to_string()))
+        })
+    }
+
+    /// `file.h5:/path` data item, or `None` unless storing to HDF5
+    #[cfg(feature = "hdf5")]
+    fn hdf5_item<T: crate::hdf5_file::H5Type>(
+        &mut self,
+        path: &str,
+        dims: &[usize],
+        number_type: &str,
+        values: &[T],
+    ) -> io::Result<Option<String>> {
+        let Some((_file, root)) = self.h5.as_ref() else {
+            return Ok(None);
+        };
+        root.write_dataset(path, dims, values)?;
+        Ok(Some(format!(
+            "<DataItem {} Format=\"HDF\">{}:/{path}</DataItem>",
+            item_head(dims, number_type),
+            self.file_name
+        )))
+    }
+
+    fn item(
+        &mut self,
+        dims: &[usize],
+        number_type: &str,
+        bytes: impl Iterator<Item = [u8; 8]>,
+        text: impl FnOnce() -> String,
+    ) -> io::Result<String> {
+        let head = item_head(dims, number_type);
+        match (self.storage, self.out.as_mut()) {
+            (XdmfStorage::Binary, Some(out)) => {
+                let seek = self.offset;
+                for chunk in bytes {
+                    out.write_all(&chunk)?;
+                    self.offset += 8;
+                }
+                Ok(format!(
+                    "<DataItem {head} Format=\"Binary\" Endian=\"Little\" Seek=\"{seek}\">{}</DataItem>",
+                    self.file_name
+                ))
+            }
+            _ => Ok(format!(
+                "<DataItem {head} Format=\"XML\">{}</DataItem>",
+                text()
+            )),
+        }
+    }
+}
+
+impl<'a> XdmfWriter<'a> {
+    /// Create a writer with binary heavy-data storage
+    pub fn new(frd: &'a FrdFile) -> Self {
+        Self {
+            frd,
+            storage: XdmfStorage::default(),
+        }
+    }
+
+    /// Select how heavy arrays are stored
+    pub fn with_storage(mut self, storage: XdmfStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Path of the binary heavy-data file written next to `xmf_path`
+    pub fn heavy_data_path(xmf_path: impl AsRef<Path>) -> PathBuf {
+        xmf_path.as_ref().with_extension("bin")
+    }
+
+    /// Path of the HDF5 heavy-data file written next to `xmf_path`
+    #[cfg(feature = "hdf5")]
+    pub fn hdf5_path(xmf_path: impl AsRef<Path>) -> PathBuf {
+        xmf_path.as_ref().with_extension("h5")
+    }
+
+    /// Write the `.xmf` file (and the `.bin` or `.h5` heavy-data file)
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let heavy_path = match self.storage {
+            #[cfg(feature = "hdf5")]
+            XdmfStorage::Hdf5 => Self::hdf5_path(path),
+            _ => Self::heavy_data_path(path),
+        };
+        let mut heavy = HeavyData {
+            storage: self.storage,
+            file_name: heavy_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            out: match self.storage {
+                XdmfStorage::Binary => Some(BufWriter::new(File::create(&heavy_path)?)),
+                _ => None,
+            },
+            ..HeavyData::inline()
+        };
+        #[cfg(feature = "hdf5")]
+        if self.storage == XdmfStorage::Hdf5 {
+            let file = crate::hdf5_file::H5File::create(&heavy_path)?;
+            let root = file.root()?;
+            heavy.h5 = Some((file, root));
+        }
+
+        let xml = self.build_xml(&mut heavy)?;
+        if let Some(mut out) = heavy.out.take() {
+            out.flush()?;
+        }
+        fs::write(path, xml)
+    }
+
+    fn build_xml(&self, heavy: &mut HeavyData) -> io::Result<String> {
+        let mut node_ids: Vec<i32> = self.frd.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        let index: HashMap<i32, i64> = node_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, &id)| (id, idx as i64))
+            .collect();
+
+        let coords: Vec<f64> = node_ids.iter().flat_map(|id| self.frd.nodes[id]).collect();
+        let (topology, num_cells) = self.mixed_topology(&index);
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" ?>\n");
+        xml.push_str(
+            "<Xdmf Version=\"3.0\" xmlns:xi=\"http://www.w3.org/2001/XInclude\">\n  <Domain>\n",
+        );
+        let _ = writeln!(xml, "    <Grid Name=\"mesh\" GridType=\"Uniform\">");
+        let _ = writeln!(
+            xml,
+            "      <Geometry GeometryType=\"XYZ\">{}</Geometry>",
+            heavy.data_item("mesh/XYZ", &[node_ids.len(), 3], &coords)?
+        );
+        let _ = writeln!(
+            xml,
+            "      <Topology TopologyType=\"Mixed\" NumberOfElements=\"{num_cells}\">{}</Topology>",
+            heavy.int_item("mesh/Topology", &[topology.len()], &topology)?
+        );
+        xml.push_str("    </Grid>\n");
+
+        xml.push_str(
+            "    <Grid Name=\"TimeSeries\" GridType=\"Collection\" CollectionType=\"Temporal\">\n",
+        );
+        for (idx, block) in self.frd.result_blocks.iter().enumerate() {
+            let grid = format!("step{}_{}", block.step, idx + 1);
+            let _ = writeln!(xml, "      <Grid Name=\"{grid}\" GridType=\"Uniform\">");
+            let _ = writeln!(xml, "        <Time Value=\"{}\"/>", block.time);
+            xml.push_str(
+                "        <xi:include xpointer=\"xpointer(//Grid[@Name='mesh']/*[self::Topology or self::Geometry])\"/>\n",
+            );
+            for dataset in &block.datasets {
+                if dataset.location != ResultLocation::Nodal || dataset.ncomps == 0 {
+                    continue;
+                }
+                let (kind, values) = nodal_values(dataset, &node_ids);
+                let width = values.len() / node_ids.len().max(1);
+                let _ = writeln!(
+                    xml,
+                    "        <Attribute Name=\"{}\" AttributeType=\"{kind}\" Center=\"Node\">{}</Attribute>",
+                    dataset.name,
+                    heavy.data_item(
+                        &format!("{grid}/{}", dataset.name),
+                        &[node_ids.len(), width],
+                        &values
+                    )?
+                );
+            }
+            xml.push_str("      </Grid>\n");
+        }
+        xml.push_str("    </Grid>\n  </Domain>\n</Xdmf>\n");
+        Ok(xml)
+    }
+
+    /// Mixed topology array: `type, [count,] nodes...` per cell
+    fn mixed_topology(&self, index: &HashMap<i32, i64>) -> (Vec<i64>, usize) {
+        let mut element_ids: Vec<i32> = self.frd.elements.keys().copied().collect();
+        element_ids.sort_unstable();
+        let mut topology = Vec::new();
+        let mut cells = 0;
+        for id in element_ids {
+            let element = &self.frd.elements[&id];
+            let Some(code) = xdmf_cell_type(element.element_type) else {
+                continue;
+            };
+            topology.push(code);
+            if code == 2 {
+                topology.push(element.nodes.len() as i64);
+            }
+            topology.extend(
+                element
+                    .nodes
+                    .iter()
+                    .map(|node| index.get(node).copied().unwrap_or(0)),
+            );
+            cells += 1;
+        }
+        (topology, cells)
+    }
+}
+
+/// XDMF mixed-topology code for an FRD element type
+fn xdmf_cell_type(frd_type: i32) -> Option<i64> {
+    let code = match frd_type {
+        1 => 9,   // he8 → Hexahedron
+        2 => 8,   // pe6 → Wedge
+        3 => 6,   // te4 → Tetrahedron
+        4 => 48,  // he20 → Hexahedron_20
+        5 => 40,  // pe15 → Wedge_15
+        6 => 38,  // te10 → Tetrahedron_10
+        7 => 4,   // tr3 → Triangle
+        8 => 36,  // tr6 → Triangle_6
+        9 => 5,   // qu4 → Quadrilateral
+        10 => 37, // qu8 → Quadrilateral_8
+        11 => 2,  // be2 → Polyline
+        12 => 34, // be3 → Edge_3
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Node-ordered values and the XDMF attribute type for a dataset
+fn nodal_values(dataset: &ResultDataset, node_ids: &[i32]) -> (&'static str, Vec<f64>) {
+    let ncomps = dataset.ncomps;
+    let (kind, order): (&str, Vec<usize>) = match ncomps {
+        1 => ("Scalar", vec![0]),
+        3 => ("Vector", vec![0, 1, 2]),
+        // FRD xx, yy, zz, xy, yz, zx → XDMF xx, xy, xz, yy, yz, zz
+        6 => ("Tensor6", vec![0, 3, 5, 1, 4, 2]),
+        _ => ("Matrix", (0..ncomps).collect()),
+    };
+    let mut values = Vec::with_capacity(node_ids.len() * order.len());
+    for id in node_ids {
+        let row = dataset.values.get(id);
+        values.extend(
+            order
+                .iter()
+                .map(|&c| row.and_then(|r| r.get(c)).copied().unwrap_or(0.0)),
+        );
+    }
+    (kind, values)
+}
+
+/// Dimension, number type and precision attributes of a data item
+fn item_head(dims: &[usize], number_type: &str) -> String {
+    let dims = join(dims.iter().map(|d| d.to_string()));
+    format!("Dimensions=\"{dims}\" NumberType=\"{number_type}\" Precision=\"8\"")
+}
+
+fn join(values: impl Iterator<Item = String>) -> String {
+    values.collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frd_reader::{FrdElement, ResultBlock};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn two_step_model() -> FrdFile {
+        let mut frd = FrdFile::new();
+        frd.nodes.insert(1, [0.0, 0.0, 0.0]);
+        frd.nodes.insert(2, [1.0, 0.0, 0.0]);
+        frd.elements.insert(
+            1,
+            FrdElement {
+                id: 1,
+                element_type: 11,
+                nodes: vec![1, 2],
+            },
+        );
+        for (step, time) in [(1, 0.5), (1, 1.0)] {
+            let mut disp = HashMap::new();
+            disp.insert(2, vec![time, 0.0, 0.0]);
+            frd.result_blocks.push(ResultBlock {
+                step,
+                time,
+                mode: None,
+                datasets: vec![ResultDataset::nodal("DISP", disp)],
+            });
+        }
+        frd
+    }
+
+    #[test]
+    fn shares_geometry_across_time_steps() {
+        let frd = two_step_model();
+        let mut heavy = HeavyData::inline();
+        let xml = XdmfWriter::new(&frd)
+            .build_xml(&mut heavy)
+            .expect("build xml");
+        assert_eq!(xml.matches("<Geometry").count(), 1);
+        assert_eq!(xml.matches("<xi:include").count(), 2);
+        assert!(xml.contains("CollectionType=\"Temporal\""));
+        assert!(xml.contains("<Time Value=\"0.5\"/>"));
+        assert!(xml.contains("NumberOfElements=\"1\""));
+        assert!(xml.contains(">2 2 0 1</DataItem>"));
+        assert!(xml.contains("AttributeType=\"Vector\""));
+    }
+
+    #[test]
+    fn binary_storage_writes_seekable_sidecar() {
+        let dir = std::env::temp_dir().join(format!(
+            "ccx_io_xdmf_{}_{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock should be valid")
+                .as_nanos()
+        ));
+        let path = dir.join("job.xmf");
+        XdmfWriter::new(&two_step_model())
+            .write(&path)
+            .expect("write xdmf");
+
+        // 2×3 coordinates + 4 topology entries + 2 steps × 2×3 displacements
+        let bin = fs::read(XdmfWriter::heavy_data_path(&path)).expect("read bin");
+        assert_eq!(bin.len(), (6 + 4 + 12) * 8);
+        let xml = fs::read_to_string(&path).expect("read xmf");
+        assert!(xml.contains("Seek=\"80\">job.bin</DataItem>"));
+        let first_disp = f64::from_le_bytes(bin[80 + 24..80 + 32].try_into().expect("8 bytes"));
+        assert_eq!(first_disp, 0.5);
+    }
+
+    #[cfg(feature = "hdf5")]
+    #[test]
+    fn hdf5_storage_references_datasets() {
+        let dir = std::env::temp_dir().join(format!(
+            "ccx_io_xdmf_h5_{}_{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock should be valid")
+                .as_nanos()
+        ));
+        let path = dir.join("job.xmf");
+        XdmfWriter::new(&two_step_model())
+            .with_storage(XdmfStorage::Hdf5)
+            .write(&path)
+            .expect("write xdmf");
+
+        let xml = fs::read_to_string(&path).expect("read xmf");
+        assert!(xml.contains(
+            "<DataItem Dimensions=\"2 3\" NumberType=\"Float\" Precision=\"8\" \
+             Format=\"HDF\">job.h5:/mesh/XYZ</DataItem>"
+        ));
+        assert!(xml.contains("Format=\"HDF\">job.h5:/step1_2/DISP</DataItem>"));
+        let h5 = fs::read(XdmfWriter::hdf5_path(&path)).expect("read h5");
+        assert_eq!(&h5[..8], b"\x89HDF\r\n\x1a\n");
+
+        let file = crate::hdf5_file::H5File::open(&XdmfWriter::hdf5_path(&path)).expect("open h5");
+        let root = file.root().expect("root");
+        let (dims, xyz) = root.read_dataset::<f64>("mesh/XYZ").expect("read xyz");
+        assert_eq!(dims, [2, 3]);
+        assert_eq!(xyz, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        let (_, topology) = root.read_dataset::<i64>("mesh/Topology").expect("topology");
+        assert_eq!(topology, [2, 2, 0, 1]);
+        let (dims, disp) = root.read_dataset::<f64>("step1_2/DISP").expect("read disp");
+        assert_eq!(dims, [2, 3]);
+        assert_eq!(disp, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        drop((root, file));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,530 @@
+//! Cross-validation of the Rust pipeline against the legacy ccx binary.
+//!
+//! [`CrossValidator`] writes a deck, with its includes expanded, into a
+//! scratch directory, runs an external `ccx_2.23` executable on it and
+//! solves it with the Rust pipeline. The `.dat` tables and FRD results of
+//! ccx are the reference: the Rust tables are compared with
+//! [`dat_compare`], and the Rust FRD datasets that ccx also wrote with
+//! [`frd_compare`]. A [`Scoreboard`] collects the outcomes of a fixture
+//! suite.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use ccx_inp::{Deck, ParameterTable};
+use ccx_solver::{AnalysisPipeline, MeshBuilder};
+
+use crate::dat_compare::{DatComparison, dat_compare, parse_dat_tables};
+use crate::dat_writer::write_dat_results_to;
+use crate::frd_compare::{FrdComparison, FrdTolerances, frd_compare};
+use crate::frd_reader::FrdFile;
+use crate::output::write_frd;
+use crate::solver_results::{static_dat_steps, static_frd};
+
+/// Scratch directories of this process, numbered so that decks with the
+/// same name do not share one
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Result of cross-validating one deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum XvalidateStatus {
+    /// Every compared table and dataset is within tolerance
+    Matched,
+    /// Some table or dataset exceeds its tolerance or is missing
+    Differs,
+    /// ccx wrote no result tables or FRD results to compare with
+    NotCompared,
+    /// The Rust pipeline does not solve the deck's elements or analysis
+    Unsupported,
+    /// The Rust pipeline failed to parse or solve the deck
+    RustFailed,
+    /// ccx failed, so there is no reference
+    LegacyFailed,
+}
+
+impl XvalidateStatus {
+    pub const ALL: [XvalidateStatus; 6] = [
+        XvalidateStatus::Matched,
+        XvalidateStatus::Differs,
+        XvalidateStatus::NotCompared,
+        XvalidateStatus::Unsupported,
+        XvalidateStatus::RustFailed,
+        XvalidateStatus::LegacyFailed,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            XvalidateStatus::Matched => "matched",
+            XvalidateStatus::Differs => "differs",
+            XvalidateStatus::NotCompared => "not_compared",
+            XvalidateStatus::Unsupported => "unsupported",
+            XvalidateStatus::RustFailed => "rust_failed",
+            XvalidateStatus::LegacyFailed => "legacy_failed",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            XvalidateStatus::Matched => "MATCH",
+            XvalidateStatus::Differs => "DIFF ",
+            XvalidateStatus::NotCompared => "NOREF",
+            XvalidateStatus::Unsupported => "UNSUP",
+            XvalidateStatus::RustFailed => "RFAIL",
+            XvalidateStatus::LegacyFailed => "LFAIL",
+        }
+    }
+}
+
+/// Outcome of cross-validating one deck
+#[derive(Debug, Clone)]
+pub struct XvalidateOutcome {
+    pub deck: PathBuf,
+    pub status: XvalidateStatus,
+    pub message: String,
+    /// Comparison of the `.dat` tables, when ccx wrote any
+    pub dat: Option<DatComparison>,
+    /// Comparison of the FRD results, when ccx wrote any
+    pub frd: Option<FrdComparison>,
+    /// Wall time of the ccx run
+    pub legacy_seconds: f64,
+    /// Wall time of the Rust solve and its output
+    pub rust_seconds: f64,
+}
+
+/// Runs decks through the legacy ccx executable and the Rust pipeline
+#[derive(Debug, Clone)]
+pub struct CrossValidator {
+    executable: PathBuf,
+    work_dir: PathBuf,
+    tolerances: FrdTolerances,
+    timeout: Option<Duration>,
+    keep_files: bool,
+}
+
+impl CrossValidator {
+    /// Validator running `executable` as `<executable> -i <job>`, with
+    /// scratch directories in the system temporary directory
+    pub fn new(executable: impl Into<PathBuf>) -> Self {
+        Self {
+            executable: executable.into(),
+            work_dir: std::env::temp_dir().join(format!("ccx_xvalidate_{}", std::process::id())),
+            tolerances: FrdTolerances::new(1e-9, 1e-4),
+            timeout: None,
+            keep_files: false,
+        }
+    }
+
+    /// Directory for the scratch directories of the runs
+    pub fn with_work_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.work_dir = dir.into();
+        self
+    }
+
+    pub fn with_tolerances(mut self, tolerances: FrdTolerances) -> Self {
+        self.tolerances = tolerances;
+        self
+    }
+
+    /// Kill ccx runs taking longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Keep the scratch directory of each run, with the ccx files and the
+    /// Rust results as `<job>_rust.dat` and `<job>_rust.frd`
+    pub fn with_keep_files(mut self, keep: bool) -> Self {
+        self.keep_files = keep;
+        self
+    }
+
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    /// Cross-validate the deck at `path`
+    pub fn run(&self, path: &Path) -> XvalidateOutcome {
+        let mut outcome = XvalidateOutcome {
+            deck: path.to_path_buf(),
+            status: XvalidateStatus::RustFailed,
+            message: String::new(),
+            dat: None,
+            frd: None,
+            legacy_seconds: 0.0,
+            rust_seconds: 0.0,
+        };
+        let job = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().replace(' ', "_"))
+            .unwrap_or_else(|| "job".to_string());
+        let run = RUNS.fetch_add(1, Ordering::Relaxed);
+        let dir = self.work_dir.join(format!("{run:04}_{job}"));
+        let result = self.run_in(path, &dir, &job, &mut outcome);
+        if let Err((status, message)) = result {
+            outcome.status = status;
+            outcome.message = message;
+        }
+        if !self.keep_files {
+            let _ = fs::remove_dir_all(&dir);
+        }
+        outcome
+    }
+
+    fn run_in(
+        &self,
+        path: &Path,
+        dir: &Path,
+        job: &str,
+        outcome: &mut XvalidateOutcome,
+    ) -> Result<(), (XvalidateStatus, String)> {
+        let rust_failed = |message: String| (XvalidateStatus::RustFailed, message);
+        let deck = flattened_deck(path).map_err(rust_failed)?;
+        fs::create_dir_all(dir)
+            .and_then(|()| deck.write_file(dir.join(format!("{job}.inp"))))
+            .map_err(|err| rust_failed(format!("cannot write {}: {err}", dir.display())))?;
+
+        let start = Instant::now();
+        let legacy = self.run_legacy(dir, job);
+        outcome.legacy_seconds = start.elapsed().as_secs_f64();
+        legacy.map_err(|message| (XvalidateStatus::LegacyFailed, message))?;
+        let legacy_tables = match fs::read_to_string(dir.join(format!("{job}.dat"))) {
+            Ok(text) => parse_dat_tables(&text),
+            Err(_) => Vec::new(),
+        };
+        let legacy_frd = FrdFile::from_file(dir.join(format!("{job}.frd")))
+            .ok()
+            .filter(|frd| !frd.result_blocks.is_empty());
+
+        let start = Instant::now();
+        let rust = rust_results(&deck, dir, job);
+        outcome.rust_seconds = start.elapsed().as_secs_f64();
+        let (rust_dat, mut rust_frd) = rust?;
+
+        outcome.dat = (!legacy_tables.is_empty()).then(|| {
+            let tables = parse_dat_tables(&rust_dat);
+            dat_compare(&legacy_tables, &tables, &self.tolerances)
+        });
+        outcome.frd = legacy_frd.map(|legacy| {
+            keep_legacy_datasets(&legacy, &mut rust_frd);
+            frd_compare(&legacy, &rust_frd, &self.tolerances)
+        });
+        (outcome.status, outcome.message) = verdict(outcome.dat.as_ref(), outcome.frd.as_ref());
+        Ok(())
+    }
+
+    /// Run ccx on `<dir>/<job>.inp`, logging its output to `<job>.log`
+    fn run_legacy(&self, dir: &Path, job: &str) -> Result<(), String> {
+        let log_path = dir.join(format!("{job}.log"));
+        let log = File::create(&log_path)
+            .map_err(|err| format!("cannot create {}: {err}", log_path.display()))?;
+        let log_err = log.try_clone().map_err(|err| err.to_string())?;
+        let mut child = Command::new(&self.executable)
+            .arg("-i")
+            .arg(job)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(log)
+            .stderr(log_err)
+            .spawn()
+            .map_err(|err| format!("cannot run {}: {err}", self.executable.display()))?;
+        let start = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) => {}
+                Err(err) => return Err(format!("cannot wait for ccx: {err}")),
+            }
+            if let Some(timeout) = self.timeout
+                && start.elapsed() > timeout
+            {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {timeout:?}"));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let log = fs::read_to_string(&log_path).unwrap_or_default();
+        // ccx reports input errors on stdout and may still exit with 0
+        let error = log
+            .lines()
+            .find(|line| line.contains("*ERROR"))
+            .or_else(|| log.lines().rev().find(|line| !line.trim().is_empty()));
+        match (status.success(), error) {
+            (true, Some(line)) if line.contains("*ERROR") => Err(line.trim().to_string()),
+            (true, _) => Ok(()),
+            (false, line) => Err(format!(
+                "ccx exited with {status}{}",
+                line.map(|line| format!(": {}", line.trim()))
+                    .unwrap_or_default()
+            )),
+        }
+    }
+}
+
+/// The deck at `path` with its includes expanded and parameters applied
+fn flattened_deck(path: &Path) -> Result<Deck, String> {
+    let error = |err: ccx_inp::ParseError| format!("{}: {err}", path.display());
+    let mut deck = Deck::parse_file_with_includes(path).map_err(error)?;
+    let mut table = ParameterTable::default();
+    deck.apply_parameters(&mut table).map_err(error)?;
+    deck.flatten(&table);
+    Ok(deck)
+}
+
+/// `.dat` text and FRD results of the Rust solve of `deck`, also written to
+/// `<dir>/<job>_rust.dat` and `.frd`
+fn rust_results(
+    deck: &Deck,
+    dir: &Path,
+    job: &str,
+) -> Result<(String, FrdFile), (XvalidateStatus, String)> {
+    let failed = |message: String| (XvalidateStatus::RustFailed, message);
+    let results = AnalysisPipeline::detect_from_deck(deck)
+        .run(deck)
+        .map_err(|err| failed(format!("analysis error: {err}")))?;
+    if results.displacements.is_empty() {
+        return Err((XvalidateStatus::Unsupported, results.message));
+    }
+    let (_, steps) = static_dat_steps(deck, &results).map_err(failed)?;
+    let mut dat = Vec::new();
+    write_dat_results_to(&mut dat, &steps).map_err(|err| failed(err.to_string()))?;
+    let dat = String::from_utf8_lossy(&dat).into_owned();
+    let mesh = MeshBuilder::build_from_deck(deck).map_err(failed)?;
+    let frd = static_frd(&mesh, &results, job);
+
+    let dat_path = dir.join(format!("{job}_rust.dat"));
+    let frd_path = dir.join(format!("{job}_rust.frd"));
+    fs::write(&dat_path, &dat)
+        .and_then(|()| write_frd(&frd_path, &frd))
+        .map_err(|err| failed(format!("cannot write Rust results: {err}")))?;
+    Ok((dat, frd))
+}
+
+/// Drop the Rust datasets ccx did not write in the same result block, since
+/// ccx only writes the requested ones
+fn keep_legacy_datasets(legacy: &FrdFile, rust: &mut FrdFile) {
+    for (index, block) in rust.result_blocks.iter_mut().enumerate() {
+        let names: Vec<&str> = legacy
+            .result_blocks
+            .get(index)
+            .map(|block| block.datasets.iter().map(|d| d.name.as_str()).collect())
+            .unwrap_or_default();
+        block.datasets.retain(|d| names.contains(&d.name.as_str()));
+    }
+}
+
+fn verdict(dat: Option<&DatComparison>, frd: Option<&FrdComparison>) -> (XvalidateStatus, String) {
+    if dat.is_none() && frd.is_none() {
+        let message = "ccx wrote no result tables or FRD results".to_string();
+        return (XvalidateStatus::NotCompared, message);
+    }
+    let mut problems: Vec<String> = Vec::new();
+    if let Some(dat) = dat {
+        problems.extend(dat.errors.iter().map(|e| format!("dat: {e}")));
+        problems.extend(dat.failures().map(|q| format!("dat {}", q.quantity)));
+        if problems.is_empty() && dat.quantities.is_empty() {
+            problems.push("dat: no table pairs with a ccx table".to_string());
+        }
+    }
+    if let Some(frd) = frd {
+        problems.extend(frd.errors.iter().map(|e| format!("frd: {e}")));
+        problems.extend(
+            frd.failures()
+                .map(|d| format!("frd {} step {}", d.name, d.step)),
+        );
+    }
+    if !problems.is_empty() {
+        return (XvalidateStatus::Differs, problems.join("; "));
+    }
+    let compared: Vec<&str> = [dat.map(|_| "dat"), frd.map(|_| "frd")]
+        .into_iter()
+        .flatten()
+        .collect();
+    let message = format!("{} within tolerance", compared.join(" and "));
+    (XvalidateStatus::Matched, message)
+}
+
+/// Outcomes of a fixture suite
+#[derive(Debug, Clone, Default)]
+pub struct Scoreboard {
+    pub outcomes: Vec<XvalidateOutcome>,
+}
+
+impl Scoreboard {
+    pub fn new(outcomes: Vec<XvalidateOutcome>) -> Self {
+        Self { outcomes }
+    }
+
+    pub fn count(&self, status: XvalidateStatus) -> usize {
+        self.outcomes.iter().filter(|o| o.status == status).count()
+    }
+
+    /// Fraction of the decks with a ccx reference that match it; `None`
+    /// when no deck has one
+    pub fn compatibility(&self) -> Option<f64> {
+        let referenced = self
+            .outcomes
+            .iter()
+            .filter(|o| {
+                !matches!(
+                    o.status,
+                    XvalidateStatus::NotCompared | XvalidateStatus::LegacyFailed
+                )
+            })
+            .count();
+        (referenced > 0).then(|| self.count(XvalidateStatus::Matched) as f64 / referenced as f64)
+    }
+
+    /// Format the scoreboard, one line per deck and then the counts
+    pub fn format(&self) -> String {
+        let mut lines: Vec<String> = self
+            .outcomes
+            .iter()
+            .map(|o| {
+                format!(
+                    "{}  {}  {} (ccx {:.2} s, rust {:.2} s)",
+                    o.status.label(),
+                    o.deck.display(),
+                    o.message,
+                    o.legacy_seconds,
+                    o.rust_seconds
+                )
+            })
+            .collect();
+        lines.push(format!("decks: {}", self.outcomes.len()));
+        for status in XvalidateStatus::ALL {
+            lines.push(format!("{}: {}", status.name(), self.count(status)));
+        }
+        if let Some(compatibility) = self.compatibility() {
+            lines.push(format!("compatibility: {:.1} %", 100.0 * compatibility));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const TRUSS: &str = "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n\
+        *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+        *SOLID SECTION,ELSET=EALL,MATERIAL=STEEL\n\
+        *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n";
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock should be valid")
+            .as_nanos();
+        std::env::temp_dir().join(format!("{prefix}_{}_{nanos}", std::process::id()))
+    }
+
+    /// Executable standing in for ccx: runs `script` with the job name as $2
+    fn fake_ccx(dir: &Path, name: &str, script: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn compares_rust_tables_with_the_legacy_output() {
+        let root = unique_temp_dir("ccx_io_xvalidate");
+        fs::create_dir_all(&root).unwrap();
+        let deck = root.join("truss.inp");
+        fs::write(&deck, TRUSS).unwrap();
+
+        // ccx writes the tables the Rust solve produces, then perturbed ones
+        let parsed = Deck::parse_str(TRUSS).unwrap();
+        let results = AnalysisPipeline::detect_from_deck(&parsed)
+            .run(&parsed)
+            .unwrap();
+        let (_, steps) = static_dat_steps(&parsed, &results).unwrap();
+        let mut dat = Vec::new();
+        write_dat_results_to(&mut dat, &steps).unwrap();
+        let reference = root.join("reference.dat");
+        fs::write(&reference, &dat).unwrap();
+        let same = fake_ccx(
+            &root,
+            "same",
+            &format!("cp '{}' \"$2.dat\"", reference.display()),
+        );
+        let work = root.join("work");
+        let validator = CrossValidator::new(&same)
+            .with_work_dir(&work)
+            .with_tolerances(FrdTolerances::new(1e-12, 1e-6));
+        let matched = validator.run(&deck);
+        assert_eq!(
+            matched.status,
+            XvalidateStatus::Matched,
+            "{}",
+            matched.message
+        );
+        assert_eq!(matched.message, "dat within tolerance");
+        assert!(matched.frd.is_none());
+
+        let mut perturbed = results.clone();
+        perturbed.displacements[1].1[0] *= 2.0;
+        let (_, steps) = static_dat_steps(&parsed, &perturbed).unwrap();
+        let mut perturbed = Vec::new();
+        write_dat_results_to(&mut perturbed, &steps).unwrap();
+        fs::write(&reference, perturbed).unwrap();
+        let differs = validator.clone().with_keep_files(true).run(&deck);
+        assert_eq!(differs.status, XvalidateStatus::Differs);
+        assert_eq!(differs.message, "dat displacements");
+        let kept: Vec<PathBuf> = fs::read_dir(&work)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].join("truss_rust.dat").is_file());
+        assert!(kept[0].join("truss.inp").is_file());
+
+        let board = Scoreboard::new(vec![matched, differs]);
+        assert_eq!(board.compatibility(), Some(0.5));
+        assert!(board.format().ends_with("compatibility: 50.0 %"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn reports_legacy_failures_and_missing_references() {
+        let root = unique_temp_dir("ccx_io_xvalidate_fail");
+        fs::create_dir_all(&root).unwrap();
+        let deck = root.join("truss.inp");
+        fs::write(&deck, TRUSS).unwrap();
+        let run = |script: &str| {
+            CrossValidator::new(fake_ccx(&root, "ccx", script))
+                .with_work_dir(root.join("work"))
+                .with_timeout(Duration::from_millis(500))
+                .run(&deck)
+        };
+
+        let failed = run("echo ' *ERROR reading *CLOAD'");
+        assert_eq!(failed.status, XvalidateStatus::LegacyFailed);
+        assert_eq!(failed.message, "*ERROR reading *CLOAD");
+        let crashed = run("echo 'segmentation fault'; exit 3");
+        assert_eq!(crashed.status, XvalidateStatus::LegacyFailed);
+        assert_eq!(
+            crashed.message,
+            "ccx exited with exit status: 3: segmentation fault"
+        );
+        let hung = run("sleep 5");
+        assert_eq!(hung.message, "timed out after 500ms");
+        let silent = run("true");
+        assert_eq!(silent.status, XvalidateStatus::NotCompared);
+
+        let missing = CrossValidator::new(root.join("no_such_ccx"))
+            .with_work_dir(root.join("work"))
+            .run(&deck);
+        assert_eq!(missing.status, XvalidateStatus::LegacyFailed);
+        assert!(missing.message.starts_with("cannot run"));
+        assert_eq!(Scoreboard::new(vec![silent, missing]).compatibility(), None);
+        assert!(!root.join("work").read_dir().unwrap().any(|_| true));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
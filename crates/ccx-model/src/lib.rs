@@ -4,6 +4,10 @@ use std::collections::BTreeMap;
 
 use ccx_inp::{Card, Deck};
 
+mod lint;
+
+pub use lint::{Diagnostic, Severity, lint_deck};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModelSummary {
     pub total_cards: usize,
@@ -0,0 +1,571 @@
+//! Static checks of a parsed deck that need no mesh or solver.
+//!
+//! [`lint_deck`] flags keywords CalculiX does not know, cards missing a
+//! required parameter, material property cards outside a `*MATERIAL` block
+//! and names of materials, node sets, element sets and surfaces that are used
+//! but never defined. Problems found while building the model are reported by
+//! the callers in the same [`Diagnostic`] form.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
+
+use ccx_inp::{Card, Deck};
+
+use crate::normalized;
+
+/// How serious a [`Diagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Suspicious but the deck can still be solved
+    Warning,
+    /// The deck cannot be solved as written
+    Error,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A problem found in a deck, with the source line when known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// 1-based source line
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(
+                f,
+                "line {}: {}: {}",
+                line,
+                self.severity.label(),
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.severity.label(), self.message),
+        }
+    }
+}
+
+/// Keywords of the CalculiX input format, normalized as by [`normalized`]
+const KNOWN_KEYWORDS: &[&str] = &[
+    "AMPLITUDE",
+    "BEAMSECTION",
+    "BOUNDARY",
+    "BUCKLE",
+    "CFLUX",
+    "CHANGEFRICTION",
+    "CHANGEMATERIAL",
+    "CHANGEPLASTIC",
+    "CHANGESOLIDSECTION",
+    "CHANGESURFACEBEHAVIOR",
+    "CLEARANCE",
+    "CLOAD",
+    "COMPLEXFREQUENCY",
+    "CONDUCTIVITY",
+    "CONSTRAINT",
+    "CONTACTDAMPING",
+    "CONTACTFILE",
+    "CONTACTOUTPUT",
+    "CONTACTPAIR",
+    "CONTACTPRINT",
+    "CONTROLS",
+    "COUPLEDTEMPERATURE-DISPLACEMENT",
+    "COUPLING",
+    "CRACKPROPAGATION",
+    "CREEP",
+    "CYCLICHARDENING",
+    "CYCLICSYMMETRYMODEL",
+    "DAMPING",
+    "DASHPOT",
+    "DEFORMATIONPLASTICITY",
+    "DENSITY",
+    "DEPVAR",
+    "DESIGNVARIABLES",
+    "DFLUX",
+    "DISTRIBUTING",
+    "DISTRIBUTINGCOUPLING",
+    "DLOAD",
+    "DSLOAD",
+    "DYNAMIC",
+    "ELASTIC",
+    "ELCOPY",
+    "ELECTRICALCONDUCTIVITY",
+    "ELECTROMAGNETICS",
+    "ELEMENT",
+    "ELEMENTOUTPUT",
+    "ELFILE",
+    "ELPRINT",
+    "ELSET",
+    "ENDSTEP",
+    "EQUATION",
+    "EXPANSION",
+    "FACEPRINT",
+    "FILM",
+    "FLUIDCONSTANTS",
+    "FLUIDSECTION",
+    "FREQUENCY",
+    "FRICTION",
+    "GAP",
+    "GAPCONDUCTANCE",
+    "GAPHEATGENERATION",
+    "GREEN",
+    "HEADING",
+    "HEATTRANSFER",
+    "HYPERELASTIC",
+    "HYPERFOAM",
+    "INCLUDE",
+    "INITIALCONDITIONS",
+    "INITIALSTRAININCREASE",
+    "KINEMATIC",
+    "MAGNETICPERMEABILITY",
+    "MASS",
+    "MASSFLOW",
+    "MATERIAL",
+    "MEMBRANESECTION",
+    "MODALDAMPING",
+    "MODALDYNAMIC",
+    "MODELCHANGE",
+    "MPC",
+    "NETWORKMPC",
+    "NOANALYSIS",
+    "NODALTHICKNESS",
+    "NODE",
+    "NODEFILE",
+    "NODEOUTPUT",
+    "NODEPRINT",
+    "NORMAL",
+    "NSET",
+    "OBJECTIVE",
+    "ORIENTATION",
+    "OUTPUT",
+    "PARAMETER",
+    "PHYSICALCONSTANTS",
+    "PLASTIC",
+    "PRE-TENSIONSECTION",
+    "RADIATE",
+    "REFINEMESH",
+    "RESTART",
+    "RETAINEDNODALDOFS",
+    "RIGIDBODY",
+    "SECTIONPRINT",
+    "SELECTCYCLICSYMMETRYMODES",
+    "SENSITIVITY",
+    "SHELLSECTION",
+    "SOLIDSECTION",
+    "SPECIFICGASCONSTANT",
+    "SPECIFICHEAT",
+    "SPRING",
+    "STATIC",
+    "STEADYSTATEDYNAMICS",
+    "STEP",
+    "SUBMODEL",
+    "SUBSTRUCTUREGENERATE",
+    "SUBSTRUCTUREMATRIXOUTPUT",
+    "SURFACE",
+    "SURFACEBEHAVIOR",
+    "SURFACEINTERACTION",
+    "TEMPERATURE",
+    "TIE",
+    "TIMEPOINTS",
+    "TRANSFORM",
+    "TRANSFORMF",
+    "UNCOUPLEDTEMPERATURE-DISPLACEMENT",
+    "USERELEMENT",
+    "USERMATERIAL",
+    "USERSECTION",
+    "VALUESATINFINITY",
+    "VIEWFACTOR",
+    "VISCO",
+];
+
+/// Cards that define a property of the material opened by `*MATERIAL`
+const MATERIAL_PROPERTIES: &[&str] = &[
+    "CONDUCTIVITY",
+    "CREEP",
+    "CYCLICHARDENING",
+    "DAMPING",
+    "DEFORMATIONPLASTICITY",
+    "DENSITY",
+    "DEPVAR",
+    "ELASTIC",
+    "ELECTRICALCONDUCTIVITY",
+    "EXPANSION",
+    "HYPERELASTIC",
+    "HYPERFOAM",
+    "PLASTIC",
+    "SPECIFICHEAT",
+    "USERMATERIAL",
+];
+
+/// Parameters a card cannot do without
+fn required_parameters(keyword: &str) -> &'static [&'static str] {
+    match keyword {
+        "MATERIAL" | "SURFACE" | "ORIENTATION" | "AMPLITUDE" | "SURFACEINTERACTION" => &["NAME"],
+        "ELEMENT" => &["TYPE"],
+        "NSET" => &["NSET"],
+        "ELSET" => &["ELSET"],
+        "SOLIDSECTION" | "SHELLSECTION" | "BEAMSECTION" | "MEMBRANESECTION" => {
+            &["ELSET", "MATERIAL"]
+        }
+        "INCLUDE" => &["INPUT"],
+        _ => &[],
+    }
+}
+
+/// Names defined anywhere in the deck, uppercased
+#[derive(Default)]
+struct Definitions {
+    materials: HashSet<String>,
+    node_sets: HashSet<String>,
+    element_sets: HashSet<String>,
+    surfaces: HashSet<String>,
+    nodes: HashSet<i32>,
+    elements: HashSet<i32>,
+}
+
+impl Definitions {
+    fn collect(deck: &Deck) -> Self {
+        let mut defs = Self::default();
+        for card in &deck.cards {
+            let keyword = normalized(&card.keyword);
+            let ids = card
+                .data_lines
+                .iter()
+                .filter_map(|line| line.split(',').next()?.trim().parse::<i32>().ok());
+            match keyword.as_str() {
+                "MATERIAL" => defs.materials.extend(parameter(card, "NAME")),
+                "NODE" => {
+                    defs.node_sets.extend(parameter(card, "NSET"));
+                    defs.nodes.extend(ids);
+                }
+                // Continuation lines of long elements start with a node ID,
+                // which at worst hides an undefined element reference
+                "ELEMENT" => {
+                    defs.element_sets.extend(parameter(card, "ELSET"));
+                    defs.elements.extend(ids);
+                }
+                "NSET" => defs.node_sets.extend(parameter(card, "NSET")),
+                "ELSET" => defs.element_sets.extend(parameter(card, "ELSET")),
+                "SURFACE" => defs.surfaces.extend(parameter(card, "NAME")),
+                _ => {}
+            }
+        }
+        defs
+    }
+}
+
+/// Uppercased value of parameter `key`
+fn parameter(card: &Card, key: &str) -> Option<String> {
+    card.parameters
+        .iter()
+        .find(|p| p.key == key)
+        .and_then(|p| p.value.as_deref())
+        .map(|value| value.trim().to_ascii_uppercase())
+        .filter(|value| !value.is_empty())
+}
+
+/// Source line of parameter `key`, falling back to the header line
+fn parameter_line(card: &Card, key: &str) -> usize {
+    card.parameter_span(key)
+        .map(|span| span.line)
+        .unwrap_or(card.line_start)
+}
+
+/// Run all static checks, returning the diagnostics in deck order
+pub fn lint_deck(deck: &Deck) -> Vec<Diagnostic> {
+    let defs = Definitions::collect(deck);
+    let mut diagnostics = Vec::new();
+    let mut in_material = false;
+    let mut step_open: Option<usize> = None;
+    let mut materials_seen = BTreeSet::new();
+
+    for card in &deck.cards {
+        let keyword = normalized(&card.keyword);
+        let line = Some(card.line_start);
+        if !KNOWN_KEYWORDS.contains(&keyword.as_str()) {
+            diagnostics.push(Diagnostic::warning(
+                line,
+                format!("unknown keyword *{}", card.keyword),
+            ));
+            continue;
+        }
+        for key in required_parameters(&keyword) {
+            if parameter(card, key).is_none() {
+                diagnostics.push(Diagnostic::error(
+                    line,
+                    format!("*{} requires the {} parameter", card.keyword, key),
+                ));
+            }
+        }
+
+        if MATERIAL_PROPERTIES.contains(&keyword.as_str()) {
+            if !in_material {
+                diagnostics.push(Diagnostic::error(
+                    line,
+                    format!("*{} outside a *MATERIAL definition", card.keyword),
+                ));
+            }
+        } else {
+            in_material = keyword == "MATERIAL";
+        }
+
+        match keyword.as_str() {
+            "MATERIAL" => {
+                if let Some(name) = parameter(card, "NAME")
+                    && !materials_seen.insert(name.clone())
+                {
+                    diagnostics.push(Diagnostic::warning(
+                        line,
+                        format!("material {name} is defined more than once"),
+                    ));
+                }
+            }
+            "STEP" => {
+                if step_open.is_some() {
+                    diagnostics.push(Diagnostic::error(
+                        line,
+                        "*STEP inside a step; missing *END STEP",
+                    ));
+                }
+                step_open = Some(card.line_start);
+            }
+            "ENDSTEP" => {
+                if step_open.is_none() {
+                    diagnostics.push(Diagnostic::error(line, "*END STEP without *STEP"));
+                }
+                step_open = None;
+            }
+            "NODE" | "ELEMENT" if card.data_lines.is_empty() => {
+                diagnostics.push(Diagnostic::warning(
+                    line,
+                    format!("*{} has no data lines", card.keyword),
+                ));
+            }
+            _ => {}
+        }
+
+        check_parameter_references(card, &keyword, &defs, &mut diagnostics);
+        check_data_references(card, &keyword, &defs, &mut diagnostics);
+    }
+
+    if let Some(line) = step_open {
+        diagnostics.push(Diagnostic::error(Some(line), "*STEP without *END STEP"));
+    }
+    if !deck
+        .cards
+        .iter()
+        .any(|card| normalized(&card.keyword) == "STEP")
+    {
+        diagnostics.push(Diagnostic::warning(None, "deck has no *STEP"));
+    }
+    diagnostics
+}
+
+/// Materials, sets and surfaces named by parameters
+fn check_parameter_references(
+    card: &Card,
+    keyword: &str,
+    defs: &Definitions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let section = keyword.ends_with("SECTION");
+    let references = [
+        ("MATERIAL", section, &defs.materials, "material"),
+        (
+            "NSET",
+            !matches!(keyword, "NODE" | "NSET"),
+            &defs.node_sets,
+            "node set",
+        ),
+        (
+            "ELSET",
+            !matches!(keyword, "ELEMENT" | "ELSET"),
+            &defs.element_sets,
+            "element set",
+        ),
+    ];
+    for (key, applies, names, kind) in references {
+        if !applies {
+            continue;
+        }
+        if let Some(name) = parameter(card, key)
+            && !names.contains(&name)
+        {
+            diagnostics.push(Diagnostic::error(
+                Some(parameter_line(card, key)),
+                format!("*{} references undefined {} {}", card.keyword, kind, name),
+            ));
+        }
+    }
+}
+
+/// Nodes, elements, sets and surfaces named in the first field of load and
+/// boundary data lines
+fn check_data_references(
+    card: &Card,
+    keyword: &str,
+    defs: &Definitions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    enum Target {
+        Node,
+        Element,
+        Surface,
+    }
+    let target = match keyword {
+        "BOUNDARY" | "CLOAD" | "CFLUX" => Target::Node,
+        "DLOAD" | "DFLUX" | "FILM" | "RADIATE" => Target::Element,
+        "DSLOAD" => Target::Surface,
+        _ => return,
+    };
+    for (index, data_line) in card.data_lines.iter().enumerate() {
+        let field = data_line.split(',').next().unwrap_or("").trim();
+        if field.is_empty() {
+            continue;
+        }
+        let name = field.to_ascii_uppercase();
+        let message = match (&target, field.parse::<i32>()) {
+            (Target::Node, Ok(id)) if !defs.nodes.contains(&id) => {
+                format!("*{} references undefined node {}", card.keyword, id)
+            }
+            (Target::Element, Ok(id)) if !defs.elements.contains(&id) => {
+                format!("*{} references undefined element {}", card.keyword, id)
+            }
+            (Target::Node, Err(_)) if !defs.node_sets.contains(&name) => {
+                format!("*{} references undefined node set {}", card.keyword, field)
+            }
+            (Target::Element, Err(_)) if !defs.element_sets.contains(&name) => {
+                format!(
+                    "*{} references undefined element set {}",
+                    card.keyword, field
+                )
+            }
+            (Target::Surface, _) if !defs.surfaces.contains(&name) => {
+                format!("*{} references undefined surface {}", card.keyword, field)
+            }
+            _ => continue,
+        };
+        diagnostics.push(Diagnostic::error(
+            Some(card.data_line_number(index)),
+            message,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ccx_inp::Deck;
+
+    use super::*;
+
+    const CLEAN: &str = "\
+*NODE, NSET=NALL
+1, 0, 0, 0
+2, 1, 0, 0
+*ELEMENT, TYPE=T3D2, ELSET=BARS
+1, 1, 2
+*NSET, NSET=FIXED
+1
+*MATERIAL, NAME=STEEL
+*ELASTIC
+210000, 0.3
+*SOLID SECTION, ELSET=BARS, MATERIAL=STEEL
+1.
+*STEP
+*STATIC
+*BOUNDARY
+FIXED, 1, 3
+*CLOAD
+2, 1, 10.
+*END STEP
+";
+
+    fn messages(src: &str) -> Vec<String> {
+        let deck = Deck::parse_str(src).unwrap();
+        lint_deck(&deck).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn clean_deck_has_no_diagnostics() {
+        assert!(messages(CLEAN).is_empty(), "{:?}", messages(CLEAN));
+    }
+
+    #[test]
+    fn reports_undefined_references_with_lines() {
+        let src = CLEAN
+            .replace("MATERIAL=STEEL", "MATERIAL=ALU")
+            .replace("FIXED, 1, 3", "CLAMPED, 1, 3")
+            .replace("2, 1, 10.", "7, 1, 10.");
+        assert_eq!(
+            messages(&src),
+            vec![
+                "line 11: error: *SOLID SECTION references undefined material ALU",
+                "line 16: error: *BOUNDARY references undefined node set CLAMPED",
+                "line 18: error: *CLOAD references undefined node 7",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_structural_problems() {
+        let src = "\
+*NODE
+1, 0, 0, 0
+*ELASTIC
+1., 0.3
+*MATERIAL
+*SOLID SECTION, ELSET=E1
+*FOO
+*STEP
+*STATIC
+";
+        let deck = Deck::parse_str(src).unwrap();
+        let diagnostics = lint_deck(&deck);
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "line 3: error: *ELASTIC outside a *MATERIAL definition",
+                "line 5: error: *MATERIAL requires the NAME parameter",
+                "line 6: error: *SOLID SECTION requires the MATERIAL parameter",
+                "line 6: error: *SOLID SECTION references undefined element set E1",
+                "line 7: warning: unknown keyword *FOO",
+                "line 8: error: *STEP without *END STEP",
+            ]
+        );
+        assert_eq!(errors, 5);
+    }
+}
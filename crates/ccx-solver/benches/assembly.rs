@@ -0,0 +1,93 @@
+//! Thread scaling of the global stiffness assembly.
+//!
+//! Assembles a cubic truss lattice (15,625 nodes, 47k DOFs) with rayon pools of
+//! increasing size:
+//!
+//! ```text
+//! cargo bench -p ccx-solver --bench assembly
+//! ```
+
+use ccx_solver::{
+    BoundaryConditions, Element, ElementType, Material, MaterialLibrary, Mesh, Node,
+    SparseGlobalSystem,
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+/// Lattice of `n × n × n` nodes joined by axial and face-diagonal trusses
+fn lattice(n: i32) -> (Mesh, MaterialLibrary) {
+    let mut mesh = Mesh::new();
+    let mut library = MaterialLibrary::new();
+    let mut steel = Material::new("STEEL".to_string());
+    steel.elastic_modulus = Some(210000.0);
+    steel.poissons_ratio = Some(0.3);
+    library.add_material(steel);
+
+    let id = |i: i32, j: i32, k: i32| 1 + i + n * (j + n * k);
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                mesh.add_node(Node::new(id(i, j, k), i as f64, j as f64, k as f64));
+            }
+        }
+    }
+
+    let offsets = [
+        (1, 0, 0),
+        (0, 1, 0),
+        (0, 0, 1),
+        (1, 1, 0),
+        (0, 1, 1),
+        (1, 0, 1),
+    ];
+    let mut elem_id = 0;
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                for (di, dj, dk) in offsets {
+                    let (i2, j2, k2) = (i + di, j + dj, k + dk);
+                    if i2 >= n || j2 >= n || k2 >= n {
+                        continue;
+                    }
+                    elem_id += 1;
+                    mesh.add_element(Element::new(
+                        elem_id,
+                        ElementType::T3D2,
+                        vec![id(i, j, k), id(i2, j2, k2)],
+                    ))
+                    .unwrap();
+                    library.assign_material(elem_id, "STEEL".to_string());
+                }
+            }
+        }
+    }
+    mesh.calculate_dofs();
+    (mesh, library)
+}
+
+fn assembly_scaling(c: &mut Criterion) {
+    let (mesh, library) = lattice(25);
+    let bcs = BoundaryConditions::new();
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut group = c.benchmark_group("sparse_assembly");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(mesh.elements.len() as u64));
+    let mut threads = 1;
+    while threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, _| {
+            b.iter(|| {
+                pool.install(|| SparseGlobalSystem::assemble(&mesh, &library, &bcs, 0.01))
+                    .unwrap()
+            })
+        });
+        threads *= 2;
+    }
+    group.finish();
+}
+
+criterion_group!(benches, assembly_scaling);
+criterion_main!(benches);
@@ -27,7 +27,7 @@ fn main() {
 
     let mut generated = String::new();
     generated.push_str("pub const LEGACY_SOURCE_UNITS: &[LegacySourceUnit] = &[\n");
-    for unit in units {
+    for unit in &units {
         generated.push_str("    LegacySourceUnit {\n");
         generated.push_str(&format!(
             "        legacy_rel_path: {:?},\n",
@@ -46,6 +46,158 @@ fn main() {
     let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
     let out_file = out_dir.join("legacy_source_units.rs");
     fs::write(&out_file, generated).expect("write generated catalog");
+
+    let manifest_path = manifest_dir.join("porting_manifest.toml");
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+    let entries = match read_manifest(&manifest_path, &manifest_dir.join("src"), &units) {
+        Ok(entries) => entries,
+        Err(problems) => panic!(
+            "{} does not match the legacy catalog:\n  {}",
+            manifest_path.display(),
+            problems.join("\n  ")
+        ),
+    };
+    fs::write(out_dir.join("porting_manifest.rs"), manifest_code(&entries))
+        .expect("write generated porting manifest");
+
+    if env::var_os("CARGO_FEATURE_SUITESPARSE").is_some() {
+        println!("cargo:rerun-if-env-changed=SUITESPARSE_LIB_DIR");
+        if let Ok(dir) = env::var("SUITESPARSE_LIB_DIR") {
+            println!("cargo:rustc-link-search=native={dir}");
+        }
+    }
+    if env::var_os("CARGO_FEATURE_PARDISO").is_some() {
+        println!("cargo:rerun-if-env-changed=MKL_LIB_DIR");
+        if let Ok(dir) = env::var("MKL_LIB_DIR") {
+            println!("cargo:rustc-link-search=native={dir}");
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ManifestEntry {
+    path: String,
+    status: &'static str,
+    parity_test: Option<String>,
+    notes: String,
+}
+
+/// Entries of the porting manifest, or every problem found in it: unknown
+/// statuses, duplicate or unsorted paths, paths missing from the legacy
+/// catalog, ported units without a parity test that exists in `src`, and
+/// superseded units without notes
+fn read_manifest(
+    path: &Path,
+    src: &Path,
+    units: &[Unit],
+) -> Result<Vec<ManifestEntry>, Vec<String>> {
+    let text = fs::read_to_string(path).map_err(|err| vec![err.to_string()])?;
+    let table: toml::Table = text.parse().map_err(|err| vec![format!("{err}")])?;
+    let Some(toml::Value::Array(items)) = table.get("unit") else {
+        return Ok(Vec::new());
+    };
+    // A tree without C or Fortran sources is a stub checkout, not a catalog;
+    // its manifest paths are left unchecked without a warning
+    let check_catalog = units.iter().any(|unit| unit.language != "Other");
+
+    let mut problems = Vec::new();
+    let mut entries: Vec<ManifestEntry> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let field = |key: &str| item.get(key).and_then(toml::Value::as_str);
+        let Some(path) = field("path") else {
+            problems.push(format!("unit {}: missing path", index + 1));
+            continue;
+        };
+        let status = match field("status") {
+            Some("pending") => "Pending",
+            Some("in-progress") => "InProgress",
+            Some("ported") => "Ported",
+            Some("superseded") => "Superseded",
+            other => {
+                problems.push(format!("{path}: invalid status {other:?}"));
+                continue;
+            }
+        };
+        let entry = ManifestEntry {
+            path: path.to_string(),
+            status,
+            parity_test: field("parity_test").map(str::to_string),
+            notes: field("notes").unwrap_or_default().to_string(),
+        };
+        if let Some(previous) = entries.last()
+            && previous.path >= entry.path
+        {
+            problems.push(format!(
+                "{path}: duplicate or out of order after {}",
+                previous.path
+            ));
+        }
+        if check_catalog && !units.iter().any(|unit| unit.legacy_rel_path == path) {
+            problems.push(format!("{path}: not in the legacy catalog"));
+        }
+        match (&entry.parity_test, status) {
+            (None, "Ported") => problems.push(format!("{path}: ported without a parity_test")),
+            (Some(test), _) => {
+                if let Err(problem) = check_test_path(src, test) {
+                    problems.push(format!("{path}: parity_test {test}: {problem}"));
+                }
+            }
+            _ => {}
+        }
+        if status == "Superseded" && entry.notes.is_empty() {
+            problems.push(format!("{path}: superseded without notes"));
+        }
+        entries.push(entry);
+    }
+    match problems.is_empty() {
+        true => Ok(entries),
+        false => Err(problems),
+    }
+}
+
+/// Check that the test path `module::...::item` names a module file under
+/// `src` that defines `item` as a function or module; the file read is
+/// registered with cargo, so removing or renaming the test reruns the check
+fn check_test_path(src: &Path, test: &str) -> Result<(), String> {
+    let segments: Vec<&str> = test.split("::").collect();
+    for split in (1..segments.len()).rev() {
+        let dir = src.join(segments[..split].join("/"));
+        let file = [dir.with_extension("rs"), dir.join("mod.rs")]
+            .into_iter()
+            .find(|file| file.is_file());
+        if let Some(file) = file {
+            println!("cargo:rerun-if-changed={}", file.display());
+            let source = fs::read_to_string(&file).map_err(|err| err.to_string())?;
+            let item = segments[segments.len() - 1];
+            let defined = [format!("fn {item}("), format!("mod {item} ")]
+                .iter()
+                .any(|definition| source.contains(definition.as_str()));
+            return match defined {
+                true => Ok(()),
+                false => Err(format!("{item} is not defined in {}", file.display())),
+            };
+        }
+    }
+    Err("no module file".to_string())
+}
+
+fn manifest_code(entries: &[ManifestEntry]) -> String {
+    let mut code = String::from("pub const PORTING_MANIFEST: &[PortingEntry] = &[\n");
+    for entry in entries {
+        code.push_str(&format!(
+            "    PortingEntry {{\n        legacy_rel_path: {:?},\n        \
+             status: PortingStatus::{},\n        parity_test: {:?},\n        \
+             notes: {:?},\n    }},\n",
+            entry.path, entry.status, entry.parity_test, entry.notes
+        ));
+    }
+    code.push_str("];\n\n/// Legacy units whose status in the manifest is ported\n");
+    code.push_str("pub const PORTED_UNITS: &[&str] = &[\n");
+    for entry in entries.iter().filter(|entry| entry.status == "Ported") {
+        code.push_str(&format!("    {:?},\n", entry.path));
+    }
+    code.push_str("];\n");
+    code
 }
 
 fn visit_dir(root: &Path, dir: &Path, units: &mut Vec<Unit>) -> io::Result<()> {
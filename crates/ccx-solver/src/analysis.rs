@@ -5,9 +5,10 @@
 
 use ccx_inp::Deck;
 use ccx_model::ModelSummary;
+use serde::{Deserialize, Serialize};
 
 /// Analysis type enumeration matching CalculiX capabilities
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AnalysisType {
     /// Linear static structural analysis (*STATIC)
     LinearStatic,
@@ -44,7 +45,7 @@ pub enum AnalysisType {
 }
 
 /// Analysis results and statistics
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AnalysisResults {
     /// Whether the analysis completed successfully
     pub success: bool,
@@ -56,6 +57,80 @@ pub struct AnalysisResults {
     pub analysis_type: AnalysisType,
     /// Human-readable status message
     pub message: String,
+    /// Linear solve statistics, when a sparse system was solved
+    pub solve_info: Option<crate::linear_solver::SolveInfo>,
+    /// Nodal displacements (ux, uy, uz) by node ID, when a static system was solved
+    pub displacements: Vec<(i32, [f64; 3])>,
+    /// Nodal reaction forces (fx, fy, fz) by node ID, K·u − F at the
+    /// constrained DOFs and zero at the free ones
+    pub reaction_forces: Vec<(i32, [f64; 3])>,
+    /// Recovered stresses, when the solved model has solid elements
+    pub stresses: Option<crate::stress_recovery::StressField>,
+    /// Section forces of the solved B31 beams
+    pub section_forces: Vec<crate::stress_recovery::BeamSectionForces>,
+    /// Zienkiewicz–Zhu error estimate of the recovered solid stresses
+    pub error_estimate: Option<crate::error_estimate::ErrorEstimate>,
+    /// Connectivity audit of the mesh and sets, made before assembly
+    pub audit: crate::mesh_audit::MeshAudit,
+    /// Implausible material data and gravity for the model's unit system
+    pub unit_warnings: Vec<String>,
+    /// Inverted and degenerate elements, found before assembly
+    pub inverted_elements: Vec<crate::jacobian_check::InvertedElement>,
+    /// Why a static solve produced no solution, if it was attempted and failed
+    pub failure: Option<SolveFailure>,
+}
+
+/// Stage at which a linear static solve stopped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SolveFailure {
+    /// Inverted or degenerate elements stopped the pipeline before assembly
+    InvertedElements(usize),
+    /// The element or global matrices could not be assembled
+    Assembly(String),
+    /// The linear solver failed, e.g. on a singular matrix or without
+    /// converging
+    Solve(String),
+    /// Stresses or section forces could not be recovered from the solution
+    StressRecovery(String),
+}
+
+impl std::fmt::Display for SolveFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvertedElements(count) => {
+                write!(
+                    f,
+                    "{count} inverted or degenerate elements, assembly skipped"
+                )
+            }
+            Self::Assembly(e) => write!(f, "ASSEMBLY FAILED: {e}"),
+            Self::Solve(e) => write!(f, "SOLVE FAILED: {e}"),
+            Self::StressRecovery(e) => write!(f, "STRESS RECOVERY FAILED: {e}"),
+        }
+    }
+}
+
+/// Solution fields of a linear static solve
+#[derive(Default)]
+struct StaticSolution {
+    solve_info: Option<crate::linear_solver::SolveInfo>,
+    displacements: Vec<(i32, [f64; 3])>,
+    reaction_forces: Vec<(i32, [f64; 3])>,
+    stresses: Option<crate::stress_recovery::StressField>,
+    section_forces: Vec<crate::stress_recovery::BeamSectionForces>,
+    error_estimate: Option<crate::error_estimate::ErrorEstimate>,
+    failure: Option<SolveFailure>,
+}
+
+/// Storage of the assembled global matrices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatrixStorage {
+    /// Compressed sparse row storage ([`crate::SparseGlobalSystem`])
+    #[default]
+    Sparse,
+    /// Dense storage ([`crate::GlobalSystem`]); O(n²) memory, for debugging
+    /// small models only
+    Dense,
 }
 
 /// Analysis configuration and control
@@ -69,6 +144,20 @@ pub struct AnalysisConfig {
     pub tolerance: f64,
     /// Whether to write detailed output
     pub verbose: bool,
+    /// Global matrix storage, shared by all analysis types
+    pub matrix_storage: MatrixStorage,
+    /// Linear solver backend for sparse storage
+    pub linear_solver: crate::linear_solver::LinearSolverKind,
+    /// Averaging of the extrapolated nodal stresses and strains
+    pub nodal_averaging: crate::stress_recovery::NodalAveraging,
+    /// Unit system to check material data and gravity against; when absent,
+    /// a `** UNITS:` comment of the deck is used
+    pub unit_system: Option<crate::units::UnitSystem>,
+    /// Whether inverted or degenerate elements stop the solve
+    pub inverted_elements: crate::jacobian_check::InvertedElementAction,
+    /// Legacy routines for the element types without a port; such
+    /// elements are skipped when absent
+    pub legacy_elements: Option<std::sync::Arc<crate::legacy_elements::LegacyElements>>,
 }
 
 impl Default for AnalysisConfig {
@@ -78,6 +167,12 @@ impl Default for AnalysisConfig {
             max_iterations: 200,
             tolerance: 1e-8,
             verbose: false,
+            matrix_storage: MatrixStorage::default(),
+            linear_solver: crate::linear_solver::LinearSolverKind::default(),
+            nodal_averaging: crate::stress_recovery::NodalAveraging::default(),
+            unit_system: None,
+            inverted_elements: crate::jacobian_check::InvertedElementAction::default(),
+            legacy_elements: None,
         }
     }
 }
@@ -240,6 +335,20 @@ impl AnalysisPipeline {
         let mut mesh = crate::mesh_builder::MeshBuilder::build_from_deck(deck)?;
         mesh.calculate_dofs();
         let mesh_stats = mesh.statistics();
+        tracing::debug!(
+            "Built mesh: {} nodes, {} elements, {} DOFs",
+            mesh_stats.num_nodes,
+            mesh_stats.num_elements,
+            mesh.num_dofs
+        );
+        let mut audit = mesh.audit();
+        if let Ok(mut sets) = crate::sets::Sets::build_from_deck(deck)
+            && sets.add_card_sets(deck).is_ok()
+        {
+            audit = audit.with_unused_sets(&sets, deck);
+        }
+        let unit_warnings = self.check_units(deck);
+        let inverted_elements = mesh.check_jacobians();
 
         // Step 2: Build boundary conditions and loads
         let bcs = crate::bc_builder::BCBuilder::build_from_deck(deck)?;
@@ -248,8 +357,14 @@ impl AnalysisPipeline {
         // Calculate constrained and free DOFs
         let constrained_dofs = bcs.get_constrained_dofs();
         let free_dofs = mesh.num_dofs - constrained_dofs.len();
+        tracing::debug!(
+            "Built boundary conditions: {} constrained DOFs, {} loads",
+            constrained_dofs.len(),
+            bc_stats.num_concentrated_loads
+        );
 
         // For structural analysis with truss elements, attempt to solve
+        let mut solution = StaticSolution::default();
         let solve_message = if self.config.analysis_type == AnalysisType::LinearStatic {
             // Step 3: Build materials
             match crate::materials::MaterialLibrary::build_from_deck(deck) {
@@ -263,25 +378,20 @@ impl AnalysisPipeline {
                         }
                     }
 
-                    // Step 4: Assemble and solve (only for truss elements currently)
-                    let has_truss_elements = mesh
-                        .elements
-                        .values()
-                        .any(|e| matches!(e.element_type, crate::mesh::ElementType::T3D2));
-
-                    if has_truss_elements {
-                        match crate::assembly::GlobalSystem::assemble(
-                            &mesh, &materials, &bcs, 0.001,
-                        ) {
-                            Ok(system) => match system.solve() {
-                                Ok(_displacements) => " [SOLVED]".to_string(),
-                                Err(e) => format!(" [SOLVE FAILED: {}]", e),
-                            },
-                            Err(e) => format!(" [ASSEMBLY FAILED: {}]", e),
-                        }
-                    } else {
-                        " [solver supports T3D2 truss elements only]".to_string()
-                    }
+                    // Step 4: Assemble and solve (truss, beam and solid elements)
+                    let average = |field: &mut crate::stress_recovery::StressField| {
+                        self.average_nodal_fields(deck, &mesh, &materials, field)
+                    };
+                    let (message, solved) = self.solve_static(
+                        &mesh,
+                        &materials,
+                        &bcs,
+                        &inverted_elements,
+                        0.001,
+                        &average,
+                    );
+                    solution = solved;
+                    message
                 }
                 Err(_) => " [no materials defined]".to_string(),
             }
@@ -294,25 +404,393 @@ impl AnalysisPipeline {
             num_dofs: mesh.num_dofs,
             num_equations: free_dofs, // Only free DOFs are solved
             analysis_type: self.config.analysis_type,
-            message: format!(
-                "Model initialized: {} nodes, {} elements, {} DOFs ({} free, {} constrained), {} loads{}",
-                mesh_stats.num_nodes,
-                mesh_stats.num_elements,
-                mesh.num_dofs,
-                free_dofs,
-                constrained_dofs.len(),
-                bc_stats.num_concentrated_loads,
-                solve_message
-            ),
+            message: model_message(&mesh, &bcs, &solve_message),
+            solve_info: solution.solve_info,
+            displacements: solution.displacements,
+            reaction_forces: solution.reaction_forces,
+            stresses: solution.stresses,
+            section_forces: solution.section_forces,
+            error_estimate: solution.error_estimate,
+            audit,
+            unit_warnings,
+            inverted_elements,
+            failure: solution.failure,
         })
     }
 
+    /// Run the analysis on a model built in code, e.g. with
+    /// [`crate::ModelBuilder`], instead of read from a deck
+    ///
+    /// As with [`Self::run`], only linear static analyses are solved.
+    pub fn run_model(
+        &self,
+        model: &crate::model_builder::Model,
+    ) -> Result<AnalysisResults, String> {
+        let mesh = &model.mesh;
+        if mesh.nodes.is_empty() {
+            return Err("No nodes defined in model".to_string());
+        }
+        if mesh.elements.is_empty() {
+            return Err("No elements defined in model".to_string());
+        }
+        let bcs = &model.boundary_conditions;
+        let free_dofs = mesh.num_dofs - bcs.get_constrained_dofs().len();
+        let unit_warnings = self
+            .config
+            .unit_system
+            .map_or_else(Vec::new, |system| system.check_materials(&model.materials));
+        let inverted_elements = mesh.check_jacobians();
+
+        let (solve_message, solution) = match self.config.analysis_type {
+            AnalysisType::LinearStatic => {
+                let average = |field: &mut crate::stress_recovery::StressField| {
+                    if self.config.nodal_averaging == crate::stress_recovery::NodalAveraging::All {
+                        return Ok(());
+                    }
+                    let regions = self.config.nodal_averaging.element_regions(
+                        mesh,
+                        &model.materials,
+                        &model.sets,
+                    )?;
+                    field.average_within(mesh, &regions);
+                    Ok(())
+                };
+                self.solve_static(
+                    mesh,
+                    &model.materials,
+                    bcs,
+                    &inverted_elements,
+                    model.section_area,
+                    &average,
+                )
+            }
+            _ => (String::new(), StaticSolution::default()),
+        };
+
+        Ok(AnalysisResults {
+            success: true,
+            num_dofs: mesh.num_dofs,
+            num_equations: free_dofs,
+            analysis_type: self.config.analysis_type,
+            message: model_message(mesh, bcs, &solve_message),
+            solve_info: solution.solve_info,
+            displacements: solution.displacements,
+            reaction_forces: solution.reaction_forces,
+            stresses: solution.stresses,
+            section_forces: solution.section_forces,
+            error_estimate: solution.error_estimate,
+            audit: mesh.audit(),
+            unit_warnings,
+            inverted_elements,
+            failure: solution.failure,
+        })
+    }
+
+    /// Assemble and solve a linear static model, recover its stresses and
+    /// average them with `average`
+    ///
+    /// Returns the status suffix of the results message.
+    fn solve_static(
+        &self,
+        mesh: &crate::mesh::Mesh,
+        materials: &crate::materials::MaterialLibrary,
+        bcs: &crate::boundary_conditions::BoundaryConditions,
+        inverted_elements: &[crate::jacobian_check::InvertedElement],
+        default_area: f64,
+        average: &dyn Fn(&mut crate::stress_recovery::StressField) -> Result<(), String>,
+    ) -> (String, StaticSolution) {
+        let mut solution = StaticSolution::default();
+        let legacy = self.config.legacy_elements.as_deref();
+        let has_supported_elements = mesh.elements.values().any(|e| {
+            crate::elements::DynamicElement::is_supported(e.element_type)
+                || legacy.is_some_and(|legacy| legacy.handles(e.element_type))
+        });
+
+        let message = if has_supported_elements
+            && !inverted_elements.is_empty()
+            && self.config.inverted_elements == crate::jacobian_check::InvertedElementAction::Abort
+        {
+            let inverted = SolveFailure::InvertedElements(inverted_elements.len());
+            let message = format!(" [{inverted}]");
+            solution.failure = Some(inverted);
+            message
+        } else if has_supported_elements {
+            match self.assemble_and_solve(mesh, materials, bcs, default_area) {
+                Ok((u, reactions, info)) => {
+                    solution.solve_info = info;
+                    solution.reaction_forces = nodal_translations(mesh, &reactions);
+                    match Self::recover_results(mesh, materials, &u, default_area) {
+                        Ok((nodal, mut field, beams)) => {
+                            // The ZZ estimate needs the fully averaged field
+                            solution.error_estimate = field.as_ref().and_then(|field| {
+                                crate::error_estimate::zz_error_estimate(mesh, materials, field)
+                                    .ok()
+                            });
+                            let averaged = match field.as_mut() {
+                                Some(field) => average(field),
+                                None => Ok(()),
+                            };
+                            solution.displacements = nodal;
+                            solution.stresses = field;
+                            solution.section_forces = beams;
+                            match averaged {
+                                Ok(()) => " [SOLVED]".to_string(),
+                                Err(e) => format!(" [NODAL AVERAGING FAILED: {}]", e),
+                            }
+                        }
+                        Err(e) => {
+                            let recovery = SolveFailure::StressRecovery(e);
+                            let message = format!(" [{recovery}]");
+                            solution.failure = Some(recovery);
+                            message
+                        }
+                    }
+                }
+                Err(e) => {
+                    let message = format!(" [{e}]");
+                    solution.failure = Some(e);
+                    message
+                }
+            }
+        } else {
+            " [solver supports T3D2, B31 and C3D4/C3D8/C3D10/C3D20 elements only]".to_string()
+        };
+        (message, solution)
+    }
+
+    /// Plausibility warnings for the configured or declared unit system
+    fn check_units(&self, deck: &Deck) -> Vec<String> {
+        let system = match self.config.unit_system {
+            Some(system) => system,
+            None => match crate::units::UnitSystem::from_deck(deck) {
+                Ok(Some(system)) => system,
+                Ok(None) => return Vec::new(),
+                Err(e) => return vec![e],
+            },
+        };
+        let mut warnings = match crate::materials::MaterialLibrary::build_from_deck(deck) {
+            Ok(materials) => system.check_materials(&materials),
+            Err(_) => Vec::new(),
+        };
+        warnings.extend(system.check_gravity(deck));
+        warnings
+    }
+
+    /// Restrict the nodal averaging of `field` as configured
+    fn average_nodal_fields(
+        &self,
+        deck: &Deck,
+        mesh: &crate::mesh::Mesh,
+        materials: &crate::materials::MaterialLibrary,
+        field: &mut crate::stress_recovery::StressField,
+    ) -> Result<(), String> {
+        if self.config.nodal_averaging == crate::stress_recovery::NodalAveraging::All {
+            return Ok(());
+        }
+        let mut sets = crate::sets::Sets::build_from_deck(deck)?;
+        sets.add_card_sets(deck)?;
+        let regions = self
+            .config
+            .nodal_averaging
+            .element_regions(mesh, materials, &sets)?;
+        field.average_within(mesh, &regions);
+        Ok(())
+    }
+
+    /// Split the solution into nodal displacements and recover solid stresses
+    /// and beam section forces
+    #[allow(clippy::type_complexity)]
+    fn recover_results(
+        mesh: &crate::mesh::Mesh,
+        materials: &crate::materials::MaterialLibrary,
+        u: &nalgebra::DVector<f64>,
+        default_area: f64,
+    ) -> Result<
+        (
+            Vec<(i32, [f64; 3])>,
+            Option<crate::stress_recovery::StressField>,
+            Vec<crate::stress_recovery::BeamSectionForces>,
+        ),
+        String,
+    > {
+        let dofs_per_node = dofs_per_node(mesh);
+        let displacements = nodal_translations(mesh, u);
+
+        let stresses = crate::stress_recovery::recover_stresses(mesh, materials, u, dofs_per_node)?;
+        let section_forces = crate::stress_recovery::recover_section_forces(
+            mesh,
+            materials,
+            u,
+            dofs_per_node,
+            default_area,
+        )?;
+        Ok((
+            displacements,
+            (!stresses.is_empty()).then_some(stresses),
+            section_forces,
+        ))
+    }
+
+    /// Assemble K and F with the configured storage, solve K u = F and
+    /// compute the reactions at the constrained DOFs
+    #[allow(clippy::type_complexity)]
+    fn assemble_and_solve(
+        &self,
+        mesh: &crate::mesh::Mesh,
+        materials: &crate::materials::MaterialLibrary,
+        bcs: &crate::boundary_conditions::BoundaryConditions,
+        default_area: f64,
+    ) -> Result<
+        (
+            nalgebra::DVector<f64>,
+            nalgebra::DVector<f64>,
+            Option<crate::linear_solver::SolveInfo>,
+        ),
+        SolveFailure,
+    > {
+        let legacy = self.config.legacy_elements.as_deref();
+        match self.config.matrix_storage {
+            MatrixStorage::Sparse => {
+                let system = crate::sparse_assembly::SparseGlobalSystem::assemble_with_legacy(
+                    mesh,
+                    materials,
+                    bcs,
+                    default_area,
+                    legacy,
+                )
+                .map_err(SolveFailure::Assembly)?;
+                tracing::debug!(
+                    "Assembled sparse system: {} DOFs, {} nnz",
+                    system.num_dofs,
+                    system.stiffness.nnz()
+                );
+                let (u, info) = system
+                    .solve_with_info(self.config.linear_solver.create().as_mut())
+                    .map_err(SolveFailure::Solve)?;
+                tracing::debug!("Solved {}", info.summary());
+                let reactions = system.reactions(&u, bcs);
+                Ok((u, reactions, Some(info)))
+            }
+            MatrixStorage::Dense => {
+                tracing::debug!("Assembling dense system");
+                let system = crate::assembly::GlobalSystem::assemble_with_legacy(
+                    mesh,
+                    materials,
+                    bcs,
+                    default_area,
+                    legacy,
+                )
+                .map_err(SolveFailure::Assembly)?;
+                let u = system.solve().map_err(SolveFailure::Solve)?;
+                let reactions = system.reactions(&u, bcs, dofs_per_node(mesh));
+                Ok((u, reactions, None))
+            }
+        }
+    }
+
+    /// Use `linear_solver` for sparse solves
+    pub fn with_linear_solver(
+        mut self,
+        linear_solver: crate::linear_solver::LinearSolverKind,
+    ) -> Self {
+        self.config.linear_solver = linear_solver;
+        self
+    }
+
+    /// Average nodal stresses and strains as given by `nodal_averaging`
+    pub fn with_nodal_averaging(
+        mut self,
+        nodal_averaging: crate::stress_recovery::NodalAveraging,
+    ) -> Self {
+        self.config.nodal_averaging = nodal_averaging;
+        self
+    }
+
+    /// Check material data and gravity against `unit_system`
+    pub fn with_unit_system(mut self, unit_system: crate::units::UnitSystem) -> Self {
+        self.config.unit_system = Some(unit_system);
+        self
+    }
+
+    /// Choose whether inverted or degenerate elements stop the solve
+    pub fn with_inverted_elements(
+        mut self,
+        action: crate::jacobian_check::InvertedElementAction,
+    ) -> Self {
+        self.config.inverted_elements = action;
+        self
+    }
+
+    /// Compute the element types without a port with the routines of
+    /// `legacy_elements`
+    pub fn with_legacy_elements(
+        mut self,
+        legacy_elements: std::sync::Arc<crate::legacy_elements::LegacyElements>,
+    ) -> Self {
+        self.config.legacy_elements = Some(legacy_elements);
+        self
+    }
+
     /// Get the current configuration
     pub fn config(&self) -> &AnalysisConfig {
         &self.config
     }
 }
 
+/// Results message: the model size and the status of the solve
+/// DOF stride per node of the assembled system: the largest DOF count of
+/// any element type
+fn dofs_per_node(mesh: &crate::mesh::Mesh) -> usize {
+    mesh.elements
+        .values()
+        .map(|e| e.element_type.dofs_per_node())
+        .max()
+        .unwrap_or(3)
+}
+
+/// Translational components of a global DOF vector by node ID
+fn nodal_translations(
+    mesh: &crate::mesh::Mesh,
+    values: &nalgebra::DVector<f64>,
+) -> Vec<(i32, [f64; 3])> {
+    let dofs_per_node = dofs_per_node(mesh);
+    let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+    node_ids.sort_unstable();
+    node_ids
+        .into_iter()
+        .filter_map(|id| {
+            let base = (id - 1) as usize * dofs_per_node;
+            Some((
+                id,
+                [
+                    *values.get(base)?,
+                    *values.get(base + 1)?,
+                    *values.get(base + 2)?,
+                ],
+            ))
+        })
+        .collect()
+}
+
+fn model_message(
+    mesh: &crate::mesh::Mesh,
+    bcs: &crate::boundary_conditions::BoundaryConditions,
+    solve_message: &str,
+) -> String {
+    let stats = mesh.statistics();
+    let constrained = bcs.get_constrained_dofs().len();
+    format!(
+        "Model initialized: {} nodes, {} elements, {} DOFs ({} free, {} constrained), {} loads{}",
+        stats.num_nodes,
+        stats.num_elements,
+        mesh.num_dofs,
+        mesh.num_dofs - constrained,
+        constrained,
+        bcs.statistics().num_concentrated_loads,
+        solve_message
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -399,6 +877,153 @@ mod tests {
         assert_eq!(result.analysis_type, AnalysisType::LinearStatic);
     }
 
+    #[test]
+    fn sparse_storage_is_default_and_dense_is_selectable() {
+        assert_eq!(
+            AnalysisConfig::default().matrix_storage,
+            MatrixStorage::Sparse
+        );
+
+        let deck = Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,1,0,0\n*ELEMENT,TYPE=T3D2\n1,1,2\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n*STEP\n*STATIC\n*CLOAD\n2,1,100.\n*END STEP\n",
+        )
+        .expect("deck should parse");
+        for matrix_storage in [MatrixStorage::Sparse, MatrixStorage::Dense] {
+            let pipeline = AnalysisPipeline::new(AnalysisConfig {
+                matrix_storage,
+                ..Default::default()
+            });
+            let result = pipeline.run(&deck).expect("run should succeed");
+            assert!(result.message.ends_with("[SOLVED]"), "{}", result.message);
+            assert_eq!(
+                result.solve_info.is_some(),
+                matrix_storage == MatrixStorage::Sparse
+            );
+        }
+    }
+
+    #[test]
+    fn unported_elements_fall_back_to_legacy_routines() {
+        let deck = Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,2,0,0\n3,0,1,0\n*ELEMENT,TYPE=M3D3\n1,1,2,3\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n10,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n3,1,3\n*STEP\n*STATIC\n*CLOAD\n2,1,10.\n*END STEP\n",
+        )
+        .expect("deck should parse");
+        let result = AnalysisPipeline::linear_static().run(&deck).unwrap();
+        assert!(result.displacements.is_empty());
+
+        let legacy = std::sync::Arc::new(crate::legacy_elements::tests::spring_elements());
+        for matrix_storage in [MatrixStorage::Sparse, MatrixStorage::Dense] {
+            let pipeline = AnalysisPipeline::new(AnalysisConfig {
+                matrix_storage,
+                ..Default::default()
+            })
+            .with_legacy_elements(legacy.clone());
+            let result = pipeline.run(&deck).expect("run should succeed");
+            assert!(result.message.ends_with("[SOLVED]"), "{}", result.message);
+            let (_, u2) = result
+                .displacements
+                .iter()
+                .find(|(id, _)| *id == 2)
+                .unwrap();
+            assert!((u2[0] - 2.0).abs() < 1e-6, "{u2:?}");
+        }
+        assert_eq!(legacy.usage().calls(ccx_compat::DEFAULT_JOB, "e_m3d3_"), 2);
+    }
+
+    #[test]
+    fn reaction_forces_balance_the_applied_loads() {
+        // Unit cube fixed at x = 0 and pulled with 100 at x = 1
+        let deck = Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,1,0\n4,0,1,0\n5,0,0,1\n6,1,0,1\n7,1,1,1\n8,0,1,1\n\
+             *ELEMENT,TYPE=C3D8\n1,1,2,3,4,5,6,7,8\n*MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n4,1,3\n5,1,3\n8,1,3\n*STEP\n*STATIC\n\
+             *CLOAD\n2,1,25.\n3,1,25.\n6,1,25.\n7,1,25.\n*END STEP\n",
+        )
+        .expect("deck should parse");
+        for matrix_storage in [MatrixStorage::Sparse, MatrixStorage::Dense] {
+            let results = AnalysisPipeline::new(AnalysisConfig {
+                matrix_storage,
+                ..Default::default()
+            })
+            .run(&deck)
+            .expect("run should succeed");
+            assert_eq!(results.reaction_forces.len(), 8);
+
+            let mut total = [0.0; 3];
+            for (node, force) in &results.reaction_forces {
+                if [2, 3, 6, 7].contains(node) {
+                    assert_eq!(*force, [0.0; 3], "free node {node}");
+                }
+                for (t, f) in total.iter_mut().zip(force) {
+                    *t += f;
+                }
+            }
+            assert!((total[0] + 100.0).abs() < 1e-6, "{total:?}");
+            assert!(total[1].abs() < 1e-6 && total[2].abs() < 1e-6, "{total:?}");
+        }
+    }
+
+    #[test]
+    fn results_round_trip_through_json() {
+        // Unit cube fixed at x = 0 and pulled at x = 1
+        let deck = Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,1,0\n4,0,1,0\n5,0,0,1\n6,1,0,1\n7,1,1,1\n8,0,1,1\n\
+             *ELEMENT,TYPE=C3D8\n1,1,2,3,4,5,6,7,8\n*MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n4,1,3\n5,1,3\n8,1,3\n*STEP\n*STATIC\n\
+             *CLOAD\n2,1,25.\n3,1,25.\n6,1,25.\n7,1,25.\n*END STEP\n",
+        )
+        .expect("deck should parse");
+        let results = AnalysisPipeline::linear_static()
+            .run(&deck)
+            .expect("run should succeed");
+        assert!(results.stresses.is_some(), "{}", results.message);
+        assert!(results.solve_info.is_some());
+
+        let json = serde_json::to_string(&results).unwrap();
+        let restored: AnalysisResults = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, results);
+        let failure: SolveFailure = serde_json::from_str(r#"{"Solve":"singular matrix"}"#).unwrap();
+        assert_eq!(failure, SolveFailure::Solve("singular matrix".to_string()));
+    }
+
+    #[test]
+    fn inverted_elements_skip_assembly_unless_warned() {
+        // Element 2 has zero length
+        let deck = Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,0,0\n*ELEMENT,TYPE=T3D2\n1,1,2\n2,2,3\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n2,2,3\n3,2,3\n*STEP\n*STATIC\n*CLOAD\n3,1,100.\n*END STEP\n",
+        )
+        .expect("deck should parse");
+        let result = AnalysisPipeline::linear_static().run(&deck).unwrap();
+        assert_eq!(result.inverted_elements.len(), 1);
+        assert_eq!(result.inverted_elements[0].nodes, vec![2, 3]);
+        assert!(
+            result
+                .message
+                .ends_with("[1 inverted or degenerate elements, assembly skipped]"),
+            "{}",
+            result.message
+        );
+        assert!(result.displacements.is_empty());
+        assert_eq!(result.failure, Some(SolveFailure::InvertedElements(1)));
+
+        let result = AnalysisPipeline::linear_static()
+            .with_inverted_elements(crate::jacobian_check::InvertedElementAction::Warn)
+            .run(&deck)
+            .unwrap();
+        assert_eq!(result.inverted_elements.len(), 1);
+        assert!(
+            !result.message.contains("assembly skipped"),
+            "{}",
+            result.message
+        );
+    }
+
     #[test]
     fn detects_buckling_analysis() {
         let deck = deck_with_keywords("*BUCKLE");
@@ -1,35 +1,35 @@
-//! Global matrix assembly for finite element systems.
+//! Dense global matrix assembly for finite element systems.
 //!
 //! Assembles element stiffness matrices into the global system:
-//! - K: Global stiffness matrix (sparse CSR format)
+//! - K: Global stiffness matrix (dense `DMatrix`)
 //! - F: Global force vector
 //!
+//! Dense storage needs O(n²) memory and is kept as a debugging reference
+//! for small models; the analysis pipeline assembles into
+//! [`crate::SparseGlobalSystem`] unless [`crate::MatrixStorage::Dense`] is
+//! selected.
+//!
 //! ## Assembly Process
 //!
-//! 1. Allocate sparse global stiffness matrix K (num_dofs × num_dofs)
-//! 2. Loop over all elements:
+//! 1. Allocate global stiffness matrix K (num_dofs × num_dofs)
+//! 2. Loop over all elements (in parallel with rayon):
 //!    - Compute element stiffness k_e
 //!    - Get element DOF indices
 //!    - Add k_e contributions to K
 //! 3. Build force vector F from boundary conditions
 //! 4. Apply displacement boundary conditions
-//!
-//! ## Sparse Matrix Format
-//!
-//! Uses Compressed Sparse Row (CSR) format for efficiency:
-//! - Only stores non-zero entries
-//! - Fast matrix-vector multiplication
-//! - Efficient for iterative solvers
 
 use crate::boundary_conditions::BoundaryConditions;
+use crate::legacy_elements::LegacyElements;
 use crate::materials::MaterialLibrary;
 use crate::mesh::Mesh;
 use nalgebra::{DMatrix, DVector};
+use rayon::prelude::*;
 
 /// Global finite element system
 #[derive(Debug, Clone)]
 pub struct GlobalSystem {
-    /// Global stiffness matrix (dense for now, sparse later)
+    /// Global stiffness matrix
     pub stiffness: DMatrix<f64>,
     /// Global force vector
     pub force: DVector<f64>,
@@ -54,7 +54,7 @@ impl GlobalSystem {
     ///
     /// # Current Limitations
     /// - Assumes uniform cross-sectional area
-    /// - Dense matrix storage (will switch to sparse CSR later)
+    /// - Dense matrix storage (see [`crate::SparseGlobalSystem`] for large models)
     ///
     /// # Supported Elements
     /// - T3D2: 2-node truss (3 DOFs/node)
@@ -64,6 +64,18 @@ impl GlobalSystem {
         materials: &MaterialLibrary,
         bcs: &BoundaryConditions,
         default_area: f64,
+    ) -> Result<Self, String> {
+        Self::assemble_with_legacy(mesh, materials, bcs, default_area, None)
+    }
+
+    /// Assemble like [`GlobalSystem::assemble`], computing the element types
+    /// without a port with the routines of `legacy`
+    pub fn assemble_with_legacy(
+        mesh: &Mesh,
+        materials: &MaterialLibrary,
+        bcs: &BoundaryConditions,
+        default_area: f64,
+        legacy: Option<&LegacyElements>,
     ) -> Result<Self, String> {
         // Determine maximum DOFs per node for mixed meshes
         let max_dofs_per_node = mesh
@@ -79,7 +91,7 @@ impl GlobalSystem {
         let mut system = Self::new(num_dofs);
 
         // Assemble stiffness matrix
-        system.assemble_stiffness(mesh, materials, default_area, max_dofs_per_node)?;
+        system.assemble_stiffness(mesh, materials, default_area, max_dofs_per_node, legacy)?;
 
         // Assemble force vector
         system.assemble_forces(bcs, max_dofs_per_node)?;
@@ -91,59 +103,36 @@ impl GlobalSystem {
     }
 
     /// Assemble element stiffness contributions into global matrix
+    ///
+    /// Element matrices are computed in parallel, then added to K in
+    /// element id order so the result does not depend on the thread count.
     fn assemble_stiffness(
         &mut self,
         mesh: &Mesh,
         materials: &MaterialLibrary,
         default_area: f64,
         max_dofs_per_node: usize,
+        legacy: Option<&LegacyElements>,
     ) -> Result<(), String> {
-        use crate::elements::DynamicElement;
-
-        for (elem_id, element) in &mesh.elements {
-            // Get element nodes
-            let nodes: Vec<_> = element
-                .nodes
-                .iter()
-                .map(|&node_id| {
-                    mesh.nodes
-                        .get(&node_id)
-                        .cloned()
-                        .ok_or(format!("Node {} not found", node_id))
-                })
-                .collect::<Result<Vec<_>, String>>()?;
-
-            // Get material for this element
-            let material = materials
-                .get_element_material(*elem_id)
-                .ok_or(format!("No material assigned to element {}", elem_id))?;
-
-            // Create element using factory
-            let dyn_elem = DynamicElement::from_mesh_element(
-                element.element_type,
-                *elem_id,
-                element.nodes.clone(),
-                default_area,
-            );
-
-            let dyn_elem = match dyn_elem {
-                Some(e) => e,
-                None => {
-                    eprintln!(
-                        "Warning: Unsupported element type {:?}, skipping element {}",
-                        element.element_type, elem_id
-                    );
-                    continue;
-                }
-            };
-
-            // Compute element stiffness matrix
-            let k_e = dyn_elem.stiffness_matrix(&nodes, material)?;
-
-            // Get global DOF indices with correct stride
-            let dof_indices = dyn_elem.global_dof_indices(&element.nodes, max_dofs_per_node);
-
-            // Add element contribution to global matrix
+        let mut elem_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        elem_ids.sort_unstable();
+
+        let contributions = elem_ids
+            .par_iter()
+            .map(|&elem_id| {
+                element_stiffness(
+                    mesh,
+                    materials,
+                    elem_id,
+                    default_area,
+                    max_dofs_per_node,
+                    legacy,
+                )
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Add element contributions to global matrix
+        for (dof_indices, k_e) in contributions.into_iter().flatten() {
             for (i_local, &i_global) in dof_indices.iter().enumerate() {
                 for (j_local, &j_global) in dof_indices.iter().enumerate() {
                     self.stiffness[(i_global, j_global)] += k_e[(i_local, j_local)];
@@ -186,7 +175,7 @@ impl GlobalSystem {
         bcs: &BoundaryConditions,
         max_dofs_per_node: usize,
     ) -> Result<(), String> {
-        let penalty = 1e10; // Large penalty factor
+        let penalty = crate::sparse_assembly::PENALTY;
 
         for bc in &bcs.displacement_bcs {
             for dof in bc.first_dof..=bc.last_dof {
@@ -249,6 +238,100 @@ impl GlobalSystem {
 
         Ok(lu)
     }
+
+    /// Reactions K·u − F at the prescribed DOFs of `bcs`, without the
+    /// penalty terms; see [`crate::SparseGlobalSystem::reactions`]
+    pub fn reactions(
+        &self,
+        u: &DVector<f64>,
+        bcs: &BoundaryConditions,
+        dofs_per_node: usize,
+    ) -> DVector<f64> {
+        crate::sparse_assembly::penalty_reactions(bcs, dofs_per_node, u, &self.force, |i| {
+            self.stiffness.row(i).dot(&u.transpose())
+        })
+    }
+}
+
+/// Global DOF indices and stiffness matrix of one element
+pub(crate) type ElementContribution = (Vec<usize>, DMatrix<f64>);
+
+/// Global DOF indices and stiffness matrix of one mesh element.
+///
+/// Element types without a stiffness formulation are computed by their
+/// routine in `legacy`, if it has one, and otherwise skipped with a warning
+/// (`None`). Shared by the dense and sparse assembly loops.
+pub(crate) fn element_stiffness(
+    mesh: &Mesh,
+    materials: &MaterialLibrary,
+    elem_id: i32,
+    default_area: f64,
+    max_dofs_per_node: usize,
+    legacy: Option<&LegacyElements>,
+) -> Result<Option<ElementContribution>, String> {
+    use crate::elements::DynamicElement;
+
+    let element = mesh
+        .elements
+        .get(&elem_id)
+        .ok_or(format!("Element {} not found", elem_id))?;
+
+    // Get element nodes
+    let nodes: Vec<_> = element
+        .nodes
+        .iter()
+        .map(|&node_id| {
+            mesh.nodes
+                .get(&node_id)
+                .cloned()
+                .ok_or(format!("Node {} not found", node_id))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // Get material for this element
+    let material = materials
+        .get_element_material(elem_id)
+        .ok_or(format!("No material assigned to element {}", elem_id))?;
+
+    // Create element using factory
+    let dyn_elem = DynamicElement::from_mesh_element(
+        element.element_type,
+        elem_id,
+        element.nodes.clone(),
+        default_area,
+    );
+
+    if dyn_elem.is_none()
+        && let Some(legacy) = legacy.filter(|legacy| legacy.handles(element.element_type))
+    {
+        let k_e = legacy.stiffness_matrix(element.element_type, &nodes, material)?;
+        let dofs_per_node = element.element_type.dofs_per_node();
+        let dof_indices = element
+            .nodes
+            .iter()
+            .flat_map(|&node_id| {
+                let base_dof = (node_id - 1) as usize * max_dofs_per_node;
+                base_dof..base_dof + dofs_per_node
+            })
+            .collect();
+        return Ok(Some((dof_indices, k_e)));
+    }
+    let Some(dyn_elem) = dyn_elem else {
+        tracing::warn!(
+            "Unsupported element type {:?}, skipping element {}",
+            element.element_type,
+            elem_id
+        );
+        return Ok(None);
+    };
+
+    // Compute element stiffness matrix
+    let k_e = dyn_elem.stiffness_matrix(&nodes, material)?;
+
+    // Get global DOF indices with correct stride
+    let dof_indices = dyn_elem.global_dof_indices(&element.nodes, max_dofs_per_node);
+
+    Ok(Some((dof_indices, k_e)))
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 //! Builder for extracting boundary conditions from input decks.
 
 use crate::boundary_conditions::{BoundaryConditions, ConcentratedLoad, DisplacementBC};
+use crate::mesh_builder::field_location;
 use crate::sets::Sets;
 use ccx_inp::{Card, Deck};
 
@@ -59,12 +60,13 @@ impl BCBuilder {
 
     /// Process a *BOUNDARY card
     fn process_boundary_card(&mut self, card: &Card) -> Result<(), String> {
-        for data_line in &card.data_lines {
+        for (line_idx, data_line) in card.data_lines.iter().enumerate() {
             let parts: Vec<&str> = data_line.split(',').collect();
 
             if parts.len() < 2 {
                 self.errors.push(format!(
-                    "Invalid BOUNDARY line (expected at least 2 fields): {}",
+                    "{}: Invalid BOUNDARY line (expected at least 2 fields): {}",
+                    field_location(card, line_idx, 0),
                     data_line
                 ));
                 continue;
@@ -80,7 +82,8 @@ impl BCBuilder {
                         Some(set_nodes) => set_nodes.to_vec(),
                         None => {
                             self.errors.push(format!(
-                                "Unknown node or node set in BOUNDARY: {}",
+                                "{}: Unknown node or node set in BOUNDARY: {}",
+                                field_location(card, line_idx, 0),
                                 node_str
                             ));
                             continue;
@@ -94,7 +97,8 @@ impl BCBuilder {
                 Ok(d) => d,
                 Err(_) => {
                     self.errors.push(format!(
-                        "Invalid first DOF in BOUNDARY: {}",
+                        "{}: Invalid first DOF in BOUNDARY: {}",
+                        field_location(card, line_idx, 1),
                         parts[1].trim()
                     ));
                     continue;
@@ -106,8 +110,11 @@ impl BCBuilder {
                 match parts[2].trim().parse::<usize>() {
                     Ok(d) => d,
                     Err(_) => {
-                        self.errors
-                            .push(format!("Invalid last DOF in BOUNDARY: {}", parts[2].trim()));
+                        self.errors.push(format!(
+                            "{}: Invalid last DOF in BOUNDARY: {}",
+                            field_location(card, line_idx, 2),
+                            parts[2].trim()
+                        ));
                         continue;
                     }
                 }
@@ -120,8 +127,11 @@ impl BCBuilder {
                 match parts[3].trim().parse::<f64>() {
                     Ok(v) => v,
                     Err(_) => {
-                        self.errors
-                            .push(format!("Invalid value in BOUNDARY: {}", parts[3].trim()));
+                        self.errors.push(format!(
+                            "{}: Invalid value in BOUNDARY: {}",
+                            field_location(card, line_idx, 3),
+                            parts[3].trim()
+                        ));
                         continue;
                     }
                 }
@@ -141,12 +151,13 @@ impl BCBuilder {
 
     /// Process a *CLOAD card
     fn process_cload_card(&mut self, card: &Card) -> Result<(), String> {
-        for data_line in &card.data_lines {
+        for (line_idx, data_line) in card.data_lines.iter().enumerate() {
             let parts: Vec<&str> = data_line.split(',').collect();
 
             if parts.len() < 3 {
                 self.errors.push(format!(
-                    "Invalid CLOAD line (expected at least 3 fields): {}",
+                    "{}: Invalid CLOAD line (expected at least 3 fields): {}",
+                    field_location(card, line_idx, 0),
                     data_line
                 ));
                 continue;
@@ -161,8 +172,11 @@ impl BCBuilder {
                     match self.sets.get_nodes(node_str) {
                         Some(set_nodes) => set_nodes.to_vec(),
                         None => {
-                            self.errors
-                                .push(format!("Unknown node or node set in CLOAD: {}", node_str));
+                            self.errors.push(format!(
+                                "{}: Unknown node or node set in CLOAD: {}",
+                                field_location(card, line_idx, 0),
+                                node_str
+                            ));
                             continue;
                         }
                     }
@@ -173,8 +187,11 @@ impl BCBuilder {
             let dof = match parts[1].trim().parse::<usize>() {
                 Ok(d) => d,
                 Err(_) => {
-                    self.errors
-                        .push(format!("Invalid DOF in CLOAD: {}", parts[1].trim()));
+                    self.errors.push(format!(
+                        "{}: Invalid DOF in CLOAD: {}",
+                        field_location(card, line_idx, 1),
+                        parts[1].trim()
+                    ));
                     continue;
                 }
             };
@@ -183,8 +200,11 @@ impl BCBuilder {
             let magnitude = match parts[2].trim().parse::<f64>() {
                 Ok(m) => m,
                 Err(_) => {
-                    self.errors
-                        .push(format!("Invalid magnitude in CLOAD: {}", parts[2].trim()));
+                    self.errors.push(format!(
+                        "{}: Invalid magnitude in CLOAD: {}",
+                        field_location(card, line_idx, 2),
+                        parts[2].trim()
+                    ));
                     continue;
                 }
             };
@@ -389,6 +409,23 @@ LoadSet, 1, 100.0
         assert_eq!(bcs.concentrated_loads.len(), 1);
     }
 
+    #[test]
+    fn reports_exact_field_position_of_malformed_bc_data() {
+        let input = "*NODE\n1, 0.0, 0.0, 0.0\n*BOUNDARY\n1, 1, 3\nNOSET, 1\n*CLOAD\n1,  x, 10.0\n";
+
+        let deck = parse_deck(input);
+        let err = BCBuilder::build_from_deck(&deck).expect_err("bad BC data");
+
+        assert!(
+            err.contains("line 5:1: Unknown node or node set in BOUNDARY: NOSET"),
+            "unexpected error: {err}"
+        );
+        assert!(
+            err.contains("line 7:5: Invalid DOF in CLOAD: x"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn handles_scientific_notation_in_loads() {
         let input = r#"
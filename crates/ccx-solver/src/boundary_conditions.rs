@@ -8,8 +8,10 @@
 
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 /// Degree of freedom index (0-based)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DofId {
     /// Node ID
     pub node: i32,
@@ -24,8 +26,15 @@ impl DofId {
     }
 }
 
+impl std::fmt::Display for DofId {
+    /// Input-deck style label with a 1-based DOF, e.g. "node 42 DOF 3"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node {} DOF {}", self.node, self.dof + 1)
+    }
+}
+
 /// A displacement boundary condition (fixed DOF)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DisplacementBC {
     /// Node ID
     pub node: i32,
@@ -59,7 +68,7 @@ impl DisplacementBC {
 }
 
 /// A concentrated load on a node
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConcentratedLoad {
     /// Node ID
     pub node: i32,
@@ -86,7 +95,7 @@ impl ConcentratedLoad {
 }
 
 /// Type of distributed load
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DistributedLoadType {
     /// Pressure load (normal to surface)
     Pressure,
@@ -99,7 +108,7 @@ pub enum DistributedLoadType {
 }
 
 /// A distributed load on elements
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DistributedLoad {
     /// Element ID or element set name
     pub element: String,
@@ -112,7 +121,7 @@ pub struct DistributedLoad {
 }
 
 /// Complete boundary condition and loading specification
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoundaryConditions {
     /// All displacement boundary conditions
     pub displacement_bcs: Vec<DisplacementBC>,
@@ -0,0 +1,402 @@
+//! Generalized eigenvalue solvers for modal and buckling analyses.
+//!
+//! Solves K φ = λ M φ for the eigenpairs closest to a shift σ, where K is
+//! the stiffness and M the mass (modal) or geometric stiffness (buckling)
+//! matrix. For modal analysis λ = ω², so f = √λ / 2π.
+//!
+//! ## Shift-invert Lanczos
+//!
+//! [`ShiftInvertLanczos`] factorizes A = K - σM once with a sparse
+//! [`crate::LinearSolver`] and runs the Lanczos iteration on the operator
+//! A⁻¹M, which is symmetric in the M inner product. Its eigenvalues
+//! θ = 1 / (λ - σ) are largest in magnitude for the λ closest to σ, so the
+//! wanted modes converge first and only matrix-vector products plus
+//! triangular solves touch the full system. Lanczos vectors are fully reorthogonalized,
+//! which keeps the basis M-orthonormal at the cost of O(m²n) work for a
+//! basis of m vectors.
+//!
+//! With the default Cholesky backend A must be positive definite, i.e. the
+//! shift must lie below the lowest eigenvalue (σ = 0 for a constrained
+//! structure, or a small negative value for a free one).
+
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+
+use crate::linear_solver::LinearSolverKind;
+
+/// Eigenpairs of K φ = λ M φ, sorted by eigenvalue.
+#[derive(Debug, Clone)]
+pub struct EigenResult {
+    pub eigenvalues: Vec<f64>,
+    /// Mass-normalized eigenvectors (φᵀ M φ = 1), one per eigenvalue
+    pub eigenvectors: Vec<DVector<f64>>,
+    /// Size of the Lanczos basis when the iteration stopped
+    pub iterations: usize,
+}
+
+impl EigenResult {
+    /// Natural frequencies in Hz, taking the eigenvalues as ω²
+    pub fn frequencies(&self) -> Vec<f64> {
+        self.eigenvalues
+            .iter()
+            .map(|&lambda| lambda.max(0.0).sqrt() / (2.0 * std::f64::consts::PI))
+            .collect()
+    }
+}
+
+/// A solver for the lowest (or shift-nearest) eigenpairs of (K, M).
+pub trait EigenSolver {
+    /// Short backend name for logs and reports
+    fn name(&self) -> &'static str;
+
+    /// Compute `num_modes` eigenpairs of K φ = λ M φ
+    fn solve(
+        &mut self,
+        stiffness: &CsrMatrix<f64>,
+        mass: &CsrMatrix<f64>,
+        num_modes: usize,
+    ) -> Result<EigenResult, String>;
+}
+
+/// Shift-invert Lanczos eigensolver on a sparse factorization.
+#[derive(Debug, Clone)]
+pub struct ShiftInvertLanczos {
+    /// Shift σ; eigenvalues closest to it are returned
+    pub shift: f64,
+    /// Relative residual ‖K φ - λ M φ‖ bound for a converged pair, estimated
+    /// from the Lanczos recurrence
+    pub tolerance: f64,
+    /// Largest Lanczos basis; `None` grows it up to the problem size
+    pub max_basis: Option<usize>,
+    /// Backend used to factorize K - σM
+    pub linear_solver: LinearSolverKind,
+}
+
+impl Default for ShiftInvertLanczos {
+    fn default() -> Self {
+        Self {
+            shift: 0.0,
+            tolerance: 1e-10,
+            max_basis: None,
+            linear_solver: LinearSolverKind::default(),
+        }
+    }
+}
+
+impl ShiftInvertLanczos {
+    pub fn new(shift: f64) -> Self {
+        Self {
+            shift,
+            ..Default::default()
+        }
+    }
+}
+
+impl EigenSolver for ShiftInvertLanczos {
+    fn name(&self) -> &'static str {
+        "shift-invert-lanczos"
+    }
+
+    fn solve(
+        &mut self,
+        stiffness: &CsrMatrix<f64>,
+        mass: &CsrMatrix<f64>,
+        num_modes: usize,
+    ) -> Result<EigenResult, String> {
+        let n = stiffness.nrows();
+        if stiffness.ncols() != n || mass.nrows() != n || mass.ncols() != n {
+            return Err(format!(
+                "K ({}x{}) and M ({}x{}) must be square and of equal size",
+                n,
+                stiffness.ncols(),
+                mass.nrows(),
+                mass.ncols()
+            ));
+        }
+        if num_modes == 0 || num_modes > n {
+            return Err(format!(
+                "Cannot compute {} modes of a {}-DOF system",
+                num_modes, n
+            ));
+        }
+
+        let shifted = shifted_matrix(stiffness, mass, self.shift)?;
+        let mut solver = self.linear_solver.create();
+        solver.factorize(&shifted)?;
+
+        let max_basis = self.max_basis.unwrap_or(n).clamp(num_modes, n);
+        let mut basis: Vec<DVector<f64>> = Vec::new();
+        let mut alphas: Vec<f64> = Vec::new();
+        let mut betas: Vec<f64> = Vec::new();
+
+        let mut v = start_vector(n, 0);
+        if !m_normalize(&mut v, mass, &basis) {
+            return Err("Mass matrix is zero on the start vector".to_string());
+        }
+
+        loop {
+            // w = A⁻¹ M v_j
+            let mv = mass * &v;
+            let mut w = solver.solve(&mv)?;
+            let alpha = w.dot(&mv);
+            basis.push(v);
+            alphas.push(alpha);
+
+            // Full reorthogonalization in the M inner product also removes
+            // the three-term recurrence components α v_j and β v_(j-1).
+            reorthogonalize(&mut w, mass, &basis);
+            reorthogonalize(&mut w, mass, &basis);
+            let beta = m_norm(&w, mass);
+
+            let m = basis.len();
+            if m >= num_modes {
+                let (theta, s) = tridiagonal_eigen(&alphas, &betas);
+                // Ritz pairs sorted by decreasing |θ|, i.e. closest to the shift
+                let mut order: Vec<usize> = (0..m).collect();
+                order.sort_by(|&a, &b| theta[b].abs().total_cmp(&theta[a].abs()));
+                let wanted = &order[..num_modes];
+
+                let converged = wanted.iter().all(|&i| {
+                    let residual = (beta * s[(m - 1, i)]).abs();
+                    residual <= self.tolerance * theta[i].abs()
+                });
+                if converged || m == max_basis {
+                    if !converged {
+                        return Err(format!(
+                            "Lanczos did not converge {} modes with a basis of {} vectors",
+                            num_modes, m
+                        ));
+                    }
+                    return Ok(ritz_pairs(&basis, &theta, &s, wanted, self.shift, mass, m));
+                }
+            }
+
+            if beta <= 1e-12 * alpha.abs().max(f64::MIN_POSITIVE) {
+                // Invariant subspace found: continue with a fresh direction
+                // outside it; the tridiagonal matrix decouples there.
+                v = start_vector(n, m);
+                if !m_normalize(&mut v, mass, &basis) {
+                    return Err("Lanczos breakdown: no new search direction".to_string());
+                }
+                betas.push(0.0);
+            } else {
+                v = w / beta;
+                betas.push(beta);
+            }
+        }
+    }
+}
+
+/// K - σM as CSR
+fn shifted_matrix(
+    stiffness: &CsrMatrix<f64>,
+    mass: &CsrMatrix<f64>,
+    shift: f64,
+) -> Result<CsrMatrix<f64>, String> {
+    if shift == 0.0 {
+        return Ok(stiffness.clone());
+    }
+    let n = stiffness.nrows();
+    let mut coo = CooMatrix::new(n, n);
+    for (i, j, &value) in stiffness.triplet_iter() {
+        coo.push(i, j, value);
+    }
+    for (i, j, &value) in mass.triplet_iter() {
+        coo.push(i, j, -shift * value);
+    }
+    Ok(CsrMatrix::from(&coo))
+}
+
+/// Deterministic start vector with components in every DOF
+fn start_vector(n: usize, seed: usize) -> DVector<f64> {
+    DVector::from_fn(n, |i, _| {
+        let x = ((i + 1) * 7919 + seed * 104_729) % 1013;
+        1.0 + x as f64 / 1013.0
+    })
+}
+
+fn m_norm(v: &DVector<f64>, mass: &CsrMatrix<f64>) -> f64 {
+    v.dot(&(mass * v)).max(0.0).sqrt()
+}
+
+/// Remove the M-projections of `w` onto the (M-orthonormal) basis
+fn reorthogonalize(w: &mut DVector<f64>, mass: &CsrMatrix<f64>, basis: &[DVector<f64>]) {
+    let mw = mass * &*w;
+    let coefficients: Vec<f64> = basis.iter().map(|q| q.dot(&mw)).collect();
+    for (q, c) in basis.iter().zip(coefficients) {
+        w.axpy(-c, q, 1.0);
+    }
+}
+
+/// M-orthogonalize against the basis and normalize; false if nothing is left
+fn m_normalize(v: &mut DVector<f64>, mass: &CsrMatrix<f64>, basis: &[DVector<f64>]) -> bool {
+    let initial = m_norm(v, mass);
+    reorthogonalize(v, mass, basis);
+    reorthogonalize(v, mass, basis);
+    let norm = m_norm(v, mass);
+    if norm <= 1e-10 * initial || norm == 0.0 {
+        return false;
+    }
+    *v /= norm;
+    true
+}
+
+/// Eigen decomposition of the Lanczos tridiagonal matrix
+fn tridiagonal_eigen(alphas: &[f64], betas: &[f64]) -> (DVector<f64>, DMatrix<f64>) {
+    let m = alphas.len();
+    let mut t = DMatrix::zeros(m, m);
+    for i in 0..m {
+        t[(i, i)] = alphas[i];
+        if i + 1 < m {
+            t[(i, i + 1)] = betas[i];
+            t[(i + 1, i)] = betas[i];
+        }
+    }
+    let eigen = SymmetricEigen::new(t);
+    (eigen.eigenvalues, eigen.eigenvectors)
+}
+
+/// Back-transformed eigenpairs φ = V s, λ = σ + 1/θ, sorted by λ
+fn ritz_pairs(
+    basis: &[DVector<f64>],
+    theta: &DVector<f64>,
+    s: &DMatrix<f64>,
+    wanted: &[usize],
+    shift: f64,
+    mass: &CsrMatrix<f64>,
+    iterations: usize,
+) -> EigenResult {
+    let mut pairs: Vec<(f64, DVector<f64>)> = wanted
+        .iter()
+        .map(|&i| {
+            let mut phi = DVector::zeros(basis[0].len());
+            for (j, q) in basis.iter().enumerate() {
+                phi.axpy(s[(j, i)], q, 1.0);
+            }
+            let norm = m_norm(&phi, mass);
+            if norm > 0.0 {
+                phi /= norm;
+            }
+            (shift + 1.0 / theta[i], phi)
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (eigenvalues, eigenvectors) = pairs.into_iter().unzip();
+    EigenResult {
+        eigenvalues,
+        eigenvectors,
+        iterations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fixed-free chain of n unit masses and springs of stiffness k
+    fn spring_chain(n: usize, k: f64) -> (CsrMatrix<f64>, CsrMatrix<f64>) {
+        let mut stiffness = CooMatrix::new(n, n);
+        let mut mass = CooMatrix::new(n, n);
+        for i in 0..n {
+            stiffness.push(i, i, if i + 1 == n { k } else { 2.0 * k });
+            if i + 1 < n {
+                stiffness.push(i, i + 1, -k);
+                stiffness.push(i + 1, i, -k);
+            }
+            mass.push(i, i, 1.0);
+        }
+        (CsrMatrix::from(&stiffness), CsrMatrix::from(&mass))
+    }
+
+    /// λ_j = 4k sin²((2j - 1)π / (2(2n + 1)))
+    fn chain_eigenvalue(n: usize, k: f64, j: usize) -> f64 {
+        let angle = (2 * j - 1) as f64 * std::f64::consts::PI / (2.0 * (2 * n + 1) as f64);
+        4.0 * k * angle.sin().powi(2)
+    }
+
+    #[test]
+    fn finds_lowest_modes_of_spring_chain() {
+        let (k, m) = spring_chain(200, 1000.0);
+        let mut solver = ShiftInvertLanczos::default();
+        let result = solver.solve(&k, &m, 5).expect("lanczos");
+
+        assert_eq!(result.eigenvalues.len(), 5);
+        assert!(result.iterations < 200);
+        for (j, (&lambda, phi)) in result
+            .eigenvalues
+            .iter()
+            .zip(&result.eigenvectors)
+            .enumerate()
+        {
+            let exact = chain_eigenvalue(200, 1000.0, j + 1);
+            assert!(((lambda - exact) / exact).abs() < 1e-8, "mode {}", j + 1);
+            let residual = (&k * phi - &m * phi * lambda).norm();
+            assert!(
+                residual < 1e-6 * lambda,
+                "mode {} residual {}",
+                j + 1,
+                residual
+            );
+            assert!((phi.dot(&(&m * phi)) - 1.0).abs() < 1e-10);
+        }
+        let f1 = result.frequencies()[0];
+        assert!(
+            (f1 - chain_eigenvalue(200, 1000.0, 1).sqrt() / (2.0 * std::f64::consts::PI)).abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn shift_selects_modes_above_it() {
+        let (k, m) = spring_chain(30, 1.0);
+        let all: Vec<f64> = (1..=30).map(|j| chain_eigenvalue(30, 1.0, j)).collect();
+        let shift = 0.5 * (all[9] + all[10]);
+
+        let mut solver = ShiftInvertLanczos {
+            shift,
+            linear_solver: LinearSolverKind::DenseLu,
+            ..Default::default()
+        };
+        let result = solver.solve(&k, &m, 2).expect("lanczos");
+        assert!((result.eigenvalues[0] - all[9]).abs() < 1e-8);
+        assert!((result.eigenvalues[1] - all[10]).abs() < 1e-8);
+    }
+
+    #[test]
+    fn handles_repeated_eigenvalues() {
+        // Two identical uncoupled chains: every eigenvalue is double.
+        let (single_k, single_m) = spring_chain(10, 1.0);
+        let mut k = CooMatrix::new(20, 20);
+        let mut m = CooMatrix::new(20, 20);
+        for offset in [0, 10] {
+            for (i, j, &v) in single_k.triplet_iter() {
+                k.push(i + offset, j + offset, v);
+            }
+            for (i, j, &v) in single_m.triplet_iter() {
+                m.push(i + offset, j + offset, v);
+            }
+        }
+        let (k, m) = (CsrMatrix::from(&k), CsrMatrix::from(&m));
+
+        let result = ShiftInvertLanczos::default()
+            .solve(&k, &m, 4)
+            .expect("lanczos");
+        let lambda1 = chain_eigenvalue(10, 1.0, 1);
+        let lambda2 = chain_eigenvalue(10, 1.0, 2);
+        for (got, exact) in result
+            .eigenvalues
+            .iter()
+            .zip([lambda1, lambda1, lambda2, lambda2])
+        {
+            assert!((got - exact).abs() < 1e-8, "{got} vs {exact}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let (k, m) = spring_chain(4, 1.0);
+        let mut solver = ShiftInvertLanczos::default();
+        assert!(solver.solve(&k, &m, 0).is_err());
+        assert!(solver.solve(&k, &m, 5).is_err());
+    }
+}
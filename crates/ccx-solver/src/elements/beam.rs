@@ -13,7 +13,7 @@
 /// - "Finite Element Procedures" by K.J. Bathe
 /// - Cook et al., "Concepts and Applications of Finite Element Analysis"
 
-use nalgebra::{DMatrix, SMatrix, Vector3};
+use nalgebra::{DMatrix, DVector, SMatrix, Vector3};
 use crate::elements::Element;
 use crate::materials::Material;
 use crate::mesh::Node;
@@ -295,6 +295,44 @@ impl Beam31 {
 
         Ok(k)
     }
+
+    /// Compute the section forces at both element ends
+    ///
+    /// # Arguments
+    /// * `nodes` - The two element nodes
+    /// * `material` - Element material
+    /// * `displacements` - Global element displacements (12 values, 6 per node)
+    ///
+    /// # Returns
+    /// One row per end node in the local beam axes: axial force N, shear forces
+    /// Vy and Vz, torque Mt and bending moments My and Mz, acting on the cross
+    /// section whose outward normal points along the local x-axis.
+    pub fn section_forces(
+        &self,
+        nodes: &[Node],
+        material: &Material,
+        displacements: &[f64],
+    ) -> Result<[[f64; 6]; 2], String> {
+        if displacements.len() != 12 {
+            return Err(format!(
+                "B31 element expects 12 displacements, got {}",
+                displacements.len()
+            ));
+        }
+        let length = self.length(nodes)?;
+        let k_local = self.local_stiffness(length, material)?;
+        let t = self.transformation_matrix(nodes)?;
+
+        // End forces in local coordinates: f = K_local * T * u
+        let k_local = DMatrix::from_iterator(12, 12, k_local.iter().copied());
+        let f = k_local * (&t * DVector::from_column_slice(displacements));
+
+        // The end force at node 1 acts on the negative cross-section face
+        Ok([
+            std::array::from_fn(|i| -f[i]),
+            std::array::from_fn(|i| f[i + 6]),
+        ])
+    }
 }
 
 impl Element for Beam31 {
@@ -425,6 +463,7 @@ mod tests {
             thermal_expansion: None,
             conductivity: None,
             specific_heat: None,
+            yield_stress: None,
         };
 
         let k = beam.stiffness_matrix(&nodes, &material).unwrap();
@@ -455,4 +494,35 @@ mod tests {
         assert_eq!(t.nrows(), 12);
         assert_eq!(t.ncols(), 12);
     }
+
+    #[test]
+    fn test_cantilever_section_forces() {
+        // Cantilever along x, clamped at node 0, tip load P in y and axial pull N
+        let section = BeamSection::custom(0.01, 2e-6, 1e-6, 1e-6);
+        let beam = Beam31::new(1, 0, 1, section);
+        let nodes = vec![
+            Node::new(0, 0.0, 0.0, 0.0),
+            Node::new(1, 2.0, 0.0, 0.0),
+        ];
+        let mut material = Material::new("Steel".to_string());
+        material.elastic_modulus = Some(200e9);
+        material.poissons_ratio = Some(0.3);
+
+        let (e, l, p, n): (f64, f64, f64, f64) = (200e9, 2.0, 1000.0, 500.0);
+        let izz = 1e-6;
+        let mut u = vec![0.0; 12];
+        u[6] = n * l / (e * 0.01);
+        u[7] = p * l.powi(3) / (3.0 * e * izz);
+        u[11] = p * l.powi(2) / (2.0 * e * izz);
+
+        let [root, tip] = beam.section_forces(&nodes, &material, &u).unwrap();
+        let tol = 1e-6 * p * l;
+        assert!((root[0] - n).abs() < tol && (tip[0] - n).abs() < tol);
+        assert!((root[1] - p).abs() < tol && (tip[1] - p).abs() < tol);
+        assert!((root[5] - p * l).abs() < tol, "{:?}", root);
+        assert!(tip[5].abs() < tol, "{:?}", tip);
+        for c in [2, 3, 4] {
+            assert!(root[c].abs() < tol && tip[c].abs() < tol);
+        }
+    }
 }
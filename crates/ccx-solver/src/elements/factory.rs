@@ -3,7 +3,7 @@
 /// This module provides factory functions to create appropriate element implementations
 /// based on element type, handling the conversion from mesh::Element to typed elements.
 
-use crate::elements::{Beam31, BeamSection, Element, Truss2D};
+use crate::elements::{Beam31, BeamSection, Element, SolidElement, Truss2D};
 use crate::materials::Material;
 use crate::mesh::{ElementType, Node};
 use nalgebra::DMatrix;
@@ -15,9 +15,16 @@ use nalgebra::DMatrix;
 pub enum DynamicElement {
     Truss(Truss2D),
     Beam(Beam31),
+    Solid(SolidElement),
 }
 
 impl DynamicElement {
+    /// Whether [`DynamicElement::from_mesh_element`] implements `elem_type`
+    pub fn is_supported(elem_type: ElementType) -> bool {
+        matches!(elem_type, ElementType::T3D2 | ElementType::B31)
+            || SolidElement::supports(elem_type)
+    }
+
     /// Create a dynamic element from mesh element data
     ///
     /// # Arguments
@@ -46,6 +53,9 @@ impl DynamicElement {
                 let beam = Beam31::new(elem_id, nodes[0], nodes[1], section);
                 Some(DynamicElement::Beam(beam))
             }
+            t if SolidElement::supports(t) => {
+                Some(DynamicElement::Solid(SolidElement::new(elem_id, t, nodes)))
+            }
             _ => None, // Unsupported element type
         }
     }
@@ -59,6 +69,7 @@ impl DynamicElement {
         match self {
             DynamicElement::Truss(truss) => truss.stiffness_matrix(nodes, material),
             DynamicElement::Beam(beam) => beam.stiffness_matrix(nodes, material),
+            DynamicElement::Solid(solid) => solid.stiffness_matrix(nodes, material),
         }
     }
 
@@ -74,6 +85,7 @@ impl DynamicElement {
         let dofs_per_node = match self {
             DynamicElement::Truss(t) => t.dofs_per_node(),
             DynamicElement::Beam(b) => b.dofs_per_node(),
+            DynamicElement::Solid(s) => s.dofs_per_node(),
         };
 
         let mut indices = Vec::new();
@@ -91,6 +103,7 @@ impl DynamicElement {
         match self {
             DynamicElement::Truss(_) => ElementType::T3D2,
             DynamicElement::Beam(_) => ElementType::B31,
+            DynamicElement::Solid(solid) => solid.element_type,
         }
     }
 
@@ -99,6 +112,7 @@ impl DynamicElement {
         match self {
             DynamicElement::Truss(truss) => truss.num_nodes() * truss.dofs_per_node(),
             DynamicElement::Beam(beam) => beam.num_nodes() * beam.dofs_per_node(),
+            DynamicElement::Solid(solid) => solid.num_nodes() * solid.dofs_per_node(),
         }
     }
 }
@@ -138,11 +152,25 @@ mod tests {
     }
 
     #[test]
-    fn test_unsupported_element_type() {
+    fn test_create_solid_element() {
         let elem = DynamicElement::from_mesh_element(
             ElementType::C3D8,
             1,
-            vec![0, 1, 2, 3, 4, 5, 6, 7],
+            vec![1, 2, 3, 4, 5, 6, 7, 8],
+            0.01,
+        );
+
+        let elem = elem.unwrap();
+        assert_eq!(elem.element_type(), ElementType::C3D8);
+        assert_eq!(elem.num_dofs(), 24); // 8 nodes × 3 DOFs
+    }
+
+    #[test]
+    fn test_unsupported_element_type() {
+        let elem = DynamicElement::from_mesh_element(
+            ElementType::C3D6,
+            1,
+            vec![1, 2, 3, 4, 5, 6],
             0.01,
         );
 
@@ -6,10 +6,12 @@ use nalgebra::DMatrix;
 
 pub mod beam;
 pub mod factory;
+pub mod solid;
 pub mod truss;
 
 pub use beam::{Beam31, BeamSection};
 pub use factory::DynamicElement;
+pub use solid::SolidElement;
 pub use truss::Truss2D;
 
 /// Element interface for finite element calculations
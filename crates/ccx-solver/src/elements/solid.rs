@@ -0,0 +1,798 @@
+//! Isoparametric 3D solid elements (C3D4, C3D8, C3D10, C3D20).
+//!
+//! Linear elastic, small-strain formulation with full Gauss integration:
+//!
+//! | Type  | Nodes | Integration points |
+//! |-------|-------|--------------------|
+//! | C3D4  | 4     | 1                  |
+//! | C3D10 | 10    | 4                  |
+//! | C3D8  | 8     | 2×2×2              |
+//! | C3D20 | 20    | 3×3×3              |
+//!
+//! Reduced-integration variants (C3D8R, C3D20R) are read as their fully
+//! integrated counterparts. Node and integration point numbering follow
+//! CalculiX: corner nodes first, then the mid-side nodes, and ξ varies
+//! fastest over the hexahedron integration points.
+//!
+//! ## Stresses
+//!
+//! Stresses and strains use the `.dat` component order
+//! `xx, yy, zz, xy, xz, yz`; strains are tensor (not engineering) shears.
+//! [`SolidElement::extrapolate_to_nodes`] fits a linear (tetrahedra) or
+//! trilinear (hexahedra) field through the integration point values in a
+//! least-squares sense and evaluates it at the nodes.
+//!
+//! ```text
+//! k_e = ∫ Bᵀ D B dV,   σ = D B u_e
+//! ```
+
+use crate::elements::Element;
+use crate::materials::Material;
+use crate::mesh::{ElementType, Node};
+use nalgebra::{DMatrix, Matrix3};
+
+/// Hexahedron corner coordinates in the natural (ξ, η, ζ) system
+const HEX_CORNERS: [[f64; 3]; 8] = [
+    [-1.0, -1.0, -1.0],
+    [1.0, -1.0, -1.0],
+    [1.0, 1.0, -1.0],
+    [-1.0, 1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+    [1.0, -1.0, 1.0],
+    [1.0, 1.0, 1.0],
+    [-1.0, 1.0, 1.0],
+];
+
+/// Corner pairs of the C3D20 mid-side nodes 9-20
+const HEX_EDGES: [[usize; 2]; 12] = [
+    [0, 1],
+    [1, 2],
+    [2, 3],
+    [3, 0],
+    [4, 5],
+    [5, 6],
+    [6, 7],
+    [7, 4],
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+];
+
+/// Corner pairs of the C3D10 mid-side nodes 5-10
+const TET_EDGES: [[usize; 2]; 6] = [[0, 1], [1, 2], [2, 0], [0, 3], [1, 3], [2, 3]];
+
+/// Isoparametric solid element
+#[derive(Debug, Clone)]
+pub struct SolidElement {
+    /// Element ID
+    pub id: i32,
+    /// One of C3D4, C3D8, C3D10, C3D20
+    pub element_type: ElementType,
+    /// Node connectivity in CalculiX order
+    pub nodes: Vec<i32>,
+}
+
+impl SolidElement {
+    /// Create a new solid element
+    pub fn new(id: i32, element_type: ElementType, nodes: Vec<i32>) -> Self {
+        assert!(
+            Self::supports(element_type),
+            "{:?} is not a supported solid element",
+            element_type
+        );
+        assert_eq!(
+            nodes.len(),
+            element_type.num_nodes(),
+            "{:?} element must have {} nodes",
+            element_type,
+            element_type.num_nodes()
+        );
+        Self {
+            id,
+            element_type,
+            nodes,
+        }
+    }
+
+    /// Whether `element_type` is implemented by this element
+    pub fn supports(element_type: ElementType) -> bool {
+        matches!(
+            element_type,
+            ElementType::C3D4 | ElementType::C3D8 | ElementType::C3D10 | ElementType::C3D20
+        )
+    }
+
+    fn is_tetrahedron(&self) -> bool {
+        matches!(self.element_type, ElementType::C3D4 | ElementType::C3D10)
+    }
+
+    /// Integration points in natural coordinates with their weights
+    pub fn integration_points(&self) -> Vec<([f64; 3], f64)> {
+        match self.element_type {
+            ElementType::C3D4 => vec![([0.25, 0.25, 0.25], 1.0 / 6.0)],
+            ElementType::C3D10 => {
+                let a = 0.585_410_196_624_968_5;
+                let b = 0.138_196_601_125_010_5;
+                [[b, b, b], [a, b, b], [b, a, b], [b, b, a]]
+                    .into_iter()
+                    .map(|point| (point, 1.0 / 24.0))
+                    .collect()
+            }
+            ElementType::C3D8 => {
+                let g = 1.0 / 3.0_f64.sqrt();
+                gauss_product(&[(-g, 1.0), (g, 1.0)])
+            }
+            _ => {
+                let g = 0.6_f64.sqrt();
+                gauss_product(&[(-g, 5.0 / 9.0), (0.0, 8.0 / 9.0), (g, 5.0 / 9.0)])
+            }
+        }
+    }
+
+    /// Natural coordinates of the element nodes
+    fn natural_node_coordinates(&self) -> Vec<[f64; 3]> {
+        let (corners, edges): (Vec<[f64; 3]>, &[[usize; 2]]) = match self.element_type {
+            ElementType::C3D4 | ElementType::C3D10 => (
+                vec![
+                    [0.0, 0.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                ],
+                &TET_EDGES,
+            ),
+            _ => (HEX_CORNERS.to_vec(), &HEX_EDGES),
+        };
+        let mut coordinates = corners.clone();
+        if self.nodes.len() > corners.len() {
+            for &[a, b] in edges {
+                coordinates.push(std::array::from_fn(|k| {
+                    0.5 * (corners[a][k] + corners[b][k])
+                }));
+            }
+        }
+        coordinates
+    }
+
+    /// Shape function values N at a natural point
+    pub fn shape_functions(&self, point: [f64; 3]) -> Vec<f64> {
+        match self.element_type {
+            ElementType::C3D4 | ElementType::C3D10 => {
+                let [xi, eta, zeta] = point;
+                let l = [1.0 - xi - eta - zeta, xi, eta, zeta];
+                if self.nodes.len() == 4 {
+                    return l.to_vec();
+                }
+                l.iter()
+                    .map(|li| li * (2.0 * li - 1.0))
+                    .chain(TET_EDGES.iter().map(|&[a, b]| 4.0 * l[a] * l[b]))
+                    .collect()
+            }
+            _ => self
+                .natural_node_coordinates()
+                .iter()
+                .map(|node| {
+                    let f: [f64; 3] = std::array::from_fn(|k| 1.0 + point[k] * node[k]);
+                    match (self.nodes.len(), node.iter().position(|&c| c == 0.0)) {
+                        (8, _) => 0.125 * f[0] * f[1] * f[2],
+                        (_, None) => {
+                            let s: f64 = (0..3).map(|k| point[k] * node[k]).sum();
+                            0.125 * f[0] * f[1] * f[2] * (s - 2.0)
+                        }
+                        (_, Some(m)) => {
+                            let (a, b) = ((m + 1) % 3, (m + 2) % 3);
+                            0.25 * (1.0 - point[m] * point[m]) * f[a] * f[b]
+                        }
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Interpolate nodal values to the integration points
+    pub fn interpolate_from_nodes(&self, values: &[[f64; 6]]) -> Vec<[f64; 6]> {
+        self.integration_points()
+            .into_iter()
+            .map(|(point, _)| {
+                let n = self.shape_functions(point);
+                std::array::from_fn(|c| n.iter().zip(values).map(|(ni, v)| ni * v[c]).sum())
+            })
+            .collect()
+    }
+
+    /// Natural coordinates of the global point `x`, by Newton iteration on
+    /// the isoparametric mapping; `None` if the iteration does not converge
+    pub fn natural_coordinates(
+        &self,
+        nodes: &[Node],
+        x: [f64; 3],
+    ) -> Result<Option<[f64; 3]>, String> {
+        self.check_nodes(nodes)?;
+        let coords: Vec<[f64; 3]> = nodes.iter().map(Node::coords).collect();
+        let size = coords
+            .iter()
+            .map(|c| {
+                (0..3)
+                    .map(|k| (c[k] - coords[0][k]).abs())
+                    .fold(0.0, f64::max)
+            })
+            .fold(0.0, f64::max);
+        let mut point = if self.is_tetrahedron() {
+            [0.25; 3]
+        } else {
+            [0.0; 3]
+        };
+        for _ in 0..25 {
+            let n = self.shape_functions(point);
+            let dn = self.shape_derivatives(point);
+            let mut residual = [0.0; 3];
+            let mut jacobian = Matrix3::<f64>::zeros();
+            for (i, c) in coords.iter().enumerate() {
+                for r in 0..3 {
+                    residual[r] += n[i] * c[r];
+                    for k in 0..3 {
+                        jacobian[(r, k)] += dn[(r, i)] * c[k];
+                    }
+                }
+            }
+            let residual = nalgebra::Vector3::from_fn(|k, _| residual[k] - x[k]);
+            if residual.norm() <= 1e-12 * size.max(f64::MIN_POSITIVE) {
+                return Ok(Some(point));
+            }
+            // x(ξ + δ) ≈ x(ξ) + Jᵀ δ
+            let Some(inverse) = jacobian.transpose().try_inverse() else {
+                return Ok(None);
+            };
+            let step = inverse * residual;
+            for k in 0..3 {
+                point[k] -= step[k];
+            }
+            if point.iter().any(|c| !c.is_finite() || c.abs() > 1e3) {
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether a natural point lies in the element, with `tolerance` in
+    /// natural coordinates
+    pub fn contains_natural(&self, point: [f64; 3], tolerance: f64) -> bool {
+        if self.is_tetrahedron() {
+            point.iter().all(|&c| c >= -tolerance) && point.iter().sum::<f64>() <= 1.0 + tolerance
+        } else {
+            point.iter().all(|c| c.abs() <= 1.0 + tolerance)
+        }
+    }
+
+    /// Global coordinates of the integration points
+    pub fn integration_point_coordinates(&self, nodes: &[Node]) -> Result<Vec<[f64; 3]>, String> {
+        self.check_nodes(nodes)?;
+        Ok(self
+            .integration_points()
+            .into_iter()
+            .map(|(point, _)| {
+                let n = self.shape_functions(point);
+                std::array::from_fn(|k| {
+                    nodes
+                        .iter()
+                        .zip(&n)
+                        .map(|(node, ni)| ni * node.coords()[k])
+                        .sum()
+                })
+            })
+            .collect())
+    }
+
+    /// Volume represented by every integration point (weight × det J)
+    pub fn integration_volumes(&self, nodes: &[Node]) -> Result<Vec<f64>, String> {
+        self.check_nodes(nodes)?;
+        self.integration_points()
+            .into_iter()
+            .map(|(point, weight)| Ok(self.strain_displacement(nodes, point)?.1 * weight))
+            .collect()
+    }
+
+    /// Shape function derivatives dN/dξ (3 × nodes) at a natural point
+    fn shape_derivatives(&self, point: [f64; 3]) -> DMatrix<f64> {
+        let n = self.nodes.len();
+        let mut dn = DMatrix::zeros(3, n);
+        match self.element_type {
+            ElementType::C3D4 | ElementType::C3D10 => {
+                let [xi, eta, zeta] = point;
+                let l = [1.0 - xi - eta - zeta, xi, eta, zeta];
+                let dl = [
+                    [-1.0, -1.0, -1.0],
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                ];
+                let quadratic = n == 10;
+                for i in 0..4 {
+                    let factor = if quadratic { 4.0 * l[i] - 1.0 } else { 1.0 };
+                    for k in 0..3 {
+                        dn[(k, i)] = factor * dl[i][k];
+                    }
+                }
+                if quadratic {
+                    for (m, &[a, b]) in TET_EDGES.iter().enumerate() {
+                        for k in 0..3 {
+                            dn[(k, 4 + m)] = 4.0 * (l[b] * dl[a][k] + l[a] * dl[b][k]);
+                        }
+                    }
+                }
+            }
+            ElementType::C3D8 => {
+                for (i, corner) in HEX_CORNERS.iter().enumerate() {
+                    let f: [f64; 3] = std::array::from_fn(|k| 1.0 + point[k] * corner[k]);
+                    for k in 0..3 {
+                        let (a, b) = ((k + 1) % 3, (k + 2) % 3);
+                        dn[(k, i)] = 0.125 * corner[k] * f[a] * f[b];
+                    }
+                }
+            }
+            _ => {
+                for (i, node) in self.natural_node_coordinates().iter().enumerate() {
+                    let f: [f64; 3] = std::array::from_fn(|k| 1.0 + point[k] * node[k]);
+                    match node.iter().position(|&c| c == 0.0) {
+                        // Corner: N = f0 f1 f2 (ξξi + ηηi + ζζi - 2) / 8
+                        None => {
+                            let s: f64 = (0..3).map(|k| point[k] * node[k]).sum();
+                            for k in 0..3 {
+                                let (a, b) = ((k + 1) % 3, (k + 2) % 3);
+                                dn[(k, i)] =
+                                    0.125 * node[k] * f[a] * f[b] * (s + point[k] * node[k] - 1.0);
+                            }
+                        }
+                        // Mid-side with node[m] = 0: N = (1 - ξm²) fa fb / 4
+                        Some(m) => {
+                            let (a, b) = ((m + 1) % 3, (m + 2) % 3);
+                            let g = 1.0 - point[m] * point[m];
+                            dn[(m, i)] = -0.5 * point[m] * f[a] * f[b];
+                            dn[(a, i)] = 0.25 * g * node[a] * f[b];
+                            dn[(b, i)] = 0.25 * g * f[a] * node[b];
+                        }
+                    }
+                }
+            }
+        }
+        dn
+    }
+
+    /// Jacobian dx/dξ from the shape function derivatives
+    fn jacobian(dn: &DMatrix<f64>, nodes: &[Node]) -> Matrix3<f64> {
+        let mut jacobian = Matrix3::<f64>::zeros();
+        for (i, node) in nodes.iter().enumerate() {
+            let x = node.coords();
+            for r in 0..3 {
+                for c in 0..3 {
+                    jacobian[(r, c)] += dn[(r, i)] * x[c];
+                }
+            }
+        }
+        jacobian
+    }
+
+    /// Jacobian determinant at every integration point; unlike the
+    /// stiffness, this does not fail for inverted elements
+    pub fn jacobian_determinants(&self, nodes: &[Node]) -> Result<Vec<f64>, String> {
+        self.check_nodes(nodes)?;
+        Ok(self
+            .integration_points()
+            .into_iter()
+            .map(|(point, _)| Self::jacobian(&self.shape_derivatives(point), nodes).determinant())
+            .collect())
+    }
+
+    /// Strain-displacement matrix B (6 × 3·nodes) and det J at a natural
+    /// point; B yields engineering shear strains
+    fn strain_displacement(
+        &self,
+        nodes: &[Node],
+        point: [f64; 3],
+    ) -> Result<(DMatrix<f64>, f64), String> {
+        let dn = self.shape_derivatives(point);
+        let jacobian = Self::jacobian(&dn, nodes);
+        let det = jacobian.determinant();
+        if det <= 0.0 || !det.is_finite() {
+            return Err(format!(
+                "Element {} has a non-positive Jacobian determinant ({:.3e})",
+                self.id, det
+            ));
+        }
+        let inverse = jacobian
+            .try_inverse()
+            .ok_or(format!("Element {} has a singular Jacobian", self.id))?;
+
+        let mut b = DMatrix::zeros(6, 3 * nodes.len());
+        for i in 0..nodes.len() {
+            let d: [f64; 3] =
+                std::array::from_fn(|r| (0..3).map(|c| inverse[(r, c)] * dn[(c, i)]).sum::<f64>());
+            let col = 3 * i;
+            b[(0, col)] = d[0];
+            b[(1, col + 1)] = d[1];
+            b[(2, col + 2)] = d[2];
+            b[(3, col)] = d[1];
+            b[(3, col + 1)] = d[0];
+            b[(4, col)] = d[2];
+            b[(4, col + 2)] = d[0];
+            b[(5, col + 1)] = d[2];
+            b[(5, col + 2)] = d[1];
+        }
+        Ok((b, det))
+    }
+
+    fn check_nodes(&self, nodes: &[Node]) -> Result<(), String> {
+        if nodes.len() != self.nodes.len() {
+            return Err(format!(
+                "{:?} element {} requires {} nodes, got {}",
+                self.element_type,
+                self.id,
+                self.nodes.len(),
+                nodes.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Engineering strains B u_e at every integration point
+    fn engineering_strains(
+        &self,
+        nodes: &[Node],
+        displacements: &[f64],
+    ) -> Result<Vec<[f64; 6]>, String> {
+        self.check_nodes(nodes)?;
+        if displacements.len() != 3 * nodes.len() {
+            return Err(format!(
+                "Element {} expects {} displacement values, got {}",
+                self.id,
+                3 * nodes.len(),
+                displacements.len()
+            ));
+        }
+        self.integration_points()
+            .into_iter()
+            .map(|(point, _)| {
+                let (b, _) = self.strain_displacement(nodes, point)?;
+                Ok(std::array::from_fn(|r| {
+                    (0..b.ncols()).map(|c| b[(r, c)] * displacements[c]).sum()
+                }))
+            })
+            .collect()
+    }
+
+    /// Strains at every integration point (tensor shear components)
+    pub fn strains(&self, nodes: &[Node], displacements: &[f64]) -> Result<Vec<[f64; 6]>, String> {
+        Ok(self
+            .engineering_strains(nodes, displacements)?
+            .into_iter()
+            .map(|e| [e[0], e[1], e[2], 0.5 * e[3], 0.5 * e[4], 0.5 * e[5]])
+            .collect())
+    }
+
+    /// Stresses at every integration point
+    pub fn stresses(
+        &self,
+        nodes: &[Node],
+        material: &Material,
+        displacements: &[f64],
+    ) -> Result<Vec<[f64; 6]>, String> {
+        let d = elasticity_matrix(material)?;
+        Ok(self
+            .engineering_strains(nodes, displacements)?
+            .into_iter()
+            .map(|e| std::array::from_fn(|r| (0..6).map(|c| d[(r, c)] * e[c]).sum()))
+            .collect())
+    }
+
+    /// Extrapolate integration point values to the element nodes
+    pub fn extrapolate_to_nodes(&self, values: &[[f64; 6]]) -> Vec<[f64; 6]> {
+        let points = self.integration_points();
+        let basis = |p: [f64; 3]| -> Vec<f64> {
+            let [x, y, z] = p;
+            match (self.is_tetrahedron(), points.len()) {
+                (true, 1) => vec![1.0],
+                (true, _) => vec![1.0, x, y, z],
+                (false, _) => vec![1.0, x, y, z, x * y, y * z, x * z, x * y * z],
+            }
+        };
+        let terms = basis([0.0; 3]).len();
+        let fit = DMatrix::from_fn(points.len(), terms, |i, j| basis(points[i].0)[j]);
+        let pseudo_inverse = fit
+            .pseudo_inverse(1e-12)
+            .expect("extrapolation basis has full rank");
+        self.natural_node_coordinates()
+            .into_iter()
+            .map(|node| {
+                let row = basis(node);
+                let weights: Vec<f64> = (0..points.len())
+                    .map(|ip| (0..terms).map(|t| row[t] * pseudo_inverse[(t, ip)]).sum())
+                    .collect();
+                std::array::from_fn(|c| {
+                    weights
+                        .iter()
+                        .zip(values)
+                        .map(|(w, value)| w * value[c])
+                        .sum()
+                })
+            })
+            .collect()
+    }
+}
+
+impl Element for SolidElement {
+    fn stiffness_matrix(
+        &self,
+        nodes: &[Node],
+        material: &Material,
+    ) -> Result<DMatrix<f64>, String> {
+        self.check_nodes(nodes)?;
+        let d = elasticity_matrix(material)?;
+        let size = 3 * nodes.len();
+        let mut k = DMatrix::zeros(size, size);
+        for (point, weight) in self.integration_points() {
+            let (b, det) = self.strain_displacement(nodes, point)?;
+            k += b.transpose() * &d * b * (det * weight);
+        }
+        Ok(k)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn dofs_per_node(&self) -> usize {
+        3
+    }
+}
+
+/// Isotropic elasticity matrix for engineering strains, `.dat` order
+///
+/// A missing Poisson's ratio is taken as zero, as in CalculiX.
+pub fn elasticity_matrix(material: &Material) -> Result<DMatrix<f64>, String> {
+    let e = material
+        .elastic_modulus
+        .ok_or("Material missing elastic modulus")?;
+    let nu = material.poissons_ratio.unwrap_or(0.0);
+    if !(-1.0..0.5).contains(&nu) {
+        return Err(format!(
+            "Invalid Poisson's ratio {} for a solid element",
+            nu
+        ));
+    }
+    let lambda = e * nu / ((1.0 + nu) * (1.0 - 2.0 * nu));
+    let mu = e / (2.0 * (1.0 + nu));
+    let mut d = DMatrix::zeros(6, 6);
+    for i in 0..3 {
+        for j in 0..3 {
+            d[(i, j)] = lambda;
+        }
+        d[(i, i)] = lambda + 2.0 * mu;
+        d[(i + 3, i + 3)] = mu;
+    }
+    Ok(d)
+}
+
+/// Elastic strain of a stress state (tensor shear components, `.dat` order)
+///
+/// Obtained from the isotropic compliance, so it stays the elastic part of
+/// the strain once thermal or plastic strains are added to the total.
+pub fn elastic_strain(material: &Material, stress: &[f64; 6]) -> Result<[f64; 6], String> {
+    let e = material
+        .elastic_modulus
+        .ok_or("Material missing elastic modulus")?;
+    let nu = material.poissons_ratio.unwrap_or(0.0);
+    let trace = stress[0] + stress[1] + stress[2];
+    Ok(std::array::from_fn(|i| {
+        if i < 3 {
+            ((1.0 + nu) * stress[i] - nu * trace) / e
+        } else {
+            (1.0 + nu) * stress[i] / e
+        }
+    }))
+}
+
+/// Tensor-product Gauss rule on [-1, 1]³, ξ varying fastest
+fn gauss_product(rule: &[(f64, f64)]) -> Vec<([f64; 3], f64)> {
+    let mut points = Vec::with_capacity(rule.len().pow(3));
+    for &(zeta, wz) in rule {
+        for &(eta, wy) in rule {
+            for &(xi, wx) in rule {
+                points.push(([xi, eta, zeta], wx * wy * wz));
+            }
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steel() -> Material {
+        let mut material = Material::new("STEEL".to_string());
+        material.elastic_modulus = Some(210000.0);
+        material.poissons_ratio = Some(0.3);
+        material
+    }
+
+    /// Nodes of a solid of the given type over the box [0,2]×[0,1]×[0,1]
+    fn box_element(element_type: ElementType) -> (SolidElement, Vec<Node>) {
+        let element = SolidElement::new(
+            1,
+            element_type,
+            (1..=element_type.num_nodes() as i32).collect(),
+        );
+        let nodes = element
+            .natural_node_coordinates()
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let [x, y, z] = if element.is_tetrahedron() {
+                    *p
+                } else {
+                    p.map(|c| 0.5 * (c + 1.0))
+                };
+                // Skew slightly so the mapping is not a pure scaling
+                Node::new(i as i32 + 1, 2.0 * x + 0.1 * y, y, z + 0.05 * x)
+            })
+            .collect();
+        (element, nodes)
+    }
+
+    const TYPES: [ElementType; 4] = [
+        ElementType::C3D4,
+        ElementType::C3D10,
+        ElementType::C3D8,
+        ElementType::C3D20,
+    ];
+
+    #[test]
+    fn stiffness_is_symmetric_with_six_rigid_body_modes() {
+        for element_type in TYPES {
+            let (element, nodes) = box_element(element_type);
+            let k = element.stiffness_matrix(&nodes, &steel()).unwrap();
+            assert!((&k - k.transpose()).amax() < 1e-8 * k.amax());
+
+            let eigenvalues = k.symmetric_eigenvalues();
+            let zero = eigenvalues
+                .iter()
+                .filter(|&&l| l.abs() < 1e-8 * k.amax())
+                .count();
+            assert_eq!(zero, 6, "{:?}", element_type);
+        }
+    }
+
+    #[test]
+    fn linear_field_gives_exact_constant_stress() {
+        // u = (1e-3 x, -3e-4 y, 2e-4 x + 1e-4 z)
+        let material = steel();
+        let d = elasticity_matrix(&material).unwrap();
+        let strain = [1e-3, -3e-4, 1e-4, 0.0, 2e-4, 0.0];
+        let expected: Vec<f64> = (0..6)
+            .map(|r| (0..6).map(|c| d[(r, c)] * strain[c]).sum())
+            .collect();
+        for element_type in TYPES {
+            let (element, nodes) = box_element(element_type);
+            let u: Vec<f64> = nodes
+                .iter()
+                .flat_map(|n| [1e-3 * n.x, -3e-4 * n.y, 2e-4 * n.x + 1e-4 * n.z])
+                .collect();
+            let stresses = element.stresses(&nodes, &material, &u).unwrap();
+            assert_eq!(stresses.len(), element.integration_points().len());
+            for stress in element
+                .extrapolate_to_nodes(&stresses)
+                .iter()
+                .chain(&stresses)
+            {
+                for c in 0..6 {
+                    assert!(
+                        (stress[c] - expected[c]).abs() < 1e-6 * expected[0].abs(),
+                        "{:?} {:?}",
+                        element_type,
+                        stress
+                    );
+                }
+            }
+            let strains = element.strains(&nodes, &u).unwrap();
+            assert!((strains[0][4] - 1e-4).abs() < 1e-12);
+            // Without initial strains the elastic strain is the total strain
+            let elastic = elastic_strain(&material, &stresses[0]).unwrap();
+            for c in 0..6 {
+                assert!((elastic[c] - strains[0][c]).abs() < 1e-12, "{:?}", elastic);
+            }
+        }
+    }
+
+    #[test]
+    fn extrapolation_recovers_linear_field_at_nodes() {
+        for element_type in [ElementType::C3D8, ElementType::C3D10, ElementType::C3D20] {
+            let (element, _) = box_element(element_type);
+            let field = |p: [f64; 3]| 1.0 + 2.0 * p[0] - p[1] + 0.5 * p[2];
+            let values: Vec<[f64; 6]> = element
+                .integration_points()
+                .iter()
+                .map(|(p, _)| [field(*p); 6])
+                .collect();
+            let nodal = element.extrapolate_to_nodes(&values);
+            for (value, node) in nodal.iter().zip(element.natural_node_coordinates()) {
+                assert!((value[0] - field(node)).abs() < 1e-10, "{:?}", element_type);
+            }
+        }
+    }
+
+    #[test]
+    fn interpolation_and_volumes_are_exact_for_linear_geometry() {
+        for element_type in TYPES {
+            let (element, nodes) = box_element(element_type);
+            let volume: f64 = element.integration_volumes(&nodes).unwrap().iter().sum();
+            let expected = if element.is_tetrahedron() {
+                2.0 / 6.0
+            } else {
+                2.0
+            };
+            assert!((volume - expected).abs() < 1e-12, "{:?}", element_type);
+
+            let field = |p: [f64; 3]| 1.0 + 2.0 * p[0] - p[1] + 0.5 * p[2];
+            let nodal: Vec<[f64; 6]> = element
+                .natural_node_coordinates()
+                .into_iter()
+                .map(|p| [field(p); 6])
+                .collect();
+            let interpolated = element.interpolate_from_nodes(&nodal);
+            for (value, (point, _)) in interpolated.iter().zip(element.integration_points()) {
+                assert!(
+                    (value[0] - field(point)).abs() < 1e-12,
+                    "{:?}",
+                    element_type
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_inverted_element() {
+        let (element, mut nodes) = box_element(ElementType::C3D8);
+        for node in &mut nodes {
+            node.z = -node.z;
+        }
+        let err = element.stiffness_matrix(&nodes, &steel()).unwrap_err();
+        assert!(err.contains("non-positive Jacobian"), "{}", err);
+        let determinants = element.jacobian_determinants(&nodes).unwrap();
+        assert_eq!(determinants.len(), 8);
+        assert!(determinants.iter().all(|&det| det < 0.0));
+    }
+
+    #[test]
+    fn natural_coordinates_invert_the_mapping() {
+        for element_type in [
+            ElementType::C3D4,
+            ElementType::C3D10,
+            ElementType::C3D8,
+            ElementType::C3D20,
+        ] {
+            let (element, nodes) = box_element(element_type);
+            let natural = if element.is_tetrahedron() {
+                [0.2, 0.3, 0.1]
+            } else {
+                [0.3, -0.6, 0.8]
+            };
+            let n = element.shape_functions(natural);
+            let x: [f64; 3] = std::array::from_fn(|k| {
+                nodes
+                    .iter()
+                    .zip(&n)
+                    .map(|(node, ni)| ni * node.coords()[k])
+                    .sum()
+            });
+            let found = element.natural_coordinates(&nodes, x).unwrap().unwrap();
+            assert!((0..3).all(|k| (found[k] - natural[k]).abs() < 1e-9));
+            assert!(element.contains_natural(found, 1e-9));
+            let outside = element
+                .natural_coordinates(&nodes, [5.0, 0.5, 0.5])
+                .unwrap()
+                .unwrap();
+            assert!(!element.contains_natural(outside, 1e-9));
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Zienkiewicz–Zhu (ZZ) discretization error estimate for solid elements.
+//!
+//! The averaged nodal stresses σ* of a [`StressField`] are smoother and
+//! more accurate than the raw finite element stresses σ_h. Their difference
+//! measured in the energy norm estimates the error of every element:
+//!
+//! ```text
+//! ‖e‖²_K = ∫_K (σ* - σ_h)ᵀ C (σ* - σ_h) dV,   ‖u‖²_K = ∫_K σ_hᵀ C σ_h dV
+//! ```
+//!
+//! with C the isotropic compliance and σ* interpolated with the element
+//! shape functions. The relative error `‖e‖ / √(‖e‖² + ‖u‖²)` is reported in
+//! percent per element and for the whole model; values of a few percent
+//! indicate an adequate mesh, elements well above the global value are the
+//! ones to refine.
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::elements::SolidElement;
+use crate::elements::solid::elastic_strain;
+use crate::materials::{Material, MaterialLibrary};
+use crate::mesh::Mesh;
+use crate::stress_recovery::StressField;
+
+/// ZZ error estimate of one element
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ElementError {
+    pub element_id: i32,
+    /// Energy norm of the stress error ‖e‖_K
+    pub energy_error: f64,
+    /// ‖e‖_K / √(‖e‖²_K + ‖u‖²_K) in percent
+    pub relative_error: f64,
+}
+
+/// ZZ error estimate of a solved model
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ErrorEstimate {
+    /// One entry per solid element of the stress field
+    pub elements: Vec<ElementError>,
+    /// Relative error of the whole model in percent
+    pub global_relative_error: f64,
+}
+
+/// Estimate the discretization error of every element of `field`
+pub fn zz_error_estimate(
+    mesh: &Mesh,
+    materials: &MaterialLibrary,
+    field: &StressField,
+) -> Result<ErrorEstimate, String> {
+    let norms = field
+        .elements
+        .par_iter()
+        .map(|element| {
+            let mesh_element = mesh
+                .elements
+                .get(&element.element_id)
+                .ok_or(format!("Element {} not found", element.element_id))?;
+            let solid = SolidElement::new(
+                element.element_id,
+                mesh_element.element_type,
+                mesh_element.nodes.clone(),
+            );
+            let nodes = mesh_element
+                .nodes
+                .iter()
+                .map(|&node_id| {
+                    mesh.nodes
+                        .get(&node_id)
+                        .cloned()
+                        .ok_or(format!("Node {} not found", node_id))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let material = materials
+                .get_element_material(element.element_id)
+                .ok_or(format!(
+                    "No material assigned to element {}",
+                    element.element_id
+                ))?;
+            let smoothed_nodal = mesh_element
+                .nodes
+                .iter()
+                .map(|node_id| {
+                    field
+                        .nodal
+                        .get(node_id)
+                        .copied()
+                        .ok_or(format!("No nodal stress at node {}", node_id))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let smoothed = solid.interpolate_from_nodes(&smoothed_nodal);
+
+            let mut error = 0.0;
+            let mut solution = 0.0;
+            for ((stress, recovered), volume) in element
+                .integration_points
+                .iter()
+                .zip(&smoothed)
+                .zip(solid.integration_volumes(&nodes)?)
+            {
+                let difference = std::array::from_fn(|c| recovered[c] - stress[c]);
+                error += energy_density(material, &difference)? * volume;
+                solution += energy_density(material, stress)? * volume;
+            }
+            Ok((element.element_id, error, solution))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let total_error: f64 = norms.iter().map(|(_, error, _)| error).sum();
+    let total_solution: f64 = norms.iter().map(|(_, _, solution)| solution).sum();
+    Ok(ErrorEstimate {
+        elements: norms
+            .into_iter()
+            .map(|(element_id, error, solution)| ElementError {
+                element_id,
+                energy_error: error.sqrt(),
+                relative_error: relative_percent(error, solution),
+            })
+            .collect(),
+        global_relative_error: relative_percent(total_error, total_solution),
+    })
+}
+
+/// σᵀ C σ with tensor shear strains, i.e. σ : ε(σ)
+fn energy_density(material: &Material, stress: &[f64; 6]) -> Result<f64, String> {
+    let strain = elastic_strain(material, stress)?;
+    Ok((0..6)
+        .map(|c| if c < 3 { 1.0 } else { 2.0 } * stress[c] * strain[c])
+        .sum())
+}
+
+fn relative_percent(error: f64, solution: f64) -> f64 {
+    if error + solution > 0.0 {
+        100.0 * (error / (error + solution)).sqrt()
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Element, ElementType, Node};
+    use crate::stress_recovery::ElementStresses;
+
+    fn unit_cube(stresses: Vec<[f64; 6]>, nodal: [f64; 6]) -> (Mesh, MaterialLibrary, StressField) {
+        let mut mesh = Mesh::new();
+        for (i, [x, y, z]) in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D8, (1..=8).collect()))
+            .unwrap();
+        let mut library = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(1000.0);
+        steel.poissons_ratio = Some(0.0);
+        library.add_material(steel);
+        library.assign_material(1, "STEEL".to_string());
+        let field = StressField {
+            elements: vec![ElementStresses {
+                element_id: 1,
+                integration_points: stresses,
+                strains: Vec::new(),
+                elastic_strains: Vec::new(),
+            }],
+            nodal: (1..=8).map(|id| (id, nodal)).collect(),
+            ..Default::default()
+        };
+        (mesh, library, field)
+    }
+
+    #[test]
+    fn smooth_field_has_no_error() {
+        let stress = [100.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let (mesh, library, field) = unit_cube(vec![stress; 8], stress);
+        let estimate = zz_error_estimate(&mesh, &library, &field).unwrap();
+        assert_eq!(estimate.elements.len(), 1);
+        assert!(estimate.elements[0].energy_error < 1e-12);
+        assert!(estimate.global_relative_error < 1e-9);
+    }
+
+    #[test]
+    fn measures_the_jump_to_the_smoothed_field() {
+        // σ_h = 100, σ* = 110 in xx, E = 1000, ν = 0, unit volume:
+        // ‖e‖² = 10²/1000, ‖u‖² = 100²/1000
+        let (mesh, library, field) = unit_cube(
+            vec![[100.0, 0.0, 0.0, 0.0, 0.0, 0.0]; 8],
+            [110.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        );
+        let estimate = zz_error_estimate(&mesh, &library, &field).unwrap();
+        let element = estimate.elements[0];
+        assert!((element.energy_error - 0.1_f64.sqrt()).abs() < 1e-10);
+        let expected = 100.0 * (0.1_f64 / 10.1).sqrt();
+        assert!((element.relative_error - expected).abs() < 1e-9);
+        assert_eq!(estimate.global_relative_error, element.relative_error);
+    }
+}
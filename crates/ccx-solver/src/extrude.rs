@@ -0,0 +1,330 @@
+//! Extrusion and revolution of surface meshes into solids.
+//!
+//! Every S3/M3D3 element of a surface mesh becomes a column of C3D6 wedges
+//! and every S4/M3D4 element a column of C3D8 bricks, one per layer. Layers
+//! follow a [`Division`], so they can be biased towards either end. The
+//! corner order of each column is chosen for a positive Jacobian whatever
+//! the orientation of the surface element, so face S1 of an element is
+//! always on the side of the start layer and S2 on the side of the end.
+//! Quadratic solids are obtained with [`Mesh::convert_to_quadratic`] on the
+//! result.
+
+use crate::mesh::{Element, ElementType, Mesh, Node};
+use crate::renumber::Renumbering;
+use crate::reordering::DofOrdering;
+use crate::sets::{ElementSet, NodeSet, Sets, Surface};
+use crate::structured_mesh::{Division, StructuredMesh};
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Sweep every surface node through `place(coords, t)` for the layer
+/// parameters `t` in [0, 1]
+fn sweep(
+    surface: &Mesh,
+    layers: Division,
+    place: impl Fn([f64; 3], f64) -> [f64; 3],
+) -> Result<StructuredMesh, String> {
+    surface.validate()?;
+    let positions = layers.positions(1.0)?;
+    let mut node_ids: Vec<i32> = surface.nodes.keys().copied().collect();
+    node_ids.sort_unstable();
+    let mut element_ids: Vec<i32> = surface.elements.keys().copied().collect();
+    element_ids.sort_unstable();
+    let index: std::collections::HashMap<i32, i32> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(i, &id)| (id, i as i32 + 1))
+        .collect();
+    let per_layer = node_ids.len() as i32;
+    let node_id = |node: i32, layer: usize| index[&node] + per_layer * layer as i32;
+
+    let mut mesh = Mesh::new();
+    for (layer, &t) in positions.iter().enumerate() {
+        for &id in &node_ids {
+            let [x, y, z] = place(surface.nodes[&id].coords(), t);
+            mesh.add_node(Node::new(node_id(id, layer), x, y, z));
+        }
+    }
+
+    let mut bottom = Vec::new();
+    let mut top = Vec::new();
+    let mut next_id = 1;
+    for &id in &element_ids {
+        let element = &surface.elements[&id];
+        let solid = match element.element_type {
+            ElementType::S3 | ElementType::M3D3 => ElementType::C3D6,
+            ElementType::S4 | ElementType::M3D4 => ElementType::C3D8,
+            other => {
+                return Err(format!(
+                    "Element {} of type {:?} cannot be swept, only S3, S4, M3D3 and M3D4",
+                    id, other
+                ));
+            }
+        };
+        // Reverse the corners if the surface normal points against the sweep
+        let x: Vec<[f64; 3]> = element
+            .nodes
+            .iter()
+            .map(|n| surface.nodes[n].coords())
+            .collect();
+        let up = sub(place(x[0], positions[1]), x[0]);
+        let normal = cross(sub(x[1], x[0]), sub(x[x.len() - 1], x[0]));
+        let mut corners = element.nodes.clone();
+        if dot(normal, up) < 0.0 {
+            corners[1..].reverse();
+        }
+        for layer in 0..positions.len() - 1 {
+            let mut nodes: Vec<i32> = corners.iter().map(|&n| node_id(n, layer)).collect();
+            nodes.extend(corners.iter().map(|&n| node_id(n, layer + 1)));
+            mesh.add_element(Element::new(next_id, solid, nodes))?;
+            if layer == 0 {
+                bottom.push((next_id, 1));
+            }
+            if layer == positions.len() - 2 {
+                top.push((next_id, 2));
+            }
+            next_id += 1;
+        }
+    }
+
+    let mut sets = Sets::new();
+    let last = positions.len() - 1;
+    for (name, layer) in [("BOTTOM", 0), ("TOP", last)] {
+        sets.add_node_set(NodeSet {
+            name: name.to_string(),
+            nodes: node_ids.iter().map(|&id| node_id(id, layer)).collect(),
+        });
+    }
+    for (name, faces) in [("SBOTTOM", bottom), ("STOP", top)] {
+        sets.add_surface(Surface {
+            name: name.to_string(),
+            faces,
+        });
+    }
+    sets.add_node_set(NodeSet {
+        name: "NALL".to_string(),
+        nodes: (1..=per_layer * positions.len() as i32).collect(),
+    });
+    sets.add_element_set(ElementSet {
+        name: "EALL".to_string(),
+        elements: (1..next_id).collect(),
+    });
+    Ok(StructuredMesh { mesh, sets })
+}
+
+impl Mesh {
+    /// Extrude a surface mesh along `direction` (the full extrusion vector)
+    /// in `layers` layers
+    ///
+    /// Node sets `BOTTOM` and `TOP` and surfaces `SBOTTOM` and `STOP` hold
+    /// the start and end caps.
+    pub fn extrude(&self, direction: [f64; 3], layers: Division) -> Result<StructuredMesh, String> {
+        if dot(direction, direction) == 0.0 {
+            return Err("Extrusion direction must not be zero".to_string());
+        }
+        sweep(self, layers, |x, t| {
+            [0, 1, 2].map(|d| x[d] + t * direction[d])
+        })
+    }
+
+    /// Revolve a surface mesh by `angle` degrees about the axis through
+    /// `origin` along `axis`, in `layers` layers
+    ///
+    /// Sets are named as for [`Mesh::extrude`]. A full revolution of 360
+    /// degrees closes the seam and has no caps, and its nodes are numbered
+    /// densely. Surface nodes on the axis are rejected, as their elements
+    /// would collapse.
+    pub fn revolve(
+        &self,
+        origin: [f64; 3],
+        axis: [f64; 3],
+        angle: f64,
+        layers: Division,
+    ) -> Result<StructuredMesh, String> {
+        let length = dot(axis, axis).sqrt();
+        if length == 0.0 {
+            return Err("Revolution axis must not be zero".to_string());
+        }
+        if !(angle != 0.0 && angle.abs() <= 360.0) {
+            return Err(format!("Invalid revolution angle {} degrees", angle));
+        }
+        let u = axis.map(|a| a / length);
+        let mut size = 0.0_f64;
+        for node in self.nodes.values() {
+            let r = cross(u, sub(node.coords(), origin));
+            size = size.max(dot(r, r).sqrt());
+        }
+        let tolerance = 1e-9 * size.max(1.0);
+        for node in self.nodes.values() {
+            let r = cross(u, sub(node.coords(), origin));
+            if dot(r, r).sqrt() <= tolerance {
+                return Err(format!("Node {} lies on the revolution axis", node.id));
+            }
+        }
+
+        // Rodrigues' rotation about u
+        let rotate = |x: [f64; 3], t: f64| {
+            let (sin, cos) = (t * angle).to_radians().sin_cos();
+            let p = sub(x, origin);
+            let k = cross(u, p);
+            let along = dot(u, p) * (1.0 - cos);
+            [0, 1, 2].map(|d| origin[d] + p[d] * cos + k[d] * sin + u[d] * along)
+        };
+        let mut solid = sweep(self, layers, rotate)?;
+        if angle.abs() == 360.0 {
+            let merged = solid.mesh.merge_duplicate_nodes(tolerance)?;
+            solid.sets.apply_node_map(&merged);
+            for name in ["BOTTOM", "TOP"] {
+                solid.sets.node_sets.remove(name);
+            }
+            for name in ["SBOTTOM", "STOP"] {
+                solid.sets.surfaces.remove(name);
+            }
+            let renumbering = Renumbering::new(&solid.mesh, DofOrdering::Natural);
+            renumbering.apply_to_mesh(&mut solid.mesh)?;
+            renumbering.apply_to_sets(&mut solid.sets);
+        }
+        Ok(solid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_builder::MeshBuilder;
+
+    /// Unit square in the xy plane of one quad and two triangles, with the
+    /// triangles numbered clockwise when seen from +z
+    fn surface() -> Mesh {
+        let mut mesh = Mesh::new();
+        let coords = [
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [3.0, 0.0, 0.0],
+            [3.0, 1.0, 0.0],
+        ];
+        for (i, [x, y, z]) in coords.into_iter().enumerate() {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        let elements = [
+            (ElementType::S4, vec![1, 2, 3, 4]),
+            (ElementType::S3, vec![2, 3, 5]),
+            (ElementType::M3D3, vec![3, 6, 5]),
+        ];
+        for (i, (element_type, nodes)) in elements.into_iter().enumerate() {
+            mesh.add_element(Element::new(i as i32 + 1, element_type, nodes))
+                .unwrap();
+        }
+        mesh
+    }
+
+    #[test]
+    fn extrusion_builds_positive_columns() {
+        let solid = surface()
+            .extrude([0.0, 0.0, -2.0], Division::graded(3, 2.0))
+            .unwrap();
+        assert_eq!(solid.mesh.nodes.len(), 6 * 4);
+        assert_eq!(solid.mesh.elements.len(), 9);
+        assert_eq!(solid.mesh.elements[&1].element_type, ElementType::C3D8);
+        assert_eq!(solid.mesh.elements[&4].element_type, ElementType::C3D6);
+        let quality = solid.mesh.quality();
+        assert!(quality.elements.values().all(|q| q.jacobian_ratio > 0.99));
+
+        // Graded layers grow towards the end
+        let z: Vec<f64> = [1, 7, 13, 19].map(|n| solid.mesh.nodes[&n].z).to_vec();
+        assert!((z[3] + 2.0).abs() < 1e-12);
+        assert!(z[0] - z[1] < z[1] - z[2]);
+
+        assert_eq!(
+            solid.sets.get_nodes("TOP").unwrap(),
+            &[19, 20, 21, 22, 23, 24]
+        );
+        assert_eq!(solid.sets.get_surface("SBOTTOM").unwrap().len(), 3);
+        assert_eq!(solid.sets.get_surface("STOP").unwrap()[0], (3, 2));
+        // Caps plus 6 outline edges swept through 3 layers
+        assert_eq!(solid.mesh.free_faces().len(), 6 + 6 * 3);
+    }
+
+    #[test]
+    fn rejects_unsupported_input() {
+        let mut mesh = surface();
+        assert!(mesh.extrude([0.0; 3], Division::uniform(1)).is_err());
+        mesh.convert_to_quadratic().unwrap();
+        assert!(mesh.extrude([0.0, 0.0, 1.0], Division::uniform(1)).is_err());
+
+        let on_axis =
+            surface().revolve([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], 90.0, Division::uniform(2));
+        assert!(on_axis.is_err());
+    }
+
+    #[test]
+    fn full_revolution_closes_the_ring() {
+        let ring = surface()
+            .revolve([0.0; 3], [0.0, 1.0, 0.0], 360.0, Division::uniform(12))
+            .unwrap();
+        assert_eq!(ring.mesh.elements.len(), 36);
+        assert_eq!(ring.mesh.nodes.len(), 6 * 12);
+        assert!(ring.sets.get_nodes("TOP").is_none());
+        // Corner Jacobians scale with the radius, from 1 to 2 in the quad column
+        let quality = ring.mesh.quality();
+        assert!(quality.elements.values().all(|q| q.jacobian_ratio > 0.45));
+        // Only the swept outline is free: 6 boundary edges times 12 layers
+        assert_eq!(ring.mesh.free_faces().len(), 6 * 12);
+
+        let quarter = surface()
+            .revolve([0.0; 3], [0.0, 1.0, 0.0], -90.0, Division::uniform(3))
+            .unwrap();
+        let top = quarter.sets.get_nodes("TOP").unwrap();
+        let end = quarter.mesh.nodes[&top[0]].coords();
+        assert!(end[0].abs() < 1e-12 && (end[2] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn revolved_strip_matches_structured_cylinder() {
+        // Strip x in [0, 1], y in [0, 0.5] revolved about x = -1 along y
+        let strip = MeshBuilder::plate(
+            [1.0, 0.5],
+            [Division::uniform(2), Division::uniform(1)],
+            ElementType::S4,
+        )
+        .unwrap();
+        let tube = strip
+            .mesh
+            .revolve(
+                [-1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                360.0,
+                Division::uniform(12),
+            )
+            .unwrap();
+        let divisions = [
+            Division::uniform(2),
+            Division::uniform(12),
+            Division::uniform(1),
+        ];
+        let cylinder =
+            MeshBuilder::cylinder(1.0, 2.0, 0.5, 360.0, divisions, ElementType::C3D8).unwrap();
+        assert_eq!(tube.mesh.nodes.len(), cylinder.mesh.nodes.len());
+        assert_eq!(tube.mesh.elements.len(), cylinder.mesh.elements.len());
+        assert_eq!(
+            tube.mesh.free_faces().len(),
+            cylinder.mesh.free_faces().len()
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Detection of inverted and degenerate elements before assembly.
+//!
+//! An element whose Jacobian determinant is negative at an integration point
+//! is inverted: its node order runs the wrong way or a node was moved through
+//! the opposite face. One with a (near) zero determinant is degenerate, e.g.
+//! collapsed onto a plane. Either gives a singular or meaningless element
+//! stiffness, so [`Mesh::check_jacobians`] evaluates the determinants of all
+//! solids at their integration points, and the lengths of trusses and beams,
+//! before anything is assembled.
+
+use serde::{Deserialize, Serialize};
+
+use crate::elements::SolidElement;
+use crate::mesh::{ElementType, Mesh, Node};
+
+/// Smallest over largest determinant of an element below which it is
+/// reported as degenerate
+const DEGENERATE_RATIO: f64 = 1e-8;
+
+/// What the analysis pipeline does when elements are inverted or degenerate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvertedElementAction {
+    /// Skip assembly and report the elements
+    #[default]
+    Abort,
+    /// Report the elements and assemble anyway
+    Warn,
+}
+
+/// An element with a non-positive or vanishing Jacobian determinant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvertedElement {
+    pub element: i32,
+    pub element_type: ElementType,
+    /// Node IDs in connectivity order
+    pub nodes: Vec<i32>,
+    /// Smallest determinant; half the length for trusses and beams
+    pub min_jacobian: f64,
+    /// Integration points with a non-positive determinant
+    pub inverted_points: usize,
+    /// Integration points evaluated
+    pub points: usize,
+}
+
+impl InvertedElement {
+    /// Whether the element is turned inside out rather than collapsed
+    pub fn is_inverted(&self) -> bool {
+        self.min_jacobian < 0.0
+    }
+
+    /// One-line description with the node IDs
+    pub fn describe(&self) -> String {
+        let nodes: Vec<String> = self.nodes.iter().map(i32::to_string).collect();
+        format!(
+            "element {} ({:?}) is {}: min det J {:.3e} at {} of {} integration points, nodes {}",
+            self.element,
+            self.element_type,
+            if self.is_inverted() {
+                "inverted"
+            } else {
+                "degenerate"
+            },
+            self.min_jacobian,
+            self.inverted_points,
+            self.points,
+            nodes.join(", ")
+        )
+    }
+}
+
+impl Mesh {
+    /// Inverted and degenerate solid, truss and beam elements in ID order
+    ///
+    /// Shells, membranes and elements with missing nodes are not checked.
+    pub fn check_jacobians(&self) -> Vec<InvertedElement> {
+        let mut ids: Vec<i32> = self.elements.keys().copied().collect();
+        ids.sort_unstable();
+        let mut found = Vec::new();
+        for id in ids {
+            let element = &self.elements[&id];
+            let element_type = element.element_type;
+            if element.nodes.len() != element_type.num_nodes() {
+                continue;
+            }
+            let Some(nodes) = element
+                .nodes
+                .iter()
+                .map(|n| self.nodes.get(n).cloned())
+                .collect::<Option<Vec<Node>>>()
+            else {
+                continue;
+            };
+            let determinants = if SolidElement::supports(element_type) {
+                SolidElement::new(id, element_type, element.nodes.clone())
+                    .jacobian_determinants(&nodes)
+                    .unwrap_or_default()
+            } else if matches!(
+                element_type,
+                ElementType::T3D2 | ElementType::B31 | ElementType::B32
+            ) {
+                let (a, b) = (nodes[0].coords(), nodes[1].coords());
+                let length = (0..3).map(|d| (b[d] - a[d]).powi(2)).sum::<f64>().sqrt();
+                vec![0.5 * length]
+            } else {
+                continue;
+            };
+            let largest = determinants.iter().fold(0.0_f64, |m, d| m.max(d.abs()));
+            let min_jacobian = determinants.iter().copied().fold(f64::INFINITY, f64::min);
+            if min_jacobian <= DEGENERATE_RATIO * largest {
+                found.push(InvertedElement {
+                    element: id,
+                    element_type,
+                    nodes: element.nodes.clone(),
+                    min_jacobian,
+                    inverted_points: determinants.iter().filter(|d| **d <= 0.0).count(),
+                    points: determinants.len(),
+                });
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Element;
+    use crate::mesh_builder::MeshBuilder;
+    use crate::structured_mesh::Division;
+
+    fn brick(element_type: ElementType) -> Mesh {
+        let divisions = [
+            Division::uniform(2),
+            Division::uniform(1),
+            Division::uniform(1),
+        ];
+        MeshBuilder::brick([2.0, 1.0, 1.0], divisions, element_type)
+            .unwrap()
+            .mesh
+    }
+
+    #[test]
+    fn valid_meshes_pass() {
+        for element_type in [ElementType::C3D8, ElementType::C3D20] {
+            assert!(brick(element_type).check_jacobians().is_empty());
+        }
+    }
+
+    #[test]
+    fn reports_inverted_elements_with_nodes() {
+        let mut mesh = brick(ElementType::C3D8);
+        // Swap the bottom and top faces of element 2
+        let element = mesh.elements.get_mut(&2).unwrap();
+        let nodes = element.nodes.clone();
+        element.nodes = [&nodes[4..], &nodes[..4]].concat();
+
+        let found = mesh.check_jacobians();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].element, 2);
+        assert!(found[0].is_inverted());
+        assert_eq!((found[0].inverted_points, found[0].points), (8, 8));
+        assert_eq!(found[0].nodes, mesh.elements[&2].nodes);
+        assert!(
+            found[0]
+                .describe()
+                .starts_with("element 2 (C3D8) is inverted")
+        );
+    }
+
+    #[test]
+    fn reports_degenerate_elements() {
+        let mut mesh = brick(ElementType::C3D8);
+        // Flatten element 1 onto z = 0
+        let top: Vec<i32> = mesh.elements[&1].nodes[4..].to_vec();
+        for id in top {
+            mesh.nodes.get_mut(&id).unwrap().z = 0.0;
+        }
+        mesh.add_node(Node::new(100, 5.0, 0.0, 0.0));
+        mesh.add_element(Element::new(100, ElementType::T3D2, vec![100, 100]))
+            .unwrap();
+
+        let found = mesh.check_jacobians();
+        let ids: Vec<i32> = found.iter().map(|e| e.element).collect();
+        // Element 2 shares the flattened face and is only distorted
+        assert_eq!(ids, vec![1, 100]);
+        assert!(found.iter().all(|e| !e.is_inverted()));
+        assert!(found[1].describe().contains("degenerate"));
+    }
+}
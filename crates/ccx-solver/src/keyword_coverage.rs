@@ -0,0 +1,483 @@
+//! Keyword-level migration coverage of input decks.
+//!
+//! [`KEYWORD_SUPPORT`] maps each `.inp` keyword to the Rust modules that
+//! implement it and the legacy `ccx_2.23` units that read it, with how much
+//! of the legacy behaviour the port covers. [`keyword_coverage`] applies the
+//! map to the cards of a deck, so a user can see which parts of a model the
+//! Rust pipeline honours before trusting its results.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ccx_inp::Deck;
+
+/// How much of a keyword the Rust pipeline implements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Support {
+    /// Read and applied like the legacy solver
+    Full,
+    /// Read, but only some parameters or analysis paths are honoured
+    Partial,
+    /// Ignored by the Rust pipeline
+    Unimplemented,
+}
+
+impl Support {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Support::Full => "full",
+            Support::Partial => "partial",
+            Support::Unimplemented => "unimplemented",
+        }
+    }
+}
+
+impl fmt::Display for Support {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Implementation status of one keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeywordSupport {
+    /// Keyword as written in decks, without the `*`
+    pub keyword: &'static str,
+    pub support: Support,
+    /// Rust modules that read or apply the keyword
+    pub rust_modules: &'static [&'static str],
+    /// Legacy units, relative to the `ccx_2.23` source tree, that read it
+    pub legacy_units: &'static [&'static str],
+    /// What is missing for partial support
+    pub note: &'static str,
+}
+
+const fn entry(
+    keyword: &'static str,
+    support: Support,
+    rust_modules: &'static [&'static str],
+    legacy_units: &'static [&'static str],
+    note: &'static str,
+) -> KeywordSupport {
+    KeywordSupport {
+        keyword,
+        support,
+        rust_modules,
+        legacy_units,
+        note,
+    }
+}
+
+use Support::{Full, Partial, Unimplemented};
+
+/// Keywords known to the migration, sorted by keyword
+pub const KEYWORD_SUPPORT: &[KeywordSupport] = &[
+    entry("AMPLITUDE", Unimplemented, &[], &["amplitudes.f"], ""),
+    entry(
+        "BEAM SECTION",
+        Partial,
+        &["ccx_solver::mass_properties", "ccx_solver::elements"],
+        &["beamsections.f"],
+        "RECT, CIRC, PIPE and BOX sections only",
+    ),
+    entry(
+        "BOUNDARY",
+        Full,
+        &["ccx_solver::bc_builder"],
+        &["boundarys.f"],
+        "",
+    ),
+    entry(
+        "BUCKLE",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["buckles.f"],
+        "analysis type is detected but not solved",
+    ),
+    entry("CFLUX", Unimplemented, &[], &["cfluxs.f"], ""),
+    entry(
+        "CLOAD",
+        Full,
+        &["ccx_solver::bc_builder"],
+        &["cloads.f"],
+        "",
+    ),
+    entry(
+        "COMPLEX FREQUENCY",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["complexfrequencys.f"],
+        "analysis type is detected but not solved",
+    ),
+    entry(
+        "CONDUCTIVITY",
+        Partial,
+        &["ccx_solver::materials"],
+        &["conductivitys.f"],
+        "isotropic value of the first data line only; no heat transfer solve",
+    ),
+    entry("CONTACT PAIR", Unimplemented, &[], &["contactpairs.f"], ""),
+    entry("DAMPING", Unimplemented, &[], &["dampings.f"], ""),
+    entry(
+        "DENSITY",
+        Full,
+        &["ccx_solver::materials", "ccx_solver::mass_properties"],
+        &["densitys.f"],
+        "",
+    ),
+    entry(
+        "DLOAD",
+        Partial,
+        &["ccx_solver::units"],
+        &["dloads.f"],
+        "GRAV magnitudes are checked, but distributed loads are not applied",
+    ),
+    entry("DSLOAD", Unimplemented, &[], &["dsloads.f"], ""),
+    entry(
+        "DYNAMIC",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["dynamics.f"],
+        "analysis type is detected but not solved",
+    ),
+    entry(
+        "EL FILE",
+        Partial,
+        &["ccx_io::output"],
+        &["elfiles.f"],
+        "stresses are always written; requested variables are ignored",
+    ),
+    entry(
+        "EL PRINT",
+        Partial,
+        &["ccx_io::history"],
+        &["elprints.f"],
+        "variables S, E, ME and SF with ELSET and FREQUENCY; TOTALS and GLOBAL are ignored",
+    ),
+    entry(
+        "ELASTIC",
+        Partial,
+        &["ccx_solver::materials"],
+        &["elastics.f"],
+        "isotropic constants of the first data line only",
+    ),
+    entry(
+        "ELEMENT",
+        Full,
+        &["ccx_solver::mesh_builder", "ccx_solver::sets"],
+        &["elements.f"],
+        "",
+    ),
+    entry("ELSET", Full, &["ccx_solver::sets"], &["noelsets.f"], ""),
+    entry("END STEP", Full, &["ccx_inp"], &["calinput.f"], ""),
+    entry("EQUATION", Unimplemented, &[], &["equations.f"], ""),
+    entry(
+        "EXPANSION",
+        Partial,
+        &["ccx_solver::materials"],
+        &["expansions.f"],
+        "isotropic value of the first data line only; no thermal loads",
+    ),
+    entry("FILM", Unimplemented, &[], &["films.f"], ""),
+    entry(
+        "FREQUENCY",
+        Full,
+        &["ccx_solver::modal", "ccx_solver::eigen_solver"],
+        &["frequencys.f"],
+        "",
+    ),
+    entry("HEADING", Full, &["ccx_inp"], &["headings.f"], ""),
+    entry(
+        "HEAT TRANSFER",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["heattransfers.f"],
+        "analysis type is detected but not solved",
+    ),
+    entry("HYPERELASTIC", Unimplemented, &[], &["hyperelastics.f"], ""),
+    entry("INCLUDE", Full, &["ccx_inp"], &["includefilename.f"], ""),
+    entry(
+        "INITIAL CONDITIONS",
+        Unimplemented,
+        &[],
+        &["initialconditionss.f"],
+        "",
+    ),
+    entry(
+        "MATERIAL",
+        Full,
+        &["ccx_solver::materials"],
+        &["materials.f"],
+        "",
+    ),
+    entry(
+        "MEMBRANE SECTION",
+        Partial,
+        &["ccx_solver::mass_properties"],
+        &["membranesections.f"],
+        "thickness feeds mass properties only",
+    ),
+    entry(
+        "MODAL DYNAMIC",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["modaldynamics.f"],
+        "analysis type is detected but not solved",
+    ),
+    entry(
+        "NODE",
+        Full,
+        &["ccx_solver::mesh_builder", "ccx_solver::sets"],
+        &["nodes.f"],
+        "",
+    ),
+    entry(
+        "NODE FILE",
+        Partial,
+        &["ccx_io::output"],
+        &["nodefiles.f"],
+        "displacements are always written; requested variables are ignored",
+    ),
+    entry(
+        "NODE PRINT",
+        Partial,
+        &["ccx_io::history"],
+        &["nodeprints.f"],
+        "variables U and RF with NSET, FREQUENCY and TOTALS; GLOBAL is ignored",
+    ),
+    entry("NSET", Full, &["ccx_solver::sets"], &["noelsets.f"], ""),
+    entry("ORIENTATION", Unimplemented, &[], &["orientations.f"], ""),
+    entry("PARAMETER", Full, &["ccx_inp"], &[], ""),
+    entry(
+        "PLASTIC",
+        Partial,
+        &["ccx_solver::materials"],
+        &["plastics.f"],
+        "hardening curve is read, but solves stay linear elastic",
+    ),
+    entry("RADIATE", Unimplemented, &[], &["radiates.f"], ""),
+    entry(
+        "SHELL SECTION",
+        Partial,
+        &["ccx_solver::mass_properties"],
+        &["shellsections.f"],
+        "thickness feeds mass properties only",
+    ),
+    entry(
+        "SOLID SECTION",
+        Full,
+        &["ccx_solver::mass_properties"],
+        &["solidsections.f"],
+        "",
+    ),
+    entry(
+        "SPECIFIC HEAT",
+        Partial,
+        &["ccx_solver::materials"],
+        &["specificheats.f"],
+        "value of the first data line only; no heat transfer solve",
+    ),
+    entry(
+        "STATIC",
+        Full,
+        &["ccx_solver::analysis", "ccx_solver::linear_solver"],
+        &["statics.f"],
+        "",
+    ),
+    entry(
+        "STEADY STATE DYNAMICS",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["steadystatedynamicss.f"],
+        "analysis type is detected but not solved",
+    ),
+    entry(
+        "STEP",
+        Partial,
+        &["ccx_model", "ccx_solver::analysis"],
+        &["steps.f"],
+        "steps are not solved in sequence",
+    ),
+    entry("SURFACE", Unimplemented, &[], &["surfaces.f"], ""),
+    entry("TEMPERATURE", Unimplemented, &[], &["temperatures.f"], ""),
+    entry("TIE", Unimplemented, &[], &["ties.f"], ""),
+    entry(
+        "UNCOUPLED TEMPERATURE-DISPLACEMENT",
+        Partial,
+        &["ccx_solver::analysis"],
+        &["uncouptempdisps.f"],
+        "analysis type is detected but not solved",
+    ),
+];
+
+fn normalized(keyword: &str) -> String {
+    keyword
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_')
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Implementation status of `keyword`, written with or without spaces and
+/// in any case; `None` for keywords the migration does not know
+pub fn keyword_support(keyword: &str) -> Option<&'static KeywordSupport> {
+    let keyword = normalized(keyword.trim_start_matches('*'));
+    KEYWORD_SUPPORT
+        .iter()
+        .find(|entry| normalized(entry.keyword) == keyword)
+}
+
+/// Support of one card of a deck
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardCoverage {
+    /// Keyword as written in the deck
+    pub keyword: String,
+    pub line: usize,
+    pub support: Support,
+    /// Map entry of the keyword; `None` for unknown keywords, which count as
+    /// unimplemented
+    pub entry: Option<&'static KeywordSupport>,
+}
+
+/// Support of all cards of a deck, in deck order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeywordCoverage {
+    pub cards: Vec<CardCoverage>,
+}
+
+/// Support of the cards of `deck`
+pub fn keyword_coverage(deck: &Deck) -> KeywordCoverage {
+    let cards = deck
+        .cards
+        .iter()
+        .map(|card| {
+            let entry = keyword_support(&card.keyword);
+            CardCoverage {
+                keyword: card.keyword.clone(),
+                line: card.line_start,
+                support: entry.map_or(Unimplemented, |entry| entry.support),
+                entry,
+            }
+        })
+        .collect();
+    KeywordCoverage { cards }
+}
+
+impl KeywordCoverage {
+    /// Number of cards per support level
+    pub fn counts(&self) -> BTreeMap<Support, usize> {
+        let mut counts = BTreeMap::new();
+        for card in &self.cards {
+            *counts.entry(card.support).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Cards of one support level
+    pub fn cards_with(&self, support: Support) -> impl Iterator<Item = &CardCoverage> {
+        self.cards
+            .iter()
+            .filter(move |card| card.support == support)
+    }
+
+    /// Whether every card is fully supported
+    pub fn is_complete(&self) -> bool {
+        self.cards.iter().all(|card| card.support == Full)
+    }
+
+    /// Format the report: the counts, then the partially supported and
+    /// unimplemented cards with their lines
+    pub fn format(&self) -> String {
+        let counts = self.counts();
+        let count = |support| counts.get(&support).copied().unwrap_or(0);
+        let mut lines = vec![format!(
+            "Keyword coverage: {} cards, {} full, {} partial, {} unimplemented",
+            self.cards.len(),
+            count(Full),
+            count(Partial),
+            count(Unimplemented)
+        )];
+        for support in [Partial, Unimplemented] {
+            for card in self.cards_with(support) {
+                let note = card
+                    .entry
+                    .map(|entry| entry.note)
+                    .filter(|note| !note.is_empty())
+                    .unwrap_or(match card.entry {
+                        Some(_) => "not ported",
+                        None => "unknown keyword",
+                    });
+                lines.push(format!(
+                    "  line {}: *{} [{}] {}",
+                    card.line, card.keyword, support, note
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_is_sorted_and_looks_up_keywords_in_any_spelling() {
+        for pair in KEYWORD_SUPPORT.windows(2) {
+            assert!(pair[0].keyword < pair[1].keyword, "{}", pair[1].keyword);
+        }
+        for entry in KEYWORD_SUPPORT {
+            assert_eq!(
+                entry.note.is_empty(),
+                entry.support != Partial,
+                "{}",
+                entry.keyword
+            );
+            assert_eq!(
+                entry.rust_modules.is_empty(),
+                entry.support == Unimplemented,
+                "{}",
+                entry.keyword
+            );
+        }
+        let elastic = keyword_support("*elastic").unwrap();
+        assert_eq!(elastic.legacy_units, ["elastics.f"]);
+        assert_eq!(
+            keyword_support("SPECIFICHEAT").unwrap().keyword,
+            "SPECIFIC HEAT"
+        );
+        assert_eq!(keyword_support("Beam_Section").unwrap().support, Partial);
+        assert!(keyword_support("USER ELEMENT").is_none());
+    }
+
+    #[test]
+    fn reports_cards_of_a_deck_by_support() {
+        let deck = Deck::parse_str(
+            "*NODE\n1, 0, 0, 0\n2, 1, 0, 0\n*ELEMENT, TYPE=T3D2\n1, 1, 2\n\
+             *MATERIAL, NAME=STEEL\n*ELASTIC\n210000, 0.3\n*TIE, NAME=T1\nA, B\n\
+             *STEP\n*STATIC\n*CLOAD\n2, 1, 10.\n*USER LOAD\n*END STEP\n",
+        )
+        .unwrap();
+        let coverage = keyword_coverage(&deck);
+        assert_eq!(coverage.cards.len(), 10);
+        assert_eq!(
+            coverage.counts(),
+            BTreeMap::from([(Full, 6), (Partial, 2), (Unimplemented, 2)])
+        );
+        assert!(!coverage.is_complete());
+        let unimplemented: Vec<(&str, usize)> = coverage
+            .cards_with(Unimplemented)
+            .map(|card| (card.keyword.as_str(), card.line))
+            .collect();
+        assert_eq!(unimplemented, [("TIE", 9), ("USER LOAD", 15)]);
+        assert_eq!(
+            coverage.format(),
+            "Keyword coverage: 10 cards, 6 full, 2 partial, 2 unimplemented\n  \
+             line 7: *ELASTIC [partial] isotropic constants of the first data line only\n  \
+             line 11: *STEP [partial] steps are not solved in sequence\n  \
+             line 9: *TIE [unimplemented] not ported\n  \
+             line 15: *USER LOAD [unimplemented] unknown keyword"
+        );
+        assert!(keyword_coverage(&Deck::parse_str("*NODE\n1, 0, 0, 0\n").unwrap()).is_complete());
+    }
+}
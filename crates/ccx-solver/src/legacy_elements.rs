@@ -0,0 +1,292 @@
+//! Element stiffness from legacy routines for element types without a port.
+//!
+//! [`DynamicElement`] builds the stiffness of the element types the port
+//! implements. [`LegacyElements`] routes the others through a
+//! [`CompatRegistry`] to routines of a compiled legacy library, so a mesh
+//! with, e.g., S8 shells can be solved before their formulation is ported.
+//! Each element type has its own Fortran routine, `e_` and the lower-case
+//! type name (`e_s8_` for S8), taking the arguments [`ELEMENT_ROUTINE_ARGS`]:
+//!
+//! - `lakonl`, the `CHARACTER*8` element label
+//! - `nope`, the number of nodes, and `mdof`, the DOFs per node
+//! - `xl(3,nope)`, the node coordinates
+//! - `elcon(2)`, Young's modulus and Poisson's ratio
+//! - `s(mdof*nope,mdof*nope)`, the stiffness the routine fills, DOFs in
+//!   node order
+//!
+//! Calls are serialized, since legacy routines keep state in `SAVE`
+//! variables, and counted per routine by the registry.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use ccx_compat::{
+    ArgKind, CallingConvention, CompatRegistry, ForeignRoutine, LegacyArg, LegacyLibrary, UsageLog,
+};
+use nalgebra::DMatrix;
+
+use crate::elements::DynamicElement;
+use crate::materials::Material;
+use crate::mesh::{ElementType, Node};
+
+/// Argument kinds of a legacy element routine
+pub const ELEMENT_ROUTINE_ARGS: [ArgKind; 6] = [
+    ArgKind::Chars,
+    ArgKind::Int,
+    ArgKind::Int,
+    ArgKind::RealArray,
+    ArgKind::RealArray,
+    ArgKind::RealArray,
+];
+
+const ELEMENT_TYPES: [ElementType; 17] = [
+    ElementType::T3D2,
+    ElementType::C3D8,
+    ElementType::C3D20,
+    ElementType::C3D4,
+    ElementType::C3D10,
+    ElementType::C3D6,
+    ElementType::C3D15,
+    ElementType::S4,
+    ElementType::S8,
+    ElementType::S3,
+    ElementType::S6,
+    ElementType::B31,
+    ElementType::B32,
+    ElementType::M3D4,
+    ElementType::M3D8,
+    ElementType::M3D3,
+    ElementType::M3D6,
+];
+
+/// Linker name of the legacy routine for `element_type`, e.g. `e_s8_`
+pub fn element_routine_symbol(element_type: ElementType) -> String {
+    format!("e_{element_type:?}_").to_ascii_lowercase()
+}
+
+/// Legacy element routines for the element types without a port
+pub struct LegacyElements {
+    registry: Mutex<CompatRegistry>,
+    symbols: HashMap<ElementType, String>,
+}
+
+impl LegacyElements {
+    /// Element routines registered in `registry`
+    ///
+    /// Element types the port implements are left to the port even if the
+    /// registry has a routine for them.
+    pub fn new(registry: CompatRegistry) -> Self {
+        let symbols = ELEMENT_TYPES
+            .into_iter()
+            .filter(|&element_type| !DynamicElement::is_supported(element_type))
+            .map(|element_type| (element_type, element_routine_symbol(element_type)))
+            .filter(|(_, symbol)| registry.spec(symbol).is_some())
+            .collect();
+        Self {
+            registry: Mutex::new(registry),
+            symbols,
+        }
+    }
+
+    /// Element routines of `library`; types whose routine it lacks keep
+    /// being skipped by the assembly
+    ///
+    /// # Safety
+    ///
+    /// Every `e_<type>_` routine of `library` must take the arguments
+    /// [`ELEMENT_ROUTINE_ARGS`] as described in the module documentation.
+    pub unsafe fn bind(library: &LegacyLibrary) -> Self {
+        let mut registry = CompatRegistry::new();
+        for element_type in ELEMENT_TYPES {
+            let symbol = element_routine_symbol(element_type);
+            // SAFETY: the caller vouches for the signature of the library's
+            // element routines
+            let routine = unsafe {
+                ForeignRoutine::bind(
+                    library,
+                    &symbol,
+                    CallingConvention::Fortran,
+                    ELEMENT_ROUTINE_ARGS.to_vec(),
+                )
+            };
+            if let Ok(routine) = routine {
+                registry.register_legacy(routine);
+            }
+        }
+        Self::new(registry)
+    }
+
+    /// Whether a legacy routine computes the stiffness of `element_type`
+    pub fn handles(&self, element_type: ElementType) -> bool {
+        self.symbols.contains_key(&element_type)
+    }
+
+    /// Count the following calls for `job`
+    pub fn set_job(&self, job: &str) {
+        self.registry().set_job(job);
+    }
+
+    /// Calls of the element routines so far, per job and routine
+    pub fn usage(&self) -> UsageLog {
+        self.registry().usage()
+    }
+
+    /// Calls of the element routines so far, resetting the counts
+    pub fn take_usage(&self) -> UsageLog {
+        self.registry().take_usage()
+    }
+
+    fn registry(&self) -> MutexGuard<'_, CompatRegistry> {
+        self.registry.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Stiffness of an `element_type` element with `nodes` and `material`,
+    /// from its legacy routine
+    pub fn stiffness_matrix(
+        &self,
+        element_type: ElementType,
+        nodes: &[Node],
+        material: &Material,
+    ) -> Result<DMatrix<f64>, String> {
+        let symbol = self
+            .symbols
+            .get(&element_type)
+            .ok_or_else(|| format!("No legacy routine for element type {element_type:?}"))?;
+        let (Some(elastic_modulus), Some(poissons_ratio)) =
+            (material.elastic_modulus, material.poissons_ratio)
+        else {
+            return Err(format!(
+                "Material {} needs an elastic modulus and Poisson's ratio for {symbol}",
+                material.name
+            ));
+        };
+
+        let mut lakonl = [b' '; 8];
+        let label = format!("{element_type:?}");
+        lakonl[..label.len()].copy_from_slice(label.as_bytes());
+        let mut nope = nodes.len() as i32;
+        let mut mdof = element_type.dofs_per_node() as i32;
+        let mut xl: Vec<f64> = nodes.iter().flat_map(Node::coords).collect();
+        let mut elcon = [elastic_modulus, poissons_ratio];
+        let size = nodes.len() * element_type.dofs_per_node();
+        let mut s = vec![0.0; size * size];
+
+        // SAFETY: xl holds the 3*nope coordinates, elcon the two elastic
+        // constants and s the (mdof*nope)² entries of the routine interface
+        unsafe {
+            self.registry().call_legacy(
+                symbol,
+                &mut [
+                    LegacyArg::Chars(&mut lakonl),
+                    LegacyArg::Int(&mut nope),
+                    LegacyArg::Int(&mut mdof),
+                    LegacyArg::RealArray(&mut xl),
+                    LegacyArg::RealArray(&mut elcon),
+                    LegacyArg::RealArray(&mut s),
+                ],
+            )
+        }
+        .map_err(|err| err.to_string())?;
+        Ok(DMatrix::from_column_slice(size, size, &s))
+    }
+}
+
+impl fmt::Debug for LegacyElements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut symbols: Vec<&str> = self.symbols.values().map(String::as_str).collect();
+        symbols.sort_unstable();
+        f.debug_struct("LegacyElements")
+            .field("routines", &symbols)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ccx_compat::ForeignFn;
+
+    /// Axial spring along x between the first two nodes, `E` per unit
+    /// length, as a stand-in for a legacy element routine
+    pub(crate) unsafe extern "C" fn e_m3d3_(
+        lakonl: *mut u8,
+        nope: *mut i32,
+        mdof: *mut i32,
+        xl: *mut f64,
+        elcon: *mut f64,
+        s: *mut f64,
+        _lakonl_len: usize,
+    ) {
+        if unsafe { std::slice::from_raw_parts(lakonl, 8) } != b"M3D3    " {
+            return;
+        }
+        let (nope, mdof) = unsafe { (*nope as usize, *mdof as usize) };
+        let xl = unsafe { std::slice::from_raw_parts(xl, 3 * nope) };
+        let n = nope * mdof;
+        let s = unsafe { std::slice::from_raw_parts_mut(s, n * n) };
+        let k = unsafe { *elcon } / (xl[3] - xl[0]).abs();
+        for (i, j, value) in [(0, 0, k), (mdof, mdof, k), (0, mdof, -k), (mdof, 0, -k)] {
+            s[j * n + i] = value;
+        }
+    }
+
+    pub(crate) fn spring_elements() -> LegacyElements {
+        let address = unsafe { ForeignFn::from_ptr(e_m3d3_ as *const _) }.unwrap();
+        let routine = unsafe {
+            ForeignRoutine::new(
+                "e_m3d3",
+                CallingConvention::Fortran,
+                ELEMENT_ROUTINE_ARGS.to_vec(),
+                address,
+            )
+        };
+        let mut registry = CompatRegistry::new();
+        registry.register_legacy(routine);
+        LegacyElements::new(registry)
+    }
+
+    #[test]
+    fn calls_the_routine_of_the_element_type() {
+        let elements = spring_elements();
+        assert!(elements.handles(ElementType::M3D3));
+        assert!(!elements.handles(ElementType::S8));
+        assert_eq!(element_routine_symbol(ElementType::S8), "e_s8_");
+
+        let nodes = [
+            Node::new(1, 0.0, 0.0, 0.0),
+            Node::new(2, 2.0, 0.0, 0.0),
+            Node::new(3, 0.0, 1.0, 0.0),
+        ];
+        let material = Material::elastic("STEEL", 10.0, 0.3);
+        elements.set_job("plate");
+        let k = elements
+            .stiffness_matrix(ElementType::M3D3, &nodes, &material)
+            .unwrap();
+        assert_eq!(k.shape(), (9, 9));
+        assert_eq!((k[(0, 0)], k[(0, 3)], k[(3, 0)]), (5.0, -5.0, -5.0));
+        assert_eq!(elements.take_usage().calls("plate", "e_m3d3_"), 1);
+        assert!(elements.usage().is_empty());
+
+        let err = elements
+            .stiffness_matrix(ElementType::S8, &nodes, &material)
+            .unwrap_err();
+        assert_eq!(err, "No legacy routine for element type S8");
+    }
+
+    #[test]
+    fn leaves_ported_element_types_to_the_port() {
+        let address = unsafe { ForeignFn::from_ptr(e_m3d3_ as *const _) }.unwrap();
+        let routine = unsafe {
+            ForeignRoutine::new(
+                "e_c3d8",
+                CallingConvention::Fortran,
+                ELEMENT_ROUTINE_ARGS.to_vec(),
+                address,
+            )
+        };
+        let mut registry = CompatRegistry::new();
+        registry.register_legacy(routine);
+        assert!(!LegacyElements::new(registry).handles(ElementType::C3D8));
+    }
+}
@@ -9,31 +9,107 @@ pub mod analysis;
 pub mod assembly;
 pub mod bc_builder;
 pub mod boundary_conditions;
+pub mod eigen_solver;
 pub mod elements;
+pub mod error_estimate;
+pub mod extrude;
+#[cfg(feature = "cuda")]
+pub mod gpu;
+pub mod jacobian_check;
+pub mod keyword_coverage;
+pub mod legacy_elements;
+pub mod linear_solver;
+pub mod mass_properties;
 pub mod materials;
 pub mod mesh;
+pub mod mesh_audit;
 pub mod mesh_builder;
+pub mod mesh_merge;
+pub mod mesh_partition;
+pub mod mesh_quality;
+pub mod mesh_transform;
+pub mod mixed_precision;
+pub mod modal;
+pub mod model_builder;
+pub mod operator;
+#[cfg(feature = "pardiso")]
+pub mod pardiso;
 pub mod ported;
+pub mod porting;
 pub mod postprocess;
+pub mod quadratic;
+pub mod reordering;
+pub mod renumber;
 pub mod sets;
+pub mod skin;
 pub mod sparse_assembly;
-
-pub use analysis::{AnalysisConfig, AnalysisPipeline, AnalysisResults, AnalysisType};
+pub mod spatial_index;
+pub mod stress_recovery;
+pub mod structured_mesh;
+#[cfg(feature = "suitesparse")]
+pub mod suitesparse;
+pub mod units;
+
+pub use analysis::{
+    AnalysisConfig, AnalysisPipeline, AnalysisResults, AnalysisType, MatrixStorage, SolveFailure,
+};
 pub use assembly::GlobalSystem;
 pub use bc_builder::BCBuilder;
 pub use boundary_conditions::{BoundaryConditions, ConcentratedLoad, DisplacementBC, DofId};
-pub use elements::{Beam31, BeamSection, Element as ElementTrait, SectionProperties, Truss2D};
+pub use eigen_solver::{EigenResult, EigenSolver, ShiftInvertLanczos};
+pub use elements::{
+    Beam31, BeamSection, Element as ElementTrait, SectionProperties, SolidElement, Truss2D,
+};
+pub use error_estimate::{ElementError, ErrorEstimate, zz_error_estimate};
+#[cfg(feature = "cuda")]
+pub use gpu::GpuConjugateGradientSolver;
+pub use jacobian_check::{InvertedElement, InvertedElementAction};
+pub use keyword_coverage::{
+    CardCoverage, KEYWORD_SUPPORT, KeywordCoverage, KeywordSupport, Support, keyword_coverage,
+    keyword_support,
+};
+pub use legacy_elements::{ELEMENT_ROUTINE_ARGS, LegacyElements, element_routine_symbol};
+pub use linear_solver::{
+    ConjugateGradientSolver, DenseLuSolver, FactorizationCache, LinearSolver, LinearSolverKind,
+    SolveInfo, SparseCholeskySolver, estimate_condition, find_zero_pivots, solve_timed,
+};
+pub use mass_properties::{ElementSection, MassProperties, element_sections};
 pub use materials::{Material, MaterialLibrary, MaterialModel, MaterialStatistics};
 pub use mesh::{Element, ElementType, Mesh, MeshStatistics, Node};
+pub use mesh_audit::MeshAudit;
 pub use mesh_builder::MeshBuilder;
+pub use mesh_partition::{MeshPart, MeshPartition, partition_mesh};
+pub use mesh_quality::{ElementQuality, MeshQuality, MetricSummary, QualityMetric};
+pub use mesh_transform::Transform;
+pub use mixed_precision::{MixedPrecisionSolver, RefinementInfo};
+pub use modal::{ModalResults, ModalSolver, Mode, requested_modes};
+pub use model_builder::{Model, ModelBuilder};
+pub use operator::{ApplyOperator, ElementOperator, MatrixFreeSystem, conjugate_gradient};
+#[cfg(feature = "pardiso")]
+pub use pardiso::{PardisoConfig, PardisoMatrixType, PardisoOutOfCore, PardisoSolver};
 pub use ported::SUPERSEDED_FORTRAN_FILES;
+pub use porting::{
+    PORTED_UNITS, PORTING_MANIFEST, PortingEntry, PortingStatus, porting_entry, porting_status,
+};
 pub use postprocess::{
     compute_effective_strain, compute_mises_stress, compute_statistics, process_integration_points,
     read_dat_file, write_results, IntegrationPointData, IntegrationPointResult, ResultStatistics,
     StrainState, StressState,
 };
-pub use sets::{ElementSet, NodeSet, Sets};
+pub use renumber::{Renumbering, node_bandwidth};
+pub use reordering::{DofOrdering, Permutation};
+pub use sets::{ElementSet, NodeSet, Sets, Surface};
+pub use skin::SkinFace;
 pub use sparse_assembly::SparseGlobalSystem;
+pub use spatial_index::{PointLocation, SpatialIndex};
+pub use stress_recovery::{
+    BeamSectionForces, ElementStresses, NodalAveraging, StressField, recover_section_forces,
+    recover_stresses,
+};
+pub use structured_mesh::{Division, StructuredMesh};
+#[cfg(feature = "suitesparse")]
+pub use suitesparse::{CholmodSolver, UmfpackSolver};
+pub use units::UnitSystem;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LegacyLanguage {
@@ -53,22 +129,12 @@ pub struct LegacySourceUnit {
 
 include!(concat!(env!("OUT_DIR"), "/legacy_source_units.rs"));
 
-pub const PORTED_UNITS: &[&str] = &[
-    "compare.c",
-    "strcmp1.c",
-    "stof.c",
-    "stoi.c",
-    "superseded/bsort.f",
-    "superseded/cident.f",
-    "superseded/insertsortd.f",
-    "superseded/nident.f",
-    "superseded/nident2.f",
-];
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MigrationReport {
     pub total_units: usize,
     pub ported_units: usize,
+    /// Units whose port is in progress according to the porting manifest
+    pub in_progress_units: usize,
     pub superseded_fortran_units: usize,
     pub pending_units: usize,
     pub by_language: BTreeMap<LegacyLanguage, usize>,
@@ -79,18 +145,21 @@ pub fn legacy_units() -> &'static [LegacySourceUnit] {
 }
 
 pub fn is_ported(legacy_rel_path: &str) -> bool {
-    PORTED_UNITS.contains(&legacy_rel_path)
+    porting_status(legacy_rel_path) == PortingStatus::Ported
 }
 
 pub fn migration_report() -> MigrationReport {
     let mut by_language = BTreeMap::<LegacyLanguage, usize>::new();
     let mut ported = 0usize;
+    let mut in_progress = 0usize;
     let mut superseded_fortran = 0usize;
 
     for unit in legacy_units() {
         *by_language.entry(unit.language).or_insert(0) += 1;
-        if is_ported(unit.legacy_rel_path) {
-            ported += 1;
+        match porting_status(unit.legacy_rel_path) {
+            PortingStatus::Ported => ported += 1,
+            PortingStatus::InProgress => in_progress += 1,
+            PortingStatus::Pending | PortingStatus::Superseded => {}
         }
         if ported::is_superseded_fortran(unit.legacy_rel_path) {
             superseded_fortran += 1;
@@ -101,6 +170,7 @@ pub fn migration_report() -> MigrationReport {
     MigrationReport {
         total_units: total,
         ported_units: ported,
+        in_progress_units: in_progress,
         superseded_fortran_units: superseded_fortran,
         pending_units: total.saturating_sub(superseded_fortran),
         by_language,
@@ -0,0 +1,907 @@
+//! Linear solver backends for sparse finite element systems.
+//!
+//! Every backend implements [`LinearSolver`]: `factorize` prepares the
+//! solver for a matrix K (a factorization for direct solvers, a
+//! preconditioner for iterative ones) and `solve` returns u for K u = F.
+//! Splitting the two steps lets several right-hand sides share one
+//! factorization: `resolve` solves a batch of load cases against it, and
+//! [`FactorizationCache`] skips `factorize` altogether when K is unchanged.
+//!
+//! ## Backends
+//!
+//! | Backend | Kind | Matrices |
+//! |---------|------|----------|
+//! | [`SparseCholeskySolver`] | direct LLᵀ (default) | symmetric positive definite |
+//! | [`ConjugateGradientSolver`] | iterative, Jacobi preconditioned | symmetric positive definite |
+//! | [`DenseLuSolver`] | dense LU, O(n²) memory | any non-singular (debugging) |
+//! | [`crate::MixedPrecisionSolver`] | f32 LLᵀ + f64 refinement | symmetric positive definite |
+//! | `UmfpackSolver` | SuiteSparse LU (feature `suitesparse`) | any non-singular |
+//! | `CholmodSolver` | SuiteSparse Cholesky (feature `suitesparse`) | symmetric positive definite |
+//! | `PardisoSolver` | MKL PARDISO, multithreaded (feature `pardiso`) | configurable |
+//! | `GpuConjugateGradientSolver` | CUDA CG, experimental (feature `cuda`) | symmetric positive definite |
+//!
+//! [`SparseCholeskySolver`] keeps the symbolic analysis of the last matrix:
+//! refactorizing a matrix with the same sparsity pattern (e.g. a new
+//! Newton iteration or a changed material) only repeats the numerical phase.
+//! It factorizes a reverse Cuthill–McKee reordering of K, see
+//! [`crate::reordering`].
+
+use std::time::{Duration, Instant};
+
+use nalgebra::{DMatrix, DVector};
+use nalgebra_sparse::factorization::CscCholesky;
+use nalgebra_sparse::pattern::SparsityPattern;
+use nalgebra_sparse::{CscMatrix, CsrMatrix};
+use serde::{Deserialize, Serialize};
+
+use crate::boundary_conditions::DofId;
+use crate::mixed_precision::{MixedPrecisionSolver, RefinementInfo};
+use crate::operator::{ApplyOperator, preconditioned_cg};
+use crate::reordering::{DofOrdering, Permutation};
+
+/// A solver for K u = F with a sparse K.
+pub trait LinearSolver {
+    /// Short backend name for logs and reports
+    fn name(&self) -> &'static str;
+
+    /// Prepare the solver for `matrix`, replacing any previous matrix
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String>;
+
+    /// Solve with the last factorized matrix
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String>;
+
+    /// Solve for further right-hand sides with the current factorization,
+    /// e.g. the load cases of a linear step or modified Newton iterations
+    fn resolve(&self, rhs: &[DVector<f64>]) -> Result<Vec<DVector<f64>>, String> {
+        rhs.iter().map(|f| self.solve(f)).collect()
+    }
+
+    /// Non-zeros of the factors, for direct solvers that know it
+    fn factor_nnz(&self) -> Option<usize> {
+        None
+    }
+
+    /// Peak memory of the last factorization, for solvers that report it
+    fn peak_memory_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Equations whose pivot in the last factorization was numerically
+    /// zero, for solvers that expose their factor
+    fn zero_pivots(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// Refinement statistics of the last solve, for mixed-precision solvers
+    fn refinement(&self) -> Option<RefinementInfo> {
+        None
+    }
+}
+
+/// Pivot-to-diagonal ratio below which a pivot counts as zero
+pub const ZERO_PIVOT_TOLERANCE: f64 = 1e-12;
+
+/// Condition estimate above which [`SolveInfo::warnings`] reports K as
+/// nearly singular
+pub const ILL_CONDITIONED: f64 = 1e15;
+
+/// Statistics of one factorize + solve.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct SolveInfo {
+    /// Backend name, see [`LinearSolver::name`]
+    pub backend: String,
+    pub num_equations: usize,
+    /// Non-zeros of the system matrix
+    pub nnz: usize,
+    pub factor_nnz: Option<usize>,
+    pub peak_memory_bytes: Option<usize>,
+    pub factorization_time: Duration,
+    pub solve_time: Duration,
+    /// 1-norm condition number estimate, for direct solvers
+    pub condition_estimate: Option<f64>,
+    /// Equations with an empty row or a numerically zero pivot
+    pub zero_pivot_dofs: Vec<usize>,
+    /// Node/DOF labels of `zero_pivot_dofs`, filled in by callers that know
+    /// the DOF numbering
+    pub problem_dofs: Vec<DofId>,
+    /// Iterative refinement of a mixed-precision solve
+    pub refinement: Option<RefinementInfo>,
+}
+
+impl SolveInfo {
+    /// One-line summary for logs
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "{}: {} equations, {} nnz",
+            self.backend, self.num_equations, self.nnz
+        );
+        if let Some(factor_nnz) = self.factor_nnz {
+            line.push_str(&format!(", {} factor nnz", factor_nnz));
+        }
+        if let Some(bytes) = self.peak_memory_bytes {
+            line.push_str(&format!(", {:.1} MB peak", bytes as f64 / 1e6));
+        }
+        if let Some(condition) = self.condition_estimate {
+            line.push_str(&format!(", condition ~{:.1e}", condition));
+        }
+        if let Some(refinement) = self.refinement {
+            if refinement.fell_back {
+                line.push_str(", fell back to double precision");
+            } else {
+                line.push_str(&format!(
+                    ", {} refinement steps (backward error {:.1e})",
+                    refinement.iterations, refinement.backward_error
+                ));
+            }
+        }
+        line.push_str(&format!(
+            ", factorization {:.3} s, solve {:.3} s",
+            self.factorization_time.as_secs_f64(),
+            self.solve_time.as_secs_f64()
+        ));
+        line
+    }
+
+    /// Actionable messages about singular or ill-conditioned equations
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = if self.problem_dofs.is_empty() {
+            self.zero_pivot_dofs
+                .iter()
+                .map(|dof| format!("equation {} has a zero pivot", dof))
+                .collect()
+        } else {
+            self.problem_dofs
+                .iter()
+                .map(|dof| format!("{} unconstrained", dof))
+                .collect()
+        };
+        if let Some(condition) = self.condition_estimate.filter(|&c| c > ILL_CONDITIONED) {
+            warnings.push(format!(
+                "stiffness matrix is nearly singular (condition estimate {:.1e})",
+                condition
+            ));
+        }
+        warnings
+    }
+}
+
+/// Factorize `matrix` and solve for `rhs`, timing both phases.
+///
+/// Also looks for zero pivots and, for direct solvers (those reporting a
+/// factor size), estimates the condition number with a few extra solves.
+pub fn solve_timed(
+    solver: &mut dyn LinearSolver,
+    matrix: &CsrMatrix<f64>,
+    rhs: &DVector<f64>,
+) -> Result<(DVector<f64>, SolveInfo), String> {
+    let start = Instant::now();
+    solver.factorize(matrix)?;
+    let factorization_time = start.elapsed();
+
+    let start = Instant::now();
+    let solution = solver.solve(rhs)?;
+    let solve_time = start.elapsed();
+    let refinement = solver.refinement();
+
+    let mut zero_pivot_dofs = find_zero_pivots(matrix);
+    zero_pivot_dofs.extend(solver.zero_pivots());
+    zero_pivot_dofs.sort_unstable();
+    zero_pivot_dofs.dedup();
+    let condition_estimate = match solver.factor_nnz() {
+        Some(_) => estimate_condition(matrix, solver).ok(),
+        None => None,
+    };
+
+    let info = SolveInfo {
+        backend: solver.name().to_string(),
+        num_equations: matrix.nrows(),
+        nnz: matrix.nnz(),
+        factor_nnz: solver.factor_nnz(),
+        peak_memory_bytes: solver.peak_memory_bytes(),
+        factorization_time,
+        solve_time,
+        condition_estimate,
+        zero_pivot_dofs,
+        problem_dofs: Vec::new(),
+        refinement,
+    };
+    Ok((solution, info))
+}
+
+/// Equations that cannot be pivoted on: rows whose diagonal is zero
+/// relative to the largest entry of the row (all-zero rows included).
+///
+/// These are typically DOFs no element stiffens and no boundary condition
+/// holds, such as the lateral DOFs of a truss node.
+pub fn find_zero_pivots(matrix: &CsrMatrix<f64>) -> Vec<usize> {
+    matrix
+        .row_iter()
+        .enumerate()
+        .filter(|(i, row)| {
+            let mut diagonal = 0.0_f64;
+            let mut largest = 0.0_f64;
+            for (&col, &value) in row.col_indices().iter().zip(row.values()) {
+                if col == *i {
+                    diagonal = value.abs();
+                }
+                largest = largest.max(value.abs());
+            }
+            diagonal <= ZERO_PIVOT_TOLERANCE * largest
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Estimate the 1-norm condition number ‖K‖₁ ‖K⁻¹‖₁ of a symmetric matrix
+/// factorized by `solver` (Hager's method, as in LAPACK `xLACON`).
+pub fn estimate_condition(
+    matrix: &CsrMatrix<f64>,
+    solver: &dyn LinearSolver,
+) -> Result<f64, String> {
+    let n = matrix.nrows();
+    if n == 0 {
+        return Ok(0.0);
+    }
+    // K is symmetric, so the largest column sum is the largest row sum.
+    let norm = matrix
+        .row_iter()
+        .map(|row| row.values().iter().map(|v| v.abs()).sum::<f64>())
+        .fold(0.0, f64::max);
+
+    let mut x = DVector::from_element(n, 1.0 / n as f64);
+    let mut inverse_norm = 0.0;
+    for iteration in 0..5 {
+        let y = solver.solve(&x)?;
+        inverse_norm = y.lp_norm(1);
+        let signs = y.map(|v| if v >= 0.0 { 1.0 } else { -1.0 });
+        let z = solver.solve(&signs)?;
+        let j = z.iamax();
+        if iteration > 0 && z[j].abs() <= z.dot(&x) {
+            break;
+        }
+        x = DVector::zeros(n);
+        x[j] = 1.0;
+    }
+    Ok(norm * inverse_norm)
+}
+
+/// Available linear solver backends
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LinearSolverKind {
+    /// Sparse direct Cholesky factorization
+    #[default]
+    SparseCholesky,
+    /// Jacobi-preconditioned Conjugate Gradient
+    ConjugateGradient {
+        tolerance: f64,
+        max_iterations: usize,
+    },
+    /// Dense LU decomposition (debugging only)
+    DenseLu,
+    /// Single precision Cholesky refined to `tolerance` backward error
+    MixedPrecisionCholesky {
+        tolerance: f64,
+        max_refinements: usize,
+    },
+    /// SuiteSparse UMFPACK sparse LU
+    #[cfg(feature = "suitesparse")]
+    Umfpack,
+    /// SuiteSparse CHOLMOD sparse Cholesky
+    #[cfg(feature = "suitesparse")]
+    Cholmod,
+    /// Intel MKL PARDISO
+    #[cfg(feature = "pardiso")]
+    Pardiso(crate::pardiso::PardisoConfig),
+    /// Jacobi-preconditioned Conjugate Gradient on a CUDA device
+    #[cfg(feature = "cuda")]
+    GpuConjugateGradient {
+        tolerance: f64,
+        max_iterations: usize,
+    },
+}
+
+impl LinearSolverKind {
+    /// Create a fresh solver of this kind
+    pub fn create(&self) -> Box<dyn LinearSolver> {
+        match *self {
+            LinearSolverKind::SparseCholesky => Box::new(SparseCholeskySolver::new()),
+            LinearSolverKind::ConjugateGradient {
+                tolerance,
+                max_iterations,
+            } => Box::new(ConjugateGradientSolver::new(tolerance, max_iterations)),
+            LinearSolverKind::DenseLu => Box::new(DenseLuSolver::new()),
+            LinearSolverKind::MixedPrecisionCholesky {
+                tolerance,
+                max_refinements,
+            } => Box::new(MixedPrecisionSolver::new(tolerance, max_refinements)),
+            #[cfg(feature = "suitesparse")]
+            LinearSolverKind::Umfpack => Box::new(crate::suitesparse::UmfpackSolver::new()),
+            #[cfg(feature = "suitesparse")]
+            LinearSolverKind::Cholmod => Box::new(crate::suitesparse::CholmodSolver::new()),
+            #[cfg(feature = "pardiso")]
+            LinearSolverKind::Pardiso(config) => {
+                Box::new(crate::pardiso::PardisoSolver::new(config))
+            }
+            #[cfg(feature = "cuda")]
+            LinearSolverKind::GpuConjugateGradient {
+                tolerance,
+                max_iterations,
+            } => Box::new(crate::gpu::GpuConjugateGradientSolver::new(
+                tolerance,
+                max_iterations,
+            )),
+        }
+    }
+
+    /// Backend names accepted by [`LinearSolverKind::from_name`]
+    pub fn names() -> &'static [&'static str] {
+        &[
+            "cholesky",
+            "cg",
+            "dense",
+            "mixed",
+            #[cfg(feature = "suitesparse")]
+            "umfpack",
+            #[cfg(feature = "suitesparse")]
+            "cholmod",
+            #[cfg(feature = "pardiso")]
+            "pardiso",
+            #[cfg(feature = "pardiso")]
+            "pardiso-ooc",
+            #[cfg(feature = "cuda")]
+            "gpu-cg",
+        ]
+    }
+
+    /// Look up a backend by name, e.g. from a `--backend` option
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "cholesky" => Ok(LinearSolverKind::SparseCholesky),
+            "cg" => Ok(LinearSolverKind::ConjugateGradient {
+                tolerance: 1e-10,
+                max_iterations: 10_000,
+            }),
+            "dense" => Ok(LinearSolverKind::DenseLu),
+            "mixed" => Ok(LinearSolverKind::MixedPrecisionCholesky {
+                tolerance: 1e-14,
+                max_refinements: 30,
+            }),
+            #[cfg(feature = "suitesparse")]
+            "umfpack" => Ok(LinearSolverKind::Umfpack),
+            #[cfg(feature = "suitesparse")]
+            "cholmod" => Ok(LinearSolverKind::Cholmod),
+            #[cfg(not(feature = "suitesparse"))]
+            "umfpack" | "cholmod" => Err(format!(
+                "solver backend {name} requires the `suitesparse` feature"
+            )),
+            #[cfg(feature = "pardiso")]
+            "pardiso" => Ok(LinearSolverKind::Pardiso(Default::default())),
+            #[cfg(feature = "pardiso")]
+            "pardiso-ooc" => Ok(LinearSolverKind::Pardiso(crate::pardiso::PardisoConfig {
+                out_of_core: crate::pardiso::PardisoOutOfCore::Always,
+                ..Default::default()
+            })),
+            #[cfg(not(feature = "pardiso"))]
+            "pardiso" | "pardiso-ooc" => Err(format!(
+                "solver backend {name} requires the `pardiso` feature"
+            )),
+            #[cfg(feature = "cuda")]
+            "gpu-cg" => Ok(LinearSolverKind::GpuConjugateGradient {
+                tolerance: 1e-10,
+                max_iterations: 10_000,
+            }),
+            #[cfg(not(feature = "cuda"))]
+            "gpu-cg" => Err(format!("solver backend {name} requires the `cuda` feature")),
+            _ => Err(format!(
+                "unknown solver backend {name} (available: {})",
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+/// Wraps a backend and skips `factorize` when the matrix is unchanged.
+///
+/// The last matrix is kept and compared exactly (pattern and values), so a
+/// stiffness matrix reassembled from an unchanged model still reuses the
+/// factorization. This costs one extra copy of K.
+pub struct FactorizationCache {
+    inner: Box<dyn LinearSolver>,
+    matrix: Option<CsrMatrix<f64>>,
+    factorizations: usize,
+    reuses: usize,
+}
+
+impl FactorizationCache {
+    pub fn new(inner: Box<dyn LinearSolver>) -> Self {
+        Self {
+            inner,
+            matrix: None,
+            factorizations: 0,
+            reuses: 0,
+        }
+    }
+
+    /// Number of factorizations passed on to the backend
+    pub fn factorizations(&self) -> usize {
+        self.factorizations
+    }
+
+    /// Number of `factorize` calls answered from the cache
+    pub fn reuses(&self) -> usize {
+        self.reuses
+    }
+
+    /// Force the next `factorize` to reach the backend
+    pub fn invalidate(&mut self) {
+        self.matrix = None;
+    }
+}
+
+impl LinearSolver for FactorizationCache {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String> {
+        if self.matrix.as_ref() == Some(matrix) {
+            self.reuses += 1;
+            return Ok(());
+        }
+        self.matrix = None;
+        self.inner.factorize(matrix)?;
+        self.factorizations += 1;
+        self.matrix = Some(matrix.clone());
+        Ok(())
+    }
+
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String> {
+        self.inner.solve(rhs)
+    }
+
+    fn resolve(&self, rhs: &[DVector<f64>]) -> Result<Vec<DVector<f64>>, String> {
+        self.inner.resolve(rhs)
+    }
+
+    fn factor_nnz(&self) -> Option<usize> {
+        self.inner.factor_nnz()
+    }
+
+    fn peak_memory_bytes(&self) -> Option<usize> {
+        self.inner.peak_memory_bytes()
+    }
+
+    fn zero_pivots(&self) -> Vec<usize> {
+        self.inner.zero_pivots()
+    }
+
+    fn refinement(&self) -> Option<RefinementInfo> {
+        self.inner.refinement()
+    }
+}
+
+/// Sparse Cholesky (LLᵀ) solver with symbolic analysis reuse.
+///
+/// The matrix must be symmetric positive definite; only the sparsity
+/// pattern symmetry is relied upon, so an unsymmetric K gives wrong
+/// results rather than an error.
+///
+/// Equations are reordered with [`DofOrdering`] (reverse Cuthill–McKee by
+/// default) before factorization; `solve` takes and returns vectors in the
+/// original DOF numbering.
+#[derive(Debug, Default)]
+pub struct SparseCholeskySolver {
+    ordering: DofOrdering,
+    factor: Option<CscCholesky<f64>>,
+    /// Pattern of the factorized matrix, to detect when reuse is possible
+    pattern: Option<SparsityPattern>,
+    permutation: Option<Permutation>,
+    /// Diagonal of the factorized matrix, the reference for zero pivots
+    diagonal: Vec<f64>,
+    symbolic_factorizations: usize,
+}
+
+impl SparseCholeskySolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Solver using `ordering` instead of reverse Cuthill–McKee
+    pub fn with_ordering(ordering: DofOrdering) -> Self {
+        Self {
+            ordering,
+            ..Self::default()
+        }
+    }
+
+    pub fn ordering(&self) -> DofOrdering {
+        self.ordering
+    }
+
+    /// Number of symbolic analyses performed so far
+    pub fn symbolic_factorizations(&self) -> usize {
+        self.symbolic_factorizations
+    }
+
+    fn reset(&mut self) {
+        self.factor = None;
+        self.pattern = None;
+        self.permutation = None;
+    }
+}
+
+impl LinearSolver for SparseCholeskySolver {
+    fn name(&self) -> &'static str {
+        "sparse-cholesky"
+    }
+
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String> {
+        if matrix.nrows() != matrix.ncols() {
+            return Err(format!(
+                "Cholesky factorization needs a square matrix, got {}x{}",
+                matrix.nrows(),
+                matrix.ncols()
+            ));
+        }
+
+        self.diagonal = matrix
+            .row_iter()
+            .enumerate()
+            .map(|(i, row)| row.get_entry(i).map_or(0.0, |entry| entry.into_value()))
+            .collect();
+        if self.pattern.as_ref() == Some(matrix.pattern())
+            && let (Some(factor), Some(permutation)) =
+                (self.factor.as_mut(), self.permutation.as_ref())
+        {
+            // Same pattern, so the permuted values line up with the old ones.
+            let permuted = permutation.permute_matrix(matrix)?;
+            if let Err(e) = factor.refactor(permuted.values()) {
+                self.reset();
+                return Err(not_positive_definite(e));
+            }
+            return Ok(());
+        }
+
+        let permutation = self.ordering.permutation(matrix.pattern());
+        let permuted = permutation.permute_matrix(matrix)?;
+        // The CSR arrays of a symmetric matrix are also its CSC arrays.
+        let (offsets, indices, values) = permuted.disassemble();
+        let csc =
+            CscMatrix::try_from_csc_data(matrix.nrows(), matrix.ncols(), offsets, indices, values)
+                .map_err(|e| format!("Invalid sparse matrix: {}", e))?;
+        self.symbolic_factorizations += 1;
+        match CscCholesky::factor(&csc) {
+            Ok(factor) => {
+                self.factor = Some(factor);
+                self.pattern = Some(matrix.pattern().clone());
+                self.permutation = Some(permutation);
+                Ok(())
+            }
+            Err(e) => {
+                self.reset();
+                Err(not_positive_definite(e))
+            }
+        }
+    }
+
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String> {
+        let (Some(factor), Some(permutation)) = (self.factor.as_ref(), self.permutation.as_ref())
+        else {
+            return Err("Cholesky solver used before factorize".to_string());
+        };
+        if rhs.len() != factor.l().nrows() {
+            return Err(format!(
+                "Right-hand side has {} entries, matrix has {} rows",
+                rhs.len(),
+                factor.l().nrows()
+            ));
+        }
+        let solution = factor.solve(&permutation.permute_vector(rhs));
+        Ok(permutation.unpermute_vector(&DVector::from_column_slice(solution.as_slice())))
+    }
+
+    fn factor_nnz(&self) -> Option<usize> {
+        self.factor.as_ref().map(|f| f.l().nnz())
+    }
+
+    fn zero_pivots(&self) -> Vec<usize> {
+        let (Some(factor), Some(permutation)) = (self.factor.as_ref(), self.permutation.as_ref())
+        else {
+            return Vec::new();
+        };
+        let l = factor.l();
+        let mut dofs: Vec<usize> = (0..l.ncols())
+            .filter(|&j| {
+                let column = l.col(j);
+                let pivot = match column.row_indices().first() {
+                    Some(&row) if row == j => column.values()[0],
+                    _ => 0.0,
+                };
+                let original = permutation.order()[j];
+                pivot * pivot <= ZERO_PIVOT_TOLERANCE * self.diagonal[original].abs()
+            })
+            .map(|j| permutation.order()[j])
+            .collect();
+        dofs.sort_unstable();
+        dofs
+    }
+}
+
+fn not_positive_definite(error: impl std::fmt::Display) -> String {
+    format!(
+        "Cholesky factorization failed: {} (matrix not positive definite?)",
+        error
+    )
+}
+
+/// Jacobi-preconditioned Conjugate Gradient solver.
+///
+/// Stops once `|F - K u| <= tolerance * |F|`. The diagonal scaling also
+/// tames the penalty entries of constrained DOFs.
+#[derive(Debug, Clone)]
+pub struct ConjugateGradientSolver {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    matrix: Option<CsrMatrix<f64>>,
+    inv_diag: DVector<f64>,
+}
+
+impl ConjugateGradientSolver {
+    pub fn new(tolerance: f64, max_iterations: usize) -> Self {
+        Self {
+            tolerance,
+            max_iterations,
+            matrix: None,
+            inv_diag: DVector::zeros(0),
+        }
+    }
+}
+
+impl LinearSolver for ConjugateGradientSolver {
+    fn name(&self) -> &'static str {
+        "conjugate-gradient"
+    }
+
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String> {
+        self.inv_diag = matrix
+            .diagonal()
+            .map(|d| if d > 0.0 { 1.0 / d } else { 1.0 });
+        self.matrix = Some(matrix.clone());
+        Ok(())
+    }
+
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String> {
+        let matrix = self
+            .matrix
+            .as_ref()
+            .ok_or("Conjugate gradient solver used before factorize")?;
+        preconditioned_cg(
+            matrix,
+            &self.inv_diag,
+            rhs,
+            self.tolerance,
+            self.max_iterations,
+        )
+    }
+}
+
+/// Dense LU solver, for debugging small systems against the sparse backends.
+#[derive(Debug, Clone, Default)]
+pub struct DenseLuSolver {
+    lu: Option<nalgebra::LU<f64, nalgebra::Dyn, nalgebra::Dyn>>,
+}
+
+impl DenseLuSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LinearSolver for DenseLuSolver {
+    fn name(&self) -> &'static str {
+        "dense-lu"
+    }
+
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String> {
+        let mut dense = DMatrix::zeros(matrix.nrows(), matrix.ncols());
+        for (row_idx, row) in matrix.row_iter().enumerate() {
+            for (&col_idx, &value) in row.col_indices().iter().zip(row.values()) {
+                dense[(row_idx, col_idx)] = value;
+            }
+        }
+        self.lu = Some(dense.lu());
+        Ok(())
+    }
+
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String> {
+        self.lu
+            .as_ref()
+            .ok_or("Dense LU solver used before factorize")?
+            .solve(rhs)
+            .ok_or_else(|| "Failed to solve linear system (singular matrix?)".to_string())
+    }
+
+    fn factor_nnz(&self) -> Option<usize> {
+        self.lu.as_ref().map(|lu| lu.l().len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_sparse::CooMatrix;
+
+    /// 1D Laplacian with a Dirichlet end, scaled by `k`
+    fn laplacian(n: usize, k: f64) -> CsrMatrix<f64> {
+        let mut coo = CooMatrix::new(n, n);
+        for i in 0..n {
+            coo.push(i, i, if i + 1 == n { k } else { 2.0 * k });
+            if i + 1 < n {
+                coo.push(i, i + 1, -k);
+                coo.push(i + 1, i, -k);
+            }
+        }
+        CsrMatrix::from(&coo)
+    }
+
+    #[test]
+    fn backends_agree_on_spd_system() {
+        let k = laplacian(50, 3.0);
+        let f = DVector::from_fn(50, |i, _| (i as f64 * 0.3).sin());
+
+        let kinds = [
+            LinearSolverKind::SparseCholesky,
+            LinearSolverKind::ConjugateGradient {
+                tolerance: 1e-12,
+                max_iterations: 500,
+            },
+            LinearSolverKind::DenseLu,
+        ];
+        for kind in kinds {
+            let mut solver = kind.create();
+            solver.factorize(&k).unwrap();
+            let u = solver.solve(&f).unwrap();
+            let residual = (&f - &k * &u).norm() / f.norm();
+            assert!(residual < 1e-9, "{}: residual {}", solver.name(), residual);
+        }
+    }
+
+    #[test]
+    fn looks_up_backends_by_name() {
+        assert_eq!(
+            LinearSolverKind::from_name("Cholesky"),
+            Ok(LinearSolverKind::SparseCholesky)
+        );
+        assert_eq!(
+            LinearSolverKind::from_name("dense"),
+            Ok(LinearSolverKind::DenseLu)
+        );
+        assert!(LinearSolverKind::from_name("mumps").is_err());
+        for name in LinearSolverKind::names() {
+            assert!(LinearSolverKind::from_name(name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn solve_timed_reports_statistics() {
+        let k = laplacian(30, 1.0);
+        let f = DVector::from_element(30, 1.0);
+        let mut solver = SparseCholeskySolver::new();
+        let (_, info) = solve_timed(&mut solver, &k, &f).unwrap();
+        assert_eq!(info.backend, "sparse-cholesky");
+        assert_eq!((info.num_equations, info.nnz), (30, 88));
+        assert!(info.factor_nnz.unwrap() >= 59);
+        assert!(info.summary().starts_with("sparse-cholesky: 30 equations"));
+    }
+
+    #[test]
+    fn condition_estimate_matches_dense_inverse() {
+        let k = laplacian(40, 2.0);
+        let mut solver = SparseCholeskySolver::new();
+        solver.factorize(&k).unwrap();
+        let estimate = estimate_condition(&k, &solver).unwrap();
+
+        let mut inverse = DMatrix::zeros(40, 40);
+        for j in 0..40 {
+            let e = DVector::from_fn(40, |i, _| if i == j { 1.0 } else { 0.0 });
+            inverse.set_column(j, &solver.solve(&e).unwrap());
+        }
+        let norm1 = |m: &DMatrix<f64>| {
+            (0..m.ncols())
+                .map(|j| m.column(j).lp_norm(1))
+                .fold(0.0, f64::max)
+        };
+        let mut k_dense = DMatrix::zeros(40, 40);
+        for (i, row) in k.row_iter().enumerate() {
+            for (&j, &v) in row.col_indices().iter().zip(row.values()) {
+                k_dense[(i, j)] = v;
+            }
+        }
+        let exact = norm1(&k_dense) * norm1(&inverse);
+        assert!(estimate <= exact * (1.0 + 1e-12) && estimate > 0.5 * exact);
+    }
+
+    #[test]
+    fn cholesky_reuses_symbolic_analysis() {
+        let mut solver = SparseCholeskySolver::new();
+        let f = DVector::from_element(20, 1.0);
+
+        solver.factorize(&laplacian(20, 1.0)).unwrap();
+        let u1 = solver.solve(&f).unwrap();
+        solver.factorize(&laplacian(20, 2.0)).unwrap();
+        let u2 = solver.solve(&f).unwrap();
+        assert_eq!(solver.symbolic_factorizations(), 1);
+        assert!((&u1 - &u2 * 2.0).amax() < 1e-10);
+
+        solver.factorize(&laplacian(10, 1.0)).unwrap();
+        assert_eq!(solver.symbolic_factorizations(), 2);
+    }
+
+    #[test]
+    fn rcm_ordering_cuts_cholesky_fill() {
+        // 20x20 grid Laplacian with randomly shuffled equation numbers
+        let n = 20;
+        let mut numbers: Vec<usize> = (0..n * n).collect();
+        let mut seed = 12345_u64;
+        for i in (1..numbers.len()).rev() {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            numbers.swap(i, (seed >> 33) as usize % (i + 1));
+        }
+        let number = |i: usize, j: usize| numbers[i + n * j];
+        let mut coo = CooMatrix::new(n * n, n * n);
+        for j in 0..n {
+            for i in 0..n {
+                coo.push(number(i, j), number(i, j), 4.01);
+                for (i2, j2) in [(i + 1, j), (i, j + 1)] {
+                    if i2 < n && j2 < n {
+                        coo.push(number(i, j), number(i2, j2), -1.0);
+                        coo.push(number(i2, j2), number(i, j), -1.0);
+                    }
+                }
+            }
+        }
+        let k = CsrMatrix::from(&coo);
+        let f = DVector::from_fn(n * n, |i, _| (i as f64).cos());
+
+        let mut rcm = SparseCholeskySolver::new();
+        let mut unordered = SparseCholeskySolver::with_ordering(DofOrdering::Natural);
+        rcm.factorize(&k).unwrap();
+        unordered.factorize(&k).unwrap();
+        assert!(2 * rcm.factor_nnz().unwrap() < unordered.factor_nnz().unwrap());
+
+        let u = rcm.solve(&f).unwrap();
+        assert!((&k * &u - &f).norm() < 1e-9 * f.norm());
+        assert!((&u - unordered.solve(&f).unwrap()).amax() < 1e-9 * u.amax());
+    }
+
+    #[test]
+    fn cache_skips_refactorization_of_unchanged_matrix() {
+        let mut solver = FactorizationCache::new(LinearSolverKind::SparseCholesky.create());
+        let loads = [
+            DVector::from_element(20, 1.0),
+            DVector::from_element(20, -2.0),
+        ];
+
+        solver.factorize(&laplacian(20, 1.0)).unwrap();
+        solver.factorize(&laplacian(20, 1.0)).unwrap();
+        let u = solver.resolve(&loads).unwrap();
+        assert_eq!((solver.factorizations(), solver.reuses()), (1, 1));
+        assert!((&u[0] * -2.0 - &u[1]).amax() < 1e-12);
+
+        solver.factorize(&laplacian(20, 2.0)).unwrap();
+        assert_eq!(solver.factorizations(), 2);
+        solver.invalidate();
+        solver.factorize(&laplacian(20, 2.0)).unwrap();
+        assert_eq!((solver.factorizations(), solver.reuses()), (3, 1));
+    }
+
+    #[test]
+    fn cholesky_rejects_indefinite_matrix() {
+        let mut coo = CooMatrix::new(2, 2);
+        coo.push(0, 0, 1.0);
+        coo.push(1, 1, -1.0);
+        let mut solver = SparseCholeskySolver::new();
+        assert!(solver.factorize(&CsrMatrix::from(&coo)).is_err());
+        assert!(solver.solve(&DVector::from_element(2, 1.0)).is_err());
+    }
+}
@@ -17,6 +17,7 @@ fn print_migration_report() {
     let report = migration_report();
     println!("legacy_units_total: {}", report.total_units);
     println!("ported_units: {}", report.ported_units);
+    println!("in_progress_units: {}", report.in_progress_units);
     println!(
         "superseded_fortran_units: {}",
         report.superseded_fortran_units
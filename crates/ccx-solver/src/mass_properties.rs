@@ -0,0 +1,524 @@
+//! Mass, centre of gravity and inertia tensor of a model.
+//!
+//! Every element gets its material and section from the `*SOLID SECTION`,
+//! `*SHELL SECTION`, `*MEMBRANE SECTION` and `*BEAM SECTION` cards. Solids
+//! are integrated with their integration points (C3D8, C3D20) or split
+//! into tetrahedra (C3D4, C3D10, C3D6, C3D15); shells and membranes are
+//! integrated over their corner triangles times the thickness, trusses and
+//! beams along their chord times the section area. Linear shapes are
+//! integrated exactly, and shell thickness and beam sections contribute no
+//! rotary inertia of their own.
+
+use std::collections::BTreeMap;
+
+use ccx_inp::{Card, Deck};
+
+use crate::elements::SolidElement;
+use crate::materials::MaterialLibrary;
+use crate::mesh::{ElementType, Mesh, Node};
+use crate::sets::Sets;
+
+/// Material and geometric section data of one element
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementSection {
+    pub material: String,
+    /// Thickness of shell and membrane sections
+    pub thickness: Option<f64>,
+    /// Cross-section area of truss and beam sections
+    pub area: Option<f64>,
+}
+
+/// Mass properties of the elements with a section and a density
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MassProperties {
+    pub mass: f64,
+    pub center_of_gravity: [f64; 3],
+    /// Inertia tensor about the centre of gravity
+    pub inertia: [[f64; 3]; 3],
+    /// Elements without section, density, thickness or area, in ID order
+    pub skipped: Vec<i32>,
+}
+
+fn parameter<'a>(card: &'a Card, key: &str) -> Option<&'a str> {
+    card.parameters
+        .iter()
+        .find(|p| p.key.eq_ignore_ascii_case(key))
+        .and_then(|p| p.value.as_deref())
+}
+
+/// Numbers on the first data line of `card`
+fn first_line_values(card: &Card) -> Result<Vec<f64>, String> {
+    let Some(line) = card.data_lines.first() else {
+        return Ok(Vec::new());
+    };
+    line.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            v.parse::<f64>()
+                .map_err(|_| format!("Invalid *{} value: {}", card.keyword, v))
+        })
+        .collect()
+}
+
+/// Cross-section area of a `*BEAM SECTION` from its first data line
+fn beam_area(section: &str, values: &[f64]) -> Result<f64, String> {
+    let value = |i: usize| {
+        values
+            .get(i)
+            .copied()
+            .ok_or_else(|| format!("Beam section {} needs {} dimensions", section, i + 1))
+    };
+    match section.to_uppercase().as_str() {
+        "RECT" => Ok(value(0)? * value(1)?),
+        "CIRC" => Ok(std::f64::consts::PI * value(0)?.powi(2)),
+        "PIPE" => {
+            let (r, t) = (value(0)?, value(1)?);
+            Ok(std::f64::consts::PI * (r * r - (r - t).powi(2)))
+        }
+        "BOX" => {
+            let (a, b) = (value(0)?, value(1)?);
+            let (t1, t2, t3, t4) = (value(2)?, value(3)?, value(4)?, value(5)?);
+            Ok(a * b - (a - t2 - t4) * (b - t1 - t3))
+        }
+        other => Err(format!("Unsupported beam section {}", other)),
+    }
+}
+
+/// Sections of all elements in the section cards of `deck`, by element ID
+///
+/// Element sets are looked up case-insensitively in `sets`.
+pub fn element_sections(deck: &Deck, sets: &Sets) -> Result<BTreeMap<i32, ElementSection>, String> {
+    let mut sections = BTreeMap::new();
+    for card in &deck.cards {
+        let keyword = card.keyword.to_uppercase();
+        if !matches!(
+            keyword.as_str(),
+            "SOLID SECTION" | "SHELL SECTION" | "MEMBRANE SECTION" | "BEAM SECTION"
+        ) {
+            continue;
+        }
+        let elset = parameter(card, "ELSET").ok_or(format!("*{} without ELSET", keyword))?;
+        let material =
+            parameter(card, "MATERIAL").ok_or(format!("*{} without MATERIAL", keyword))?;
+        let elements = sets
+            .element_sets
+            .values()
+            .find(|set| set.name.eq_ignore_ascii_case(elset))
+            .map(|set| &set.elements)
+            .ok_or(format!("Element set {} not found", elset))?;
+        let values = first_line_values(card)?;
+        let (thickness, area) = match keyword.as_str() {
+            "SHELL SECTION" | "MEMBRANE SECTION" => (values.first().copied(), None),
+            "BEAM SECTION" => {
+                let section = parameter(card, "SECTION").ok_or("*BEAM SECTION without SECTION")?;
+                (None, Some(beam_area(section, &values)?))
+            }
+            // The optional value of a solid section is the truss area
+            _ => (None, values.first().copied()),
+        };
+        for id in elements {
+            sections.insert(
+                *id,
+                ElementSection {
+                    material: material.to_string(),
+                    thickness,
+                    area,
+                },
+            );
+        }
+    }
+    Ok(sections)
+}
+
+/// Mass, first and second moments of mass about the origin
+#[derive(Debug, Default)]
+struct Moments {
+    mass: f64,
+    first: [f64; 3],
+    second: [[f64; 3]; 3],
+}
+
+impl Moments {
+    fn add(&mut self, other: &Moments) {
+        self.mass += other.mass;
+        for i in 0..3 {
+            self.first[i] += other.first[i];
+            for j in 0..3 {
+                self.second[i][j] += other.second[i][j];
+            }
+        }
+    }
+
+    fn add_point(&mut self, mass: f64, x: [f64; 3]) {
+        self.mass += mass;
+        for i in 0..3 {
+            self.first[i] += mass * x[i];
+            for j in 0..3 {
+                self.second[i][j] += mass * x[i] * x[j];
+            }
+        }
+    }
+
+    /// Simplex (segment, triangle or tetrahedron) with corners `x` and
+    /// uniform mass `mass`, integrated exactly
+    fn add_simplex(&mut self, mass: f64, x: &[[f64; 3]]) {
+        let n = x.len() as f64;
+        let sum: [f64; 3] = std::array::from_fn(|i| x.iter().map(|c| c[i]).sum());
+        self.mass += mass;
+        for i in 0..3 {
+            self.first[i] += mass * sum[i] / n;
+            for j in 0..3 {
+                let products: f64 = x.iter().map(|c| c[i] * c[j]).sum();
+                self.second[i][j] += mass * (products + sum[i] * sum[j]) / (n * (n + 1.0));
+            }
+        }
+    }
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(a: [f64; 3]) -> f64 {
+    (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt()
+}
+
+/// Measure (length, area or volume) of a simplex
+fn simplex_measure(x: &[[f64; 3]]) -> f64 {
+    match x.len() {
+        2 => norm(sub(x[1], x[0])),
+        3 => 0.5 * norm(cross(sub(x[1], x[0]), sub(x[2], x[0]))),
+        _ => {
+            let c = cross(sub(x[2], x[0]), sub(x[3], x[0]));
+            let a = sub(x[1], x[0]);
+            (a[0] * c[0] + a[1] * c[1] + a[2] * c[2]).abs() / 6.0
+        }
+    }
+}
+
+/// Corner index simplices of an element shape
+fn simplices(element_type: ElementType) -> &'static [&'static [usize]] {
+    match element_type {
+        ElementType::C3D4 | ElementType::C3D10 => &[&[0, 1, 2, 3]],
+        ElementType::C3D6 | ElementType::C3D15 => &[&[0, 1, 2, 3], &[1, 2, 3, 4], &[2, 3, 4, 5]],
+        ElementType::S3 | ElementType::S6 | ElementType::M3D3 | ElementType::M3D6 => &[&[0, 1, 2]],
+        ElementType::S4 | ElementType::S8 | ElementType::M3D4 | ElementType::M3D8 => {
+            &[&[0, 1, 2], &[0, 2, 3]]
+        }
+        ElementType::T3D2 | ElementType::B31 => &[&[0, 1]],
+        ElementType::B32 => &[&[0, 2]],
+        ElementType::C3D8 | ElementType::C3D20 => &[],
+    }
+}
+
+/// Density of the material named `name` in `materials`, matched
+/// case-insensitively
+fn density(materials: &MaterialLibrary, name: &str) -> Option<f64> {
+    materials
+        .material_names()
+        .iter()
+        .find(|n| n.eq_ignore_ascii_case(name))
+        .and_then(|n| materials.get_material(n))
+        .and_then(|m| m.density)
+}
+
+impl Mesh {
+    /// Moments of element `id`, or `None` when it lacks a node, a section,
+    /// a density or the thickness or area its type needs
+    fn element_moments(
+        &self,
+        id: i32,
+        sections: &BTreeMap<i32, ElementSection>,
+        materials: &MaterialLibrary,
+    ) -> Option<Moments> {
+        let element = &self.elements[&id];
+        let nodes: Vec<Node> = element
+            .nodes
+            .iter()
+            .map(|n| self.nodes.get(n).cloned())
+            .collect::<Option<_>>()?;
+        let section = sections.get(&id)?;
+        let rho = density(materials, &section.material)?;
+        let factor = match element.element_type {
+            ElementType::S3
+            | ElementType::S4
+            | ElementType::S6
+            | ElementType::S8
+            | ElementType::M3D3
+            | ElementType::M3D4
+            | ElementType::M3D6
+            | ElementType::M3D8 => section.thickness,
+            ElementType::T3D2 | ElementType::B31 | ElementType::B32 => section.area,
+            _ => Some(1.0),
+        };
+        let factor = factor.filter(|_| nodes.len() == element.element_type.num_nodes())?;
+
+        let mut moments = Moments::default();
+        let shape = simplices(element.element_type);
+        if shape.is_empty() {
+            let solid = SolidElement::new(id, element.element_type, element.nodes.clone());
+            let points = solid.integration_point_coordinates(&nodes).ok()?;
+            let volumes = solid.integration_volumes(&nodes).ok()?;
+            for (x, volume) in points.into_iter().zip(volumes) {
+                moments.add_point(rho * volume, x);
+            }
+        } else {
+            for corners in shape {
+                let x: Vec<[f64; 3]> = corners.iter().map(|&c| nodes[c].coords()).collect();
+                moments.add_simplex(rho * factor * simplex_measure(&x), &x);
+            }
+        }
+        Some(moments)
+    }
+
+    /// Mass properties of the elements from their `sections` and the
+    /// densities in `materials`
+    ///
+    /// Material names are matched case-insensitively.
+    pub fn mass_properties(
+        &self,
+        sections: &BTreeMap<i32, ElementSection>,
+        materials: &MaterialLibrary,
+    ) -> MassProperties {
+        let mut ids: Vec<i32> = self.elements.keys().copied().collect();
+        ids.sort_unstable();
+        let mut moments = Moments::default();
+        let mut skipped = Vec::new();
+        for id in ids {
+            match self.element_moments(id, sections, materials) {
+                Some(element) => moments.add(&element),
+                None => skipped.push(id),
+            }
+        }
+
+        let mass = moments.mass;
+        if mass == 0.0 {
+            return MassProperties {
+                skipped,
+                ..Default::default()
+            };
+        }
+        let cg = moments.first.map(|s| s / mass);
+        // Second moments about the centre of gravity, then I = tr(Q) 1 - Q
+        let q: [[f64; 3]; 3] = std::array::from_fn(|i| {
+            std::array::from_fn(|j| moments.second[i][j] - mass * cg[i] * cg[j])
+        });
+        let trace = q[0][0] + q[1][1] + q[2][2];
+        let inertia = std::array::from_fn(|i| {
+            std::array::from_fn(|j| if i == j { trace - q[i][j] } else { -q[i][j] })
+        });
+        MassProperties {
+            mass,
+            center_of_gravity: cg,
+            inertia,
+            skipped,
+        }
+    }
+
+    /// Element masses split equally among the element nodes, by node ID
+    ///
+    /// The elements skipped as in [`Mesh::mass_properties`] are returned
+    /// alongside, in ID order.
+    pub fn lumped_masses(
+        &self,
+        sections: &BTreeMap<i32, ElementSection>,
+        materials: &MaterialLibrary,
+    ) -> (BTreeMap<i32, f64>, Vec<i32>) {
+        let mut ids: Vec<i32> = self.elements.keys().copied().collect();
+        ids.sort_unstable();
+        let mut masses = BTreeMap::new();
+        let mut skipped = Vec::new();
+        for id in ids {
+            let Some(moments) = self.element_moments(id, sections, materials) else {
+                skipped.push(id);
+                continue;
+            };
+            let nodes = &self.elements[&id].nodes;
+            for node in nodes {
+                *masses.entry(*node).or_insert(0.0) += moments.mass / nodes.len() as f64;
+            }
+        }
+        (masses, skipped)
+    }
+}
+
+impl MassProperties {
+    /// Mass properties of a deck: sections, sets and materials from its cards
+    pub fn from_deck(deck: &Deck, mesh: &Mesh) -> Result<Self, String> {
+        let mut sets = Sets::build_from_deck(deck)?;
+        sets.add_card_sets(deck)?;
+        let sections = element_sections(deck, &sets)?;
+        let materials = MaterialLibrary::build_from_deck(deck)?;
+        Ok(mesh.mass_properties(&sections, &materials))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_builder::MeshBuilder;
+    use crate::structured_mesh::Division;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * b.abs().max(1.0)
+    }
+
+    fn library(density: f64) -> MaterialLibrary {
+        let mut materials = MaterialLibrary::new();
+        let mut steel = crate::materials::Material::new("STEEL".to_string());
+        steel.density = Some(density);
+        materials.add_material(steel);
+        materials
+    }
+
+    fn sections(
+        mesh: &Mesh,
+        thickness: Option<f64>,
+        area: Option<f64>,
+    ) -> BTreeMap<i32, ElementSection> {
+        mesh.elements
+            .keys()
+            .map(|&id| {
+                let section = ElementSection {
+                    material: "steel".to_string(),
+                    thickness,
+                    area,
+                };
+                (id, section)
+            })
+            .collect()
+    }
+
+    /// Inertia of a uniform box about its centre
+    fn box_inertia(mass: f64, [a, b, c]: [f64; 3]) -> [f64; 3] {
+        [
+            mass * (b * b + c * c) / 12.0,
+            mass * (a * a + c * c) / 12.0,
+            mass * (a * a + b * b) / 12.0,
+        ]
+    }
+
+    #[test]
+    fn brick_matches_closed_form() {
+        let size = [2.0, 3.0, 4.0];
+        let divisions = [
+            Division::uniform(2),
+            Division::uniform(1),
+            Division::graded(3, 2.0),
+        ];
+        for element_type in [ElementType::C3D8, ElementType::C3D20] {
+            let mesh = MeshBuilder::brick(size, divisions, element_type)
+                .unwrap()
+                .mesh;
+            let properties = mesh.mass_properties(&sections(&mesh, None, None), &library(2.0));
+            assert!(close(properties.mass, 48.0));
+            assert!(properties.skipped.is_empty());
+            for (d, expected) in [1.0, 1.5, 2.0].into_iter().enumerate() {
+                assert!(close(properties.center_of_gravity[d], expected));
+            }
+            for (d, expected) in box_inertia(48.0, size).into_iter().enumerate() {
+                assert!(close(properties.inertia[d][d], expected));
+            }
+            assert!(properties.inertia[0][1].abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn tetrahedra_wedges_and_shells_are_exact() {
+        // A unit cube of two wedges
+        let mut mesh = Mesh::new();
+        let corners = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ];
+        for (i, [x, y, z]) in corners.into_iter().enumerate() {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        for (id, nodes) in [(1, vec![1, 2, 3, 5, 6, 7]), (2, vec![1, 3, 4, 5, 7, 8])] {
+            mesh.add_element(crate::mesh::Element::new(id, ElementType::C3D6, nodes))
+                .unwrap();
+        }
+        let properties = mesh.mass_properties(&sections(&mesh, None, None), &library(1.0));
+        assert!(close(properties.mass, 1.0));
+        assert!(close(properties.inertia[2][2], 1.0 / 6.0));
+        assert!(close(properties.center_of_gravity[1], 0.5));
+
+        // A 2 x 1 plate, 0.1 thick, in the xy plane
+        let plate = MeshBuilder::plate([2.0, 1.0], [Division::uniform(2); 2], ElementType::S8)
+            .unwrap()
+            .mesh;
+        let properties = plate.mass_properties(&sections(&plate, Some(0.1), None), &library(10.0));
+        assert!(close(properties.mass, 2.0));
+        assert!(close(properties.inertia[2][2], 2.0 * 5.0 / 12.0));
+        assert!(close(properties.inertia[0][0], 2.0 / 12.0));
+    }
+
+    #[test]
+    fn beams_and_missing_data_are_handled() {
+        let mut mesh = Mesh::new();
+        for id in 1..=3 {
+            mesh.add_node(Node::new(id, (id - 1) as f64, 0.0, 0.0));
+        }
+        mesh.add_element(crate::mesh::Element::new(1, ElementType::B31, vec![1, 2]))
+            .unwrap();
+        mesh.add_element(crate::mesh::Element::new(2, ElementType::T3D2, vec![2, 3]))
+            .unwrap();
+        let mut all = sections(&mesh, None, Some(0.5));
+        let properties = mesh.mass_properties(&all, &library(4.0));
+        assert!(close(properties.mass, 4.0));
+        assert!(close(properties.center_of_gravity[0], 1.0));
+        assert!(close(properties.inertia[1][1], 4.0 * 4.0 / 12.0));
+
+        all.get_mut(&2).unwrap().area = None;
+        let properties = mesh.mass_properties(&all, &library(4.0));
+        assert_eq!(properties.skipped, vec![2]);
+        assert!(close(properties.mass, 2.0));
+        let properties = mesh.mass_properties(&all, &MaterialLibrary::new());
+        assert_eq!(properties.skipped, vec![1, 2]);
+        assert_eq!(properties.mass, 0.0);
+    }
+
+    #[test]
+    fn reads_sections_from_the_deck() {
+        let deck = Deck::parse_str(
+            "*NODE\n1, 0, 0, 0\n2, 2, 0, 0\n3, 2, 1, 0\n4, 0, 1, 0\n\
+             *ELEMENT, TYPE=B31, ELSET=BEAMS\n1, 1, 2\n\
+             *ELEMENT, TYPE=S4, ELSET=PLATE\n2, 1, 2, 3, 4\n\
+             *MATERIAL, NAME=Steel\n*DENSITY\n7.85e-9\n\
+             *BEAM SECTION, ELSET=beams, MATERIAL=STEEL, SECTION=RECT\n2., 3.\n0, 0, 1\n\
+             *SHELL SECTION, ELSET=PLATE, MATERIAL=STEEL\n0.5\n",
+        )
+        .unwrap();
+        let mesh = MeshBuilder::build_from_deck(&deck).unwrap();
+        let mut sets = Sets::build_from_deck(&deck).unwrap();
+        sets.add_card_sets(&deck).unwrap();
+        let sections = element_sections(&deck, &sets).unwrap();
+        assert_eq!(sections[&1].area, Some(6.0));
+        assert_eq!(sections[&2].thickness, Some(0.5));
+        let properties = MassProperties::from_deck(&deck, &mesh).unwrap();
+        assert!(close(properties.mass, 7.85e-9 * (2.0 * 6.0 + 2.0 * 0.5)));
+
+        let pipe = beam_area("PIPE", &[2.0, 1.0]).unwrap();
+        assert!(close(pipe, 3.0 * std::f64::consts::PI));
+        assert!(close(
+            beam_area("BOX", &[4.0, 2.0, 0.5, 0.5, 0.5, 0.5]).unwrap(),
+            5.0
+        ));
+        assert!(beam_area("GEN", &[]).is_err());
+    }
+}
@@ -1,10 +1,11 @@
 //! Material properties for finite element analysis.
 
 use ccx_inp::{Card, Deck};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Material model type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MaterialModel {
     /// Linear elastic isotropic
     LinearElastic,
@@ -17,7 +18,7 @@ pub enum MaterialModel {
 }
 
 /// A material definition
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Material {
     /// Material name
     pub name: String,
@@ -35,6 +36,8 @@ pub struct Material {
     pub conductivity: Option<f64>,
     /// Specific heat [J/(kg·K)]
     pub specific_heat: Option<f64>,
+    /// Initial yield stress from *PLASTIC [Pa]
+    pub yield_stress: Option<f64>,
 }
 
 impl Material {
@@ -49,9 +52,25 @@ impl Material {
             thermal_expansion: None,
             conductivity: None,
             specific_heat: None,
+            yield_stress: None,
         }
     }
 
+    /// Create a linear elastic isotropic material
+    pub fn elastic(name: &str, elastic_modulus: f64, poissons_ratio: f64) -> Self {
+        Self {
+            elastic_modulus: Some(elastic_modulus),
+            poissons_ratio: Some(poissons_ratio),
+            ..Self::new(name.to_string())
+        }
+    }
+
+    /// Set the density
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = Some(density);
+        self
+    }
+
     /// Check if material has minimum required properties for structural analysis
     pub fn is_valid_for_structural(&self) -> bool {
         self.elastic_modulus.is_some() && self.poissons_ratio.is_some()
@@ -75,7 +94,7 @@ impl Material {
 }
 
 /// Material library containing all materials and their assignments
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MaterialLibrary {
     /// All materials by name
     materials: HashMap<String, Material>,
@@ -156,6 +175,11 @@ impl MaterialLibrary {
                         Self::parse_specific_heat(card, &mut library, mat_name)?;
                     }
                 }
+                "PLASTIC" => {
+                    if let Some(ref mat_name) = current_material {
+                        Self::parse_plastic(card, &mut library, mat_name)?;
+                    }
+                }
                 _ => {}
             }
         }
@@ -327,6 +351,34 @@ impl MaterialLibrary {
         Ok(())
     }
 
+    /// Parse a *PLASTIC card; the first line holds the initial yield stress
+    fn parse_plastic(
+        card: &Card,
+        library: &mut MaterialLibrary,
+        material_name: &str,
+    ) -> Result<(), String> {
+        if card.data_lines.is_empty() {
+            return Err("PLASTIC card has no data lines".to_string());
+        }
+
+        let line = &card.data_lines[0];
+        let yield_stress = line
+            .trim()
+            .split(',')
+            .next()
+            .ok_or("PLASTIC data line is empty")?
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid yield stress value: {}", line.trim()))?;
+
+        if let Some(material) = library.materials.get_mut(material_name) {
+            material.model = MaterialModel::Plastic;
+            material.yield_stress = Some(yield_stress);
+        }
+
+        Ok(())
+    }
+
     /// Get statistics
     pub fn statistics(&self) -> MaterialStatistics {
         let valid_materials = self
@@ -404,6 +456,25 @@ mod tests {
         assert_eq!(al.density, Some(2700.0));
     }
 
+    #[test]
+    fn parses_initial_yield_stress() {
+        let input = r#"
+*MATERIAL, NAME=STEEL
+*ELASTIC
+210000, 0.3
+*PLASTIC
+235, 0.0
+360, 0.2
+"#;
+
+        let deck = parse_deck(input);
+        let library = MaterialLibrary::build_from_deck(&deck).expect("Failed to build library");
+
+        let steel = library.get_material("STEEL").unwrap();
+        assert_eq!(steel.yield_stress, Some(235.0));
+        assert_eq!(steel.model, MaterialModel::Plastic);
+    }
+
     #[test]
     fn calculates_shear_modulus() {
         let mut mat = Material::new("TEST".to_string());
@@ -5,8 +5,10 @@
 
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 /// A node in the finite element mesh
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Node {
     /// Node ID (1-based indexing from input file)
     pub id: i32,
@@ -31,7 +33,7 @@ impl Node {
 }
 
 /// Element type enumeration
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ElementType {
     /// 2-node truss element (T3D2)
     T3D2,
@@ -144,7 +146,7 @@ impl ElementType {
 }
 
 /// An element in the finite element mesh
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Element {
     /// Element ID (1-based indexing from input file)
     pub id: i32,
@@ -179,7 +181,7 @@ impl Element {
 }
 
 /// Complete finite element mesh
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mesh {
     /// All nodes in the mesh, indexed by node ID
     pub nodes: HashMap<i32, Node>,
@@ -251,11 +253,30 @@ impl Mesh {
             *element_type_counts.entry(element.element_type).or_insert(0) += 1;
         }
 
+        let id_range = |ids: &mut dyn Iterator<Item = i32>| {
+            ids.fold(None, |range: Option<(i32, i32)>, id| match range {
+                Some((lo, hi)) => Some((lo.min(id), hi.max(id))),
+                None => Some((id, id)),
+            })
+        };
+        let bounding_box = self.nodes.values().fold(None, |bounds, node| {
+            let p = node.coords();
+            let (mut lo, mut hi) = bounds.unwrap_or((p, p));
+            for d in 0..3 {
+                lo[d] = lo[d].min(p[d]);
+                hi[d] = hi[d].max(p[d]);
+            }
+            Some((lo, hi))
+        });
+
         MeshStatistics {
             num_nodes: self.nodes.len(),
             num_elements: self.elements.len(),
             num_dofs: self.num_dofs,
             element_type_counts,
+            node_id_range: id_range(&mut self.nodes.keys().copied()),
+            element_id_range: id_range(&mut self.elements.keys().copied()),
+            bounding_box,
         }
     }
 }
@@ -277,6 +298,12 @@ pub struct MeshStatistics {
     pub num_dofs: usize,
     /// Count of each element type
     pub element_type_counts: HashMap<ElementType, usize>,
+    /// Smallest and largest node ID
+    pub node_id_range: Option<(i32, i32)>,
+    /// Smallest and largest element ID
+    pub element_id_range: Option<(i32, i32)>,
+    /// Minimum and maximum node coordinates
+    pub bounding_box: Option<([f64; 3], [f64; 3])>,
 }
 
 impl MeshStatistics {
@@ -287,6 +314,22 @@ impl MeshStatistics {
             format!("Elements: {}", self.num_elements),
             format!("DOFs: {}", self.num_dofs),
         ];
+        if let Some((lo, hi)) = self.node_id_range {
+            lines.push(format!("Node IDs: {} to {}", lo, hi));
+        }
+        if let Some((lo, hi)) = self.element_id_range {
+            lines.push(format!("Element IDs: {} to {}", lo, hi));
+        }
+        if let Some((lo, hi)) = self.bounding_box {
+            let point = |p: [f64; 3]| format!("({:.6e}, {:.6e}, {:.6e})", p[0], p[1], p[2]);
+            lines.push(format!("Bounding box: {} to {}", point(lo), point(hi)));
+            lines.push(format!(
+                "Extent: {:.6e} x {:.6e} x {:.6e}",
+                hi[0] - lo[0],
+                hi[1] - lo[1],
+                hi[2] - lo[2]
+            ));
+        }
 
         if !self.element_type_counts.is_empty() {
             lines.push("Element types:".to_string());
@@ -404,5 +447,27 @@ mod tests {
         assert_eq!(stats.num_elements, 1);
         assert_eq!(stats.num_dofs, 24);
         assert_eq!(stats.element_type_counts.get(&ElementType::C3D8), Some(&1));
+        assert_eq!(stats.node_id_range, Some((1, 8)));
+        assert_eq!(stats.element_id_range, Some((1, 1)));
+    }
+
+    #[test]
+    fn mesh_statistics_bounding_box() {
+        let mut mesh = Mesh::new();
+        mesh.add_node(Node::new(5, -1.0, 2.0, 0.5));
+        mesh.add_node(Node::new(12, 3.0, -2.0, 0.0));
+
+        let stats = mesh.statistics();
+        assert_eq!(stats.node_id_range, Some((5, 12)));
+        assert_eq!(stats.element_id_range, None);
+        assert_eq!(
+            stats.bounding_box,
+            Some(([-1.0, -2.0, 0.0], [3.0, 2.0, 0.5]))
+        );
+        assert!(
+            stats
+                .format()
+                .contains("Extent: 4.000000e0 x 4.000000e0 x 5.000000e-1")
+        );
     }
 }
@@ -0,0 +1,274 @@
+//! Connectivity audit of a model before assembly.
+//!
+//! Nodes that no element uses get no stiffness, and without a check they
+//! only show up as zero pivots of the factorization. Elements that reference
+//! undefined nodes, parts that are not connected to the rest of the mesh and
+//! sets that no card uses point at modelling errors as well. [`MeshAudit`]
+//! collects all of them in one report.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use ccx_inp::Deck;
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::Mesh;
+use crate::sets::Sets;
+
+/// Connectivity problems of a mesh and its sets
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeshAudit {
+    /// Nodes not used by any element
+    pub orphan_nodes: Vec<i32>,
+    /// Elements with the IDs of the undefined nodes they reference
+    pub missing_nodes: BTreeMap<i32, Vec<i32>>,
+    /// Element IDs of every group of elements connected through shared
+    /// nodes, the largest group first
+    pub components: Vec<Vec<i32>>,
+    /// Node sets, element sets and surfaces no other card refers to
+    pub unused_sets: Vec<String>,
+}
+
+/// Find the representative of `i`, compressing the path
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+impl Mesh {
+    /// Check for orphan nodes, elements referencing missing nodes and
+    /// disconnected parts
+    pub fn audit(&self) -> MeshAudit {
+        let mut element_ids: Vec<i32> = self.elements.keys().copied().collect();
+        element_ids.sort_unstable();
+
+        let mut used = HashSet::new();
+        let mut missing_nodes = BTreeMap::new();
+        for id in &element_ids {
+            let mut missing = Vec::new();
+            for node in &self.elements[id].nodes {
+                if self.nodes.contains_key(node) {
+                    used.insert(*node);
+                } else if !missing.contains(node) {
+                    missing.push(*node);
+                }
+            }
+            if !missing.is_empty() {
+                missing_nodes.insert(*id, missing);
+            }
+        }
+        let mut orphan_nodes: Vec<i32> = self
+            .nodes
+            .keys()
+            .copied()
+            .filter(|id| !used.contains(id))
+            .collect();
+        orphan_nodes.sort_unstable();
+
+        // Union the elements through the first element seen at every node
+        let mut parent: Vec<usize> = (0..element_ids.len()).collect();
+        let mut first_at_node = BTreeMap::new();
+        for (index, id) in element_ids.iter().enumerate() {
+            for node in &self.elements[id].nodes {
+                let other = *first_at_node.entry(*node).or_insert(index);
+                let (a, b) = (find(&mut parent, index), find(&mut parent, other));
+                if a != b {
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+        let mut groups: BTreeMap<usize, Vec<i32>> = BTreeMap::new();
+        for (index, id) in element_ids.iter().enumerate() {
+            let root = find(&mut parent, index);
+            groups.entry(root).or_default().push(*id);
+        }
+        let mut components: Vec<Vec<i32>> = groups.into_values().collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+
+        MeshAudit {
+            orphan_nodes,
+            missing_nodes,
+            components,
+            unused_sets: Vec::new(),
+        }
+    }
+}
+
+/// Sets ccx defines for every model, used by its outputs without any card
+/// naming them
+const IMPLICIT_SETS: [&str; 2] = ["NALL", "EALL"];
+
+impl MeshAudit {
+    /// Add the sets of `sets` that no card of `deck` refers to by name,
+    /// other than the cards defining them and the implicit `NALL`/`EALL`
+    pub fn with_unused_sets(mut self, sets: &Sets, deck: &Deck) -> Self {
+        let mut referenced: HashSet<String> =
+            IMPLICIT_SETS.iter().map(|name| name.to_string()).collect();
+        for card in &deck.cards {
+            let keyword = card.keyword.to_uppercase();
+            for parameter in &card.parameters {
+                let key = parameter.key.to_uppercase();
+                let defines = matches!(
+                    (keyword.as_str(), key.as_str()),
+                    ("NSET" | "NODE", "NSET")
+                        | ("ELSET" | "ELEMENT", "ELSET")
+                        | ("SURFACE", "NAME")
+                );
+                if let Some(value) = &parameter.value
+                    && !defines
+                {
+                    referenced.insert(value.trim().to_uppercase());
+                }
+            }
+            for line in &card.data_lines {
+                referenced.extend(line.split(',').map(|token| token.trim().to_uppercase()));
+            }
+        }
+        let names: BTreeSet<&String> = sets
+            .node_sets
+            .keys()
+            .chain(sets.element_sets.keys())
+            .chain(sets.surfaces.keys())
+            .collect();
+        self.unused_sets = names
+            .into_iter()
+            .filter(|name| !referenced.contains(&name.to_uppercase()))
+            .cloned()
+            .collect();
+        self
+    }
+
+    /// Whether no problem was found
+    pub fn is_clean(&self) -> bool {
+        self.warnings().is_empty()
+    }
+
+    /// One message per kind of problem found
+    pub fn warnings(&self) -> Vec<String> {
+        fn list(ids: &[i32]) -> String {
+            let mut text: Vec<String> = ids.iter().take(10).map(i32::to_string).collect();
+            if ids.len() > 10 {
+                text.push(format!("and {} more", ids.len() - 10));
+            }
+            text.join(", ")
+        }
+        let mut warnings = Vec::new();
+        if !self.orphan_nodes.is_empty() {
+            warnings.push(format!(
+                "{} nodes are not used by any element: {}",
+                self.orphan_nodes.len(),
+                list(&self.orphan_nodes)
+            ));
+        }
+        for (element, nodes) in &self.missing_nodes {
+            warnings.push(format!(
+                "element {} references undefined nodes {}",
+                element,
+                list(nodes)
+            ));
+        }
+        if self.components.len() > 1 {
+            let sizes: Vec<String> = self
+                .components
+                .iter()
+                .map(|elements| format!("{} (from element {})", elements.len(), elements[0]))
+                .collect();
+            warnings.push(format!(
+                "mesh has {} disconnected parts with {} elements",
+                self.components.len(),
+                sizes.join(", ")
+            ));
+        }
+        if !self.unused_sets.is_empty() {
+            warnings.push(format!(
+                "sets not used by any card: {}",
+                self.unused_sets.join(", ")
+            ));
+        }
+        warnings
+    }
+
+    /// Format the report, one problem per line
+    pub fn format(&self) -> String {
+        let warnings = self.warnings();
+        if warnings.is_empty() {
+            return "Connectivity audit: no problems found".to_string();
+        }
+        let mut lines = vec![format!("Connectivity audit: {} problems", warnings.len())];
+        lines.extend(warnings.into_iter().map(|w| format!("  {}", w)));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Element, ElementType, Node};
+
+    /// Two separate bars 1-2-3 and 4-5 plus orphan node 6
+    fn bars() -> Mesh {
+        let mut mesh = Mesh::new();
+        for id in 1..=6 {
+            mesh.add_node(Node::new(id, id as f64, 0.0, 0.0));
+        }
+        for (id, nodes) in [(1, vec![1, 2]), (2, vec![2, 3]), (3, vec![4, 5])] {
+            mesh.add_element(Element::new(id, ElementType::T3D2, nodes))
+                .unwrap();
+        }
+        mesh
+    }
+
+    #[test]
+    fn finds_orphans_and_components() {
+        let mut mesh = bars();
+        let audit = mesh.audit();
+        assert_eq!(audit.orphan_nodes, vec![6]);
+        assert!(audit.missing_nodes.is_empty());
+        assert_eq!(audit.components, vec![vec![1, 2], vec![3]]);
+        assert_eq!(audit.warnings().len(), 2);
+
+        // Joining the bars and using node 6 leaves one clean component
+        mesh.add_element(Element::new(4, ElementType::T3D2, vec![3, 4]))
+            .unwrap();
+        mesh.add_element(Element::new(5, ElementType::T3D2, vec![5, 6]))
+            .unwrap();
+        let audit = mesh.audit();
+        assert_eq!(audit.components, vec![vec![1, 2, 3, 4, 5]]);
+        assert!(audit.is_clean());
+        assert_eq!(audit.format(), "Connectivity audit: no problems found");
+    }
+
+    #[test]
+    fn finds_missing_nodes() {
+        let mut mesh = bars();
+        mesh.elements
+            .insert(4, Element::new(4, ElementType::T3D2, vec![6, 9]));
+        let audit = mesh.audit();
+        assert_eq!(audit.missing_nodes[&4], vec![9]);
+        assert!(audit.orphan_nodes.is_empty());
+        assert!(
+            audit
+                .format()
+                .contains("element 4 references undefined nodes 9")
+        );
+    }
+
+    #[test]
+    fn finds_sets_no_card_uses() {
+        let deck = Deck::parse_str(
+            "*NODE, NSET=NALL\n1, 0, 0, 0\n2, 1, 0, 0\n\
+             *ELEMENT, TYPE=T3D2, ELSET=BARS\n1, 1, 2\n\
+             *NSET, NSET=FIX\n1\n*NSET, NSET=SPARE\n2\n\
+             *SOLID SECTION, ELSET=bars, MATERIAL=STEEL\n\
+             *BOUNDARY\nFIX, 1, 3\n",
+        )
+        .unwrap();
+        let mut sets = Sets::build_from_deck(&deck).unwrap();
+        sets.add_card_sets(&deck).unwrap();
+        let audit = MeshAudit::default().with_unused_sets(&sets, &deck);
+        assert_eq!(audit.unused_sets, vec!["SPARE"]);
+        assert!(audit.format().contains("sets not used by any card: SPARE"));
+    }
+}
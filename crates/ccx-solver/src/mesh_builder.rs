@@ -55,12 +55,13 @@ impl MeshBuilder {
 
     /// Process a *NODE card
     fn process_node_card(&mut self, card: &Card) -> Result<(), String> {
-        for data_line in &card.data_lines {
+        for (line_idx, data_line) in card.data_lines.iter().enumerate() {
             let parts: Vec<&str> = data_line.split(',').collect();
 
             if parts.len() < 4 {
                 self.errors.push(format!(
-                    "Invalid node data line (expected at least 4 fields): {}",
+                    "{}: Invalid node data line (expected at least 4 fields): {}",
+                    field_location(card, line_idx, 0),
                     data_line
                 ));
                 continue;
@@ -70,8 +71,11 @@ impl MeshBuilder {
             let id = match parts[0].trim().parse::<i32>() {
                 Ok(id) => id,
                 Err(_) => {
-                    self.errors
-                        .push(format!("Invalid node ID: {}", parts[0].trim()));
+                    self.errors.push(format!(
+                        "{}: Invalid node ID: {}",
+                        field_location(card, line_idx, 0),
+                        parts[0].trim()
+                    ));
                     continue;
                 }
             };
@@ -81,7 +85,8 @@ impl MeshBuilder {
                 Ok(x) => x,
                 Err(_) => {
                     self.errors.push(format!(
-                        "Invalid X coordinate for node {}: {}",
+                        "{}: Invalid X coordinate for node {}: {}",
+                        field_location(card, line_idx, 1),
                         id,
                         parts[1].trim()
                     ));
@@ -93,7 +98,8 @@ impl MeshBuilder {
                 Ok(y) => y,
                 Err(_) => {
                     self.errors.push(format!(
-                        "Invalid Y coordinate for node {}: {}",
+                        "{}: Invalid Y coordinate for node {}: {}",
+                        field_location(card, line_idx, 2),
                         id,
                         parts[2].trim()
                     ));
@@ -105,7 +111,8 @@ impl MeshBuilder {
                 Ok(z) => z,
                 Err(_) => {
                     self.errors.push(format!(
-                        "Invalid Z coordinate for node {}: {}",
+                        "{}: Invalid Z coordinate for node {}: {}",
+                        field_location(card, line_idx, 3),
                         id,
                         parts[3].trim()
                     ));
@@ -143,7 +150,7 @@ impl MeshBuilder {
         let mut current_element_id: Option<i32> = None;
         let mut accumulated_nodes = Vec::new();
 
-        for data_line in &card.data_lines {
+        for (line_idx, data_line) in card.data_lines.iter().enumerate() {
             let parts: Vec<&str> = data_line.split(',').collect();
 
             if parts.is_empty() {
@@ -162,7 +169,7 @@ impl MeshBuilder {
 
             if is_continuation {
                 // Continuation line - all fields are node IDs
-                for node_str in &parts {
+                for (field_idx, node_str) in parts.iter().enumerate() {
                     let node_str = node_str.trim();
                     if node_str.is_empty() {
                         continue;
@@ -172,7 +179,8 @@ impl MeshBuilder {
                         Ok(node_id) => accumulated_nodes.push(node_id),
                         Err(_) => {
                             self.errors.push(format!(
-                                "Invalid node ID in element {}: {}",
+                                "{}: Invalid node ID in element {}: {}",
+                                field_location(card, line_idx, field_idx),
                                 current_element_id.unwrap(),
                                 node_str
                             ));
@@ -206,7 +214,7 @@ impl MeshBuilder {
                         current_element_id = Some(id);
 
                         // Collect node IDs from remaining fields
-                        for node_str in &parts[1..] {
+                        for (field_idx, node_str) in parts.iter().enumerate().skip(1) {
                             let node_str = node_str.trim();
                             if node_str.is_empty() {
                                 continue;
@@ -216,8 +224,10 @@ impl MeshBuilder {
                                 Ok(node_id) => accumulated_nodes.push(node_id),
                                 Err(_) => {
                                     self.errors.push(format!(
-                                        "Invalid node ID in element {}: {}",
-                                        id, node_str
+                                        "{}: Invalid node ID in element {}: {}",
+                                        field_location(card, line_idx, field_idx),
+                                        id,
+                                        node_str
                                     ));
                                 }
                             }
@@ -237,8 +247,11 @@ impl MeshBuilder {
                         }
                     }
                     Err(_) => {
-                        self.errors
-                            .push(format!("Invalid element ID: {}", first_field));
+                        self.errors.push(format!(
+                            "{}: Invalid element ID: {}",
+                            field_location(card, line_idx, 0),
+                            first_field
+                        ));
                     }
                 }
             }
@@ -294,6 +307,14 @@ impl MeshBuilder {
     }
 }
 
+/// `line L:C` location of a data field, for error messages.
+pub(crate) fn field_location(card: &Card, line_idx: usize, field_idx: usize) -> String {
+    match card.data_field_span(line_idx, field_idx) {
+        Some(span) => format!("line {}:{}", span.line, span.column),
+        None => format!("line {}", card.data_line_number(line_idx)),
+    }
+}
+
 impl Default for MeshBuilder {
     fn default() -> Self {
         Self::new()
@@ -381,6 +402,19 @@ mod tests {
         assert_eq!(elem1.nodes, vec![1, 2]);
     }
 
+    #[test]
+    fn reports_exact_field_position_of_malformed_node_data() {
+        let input = "*NODE\n1, 0.0, 0.0, 0.0\n2, 1.0, abc, 0.0\n";
+
+        let deck = parse_deck(input);
+        let err = MeshBuilder::build_from_deck(&deck).expect_err("bad coordinate");
+
+        assert!(
+            err.contains("line 3:9: Invalid Y coordinate for node 2: abc"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn rejects_elements_with_wrong_node_count() {
         let input = r#"
@@ -0,0 +1,153 @@
+//! Merging of coincident nodes.
+//!
+//! Meshes assembled from separately meshed parts, or imported from surface
+//! formats that store every facet on its own, carry several nodes at the
+//! same position. Those nodes are merged into the one with the lowest ID,
+//! and the returned merge map is applied to element connectivity here and to
+//! node sets through [`Sets::apply_node_map`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::mesh::Mesh;
+use crate::sets::Sets;
+
+/// Grid cell of a position for cells of size `cell`
+fn cell_of(coords: [f64; 3], cell: f64) -> [i64; 3] {
+    coords.map(|c| (c / cell).floor() as i64)
+}
+
+impl Mesh {
+    /// Merge nodes closer than `tolerance` and rewrite element connectivity
+    ///
+    /// Nodes are visited in ID order and merged into the lowest kept node
+    /// within the tolerance, so every cluster keeps its lowest ID. Returns
+    /// the map from removed node IDs to the kept ones.
+    pub fn merge_duplicate_nodes(&mut self, tolerance: f64) -> Result<BTreeMap<i32, i32>, String> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(format!("invalid merge tolerance {tolerance}"));
+        }
+        let cell = if tolerance > 0.0 { tolerance } else { 1.0 };
+        let mut ids: Vec<i32> = self.nodes.keys().copied().collect();
+        ids.sort_unstable();
+
+        let mut grid: HashMap<[i64; 3], Vec<i32>> = HashMap::new();
+        let mut merged = BTreeMap::new();
+        for id in ids {
+            let coords = self.nodes[&id].coords();
+            let [i, j, k] = cell_of(coords, cell);
+            let mut target = None;
+            'search: for di in -1..=1 {
+                for dj in -1..=1 {
+                    for dk in -1..=1 {
+                        let Some(kept) = grid.get(&[i + di, j + dj, k + dk]) else {
+                            continue;
+                        };
+                        for &other in kept {
+                            let other_coords = self.nodes[&other].coords();
+                            let distance = (0..3)
+                                .map(|d| (coords[d] - other_coords[d]).powi(2))
+                                .sum::<f64>()
+                                .sqrt();
+                            if distance <= tolerance && target.is_none_or(|t| other < t) {
+                                target = Some(other);
+                                if tolerance == 0.0 {
+                                    break 'search;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            match target {
+                Some(kept) => {
+                    merged.insert(id, kept);
+                }
+                None => grid.entry([i, j, k]).or_default().push(id),
+            }
+        }
+
+        for id in merged.keys() {
+            self.nodes.remove(id);
+        }
+        for element in self.elements.values_mut() {
+            for node in &mut element.nodes {
+                if let Some(&kept) = merged.get(node) {
+                    *node = kept;
+                }
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl Sets {
+    /// Replace node IDs in all node sets by `map` (e.g. the result of
+    /// [`Mesh::merge_duplicate_nodes`]), dropping repeated entries
+    pub fn apply_node_map(&mut self, map: &BTreeMap<i32, i32>) {
+        for set in self.node_sets.values_mut() {
+            let mut seen = HashSet::new();
+            set.nodes = set
+                .nodes
+                .iter()
+                .map(|id| map.get(id).copied().unwrap_or(*id))
+                .filter(|id| seen.insert(*id))
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Element, ElementType, Node};
+    use crate::sets::NodeSet;
+
+    /// Two triangles meshed separately along the shared edge x = 1
+    fn two_parts() -> Mesh {
+        let mut mesh = Mesh::new();
+        let coords = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [1.0 + 1e-9, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [1.0, 1.0 - 1e-9, 0.0],
+        ];
+        for (i, [x, y, z]) in coords.into_iter().enumerate() {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::S3, vec![1, 2, 3]))
+            .unwrap();
+        mesh.add_element(Element::new(2, ElementType::S3, vec![4, 5, 6]))
+            .unwrap();
+        mesh
+    }
+
+    #[test]
+    fn merges_nodes_within_tolerance() {
+        let mut mesh = two_parts();
+        let merged = mesh.merge_duplicate_nodes(1e-6).unwrap();
+        assert_eq!(merged, BTreeMap::from([(4, 2), (6, 3)]));
+        assert_eq!(mesh.nodes.len(), 4);
+        assert_eq!(mesh.elements[&2].nodes, vec![2, 5, 3]);
+        assert!(mesh.validate().is_ok());
+
+        let mut exact = two_parts();
+        assert!(exact.merge_duplicate_nodes(0.0).unwrap().is_empty());
+        assert!(exact.merge_duplicate_nodes(-1.0).is_err());
+        assert!(exact.merge_duplicate_nodes(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn merge_map_rewrites_node_sets() {
+        let mut mesh = two_parts();
+        let mut sets = Sets::new();
+        sets.add_node_set(NodeSet {
+            name: "EDGE".to_string(),
+            nodes: vec![2, 3, 4, 6, 5],
+        });
+        let merged = mesh.merge_duplicate_nodes(1e-6).unwrap();
+        sets.apply_node_map(&merged);
+        assert_eq!(sets.get_nodes("EDGE"), Some(&[2, 3, 5][..]));
+    }
+}
@@ -0,0 +1,183 @@
+//! Mesh partitioning into compact element parts.
+//!
+//! Used by `ccx-cli partition` to split a deck into element sets or part
+//! decks with the nodes they share.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+
+use crate::mesh::Mesh;
+use crate::reordering::Permutation;
+
+/// Elements and nodes of one part of a [`MeshPartition`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshPart {
+    /// Element IDs, sorted
+    pub elements: Vec<i32>,
+    /// Nodes of the elements, sorted
+    pub nodes: Vec<i32>,
+    /// Nodes shared with other parts, sorted
+    pub interface_nodes: Vec<i32>,
+}
+
+/// Split of the elements of a mesh into parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshPartition {
+    pub parts: Vec<MeshPart>,
+}
+
+impl MeshPartition {
+    /// Nodes shared by two or more parts
+    pub fn interface_nodes(&self) -> BTreeSet<i32> {
+        self.parts
+            .iter()
+            .flat_map(|part| part.interface_nodes.iter().copied())
+            .collect()
+    }
+
+    /// Element count of the largest part over the mean element count
+    pub fn imbalance(&self) -> f64 {
+        let largest = self
+            .parts
+            .iter()
+            .map(|p| p.elements.len())
+            .max()
+            .unwrap_or(0);
+        let total: usize = self.parts.iter().map(|p| p.elements.len()).sum();
+        match total {
+            0 => 1.0,
+            total => largest as f64 * self.parts.len() as f64 / total as f64,
+        }
+    }
+}
+
+/// Split the elements of `mesh` into `parts` parts
+///
+/// The graph of elements sharing a node is ordered by reverse Cuthill–McKee
+/// and cut into blocks of similar size, so each part is a compact region of
+/// the mesh with a small interface.
+pub fn partition_mesh(mesh: &Mesh, parts: usize) -> Result<MeshPartition, String> {
+    if parts == 0 {
+        return Err("Mesh partitioning needs at least one part".to_string());
+    }
+    if parts > mesh.elements.len() {
+        return Err(format!(
+            "Cannot split {} elements into {} parts",
+            mesh.elements.len(),
+            parts
+        ));
+    }
+
+    let mut element_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+    element_ids.sort_unstable();
+    let mut node_elements: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    for (i, id) in element_ids.iter().enumerate() {
+        for node in &mesh.elements[id].nodes {
+            node_elements.entry(*node).or_default().push(i);
+        }
+    }
+    let n = element_ids.len();
+    let mut graph = CooMatrix::new(n, n);
+    for (i, id) in element_ids.iter().enumerate() {
+        let mut neighbours: Vec<usize> = mesh.elements[id]
+            .nodes
+            .iter()
+            .flat_map(|node| node_elements[node].iter().copied())
+            .collect();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        for j in neighbours {
+            graph.push(i, j, 1.0);
+        }
+    }
+    let owner = row_owners(&CsrMatrix::from(&graph), parts);
+
+    let mut split = vec![
+        MeshPart {
+            elements: Vec::new(),
+            nodes: Vec::new(),
+            interface_nodes: Vec::new(),
+        };
+        parts
+    ];
+    for (i, id) in element_ids.iter().enumerate() {
+        split[owner[i]].elements.push(*id);
+        split[owner[i]].nodes.extend(&mesh.elements[id].nodes);
+    }
+    for part in &mut split {
+        part.nodes.sort_unstable();
+        part.nodes.dedup();
+        part.interface_nodes = part
+            .nodes
+            .iter()
+            .copied()
+            .filter(|node| {
+                let mut owners = node_elements[node].iter().map(|&i| owner[i]);
+                let first = owners.next();
+                owners.any(|o| Some(o) != first)
+            })
+            .collect();
+    }
+    Ok(MeshPartition { parts: split })
+}
+
+/// Part owning each row of `matrix`: the rows are ordered by reverse
+/// Cuthill–McKee and the order is cut into `parts` blocks of about
+/// nnz / parts entries
+fn row_owners(matrix: &CsrMatrix<f64>, parts: usize) -> Vec<usize> {
+    let order = Permutation::reverse_cuthill_mckee(matrix.pattern());
+    let target = matrix.nnz().div_ceil(parts).max(1);
+    let mut owner = vec![0; matrix.nrows()];
+    let mut rank = 0;
+    let mut filled = 0;
+    for &row in order.order() {
+        if filled >= target && rank + 1 < parts {
+            rank += 1;
+            filled = 0;
+        }
+        owner[row] = rank;
+        filled += matrix.row(row).nnz();
+    }
+    owner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_mesh_assigns_every_element_once() {
+        use crate::mesh::ElementType;
+        use crate::structured_mesh::Division;
+
+        let brick = crate::MeshBuilder::brick(
+            [8.0, 2.0, 2.0],
+            [
+                Division::uniform(16),
+                Division::uniform(2),
+                Division::uniform(2),
+            ],
+            ElementType::C3D8,
+        )
+        .unwrap();
+        let split = partition_mesh(&brick.mesh, 4).unwrap();
+        let mut elements: Vec<i32> = split
+            .parts
+            .iter()
+            .flat_map(|p| p.elements.clone())
+            .collect();
+        elements.sort_unstable();
+        let mut expected: Vec<i32> = brick.mesh.elements.keys().copied().collect();
+        expected.sort_unstable();
+        assert_eq!(elements, expected);
+
+        // A bar cut into slices: the interfaces stay close to three cut
+        // planes of 9 nodes
+        assert!(split.imbalance() < 1.3, "{}", split.imbalance());
+        let interface = split.interface_nodes().len();
+        assert!((3 * 9..=4 * 9).contains(&interface), "{interface}");
+        assert!(split.parts.iter().all(|p| !p.interface_nodes.is_empty()));
+        assert!(partition_mesh(&brick.mesh, 65).is_err());
+    }
+}
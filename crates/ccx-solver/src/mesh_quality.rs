@@ -0,0 +1,470 @@
+//! Element shape quality metrics.
+//!
+//! All metrics are evaluated on the corner nodes, so quadratic elements are
+//! measured by their linear shape:
+//!
+//! - Jacobian ratio: smallest over largest corner Jacobian determinant, 1 for
+//!   a parallelepiped and negative for inverted corners
+//! - aspect ratio: longest over shortest edge
+//! - skew: largest equiangular skew of the faces, 0 for equilateral triangles
+//!   and rectangles up to 1 for degenerate faces
+//! - warpage: largest angle in degrees between the two triangles of a
+//!   quadrilateral face split along either diagonal
+//! - min angle: smallest interior face angle in degrees
+//!
+//! Truss and beam elements have no shape to measure and are skipped.
+
+use std::collections::BTreeMap;
+
+use nalgebra::Vector3;
+
+use crate::mesh::{ElementType, Mesh};
+
+/// Shape metrics of one element
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementQuality {
+    pub jacobian_ratio: f64,
+    pub aspect_ratio: f64,
+    pub skew: f64,
+    pub warpage: f64,
+    pub min_angle: f64,
+}
+
+/// Selects one metric of [`ElementQuality`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityMetric {
+    JacobianRatio,
+    AspectRatio,
+    Skew,
+    Warpage,
+    MinAngle,
+}
+
+impl QualityMetric {
+    pub const ALL: [QualityMetric; 5] = [
+        QualityMetric::JacobianRatio,
+        QualityMetric::AspectRatio,
+        QualityMetric::Skew,
+        QualityMetric::Warpage,
+        QualityMetric::MinAngle,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QualityMetric::JacobianRatio => "Jacobian ratio",
+            QualityMetric::AspectRatio => "Aspect ratio",
+            QualityMetric::Skew => "Skew",
+            QualityMetric::Warpage => "Warpage (deg)",
+            QualityMetric::MinAngle => "Min angle (deg)",
+        }
+    }
+
+    pub fn value(&self, quality: &ElementQuality) -> f64 {
+        match self {
+            QualityMetric::JacobianRatio => quality.jacobian_ratio,
+            QualityMetric::AspectRatio => quality.aspect_ratio,
+            QualityMetric::Skew => quality.skew,
+            QualityMetric::Warpage => quality.warpage,
+            QualityMetric::MinAngle => quality.min_angle,
+        }
+    }
+
+    /// Whether small values mark bad elements
+    pub fn lower_is_worse(&self) -> bool {
+        matches!(self, QualityMetric::JacobianRatio | QualityMetric::MinAngle)
+    }
+}
+
+/// Range and mean of one metric over the measured elements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// Quality of all measurable elements of a mesh
+#[derive(Debug, Clone, Default)]
+pub struct MeshQuality {
+    pub elements: BTreeMap<i32, ElementQuality>,
+    /// Line elements and elements with missing nodes
+    pub skipped: usize,
+}
+
+impl MeshQuality {
+    pub fn summary(&self, metric: QualityMetric) -> Option<MetricSummary> {
+        let values: Vec<f64> = self.elements.values().map(|q| metric.value(q)).collect();
+        if values.is_empty() {
+            return None;
+        }
+        Some(MetricSummary {
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        })
+    }
+
+    /// The `count` worst elements for a metric, worst first
+    pub fn worst(&self, metric: QualityMetric, count: usize) -> Vec<(i32, f64)> {
+        let mut ranked: Vec<(i32, f64)> = self
+            .elements
+            .iter()
+            .map(|(&id, q)| (id, metric.value(q)))
+            .collect();
+        ranked.sort_by(|a, b| {
+            let order = a.1.total_cmp(&b.1);
+            let order = if metric.lower_is_worse() {
+                order
+            } else {
+                order.reverse()
+            };
+            order.then(a.0.cmp(&b.0))
+        });
+        ranked.truncate(count);
+        ranked
+    }
+
+    /// Format the summary table and the `worst` worst elements per metric
+    pub fn format(&self, worst: usize) -> String {
+        let mut lines = vec![format!(
+            "Element quality: {} elements measured, {} skipped",
+            self.elements.len(),
+            self.skipped
+        )];
+        for metric in QualityMetric::ALL {
+            let Some(summary) = self.summary(metric) else {
+                continue;
+            };
+            lines.push(format!(
+                "  {:<16} min {:>10.4} max {:>10.4} mean {:>10.4}",
+                metric.name(),
+                summary.min,
+                summary.max,
+                summary.mean
+            ));
+        }
+        if worst > 0 && !self.elements.is_empty() {
+            lines.push("Worst elements:".to_string());
+            for metric in QualityMetric::ALL {
+                let offenders: Vec<String> = self
+                    .worst(metric, worst)
+                    .iter()
+                    .map(|(id, value)| format!("{} ({:.4})", id, value))
+                    .collect();
+                lines.push(format!("  {}: {}", metric.name(), offenders.join(", ")));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Corner topology of the measurable element shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Hexahedron,
+    Wedge,
+    Tetrahedron,
+    Quadrilateral,
+    Triangle,
+}
+
+impl Shape {
+    fn of(element_type: ElementType) -> Option<Self> {
+        match element_type {
+            ElementType::C3D8 | ElementType::C3D20 => Some(Shape::Hexahedron),
+            ElementType::C3D6 | ElementType::C3D15 => Some(Shape::Wedge),
+            ElementType::C3D4 | ElementType::C3D10 => Some(Shape::Tetrahedron),
+            ElementType::S4 | ElementType::S8 | ElementType::M3D4 | ElementType::M3D8 => {
+                Some(Shape::Quadrilateral)
+            }
+            ElementType::S3 | ElementType::S6 | ElementType::M3D3 | ElementType::M3D6 => {
+                Some(Shape::Triangle)
+            }
+            ElementType::T3D2 | ElementType::B31 | ElementType::B32 => None,
+        }
+    }
+
+    fn corners(&self) -> usize {
+        match self {
+            Shape::Hexahedron => 8,
+            Shape::Wedge => 6,
+            Shape::Tetrahedron => 4,
+            Shape::Quadrilateral => 4,
+            Shape::Triangle => 3,
+        }
+    }
+
+    /// Corner faces, ordered around their boundary
+    fn faces(&self) -> &'static [&'static [usize]] {
+        match self {
+            Shape::Hexahedron => &[
+                &[0, 1, 2, 3],
+                &[4, 5, 6, 7],
+                &[0, 1, 5, 4],
+                &[1, 2, 6, 5],
+                &[2, 3, 7, 6],
+                &[3, 0, 4, 7],
+            ],
+            Shape::Wedge => &[
+                &[0, 1, 2],
+                &[3, 4, 5],
+                &[0, 1, 4, 3],
+                &[1, 2, 5, 4],
+                &[2, 0, 3, 5],
+            ],
+            Shape::Tetrahedron => &[&[0, 1, 2], &[0, 1, 3], &[1, 2, 3], &[0, 2, 3]],
+            Shape::Quadrilateral => &[&[0, 1, 2, 3]],
+            Shape::Triangle => &[&[0, 1, 2]],
+        }
+    }
+
+    /// Jacobian determinants at the corners, up to a constant factor
+    fn corner_jacobians(&self, x: &[Vector3<f64>]) -> Vec<f64> {
+        let det = |corner: usize, a: usize, b: usize, c: usize| {
+            (x[a] - x[corner])
+                .cross(&(x[b] - x[corner]))
+                .dot(&(x[c] - x[corner]))
+        };
+        // Bottom corners span (next, previous, up), top corners
+        // (previous, next, down) to keep the orientation
+        let prism = |n: usize| {
+            (0..n)
+                .map(|i| det(i, (i + 1) % n, (i + n - 1) % n, i + n))
+                .chain((0..n).map(|i| det(i + n, (i + n - 1) % n + n, (i + 1) % n + n, i)))
+                .collect()
+        };
+        match self {
+            Shape::Hexahedron => prism(4),
+            Shape::Wedge => prism(3),
+            Shape::Tetrahedron => vec![det(0, 1, 2, 3)],
+            Shape::Quadrilateral => {
+                let normal = face_normal(x, &[0, 1, 2, 3]);
+                (0..4)
+                    .map(|i| {
+                        (x[(i + 1) % 4] - x[i])
+                            .cross(&(x[(i + 3) % 4] - x[i]))
+                            .dot(&normal)
+                    })
+                    .collect()
+            }
+            Shape::Triangle => vec![1.0],
+        }
+    }
+}
+
+/// Unit normal of a face by Newell's method
+fn face_normal(x: &[Vector3<f64>], face: &[usize]) -> Vector3<f64> {
+    let normal: Vector3<f64> = (0..face.len())
+        .map(|i| x[face[i]].cross(&x[face[(i + 1) % face.len()]]))
+        .sum();
+    normal.try_normalize(0.0).unwrap_or_else(Vector3::zeros)
+}
+
+/// Angle in degrees between two vectors, 0 for degenerate ones
+fn angle(a: &Vector3<f64>, b: &Vector3<f64>) -> f64 {
+    let norm = a.norm() * b.norm();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    (a.dot(b) / norm).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// Interior angles of a face in degrees
+fn face_angles(x: &[Vector3<f64>], face: &[usize]) -> Vec<f64> {
+    let n = face.len();
+    (0..n)
+        .map(|i| {
+            let corner = x[face[i]];
+            angle(
+                &(x[face[(i + 1) % n]] - corner),
+                &(x[face[(i + n - 1) % n]] - corner),
+            )
+        })
+        .collect()
+}
+
+/// Largest angle between the triangle normals of a quadrilateral face
+fn face_warpage(x: &[Vector3<f64>], face: &[usize]) -> f64 {
+    if face.len() != 4 {
+        return 0.0;
+    }
+    let normal =
+        |a: usize, b: usize, c: usize| (x[face[b]] - x[face[a]]).cross(&(x[face[c]] - x[face[a]]));
+    let first = angle(&normal(0, 1, 2), &normal(0, 2, 3));
+    let second = angle(&normal(1, 2, 3), &normal(1, 3, 0));
+    first.max(second)
+}
+
+fn element_quality(shape: Shape, x: &[Vector3<f64>]) -> ElementQuality {
+    let jacobians = shape.corner_jacobians(x);
+    let largest = jacobians.iter().fold(0.0_f64, |m, d| m.max(d.abs()));
+    let smallest = jacobians.iter().copied().fold(f64::INFINITY, f64::min);
+    let jacobian_ratio = if largest > 0.0 {
+        smallest / largest
+    } else {
+        0.0
+    };
+
+    let mut longest = 0.0_f64;
+    let mut shortest = f64::INFINITY;
+    let mut skew = 0.0_f64;
+    let mut warpage = 0.0_f64;
+    let mut min_angle = f64::INFINITY;
+    for face in shape.faces() {
+        for i in 0..face.len() {
+            let length = (x[face[(i + 1) % face.len()]] - x[face[i]]).norm();
+            longest = longest.max(length);
+            shortest = shortest.min(length);
+        }
+        let angles = face_angles(x, face);
+        let ideal = 180.0 * (face.len() - 2) as f64 / face.len() as f64;
+        let max = angles.iter().copied().fold(0.0_f64, f64::max);
+        let min = angles.iter().copied().fold(f64::INFINITY, f64::min);
+        skew = skew.max(((max - ideal) / (180.0 - ideal)).max((ideal - min) / ideal));
+        min_angle = min_angle.min(min);
+        warpage = warpage.max(face_warpage(x, face));
+    }
+    ElementQuality {
+        jacobian_ratio,
+        aspect_ratio: if shortest > 0.0 {
+            longest / shortest
+        } else {
+            f64::INFINITY
+        },
+        skew,
+        warpage,
+        min_angle,
+    }
+}
+
+impl Mesh {
+    /// Shape quality of every solid, shell and membrane element
+    pub fn quality(&self) -> MeshQuality {
+        let mut quality = MeshQuality::default();
+        for (&id, element) in &self.elements {
+            let Some(shape) = Shape::of(element.element_type) else {
+                quality.skipped += 1;
+                continue;
+            };
+            let corners: Option<Vec<Vector3<f64>>> = element
+                .nodes
+                .iter()
+                .take(shape.corners())
+                .map(|n| self.nodes.get(n).map(|node| Vector3::from(node.coords())))
+                .collect();
+            match corners {
+                Some(x) if x.len() == shape.corners() => {
+                    quality.elements.insert(id, element_quality(shape, &x));
+                }
+                _ => quality.skipped += 1,
+            }
+        }
+        quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Element, Node};
+
+    fn mesh(coords: &[[f64; 3]], elements: &[(ElementType, Vec<i32>)]) -> Mesh {
+        let mut mesh = Mesh::new();
+        for (i, c) in coords.iter().enumerate() {
+            mesh.add_node(Node::new(i as i32 + 1, c[0], c[1], c[2]));
+        }
+        for (i, (element_type, nodes)) in elements.iter().enumerate() {
+            mesh.add_element(Element::new(i as i32 + 1, *element_type, nodes.clone()))
+                .unwrap();
+        }
+        mesh
+    }
+
+    fn brick(top: [[f64; 3]; 4]) -> Mesh {
+        let mut coords = vec![
+            [0.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        coords.extend(top);
+        mesh(&coords, &[(ElementType::C3D8, (1..=8).collect())])
+    }
+
+    #[test]
+    fn rectangular_brick_is_ideal() {
+        let quality = brick([
+            [0.0, 0.0, 1.0],
+            [2.0, 0.0, 1.0],
+            [2.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ])
+        .quality();
+        let q = quality.elements[&1];
+        assert!((q.jacobian_ratio - 1.0).abs() < 1e-12);
+        assert!((q.aspect_ratio - 2.0).abs() < 1e-12);
+        assert!(q.skew.abs() < 1e-12);
+        assert!(q.warpage.abs() < 1e-12);
+        assert!((q.min_angle - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn distorted_brick_is_flagged() {
+        // Corner 7 pulled up: warped top face and uneven corner Jacobians
+        let quality = brick([
+            [0.0, 0.0, 1.0],
+            [2.0, 0.0, 1.0],
+            [2.0, 1.0, 2.0],
+            [0.0, 1.0, 1.0],
+        ])
+        .quality();
+        let q = quality.elements[&1];
+        assert!(q.jacobian_ratio > 0.0 && q.jacobian_ratio < 1.0);
+        assert!(q.warpage > 10.0);
+        assert!(q.skew > 0.0);
+
+        // Inverted corner
+        let inverted = brick([
+            [0.0, 0.0, 1.0],
+            [2.0, 0.0, 1.0],
+            [2.0, 1.0, -1.0],
+            [0.0, 1.0, 1.0],
+        ])
+        .quality();
+        assert!(inverted.elements[&1].jacobian_ratio < 0.0);
+    }
+
+    #[test]
+    fn reports_worst_elements_and_skips_beams() {
+        let coords = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [10.0, 0.0, 0.1],
+        ];
+        let quality = mesh(
+            &coords,
+            &[
+                (ElementType::C3D4, vec![1, 2, 3, 4]),
+                (ElementType::S3, vec![1, 2, 5]),
+                (ElementType::B31, vec![1, 2]),
+            ],
+        )
+        .quality();
+        assert_eq!(quality.elements.len(), 2);
+        assert_eq!(quality.skipped, 1);
+        assert_eq!(quality.elements[&1].jacobian_ratio, 1.0);
+        assert!((quality.elements[&1].min_angle - 45.0).abs() < 1e-9);
+
+        let worst = quality.worst(QualityMetric::MinAngle, 1);
+        assert_eq!(worst[0].0, 2);
+        assert_eq!(quality.worst(QualityMetric::AspectRatio, 5)[0].0, 2);
+        let summary = quality.summary(QualityMetric::Skew).unwrap();
+        assert!(summary.min <= summary.mean && summary.mean <= summary.max);
+
+        let report = quality.format(3);
+        assert!(report.contains("2 elements measured, 1 skipped"));
+        assert!(report.contains("Min angle (deg): 2 ("));
+    }
+}
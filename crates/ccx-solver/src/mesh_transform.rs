@@ -0,0 +1,445 @@
+//! Copying and transforming meshes.
+//!
+//! Symmetric or repeated structures are modelled as one segment that is
+//! translated, rotated or mirrored into place. [`Mesh::append`] adds a copy
+//! of another mesh with its IDs moved past the existing ones and returns the
+//! ID tables, which [`Sets::append`] uses to carry over the sets of the
+//! copy. [`Mesh::pattern`] combines both to repeat a segment and optionally
+//! merges the nodes on the interfaces between the copies.
+
+use std::collections::BTreeMap;
+
+use crate::mesh::{ElementType, Mesh};
+use crate::renumber::Renumbering;
+use crate::sets::Sets;
+
+/// Rigid motion or reflection of node coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    Translate([f64; 3]),
+    /// Rotation by `angle` degrees about the axis through `origin`
+    Rotate {
+        origin: [f64; 3],
+        axis: [f64; 3],
+        angle: f64,
+    },
+    /// Reflection in the plane through `point` with normal `normal`
+    Mirror {
+        point: [f64; 3],
+        normal: [f64; 3],
+    },
+}
+
+fn unit(v: [f64; 3]) -> Result<[f64; 3], String> {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length == 0.0 || !length.is_finite() {
+        return Err(format!("Invalid transform direction {:?}", v));
+    }
+    Ok(v.map(|c| c / length))
+}
+
+impl Transform {
+    /// Transformed position of `x`
+    pub fn apply(&self, x: [f64; 3]) -> Result<[f64; 3], String> {
+        match *self {
+            Transform::Translate(offset) => Ok([0, 1, 2].map(|d| x[d] + offset[d])),
+            Transform::Rotate {
+                origin,
+                axis,
+                angle,
+            } => {
+                let u = unit(axis)?;
+                let p = [0, 1, 2].map(|d| x[d] - origin[d]);
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let k = [
+                    u[1] * p[2] - u[2] * p[1],
+                    u[2] * p[0] - u[0] * p[2],
+                    u[0] * p[1] - u[1] * p[0],
+                ];
+                let along = (u[0] * p[0] + u[1] * p[1] + u[2] * p[2]) * (1.0 - cos);
+                Ok([0, 1, 2].map(|d| origin[d] + p[d] * cos + k[d] * sin + u[d] * along))
+            }
+            Transform::Mirror { point, normal } => {
+                let n = unit(normal)?;
+                let distance: f64 = (0..3).map(|d| (x[d] - point[d]) * n[d]).sum();
+                Ok([0, 1, 2].map(|d| x[d] - 2.0 * distance * n[d]))
+            }
+        }
+    }
+
+    pub fn is_mirror(&self) -> bool {
+        matches!(self, Transform::Mirror { .. })
+    }
+}
+
+/// Node order of an element after a reflection that keeps its orientation,
+/// as indices into the original connectivity
+fn mirrored_order(element_type: ElementType) -> Option<&'static [usize]> {
+    match element_type {
+        ElementType::C3D4 => Some(&[0, 2, 1, 3]),
+        ElementType::C3D10 => Some(&[0, 2, 1, 3, 6, 5, 4, 7, 9, 8]),
+        ElementType::C3D6 => Some(&[3, 4, 5, 0, 1, 2]),
+        ElementType::C3D15 => Some(&[3, 4, 5, 0, 1, 2, 9, 10, 11, 6, 7, 8, 12, 13, 14]),
+        ElementType::C3D8 => Some(&[4, 5, 6, 7, 0, 1, 2, 3]),
+        ElementType::C3D20 => Some(&[
+            4, 5, 6, 7, 0, 1, 2, 3, 12, 13, 14, 15, 8, 9, 10, 11, 16, 17, 18, 19,
+        ]),
+        ElementType::S3 | ElementType::M3D3 => Some(&[0, 2, 1]),
+        ElementType::S6 | ElementType::M3D6 => Some(&[0, 2, 1, 5, 4, 3]),
+        ElementType::S4 | ElementType::M3D4 => Some(&[0, 3, 2, 1]),
+        ElementType::S8 | ElementType::M3D8 => Some(&[0, 3, 2, 1, 7, 6, 5, 4]),
+        ElementType::T3D2 | ElementType::B31 | ElementType::B32 => None,
+    }
+}
+
+/// Face number of a face after the element was reordered by
+/// [`mirrored_order`]
+fn mirrored_face(element_type: ElementType, face: usize) -> usize {
+    let swap = match element_type {
+        ElementType::C3D8 | ElementType::C3D20 | ElementType::C3D6 | ElementType::C3D15 => [1, 2],
+        ElementType::C3D4 | ElementType::C3D10 => [2, 4],
+        ElementType::S3 | ElementType::S6 | ElementType::M3D3 | ElementType::M3D6 => [3, 5],
+        ElementType::S4 | ElementType::S8 | ElementType::M3D4 | ElementType::M3D8 => {
+            return match face {
+                3 => 6,
+                4 => 5,
+                5 => 4,
+                6 => 3,
+                other => other,
+            };
+        }
+        _ => return face,
+    };
+    match face {
+        f if f == swap[0] => swap[1],
+        f if f == swap[1] => swap[0],
+        other => other,
+    }
+}
+
+impl Mesh {
+    /// Move all nodes by `transform`
+    ///
+    /// Reflections reorder the element connectivity so that solids keep a
+    /// positive Jacobian and shell normals are mirrored with the geometry;
+    /// surfaces on the mesh then need [`Sets::mirror_surfaces`].
+    pub fn transform(&mut self, transform: &Transform) -> Result<(), String> {
+        let mut moved = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.values() {
+            moved.push((node.id, transform.apply(node.coords())?));
+        }
+        for (id, [x, y, z]) in moved {
+            let node = self.nodes.get_mut(&id).expect("node id from the mesh");
+            (node.x, node.y, node.z) = (x, y, z);
+        }
+        if transform.is_mirror() {
+            for element in self.elements.values_mut() {
+                if let Some(order) = mirrored_order(element.element_type)
+                    && order.len() == element.nodes.len()
+                {
+                    element.nodes = order.iter().map(|&i| element.nodes[i]).collect();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a copy of `other` with node and element IDs moved past the
+    /// highest IDs of this mesh; returns the applied ID tables
+    pub fn append(&mut self, other: &Mesh) -> Result<Renumbering, String> {
+        let node_offset = self.nodes.keys().max().copied().unwrap_or(0);
+        let element_offset = self.elements.keys().max().copied().unwrap_or(0);
+        let offset = |ids: Vec<i32>, by: i32| -> Result<BTreeMap<i32, i32>, String> {
+            ids.into_iter()
+                .map(|id| {
+                    id.checked_add(by)
+                        .map(|new| (id, new))
+                        .ok_or_else(|| format!("ID {} overflows after an offset of {}", id, by))
+                })
+                .collect()
+        };
+        let tables = Renumbering {
+            nodes: offset(other.nodes.keys().copied().collect(), node_offset)?,
+            elements: offset(other.elements.keys().copied().collect(), element_offset)?,
+        };
+        let mut copy = other.clone();
+        tables.apply_to_mesh(&mut copy)?;
+        self.nodes.extend(copy.nodes);
+        self.elements.extend(copy.elements);
+        Ok(tables)
+    }
+
+    /// Repeat this mesh `copies` times, copy `i` being moved by `step`
+    /// applied `i` times, and merge nodes closer than `merge_tolerance`
+    ///
+    /// Sets of the segment are carried over to every copy and joined by
+    /// name, so e.g. a fixed node set of one segment fixes all of them.
+    /// Mirroring with two copies builds a symmetric structure, rotation
+    /// with `n` copies of `360 / n` degrees a cyclic one.
+    pub fn pattern(
+        &self,
+        sets: &Sets,
+        copies: usize,
+        step: &Transform,
+        merge_tolerance: Option<f64>,
+    ) -> Result<(Mesh, Sets), String> {
+        if copies == 0 {
+            return Err("Pattern needs at least one copy".to_string());
+        }
+        let mut mesh = self.clone();
+        let mut all_sets = sets.clone();
+        let mut segment = self.clone();
+        let mut segment_sets = sets.clone();
+        for _ in 1..copies {
+            segment.transform(step)?;
+            if step.is_mirror() {
+                segment_sets.mirror_surfaces(&segment);
+            }
+            let tables = mesh.append(&segment)?;
+            all_sets.append(&segment_sets, &tables);
+        }
+        if let Some(tolerance) = merge_tolerance {
+            let merged = mesh.merge_duplicate_nodes(tolerance)?;
+            all_sets.apply_node_map(&merged);
+        }
+        Ok((mesh, all_sets))
+    }
+}
+
+impl Sets {
+    /// Relabel the element faces of all surfaces after `mesh` was mirrored
+    /// with [`Mesh::transform`], which changes the face numbering
+    pub fn mirror_surfaces(&mut self, mesh: &Mesh) {
+        for surface in self.surfaces.values_mut() {
+            for (element, face) in &mut surface.faces {
+                if let Some(element) = mesh.elements.get(element) {
+                    *face = mirrored_face(element.element_type, *face);
+                }
+            }
+        }
+    }
+
+    /// Add the sets of a mesh copy, renumbered with the `tables` returned by
+    /// [`Mesh::append`]; sets with an existing name are joined
+    pub fn append(&mut self, other: &Sets, tables: &Renumbering) {
+        let mut copy = other.clone();
+        tables.apply_to_sets(&mut copy);
+        for (name, set) in copy.node_sets {
+            match self.node_sets.get_mut(&name) {
+                Some(existing) => existing.nodes.extend(set.nodes),
+                None => self.add_node_set(set),
+            }
+        }
+        for (name, set) in copy.element_sets {
+            match self.element_sets.get_mut(&name) {
+                Some(existing) => existing.elements.extend(set.elements),
+                None => self.add_element_set(set),
+            }
+        }
+        for (name, surface) in copy.surfaces {
+            match self.surfaces.get_mut(&name) {
+                Some(existing) => existing.faces.extend(surface.faces),
+                None => self.add_surface(surface),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Element, Node};
+    use crate::mesh_builder::MeshBuilder;
+    use crate::structured_mesh::Division;
+
+    fn close(a: [f64; 3], b: [f64; 3]) -> bool {
+        (0..3).all(|d| (a[d] - b[d]).abs() < 1e-12)
+    }
+
+    #[test]
+    fn transforms_move_points() {
+        let x = [1.0, 2.0, 3.0];
+        assert_eq!(
+            Transform::Translate([1.0, 0.0, -1.0]).apply(x).unwrap(),
+            [2.0, 2.0, 2.0]
+        );
+        let quarter = Transform::Rotate {
+            origin: [1.0, 0.0, 0.0],
+            axis: [0.0, 0.0, 2.0],
+            angle: 90.0,
+        };
+        assert!(close(quarter.apply(x).unwrap(), [-1.0, 0.0, 3.0]));
+        let mirror = Transform::Mirror {
+            point: [0.0, 0.0, 1.0],
+            normal: [0.0, 0.0, -1.0],
+        };
+        assert!(close(mirror.apply(x).unwrap(), [1.0, 2.0, -1.0]));
+        let degenerate = Transform::Mirror {
+            point: [0.0; 3],
+            normal: [0.0; 3],
+        };
+        assert!(degenerate.apply(x).is_err());
+    }
+
+    #[test]
+    fn mirrored_elements_keep_positive_jacobians() {
+        let mirror = Transform::Mirror {
+            point: [0.0; 3],
+            normal: [1.0, 0.0, 0.0],
+        };
+        for element_type in [ElementType::C3D8, ElementType::C3D20] {
+            let mut brick =
+                MeshBuilder::brick([1.0, 2.0, 3.0], [Division::uniform(1); 3], element_type)
+                    .unwrap()
+                    .mesh;
+            brick.transform(&mirror).unwrap();
+            let quality = brick.quality();
+            assert!((quality.elements[&1].jacobian_ratio - 1.0).abs() < 1e-12);
+            assert!(brick.nodes.values().all(|n| n.x <= 0.0));
+        }
+
+        // Tetrahedron and wedge, mirrored as quadratic elements
+        let mut mesh = Mesh::new();
+        let corners = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ];
+        for (i, [x, y, z]) in corners.into_iter().enumerate() {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D4, vec![1, 2, 3, 4]))
+            .unwrap();
+        mesh.add_element(Element::new(2, ElementType::C3D6, (1..=6).collect()))
+            .unwrap();
+        mesh.convert_to_quadratic().unwrap();
+        let midpoint = |mesh: &Mesh, element: i32, index: usize| {
+            mesh.nodes[&mesh.elements[&element].nodes[index]].coords()
+        };
+        mesh.transform(&mirror).unwrap();
+        let quality = mesh.quality();
+        assert!(quality.elements.values().all(|q| q.jacobian_ratio > 0.0));
+        // Mid-side nodes still follow the CalculiX edge order
+        for (element, edges) in [(1, ElementType::C3D10), (2, ElementType::C3D15)] {
+            let corners = element_corners(edges);
+            for (k, [a, b]) in crate::quadratic::quadratic_edges(edges).iter().enumerate() {
+                let (p, q) = (midpoint(&mesh, element, *a), midpoint(&mesh, element, *b));
+                let expected = [0, 1, 2].map(|d| 0.5 * (p[d] + q[d]));
+                assert!(close(midpoint(&mesh, element, corners + k), expected));
+            }
+        }
+    }
+
+    fn element_corners(element_type: ElementType) -> usize {
+        element_type.num_nodes() - crate::quadratic::quadratic_edges(element_type).len()
+    }
+
+    #[test]
+    fn append_offsets_ids_and_sets() {
+        let segment = MeshBuilder::plate(
+            [1.0, 1.0],
+            [Division::uniform(2), Division::uniform(1)],
+            ElementType::S4,
+        )
+        .unwrap();
+        let mut mesh = segment.mesh.clone();
+        let mut sets = segment.sets.clone();
+        let mut copy = segment.mesh.clone();
+        copy.transform(&Transform::Translate([0.0, 1.0, 0.0]))
+            .unwrap();
+        let tables = mesh.append(&copy).unwrap();
+        sets.append(&segment.sets, &tables);
+        assert_eq!(tables.nodes[&1], 7);
+        assert_eq!(tables.elements[&2], 4);
+        assert_eq!(mesh.nodes.len(), 12);
+        assert_eq!(sets.get_nodes("XMIN"), Some(&[1, 4, 7, 10][..]));
+        assert_eq!(sets.get_elements("EALL"), Some(&[1, 2, 3, 4][..]));
+    }
+
+    #[test]
+    fn pattern_merges_interfaces() {
+        // Three 120 degree sectors of a tube make the closed tube
+        let divisions = [
+            Division::uniform(1),
+            Division::uniform(4),
+            Division::uniform(1),
+        ];
+        let sector =
+            MeshBuilder::cylinder(1.0, 2.0, 1.0, 120.0, divisions, ElementType::C3D8).unwrap();
+        let step = Transform::Rotate {
+            origin: [0.0; 3],
+            axis: [0.0, 0.0, 1.0],
+            angle: 120.0,
+        };
+        let (tube, sets) = sector
+            .mesh
+            .pattern(&sector.sets, 3, &step, Some(1e-9))
+            .unwrap();
+        assert_eq!(tube.elements.len(), 12);
+        assert_eq!(tube.nodes.len(), 2 * 2 * 12);
+        assert_eq!(tube.free_faces().len(), 4 * 12);
+        assert_eq!(sets.get_nodes("INNER").unwrap().len(), 12 * 2);
+        assert!(
+            tube.quality()
+                .elements
+                .values()
+                .all(|q| q.jacobian_ratio > 0.0)
+        );
+
+        // Mirrored halves of a plate meet at x = 0
+        let half = MeshBuilder::plate(
+            [1.0, 1.0],
+            [Division::uniform(2), Division::uniform(2)],
+            ElementType::S4,
+        )
+        .unwrap();
+        let mirror = Transform::Mirror {
+            point: [0.0; 3],
+            normal: [1.0, 0.0, 0.0],
+        };
+        let (plate, _) = half
+            .mesh
+            .pattern(&half.sets, 2, &mirror, Some(1e-9))
+            .unwrap();
+        assert_eq!(plate.nodes.len(), 5 * 3);
+        assert!(
+            plate
+                .quality()
+                .elements
+                .values()
+                .all(|q| q.jacobian_ratio > 0.0)
+        );
+        assert!(half.mesh.pattern(&half.sets, 0, &mirror, None).is_err());
+    }
+
+    #[test]
+    fn mirrored_surfaces_keep_their_faces() {
+        let divisions = [
+            Division::uniform(2),
+            Division::uniform(1),
+            Division::uniform(1),
+        ];
+        let mut half = MeshBuilder::brick([1.0; 3], divisions, ElementType::C3D8).unwrap();
+        half.sets.add_skin_surface(&half.mesh, "SKIN");
+        let mirror = Transform::Mirror {
+            point: [0.0; 3],
+            normal: [1.0, 0.0, 0.0],
+        };
+        let (mesh, sets) = half
+            .mesh
+            .pattern(&half.sets, 2, &mirror, Some(1e-9))
+            .unwrap();
+        let skin = sets.get_surface("SKIN").unwrap();
+        let free: Vec<(i32, usize)> = mesh
+            .free_faces()
+            .iter()
+            .map(|f| (f.element, f.face))
+            .collect();
+        // The surface holds every free face plus the two sides of the interface
+        assert_eq!(skin.len(), free.len() + 2);
+        assert!(free.iter().all(|face| skin.contains(face)));
+    }
+}
@@ -0,0 +1,316 @@
+//! Natural frequencies and mode shapes of a deck.
+//!
+//! The stiffness matrix is assembled as for a linear static solve, with
+//! penalty terms for the `*BOUNDARY` conditions. The mass matrix is lumped:
+//! the mass of every element (see [`crate::MassProperties`]) is split
+//! equally among its nodes and put on their translational DOFs. The modes
+//! closest to the shift, by default the lowest ones, come from
+//! [`ShiftInvertLanczos`]. Constrained DOFs only take part in modes at the
+//! penalty frequency, far above the extracted ones.
+//!
+//! For mass-normalized shapes φᵢ the participation factor of a global
+//! direction r is Γᵢ = φᵢᵀ M r and the effective mass Γᵢ². Summed over all
+//! modes, the effective masses add up to the mass on the free DOFs of that
+//! direction.
+
+use ccx_inp::Deck;
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
+use serde::{Deserialize, Serialize};
+
+use crate::bc_builder::BCBuilder;
+use crate::eigen_solver::{EigenSolver, ShiftInvertLanczos};
+use crate::linear_solver::LinearSolverKind;
+use crate::mass_properties::{ElementSection, element_sections};
+use crate::materials::MaterialLibrary;
+use crate::mesh_builder::MeshBuilder;
+use crate::sets::Sets;
+use crate::sparse_assembly::SparseGlobalSystem;
+
+/// Truss and beam cross-section area of the stiffness matrix, as in the
+/// static pipeline
+const DEFAULT_AREA: f64 = 0.001;
+
+/// Frequency extraction for a deck
+#[derive(Debug, Clone)]
+pub struct ModalSolver {
+    /// Number of modes to extract
+    pub num_modes: usize,
+    /// Shift σ; the modes with ω² closest to it are extracted
+    pub shift: f64,
+    /// Backend factorizing K - σM
+    pub linear_solver: LinearSolverKind,
+}
+
+/// One extracted mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mode {
+    /// 1-based mode number, by increasing eigenvalue
+    pub number: usize,
+    /// Eigenvalue ω²
+    pub eigenvalue: f64,
+    /// Natural frequency in Hz
+    pub frequency: f64,
+    /// Participation factors Γ in x, y and z
+    pub participation: [f64; 3],
+    /// Effective masses Γ² in x, y and z
+    pub effective_mass: [f64; 3],
+    /// Mass-normalized translations of the nodes, by node ID
+    pub shape: Vec<(i32, [f64; 3])>,
+}
+
+/// Modes of a deck
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModalResults {
+    pub modes: Vec<Mode>,
+    /// Lumped mass on the free DOFs of x, y and z
+    pub free_mass: [f64; 3],
+    /// Elements without section, density, thickness or area, which carry
+    /// no mass
+    pub skipped: Vec<i32>,
+    /// Size of the Lanczos basis
+    pub iterations: usize,
+}
+
+impl ModalResults {
+    /// Effective mass of `mode` in `direction` (0 to 2) as a fraction of
+    /// the free mass
+    pub fn effective_mass_fraction(&self, mode: &Mode, direction: usize) -> f64 {
+        match self.free_mass[direction] {
+            0.0 => 0.0,
+            total => mode.effective_mass[direction] / total,
+        }
+    }
+}
+
+/// Number of eigenvalues requested by the `*FREQUENCY` card of `deck`
+pub fn requested_modes(deck: &Deck) -> Option<usize> {
+    let card = deck
+        .cards
+        .iter()
+        .find(|card| card.keyword.eq_ignore_ascii_case("FREQUENCY"))?;
+    card.data_lines
+        .first()?
+        .split(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+impl ModalSolver {
+    pub fn new(num_modes: usize) -> Self {
+        Self {
+            num_modes,
+            shift: 0.0,
+            linear_solver: LinearSolverKind::default(),
+        }
+    }
+
+    /// Extract the modes closest to ω² = `shift`, e.g. a small negative
+    /// value for a model without supports
+    pub fn with_shift(mut self, shift: f64) -> Self {
+        self.shift = shift;
+        self
+    }
+
+    /// Factorize K - σM with `linear_solver`
+    pub fn with_linear_solver(mut self, linear_solver: LinearSolverKind) -> Self {
+        self.linear_solver = linear_solver;
+        self
+    }
+
+    /// Extract the modes of `deck`
+    ///
+    /// Fewer modes than requested are returned when the model has fewer
+    /// free DOFs with mass.
+    pub fn solve(&self, deck: &Deck) -> Result<ModalResults, String> {
+        let mut mesh = MeshBuilder::build_from_deck(deck)?;
+        mesh.calculate_dofs();
+        let bcs = BCBuilder::build_from_deck(deck)?;
+        let mut materials = MaterialLibrary::build_from_deck(deck)?;
+        let default_material = materials
+            .material_names()
+            .first()
+            .cloned()
+            .ok_or("no materials defined")?;
+        for id in mesh.elements.keys() {
+            if materials.get_element_material(*id).is_none() {
+                materials.assign_material(*id, default_material.clone());
+            }
+        }
+        let system = SparseGlobalSystem::assemble(&mesh, &materials, &bcs, DEFAULT_AREA)?;
+
+        // Elements without a section take the material of the stiffness
+        // matrix and, for trusses and beams, its area
+        let mut sets = Sets::build_from_deck(deck)?;
+        sets.add_card_sets(deck)?;
+        let mut sections = element_sections(deck, &sets)?;
+        for id in mesh.elements.keys() {
+            if let Some(material) = materials.get_element_material(*id) {
+                sections.entry(*id).or_insert_with(|| ElementSection {
+                    material: material.name.clone(),
+                    thickness: None,
+                    area: Some(DEFAULT_AREA),
+                });
+            }
+        }
+        let (masses, skipped) = mesh.lumped_masses(&sections, &materials);
+
+        let dofs_per_node = system.dofs_per_node;
+        let mut diagonal = vec![0.0; system.num_dofs];
+        for (&node, &mass) in &masses {
+            let base = (node - 1) as usize * dofs_per_node;
+            for value in diagonal.iter_mut().skip(base).take(3) {
+                *value += mass;
+            }
+        }
+        let mut free = vec![true; system.num_dofs];
+        for &dof in &system.constrained_dofs {
+            free[dof] = false;
+        }
+        let mut free_mass = [0.0; 3];
+        let mut massed_dofs = 0;
+        for (dof, &mass) in diagonal.iter().enumerate() {
+            if free[dof] && mass > 0.0 {
+                massed_dofs += 1;
+                if dof % dofs_per_node < 3 {
+                    free_mass[dof % dofs_per_node] += mass;
+                }
+            }
+        }
+        if massed_dofs == 0 {
+            return Err("no free DOF carries mass; check *DENSITY and the sections".to_string());
+        }
+        let num_modes = self.num_modes.min(massed_dofs);
+        if num_modes < self.num_modes {
+            tracing::warn!(
+                "{} modes requested, but only {} free DOFs carry mass",
+                self.num_modes,
+                num_modes
+            );
+        }
+
+        let n = system.num_dofs;
+        let (dofs, values): (Vec<usize>, Vec<f64>) = diagonal
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| **m != 0.0)
+            .map(|(dof, m)| (dof, *m))
+            .unzip();
+        let mass_matrix = CooMatrix::try_from_triplets(n, n, dofs.clone(), dofs, values)
+            .map_err(|e| format!("Invalid mass triplets: {}", e))?;
+        let mut eigen_solver = ShiftInvertLanczos {
+            linear_solver: self.linear_solver,
+            ..ShiftInvertLanczos::new(self.shift)
+        };
+        let eigen =
+            eigen_solver.solve(&system.stiffness, &CsrMatrix::from(&mass_matrix), num_modes)?;
+
+        let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        let modes = eigen
+            .eigenvalues
+            .iter()
+            .zip(eigen.frequencies())
+            .zip(&eigen.eigenvectors)
+            .enumerate()
+            .map(|(i, ((&eigenvalue, frequency), phi))| {
+                let participation = std::array::from_fn(|direction| {
+                    (0..n)
+                        .filter(|&dof| free[dof] && dof % dofs_per_node == direction)
+                        .map(|dof| diagonal[dof] * phi[dof])
+                        .sum::<f64>()
+                });
+                let shape = node_ids
+                    .iter()
+                    .filter_map(|&id| {
+                        let base = (id - 1) as usize * dofs_per_node;
+                        Some((
+                            id,
+                            [*phi.get(base)?, *phi.get(base + 1)?, *phi.get(base + 2)?],
+                        ))
+                    })
+                    .collect();
+                Mode {
+                    number: i + 1,
+                    eigenvalue,
+                    frequency,
+                    participation,
+                    effective_mass: participation.map(|gamma: f64| gamma * gamma),
+                    shape,
+                }
+            })
+            .collect();
+        Ok(ModalResults {
+            modes,
+            free_mass,
+            skipped,
+            iterations: eigen.iterations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Truss along x fixed at node 1, with node 2 free in x only
+    const SPRING_MASS: &str = "\
+*NODE
+1,0,0,0
+2,100,0,0
+*ELEMENT,TYPE=T3D2,ELSET=BAR
+1,1,2
+*MATERIAL,NAME=STEEL
+*ELASTIC
+210000,0.3
+*DENSITY
+7.85e-9
+*SOLID SECTION,ELSET=BAR,MATERIAL=STEEL
+0.001
+*BOUNDARY
+1,1,3
+2,2,3
+*STEP
+*FREQUENCY
+4
+*END STEP
+";
+
+    #[test]
+    fn spring_mass_frequency_and_effective_mass() {
+        let deck = Deck::parse_str(SPRING_MASS).unwrap();
+        assert_eq!(requested_modes(&deck), Some(4));
+        let results = ModalSolver::new(4).solve(&deck).unwrap();
+
+        // One free DOF with half the bar mass: ω² = (EA/L) / (ρAL/2)
+        assert_eq!(results.modes.len(), 1);
+        let mode = &results.modes[0];
+        let (e, rho, l) = (210000.0, 7.85e-9, 100.0);
+        let omega2 = 2.0 * e / (rho * l * l);
+        assert!((mode.eigenvalue - omega2).abs() <= 1e-6 * omega2);
+        let frequency = omega2.sqrt() / (2.0 * std::f64::consts::PI);
+        assert!((mode.frequency - frequency).abs() <= 1e-6 * frequency);
+        let free_mass = rho * 0.001 * l / 2.0;
+        assert!((results.free_mass[0] - free_mass).abs() <= 1e-12 * free_mass);
+        assert!((results.effective_mass_fraction(mode, 0) - 1.0).abs() < 1e-6);
+        assert_eq!(results.effective_mass_fraction(mode, 1), 0.0);
+        assert!(results.skipped.is_empty());
+    }
+
+    #[test]
+    fn results_round_trip_through_json() {
+        let deck = Deck::parse_str(SPRING_MASS).unwrap();
+        let results = ModalSolver::new(4).solve(&deck).unwrap();
+        let json = serde_json::to_string(&results).unwrap();
+        let restored: ModalResults = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, results);
+    }
+
+    #[test]
+    fn rejects_massless_model() {
+        let deck = Deck::parse_str(&SPRING_MASS.replace("*DENSITY\n7.85e-9\n", "")).unwrap();
+        let err = ModalSolver::new(1).solve(&deck).unwrap_err();
+        assert!(err.contains("carries mass"), "{err}");
+    }
+}
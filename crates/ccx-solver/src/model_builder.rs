@@ -0,0 +1,467 @@
+//! Models defined in code instead of `.inp` text.
+//!
+//! [`ModelBuilder`] collects nodes, elements, sets, materials, constraints
+//! and loads with chained calls and checks them together in
+//! [`ModelBuilder::build`], which returns the [`Mesh`], [`MaterialLibrary`],
+//! [`BoundaryConditions`] and [`AnalysisConfig`] the solver works with.
+//! Mistakes are collected rather than reported one call at a time, so a
+//! failed build lists all of them.
+//!
+//! ```
+//! use ccx_solver::{AnalysisType, ElementType, Material, ModelBuilder};
+//!
+//! let model = ModelBuilder::new()
+//!     .add_node(1, 0.0, 0.0, 0.0)
+//!     .add_node(2, 1.0, 0.0, 0.0)
+//!     .add_element(1, ElementType::T3D2, &[1, 2])
+//!     .material(Material::elastic("STEEL", 210_000.0, 0.3))
+//!     .fix(1, 1, 3)
+//!     .fix(2, 2, 3)
+//!     .load(2, 1, 100.0)
+//!     .step(AnalysisType::LinearStatic)
+//!     .build()
+//!     .unwrap();
+//! let results = model.solve().unwrap();
+//! assert_eq!(results.displacements.len(), 2);
+//! ```
+
+use crate::analysis::{AnalysisConfig, AnalysisPipeline, AnalysisResults, AnalysisType};
+use crate::boundary_conditions::{BoundaryConditions, ConcentratedLoad, DisplacementBC};
+use crate::materials::{Material, MaterialLibrary};
+use crate::mesh::{Element, ElementType, Mesh, Node};
+use crate::sets::{ElementSet, NodeSet, Sets};
+
+/// Cross-section area of truss elements, as used for decks
+const DEFAULT_SECTION_AREA: f64 = 0.001;
+
+/// Solver input of a model built with [`ModelBuilder`]
+#[derive(Debug, Clone)]
+pub struct Model {
+    pub mesh: Mesh,
+    /// Node and element sets, including `NALL` and `EALL`
+    pub sets: Sets,
+    pub materials: MaterialLibrary,
+    pub boundary_conditions: BoundaryConditions,
+    pub config: AnalysisConfig,
+    /// Cross-section area of truss elements
+    pub section_area: f64,
+}
+
+impl Model {
+    /// Run the configured analysis on the model
+    pub fn solve(&self) -> Result<AnalysisResults, String> {
+        AnalysisPipeline::new(self.config.clone()).run_model(self)
+    }
+}
+
+/// Fluent definition of a [`Model`]
+#[derive(Debug, Clone)]
+pub struct ModelBuilder {
+    mesh: Mesh,
+    sets: Sets,
+    materials: Vec<Material>,
+    /// Element set and material name of each section
+    sections: Vec<(String, String)>,
+    boundary_conditions: BoundaryConditions,
+    config: AnalysisConfig,
+    section_area: f64,
+    errors: Vec<String>,
+}
+
+impl Default for ModelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelBuilder {
+    /// Create an empty linear static model
+    pub fn new() -> Self {
+        Self {
+            mesh: Mesh::new(),
+            sets: Sets::new(),
+            materials: Vec::new(),
+            sections: Vec::new(),
+            boundary_conditions: BoundaryConditions::new(),
+            config: AnalysisConfig::default(),
+            section_area: DEFAULT_SECTION_AREA,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Add a node; the solver needs node IDs running from 1 without gaps
+    pub fn add_node(mut self, id: i32, x: f64, y: f64, z: f64) -> Self {
+        if self.mesh.nodes.contains_key(&id) {
+            self.errors.push(format!("Node {} is defined twice", id));
+        }
+        self.mesh.add_node(Node::new(id, x, y, z));
+        self
+    }
+
+    /// Add an element connecting `nodes` in the CalculiX node order
+    pub fn add_element(mut self, id: i32, element_type: ElementType, nodes: &[i32]) -> Self {
+        if self.mesh.elements.contains_key(&id) {
+            self.errors.push(format!("Element {} is defined twice", id));
+        }
+        if let Err(e) = self
+            .mesh
+            .add_element(Element::new(id, element_type, nodes.to_vec()))
+        {
+            self.errors.push(e);
+        }
+        self
+    }
+
+    /// Add a node set, like `*NSET`
+    pub fn node_set(mut self, name: &str, nodes: &[i32]) -> Self {
+        self.sets.add_node_set(NodeSet {
+            name: name.to_string(),
+            nodes: nodes.to_vec(),
+        });
+        self
+    }
+
+    /// Add an element set, like `*ELSET`
+    pub fn element_set(mut self, name: &str, elements: &[i32]) -> Self {
+        self.sets.add_element_set(ElementSet {
+            name: name.to_string(),
+            elements: elements.to_vec(),
+        });
+        self
+    }
+
+    /// Add a material; elements outside every section get the first one
+    pub fn material(mut self, material: Material) -> Self {
+        if self.materials.iter().any(|m| m.name == material.name) {
+            self.errors
+                .push(format!("Material {} is defined twice", material.name));
+        }
+        self.materials.push(material);
+        self
+    }
+
+    /// Assign a material to the elements of a set, like `*SOLID SECTION`
+    pub fn section(mut self, element_set: &str, material: &str) -> Self {
+        self.sections
+            .push((element_set.to_string(), material.to_string()));
+        self
+    }
+
+    /// Cross-section area of truss elements [default: 0.001, as for decks]
+    pub fn section_area(mut self, area: f64) -> Self {
+        self.section_area = area;
+        self
+    }
+
+    /// Fix DOFs `first_dof` to `last_dof` (1-based) of a node
+    pub fn fix(self, node: i32, first_dof: usize, last_dof: usize) -> Self {
+        self.constrain(DisplacementBC::new(node, first_dof, last_dof, 0.0))
+    }
+
+    /// Fix DOFs `first_dof` to `last_dof` of every node of a set
+    pub fn fix_set(mut self, node_set: &str, first_dof: usize, last_dof: usize) -> Self {
+        match self.sets.get_nodes(node_set).map(<[i32]>::to_vec) {
+            Some(nodes) => nodes
+                .into_iter()
+                .fold(self, |builder, node| builder.fix(node, first_dof, last_dof)),
+            None => {
+                self.errors
+                    .push(format!("Node set {} is not defined", node_set));
+                self
+            }
+        }
+    }
+
+    /// Prescribe the displacement of one DOF (1-based) of a node
+    pub fn displace(self, node: i32, dof: usize, value: f64) -> Self {
+        self.constrain(DisplacementBC::new(node, dof, dof, value))
+    }
+
+    fn constrain(mut self, bc: DisplacementBC) -> Self {
+        if bc.first_dof == 0 || bc.first_dof > bc.last_dof || bc.last_dof > 6 {
+            self.errors.push(format!(
+                "Invalid DOF range {} to {} at node {}",
+                bc.first_dof, bc.last_dof, bc.node
+            ));
+        }
+        self.boundary_conditions.add_displacement_bc(bc);
+        self
+    }
+
+    /// Apply a concentrated force or moment to one DOF (1-based) of a node,
+    /// like `*CLOAD`
+    pub fn load(mut self, node: i32, dof: usize, magnitude: f64) -> Self {
+        if dof == 0 || dof > 6 {
+            self.errors
+                .push(format!("Invalid load DOF {} at node {}", dof, node));
+        }
+        self.boundary_conditions
+            .add_concentrated_load(ConcentratedLoad::new(node, dof, magnitude));
+        self
+    }
+
+    /// Analysis type of the model's step
+    pub fn step(mut self, analysis_type: AnalysisType) -> Self {
+        self.config.analysis_type = analysis_type;
+        self
+    }
+
+    /// Use `config` for the analysis; its analysis type replaces the step's
+    pub fn with_config(mut self, config: AnalysisConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Check the model and return the solver input, or every problem found
+    pub fn build(self) -> Result<Model, String> {
+        let ModelBuilder {
+            mut mesh,
+            mut sets,
+            materials,
+            sections,
+            boundary_conditions,
+            config,
+            section_area,
+            mut errors,
+        } = self;
+
+        if let Err(e) = mesh.validate() {
+            errors.push(e);
+        }
+        let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        if node_ids
+            .iter()
+            .zip(1..)
+            .any(|(&id, expected)| id != expected)
+        {
+            errors.push(format!(
+                "Node IDs must run from 1 to {} without gaps",
+                node_ids.len()
+            ));
+        }
+        let mut element_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        element_ids.sort_unstable();
+        for set in sets.element_sets.values() {
+            if let Some(id) = set
+                .elements
+                .iter()
+                .find(|id| !mesh.elements.contains_key(id))
+            {
+                errors.push(format!(
+                    "Element set {} has unknown element {}",
+                    set.name, id
+                ));
+            }
+        }
+        let nodes = boundary_conditions
+            .displacement_bcs
+            .iter()
+            .map(|bc| bc.node)
+            .chain(
+                boundary_conditions
+                    .concentrated_loads
+                    .iter()
+                    .map(|l| l.node),
+            );
+        for node in nodes {
+            if !mesh.nodes.contains_key(&node) {
+                errors.push(format!("Constraint or load on unknown node {}", node));
+            }
+        }
+
+        let mut library = MaterialLibrary::new();
+        for material in &materials {
+            if !material.is_valid_for_structural() {
+                errors.push(format!(
+                    "Material {} needs an elastic modulus and Poisson's ratio",
+                    material.name
+                ));
+            }
+            library.add_material(material.clone());
+        }
+        for (set, material) in &sections {
+            if library.get_material(material).is_none() {
+                errors.push(format!(
+                    "Section on {} uses unknown material {}",
+                    set, material
+                ));
+            }
+            match sets.get_elements(set) {
+                Some(elements) => {
+                    for &id in elements {
+                        library.assign_material(id, material.clone());
+                    }
+                }
+                None => errors.push(format!("Element set {} is not defined", set)),
+            }
+        }
+        match materials.first() {
+            Some(first) => {
+                for &id in &element_ids {
+                    if library.get_element_material(id).is_none() {
+                        library.assign_material(id, first.name.clone());
+                    }
+                }
+            }
+            None if !element_ids.is_empty() => errors.push("No material defined".to_string()),
+            None => {}
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+
+        sets.add_node_set(NodeSet {
+            name: "NALL".to_string(),
+            nodes: node_ids,
+        });
+        sets.add_element_set(ElementSet {
+            name: "EALL".to_string(),
+            elements: element_ids,
+        });
+        mesh.calculate_dofs();
+        Ok(Model {
+            mesh,
+            sets,
+            materials: library,
+            boundary_conditions,
+            config,
+            section_area,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ccx_inp::Deck;
+
+    #[test]
+    fn built_model_solves_like_the_same_deck() {
+        let deck = Deck::parse_str(
+            "*NODE\n1,0,0,0\n2,1,0,0\n3,1,1,0\n*ELEMENT,TYPE=T3D2,ELSET=EALL\n1,1,2\n2,2,3\n\
+             *MATERIAL,NAME=STEEL\n*ELASTIC\n210000,0.3\n\
+             *BOUNDARY\n1,1,3\n3,1,3\n2,3,3\n*STEP\n*STATIC\n*CLOAD\n2,2,-50.\n*END STEP\n",
+        )
+        .unwrap();
+        let expected = AnalysisPipeline::detect_from_deck(&deck)
+            .run(&deck)
+            .unwrap();
+
+        let model = ModelBuilder::new()
+            .add_node(1, 0.0, 0.0, 0.0)
+            .add_node(2, 1.0, 0.0, 0.0)
+            .add_node(3, 1.0, 1.0, 0.0)
+            .add_element(1, ElementType::T3D2, &[1, 2])
+            .add_element(2, ElementType::T3D2, &[2, 3])
+            .element_set("BARS", &[1, 2])
+            .node_set("SUPPORTS", &[1, 3])
+            .material(Material::elastic("STEEL", 210_000.0, 0.3).with_density(7.85e-9))
+            .section("BARS", "STEEL")
+            .fix_set("SUPPORTS", 1, 3)
+            .fix(2, 3, 3)
+            .load(2, 2, -50.0)
+            .step(AnalysisType::LinearStatic)
+            .build()
+            .unwrap();
+        assert_eq!(model.mesh.num_dofs, 9);
+        assert_eq!(model.sets.get_elements("EALL"), Some(&[1, 2][..]));
+        assert_eq!(
+            model.materials.get_element_material(2).unwrap().density,
+            Some(7.85e-9)
+        );
+        let results = model.solve().unwrap();
+        assert_eq!(results.message, expected.message);
+        assert_eq!(results.displacements, expected.displacements);
+        assert!(results.displacements[1].1[1] < 0.0);
+
+        let modal = ModelBuilder::new()
+            .add_node(1, 0.0, 0.0, 0.0)
+            .add_node(2, 1.0, 0.0, 0.0)
+            .add_element(1, ElementType::T3D2, &[1, 2])
+            .material(Material::elastic("STEEL", 210_000.0, 0.3))
+            .step(AnalysisType::Modal)
+            .build()
+            .unwrap();
+        let results = modal.solve().unwrap();
+        assert_eq!(results.analysis_type, AnalysisType::Modal);
+        assert!(results.displacements.is_empty());
+    }
+
+    #[test]
+    fn model_parts_round_trip_through_json() {
+        let model = ModelBuilder::new()
+            .add_node(1, 0.0, 0.0, 0.0)
+            .add_node(2, 1.0, 0.0, 0.0)
+            .add_element(1, ElementType::T3D2, &[1, 2])
+            .material(Material::elastic("STEEL", 210_000.0, 0.3).with_density(7.85e-9))
+            .fix(1, 1, 3)
+            .displace(2, 2, 0.5)
+            .load(2, 1, 100.0)
+            .build()
+            .unwrap();
+
+        let mesh: Mesh =
+            serde_json::from_str(&serde_json::to_string(&model.mesh).unwrap()).unwrap();
+        assert_eq!(mesh.nodes, model.mesh.nodes);
+        assert_eq!(mesh.elements, model.mesh.elements);
+        assert_eq!(mesh.num_dofs, 6);
+        let json = serde_json::to_string(&model.boundary_conditions).unwrap();
+        let bcs: BoundaryConditions = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            bcs.displacement_bcs,
+            model.boundary_conditions.displacement_bcs
+        );
+        assert_eq!(
+            bcs.concentrated_loads,
+            model.boundary_conditions.concentrated_loads
+        );
+        let json = serde_json::to_string(&model.materials).unwrap();
+        let materials: MaterialLibrary = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            materials.get_element_material(1),
+            model.materials.get_element_material(1)
+        );
+        assert_eq!(
+            materials.get_element_material(1).unwrap().density,
+            Some(7.85e-9)
+        );
+    }
+
+    #[test]
+    fn build_lists_every_problem() {
+        let err = ModelBuilder::new()
+            .add_node(1, 0.0, 0.0, 0.0)
+            .add_node(3, 1.0, 0.0, 0.0)
+            .add_element(1, ElementType::T3D2, &[1, 4])
+            .add_element(2, ElementType::C3D8, &[1, 3])
+            .material(Material::new("RUBBER".to_string()))
+            .section("SOLID", "STEEL")
+            .fix(1, 4, 3)
+            .load(5, 1, 1.0)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.lines().collect::<Vec<_>>(),
+            [
+                "Element 2 of type C3D8 has 2 nodes but expected 8",
+                "Invalid DOF range 4 to 3 at node 1",
+                "Element 1 references non-existent node 4",
+                "Node IDs must run from 1 to 2 without gaps",
+                "Constraint or load on unknown node 5",
+                "Material RUBBER needs an elastic modulus and Poisson's ratio",
+                "Section on SOLID uses unknown material STEEL",
+                "Element set SOLID is not defined",
+            ]
+        );
+        assert_eq!(
+            ModelBuilder::new()
+                .add_node(1, 0.0, 0.0, 0.0)
+                .build()
+                .unwrap()
+                .solve()
+                .unwrap_err(),
+            "No elements defined in model"
+        );
+    }
+}
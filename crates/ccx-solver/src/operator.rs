@@ -0,0 +1,356 @@
+//! Matrix-free operators for the iterative solvers.
+//!
+//! Krylov methods only need products K x, so they can run on an
+//! [`ApplyOperator`] instead of an assembled matrix. [`ElementOperator`]
+//! keeps the element stiffness matrices and scatters their products
+//! element by element: memory grows with the number of elements rather
+//! than with the coupling of the global matrix, and no global sparsity
+//! pattern is ever built.
+//!
+//! [`MatrixFreeSystem`] is the matrix-free counterpart of
+//! [`crate::SparseGlobalSystem`]; both apply boundary conditions with the
+//! same penalty terms, so their solutions agree.
+
+use nalgebra::{DMatrix, DVector};
+use nalgebra_sparse::CsrMatrix;
+use rayon::prelude::*;
+
+use crate::assembly::element_stiffness;
+use crate::boundary_conditions::BoundaryConditions;
+use crate::materials::MaterialLibrary;
+use crate::mesh::Mesh;
+use crate::sparse_assembly::{PENALTY, SparseGlobalSystem};
+
+/// A linear operator y = A x, e.g. a stiffness matrix that is never formed.
+pub trait ApplyOperator {
+    /// Number of rows (and columns)
+    fn dim(&self) -> usize;
+
+    /// y = A x
+    fn apply(&self, x: &DVector<f64>, y: &mut DVector<f64>);
+
+    /// Diagonal of A, for Jacobi preconditioning
+    fn diagonal(&self) -> DVector<f64>;
+}
+
+impl ApplyOperator for CsrMatrix<f64> {
+    fn dim(&self) -> usize {
+        self.nrows()
+    }
+
+    fn apply(&self, x: &DVector<f64>, y: &mut DVector<f64>) {
+        for (i, row) in self.row_iter().enumerate() {
+            y[i] = row
+                .col_indices()
+                .iter()
+                .zip(row.values())
+                .map(|(&j, &value)| value * x[j])
+                .sum();
+        }
+    }
+
+    fn diagonal(&self) -> DVector<f64> {
+        DVector::from_iterator(
+            self.nrows(),
+            self.row_iter()
+                .enumerate()
+                .map(|(i, row)| row.get_entry(i).map_or(0.0, |entry| entry.into_value())),
+        )
+    }
+}
+
+/// Stiffness operator applied element by element.
+#[derive(Debug, Clone)]
+pub struct ElementOperator {
+    num_dofs: usize,
+    /// Global DOF indices and stiffness matrix of each element
+    elements: Vec<(Vec<usize>, DMatrix<f64>)>,
+    /// Extra diagonal terms (penalty constraints)
+    diagonal_terms: Vec<(usize, f64)>,
+}
+
+impl ElementOperator {
+    pub fn new(num_dofs: usize) -> Self {
+        Self {
+            num_dofs,
+            elements: Vec::new(),
+            diagonal_terms: Vec::new(),
+        }
+    }
+
+    /// Add one element's stiffness `k_e` acting on the global `dofs`
+    pub fn add_element(&mut self, dofs: Vec<usize>, k_e: DMatrix<f64>) -> Result<(), String> {
+        if k_e.nrows() != dofs.len() || k_e.ncols() != dofs.len() {
+            return Err(format!(
+                "Element matrix is {}x{} for {} DOFs",
+                k_e.nrows(),
+                k_e.ncols(),
+                dofs.len()
+            ));
+        }
+        if let Some(&dof) = dofs.iter().find(|&&dof| dof >= self.num_dofs) {
+            return Err(format!(
+                "DOF index {} out of range (max {})",
+                dof, self.num_dofs
+            ));
+        }
+        self.elements.push((dofs, k_e));
+        Ok(())
+    }
+
+    /// Add `value` to the diagonal entry of `dof`
+    pub fn add_diagonal(&mut self, dof: usize, value: f64) {
+        self.diagonal_terms.push((dof, value));
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Stored values: element matrices plus diagonal terms
+    pub fn stored_values(&self) -> usize {
+        self.elements.iter().map(|(_, k)| k.len()).sum::<usize>() + self.diagonal_terms.len()
+    }
+}
+
+impl ApplyOperator for ElementOperator {
+    fn dim(&self) -> usize {
+        self.num_dofs
+    }
+
+    fn apply(&self, x: &DVector<f64>, y: &mut DVector<f64>) {
+        y.fill(0.0);
+        for (dofs, k_e) in &self.elements {
+            let x_e = DVector::from_iterator(dofs.len(), dofs.iter().map(|&dof| x[dof]));
+            let y_e = k_e * &x_e;
+            for (&dof, value) in dofs.iter().zip(y_e.iter()) {
+                y[dof] += value;
+            }
+        }
+        for &(dof, value) in &self.diagonal_terms {
+            y[dof] += value * x[dof];
+        }
+    }
+
+    fn diagonal(&self) -> DVector<f64> {
+        let mut diagonal = DVector::zeros(self.num_dofs);
+        for (dofs, k_e) in &self.elements {
+            for (i, &dof) in dofs.iter().enumerate() {
+                diagonal[dof] += k_e[(i, i)];
+            }
+        }
+        for &(dof, value) in &self.diagonal_terms {
+            diagonal[dof] += value;
+        }
+        diagonal
+    }
+}
+
+/// Finite element system with a matrix-free stiffness operator
+#[derive(Debug, Clone)]
+pub struct MatrixFreeSystem {
+    pub operator: ElementOperator,
+    /// Global force vector
+    pub force: DVector<f64>,
+    /// Number of degrees of freedom
+    pub num_dofs: usize,
+    /// Constrained DOFs (for boundary conditions)
+    pub constrained_dofs: Vec<usize>,
+}
+
+impl MatrixFreeSystem {
+    /// Compute the element matrices, loads and penalty constraints
+    ///
+    /// Supports the same elements as [`crate::SparseGlobalSystem::assemble`].
+    pub fn assemble(
+        mesh: &Mesh,
+        materials: &MaterialLibrary,
+        bcs: &BoundaryConditions,
+        default_area: f64,
+    ) -> Result<Self, String> {
+        let max_dofs_per_node = mesh
+            .elements
+            .values()
+            .map(|e| e.element_type.dofs_per_node())
+            .max()
+            .unwrap_or(3);
+        let num_dofs = mesh.nodes.len() * max_dofs_per_node;
+
+        let mut elem_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        elem_ids.sort_unstable();
+        let contributions = elem_ids
+            .par_iter()
+            .map(|&elem_id| {
+                element_stiffness(
+                    mesh,
+                    materials,
+                    elem_id,
+                    default_area,
+                    max_dofs_per_node,
+                    None,
+                )
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut operator = ElementOperator::new(num_dofs);
+        for (dofs, k_e) in contributions.into_iter().flatten() {
+            operator.add_element(dofs, k_e)?;
+        }
+
+        let mut force = DVector::zeros(num_dofs);
+        SparseGlobalSystem::assemble_forces_into(&mut force, bcs, max_dofs_per_node)?;
+        let mut constrained_dofs = Vec::new();
+        for bc in &bcs.displacement_bcs {
+            for dof in bc.first_dof..=bc.last_dof {
+                let dof_index = (bc.node - 1) as usize * max_dofs_per_node + (dof - 1);
+                if dof_index >= num_dofs {
+                    return Err(format!(
+                        "BC DOF index {} out of range (max {})",
+                        dof_index, num_dofs
+                    ));
+                }
+                operator.add_diagonal(dof_index, PENALTY);
+                force[dof_index] += PENALTY * bc.value;
+                constrained_dofs.push(dof_index);
+            }
+        }
+
+        Ok(Self {
+            operator,
+            force,
+            num_dofs,
+            constrained_dofs,
+        })
+    }
+
+    /// Solve with Jacobi-preconditioned CG on the element operator
+    pub fn solve_cg(&self, tolerance: f64, max_iterations: usize) -> Result<DVector<f64>, String> {
+        conjugate_gradient(&self.operator, &self.force, tolerance, max_iterations)
+    }
+}
+
+/// Jacobi-preconditioned Conjugate Gradient on any operator.
+///
+/// Stops once `|F - A u| <= tolerance * |F|`. Non-positive diagonal entries
+/// are left unscaled.
+pub fn conjugate_gradient(
+    operator: &dyn ApplyOperator,
+    rhs: &DVector<f64>,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<DVector<f64>, String> {
+    let inv_diag = operator
+        .diagonal()
+        .map(|d| if d > 0.0 { 1.0 / d } else { 1.0 });
+    preconditioned_cg(operator, &inv_diag, rhs, tolerance, max_iterations)
+}
+
+/// CG with a given inverse diagonal, shared with
+/// [`crate::ConjugateGradientSolver`]
+pub(crate) fn preconditioned_cg(
+    operator: &dyn ApplyOperator,
+    inv_diag: &DVector<f64>,
+    rhs: &DVector<f64>,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<DVector<f64>, String> {
+    if rhs.len() != operator.dim() {
+        return Err(format!(
+            "Right-hand side has {} entries, operator has {} rows",
+            rhs.len(),
+            operator.dim()
+        ));
+    }
+    let mut u = DVector::zeros(rhs.len());
+    let mut r = rhs.clone();
+    let f_norm = r.norm();
+    if f_norm == 0.0 {
+        return Ok(u);
+    }
+
+    let mut z = r.component_mul(inv_diag);
+    let mut p = z.clone();
+    let mut kp = DVector::zeros(rhs.len());
+    let mut rz = r.dot(&z);
+    for _ in 0..max_iterations {
+        operator.apply(&p, &mut kp);
+        let pkp = p.dot(&kp);
+        if pkp <= 0.0 || !pkp.is_finite() {
+            return Err(
+                "Stiffness matrix is not positive definite (insufficient boundary conditions?)"
+                    .to_string(),
+            );
+        }
+        let alpha = rz / pkp;
+        u.axpy(alpha, &p, 1.0);
+        r.axpy(-alpha, &kp, 1.0);
+        if r.norm() <= tolerance * f_norm {
+            return Ok(u);
+        }
+        z = r.component_mul(inv_diag);
+        let rz_next = r.dot(&z);
+        p = &z + &p * (rz_next / rz);
+        rz = rz_next;
+    }
+
+    Err(format!(
+        "Conjugate gradient did not converge in {} iterations",
+        max_iterations
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boundary_conditions::{ConcentratedLoad, DisplacementBC};
+    use crate::materials::Material;
+    use crate::mesh::{Element, ElementType, Node};
+
+    #[test]
+    fn element_operator_matches_assembled_matrix() {
+        // Zig-zag truss: 10 bays in the xy-plane, z held at every node
+        let mut mesh = Mesh::new();
+        let mut library = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(210000.0);
+        library.add_material(steel);
+        let mut bcs = BoundaryConditions::new();
+        for id in 1..=22 {
+            let x = ((id - 1) / 2) as f64;
+            let y = ((id - 1) % 2) as f64;
+            mesh.add_node(Node::new(id, x, y, 0.0));
+            bcs.add_displacement_bc(DisplacementBC::new(id, 3, 3, 0.0));
+        }
+        let mut elem_id = 0;
+        for a in 1..=22 {
+            for b in [a + 1, a + 2] {
+                if b <= 22 {
+                    elem_id += 1;
+                    mesh.add_element(Element::new(elem_id, ElementType::T3D2, vec![a, b]))
+                        .unwrap();
+                    library.assign_material(elem_id, "STEEL".to_string());
+                }
+            }
+        }
+        mesh.calculate_dofs();
+        bcs.add_displacement_bc(DisplacementBC::new(1, 1, 2, 0.0));
+        bcs.add_displacement_bc(DisplacementBC::new(2, 1, 2, 0.0));
+        bcs.add_concentrated_load(ConcentratedLoad::new(22, 2, -100.0));
+
+        let sparse = SparseGlobalSystem::assemble(&mesh, &library, &bcs, 0.01).unwrap();
+        let free = MatrixFreeSystem::assemble(&mesh, &library, &bcs, 0.01).unwrap();
+        assert_eq!(free.force, sparse.force);
+        assert_eq!(free.operator.diagonal(), sparse.stiffness.diagonal());
+
+        let x = DVector::from_fn(free.num_dofs, |i, _| (i as f64).sin());
+        let mut y_free = DVector::zeros(free.num_dofs);
+        let mut y_sparse = DVector::zeros(free.num_dofs);
+        free.operator.apply(&x, &mut y_free);
+        sparse.stiffness.apply(&x, &mut y_sparse);
+        assert!((&y_free - &y_sparse).amax() < 1e-9 * y_sparse.amax());
+
+        let u_free = free.solve_cg(1e-12, 5000).unwrap();
+        let u_sparse = sparse.solve().unwrap();
+        assert!((&u_free - &u_sparse).amax() < 1e-8 * u_sparse.amax());
+    }
+}
@@ -0,0 +1,102 @@
+//! Porting status of the legacy units.
+//!
+//! The manifest in `porting_manifest.toml` records, per legacy unit, whether
+//! it is pending, in progress, ported or superseded, the test that checks a
+//! port against the legacy behaviour and free-form notes. The build script
+//! rejects a manifest whose paths are missing from the legacy catalog or
+//! whose ported units name no existing parity test, and generates
+//! [`PORTING_MANIFEST`] and [`PORTED_UNITS`] from it.
+
+use std::fmt;
+
+/// Porting state of a legacy unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PortingStatus {
+    Pending,
+    InProgress,
+    Ported,
+    /// Not ported because the Rust design replaces it
+    Superseded,
+}
+
+impl PortingStatus {
+    /// Status as written in the manifest
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PortingStatus::Pending => "pending",
+            PortingStatus::InProgress => "in-progress",
+            PortingStatus::Ported => "ported",
+            PortingStatus::Superseded => "superseded",
+        }
+    }
+}
+
+impl fmt::Display for PortingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Manifest entry of one legacy unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortingEntry {
+    pub legacy_rel_path: &'static str,
+    pub status: PortingStatus,
+    /// Rust test module or function that checks the port, like
+    /// `ported::compare::tests`
+    pub parity_test: Option<&'static str>,
+    pub notes: &'static str,
+}
+
+include!(concat!(env!("OUT_DIR"), "/porting_manifest.rs"));
+
+/// Manifest entry of `legacy_rel_path`
+pub fn porting_entry(legacy_rel_path: &str) -> Option<&'static PortingEntry> {
+    PORTING_MANIFEST
+        .binary_search_by(|entry| entry.legacy_rel_path.cmp(legacy_rel_path))
+        .ok()
+        .map(|index| &PORTING_MANIFEST[index])
+}
+
+/// Status of `legacy_rel_path`; units missing from the manifest are pending
+pub fn porting_status(legacy_rel_path: &str) -> PortingStatus {
+    porting_entry(legacy_rel_path).map_or(PortingStatus::Pending, |entry| entry.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_is_sorted_and_ported_units_have_parity_tests() {
+        assert!(
+            PORTING_MANIFEST
+                .windows(2)
+                .all(|pair| pair[0].legacy_rel_path < pair[1].legacy_rel_path)
+        );
+        for entry in PORTING_MANIFEST {
+            if entry.status == PortingStatus::Ported {
+                assert!(entry.parity_test.is_some(), "{}", entry.legacy_rel_path);
+            }
+        }
+        let ported: Vec<&str> = PORTING_MANIFEST
+            .iter()
+            .filter(|entry| entry.status == PortingStatus::Ported)
+            .map(|entry| entry.legacy_rel_path)
+            .collect();
+        assert_eq!(ported, PORTED_UNITS);
+    }
+
+    #[test]
+    fn looks_up_status_and_defaults_to_pending() {
+        let nident2 = porting_entry("superseded/nident2.f").unwrap();
+        assert_eq!(nident2.status, PortingStatus::Ported);
+        assert_eq!(
+            nident2.parity_test,
+            Some("ported::nident::tests::nident2_finds_correct_positions")
+        );
+        assert_eq!(porting_status("compare.c"), PortingStatus::Ported);
+        assert_eq!(porting_status("ccx_2.23.c"), PortingStatus::Pending);
+        assert_eq!(PortingStatus::InProgress.to_string(), "in-progress");
+    }
+}
@@ -34,7 +34,8 @@ pub struct IntegrationPointData {
     pub element_id: i32,
     pub point_id: i32,
     pub stress: Option<StressState>,
-    pub strain: Option<StrainState>,
+    pub strain: Option<StrainState>,         // Total strain E
+    pub elastic_strain: Option<StrainState>, // Elastic strain EE, from ME output
     pub peeq: Option<f64>, // Equivalent plastic strain
 }
 
@@ -166,13 +167,17 @@ pub fn read_dat_file<P: AsRef<Path>>(filepath: P) -> Result<Vec<IntegrationPoint
     // Find section headers
     let mut stress_idx: Option<usize> = None;
     let mut strain_idx: Option<usize> = None;
+    let mut elastic_strain_idx: Option<usize> = None;
     let mut peeq_idx: Option<usize> = None;
 
     for (i, parts) in lines.iter().enumerate() {
         let joined = parts.join(" ").to_lowercase();
-        if joined.contains("stresses") && joined.contains("elem") && joined.contains("integ") {
+        let per_point = joined.contains("elem") && joined.contains("integ");
+        if joined.contains("stresses") && per_point {
             stress_idx = Some(i);
-        } else if joined.contains("strains") && joined.contains("elem") && joined.contains("integ") {
+        } else if joined.contains("mechanical strains") && per_point {
+            elastic_strain_idx = Some(i);
+        } else if joined.contains("strains") && per_point {
             strain_idx = Some(i);
         } else if joined.contains("equivalent plastic strain") {
             peeq_idx = Some(i);
@@ -183,15 +188,21 @@ pub fn read_dat_file<P: AsRef<Path>>(filepath: P) -> Result<Vec<IntegrationPoint
         return Err("Stress output S not found in .dat file".to_string());
     }
 
+    // A section ends at the next section header
+    let headers = [stress_idx, strain_idx, elastic_strain_idx, peeq_idx];
+    let section_end = |start: usize| {
+        headers
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&idx| idx > start)
+            .min()
+            .unwrap_or(lines.len())
+    };
+
     // Parse stress data
     let stress_start = stress_idx.unwrap() + 1;
-    let stress_end = if let Some(idx) = strain_idx {
-        idx
-    } else if let Some(idx) = peeq_idx {
-        idx
-    } else {
-        lines.len()
-    };
+    let stress_end = section_end(stress_start - 1);
 
     let mut stress_data: Vec<(i32, i32, StressState)> = Vec::new();
     for i in stress_start..stress_end {
@@ -212,38 +223,20 @@ pub fn read_dat_file<P: AsRef<Path>>(filepath: P) -> Result<Vec<IntegrationPoint
     }
 
     // Parse strain data if available
-    let mut strain_data: Vec<(i32, i32, StrainState)> = Vec::new();
-    if let Some(strain_start_idx) = strain_idx {
-        let strain_start = strain_start_idx + 1;
-        let strain_end = if let Some(idx) = peeq_idx {
-            idx
-        } else {
-            lines.len()
-        };
-
-        for i in strain_start..strain_end {
-            let parts = &lines[i];
-            if parts.len() >= 8 && parts[0].chars().all(|c| c.is_numeric() || c == '-') {
-                let elem_id = parts[0].parse::<i32>().map_err(|e| format!("Parse error: {}", e))?;
-                let pt_id = parts[1].parse::<i32>().map_err(|e| format!("Parse error: {}", e))?;
-                let strain = StrainState {
-                    exx: parts[2].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
-                    eyy: parts[3].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
-                    ezz: parts[4].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
-                    exy: parts[5].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
-                    exz: parts[6].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
-                    eyz: parts[7].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
-                };
-                strain_data.push((elem_id, pt_id, strain));
-            }
-        }
-    }
+    let strain_data = match strain_idx {
+        Some(idx) => parse_strain_rows(&lines[idx + 1..section_end(idx)])?,
+        None => Vec::new(),
+    };
+    let elastic_strain_data = match elastic_strain_idx {
+        Some(idx) => parse_strain_rows(&lines[idx + 1..section_end(idx)])?,
+        None => Vec::new(),
+    };
 
     // Parse PEEQ data if available
     let mut peeq_data: Vec<(i32, i32, f64)> = Vec::new();
     if let Some(peeq_start_idx) = peeq_idx {
         let peeq_start = peeq_start_idx + 1;
-        let peeq_end = lines.len();
+        let peeq_end = section_end(peeq_start_idx);
 
         for i in peeq_start..peeq_end {
             let parts = &lines[i];
@@ -262,6 +255,9 @@ pub fn read_dat_file<P: AsRef<Path>>(filepath: P) -> Result<Vec<IntegrationPoint
         let strain = strain_data.iter()
             .find(|(e, p, _)| *e == elem_id && *p == pt_id)
             .map(|(_, _, s)| s.clone());
+        let elastic_strain = elastic_strain_data.iter()
+            .find(|(e, p, _)| *e == elem_id && *p == pt_id)
+            .map(|(_, _, s)| s.clone());
         let peeq = peeq_data.iter()
             .find(|(e, p, _)| *e == elem_id && *p == pt_id)
             .map(|(_, _, p)| *p);
@@ -271,6 +267,7 @@ pub fn read_dat_file<P: AsRef<Path>>(filepath: P) -> Result<Vec<IntegrationPoint
             point_id: pt_id,
             stress: Some(stress),
             strain,
+            elastic_strain,
             peeq,
         });
     }
@@ -278,6 +275,27 @@ pub fn read_dat_file<P: AsRef<Path>>(filepath: P) -> Result<Vec<IntegrationPoint
     Ok(result)
 }
 
+/// Parse `(elem, integ.pnt., exx, eyy, ezz, exy, exz, eyz)` rows of a strain table
+fn parse_strain_rows(lines: &[Vec<String>]) -> Result<Vec<(i32, i32, StrainState)>, String> {
+    let mut strain_data = Vec::new();
+    for parts in lines {
+        if parts.len() >= 8 && parts[0].chars().all(|c| c.is_numeric() || c == '-') {
+            let elem_id = parts[0].parse::<i32>().map_err(|e| format!("Parse error: {}", e))?;
+            let pt_id = parts[1].parse::<i32>().map_err(|e| format!("Parse error: {}", e))?;
+            let strain = StrainState {
+                exx: parts[2].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
+                eyy: parts[3].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
+                ezz: parts[4].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
+                exy: parts[5].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
+                exz: parts[6].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
+                eyz: parts[7].parse::<f64>().map_err(|e| format!("Parse error: {}", e))?,
+            };
+            strain_data.push((elem_id, pt_id, strain));
+        }
+    }
+    Ok(strain_data)
+}
+
 /// Process integration point data and compute results
 ///
 /// # Arguments
@@ -415,7 +433,7 @@ pub fn write_results<P: AsRef<Path>>(
 
     writeln!(file).map_err(|e| format!("Write error: {}", e))?;
 
-    println!("Results successfully written to file '{}'", output_path.display());
+    tracing::info!("Results written to file '{}'", output_path.display());
 
     Ok(())
 }
@@ -512,6 +530,27 @@ mod tests {
         assert!(eeq < 0.01); // Sanity check
     }
 
+    #[test]
+    fn test_read_dat_file_total_and_mechanical_strains() {
+        let dat = "\
+ stresses (elem, integ.pnt.,sxx,syy,szz,sxy,sxz,syz) for set EALL and time  0.1000000E+01
+          1   1  1.000000E+03  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00
+ strains (elem, integ.pnt.,exx,eyy,ezz,exy,exz,eyz) for set EALL and time  0.1000000E+01
+          1   1  2.000000E-03  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00
+ mechanical strains (elem, integ.pnt.,exx,eyy,ezz,exy,exz,eyz) for set EALL and time  0.1000000E+01
+          1   1  1.500000E-03  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00  0.000000E+00
+";
+        let path = std::env::temp_dir()
+            .join(format!("ccx_postprocess_me_{}.dat", std::process::id()));
+        std::fs::write(&path, dat).unwrap();
+        let data = read_dat_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].strain.as_ref().unwrap().exx, 2.0e-3);
+        assert_eq!(data[0].elastic_strain.as_ref().unwrap().exx, 1.5e-3);
+    }
+
     #[test]
     fn test_process_integration_points() {
         let data = vec![
@@ -526,6 +565,7 @@ mod tests {
                     exx: 0.001, eyy: 0.0, ezz: 0.0,
                     exy: 0.0, exz: 0.0, eyz: 0.0,
                 }),
+                elastic_strain: None,
                 peeq: Some(0.0),
             },
         ];
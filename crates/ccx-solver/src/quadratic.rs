@@ -0,0 +1,223 @@
+//! Conversion of linear elements to their quadratic counterparts.
+//!
+//! Every edge of a linear element gets a mid-side node at its midpoint,
+//! numbered after the highest existing node ID. Edges shared by several
+//! elements, including edges of elements that are already quadratic, get a
+//! single node, so the converted mesh stays conforming. Mid-side nodes are
+//! appended in the CalculiX edge order of the quadratic element.
+
+use std::collections::BTreeMap;
+
+use crate::mesh::{ElementType, Mesh, Node};
+
+/// Quadratic counterpart of a linear element type
+fn quadratic_type(element_type: ElementType) -> Option<ElementType> {
+    match element_type {
+        ElementType::C3D4 => Some(ElementType::C3D10),
+        ElementType::C3D6 => Some(ElementType::C3D15),
+        ElementType::C3D8 => Some(ElementType::C3D20),
+        ElementType::S3 => Some(ElementType::S6),
+        ElementType::S4 => Some(ElementType::S8),
+        ElementType::M3D3 => Some(ElementType::M3D6),
+        ElementType::M3D4 => Some(ElementType::M3D8),
+        ElementType::B31 => Some(ElementType::B32),
+        _ => None,
+    }
+}
+
+/// Corner pairs of the mid-side nodes of a quadratic element, in the order
+/// the mid-side nodes follow the corners
+pub(crate) fn quadratic_edges(element_type: ElementType) -> &'static [[usize; 2]] {
+    match element_type {
+        ElementType::C3D10 => &[[0, 1], [1, 2], [2, 0], [0, 3], [1, 3], [2, 3]],
+        ElementType::C3D15 => &[
+            [0, 1],
+            [1, 2],
+            [2, 0],
+            [3, 4],
+            [4, 5],
+            [5, 3],
+            [0, 3],
+            [1, 4],
+            [2, 5],
+        ],
+        ElementType::C3D20 => &[
+            [0, 1],
+            [1, 2],
+            [2, 3],
+            [3, 0],
+            [4, 5],
+            [5, 6],
+            [6, 7],
+            [7, 4],
+            [0, 4],
+            [1, 5],
+            [2, 6],
+            [3, 7],
+        ],
+        ElementType::S6 | ElementType::M3D6 => &[[0, 1], [1, 2], [2, 0]],
+        ElementType::S8 | ElementType::M3D8 => &[[0, 1], [1, 2], [2, 3], [3, 0]],
+        _ => &[],
+    }
+}
+
+/// Order-independent key of an edge
+fn edge_key(a: i32, b: i32) -> [i32; 2] {
+    if a < b { [a, b] } else { [b, a] }
+}
+
+impl Mesh {
+    /// Convert C3D4, C3D6, C3D8, S3, S4, M3D3, M3D4 and B31 elements to
+    /// C3D10, C3D15, C3D20, S6, S8, M3D6, M3D8 and B32
+    ///
+    /// Element IDs are kept. Returns the mid-side node of every edge of the
+    /// quadratic elements, keyed by its corner node IDs in increasing order.
+    /// Fails without changing the mesh if it does not validate.
+    pub fn convert_to_quadratic(&mut self) -> Result<BTreeMap<[i32; 2], i32>, String> {
+        self.validate()?;
+        for element in self.elements.values() {
+            element.validate()?;
+        }
+
+        let mut midside = BTreeMap::new();
+        for element in self.elements.values() {
+            let nodes = &element.nodes;
+            if element.element_type == ElementType::B32 {
+                midside.insert(edge_key(nodes[0], nodes[2]), nodes[1]);
+            }
+            let edges = quadratic_edges(element.element_type);
+            let corners = nodes.len() - edges.len();
+            for (i, [a, b]) in edges.iter().enumerate() {
+                midside.insert(edge_key(nodes[*a], nodes[*b]), nodes[corners + i]);
+            }
+        }
+
+        let mut next_id = self.nodes.keys().max().copied().unwrap_or(0) + 1;
+        let mut ids: Vec<i32> = self.elements.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let element = &self.elements[&id];
+            let Some(quadratic) = quadratic_type(element.element_type) else {
+                continue;
+            };
+            let edges: &[[usize; 2]] = match quadratic {
+                ElementType::B32 => &[[0, 1]],
+                other => quadratic_edges(other),
+            };
+            let mut mids = Vec::with_capacity(edges.len());
+            for &[a, b] in edges {
+                let (a, b) = (element.nodes[a], element.nodes[b]);
+                let key = edge_key(a, b);
+                if let Some(&node) = midside.get(&key) {
+                    mids.push(node);
+                    continue;
+                }
+                let (first, second) = (self.nodes[&a].coords(), self.nodes[&b].coords());
+                let [x, y, z] = [0, 1, 2].map(|d| 0.5 * (first[d] + second[d]));
+                self.nodes.insert(next_id, Node::new(next_id, x, y, z));
+                midside.insert(key, next_id);
+                mids.push(next_id);
+                next_id += 1;
+            }
+
+            let element = self
+                .elements
+                .get_mut(&id)
+                .expect("element id from the mesh");
+            element.element_type = quadratic;
+            if quadratic == ElementType::B32 {
+                element.nodes.insert(1, mids[0]);
+            } else {
+                element.nodes.extend(mids);
+            }
+        }
+        Ok(midside)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Element;
+
+    /// Unit cube C3D8 (element 1) with a C3D4 (element 2) on its top face
+    fn mixed() -> Mesh {
+        let mut mesh = Mesh::new();
+        let coords = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+            [0.0, 0.0, 2.0],
+        ];
+        for (i, [x, y, z]) in coords.into_iter().enumerate() {
+            mesh.add_node(Node::new(i as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D8, (1..=8).collect()))
+            .unwrap();
+        mesh.add_element(Element::new(2, ElementType::C3D4, vec![5, 6, 8, 9]))
+            .unwrap();
+        mesh
+    }
+
+    #[test]
+    fn shared_edges_get_one_midside_node() {
+        let mut mesh = mixed();
+        let midside = mesh.convert_to_quadratic().unwrap();
+        // 12 hex edges plus 6 tet edges, of which 5-6 and 8-5 are shared
+        assert_eq!(midside.len(), 16);
+        assert_eq!(mesh.nodes.len(), 9 + 16);
+        assert!(mesh.validate().is_ok());
+
+        let hex = &mesh.elements[&1];
+        assert_eq!(hex.element_type, ElementType::C3D20);
+        hex.validate().unwrap();
+        assert_eq!(mesh.nodes[&hex.nodes[8]].coords(), [0.5, 0.0, 0.0]);
+        assert_eq!(mesh.nodes[&hex.nodes[19]].coords(), [0.0, 1.0, 0.5]);
+
+        let tet = &mesh.elements[&2];
+        assert_eq!(tet.element_type, ElementType::C3D10);
+        // Tet edge 5-6 is hex edge 13 (5-6), tet edge 8-5 is hex edge 16
+        assert_eq!(tet.nodes[4], hex.nodes[12]);
+        assert_eq!(tet.nodes[6], hex.nodes[15]);
+        assert_eq!(mesh.nodes[&tet.nodes[7]].coords(), [0.0, 0.0, 1.5]);
+    }
+
+    #[test]
+    fn invalid_mesh_is_left_unchanged() {
+        let mut mesh = mixed();
+        mesh.add_element(Element::new(3, ElementType::S3, vec![1, 2, 10]))
+            .unwrap();
+        assert!(mesh.convert_to_quadratic().is_err());
+        assert_eq!(mesh.nodes.len(), 9);
+        assert_eq!(mesh.elements[&1].element_type, ElementType::C3D8);
+    }
+
+    #[test]
+    fn reuses_existing_midside_nodes_and_converts_shells() {
+        let mut mesh = mixed();
+        mesh.convert_to_quadratic().unwrap();
+        let nodes = mesh.nodes.len();
+        // Already quadratic: nothing to add
+        assert_eq!(mesh.convert_to_quadratic().unwrap().len(), 16);
+        assert_eq!(mesh.nodes.len(), nodes);
+
+        // A shell on the hex bottom face shares its mid-side nodes
+        mesh.add_element(Element::new(3, ElementType::S4, vec![1, 2, 3, 4]))
+            .unwrap();
+        mesh.add_element(Element::new(4, ElementType::B31, vec![1, 9]))
+            .unwrap();
+        mesh.convert_to_quadratic().unwrap();
+        assert_eq!(mesh.nodes.len(), nodes + 1);
+        let shell = &mesh.elements[&3];
+        assert_eq!(shell.element_type, ElementType::S8);
+        assert_eq!(shell.nodes[4..], mesh.elements[&1].nodes[8..12]);
+        let beam = &mesh.elements[&4];
+        assert_eq!(beam.element_type, ElementType::B32);
+        assert_eq!(mesh.nodes[&beam.nodes[1]].coords(), [0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,299 @@
+//! Node and element renumbering.
+//!
+//! DOF numbering follows the node IDs, so decks with sparse IDs (parts
+//! offset by 100000, meshes with deleted nodes) allocate DOFs for IDs that
+//! do not exist. A [`Renumbering`] maps the IDs onto `1..=n`, either keeping
+//! their order or following a reverse Cuthill–McKee ordering of the node
+//! graph, which keeps connected nodes close and the stiffness band narrow.
+//! The same mapping tables are applied to the mesh, the sets and the
+//! boundary conditions so that the model stays consistent.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use nalgebra_sparse::pattern::SparsityPattern;
+
+use crate::boundary_conditions::BoundaryConditions;
+use crate::mesh::Mesh;
+use crate::reordering::{DofOrdering, Permutation};
+use crate::sets::Sets;
+
+/// Mapping tables from old to new node and element IDs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Renumbering {
+    pub nodes: BTreeMap<i32, i32>,
+    pub elements: BTreeMap<i32, i32>,
+}
+
+/// Largest difference of node IDs within one element
+pub fn node_bandwidth(mesh: &Mesh) -> usize {
+    mesh.elements
+        .values()
+        .filter_map(|element| {
+            let min = element.nodes.iter().min()?;
+            let max = element.nodes.iter().max()?;
+            Some(min.abs_diff(*max) as usize)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Node adjacency of the elements, over node indices in ID order
+fn node_graph(mesh: &Mesh, ids: &[i32]) -> SparsityPattern {
+    let index: BTreeMap<i32, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let mut neighbours: Vec<BTreeSet<usize>> =
+        (0..ids.len()).map(|i| BTreeSet::from([i])).collect();
+    for element in mesh.elements.values() {
+        let nodes: Vec<usize> = element
+            .nodes
+            .iter()
+            .filter_map(|id| index.get(id).copied())
+            .collect();
+        for &a in &nodes {
+            neighbours[a].extend(nodes.iter().copied());
+        }
+    }
+    let mut offsets = vec![0];
+    let mut indices = Vec::new();
+    for row in neighbours {
+        indices.extend(row);
+        offsets.push(indices.len());
+    }
+    SparsityPattern::try_from_offsets_and_indices(ids.len(), ids.len(), offsets, indices)
+        .expect("sorted, unique neighbour lists")
+}
+
+impl Renumbering {
+    /// Number nodes and elements from 1 in the given ordering
+    ///
+    /// [`DofOrdering::Natural`] compacts the IDs in their current order.
+    /// [`DofOrdering::ReverseCuthillMcKee`] orders the nodes for a small
+    /// bandwidth and the elements by their lowest new node ID.
+    pub fn new(mesh: &Mesh, ordering: DofOrdering) -> Self {
+        let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        let order = match ordering {
+            DofOrdering::Natural => Permutation::identity(node_ids.len()),
+            DofOrdering::ReverseCuthillMcKee => {
+                Permutation::reverse_cuthill_mckee(&node_graph(mesh, &node_ids))
+            }
+        };
+        let nodes: BTreeMap<i32, i32> = order
+            .order()
+            .iter()
+            .enumerate()
+            .map(|(new, &old)| (node_ids[old], new as i32 + 1))
+            .collect();
+
+        let mut element_ids: Vec<(i32, i32)> = mesh
+            .elements
+            .values()
+            .map(|element| {
+                let key = match ordering {
+                    DofOrdering::Natural => 0,
+                    DofOrdering::ReverseCuthillMcKee => element
+                        .nodes
+                        .iter()
+                        .filter_map(|id| nodes.get(id).copied())
+                        .min()
+                        .unwrap_or(i32::MAX),
+                };
+                (key, element.id)
+            })
+            .collect();
+        element_ids.sort_unstable();
+        let elements = element_ids
+            .into_iter()
+            .enumerate()
+            .map(|(new, (_, old))| (old, new as i32 + 1))
+            .collect();
+        Self { nodes, elements }
+    }
+
+    fn node(&self, id: i32) -> i32 {
+        self.nodes.get(&id).copied().unwrap_or(id)
+    }
+
+    fn element(&self, id: i32) -> i32 {
+        self.elements.get(&id).copied().unwrap_or(id)
+    }
+
+    /// Renumber nodes, elements and element connectivity
+    ///
+    /// IDs missing from the tables are kept; the mesh is left unchanged if
+    /// they collide with a new ID.
+    pub fn apply_to_mesh(&self, mesh: &mut Mesh) -> Result<(), String> {
+        let mut nodes = HashMap::with_capacity(mesh.nodes.len());
+        for (&id, node) in &mesh.nodes {
+            let mut node = node.clone();
+            node.id = self.node(id);
+            if nodes.insert(node.id, node).is_some() {
+                return Err(format!(
+                    "Renumbering maps two nodes to ID {}",
+                    self.node(id)
+                ));
+            }
+        }
+        let mut elements = HashMap::with_capacity(mesh.elements.len());
+        for (&id, element) in &mesh.elements {
+            let mut element = element.clone();
+            element.id = self.element(id);
+            for node in &mut element.nodes {
+                *node = self.node(*node);
+            }
+            if elements.insert(element.id, element).is_some() {
+                return Err(format!(
+                    "Renumbering maps two elements to ID {}",
+                    self.element(id)
+                ));
+            }
+        }
+        mesh.nodes = nodes;
+        mesh.elements = elements;
+        Ok(())
+    }
+
+    /// Renumber the members of all node and element sets and surfaces
+    pub fn apply_to_sets(&self, sets: &mut Sets) {
+        for set in sets.node_sets.values_mut() {
+            for node in &mut set.nodes {
+                *node = self.node(*node);
+            }
+        }
+        for set in sets.element_sets.values_mut() {
+            for element in &mut set.elements {
+                *element = self.element(*element);
+            }
+        }
+        for surface in sets.surfaces.values_mut() {
+            for (element, _) in &mut surface.faces {
+                *element = self.element(*element);
+            }
+        }
+    }
+
+    /// Renumber constrained and loaded nodes, and distributed loads given
+    /// by element ID rather than set name
+    pub fn apply_to_boundary_conditions(&self, bcs: &mut BoundaryConditions) {
+        for bc in &mut bcs.displacement_bcs {
+            bc.node = self.node(bc.node);
+        }
+        for load in &mut bcs.concentrated_loads {
+            load.node = self.node(load.node);
+        }
+        for load in &mut bcs.distributed_loads {
+            if let Ok(id) = load.element.trim().parse::<i32>() {
+                load.element = self.element(id).to_string();
+            }
+        }
+    }
+
+    /// Whether the tables leave every ID unchanged
+    pub fn is_identity(&self) -> bool {
+        let unchanged = |map: &BTreeMap<i32, i32>| map.iter().all(|(old, new)| old == new);
+        unchanged(&self.nodes) && unchanged(&self.elements)
+    }
+
+    /// Inverse tables, mapping new IDs back to the original ones
+    pub fn inverse(&self) -> Self {
+        let invert = |map: &BTreeMap<i32, i32>| map.iter().map(|(&old, &new)| (new, old)).collect();
+        Self {
+            nodes: invert(&self.nodes),
+            elements: invert(&self.elements),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boundary_conditions::{
+        ConcentratedLoad, DisplacementBC, DistributedLoad, DistributedLoadType,
+    };
+    use crate::mesh::{Element, ElementType, Node};
+    use crate::sets::{ElementSet, NodeSet};
+
+    /// Truss chain 500 - 10 - 7 - 30 with scattered node and element IDs
+    fn chain() -> Mesh {
+        let mut mesh = Mesh::new();
+        for (i, id) in [500, 10, 7, 30].into_iter().enumerate() {
+            mesh.add_node(Node::new(id, i as f64, 0.0, 0.0));
+        }
+        for (id, nodes) in [(40, vec![500, 10]), (20, vec![10, 7]), (90, vec![7, 30])] {
+            mesh.add_element(Element::new(id, ElementType::T3D2, nodes))
+                .unwrap();
+        }
+        mesh
+    }
+
+    #[test]
+    fn compacts_ids_in_order() {
+        let mesh = chain();
+        let renumbering = Renumbering::new(&mesh, DofOrdering::Natural);
+        assert_eq!(
+            renumbering.nodes,
+            BTreeMap::from([(7, 1), (10, 2), (30, 3), (500, 4)])
+        );
+        assert_eq!(
+            renumbering.elements,
+            BTreeMap::from([(20, 1), (40, 2), (90, 3)])
+        );
+        assert_eq!(renumbering.inverse().nodes[&4], 500);
+        assert!(!renumbering.is_identity());
+
+        let mut compact = mesh.clone();
+        renumbering.apply_to_mesh(&mut compact).unwrap();
+        assert!(Renumbering::new(&compact, DofOrdering::Natural).is_identity());
+    }
+
+    #[test]
+    fn bandwidth_ordering_follows_the_chain() {
+        let mut mesh = chain();
+        assert_eq!(node_bandwidth(&mesh), 490);
+        let renumbering = Renumbering::new(&mesh, DofOrdering::ReverseCuthillMcKee);
+        renumbering.apply_to_mesh(&mut mesh).unwrap();
+        assert_eq!(node_bandwidth(&mesh), 1);
+        assert!(mesh.validate().is_ok());
+        // Elements follow the new node order along the chain
+        let mut ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2, 3]);
+        let first = &mesh.elements[&1].nodes;
+        assert!(first.contains(&1));
+    }
+
+    #[test]
+    fn applies_tables_to_sets_and_boundary_conditions() {
+        let mesh = chain();
+        let renumbering = Renumbering::new(&mesh, DofOrdering::Natural);
+
+        let mut sets = Sets::new();
+        sets.add_node_set(NodeSet {
+            name: "FIX".to_string(),
+            nodes: vec![500, 30],
+        });
+        sets.add_element_set(ElementSet {
+            name: "EALL".to_string(),
+            elements: vec![40, 20, 90],
+        });
+        renumbering.apply_to_sets(&mut sets);
+        assert_eq!(sets.get_nodes("FIX"), Some(&[4, 3][..]));
+        assert_eq!(sets.get_elements("EALL"), Some(&[2, 1, 3][..]));
+
+        let mut bcs = BoundaryConditions::new();
+        bcs.add_displacement_bc(DisplacementBC::new(500, 1, 3, 0.0));
+        bcs.add_concentrated_load(ConcentratedLoad::new(30, 1, 10.0));
+        for element in ["90", "EALL"] {
+            bcs.add_distributed_load(DistributedLoad {
+                element: element.to_string(),
+                load_type: DistributedLoadType::Gravity,
+                magnitude: 9.81,
+                parameters: vec![0.0, 0.0, -1.0],
+            });
+        }
+        renumbering.apply_to_boundary_conditions(&mut bcs);
+        assert_eq!(bcs.displacement_bcs[0].node, 4);
+        assert_eq!(bcs.concentrated_loads[0].node, 3);
+        assert_eq!(bcs.distributed_loads[0].element, "3");
+        assert_eq!(bcs.distributed_loads[1].element, "EALL");
+    }
+}
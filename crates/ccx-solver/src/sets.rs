@@ -1,7 +1,9 @@
-//! Node sets and element sets for grouping entities.
+//! Node sets, element sets and element face surfaces for grouping entities.
 
+use crate::mesh::{ElementType, Mesh};
+use crate::quadratic::quadratic_edges;
 use ccx_inp::{Card, Deck};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// A named set of nodes
 #[derive(Debug, Clone)]
@@ -21,6 +23,15 @@ pub struct ElementSet {
     pub elements: Vec<i32>,
 }
 
+/// A named set of element faces, like an element based *SURFACE
+#[derive(Debug, Clone)]
+pub struct Surface {
+    /// Surface name
+    pub name: String,
+    /// Element ID and face number of the `S<n>` face label
+    pub faces: Vec<(i32, usize)>,
+}
+
 /// Collection of all sets in the model
 #[derive(Debug, Clone)]
 pub struct Sets {
@@ -28,6 +39,8 @@ pub struct Sets {
     pub node_sets: HashMap<String, NodeSet>,
     /// Element sets by name
     pub element_sets: HashMap<String, ElementSet>,
+    /// Element face surfaces by name
+    pub surfaces: HashMap<String, Surface>,
 }
 
 impl Sets {
@@ -36,6 +49,7 @@ impl Sets {
         Self {
             node_sets: HashMap::new(),
             element_sets: HashMap::new(),
+            surfaces: HashMap::new(),
         }
     }
 
@@ -49,6 +63,11 @@ impl Sets {
         self.element_sets.insert(set.name.clone(), set);
     }
 
+    /// Add an element face surface
+    pub fn add_surface(&mut self, surface: Surface) {
+        self.surfaces.insert(surface.name.clone(), surface);
+    }
+
     /// Get nodes from a node set by name
     pub fn get_nodes(&self, set_name: &str) -> Option<&[i32]> {
         self.node_sets.get(set_name).map(|s| s.nodes.as_slice())
@@ -61,6 +80,42 @@ impl Sets {
             .map(|s| s.elements.as_slice())
     }
 
+    /// Get the faces of a surface by name
+    pub fn get_surface(&self, name: &str) -> Option<&[(i32, usize)]> {
+        self.surfaces.get(name).map(|s| s.faces.as_slice())
+    }
+
+    /// Format the names and sizes of all sets, one set per line
+    pub fn inventory(&self) -> String {
+        let mut lines = vec![format!(
+            "Sets: {} node sets, {} element sets, {} surfaces",
+            self.node_sets.len(),
+            self.element_sets.len(),
+            self.surfaces.len()
+        )];
+        let mut entries: Vec<(&str, &str, usize, &str)> = Vec::new();
+        entries.extend(
+            self.node_sets
+                .values()
+                .map(|s| ("NSET", s.name.as_str(), s.nodes.len(), "nodes")),
+        );
+        entries.extend(
+            self.element_sets
+                .values()
+                .map(|s| ("ELSET", s.name.as_str(), s.elements.len(), "elements")),
+        );
+        entries.extend(
+            self.surfaces
+                .values()
+                .map(|s| ("SURFACE", s.name.as_str(), s.faces.len(), "faces")),
+        );
+        entries.sort();
+        for (kind, name, count, unit) in entries {
+            lines.push(format!("  {} {}: {} {}", kind, name, count, unit));
+        }
+        lines.join("\n")
+    }
+
     /// Build sets from a deck
     pub fn build_from_deck(deck: &Deck) -> Result<Self, String> {
         let mut sets = Self::new();
@@ -84,6 +139,81 @@ impl Sets {
         Ok(sets)
     }
 
+    /// Add the sets named by the NSET parameter of *NODE cards and the ELSET
+    /// parameter of *ELEMENT cards
+    ///
+    /// [`Sets::build_from_deck`] only reads *NSET and *ELSET cards. Elements
+    /// of a type unknown to the mesh are skipped.
+    pub fn add_card_sets(&mut self, deck: &Deck) -> Result<(), String> {
+        for card in &deck.cards {
+            let param = |key: &str| {
+                card.parameters
+                    .iter()
+                    .find(|p| p.key.to_uppercase() == key)
+                    .and_then(|p| p.value.clone())
+            };
+            match card.keyword.to_uppercase().as_str() {
+                "NODE" => {
+                    let Some(name) = param("NSET") else {
+                        continue;
+                    };
+                    // One node per line: ID followed by its coordinates
+                    let mut nodes = Vec::new();
+                    for line in &card.data_lines {
+                        let first = line.split(',').next().unwrap_or("").trim();
+                        if first.is_empty() {
+                            continue;
+                        }
+                        match first.parse::<i32>() {
+                            Ok(node_id) => nodes.push(node_id),
+                            Err(_) => {
+                                return Err(format!("Invalid node ID in NSET {}: {}", name, first));
+                            }
+                        }
+                    }
+                    match self.node_sets.get_mut(&name) {
+                        Some(set) => set.nodes.extend(nodes),
+                        None => self.add_node_set(NodeSet { name, nodes }),
+                    }
+                }
+                "ELEMENT" => {
+                    let (Some(name), Some(type_name)) = (param("ELSET"), param("TYPE")) else {
+                        continue;
+                    };
+                    let Some(element_type) = ElementType::from_calculix_type(&type_name) else {
+                        continue;
+                    };
+                    // Element ID followed by its nodes, possibly over several lines
+                    let fields: Vec<&str> = card
+                        .data_lines
+                        .iter()
+                        .flat_map(|line| line.split(','))
+                        .map(str::trim)
+                        .filter(|field| !field.is_empty())
+                        .collect();
+                    let mut elements = Vec::new();
+                    for row in fields.chunks(element_type.num_nodes() + 1) {
+                        match row[0].parse::<i32>() {
+                            Ok(elem_id) => elements.push(elem_id),
+                            Err(_) => {
+                                return Err(format!(
+                                    "Invalid element ID in ELSET {}: {}",
+                                    name, row[0]
+                                ));
+                            }
+                        }
+                    }
+                    match self.element_sets.get_mut(&name) {
+                        Some(set) => set.elements.extend(elements),
+                        None => self.add_element_set(ElementSet { name, elements }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Parse a *NSET card
     fn parse_nset(card: &Card) -> Result<Option<NodeSet>, String> {
         // Get the NSET parameter
@@ -159,6 +289,159 @@ impl Sets {
     }
 }
 
+/// Sorted IDs of `a` combined with `b` by `keep(in_a, in_b)`
+fn combine(a: &[i32], b: &[i32], keep: impl Fn(bool, bool) -> bool) -> Vec<i32> {
+    let a: BTreeSet<i32> = a.iter().copied().collect();
+    let b: BTreeSet<i32> = b.iter().copied().collect();
+    a.union(&b)
+        .copied()
+        .filter(|id| keep(a.contains(id), b.contains(id)))
+        .collect()
+}
+
+impl NodeSet {
+    /// Nodes in this set or in `other`
+    pub fn union(&self, other: &NodeSet, name: &str) -> NodeSet {
+        NodeSet {
+            name: name.to_string(),
+            nodes: combine(&self.nodes, &other.nodes, |a, b| a || b),
+        }
+    }
+
+    /// Nodes in both this set and `other`
+    pub fn intersection(&self, other: &NodeSet, name: &str) -> NodeSet {
+        NodeSet {
+            name: name.to_string(),
+            nodes: combine(&self.nodes, &other.nodes, |a, b| a && b),
+        }
+    }
+
+    /// Nodes in this set but not in `other`
+    pub fn difference(&self, other: &NodeSet, name: &str) -> NodeSet {
+        NodeSet {
+            name: name.to_string(),
+            nodes: combine(&self.nodes, &other.nodes, |a, b| a && !b),
+        }
+    }
+
+    /// Nodes of `mesh` that are not in this set
+    pub fn complement(&self, mesh: &Mesh, name: &str) -> NodeSet {
+        let all: Vec<i32> = mesh.nodes.keys().copied().collect();
+        NodeSet {
+            name: name.to_string(),
+            nodes: combine(&all, &self.nodes, |a, b| a && !b),
+        }
+    }
+}
+
+impl ElementSet {
+    /// Elements in this set or in `other`
+    pub fn union(&self, other: &ElementSet, name: &str) -> ElementSet {
+        ElementSet {
+            name: name.to_string(),
+            elements: combine(&self.elements, &other.elements, |a, b| a || b),
+        }
+    }
+
+    /// Elements in both this set and `other`
+    pub fn intersection(&self, other: &ElementSet, name: &str) -> ElementSet {
+        ElementSet {
+            name: name.to_string(),
+            elements: combine(&self.elements, &other.elements, |a, b| a && b),
+        }
+    }
+
+    /// Elements in this set but not in `other`
+    pub fn difference(&self, other: &ElementSet, name: &str) -> ElementSet {
+        ElementSet {
+            name: name.to_string(),
+            elements: combine(&self.elements, &other.elements, |a, b| a && !b),
+        }
+    }
+
+    /// Elements of `mesh` that are not in this set
+    pub fn complement(&self, mesh: &Mesh, name: &str) -> ElementSet {
+        let all: Vec<i32> = mesh.elements.keys().copied().collect();
+        ElementSet {
+            name: name.to_string(),
+            elements: combine(&all, &self.elements, |a, b| a && !b),
+        }
+    }
+
+    /// Elements of this set looked up in `mesh`, as a mesh of their own
+    fn submesh(&self, mesh: &Mesh) -> Result<Mesh, String> {
+        let mut submesh = Mesh::new();
+        submesh.nodes = mesh.nodes.clone();
+        for id in &self.elements {
+            let element = mesh
+                .elements
+                .get(id)
+                .ok_or_else(|| format!("Element {} of set {} is not in the mesh", id, self.name))?;
+            submesh.elements.insert(*id, element.clone());
+        }
+        Ok(submesh)
+    }
+
+    /// Nodes of the elements in this set
+    pub fn nodes(&self, mesh: &Mesh, name: &str) -> Result<NodeSet, String> {
+        let submesh = self.submesh(mesh)?;
+        let nodes: BTreeSet<i32> = submesh
+            .elements
+            .values()
+            .flat_map(|element| element.nodes.iter().copied())
+            .collect();
+        Ok(NodeSet {
+            name: name.to_string(),
+            nodes: nodes.into_iter().collect(),
+        })
+    }
+
+    /// Nodes on the boundary of the region formed by the elements in this set
+    ///
+    /// The boundary of solid elements are their free faces, the boundary of
+    /// shell and membrane elements their free edges; faces and edges shared
+    /// with elements outside the set count as boundary too.
+    pub fn boundary_nodes(&self, mesh: &Mesh, name: &str) -> Result<NodeSet, String> {
+        let submesh = self.submesh(mesh)?;
+        let mut nodes: BTreeSet<i32> = submesh
+            .free_faces()
+            .into_iter()
+            .flat_map(|face| face.nodes)
+            .collect();
+
+        let mut edges: HashMap<[i32; 2], (usize, Vec<i32>)> = HashMap::new();
+        for element in submesh.elements.values() {
+            let corners = match element.element_type {
+                ElementType::S3 | ElementType::S6 | ElementType::M3D3 | ElementType::M3D6 => 3,
+                ElementType::S4 | ElementType::S8 | ElementType::M3D4 | ElementType::M3D8 => 4,
+                _ => continue,
+            };
+            if element.nodes.len() != element.element_type.num_nodes() {
+                continue;
+            }
+            let mids = quadratic_edges(element.element_type);
+            for i in 0..corners {
+                let (a, b) = (element.nodes[i], element.nodes[(i + 1) % corners]);
+                let mut edge_nodes = vec![a, b];
+                if !mids.is_empty() {
+                    edge_nodes.push(element.nodes[corners + i]);
+                }
+                let key = if a < b { [a, b] } else { [b, a] };
+                edges.entry(key).or_insert((0, edge_nodes)).0 += 1;
+            }
+        }
+        for (count, edge_nodes) in edges.into_values() {
+            if count == 1 {
+                nodes.extend(edge_nodes);
+            }
+        }
+        Ok(NodeSet {
+            name: name.to_string(),
+            nodes: nodes.into_iter().collect(),
+        })
+    }
+}
+
 impl Default for Sets {
     fn default() -> Self {
         Self::new()
@@ -168,6 +451,7 @@ impl Default for Sets {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mesh::{Element, Node};
 
     fn parse_deck(input: &str) -> Deck {
         Deck::parse_str(input).expect("Failed to parse deck")
@@ -224,6 +508,27 @@ mod tests {
         assert_eq!(nset.nodes, vec![1, 2, 3, 4, 5, 6]);
     }
 
+    #[test]
+    fn inventory_lists_sets_by_kind_and_name() {
+        let input = r#"
+*NSET, NSET=FIX
+1, 2
+*ELSET, ELSET=WELD
+3
+*NSET, NSET=LOAD
+7
+"#;
+
+        let deck = parse_deck(input);
+        let sets = Sets::build_from_deck(&deck).expect("Failed to build sets");
+
+        assert_eq!(
+            sets.inventory(),
+            "Sets: 2 node sets, 1 element sets, 0 surfaces\n  \
+             ELSET WELD: 1 elements\n  NSET FIX: 2 nodes\n  NSET LOAD: 1 nodes"
+        );
+    }
+
     #[test]
     fn get_nodes_returns_node_ids() {
         let input = r#"
@@ -259,4 +564,107 @@ mod tests {
         // This test just ensures we don't error on them
         assert!(sets.element_sets.is_empty());
     }
+
+    #[test]
+    fn adds_node_and_element_card_sets() {
+        let input = r#"
+*NODE, NSET=Nall
+1, 0, 0, 0
+2, 1, 0, 0
+*ELEMENT, TYPE=C3D20, ELSET=Eall
+1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+16, 17, 18, 19, 20
+2, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35,
+36, 37, 38, 39, 40
+*ELEMENT, TYPE=C3D4, ELSET=Eall
+3, 1, 2, 3, 4
+"#;
+
+        let deck = parse_deck(input);
+        let mut sets = Sets::build_from_deck(&deck).expect("Failed to build sets");
+        sets.add_card_sets(&deck).expect("Failed to add sets");
+
+        assert_eq!(sets.get_nodes("Nall"), Some(&[1, 2][..]));
+        assert_eq!(sets.get_elements("Eall"), Some(&[1, 2, 3][..]));
+    }
+
+    /// 2 x 2 S4 plate, elements 1 and 2 along x at y = 0
+    fn plate() -> Mesh {
+        let mut mesh = Mesh::new();
+        for j in 0..3 {
+            for i in 0..3 {
+                mesh.add_node(Node::new(1 + i + 3 * j, i as f64, j as f64, 0.0));
+            }
+        }
+        for (id, first) in [(1, 1), (2, 2), (3, 4), (4, 5)] {
+            let nodes = vec![first, first + 1, first + 4, first + 3];
+            mesh.add_element(Element::new(id, ElementType::S4, nodes))
+                .unwrap();
+        }
+        mesh
+    }
+
+    #[test]
+    fn combines_node_and_element_sets() {
+        let mesh = plate();
+        let a = NodeSet {
+            name: "A".to_string(),
+            nodes: vec![3, 1, 2, 2],
+        };
+        let b = NodeSet {
+            name: "B".to_string(),
+            nodes: vec![2, 5, 3],
+        };
+        assert_eq!(a.union(&b, "U").nodes, vec![1, 2, 3, 5]);
+        assert_eq!(a.intersection(&b, "I").nodes, vec![2, 3]);
+        assert_eq!(a.difference(&b, "D").nodes, vec![1]);
+        let complement = a.complement(&mesh, "C");
+        assert_eq!(complement.name, "C");
+        assert_eq!(complement.nodes, vec![4, 5, 6, 7, 8, 9]);
+
+        let first = ElementSet {
+            name: "FIRST".to_string(),
+            elements: vec![1, 2],
+        };
+        let left = ElementSet {
+            name: "LEFT".to_string(),
+            elements: vec![1, 3],
+        };
+        assert_eq!(first.union(&left, "U").elements, vec![1, 2, 3]);
+        assert_eq!(first.intersection(&left, "I").elements, vec![1]);
+        assert_eq!(first.difference(&left, "D").elements, vec![2]);
+        assert_eq!(first.complement(&mesh, "C").elements, vec![3, 4]);
+    }
+
+    #[test]
+    fn derives_node_sets_from_elements() {
+        let mut mesh = plate();
+        let first = ElementSet {
+            name: "FIRST".to_string(),
+            elements: vec![1, 2],
+        };
+        assert_eq!(
+            first.nodes(&mesh, "N").unwrap().nodes,
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        // Every node of a single row of elements lies on its boundary
+        assert_eq!(first.boundary_nodes(&mesh, "B").unwrap().nodes.len(), 6);
+
+        let all = first.union(&first.complement(&mesh, "REST"), "ALL");
+        let boundary = all.boundary_nodes(&mesh, "B").unwrap();
+        assert!(!boundary.nodes.contains(&5));
+        assert_eq!(boundary.nodes.len(), 8);
+
+        mesh.convert_to_quadratic().unwrap();
+        let boundary = all.boundary_nodes(&mesh, "B").unwrap();
+        // 8 corner and 8 mid-side nodes around the plate
+        assert_eq!(boundary.nodes.len(), 16);
+        assert!(!boundary.nodes.contains(&5));
+
+        let missing = ElementSet {
+            name: "MISSING".to_string(),
+            elements: vec![7],
+        };
+        assert!(missing.nodes(&mesh, "N").is_err());
+    }
 }
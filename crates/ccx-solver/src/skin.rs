@@ -0,0 +1,274 @@
+//! Free surface (skin) extraction of solid meshes.
+//!
+//! A face of a solid element lies on the free surface when no other solid
+//! element has a face with the same corner nodes. The free faces are
+//! returned with the CalculiX face number of their element, so they can be
+//! stored as an element based surface (e.g. for pressure loads or contact),
+//! and can be covered with shell or membrane elements for visualization.
+
+use std::collections::HashMap;
+
+use crate::mesh::{Element, ElementType, Mesh};
+use crate::quadratic::quadratic_edges;
+use crate::sets::{ElementSet, Sets, Surface};
+
+/// One free face of a solid element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinFace {
+    pub element: i32,
+    /// Face number of the `S<n>` face label, counted from 1
+    pub face: usize,
+    /// Corner nodes ordered for an outward normal, followed by the mid-side
+    /// nodes of quadratic elements
+    pub nodes: Vec<i32>,
+}
+
+/// Corner indices of the faces of a solid element type in CalculiX face
+/// order, each ordered for an outward normal
+fn solid_faces(element_type: ElementType) -> &'static [&'static [usize]] {
+    match element_type {
+        ElementType::C3D8 | ElementType::C3D20 => &[
+            &[0, 3, 2, 1],
+            &[4, 5, 6, 7],
+            &[0, 1, 5, 4],
+            &[1, 2, 6, 5],
+            &[2, 3, 7, 6],
+            &[3, 0, 4, 7],
+        ],
+        ElementType::C3D6 | ElementType::C3D15 => &[
+            &[0, 2, 1],
+            &[3, 4, 5],
+            &[0, 1, 4, 3],
+            &[1, 2, 5, 4],
+            &[2, 0, 3, 5],
+        ],
+        ElementType::C3D4 | ElementType::C3D10 => &[&[0, 2, 1], &[0, 1, 3], &[1, 2, 3], &[2, 0, 3]],
+        _ => &[],
+    }
+}
+
+/// Face nodes of `element`: corners, then the mid-side node of every face
+/// edge for quadratic elements
+fn face_nodes(element: &Element, corners: &[usize]) -> Vec<i32> {
+    let mut nodes: Vec<i32> = corners.iter().map(|&c| element.nodes[c]).collect();
+    let edges = quadratic_edges(element.element_type);
+    let first_midside = element.nodes.len() - edges.len();
+    for i in 0..corners.len() {
+        let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+        if let Some(k) = edges
+            .iter()
+            .position(|&[p, q]| (p, q) == (a, b) || (p, q) == (b, a))
+        {
+            nodes.push(element.nodes[first_midside + k]);
+        }
+    }
+    nodes
+}
+
+impl Mesh {
+    /// Faces of solid elements that are not shared with another solid
+    /// element, ordered by element ID and face number
+    pub fn free_faces(&self) -> Vec<SkinFace> {
+        let mut ids: Vec<i32> = self.elements.keys().copied().collect();
+        ids.sort_unstable();
+
+        let mut count: HashMap<Vec<i32>, usize> = HashMap::new();
+        let mut faces = Vec::new();
+        for id in ids {
+            let element = &self.elements[&id];
+            if element.nodes.len() != element.element_type.num_nodes() {
+                continue;
+            }
+            for (index, corners) in solid_faces(element.element_type).iter().enumerate() {
+                let mut key: Vec<i32> = corners.iter().map(|&c| element.nodes[c]).collect();
+                key.sort_unstable();
+                *count.entry(key.clone()).or_insert(0) += 1;
+                faces.push((
+                    key,
+                    SkinFace {
+                        element: id,
+                        face: index + 1,
+                        nodes: face_nodes(element, corners),
+                    },
+                ));
+            }
+        }
+        faces
+            .into_iter()
+            .filter(|(key, _)| count[key] == 1)
+            .map(|(_, face)| face)
+            .collect()
+    }
+
+    /// Cover `faces` with shell (or membrane) elements numbered after the
+    /// highest element ID; returns the new element IDs
+    ///
+    /// Triangular faces become S3/S6 (M3D3/M3D6) and quadrilateral faces
+    /// S4/S8 (M3D4/M3D8), depending on the number of face nodes.
+    pub fn add_skin_elements(&mut self, faces: &[SkinFace], membrane: bool) -> Vec<i32> {
+        let mut next_id = self.elements.keys().max().copied().unwrap_or(0) + 1;
+        let mut ids = Vec::with_capacity(faces.len());
+        for face in faces {
+            let element_type = match (face.nodes.len(), membrane) {
+                (3, false) => ElementType::S3,
+                (6, false) => ElementType::S6,
+                (4, false) => ElementType::S4,
+                (8, false) => ElementType::S8,
+                (3, true) => ElementType::M3D3,
+                (6, true) => ElementType::M3D6,
+                (4, true) => ElementType::M3D4,
+                (8, true) => ElementType::M3D8,
+                _ => continue,
+            };
+            self.elements.insert(
+                next_id,
+                Element::new(next_id, element_type, face.nodes.clone()),
+            );
+            ids.push(next_id);
+            next_id += 1;
+        }
+        ids
+    }
+}
+
+impl Sets {
+    /// Add the free faces of the solid elements of `mesh` as surface `name`
+    pub fn add_skin_surface(&mut self, mesh: &Mesh, name: &str) -> usize {
+        let faces: Vec<(i32, usize)> = mesh
+            .free_faces()
+            .iter()
+            .map(|face| (face.element, face.face))
+            .collect();
+        let count = faces.len();
+        self.add_surface(Surface {
+            name: name.to_string(),
+            faces,
+        });
+        count
+    }
+
+    /// Cover the free faces of `mesh` with shell (or membrane) elements and
+    /// collect them in element set `name`
+    pub fn add_skin_elements(&mut self, mesh: &mut Mesh, name: &str, membrane: bool) -> usize {
+        let faces = mesh.free_faces();
+        let elements = mesh.add_skin_elements(&faces, membrane);
+        let count = elements.len();
+        self.add_element_set(ElementSet {
+            name: name.to_string(),
+            elements,
+        });
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Node;
+
+    /// Two unit cubes stacked in z, sharing face 5-6-7-8
+    fn stack() -> Mesh {
+        let mut mesh = Mesh::new();
+        let mut id = 1;
+        for z in 0..3 {
+            for [x, y] in [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]] {
+                mesh.add_node(Node::new(id, x, y, z as f64));
+                id += 1;
+            }
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D8, (1..=8).collect()))
+            .unwrap();
+        mesh.add_element(Element::new(2, ElementType::C3D8, (5..=12).collect()))
+            .unwrap();
+        mesh
+    }
+
+    fn outward_normal(mesh: &Mesh, face: &SkinFace) -> [f64; 3] {
+        let x: Vec<[f64; 3]> = face.nodes[..3]
+            .iter()
+            .map(|n| mesh.nodes[n].coords())
+            .collect();
+        let a = [0, 1, 2].map(|d| x[1][d] - x[0][d]);
+        let b = [0, 1, 2].map(|d| x[2][d] - x[0][d]);
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    }
+
+    #[test]
+    fn shared_faces_are_not_free() {
+        let mesh = stack();
+        let faces = mesh.free_faces();
+        assert_eq!(faces.len(), 10);
+        assert!(!faces.iter().any(|f| f.element == 1 && f.face == 2));
+        assert!(!faces.iter().any(|f| f.element == 2 && f.face == 1));
+
+        // Bottom face S1 points down, top face S2 of element 2 points up
+        let bottom = faces
+            .iter()
+            .find(|f| f.element == 1 && f.face == 1)
+            .unwrap();
+        assert!(outward_normal(&mesh, bottom)[2] < 0.0);
+        let top = faces
+            .iter()
+            .find(|f| f.element == 2 && f.face == 2)
+            .unwrap();
+        assert!(outward_normal(&mesh, top)[2] > 0.0);
+        let side = faces
+            .iter()
+            .find(|f| f.element == 1 && f.face == 4)
+            .unwrap();
+        assert!(outward_normal(&mesh, side)[0] > 0.0);
+    }
+
+    #[test]
+    fn tetrahedron_faces_point_outward() {
+        let mut mesh = Mesh::new();
+        for (id, [x, y, z]) in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            mesh.add_node(Node::new(id as i32 + 1, x, y, z));
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D4, vec![1, 2, 3, 4]))
+            .unwrap();
+        mesh.convert_to_quadratic().unwrap();
+        let faces = mesh.free_faces();
+        assert_eq!(faces.len(), 4);
+        let centroid = [0.25; 3];
+        for face in &faces {
+            assert_eq!(face.nodes.len(), 6);
+            let normal = outward_normal(&mesh, face);
+            let x = mesh.nodes[&face.nodes[0]].coords();
+            let outward: f64 = (0..3).map(|d| normal[d] * (x[d] - centroid[d])).sum();
+            assert!(outward > 0.0, "face S{} points inward", face.face);
+        }
+        // S3 (2-4-3) mid-side nodes follow its edges 2-3, 3-4, 4-2
+        let s3 = &faces[2];
+        assert_eq!(mesh.nodes[&s3.nodes[3]].coords(), [0.5, 0.5, 0.0]);
+        assert_eq!(mesh.nodes[&s3.nodes[4]].coords(), [0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn skin_goes_into_surfaces_and_shell_sets() {
+        let mut mesh = stack();
+        let mut sets = Sets::new();
+        assert_eq!(sets.add_skin_surface(&mesh, "SKIN"), 10);
+        assert_eq!(sets.get_surface("SKIN").unwrap()[0], (1, 1));
+
+        assert_eq!(sets.add_skin_elements(&mut mesh, "SHELLS", false), 10);
+        let shells = sets.get_elements("SHELLS").unwrap();
+        assert_eq!(shells.first(), Some(&3));
+        assert_eq!(mesh.elements[&3].element_type, ElementType::S4);
+        assert!(mesh.validate().is_ok());
+        // Shells do not change the free faces of the solids
+        assert_eq!(mesh.free_faces().len(), 10);
+    }
+}
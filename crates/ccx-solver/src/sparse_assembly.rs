@@ -2,8 +2,13 @@
 ///!
 ///! Uses Compressed Sparse Row (CSR) format for memory efficiency and faster solving:
 ///! - Memory: O(nnz) instead of O(n²) for dense matrices
-///! - Iterative solvers: Conjugate Gradient (CG) for symmetric positive definite systems
+///! - Solvers: sparse Cholesky (default) or Conjugate Gradient for symmetric
+///!   positive definite systems, see [`crate::linear_solver`]
 ///! - Suitable for large-scale problems (10,000+ DOFs)
+///! - Element matrices and triplets are generated in parallel (rayon); set
+///!   `RAYON_NUM_THREADS` to limit the thread count
+///! - Default storage of the analysis pipeline; [`crate::GlobalSystem`] is the
+///!   dense reference kept for debugging
 ///!
 ///! ## Performance Comparison
 ///!
@@ -13,12 +18,22 @@
 ///! | 10,000 | 800 MB | 8 MB | 100x |
 ///! | 100,000 | 80 GB | 800 MB | 100x |
 
-use crate::boundary_conditions::BoundaryConditions;
+use crate::assembly::element_stiffness;
+use crate::boundary_conditions::{BoundaryConditions, DofId};
+use crate::legacy_elements::LegacyElements;
+use crate::linear_solver::{
+    ConjugateGradientSolver, LinearSolver, SolveInfo, SparseCholeskySolver, find_zero_pivots,
+    solve_timed,
+};
 use crate::materials::MaterialLibrary;
 use crate::mesh::Mesh;
-use nalgebra::DVector;
+use nalgebra::{DMatrix, DVector};
 use nalgebra_sparse::{CooMatrix, CsrMatrix};
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// Penalty stiffness for prescribed displacements
+pub(crate) const PENALTY: f64 = 1e10;
 
 /// Sparse global finite element system using CSR format
 #[derive(Debug, Clone)]
@@ -29,6 +44,8 @@ pub struct SparseGlobalSystem {
     pub force: DVector<f64>,
     /// Number of degrees of freedom
     pub num_dofs: usize,
+    /// DOF stride per node (the largest DOF count of any element type)
+    pub dofs_per_node: usize,
     /// Constrained DOFs (for boundary conditions)
     pub constrained_dofs: Vec<usize>,
 }
@@ -46,6 +63,18 @@ impl SparseGlobalSystem {
         materials: &MaterialLibrary,
         bcs: &BoundaryConditions,
         default_area: f64,
+    ) -> Result<Self, String> {
+        Self::assemble_with_legacy(mesh, materials, bcs, default_area, None)
+    }
+
+    /// Assemble like [`SparseGlobalSystem::assemble`], computing the element
+    /// types without a port with the routines of `legacy`
+    pub fn assemble_with_legacy(
+        mesh: &Mesh,
+        materials: &MaterialLibrary,
+        bcs: &BoundaryConditions,
+        default_area: f64,
+        legacy: Option<&LegacyElements>,
     ) -> Result<Self, String> {
         // Determine maximum DOFs per node for mixed meshes
         let max_dofs_per_node = mesh
@@ -59,30 +88,34 @@ impl SparseGlobalSystem {
         let num_nodes = mesh.nodes.len();
         let num_dofs = num_nodes * max_dofs_per_node;
 
-        // Build stiffness matrix in COO format for efficient assembly
-        let stiffness_coo = Self::assemble_stiffness_coo(
+        // Element and penalty contributions go straight into COO triplets;
+        // duplicates are summed when converting to CSR.
+        let mut stiffness_coo = Self::assemble_stiffness_coo(
             mesh,
             materials,
             default_area,
             max_dofs_per_node,
             num_dofs,
+            legacy,
         )?;
 
-        // Convert COO to CSR for efficient solving
-        let stiffness = CsrMatrix::from(&stiffness_coo);
-
         // Build force vector
         let mut force = DVector::zeros(num_dofs);
         Self::assemble_forces_into(&mut force, bcs, max_dofs_per_node)?;
 
         // Apply displacement boundary conditions
-        let (stiffness, force, constrained_dofs) =
-            Self::apply_displacement_bcs(stiffness, force, bcs, max_dofs_per_node)?;
+        let constrained_dofs = Self::apply_displacement_bcs(
+            &mut stiffness_coo,
+            &mut force,
+            bcs,
+            max_dofs_per_node,
+        )?;
 
         Ok(Self {
-            stiffness,
+            stiffness: CsrMatrix::from(&stiffness_coo),
             force,
             num_dofs,
+            dofs_per_node: max_dofs_per_node,
             constrained_dofs,
         })
     }
@@ -93,98 +126,71 @@ impl SparseGlobalSystem {
     /// - Can add entries in any order
     /// - Duplicate (i,j) entries are automatically summed during conversion to CSR
     /// - Simple and efficient for element-by-element assembly
+    ///
+    /// Elements are processed in parallel with rayon; each worker fills its
+    /// own triplet buffer and the buffers are concatenated in element order.
     fn assemble_stiffness_coo(
         mesh: &Mesh,
         materials: &MaterialLibrary,
         default_area: f64,
         max_dofs_per_node: usize,
         num_dofs: usize,
+        legacy: Option<&LegacyElements>,
     ) -> Result<CooMatrix<f64>, String> {
-        use crate::elements::DynamicElement;
-
-        // Estimate number of non-zero entries
-        // For typical FE meshes: nnz ≈ bandwidth × num_dofs, bandwidth ≈ sqrt(num_dofs)
-        let _estimated_nnz = (num_dofs as f64).sqrt() as usize * num_dofs;
-
-        // Temporary map to accumulate entries (handles duplicates)
-        let mut entry_map: HashMap<(usize, usize), f64> = HashMap::new();
-
-        for (elem_id, element) in &mesh.elements {
-            // Get element nodes
-            let nodes: Vec<_> = element
-                .nodes
-                .iter()
-                .map(|&node_id| {
-                    mesh.nodes
-                        .get(&node_id)
-                        .cloned()
-                        .ok_or(format!("Node {} not found", node_id))
-                })
-                .collect::<Result<Vec<_>, String>>()?;
-
-            // Get material for this element
-            let material = materials
-                .get_element_material(*elem_id)
-                .ok_or(format!("No material assigned to element {}", elem_id))?;
-
-            // Create element using factory
-            let dyn_elem = DynamicElement::from_mesh_element(
-                element.element_type,
-                *elem_id,
-                element.nodes.clone(),
-                default_area,
-            );
-
-            let dyn_elem = match dyn_elem {
-                Some(e) => e,
-                None => {
-                    eprintln!(
-                        "Warning: Unsupported element type {:?}, skipping element {}",
-                        element.element_type, elem_id
-                    );
-                    continue;
+        // Sorted ids keep the triplet order, and so the summation order,
+        // reproducible between runs and thread counts.
+        let mut elem_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        elem_ids.sort_unstable();
+
+        let triplets = elem_ids
+            .par_iter()
+            .try_fold(Triplets::default, |mut triplets, &elem_id| {
+                let Some((dof_indices, k_e)) = element_stiffness(
+                    mesh,
+                    materials,
+                    elem_id,
+                    default_area,
+                    max_dofs_per_node,
+                    legacy,
+                )?
+                else {
+                    return Ok(triplets);
+                };
+                if let Some(&dof) = dof_indices.iter().find(|&&dof| dof >= num_dofs) {
+                    return Err(format!(
+                        "Element {} DOF index {} out of range (max {})",
+                        elem_id, dof, num_dofs
+                    ));
                 }
-            };
-
-            // Compute element stiffness matrix
-            let k_e = dyn_elem.stiffness_matrix(&nodes, material)?;
 
-            // Get global DOF indices with correct stride
-            let dof_indices = dyn_elem.global_dof_indices(&element.nodes, max_dofs_per_node);
-
-            // Add element contribution to entry map
-            for (i_local, &i_global) in dof_indices.iter().enumerate() {
-                for (j_local, &j_global) in dof_indices.iter().enumerate() {
-                    let key = (i_global, j_global);
-                    *entry_map.entry(key).or_insert(0.0) += k_e[(i_local, j_local)];
+                // Add element contribution as triplets, skipping exact zeros
+                for (i_local, &i_global) in dof_indices.iter().enumerate() {
+                    for (j_local, &j_global) in dof_indices.iter().enumerate() {
+                        let value = k_e[(i_local, j_local)];
+                        if value != 0.0 {
+                            triplets.push(i_global, j_global, value);
+                        }
+                    }
                 }
-            }
-        }
-
-        // Convert entry map to separate vectors for rows, cols, values
-        // Filter out near-zero entries to maintain sparsity
-        let tolerance = 1e-12;
-        let mut rows = Vec::new();
-        let mut cols = Vec::new();
-        let mut values = Vec::new();
-
-        for ((i, j), v) in entry_map {
-            if v.abs() > tolerance {
-                rows.push(i);
-                cols.push(j);
-                values.push(v);
-            }
-        }
-
-        // Create COO matrix from separate vectors
-        let coo = CooMatrix::try_from_triplets(num_dofs, num_dofs, rows, cols, values)
-            .map_err(|e| format!("Failed to create COO matrix: {:?}", e))?;
-
-        Ok(coo)
+                Ok(triplets)
+            })
+            .try_reduce(Triplets::default, |mut a, b| {
+                a.append(b);
+                Ok(a)
+            })?;
+
+        CooMatrix::try_from_triplets(
+            num_dofs,
+            num_dofs,
+            triplets.rows,
+            triplets.cols,
+            triplets.values,
+        )
+        .map_err(|e| format!("Invalid stiffness triplets: {}", e))
     }
 
     /// Assemble concentrated loads into force vector
-    fn assemble_forces_into(
+    pub(crate) fn assemble_forces_into(
         force: &mut DVector<f64>,
         bcs: &BoundaryConditions,
         max_dofs_per_node: usize,
@@ -208,28 +214,17 @@ impl SparseGlobalSystem {
 
     /// Apply displacement boundary conditions using penalty method
     ///
-    /// For sparse matrices, we need to modify CSR entries carefully.
-    /// This implementation uses penalty method which only modifies diagonal and RHS.
+    /// The penalty is pushed as an extra diagonal triplet, so the matrix
+    /// never has to be rebuilt.
     fn apply_displacement_bcs(
-        mut stiffness: CsrMatrix<f64>,
-        mut force: DVector<f64>,
+        stiffness: &mut CooMatrix<f64>,
+        force: &mut DVector<f64>,
         bcs: &BoundaryConditions,
         max_dofs_per_node: usize,
-    ) -> Result<(CsrMatrix<f64>, DVector<f64>, Vec<usize>), String> {
-        let penalty = 1e10; // Large penalty factor
+    ) -> Result<Vec<usize>, String> {
+        let penalty = PENALTY;
         let mut constrained_dofs = Vec::new();
 
-        // Convert to COO for modification
-        let coo: CooMatrix<f64> = CooMatrix::from(&stiffness);
-        let (mut rows, mut cols, mut values) = coo.disassemble();
-
-        // Build a map from (row, col) to index in values array
-        let mut entry_map: HashMap<(usize, usize), usize> = HashMap::new();
-        for (idx, (&row, &col)) in rows.iter().zip(cols.iter()).enumerate() {
-            entry_map.insert((row, col), idx);
-        }
-
-        // Apply penalty to constrained DOFs
         for bc in &bcs.displacement_bcs {
             for dof in bc.first_dof..=bc.last_dof {
                 let dof_index = (bc.node - 1) as usize * max_dofs_per_node + (dof - 1);
@@ -242,58 +237,138 @@ impl SparseGlobalSystem {
                     ));
                 }
 
-                // Modify diagonal entry in COO matrix
-                if let Some(&idx) = entry_map.get(&(dof_index, dof_index)) {
-                    // Entry exists, add penalty
-                    values[idx] += penalty;
-                } else {
-                    // Entry doesn't exist, add new diagonal entry
-                    rows.push(dof_index);
-                    cols.push(dof_index);
-                    values.push(penalty);
-                    entry_map.insert((dof_index, dof_index), values.len() - 1);
-                }
-
-                // Modify force vector
+                stiffness.push(dof_index, dof_index, penalty);
                 force[dof_index] += penalty * bc.value;
 
                 constrained_dofs.push(dof_index);
             }
         }
 
-        // Create new COO from modified vectors
-        let coo = CooMatrix::try_from_triplets(stiffness.nrows(), stiffness.ncols(), rows, cols, values)
-            .map_err(|e| format!("Failed to create modified COO matrix: {:?}", e))?;
+        Ok(constrained_dofs)
+    }
 
-        // Convert back to CSR
-        stiffness = CsrMatrix::from(&coo);
+    /// Solve the sparse linear system K * u = F
+    ///
+    /// Uses the sparse Cholesky factorization, the default direct solver for
+    /// the symmetric positive definite stiffness matrix.
+    pub fn solve(&self) -> Result<DVector<f64>, String> {
+        self.solve_with(&mut SparseCholeskySolver::new())
+    }
+
+    /// Solve with a given backend, e.g. one reused across load cases
+    pub fn solve_with(&self, solver: &mut dyn LinearSolver) -> Result<DVector<f64>, String> {
+        solver.factorize(&self.stiffness)?;
+        solver.solve(&self.force)
+    }
 
-        Ok((stiffness, force, constrained_dofs))
+    /// Solve several load cases with one factorization
+    ///
+    /// `forces` are built like [`SparseGlobalSystem::load_vector`]; pass a
+    /// [`crate::FactorizationCache`] to also share the factorization with
+    /// later calls.
+    pub fn solve_load_cases(
+        &self,
+        solver: &mut dyn LinearSolver,
+        forces: &[DVector<f64>],
+    ) -> Result<Vec<DVector<f64>>, String> {
+        solver.factorize(&self.stiffness)?;
+        solver.resolve(forces)
     }
 
-    /// Solve the sparse linear system K * u = F using Conjugate Gradient
+    /// Force vector of another load case on the same stiffness matrix
+    ///
+    /// Applies the concentrated loads of `bcs` and the penalty terms of its
+    /// prescribed displacements. Only the right-hand side changes, so every
+    /// prescribed DOF must already be constrained in K.
+    pub fn load_vector(&self, bcs: &BoundaryConditions) -> Result<DVector<f64>, String> {
+        let mut force = DVector::zeros(self.num_dofs);
+        Self::assemble_forces_into(&mut force, bcs, self.dofs_per_node)?;
+        for bc in &bcs.displacement_bcs {
+            for dof in bc.first_dof..=bc.last_dof {
+                let dof_index = (bc.node - 1) as usize * self.dofs_per_node + (dof - 1);
+                if !self.constrained_dofs.contains(&dof_index) {
+                    return Err(format!(
+                        "{} is not constrained in the assembled stiffness matrix",
+                        self.dof_id(dof_index)
+                    ));
+                }
+                force[dof_index] += PENALTY * bc.value;
+            }
+        }
+        Ok(force)
+    }
+
+    /// Reactions K·u − F at the prescribed DOFs of `bcs`, zero elsewhere
+    ///
+    /// K and F are taken without the penalty terms, so the result is the
+    /// force the supports exert to hold the prescribed displacements.
+    pub fn reactions(&self, u: &DVector<f64>, bcs: &BoundaryConditions) -> DVector<f64> {
+        penalty_reactions(bcs, self.dofs_per_node, u, &self.force, |i| {
+            let row = self.stiffness.row(i);
+            row.col_indices()
+                .iter()
+                .zip(row.values())
+                .map(|(&j, &k)| k * u[j])
+                .sum()
+        })
+    }
+
+    /// Solve with a given backend and report timings and factor statistics
+    ///
+    /// Zero-pivot equations are labelled with their node and DOF, both in
+    /// the returned [`SolveInfo`] and in the error of a failed factorization.
+    pub fn solve_with_info(
+        &self,
+        solver: &mut dyn LinearSolver,
+    ) -> Result<(DVector<f64>, SolveInfo), String> {
+        match solve_timed(solver, &self.stiffness, &self.force) {
+            Ok((u, mut info)) => {
+                info.problem_dofs = info.zero_pivot_dofs.iter().map(|&i| self.dof_id(i)).collect();
+                Ok((u, info))
+            }
+            Err(e) => {
+                let singular: Vec<String> = find_zero_pivots(&self.stiffness)
+                    .into_iter()
+                    .map(|i| format!("{} unconstrained", self.dof_id(i)))
+                    .collect();
+                if singular.is_empty() {
+                    return Err(e);
+                }
+                Err(format!(
+                    "Stiffness matrix is singular: {}",
+                    summarize_list(&singular, 10)
+                ))
+            }
+        }
+    }
+
+    /// Node and DOF of a global equation index
+    ///
+    /// Follows the assembly numbering `(node - 1) * dofs_per_node + dof`.
+    pub fn dof_id(&self, index: usize) -> DofId {
+        DofId::new(
+            (index / self.dofs_per_node) as i32 + 1,
+            index % self.dofs_per_node,
+        )
+    }
+
+    /// Solve using Jacobi-preconditioned Conjugate Gradient
     ///
     /// CG is optimal for symmetric positive definite systems (typical in FEA).
     /// Convergence: O(sqrt(κ)) where κ is the condition number.
-    pub fn solve(&self) -> Result<DVector<f64>, String> {
-        // For now, convert to dense and use LU decomposition
-        // TODO: Implement sparse iterative solver (CG, BiCGSTAB, etc.)
-        use nalgebra::DMatrix;
+    pub fn solve_cg(&self, tolerance: f64, max_iterations: usize) -> Result<DVector<f64>, String> {
+        self.solve_with(&mut ConjugateGradientSolver::new(tolerance, max_iterations))
+    }
 
-        // Convert CSR to dense matrix
+    /// Dense copy of the stiffness matrix, for debugging small systems
+    pub fn to_dense(&self) -> DMatrix<f64> {
         let mut dense = DMatrix::zeros(self.stiffness.nrows(), self.stiffness.ncols());
         for (row_idx, row) in self.stiffness.row_iter().enumerate() {
             for (&col_idx, &value) in row.col_indices().iter().zip(row.values().iter()) {
                 dense[(row_idx, col_idx)] = value;
             }
         }
-
-        let lu = dense
-            .lu()
-            .solve(&self.force)
-            .ok_or("Failed to solve sparse linear system (singular matrix?)")?;
-
-        Ok(lu)
+        dense
     }
 
     /// Validate the sparse system
@@ -322,6 +397,70 @@ impl SparseGlobalSystem {
     }
 }
 
+/// Join at most `limit` items, noting how many were left out
+fn summarize_list(items: &[String], limit: usize) -> String {
+    let mut text = items[..items.len().min(limit)].join(", ");
+    if items.len() > limit {
+        text.push_str(&format!(" and {} more", items.len() - limit));
+    }
+    text
+}
+
+/// Per-thread COO triplet buffer used during parallel assembly
+#[derive(Default)]
+struct Triplets {
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl Triplets {
+    fn push(&mut self, row: usize, col: usize, value: f64) {
+        self.rows.push(row);
+        self.cols.push(col);
+        self.values.push(value);
+    }
+
+    fn append(&mut self, mut other: Triplets) {
+        self.rows.append(&mut other.rows);
+        self.cols.append(&mut other.cols);
+        self.values.append(&mut other.values);
+    }
+}
+
+/// Reactions K·u − F of a penalized system at the prescribed DOFs of `bcs`
+///
+/// `penalized_row(i)` is row i of the penalized K times u. Each prescribed
+/// value added `PENALTY` to the diagonal and `PENALTY * value` to F; both
+/// are taken back out before the difference is formed.
+pub(crate) fn penalty_reactions(
+    bcs: &BoundaryConditions,
+    dofs_per_node: usize,
+    u: &DVector<f64>,
+    force: &DVector<f64>,
+    penalized_row: impl Fn(usize) -> f64,
+) -> DVector<f64> {
+    // Penalty count and summed prescribed values per DOF
+    let mut prescribed: BTreeMap<usize, (f64, f64)> = BTreeMap::new();
+    for bc in &bcs.displacement_bcs {
+        for dof in bc.first_dof..=bc.last_dof {
+            let dof_index = (bc.node - 1) as usize * dofs_per_node + (dof - 1);
+            if dof_index < u.len() {
+                let entry = prescribed.entry(dof_index).or_default();
+                entry.0 += 1.0;
+                entry.1 += bc.value;
+            }
+        }
+    }
+
+    let mut reactions = DVector::zeros(u.len());
+    for (dof, (count, values)) in prescribed {
+        let ku = penalized_row(dof) - count * PENALTY * u[dof];
+        reactions[dof] = ku - (force[dof] - PENALTY * values);
+    }
+    reactions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -416,4 +555,169 @@ mod tests {
         // For a single truss element, we expect very sparse matrix
         assert!(sparsity < 0.5, "Matrix should be sparse (sparsity: {})", sparsity);
     }
+
+    #[test]
+    fn test_sparse_matches_dense_assembly_and_solution() {
+        use crate::assembly::GlobalSystem;
+
+        // Cantilevered chain of 20 trusses with a lateral support at each node
+        let mut mesh = Mesh::new();
+        let mut library = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(210000.0);
+        steel.poissons_ratio = Some(0.3);
+        library.add_material(steel);
+        let mut bcs = BoundaryConditions::new();
+        for id in 1..=21 {
+            mesh.add_node(Node::new(id, (id - 1) as f64 * 0.5, 0.0, 0.0));
+            bcs.add_displacement_bc(DisplacementBC::new(id, 2, 3, 0.0));
+        }
+        for id in 1..=20 {
+            mesh.add_element(Element::new(id, ElementType::T3D2, vec![id, id + 1]))
+                .unwrap();
+            library.assign_material(id, "STEEL".to_string());
+        }
+        mesh.calculate_dofs();
+        bcs.add_displacement_bc(DisplacementBC::new(1, 1, 1, 0.0));
+        bcs.add_concentrated_load(ConcentratedLoad::new(21, 1, 500.0));
+
+        let sparse = SparseGlobalSystem::assemble(&mesh, &library, &bcs, 0.01).unwrap();
+        let dense = GlobalSystem::assemble(&mesh, &library, &bcs, 0.01).unwrap();
+        assert_eq!(sparse.to_dense(), dense.stiffness);
+        assert_eq!(sparse.force, dense.force);
+
+        let u_sparse = sparse.solve().expect("Cholesky should succeed");
+        let u_cg = sparse.solve_cg(1e-12, 1000).expect("CG should converge");
+        let u_dense = dense.solve().unwrap();
+        let expected = 500.0 * 10.0 / (0.01 * 210000.0);
+        assert!((&u_sparse - &u_cg).amax() < 1e-6 * expected);
+        assert!(((u_sparse[60] - expected) / expected).abs() < 1e-6);
+        assert!((&u_sparse - &u_dense).amax() < 1e-9 * expected);
+    }
+
+    #[test]
+    fn test_load_cases_share_one_factorization() {
+        use crate::linear_solver::FactorizationCache;
+
+        let mesh = make_simple_truss_mesh();
+        let materials = make_material_library();
+        let mut bcs = BoundaryConditions::new();
+        bcs.add_displacement_bc(DisplacementBC::new(1, 1, 3, 0.0));
+        bcs.add_displacement_bc(DisplacementBC::new(2, 2, 3, 0.0));
+        bcs.add_concentrated_load(ConcentratedLoad::new(2, 1, 1000.0));
+        let system = SparseGlobalSystem::assemble(&mesh, &materials, &bcs, 0.01).unwrap();
+
+        // Second case: opposite load plus a prescribed lateral displacement
+        let mut case2 = BoundaryConditions::new();
+        case2.add_displacement_bc(DisplacementBC::new(1, 1, 3, 0.0));
+        case2.add_displacement_bc(DisplacementBC::new(2, 2, 2, 0.01));
+        case2.add_displacement_bc(DisplacementBC::new(2, 3, 3, 0.0));
+        case2.add_concentrated_load(ConcentratedLoad::new(2, 1, -500.0));
+        let forces = [system.load_vector(&bcs).unwrap(), system.load_vector(&case2).unwrap()];
+        assert_eq!(forces[0], system.force);
+
+        let mut solver = FactorizationCache::new(Box::new(SparseCholeskySolver::new()));
+        let u = system.solve_load_cases(&mut solver, &forces).unwrap();
+        system.solve_load_cases(&mut solver, &forces[1..]).unwrap();
+        assert_eq!((solver.factorizations(), solver.reuses()), (1, 1));
+        assert!((u[1][3] + 0.5 * u[0][3]).abs() < 1e-9);
+        assert!((u[1][4] - 0.01).abs() < 1e-8);
+
+        // A DOF that is free in K cannot be prescribed by a load case
+        let mut free = BoundaryConditions::new();
+        free.add_displacement_bc(DisplacementBC::new(2, 1, 1, 0.0));
+        let err = system.load_vector(&free).unwrap_err();
+        assert!(err.contains("node 2 DOF 1"), "{err}");
+    }
+
+    #[test]
+    fn test_parallel_assembly_independent_of_thread_count() {
+        // Planar truss grid: each node is shared by several elements
+        let n = 12;
+        let mut mesh = Mesh::new();
+        let mut library = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(210000.0);
+        library.add_material(steel);
+        let id = |i: i32, j: i32| 1 + i + n * j;
+        for j in 0..n {
+            for i in 0..n {
+                mesh.add_node(Node::new(id(i, j), i as f64, j as f64 * 0.7, 0.0));
+            }
+        }
+        let mut elem_id = 0;
+        for j in 0..n {
+            for i in 0..n {
+                for (i2, j2) in [(i + 1, j), (i, j + 1), (i + 1, j + 1)] {
+                    if i2 < n && j2 < n {
+                        elem_id += 1;
+                        mesh.add_element(Element::new(
+                            elem_id,
+                            ElementType::T3D2,
+                            vec![id(i, j), id(i2, j2)],
+                        ))
+                        .unwrap();
+                        library.assign_material(elem_id, "STEEL".to_string());
+                    }
+                }
+            }
+        }
+        mesh.calculate_dofs();
+        let bcs = BoundaryConditions::new();
+
+        let assemble_with = |threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| SparseGlobalSystem::assemble(&mesh, &library, &bcs, 0.01))
+                .unwrap()
+        };
+        let serial = assemble_with(1);
+        let parallel = assemble_with(4);
+        assert_eq!(serial.stiffness, parallel.stiffness);
+    }
+
+    #[test]
+    fn test_sparse_solve_rejects_unconstrained_model() {
+        let mesh = make_simple_truss_mesh();
+        let materials = make_material_library();
+        let mut bcs = BoundaryConditions::new();
+        bcs.add_concentrated_load(ConcentratedLoad::new(2, 1, 1000.0));
+
+        let system = SparseGlobalSystem::assemble(&mesh, &materials, &bcs, 0.01).unwrap();
+        assert!(system.solve().is_err());
+
+        // The lateral DOFs of both nodes carry no stiffness at all
+        let err = system
+            .solve_with_info(&mut SparseCholeskySolver::new())
+            .unwrap_err();
+        assert!(err.contains("node 1 DOF 2 unconstrained"), "{err}");
+        assert!(err.contains("node 2 DOF 3 unconstrained"), "{err}");
+    }
+
+    #[test]
+    fn test_solve_info_reports_condition_and_zero_pivots() {
+        let mesh = make_simple_truss_mesh();
+        let materials = make_material_library();
+        let mut bcs = BoundaryConditions::new();
+        bcs.add_displacement_bc(DisplacementBC::new(1, 1, 3, 0.0));
+        bcs.add_concentrated_load(ConcentratedLoad::new(2, 1, 1000.0));
+        let system = SparseGlobalSystem::assemble(&mesh, &materials, &bcs, 0.01).unwrap();
+
+        // CG tolerates the zero rows of node 2's lateral DOFs
+        let (_, info) = system
+            .solve_with_info(&mut ConjugateGradientSolver::new(1e-12, 100))
+            .unwrap();
+        assert_eq!(info.zero_pivot_dofs, vec![4, 5]);
+        assert_eq!(info.warnings()[0], "node 2 DOF 2 unconstrained");
+
+        bcs.add_displacement_bc(DisplacementBC::new(2, 2, 3, 0.0));
+        let system = SparseGlobalSystem::assemble(&mesh, &materials, &bcs, 0.01).unwrap();
+        let (_, info) = system.solve_with_info(&mut SparseCholeskySolver::new()).unwrap();
+        assert!(info.warnings().is_empty());
+        // Penalty 1e10 against an axial stiffness of 2100
+        let condition = info.condition_estimate.unwrap();
+        assert!(condition > 1e6 && condition < 1e8, "{condition}");
+    }
 }
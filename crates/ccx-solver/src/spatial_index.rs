@@ -0,0 +1,318 @@
+//! Spatial search over node positions and element bounding boxes.
+//!
+//! A [`SpatialIndex`] keeps a k-d tree of the nodes and one of the element
+//! bounding box centres of a mesh. Nearest-node and radius queries walk the
+//! node tree; element queries collect the boxes within reach of a point and
+//! filter them exactly. [`SpatialIndex::locate`] then inverts the
+//! isoparametric mapping of the candidate solids to find the element that
+//! contains a point, e.g. to probe results at arbitrary positions.
+
+use crate::elements::SolidElement;
+use crate::mesh::{Mesh, Node};
+
+fn distance_squared(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|d| (a[d] - b[d]).powi(2)).sum()
+}
+
+/// Balanced k-d tree of IDs with positions, stored in place: the median of
+/// every subrange is its root and splits along axis `depth % 3`
+#[derive(Debug, Clone)]
+struct KdTree {
+    points: Vec<(i32, [f64; 3])>,
+}
+
+impl KdTree {
+    fn new(mut points: Vec<(i32, [f64; 3])>) -> Self {
+        fn build(points: &mut [(i32, [f64; 3])], depth: usize) {
+            if points.len() <= 1 {
+                return;
+            }
+            let axis = depth % 3;
+            let mid = points.len() / 2;
+            points.select_nth_unstable_by(mid, |a, b| a.1[axis].total_cmp(&b.1[axis]));
+            let (left, right) = points.split_at_mut(mid);
+            build(left, depth + 1);
+            build(&mut right[1..], depth + 1);
+        }
+        build(&mut points, 0);
+        Self { points }
+    }
+
+    /// Closest point, ties going to the lower ID
+    fn nearest(&self, x: [f64; 3]) -> Option<(i32, f64)> {
+        fn search(
+            points: &[(i32, [f64; 3])],
+            depth: usize,
+            x: [f64; 3],
+            best: &mut Option<(i32, f64)>,
+        ) {
+            if points.is_empty() {
+                return;
+            }
+            let mid = points.len() / 2;
+            let (id, position) = points[mid];
+            let d = distance_squared(x, position);
+            if best.is_none_or(|(best_id, best_d)| d < best_d || (d == best_d && id < best_id)) {
+                *best = Some((id, d));
+            }
+            let offset = x[depth % 3] - position[depth % 3];
+            let (near, far) = if offset < 0.0 {
+                (&points[..mid], &points[mid + 1..])
+            } else {
+                (&points[mid + 1..], &points[..mid])
+            };
+            search(near, depth + 1, x, best);
+            if best.is_none_or(|(_, best_d)| offset * offset <= best_d) {
+                search(far, depth + 1, x, best);
+            }
+        }
+        let mut best = None;
+        search(&self.points, 0, x, &mut best);
+        best.map(|(id, d)| (id, d.sqrt()))
+    }
+
+    /// All points within `radius` of `x`, unordered
+    fn within(&self, x: [f64; 3], radius: f64, found: &mut Vec<(i32, f64)>) {
+        fn search(
+            points: &[(i32, [f64; 3])],
+            depth: usize,
+            x: [f64; 3],
+            radius: f64,
+            found: &mut Vec<(i32, f64)>,
+        ) {
+            if points.is_empty() {
+                return;
+            }
+            let mid = points.len() / 2;
+            let (id, position) = points[mid];
+            let d = distance_squared(x, position);
+            if d <= radius * radius {
+                found.push((id, d.sqrt()));
+            }
+            let offset = x[depth % 3] - position[depth % 3];
+            if offset <= radius {
+                search(&points[..mid], depth + 1, x, radius, found);
+            }
+            if offset >= -radius {
+                search(&points[mid + 1..], depth + 1, x, radius, found);
+            }
+        }
+        search(&self.points, 0, x, radius, found);
+    }
+}
+
+/// Element containing a point, with the point in natural coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLocation {
+    pub element: i32,
+    pub natural: [f64; 3],
+}
+
+/// Search structure over the nodes and elements of a mesh
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<'a> {
+    mesh: &'a Mesh,
+    nodes: KdTree,
+    /// Element ID with the minimum and maximum of its bounding box
+    boxes: Vec<(i32, [f64; 3], [f64; 3])>,
+    /// Box centres, keyed by their index in `boxes`
+    centers: KdTree,
+    /// Largest distance of a box corner from its centre
+    reach: f64,
+}
+
+impl Mesh {
+    /// Build a spatial index of the nodes and elements; elements with
+    /// missing nodes are left out
+    pub fn spatial_index(&self) -> SpatialIndex<'_> {
+        let nodes = KdTree::new(
+            self.nodes
+                .values()
+                .map(|node| (node.id, node.coords()))
+                .collect(),
+        );
+        let mut boxes = Vec::with_capacity(self.elements.len());
+        for element in self.elements.values() {
+            let coords: Option<Vec<[f64; 3]>> = element
+                .nodes
+                .iter()
+                .map(|id| self.nodes.get(id).map(Node::coords))
+                .collect();
+            let Some(coords) = coords.filter(|c| !c.is_empty()) else {
+                continue;
+            };
+            let mut min = coords[0];
+            let mut max = coords[0];
+            for c in &coords {
+                for d in 0..3 {
+                    min[d] = min[d].min(c[d]);
+                    max[d] = max[d].max(c[d]);
+                }
+            }
+            boxes.push((element.id, min, max));
+        }
+        let reach = boxes
+            .iter()
+            .map(|(_, min, max)| 0.5 * distance_squared(*min, *max).sqrt())
+            .fold(0.0, f64::max);
+        let centers = KdTree::new(
+            boxes
+                .iter()
+                .enumerate()
+                .map(|(i, (_, min, max))| (i as i32, [0, 1, 2].map(|d| 0.5 * (min[d] + max[d]))))
+                .collect(),
+        );
+        SpatialIndex {
+            mesh: self,
+            nodes,
+            boxes,
+            centers,
+            reach,
+        }
+    }
+}
+
+impl SpatialIndex<'_> {
+    /// Node closest to `x` and its distance
+    pub fn nearest_node(&self, x: [f64; 3]) -> Option<(i32, f64)> {
+        self.nodes.nearest(x)
+    }
+
+    /// Nodes within `radius` of `x` with their distances, closest first
+    pub fn nodes_within(&self, x: [f64; 3], radius: f64) -> Vec<(i32, f64)> {
+        let mut found = Vec::new();
+        self.nodes.within(x, radius, &mut found);
+        found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        found
+    }
+
+    /// Elements whose bounding box lies within `radius` of `x`, in ID order
+    pub fn elements_near(&self, x: [f64; 3], radius: f64) -> Vec<i32> {
+        let mut candidates = Vec::new();
+        self.centers.within(x, radius + self.reach, &mut candidates);
+        let mut found: Vec<i32> = candidates
+            .into_iter()
+            .map(|(i, _)| self.boxes[i as usize])
+            .filter(|(_, min, max)| {
+                let gap: f64 = (0..3)
+                    .map(|d| (min[d] - x[d]).max(x[d] - max[d]).max(0.0).powi(2))
+                    .sum();
+                gap <= radius * radius
+            })
+            .map(|(id, _, _)| id)
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Solid element containing `x`, the lowest ID if it lies on a shared
+    /// face; `tolerance` is in natural coordinates
+    ///
+    /// Only the element types of [`SolidElement`] are searched.
+    pub fn locate(&self, x: [f64; 3], tolerance: f64) -> Option<PointLocation> {
+        for id in self.elements_near(x, 0.0) {
+            let element = &self.mesh.elements[&id];
+            if !SolidElement::supports(element.element_type)
+                || element.nodes.len() != element.element_type.num_nodes()
+            {
+                continue;
+            }
+            let solid = SolidElement::new(id, element.element_type, element.nodes.clone());
+            let nodes: Vec<Node> = element
+                .nodes
+                .iter()
+                .map(|n| self.mesh.nodes[n].clone())
+                .collect();
+            if let Ok(Some(natural)) = solid.natural_coordinates(&nodes, x)
+                && solid.contains_natural(natural, tolerance)
+            {
+                return Some(PointLocation {
+                    element: id,
+                    natural,
+                });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementType;
+    use crate::mesh_builder::MeshBuilder;
+    use crate::structured_mesh::Division;
+
+    fn brick(element_type: ElementType) -> Mesh {
+        let divisions = [
+            Division::uniform(4),
+            Division::uniform(3),
+            Division::graded(2, 3.0),
+        ];
+        MeshBuilder::brick([4.0, 3.0, 2.0], divisions, element_type)
+            .unwrap()
+            .mesh
+    }
+
+    fn brute_force_nearest(mesh: &Mesh, x: [f64; 3]) -> (i32, f64) {
+        let mut ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+        ids.sort_unstable();
+        ids.into_iter()
+            .map(|id| (id, distance_squared(mesh.nodes[&id].coords(), x).sqrt()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+    }
+
+    #[test]
+    fn nearest_node_matches_brute_force() {
+        let mesh = brick(ElementType::C3D20);
+        let index = mesh.spatial_index();
+        for i in 0..50 {
+            let t = i as f64;
+            let x = [
+                (t * 0.37).sin() * 3.0 + 2.0,
+                (t * 0.71).cos() * 2.0 + 1.5,
+                (t * 1.3).sin() + 0.7,
+            ];
+            let (id, distance) = index.nearest_node(x).unwrap();
+            let (expected_id, expected) = brute_force_nearest(&mesh, x);
+            assert!((distance - expected).abs() < 1e-12);
+            assert_eq!(id, expected_id);
+        }
+        assert!(Mesh::new().spatial_index().nearest_node([0.0; 3]).is_none());
+    }
+
+    #[test]
+    fn radius_search_finds_every_node_in_range() {
+        let mesh = brick(ElementType::C3D8);
+        let index = mesh.spatial_index();
+        let x = [2.0, 1.5, 0.5];
+        let found = index.nodes_within(x, 1.2);
+        let expected = mesh
+            .nodes
+            .values()
+            .filter(|n| distance_squared(n.coords(), x) <= 1.44)
+            .count();
+        assert_eq!(found.len(), expected);
+        assert!(found.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(index.nodes_within([10.0; 3], 1.0).is_empty());
+    }
+
+    #[test]
+    fn locates_points_in_elements() {
+        let mesh = brick(ElementType::C3D20);
+        let index = mesh.spatial_index();
+        // Element 6 spans [1, 2] x [1, 2] x [0, 0.5] in the first layer
+        let location = index.locate([1.25, 1.5, 0.125], 1e-9).unwrap();
+        assert_eq!(location.element, 6);
+        assert!((location.natural[0] + 0.5).abs() < 1e-9);
+        assert!(location.natural[1].abs() < 1e-9);
+        assert!((location.natural[2] + 0.5).abs() < 1e-9);
+        // On the face shared by elements 1 and 2
+        assert_eq!(index.locate([1.0, 0.5, 0.25], 1e-9).unwrap().element, 1);
+        assert!(index.locate([4.5, 0.5, 0.5], 1e-9).is_none());
+
+        assert_eq!(index.elements_near([1.5, 1.5, 0.25], 0.0), vec![6]);
+        assert_eq!(index.elements_near([4.5, 0.5, 0.25], 0.5), vec![4]);
+    }
+}
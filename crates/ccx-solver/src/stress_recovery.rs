@@ -0,0 +1,616 @@
+//! Stress and strain recovery for solid elements after a static solve.
+//!
+//! Stresses `S`, total strains `E` and elastic strains `EE` are evaluated at
+//! the integration points of every C3D4, C3D8, C3D10 and C3D20 element from
+//! the solved displacements, extrapolated to the element nodes and averaged
+//! over the elements sharing a node, like the nodal `STRESS` and `TOSTRAIN`
+//! output of CalculiX.
+//!
+//! Components use the `.dat` order `xx, yy, zz, xy, xz, yz`; strains are
+//! tensor components, as written by CalculiX.
+//!
+//! [`NodalAveraging`] limits the averaging to element sets or materials, or
+//! switches it off: nodes on the border of two regions are then split into
+//! one node per region, so that jumps of the stresses across material
+//! boundaries survive in the nodal output.
+//!
+//! B31 beams get section forces `SF` instead: axial force, shear forces,
+//! torque and bending moments at both element ends, in the local beam axes.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nalgebra::DVector;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::elements::solid::elastic_strain;
+use crate::elements::{DynamicElement, SolidElement};
+use crate::materials::MaterialLibrary;
+use crate::mesh::{ElementType, Mesh, Node};
+use crate::sets::Sets;
+
+/// Integration point stresses and strains of one element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementStresses {
+    pub element_id: i32,
+    /// Stresses `S`, one entry per integration point, in CalculiX order
+    pub integration_points: Vec<[f64; 6]>,
+    /// Total strains `E`, one entry per integration point
+    pub strains: Vec<[f64; 6]>,
+    /// Elastic strains `EE`, one entry per integration point
+    pub elastic_strains: Vec<[f64; 6]>,
+}
+
+/// Stress field of the solid elements of a model
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct StressField {
+    /// Integration point stresses, by increasing element ID
+    pub elements: Vec<ElementStresses>,
+    /// Extrapolated nodal stresses averaged over the adjacent elements
+    pub nodal: BTreeMap<i32, [f64; 6]>,
+    /// Extrapolated, averaged nodal total strains
+    pub nodal_strains: BTreeMap<i32, [f64; 6]>,
+    /// Extrapolated, averaged nodal elastic strains
+    pub nodal_elastic_strains: BTreeMap<i32, [f64; 6]>,
+    /// Nodes added by splitting the mesh between averaging regions, mapped
+    /// to the mesh node they duplicate
+    pub split_nodes: BTreeMap<i32, i32>,
+    /// Connectivity of the elements that use split nodes
+    pub element_nodes: BTreeMap<i32, Vec<i32>>,
+}
+
+/// Which elements contribute to an averaged nodal value
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum NodalAveraging {
+    /// Average over all elements sharing a node
+    #[default]
+    All,
+    /// No averaging: every element keeps its own extrapolated values
+    None,
+    /// Average within the listed element sets; an element belongs to the
+    /// first set that contains it, elements in none of them form one more
+    /// region
+    ElementSets(Vec<String>),
+    /// Average within the elements of the same material
+    Material,
+}
+
+impl NodalAveraging {
+    /// Parse `all`, `none`, `material` or `elset:<name>[,<name>...]`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let lower = name.to_ascii_lowercase();
+        match lower.as_str() {
+            "all" => Ok(Self::All),
+            "none" => Ok(Self::None),
+            "material" => Ok(Self::Material),
+            _ => match lower.strip_prefix("elset:") {
+                Some(_) => {
+                    let names: Vec<String> = name["elset:".len()..]
+                        .split(',')
+                        .map(|set| set.trim().to_string())
+                        .filter(|set| !set.is_empty())
+                        .collect();
+                    if names.is_empty() {
+                        Err("elset averaging requires at least one set name".to_string())
+                    } else {
+                        Ok(Self::ElementSets(names))
+                    }
+                }
+                None => Err(format!("unknown nodal averaging {name}")),
+            },
+        }
+    }
+
+    /// Averaging region of every element of `mesh`
+    pub fn element_regions(
+        &self,
+        mesh: &Mesh,
+        materials: &MaterialLibrary,
+        sets: &Sets,
+    ) -> Result<BTreeMap<i32, usize>, String> {
+        let mut elem_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+        elem_ids.sort_unstable();
+        match self {
+            Self::All => Ok(elem_ids.into_iter().map(|id| (id, 0)).collect()),
+            Self::None => Ok(elem_ids.into_iter().zip(0..).collect()),
+            Self::ElementSets(names) => {
+                let members = names
+                    .iter()
+                    .map(|name| {
+                        // CalculiX set names are case-insensitive
+                        sets.element_sets
+                            .values()
+                            .find(|set| set.name.eq_ignore_ascii_case(name))
+                            .map(|set| &set.elements)
+                            .map(|elements| elements.iter().copied().collect::<BTreeSet<_>>())
+                            .ok_or(format!("Element set {} not found", name))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(elem_ids
+                    .into_iter()
+                    .map(|id| {
+                        let region = members
+                            .iter()
+                            .position(|set| set.contains(&id))
+                            .unwrap_or(names.len());
+                        (id, region)
+                    })
+                    .collect())
+            }
+            Self::Material => {
+                let names: Vec<Option<&str>> = elem_ids
+                    .iter()
+                    .map(|&id| materials.get_element_material(id).map(|m| m.name.as_str()))
+                    .collect();
+                let index: BTreeMap<Option<&str>, usize> = names
+                    .iter()
+                    .copied()
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .zip(0..)
+                    .collect();
+                Ok(elem_ids
+                    .into_iter()
+                    .zip(names)
+                    .map(|(id, name)| (id, index[&name]))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Section forces of one beam element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeamSectionForces {
+    pub element_id: i32,
+    /// `N, Vy, Vz, Mt, My, Mz` at the first and second element node
+    pub ends: [[f64; 6]; 2],
+}
+
+impl StressField {
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Re-average the nodal fields within the given element regions
+    ///
+    /// Every node shared by elements of several regions keeps its ID in the
+    /// lowest region and is duplicated for the others, with new IDs above
+    /// the largest mesh node ID.
+    pub fn average_within(&mut self, mesh: &Mesh, regions: &BTreeMap<i32, usize>) {
+        let region = |elem_id: i32| regions.get(&elem_id).copied().unwrap_or(0);
+        let mut node_regions: BTreeMap<i32, BTreeSet<usize>> = BTreeMap::new();
+        for element in &self.elements {
+            for &node_id in &mesh.elements[&element.element_id].nodes {
+                node_regions
+                    .entry(node_id)
+                    .or_default()
+                    .insert(region(element.element_id));
+            }
+        }
+
+        let mut next_id = mesh.nodes.keys().max().copied().unwrap_or(0);
+        let mut renumbered: BTreeMap<(i32, usize), i32> = BTreeMap::new();
+        self.split_nodes.clear();
+        for (node_id, node_regions) in node_regions {
+            for other in node_regions.into_iter().skip(1) {
+                next_id += 1;
+                renumbered.insert((node_id, other), next_id);
+                self.split_nodes.insert(next_id, node_id);
+            }
+        }
+
+        self.element_nodes = self
+            .elements
+            .iter()
+            .filter_map(|element| {
+                let nodes = &mesh.elements[&element.element_id].nodes;
+                let region = region(element.element_id);
+                let split: Vec<i32> = nodes
+                    .iter()
+                    .map(|&node_id| {
+                        renumbered
+                            .get(&(node_id, region))
+                            .copied()
+                            .unwrap_or(node_id)
+                    })
+                    .collect();
+                (&split != nodes).then_some((element.element_id, split))
+            })
+            .collect();
+
+        let connectivity = &self.element_nodes;
+        self.nodal = average_at_nodes(mesh, &self.elements, connectivity, |e| {
+            &e.integration_points
+        });
+        self.nodal_strains = average_at_nodes(mesh, &self.elements, connectivity, |e| &e.strains);
+        self.nodal_elastic_strains =
+            average_at_nodes(mesh, &self.elements, connectivity, |e| &e.elastic_strains);
+    }
+
+    /// Node IDs of an element in the nodal fields, `mesh_nodes` unless the
+    /// element uses split nodes
+    pub fn element_node_ids<'a>(&'a self, element_id: i32, mesh_nodes: &'a [i32]) -> &'a [i32] {
+        self.element_nodes
+            .get(&element_id)
+            .map_or(mesh_nodes, Vec::as_slice)
+    }
+}
+
+/// Recover the stresses and strains of all solid elements from global displacements
+///
+/// `dofs_per_node` is the stride of `displacements`, as used in assembly.
+/// Elements of other types are skipped.
+pub fn recover_stresses(
+    mesh: &Mesh,
+    materials: &MaterialLibrary,
+    displacements: &DVector<f64>,
+    dofs_per_node: usize,
+) -> Result<StressField, String> {
+    let mut elem_ids: Vec<i32> = mesh
+        .elements
+        .iter()
+        .filter(|(_, element)| SolidElement::supports(element.element_type))
+        .map(|(&id, _)| id)
+        .collect();
+    elem_ids.sort_unstable();
+
+    let elements = elem_ids
+        .par_iter()
+        .map(|&elem_id| {
+            let element = &mesh.elements[&elem_id];
+            let solid = SolidElement::new(elem_id, element.element_type, element.nodes.clone());
+            let nodes = element_nodes(mesh, &element.nodes)?;
+            let material = materials
+                .get_element_material(elem_id)
+                .ok_or(format!("No material assigned to element {}", elem_id))?;
+            let u_e = element_displacements(displacements, &element.nodes, dofs_per_node, 3)
+                .ok_or(format!("Element {} has DOFs outside the solution", elem_id))?;
+
+            let stresses = solid.stresses(&nodes, material, &u_e)?;
+            let strains = solid.strains(&nodes, &u_e)?;
+            let elastic_strains = stresses
+                .iter()
+                .map(|stress| elastic_strain(material, stress))
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(ElementStresses {
+                element_id: elem_id,
+                integration_points: stresses,
+                strains,
+                elastic_strains,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let shared = BTreeMap::new();
+    let nodal = average_at_nodes(mesh, &elements, &shared, |e| &e.integration_points);
+    let nodal_strains = average_at_nodes(mesh, &elements, &shared, |e| &e.strains);
+    let nodal_elastic_strains = average_at_nodes(mesh, &elements, &shared, |e| &e.elastic_strains);
+    Ok(StressField {
+        elements,
+        nodal,
+        nodal_strains,
+        nodal_elastic_strains,
+        ..Default::default()
+    })
+}
+
+/// Recover the section forces of all B31 beams from global displacements
+///
+/// `default_area` must be the area the beams were assembled with.
+pub fn recover_section_forces(
+    mesh: &Mesh,
+    materials: &MaterialLibrary,
+    displacements: &DVector<f64>,
+    dofs_per_node: usize,
+    default_area: f64,
+) -> Result<Vec<BeamSectionForces>, String> {
+    let mut elem_ids: Vec<i32> = mesh
+        .elements
+        .iter()
+        .filter(|(_, element)| element.element_type == ElementType::B31)
+        .map(|(&id, _)| id)
+        .collect();
+    elem_ids.sort_unstable();
+
+    elem_ids
+        .into_iter()
+        .map(|elem_id| {
+            let element = &mesh.elements[&elem_id];
+            let Some(DynamicElement::Beam(beam)) = DynamicElement::from_mesh_element(
+                element.element_type,
+                elem_id,
+                element.nodes.clone(),
+                default_area,
+            ) else {
+                return Err(format!("Element {} is not a B31 beam", elem_id));
+            };
+            let nodes = element_nodes(mesh, &element.nodes)?;
+            let material = materials
+                .get_element_material(elem_id)
+                .ok_or(format!("No material assigned to element {}", elem_id))?;
+            let u_e = element_displacements(displacements, &element.nodes, dofs_per_node, 6)
+                .ok_or(format!("Element {} has DOFs outside the solution", elem_id))?;
+            Ok(BeamSectionForces {
+                element_id: elem_id,
+                ends: beam.section_forces(&nodes, material, &u_e)?,
+            })
+        })
+        .collect()
+}
+
+fn element_nodes(mesh: &Mesh, node_ids: &[i32]) -> Result<Vec<Node>, String> {
+    node_ids
+        .iter()
+        .map(|&node_id| {
+            mesh.nodes
+                .get(&node_id)
+                .cloned()
+                .ok_or(format!("Node {} not found", node_id))
+        })
+        .collect()
+}
+
+/// The first `dofs` displacements of every node in `node_ids`
+fn element_displacements(
+    displacements: &DVector<f64>,
+    node_ids: &[i32],
+    dofs_per_node: usize,
+    dofs: usize,
+) -> Option<Vec<f64>> {
+    node_ids
+        .iter()
+        .flat_map(|&node_id| {
+            let base = (node_id - 1) as usize * dofs_per_node;
+            (base..base + dofs).map(|dof| displacements.get(dof).copied())
+        })
+        .collect()
+}
+
+/// Extrapolate one integration point field to the nodes and average it
+///
+/// `connectivity` overrides the mesh connectivity of split elements.
+fn average_at_nodes(
+    mesh: &Mesh,
+    elements: &[ElementStresses],
+    connectivity: &BTreeMap<i32, Vec<i32>>,
+    field: impl Fn(&ElementStresses) -> &Vec<[f64; 6]>,
+) -> BTreeMap<i32, [f64; 6]> {
+    let mut sums: BTreeMap<i32, ([f64; 6], usize)> = BTreeMap::new();
+    for element in elements {
+        let mesh_element = &mesh.elements[&element.element_id];
+        let solid = SolidElement::new(
+            element.element_id,
+            mesh_element.element_type,
+            mesh_element.nodes.clone(),
+        );
+        let nodal = solid.extrapolate_to_nodes(field(element));
+        let node_ids = connectivity
+            .get(&element.element_id)
+            .unwrap_or(&mesh_element.nodes);
+        for (node_id, value) in node_ids.iter().zip(nodal) {
+            let (sum, count) = sums.entry(*node_id).or_insert(([0.0; 6], 0));
+            for (s, v) in sum.iter_mut().zip(value) {
+                *s += v;
+            }
+            *count += 1;
+        }
+    }
+    sums.into_iter()
+        .map(|(node_id, (sum, count))| (node_id, sum.map(|s| s / count as f64)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boundary_conditions::{BoundaryConditions, ConcentratedLoad, DisplacementBC};
+    use crate::materials::Material;
+    use crate::mesh::Element;
+    use crate::sparse_assembly::SparseGlobalSystem;
+
+    #[test]
+    fn uniaxial_bar_has_uniform_stress() {
+        // 4 x 1 x 1 bar of C3D8 elements pulled along x with 1000 in total
+        let mut mesh = Mesh::new();
+        let mut id = 0;
+        for i in 0..=4 {
+            for (y, z) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                id += 1;
+                mesh.add_node(Node::new(id, i as f64, y, z));
+            }
+        }
+        let mut library = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(210000.0);
+        steel.poissons_ratio = Some(0.3);
+        library.add_material(steel);
+        for e in 0..4 {
+            let a = 4 * e + 1;
+            let nodes = vec![a, a + 4, a + 5, a + 1, a + 3, a + 7, a + 6, a + 2];
+            mesh.add_element(Element::new(e + 1, ElementType::C3D8, nodes))
+                .unwrap();
+            library.assign_material(e + 1, "STEEL".to_string());
+        }
+        mesh.calculate_dofs();
+
+        // Symmetry supports on x = 0, one node held in y and z
+        let mut bcs = BoundaryConditions::new();
+        for node in 1..=4 {
+            bcs.add_displacement_bc(DisplacementBC::new(node, 1, 1, 0.0));
+        }
+        bcs.add_displacement_bc(DisplacementBC::new(1, 2, 3, 0.0));
+        bcs.add_displacement_bc(DisplacementBC::new(2, 3, 3, 0.0));
+        bcs.add_displacement_bc(DisplacementBC::new(4, 2, 2, 0.0));
+        for node in 17..=20 {
+            bcs.add_concentrated_load(ConcentratedLoad::new(node, 1, 250.0));
+        }
+
+        let system = SparseGlobalSystem::assemble(&mesh, &library, &bcs, 1.0).unwrap();
+        let u = system.solve().unwrap();
+        let field = recover_stresses(&mesh, &library, &u, 3).unwrap();
+
+        assert_eq!(field.elements.len(), 4);
+        assert_eq!(field.elements[0].integration_points.len(), 8);
+        assert_eq!(field.nodal.len(), 20);
+        let stresses = field
+            .elements
+            .iter()
+            .flat_map(|e| e.integration_points.iter())
+            .chain(field.nodal.values());
+        for stress in stresses {
+            assert!((stress[0] - 1000.0).abs() < 1e-3, "{:?}", stress);
+            for s in &stress[1..] {
+                assert!(s.abs() < 1e-3, "{:?}", stress);
+            }
+        }
+
+        // E = σ/E along the bar, -ν σ/E across it; EE = E without initial strains
+        let expected = [
+            1000.0 / 210000.0,
+            -0.3 * 1000.0 / 210000.0,
+            -0.3 * 1000.0 / 210000.0,
+        ];
+        let strains = field
+            .elements
+            .iter()
+            .flat_map(|e| e.strains.iter().chain(&e.elastic_strains))
+            .chain(field.nodal_strains.values())
+            .chain(field.nodal_elastic_strains.values());
+        for strain in strains {
+            for c in 0..6 {
+                let expected = expected.get(c).copied().unwrap_or(0.0);
+                assert!((strain[c] - expected).abs() < 1e-9, "{:?}", strain);
+            }
+        }
+        assert_eq!(field.nodal_strains.len(), 20);
+    }
+
+    #[test]
+    fn averaging_regions_split_shared_nodes() {
+        // Two unit C3D8 elements side by side in x, sharing nodes 2, 3, 6, 7,
+        // with uniform stresses of 100 and 200
+        let mut mesh = Mesh::new();
+        let mut id = 0;
+        for z in [0.0, 1.0] {
+            for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                id += 1;
+                mesh.add_node(Node::new(id, x, y, z));
+            }
+        }
+        for (x, y) in [(2.0, 0.0), (2.0, 1.0)] {
+            for z in [0.0, 1.0] {
+                id += 1;
+                mesh.add_node(Node::new(id, x, y, z));
+            }
+        }
+        mesh.add_element(Element::new(1, ElementType::C3D8, (1..=8).collect()))
+            .unwrap();
+        mesh.add_element(Element::new(
+            2,
+            ElementType::C3D8,
+            vec![2, 9, 11, 3, 6, 10, 12, 7],
+        ))
+        .unwrap();
+        let mut library = MaterialLibrary::new();
+        for (elem_id, name) in [(1, "STEEL"), (2, "ALU")] {
+            let mut material = Material::new(name.to_string());
+            material.elastic_modulus = Some(1000.0);
+            material.poissons_ratio = Some(0.0);
+            library.add_material(material);
+            library.assign_material(elem_id, name.to_string());
+        }
+        let mut sets = Sets::new();
+        sets.add_element_set(crate::sets::ElementSet {
+            name: "LEFT".to_string(),
+            elements: vec![1],
+        });
+
+        let uniform = |elem_id, value| ElementStresses {
+            element_id: elem_id,
+            integration_points: vec![[value, 0.0, 0.0, 0.0, 0.0, 0.0]; 8],
+            strains: vec![[value / 1000.0, 0.0, 0.0, 0.0, 0.0, 0.0]; 8],
+            elastic_strains: vec![[value / 1000.0, 0.0, 0.0, 0.0, 0.0, 0.0]; 8],
+        };
+        let mut field = StressField {
+            elements: vec![uniform(1, 100.0), uniform(2, 200.0)],
+            ..Default::default()
+        };
+
+        let all = NodalAveraging::All
+            .element_regions(&mesh, &library, &sets)
+            .unwrap();
+        field.average_within(&mesh, &all);
+        assert!(field.split_nodes.is_empty());
+        assert_eq!(field.nodal.len(), 12);
+        assert!((field.nodal[&2][0] - 150.0).abs() < 1e-9);
+
+        for averaging in [
+            NodalAveraging::None,
+            NodalAveraging::Material,
+            NodalAveraging::ElementSets(vec!["left".to_string()]),
+        ] {
+            let regions = averaging.element_regions(&mesh, &library, &sets).unwrap();
+            field.average_within(&mesh, &regions);
+            assert_eq!(field.split_nodes.len(), 4, "{:?}", averaging);
+            assert_eq!(field.nodal.len(), 16);
+            assert_eq!(field.nodal_strains.len(), 16);
+            let first = field.element_node_ids(1, &mesh.elements[&1].nodes);
+            let second = field.element_node_ids(2, &mesh.elements[&2].nodes);
+            assert_eq!(field.element_nodes.len(), 1);
+            for node in first {
+                assert!((field.nodal[node][0] - 100.0).abs() < 1e-9);
+            }
+            for node in second {
+                assert!((field.nodal[node][0] - 200.0).abs() < 1e-9);
+                assert!((field.nodal_elastic_strains[node][0] - 0.2).abs() < 1e-12);
+            }
+            let mut origins: Vec<i32> = field.split_nodes.values().copied().collect();
+            origins.sort_unstable();
+            assert_eq!(origins, vec![2, 3, 6, 7]);
+        }
+
+        let unknown = NodalAveraging::ElementSets(vec!["WELD".to_string()]);
+        assert!(unknown.element_regions(&mesh, &library, &sets).is_err());
+    }
+
+    #[test]
+    fn cantilever_beam_section_forces() {
+        // Two B31 elements along x, clamped at node 1, tip load 100 in y
+        let mut mesh = Mesh::new();
+        for id in 1..=3 {
+            mesh.add_node(Node::new(id, (id - 1) as f64, 0.0, 0.0));
+        }
+        let mut library = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(210000.0);
+        steel.poissons_ratio = Some(0.3);
+        library.add_material(steel);
+        for e in 1..=2 {
+            mesh.add_element(Element::new(e, ElementType::B31, vec![e, e + 1]))
+                .unwrap();
+            library.assign_material(e, "STEEL".to_string());
+        }
+        mesh.calculate_dofs();
+
+        let mut bcs = BoundaryConditions::new();
+        bcs.add_displacement_bc(DisplacementBC::new(1, 1, 6, 0.0));
+        bcs.add_concentrated_load(ConcentratedLoad::new(3, 2, 100.0));
+
+        let system = SparseGlobalSystem::assemble(&mesh, &library, &bcs, 0.01).unwrap();
+        let u = system.solve().unwrap();
+        let forces = recover_section_forces(&mesh, &library, &u, 6, 0.01).unwrap();
+        assert!(recover_stresses(&mesh, &library, &u, 6).unwrap().is_empty());
+
+        assert_eq!(forces.len(), 2);
+        let [root, middle] = forces[0].ends;
+        let [_, tip] = forces[1].ends;
+        for end in [root, middle, tip] {
+            assert!((end[1] - 100.0).abs() < 1e-6, "{:?}", end);
+        }
+        assert!((root[5] - 200.0).abs() < 1e-6, "{:?}", root);
+        assert!((middle[5] - 100.0).abs() < 1e-6, "{:?}", middle);
+        assert!((forces[1].ends[0][5] - 100.0).abs() < 1e-6);
+        assert!(tip[5].abs() < 1e-6, "{:?}", tip);
+    }
+}
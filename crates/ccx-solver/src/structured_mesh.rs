@@ -0,0 +1,349 @@
+//! Structured meshing of simple blocks.
+//!
+//! Bricks, plates and hollow cylinders are meshed as a structured grid in
+//! parameter space (with the requested element counts and grading along
+//! every direction), converted to quadratic elements if asked for, and only
+//! then mapped to their shape. Mid-side nodes of curved edges therefore lie
+//! on the curve, and boundary node sets can be picked exactly on the grid.
+
+use crate::mesh::{Element, ElementType, Mesh, Node};
+use crate::mesh_builder::MeshBuilder;
+use crate::renumber::Renumbering;
+use crate::reordering::DofOrdering;
+use crate::sets::{ElementSet, NodeSet, Sets};
+
+/// Element count and grading along one direction of a block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Division {
+    pub count: usize,
+    /// Length of the last element over the length of the first one
+    pub grading: f64,
+}
+
+impl Division {
+    pub fn uniform(count: usize) -> Self {
+        Self {
+            count,
+            grading: 1.0,
+        }
+    }
+
+    pub fn graded(count: usize, grading: f64) -> Self {
+        Self { count, grading }
+    }
+
+    /// Node positions in [0, length], element lengths growing geometrically
+    pub(crate) fn positions(&self, length: f64) -> Result<Vec<f64>, String> {
+        if self.count == 0 {
+            return Err("Structured mesh needs at least one element per direction".to_string());
+        }
+        if !(self.grading > 0.0 && self.grading.is_finite()) {
+            return Err(format!("Invalid mesh grading {}", self.grading));
+        }
+        let ratio = if self.count > 1 {
+            self.grading.powf(1.0 / (self.count - 1) as f64)
+        } else {
+            1.0
+        };
+        let lengths: Vec<f64> = (0..self.count).map(|i| ratio.powi(i as i32)).collect();
+        let total: f64 = lengths.iter().sum();
+        let mut positions = vec![0.0];
+        let mut sum = 0.0;
+        for l in &lengths[..self.count - 1] {
+            sum += l;
+            positions.push(length * sum / total);
+        }
+        positions.push(length);
+        Ok(positions)
+    }
+}
+
+/// Mesh and sets of a structured primitive
+#[derive(Debug, Clone)]
+pub struct StructuredMesh {
+    pub mesh: Mesh,
+    /// `NALL`, `EALL` and one node set per block face
+    pub sets: Sets,
+}
+
+/// Linear element type the grid is built with, for a requested type
+fn linear_type(element_type: ElementType, solid: bool) -> Result<ElementType, String> {
+    match (element_type, solid) {
+        (ElementType::C3D8 | ElementType::C3D20, true) => Ok(ElementType::C3D8),
+        (ElementType::S4 | ElementType::S8, false) => Ok(ElementType::S4),
+        (ElementType::M3D4 | ElementType::M3D8, false) => Ok(ElementType::M3D4),
+        _ => Err(format!(
+            "Structured {} mesh cannot use {:?} elements",
+            if solid { "solid" } else { "plate" },
+            element_type
+        )),
+    }
+}
+
+/// Grid over `lengths` in parameter space, with the face node sets named by
+/// `faces` (minimum and maximum face per direction)
+fn parameter_block(
+    lengths: &[f64],
+    divisions: &[Division],
+    element_type: ElementType,
+    faces: &[[&str; 2]],
+) -> Result<StructuredMesh, String> {
+    let solid = lengths.len() == 3;
+    let linear = linear_type(element_type, solid)?;
+    let axes: Vec<Vec<f64>> = lengths
+        .iter()
+        .zip(divisions)
+        .map(|(&length, division)| division.positions(length))
+        .collect::<Result<_, _>>()?;
+    let n: Vec<usize> = axes.iter().map(Vec::len).collect();
+    let layers = if solid { n[2] } else { 1 };
+    let id = |i: usize, j: usize, k: usize| (1 + i + n[0] * (j + n[1] * k)) as i32;
+
+    let mut mesh = Mesh::new();
+    for k in 0..layers {
+        for j in 0..n[1] {
+            for i in 0..n[0] {
+                let z = if solid { axes[2][k] } else { 0.0 };
+                mesh.add_node(Node::new(id(i, j, k), axes[0][i], axes[1][j], z));
+            }
+        }
+    }
+    let mut element_id = 1;
+    for k in 0..layers.saturating_sub(1).max(1) {
+        for j in 0..n[1] - 1 {
+            for i in 0..n[0] - 1 {
+                let mut nodes = vec![
+                    id(i, j, k),
+                    id(i + 1, j, k),
+                    id(i + 1, j + 1, k),
+                    id(i, j + 1, k),
+                ];
+                if solid {
+                    let top: Vec<i32> = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)]
+                        .iter()
+                        .map(|&(a, b)| id(a, b, k + 1))
+                        .collect();
+                    nodes.extend(top);
+                }
+                mesh.add_element(Element::new(element_id, linear, nodes))?;
+                element_id += 1;
+            }
+        }
+    }
+    if element_type != linear {
+        mesh.convert_to_quadratic()?;
+    }
+
+    let mut sets = Sets::new();
+    let mut node_ids: Vec<i32> = mesh.nodes.keys().copied().collect();
+    node_ids.sort_unstable();
+    let mut element_ids: Vec<i32> = mesh.elements.keys().copied().collect();
+    element_ids.sort_unstable();
+    for (axis, [min_name, max_name]) in faces.iter().enumerate() {
+        let tolerance = 1e-12 * lengths[axis];
+        for (name, value) in [(min_name, 0.0), (max_name, lengths[axis])] {
+            let on_face = |id: &i32| (mesh.nodes[id].coords()[axis] - value).abs() <= tolerance;
+            let nodes = node_ids.iter().copied().filter(on_face).collect();
+            sets.add_node_set(NodeSet {
+                name: name.to_string(),
+                nodes,
+            });
+        }
+    }
+    sets.add_node_set(NodeSet {
+        name: "NALL".to_string(),
+        nodes: node_ids,
+    });
+    sets.add_element_set(ElementSet {
+        name: "EALL".to_string(),
+        elements: element_ids,
+    });
+    Ok(StructuredMesh { mesh, sets })
+}
+
+impl MeshBuilder {
+    /// Brick `[0, size]` meshed with C3D8 or C3D20 elements
+    ///
+    /// Node sets `XMIN`, `XMAX`, `YMIN`, `YMAX`, `ZMIN` and `ZMAX` hold the
+    /// nodes of the faces.
+    pub fn brick(
+        size: [f64; 3],
+        divisions: [Division; 3],
+        element_type: ElementType,
+    ) -> Result<StructuredMesh, String> {
+        parameter_block(
+            &size,
+            &divisions,
+            element_type,
+            &[["XMIN", "XMAX"], ["YMIN", "YMAX"], ["ZMIN", "ZMAX"]],
+        )
+    }
+
+    /// Rectangular plate `[0, size]` in the xy plane meshed with S4, S8,
+    /// M3D4 or M3D8 elements
+    ///
+    /// Node sets `XMIN`, `XMAX`, `YMIN` and `YMAX` hold the edge nodes.
+    pub fn plate(
+        size: [f64; 2],
+        divisions: [Division; 2],
+        element_type: ElementType,
+    ) -> Result<StructuredMesh, String> {
+        parameter_block(
+            &size,
+            &divisions,
+            element_type,
+            &[["XMIN", "XMAX"], ["YMIN", "YMAX"]],
+        )
+    }
+
+    /// Hollow cylinder (or sector of `sweep` degrees) around the z axis,
+    /// from z = 0 to `height`, meshed with C3D8 or C3D20 elements
+    ///
+    /// `divisions` are radial, circumferential and axial. Node sets `INNER`,
+    /// `OUTER`, `ZMIN` and `ZMAX` hold the surface nodes, and for sectors
+    /// `TMIN` and `TMAX` the nodes of the cut faces at 0 and `sweep` degrees.
+    pub fn cylinder(
+        inner_radius: f64,
+        outer_radius: f64,
+        height: f64,
+        sweep: f64,
+        divisions: [Division; 3],
+        element_type: ElementType,
+    ) -> Result<StructuredMesh, String> {
+        if !(inner_radius > 0.0 && outer_radius > inner_radius && height > 0.0) {
+            return Err(format!(
+                "Invalid cylinder radii {} to {} or height {}",
+                inner_radius, outer_radius, height
+            ));
+        }
+        if !(sweep > 0.0 && sweep <= 360.0) {
+            return Err(format!("Invalid cylinder sweep {} degrees", sweep));
+        }
+        let mut block = parameter_block(
+            &[outer_radius - inner_radius, sweep, height],
+            &divisions,
+            element_type,
+            &[["INNER", "OUTER"], ["TMIN", "TMAX"], ["ZMIN", "ZMAX"]],
+        )?;
+        for node in block.mesh.nodes.values_mut() {
+            let (r, theta) = (inner_radius + node.x, node.y.to_radians());
+            node.x = r * theta.cos();
+            node.y = r * theta.sin();
+        }
+        if sweep == 360.0 {
+            // Close the seam at 0/360 degrees and number the nodes densely
+            let merged = block.mesh.merge_duplicate_nodes(1e-9 * outer_radius)?;
+            block.sets.apply_node_map(&merged);
+            block.sets.node_sets.remove("TMIN");
+            block.sets.node_sets.remove("TMAX");
+            let renumbering = Renumbering::new(&block.mesh, DofOrdering::Natural);
+            renumbering.apply_to_mesh(&mut block.mesh)?;
+            renumbering.apply_to_sets(&mut block.sets);
+        }
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graded_division_grows_geometrically() {
+        let positions = Division::graded(3, 4.0).positions(7.0).unwrap();
+        assert_eq!(positions.len(), 4);
+        let lengths: Vec<f64> = positions.windows(2).map(|w| w[1] - w[0]).collect();
+        for (length, expected) in lengths.iter().zip([1.0, 2.0, 4.0]) {
+            assert!((length - expected).abs() < 1e-12);
+        }
+        assert_eq!(Division::uniform(1).positions(2.0).unwrap(), vec![0.0, 2.0]);
+        assert!(Division::uniform(0).positions(1.0).is_err());
+        assert!(Division::graded(2, 0.0).positions(1.0).is_err());
+    }
+
+    #[test]
+    fn brick_has_grid_and_face_sets() {
+        let divisions = [
+            Division::uniform(2),
+            Division::uniform(3),
+            Division::graded(4, 2.0),
+        ];
+        let brick = MeshBuilder::brick([2.0, 3.0, 1.0], divisions, ElementType::C3D8).unwrap();
+        assert_eq!(brick.mesh.nodes.len(), 3 * 4 * 5);
+        assert_eq!(brick.mesh.elements.len(), 2 * 3 * 4);
+        assert!(brick.mesh.validate().is_ok());
+        assert_eq!(brick.sets.get_nodes("XMIN").unwrap().len(), 4 * 5);
+        assert_eq!(brick.sets.get_nodes("ZMAX").unwrap().len(), 3 * 4);
+        assert_eq!(brick.sets.get_elements("EALL").unwrap().len(), 24);
+        let quality = brick.mesh.quality();
+        assert!(
+            quality
+                .elements
+                .values()
+                .all(|q| (q.jacobian_ratio - 1.0).abs() < 1e-12)
+        );
+
+        let quadratic =
+            MeshBuilder::brick([1.0; 3], [Division::uniform(1); 3], ElementType::C3D20).unwrap();
+        assert_eq!(quadratic.mesh.nodes.len(), 20);
+        assert_eq!(quadratic.sets.get_nodes("YMAX").unwrap().len(), 8);
+        assert!(MeshBuilder::brick([1.0; 3], [Division::uniform(1); 3], ElementType::S4).is_err());
+    }
+
+    #[test]
+    fn plate_uses_shell_or_membrane_elements() {
+        let divisions = [Division::uniform(4), Division::uniform(2)];
+        let plate = MeshBuilder::plate([4.0, 1.0], divisions, ElementType::S8).unwrap();
+        assert_eq!(plate.mesh.elements.len(), 8);
+        assert!(
+            plate
+                .mesh
+                .elements
+                .values()
+                .all(|e| e.element_type == ElementType::S8)
+        );
+        // Corner grid 5 x 3 plus 4 * 3 + 5 * 2 mid-side nodes
+        assert_eq!(plate.mesh.nodes.len(), 15 + 22);
+        assert_eq!(plate.sets.get_nodes("XMAX").unwrap().len(), 5);
+        assert!(plate.sets.get_nodes("ZMIN").is_none());
+
+        let membrane = MeshBuilder::plate([1.0, 1.0], divisions, ElementType::M3D4).unwrap();
+        assert_eq!(membrane.mesh.elements[&1].element_type, ElementType::M3D4);
+    }
+
+    #[test]
+    fn closed_cylinder_shares_the_seam() {
+        let divisions = [
+            Division::uniform(2),
+            Division::uniform(8),
+            Division::uniform(1),
+        ];
+        let tube =
+            MeshBuilder::cylinder(1.0, 2.0, 0.5, 360.0, divisions, ElementType::C3D20).unwrap();
+        assert_eq!(tube.mesh.elements.len(), 16);
+        assert!(tube.mesh.validate().is_ok());
+        // No free faces along the seam: 2 x 16 end faces plus 8 inner and outer
+        assert_eq!(tube.mesh.free_faces().len(), 32 + 16);
+        assert!(tube.sets.get_nodes("TMIN").is_none());
+        let max_id = tube.mesh.nodes.keys().max().copied().unwrap();
+        assert_eq!(max_id as usize, tube.mesh.nodes.len());
+
+        // Mid-side nodes of the outer surface lie on the circle
+        for id in tube.sets.get_nodes("OUTER").unwrap() {
+            let [x, y, _] = tube.mesh.nodes[id].coords();
+            assert!((x.hypot(y) - 2.0).abs() < 1e-12);
+        }
+        assert!(
+            tube.mesh
+                .quality()
+                .elements
+                .values()
+                .all(|q| q.jacobian_ratio > 0.5)
+        );
+
+        let sector = MeshBuilder::cylinder(1.0, 2.0, 0.5, 90.0, divisions, ElementType::C3D8);
+        let sector = sector.unwrap();
+        assert_eq!(sector.sets.get_nodes("TMAX").unwrap().len(), 3 * 2);
+        assert!(MeshBuilder::cylinder(0.0, 2.0, 1.0, 90.0, divisions, ElementType::C3D8).is_err());
+    }
+}
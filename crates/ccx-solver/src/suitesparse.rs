@@ -0,0 +1,493 @@
+//! SuiteSparse direct solver backends (feature `suitesparse`).
+//!
+//! Thin FFI bindings to the system SuiteSparse libraries:
+//!
+//! - [`UmfpackSolver`]: UMFPACK multifrontal LU, for indefinite,
+//!   unsymmetric or ill-conditioned systems where Cholesky breaks down
+//! - [`CholmodSolver`]: CHOLMOD supernodal Cholesky with fill-reducing
+//!   ordering, for large symmetric positive definite systems
+//!
+//! Both keep their symbolic analysis while the sparsity pattern of the
+//! refactorized matrix is unchanged. The libraries are linked as
+//! `libumfpack` and `libcholmod`; set `SUITESPARSE_LIB_DIR` when they are
+//! not on the default linker path.
+
+use std::cell::UnsafeCell;
+use std::ffi::{c_int, c_void};
+use std::ptr;
+
+use nalgebra::DVector;
+use nalgebra_sparse::CsrMatrix;
+use nalgebra_sparse::pattern::SparsityPattern;
+
+use crate::linear_solver::LinearSolver;
+
+const UMFPACK_OK: c_int = 0;
+const UMFPACK_WARNING_SINGULAR_MATRIX: c_int = 1;
+/// Solve Aᵀx = b; the CSR arrays of K read as CSC describe Kᵀ
+const UMFPACK_AT: c_int = 1;
+
+#[link(name = "umfpack")]
+unsafe extern "C" {
+    fn umfpack_di_symbolic(
+        n_row: c_int,
+        n_col: c_int,
+        ap: *const c_int,
+        ai: *const c_int,
+        ax: *const f64,
+        symbolic: *mut *mut c_void,
+        control: *const f64,
+        info: *mut f64,
+    ) -> c_int;
+    fn umfpack_di_numeric(
+        ap: *const c_int,
+        ai: *const c_int,
+        ax: *const f64,
+        symbolic: *mut c_void,
+        numeric: *mut *mut c_void,
+        control: *const f64,
+        info: *mut f64,
+    ) -> c_int;
+    fn umfpack_di_solve(
+        sys: c_int,
+        ap: *const c_int,
+        ai: *const c_int,
+        ax: *const f64,
+        x: *mut f64,
+        b: *const f64,
+        numeric: *mut c_void,
+        control: *const f64,
+        info: *mut f64,
+    ) -> c_int;
+    fn umfpack_di_free_symbolic(symbolic: *mut *mut c_void);
+    fn umfpack_di_free_numeric(numeric: *mut *mut c_void);
+}
+
+/// CSR arrays converted to the 32-bit indices of the `di` interfaces
+struct CompressedArrays {
+    offsets: Vec<c_int>,
+    indices: Vec<c_int>,
+    values: Vec<f64>,
+}
+
+impl CompressedArrays {
+    fn from_csr(matrix: &CsrMatrix<f64>) -> Result<Self, String> {
+        let to_int = |v: &usize| {
+            c_int::try_from(*v).map_err(|_| "Matrix too large for 32-bit SuiteSparse indices")
+        };
+        Ok(Self {
+            offsets: matrix
+                .row_offsets()
+                .iter()
+                .map(to_int)
+                .collect::<Result<_, _>>()?,
+            indices: matrix
+                .col_indices()
+                .iter()
+                .map(to_int)
+                .collect::<Result<_, _>>()?,
+            values: matrix.values().to_vec(),
+        })
+    }
+}
+
+fn square_size(matrix: &CsrMatrix<f64>) -> Result<usize, String> {
+    if matrix.nrows() != matrix.ncols() {
+        return Err(format!(
+            "Direct solve needs a square matrix, got {}x{}",
+            matrix.nrows(),
+            matrix.ncols()
+        ));
+    }
+    Ok(matrix.nrows())
+}
+
+/// UMFPACK sparse LU solver.
+pub struct UmfpackSolver {
+    arrays: Option<CompressedArrays>,
+    pattern: Option<SparsityPattern>,
+    symbolic: *mut c_void,
+    numeric: *mut c_void,
+}
+
+impl UmfpackSolver {
+    pub fn new() -> Self {
+        Self {
+            arrays: None,
+            pattern: None,
+            symbolic: ptr::null_mut(),
+            numeric: ptr::null_mut(),
+        }
+    }
+
+    fn free_numeric(&mut self) {
+        if !self.numeric.is_null() {
+            // SAFETY: `numeric` was allocated by umfpack_di_numeric.
+            unsafe { umfpack_di_free_numeric(&mut self.numeric) };
+        }
+    }
+
+    fn free_symbolic(&mut self) {
+        if !self.symbolic.is_null() {
+            // SAFETY: `symbolic` was allocated by umfpack_di_symbolic.
+            unsafe { umfpack_di_free_symbolic(&mut self.symbolic) };
+        }
+    }
+}
+
+impl Default for UmfpackSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for UmfpackSolver {
+    fn drop(&mut self) {
+        self.free_numeric();
+        self.free_symbolic();
+    }
+}
+
+impl LinearSolver for UmfpackSolver {
+    fn name(&self) -> &'static str {
+        "umfpack"
+    }
+
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String> {
+        let n = square_size(matrix)? as c_int;
+        let arrays = CompressedArrays::from_csr(matrix)?;
+        self.free_numeric();
+
+        if self.pattern.as_ref() != Some(matrix.pattern()) || self.symbolic.is_null() {
+            self.free_symbolic();
+            self.pattern = None;
+            // SAFETY: the arrays describe a valid n×n compressed matrix and
+            // outlive the call; null control/info select the defaults.
+            let status = unsafe {
+                umfpack_di_symbolic(
+                    n,
+                    n,
+                    arrays.offsets.as_ptr(),
+                    arrays.indices.as_ptr(),
+                    arrays.values.as_ptr(),
+                    &mut self.symbolic,
+                    ptr::null(),
+                    ptr::null_mut(),
+                )
+            };
+            if status != UMFPACK_OK {
+                return Err(format!(
+                    "UMFPACK symbolic analysis failed (status {status})"
+                ));
+            }
+            self.pattern = Some(matrix.pattern().clone());
+        }
+
+        // SAFETY: as above; `symbolic` matches the pattern of the arrays.
+        let status = unsafe {
+            umfpack_di_numeric(
+                arrays.offsets.as_ptr(),
+                arrays.indices.as_ptr(),
+                arrays.values.as_ptr(),
+                self.symbolic,
+                &mut self.numeric,
+                ptr::null(),
+                ptr::null_mut(),
+            )
+        };
+        match status {
+            UMFPACK_OK => {
+                self.arrays = Some(arrays);
+                Ok(())
+            }
+            UMFPACK_WARNING_SINGULAR_MATRIX => {
+                self.free_numeric();
+                Err("UMFPACK factorization failed: matrix is singular".to_string())
+            }
+            _ => Err(format!(
+                "UMFPACK numeric factorization failed (status {status})"
+            )),
+        }
+    }
+
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String> {
+        let arrays = self
+            .arrays
+            .as_ref()
+            .filter(|_| !self.numeric.is_null())
+            .ok_or("UMFPACK solver used before factorize")?;
+        if rhs.len() + 1 != arrays.offsets.len() {
+            return Err(format!(
+                "Right-hand side has {} entries, matrix has {} rows",
+                rhs.len(),
+                arrays.offsets.len() - 1
+            ));
+        }
+        let mut x = DVector::zeros(rhs.len());
+        // SAFETY: x and rhs have n entries; `numeric` belongs to `arrays`.
+        let status = unsafe {
+            umfpack_di_solve(
+                UMFPACK_AT,
+                arrays.offsets.as_ptr(),
+                arrays.indices.as_ptr(),
+                arrays.values.as_ptr(),
+                x.as_mut_ptr(),
+                rhs.as_ptr(),
+                self.numeric,
+                ptr::null(),
+                ptr::null_mut(),
+            )
+        };
+        if status != UMFPACK_OK {
+            return Err(format!("UMFPACK solve failed (status {status})"));
+        }
+        Ok(x)
+    }
+}
+
+const CHOLMOD_INT: c_int = 0;
+const CHOLMOD_REAL: c_int = 1;
+const CHOLMOD_DOUBLE: c_int = 0;
+const CHOLMOD_A: c_int = 0;
+/// Use the lower triangle of a symmetric matrix
+const CHOLMOD_STYPE_LOWER: c_int = -1;
+/// Storage for `cholmod_common`, which is only handled through pointers;
+/// generously larger than the struct of any SuiteSparse release
+const CHOLMOD_COMMON_WORDS: usize = 4096;
+
+#[repr(C)]
+struct CholmodSparse {
+    nrow: usize,
+    ncol: usize,
+    nzmax: usize,
+    p: *mut c_void,
+    i: *mut c_void,
+    nz: *mut c_void,
+    x: *mut c_void,
+    z: *mut c_void,
+    stype: c_int,
+    itype: c_int,
+    xtype: c_int,
+    dtype: c_int,
+    sorted: c_int,
+    packed: c_int,
+}
+
+#[repr(C)]
+struct CholmodDense {
+    nrow: usize,
+    ncol: usize,
+    nzmax: usize,
+    d: usize,
+    x: *mut c_void,
+    z: *mut c_void,
+    xtype: c_int,
+    dtype: c_int,
+}
+
+/// Leading fields of `cholmod_factor`
+#[repr(C)]
+struct CholmodFactorHead {
+    n: usize,
+    /// Column at which the factorization failed, n if it succeeded
+    minor: usize,
+}
+
+#[link(name = "cholmod")]
+unsafe extern "C" {
+    fn cholmod_start(common: *mut c_void) -> c_int;
+    fn cholmod_finish(common: *mut c_void) -> c_int;
+    fn cholmod_analyze(a: *mut CholmodSparse, common: *mut c_void) -> *mut CholmodFactorHead;
+    fn cholmod_factorize(
+        a: *mut CholmodSparse,
+        factor: *mut CholmodFactorHead,
+        common: *mut c_void,
+    ) -> c_int;
+    fn cholmod_solve(
+        sys: c_int,
+        factor: *mut CholmodFactorHead,
+        b: *mut CholmodDense,
+        common: *mut c_void,
+    ) -> *mut CholmodDense;
+    fn cholmod_free_factor(factor: *mut *mut CholmodFactorHead, common: *mut c_void) -> c_int;
+    fn cholmod_free_dense(dense: *mut *mut CholmodDense, common: *mut c_void) -> c_int;
+}
+
+/// CHOLMOD sparse Cholesky solver.
+pub struct CholmodSolver {
+    /// CHOLMOD updates its statistics in the common even when solving.
+    common: Box<UnsafeCell<[u64; CHOLMOD_COMMON_WORDS]>>,
+    arrays: Option<CompressedArrays>,
+    pattern: Option<SparsityPattern>,
+    factor: *mut CholmodFactorHead,
+}
+
+impl CholmodSolver {
+    pub fn new() -> Self {
+        let common = Box::new(UnsafeCell::new([0u64; CHOLMOD_COMMON_WORDS]));
+        // SAFETY: `common` is zeroed, suitably aligned and large enough.
+        unsafe { cholmod_start(common.get().cast()) };
+        Self {
+            common,
+            arrays: None,
+            pattern: None,
+            factor: ptr::null_mut(),
+        }
+    }
+
+    fn common_ptr(&self) -> *mut c_void {
+        self.common.get().cast()
+    }
+
+    fn free_factor(&mut self) {
+        if !self.factor.is_null() {
+            let common = self.common_ptr();
+            // SAFETY: `factor` was allocated by cholmod_analyze with `common`.
+            unsafe { cholmod_free_factor(&mut self.factor, common) };
+        }
+    }
+}
+
+impl Default for CholmodSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for CholmodSolver {
+    fn drop(&mut self) {
+        self.free_factor();
+        let common = self.common_ptr();
+        // SAFETY: `common` was initialized by cholmod_start.
+        unsafe { cholmod_finish(common) };
+    }
+}
+
+impl LinearSolver for CholmodSolver {
+    fn name(&self) -> &'static str {
+        "cholmod"
+    }
+
+    fn factorize(&mut self, matrix: &CsrMatrix<f64>) -> Result<(), String> {
+        let n = square_size(matrix)?;
+        let mut arrays = CompressedArrays::from_csr(matrix)?;
+        // Symmetric: the CSR arrays are the CSC arrays as well.
+        let mut sparse = CholmodSparse {
+            nrow: n,
+            ncol: n,
+            nzmax: arrays.values.len(),
+            p: arrays.offsets.as_mut_ptr().cast(),
+            i: arrays.indices.as_mut_ptr().cast(),
+            nz: ptr::null_mut(),
+            x: arrays.values.as_mut_ptr().cast(),
+            z: ptr::null_mut(),
+            stype: CHOLMOD_STYPE_LOWER,
+            itype: CHOLMOD_INT,
+            xtype: CHOLMOD_REAL,
+            dtype: CHOLMOD_DOUBLE,
+            sorted: 1,
+            packed: 1,
+        };
+        let common = self.common_ptr();
+
+        if self.pattern.as_ref() != Some(matrix.pattern()) || self.factor.is_null() {
+            self.free_factor();
+            self.pattern = None;
+            // SAFETY: `sparse` points into `arrays`, alive for the call.
+            self.factor = unsafe { cholmod_analyze(&mut sparse, common) };
+            if self.factor.is_null() {
+                return Err("CHOLMOD symbolic analysis failed".to_string());
+            }
+            self.pattern = Some(matrix.pattern().clone());
+        }
+
+        // SAFETY: `factor` was analyzed for this pattern.
+        let ok = unsafe { cholmod_factorize(&mut sparse, self.factor, common) };
+        // SAFETY: `factor` is a valid cholmod_factor.
+        let (size, minor) = unsafe { ((*self.factor).n, (*self.factor).minor) };
+        if ok == 0 || minor < size {
+            self.arrays = None;
+            return Err(format!(
+                "CHOLMOD factorization failed at column {minor} (matrix not positive definite?)"
+            ));
+        }
+        self.arrays = Some(arrays);
+        Ok(())
+    }
+
+    fn solve(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, String> {
+        let arrays = self
+            .arrays
+            .as_ref()
+            .ok_or("CHOLMOD solver used before factorize")?;
+        let n = arrays.offsets.len() - 1;
+        if rhs.len() != n {
+            return Err(format!(
+                "Right-hand side has {} entries, matrix has {} rows",
+                rhs.len(),
+                n
+            ));
+        }
+        let mut b_values = rhs.as_slice().to_vec();
+        let mut b = CholmodDense {
+            nrow: n,
+            ncol: 1,
+            nzmax: n,
+            d: n,
+            x: b_values.as_mut_ptr().cast(),
+            z: ptr::null_mut(),
+            xtype: CHOLMOD_REAL,
+            dtype: CHOLMOD_DOUBLE,
+        };
+        let common = self.common_ptr();
+        // SAFETY: `factor` is numerically factorized; `b` points to n values.
+        let mut x = unsafe { cholmod_solve(CHOLMOD_A, self.factor, &mut b, common) };
+        if x.is_null() {
+            return Err("CHOLMOD solve failed".to_string());
+        }
+        // SAFETY: CHOLMOD returned an n×1 real dense matrix.
+        let solution = unsafe {
+            DVector::from_column_slice(std::slice::from_raw_parts((*x).x as *const f64, n))
+        };
+        // SAFETY: `x` was allocated by cholmod_solve with `common`.
+        unsafe { cholmod_free_dense(&mut x, common) };
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_sparse::CooMatrix;
+
+    fn system(indefinite: bool) -> CsrMatrix<f64> {
+        let mut coo = CooMatrix::new(3, 3);
+        coo.push(0, 0, 4.0);
+        coo.push(0, 1, 1.0);
+        coo.push(1, 0, 1.0);
+        coo.push(1, 1, if indefinite { -3.0 } else { 3.0 });
+        coo.push(2, 2, 2.0);
+        CsrMatrix::from(&coo)
+    }
+
+    #[test]
+    fn umfpack_solves_indefinite_system() {
+        let k = system(true);
+        let f = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let mut solver = UmfpackSolver::new();
+        solver.factorize(&k).unwrap();
+        let u = solver.solve(&f).unwrap();
+        assert!((&k * &u - &f).norm() < 1e-12);
+    }
+
+    #[test]
+    fn cholmod_solves_and_rejects_indefinite_system() {
+        let f = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let mut solver = CholmodSolver::new();
+        let k = system(false);
+        solver.factorize(&k).unwrap();
+        let u = solver.solve(&f).unwrap();
+        assert!((&k * &u - &f).norm() < 1e-12);
+        assert!(solver.factorize(&system(true)).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! Unit systems and plausibility checks of material data and gravity.
+//!
+//! CalculiX is unit-agnostic, so a deck that mixes systems (the classic
+//! case being a steel density of 7850 in a mm-t-s model, where it should be
+//! 7.85e-9) solves without complaint and gives wrong masses, frequencies and
+//! gravity loads. A [`UnitSystem`] is given by the analysis configuration or
+//! declared in the deck with a comment line such as `** UNITS: mm-t-s`.
+//! Moduli, densities and gravity accelerations are converted to SI and
+//! compared with the range of engineering materials; implausible values are
+//! reported together with the unit systems in which they would make sense.
+
+use std::ops::RangeInclusive;
+
+use ccx_inp::Deck;
+
+use crate::materials::MaterialLibrary;
+
+/// Consistent unit system of a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// m, kg, s, N, Pa
+    Si,
+    /// mm, t, s, N, MPa
+    MmTonneSecond,
+    /// mm, kg, ms, kN, GPa
+    MmKilogramMillisecond,
+    /// in, lbf·s²/in, s, lbf, psi
+    InchPoundSecond,
+}
+
+/// Plausible Young's moduli of engineering materials in Pa, from soft
+/// rubber to diamond
+const MODULUS_RANGE: RangeInclusive<f64> = 1e5..=2e12;
+/// Plausible densities in kg/m³, from foams to tungsten alloys
+const DENSITY_RANGE: RangeInclusive<f64> = 5.0..=25e3;
+/// Plausible longitudinal wave speeds sqrt(E / ρ) in m/s
+const WAVE_SPEED_RANGE: RangeInclusive<f64> = 20.0..=2e4;
+/// Standard gravity in m/s²
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+impl UnitSystem {
+    pub const ALL: [UnitSystem; 4] = [
+        UnitSystem::Si,
+        UnitSystem::MmTonneSecond,
+        UnitSystem::MmKilogramMillisecond,
+        UnitSystem::InchPoundSecond,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnitSystem::Si => "m-kg-s",
+            UnitSystem::MmTonneSecond => "mm-t-s",
+            UnitSystem::MmKilogramMillisecond => "mm-kg-ms",
+            UnitSystem::InchPoundSecond => "in-lbf-s",
+        }
+    }
+
+    /// Parse a unit system name such as `SI`, `m-kg-s`, `mm-t-s`,
+    /// `mm-kg-ms` or `in-lbf-s`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let lower = name.trim().to_ascii_lowercase().replace(['_', ' '], "-");
+        match lower.as_str() {
+            "si" | "m-kg-s" | "m-kg-s-n" => Ok(UnitSystem::Si),
+            "mm-t-s" | "mm-tonne-s" | "mm-t-s-n" => Ok(UnitSystem::MmTonneSecond),
+            "mm-kg-ms" | "mm-kg-ms-kn" => Ok(UnitSystem::MmKilogramMillisecond),
+            "in-lbf-s" | "in-lb-s" | "ips" => Ok(UnitSystem::InchPoundSecond),
+            _ => Err(format!("unknown unit system {name}")),
+        }
+    }
+
+    /// Unit system declared by a `** UNITS: <name>` (or `** UNITS = <name>`)
+    /// comment line of the deck
+    pub fn from_deck(deck: &Deck) -> Result<Option<Self>, String> {
+        let comments = deck
+            .cards
+            .iter()
+            .flat_map(|card| {
+                card.format
+                    .leading
+                    .iter()
+                    .chain(card.format.interleaved.iter().map(|(_, line)| line))
+            })
+            .chain(&deck.trailing_trivia);
+        for line in comments {
+            let text = line.trim_start_matches('>').trim_start();
+            let Some(text) = text.strip_prefix("**") else {
+                continue;
+            };
+            let text = text.trim();
+            if let Some(key) = text.get(..5)
+                && key.eq_ignore_ascii_case("UNITS")
+            {
+                let name = text[5..].trim_start().trim_start_matches([':', '=']);
+                return Self::from_name(name).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Pascal per stress unit
+    fn stress_to_si(&self) -> f64 {
+        match self {
+            UnitSystem::Si => 1.0,
+            UnitSystem::MmTonneSecond => 1e6,
+            UnitSystem::MmKilogramMillisecond => 1e9,
+            UnitSystem::InchPoundSecond => 6894.757,
+        }
+    }
+
+    /// kg/m³ per density unit
+    fn density_to_si(&self) -> f64 {
+        match self {
+            UnitSystem::Si => 1.0,
+            UnitSystem::MmTonneSecond => 1e12,
+            UnitSystem::MmKilogramMillisecond => 1e9,
+            // (lbf·s²/in) / in³
+            UnitSystem::InchPoundSecond => 4.448222 / 0.0254 / 0.0254f64.powi(3),
+        }
+    }
+
+    /// m/s² per acceleration unit
+    fn acceleration_to_si(&self) -> f64 {
+        match self {
+            UnitSystem::Si => 1.0,
+            UnitSystem::MmTonneSecond => 1e-3,
+            UnitSystem::MmKilogramMillisecond => 1e3,
+            UnitSystem::InchPoundSecond => 0.0254,
+        }
+    }
+
+    /// Standard gravity in this system
+    pub fn standard_gravity(&self) -> f64 {
+        STANDARD_GRAVITY / self.acceleration_to_si()
+    }
+
+    /// Names of the unit systems in which `to_si(system) * value` is in
+    /// `range`
+    fn plausible_in(
+        value: f64,
+        range: RangeInclusive<f64>,
+        to_si: impl Fn(&UnitSystem) -> f64,
+    ) -> String {
+        let names: Vec<&str> = Self::ALL
+            .iter()
+            .filter(|system| range.contains(&(value * to_si(system))))
+            .map(UnitSystem::name)
+            .collect();
+        if names.is_empty() {
+            String::new()
+        } else {
+            format!("; plausible in {} units", names.join(", "))
+        }
+    }
+
+    /// Warnings about material properties outside the range of engineering
+    /// materials in this unit system
+    pub fn check_materials(&self, materials: &MaterialLibrary) -> Vec<String> {
+        let mut names = materials.material_names();
+        names.sort();
+        let mut warnings = Vec::new();
+        for name in names {
+            let Some(material) = materials.get_material(&name) else {
+                continue;
+            };
+            let modulus = material.elastic_modulus.map(|e| e * self.stress_to_si());
+            let density = material.density.map(|rho| rho * self.density_to_si());
+            if let (Some(e), Some(si)) = (material.elastic_modulus, modulus)
+                && !MODULUS_RANGE.contains(&si)
+            {
+                warnings.push(format!(
+                    "material {}: Young's modulus {} is {:.3e} Pa in {} units{}",
+                    name,
+                    e,
+                    si,
+                    self.name(),
+                    Self::plausible_in(e, MODULUS_RANGE, Self::stress_to_si)
+                ));
+            }
+            if let (Some(rho), Some(si)) = (material.density, density)
+                && !DENSITY_RANGE.contains(&si)
+            {
+                warnings.push(format!(
+                    "material {}: density {} is {:.3e} kg/m³ in {} units{}",
+                    name,
+                    rho,
+                    si,
+                    self.name(),
+                    Self::plausible_in(rho, DENSITY_RANGE, Self::density_to_si)
+                ));
+            }
+            // A plausible modulus and density can still come from two systems
+            if let (Some(e), Some(rho)) = (modulus, density)
+                && e > 0.0
+                && rho > 0.0
+            {
+                let speed = (e / rho).sqrt();
+                if !WAVE_SPEED_RANGE.contains(&speed) {
+                    warnings.push(format!(
+                        "material {}: modulus and density give a wave speed of {:.3e} m/s; \
+                         are they in the same unit system?",
+                        name, speed
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Warnings about `*DLOAD` gravity magnitudes far from standard gravity
+    /// in this unit system
+    pub fn check_gravity(&self, deck: &Deck) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for card in &deck.cards {
+            if !card.keyword.eq_ignore_ascii_case("DLOAD") {
+                continue;
+            }
+            for line in &card.data_lines {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let is_gravity = fields
+                    .get(1)
+                    .is_some_and(|t| t.eq_ignore_ascii_case("GRAV"));
+                let magnitude = fields.get(2).and_then(|v| v.parse::<f64>().ok());
+                let Some(g) = magnitude.filter(|_| is_gravity) else {
+                    continue;
+                };
+                let si = g.abs() * self.acceleration_to_si();
+                // Anything between a tenth and ten times standard gravity
+                // passes, e.g. for centrifuge or lunar models
+                let ratio = si / STANDARD_GRAVITY;
+                if !(0.1..=10.0).contains(&ratio) {
+                    warnings.push(format!(
+                        "gravity {} on {} is {:.3e} m/s² in {} units (standard gravity is {:.2})",
+                        g,
+                        fields[0],
+                        si,
+                        self.name(),
+                        self.standard_gravity()
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::Material;
+
+    fn library(modulus: f64, density: f64) -> MaterialLibrary {
+        let mut materials = MaterialLibrary::new();
+        let mut steel = Material::new("STEEL".to_string());
+        steel.elastic_modulus = Some(modulus);
+        steel.poissons_ratio = Some(0.3);
+        steel.density = Some(density);
+        materials.add_material(steel);
+        materials
+    }
+
+    #[test]
+    fn parses_names_and_deck_comments() {
+        assert_eq!(UnitSystem::from_name("SI").unwrap(), UnitSystem::Si);
+        assert_eq!(
+            UnitSystem::from_name("MM_T_S").unwrap(),
+            UnitSystem::MmTonneSecond
+        );
+        assert!(UnitSystem::from_name("furlong").is_err());
+
+        let deck = Deck::parse_str("** model of a bracket\n** Units: mm-t-s\n*NODE\n1, 0, 0, 0\n")
+            .unwrap();
+        assert_eq!(
+            UnitSystem::from_deck(&deck).unwrap(),
+            Some(UnitSystem::MmTonneSecond)
+        );
+        let deck = Deck::parse_str("*NODE\n1, 0, 0, 0\n**UNITS = in-lbf-s\n").unwrap();
+        assert_eq!(
+            UnitSystem::from_deck(&deck).unwrap(),
+            Some(UnitSystem::InchPoundSecond)
+        );
+        let deck = Deck::parse_str("*NODE\n1, 0, 0, 0\n").unwrap();
+        assert_eq!(UnitSystem::from_deck(&deck).unwrap(), None);
+        let deck = Deck::parse_str("** UNITS: cubits\n*NODE\n1, 0, 0, 0\n").unwrap();
+        assert!(UnitSystem::from_deck(&deck).is_err());
+    }
+
+    #[test]
+    fn flags_steel_density_in_the_wrong_system() {
+        let mm = UnitSystem::MmTonneSecond;
+        assert!(mm.check_materials(&library(210000.0, 7.85e-9)).is_empty());
+        assert!(
+            UnitSystem::Si
+                .check_materials(&library(2.1e11, 7850.0))
+                .is_empty()
+        );
+
+        let warnings = mm.check_materials(&library(210000.0, 7850.0));
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings[0].contains("density 7850"));
+        assert!(warnings[0].contains("plausible in m-kg-s units"));
+        assert!(warnings[1].contains("wave speed"));
+
+        // Each value plausible on its own, but from different systems
+        let warnings = UnitSystem::Si.check_materials(&library(210000.0, 7850.0));
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("wave speed"));
+
+        let warnings = UnitSystem::Si.check_materials(&library(210000.0, 7.85e-9));
+        assert!(warnings.iter().any(|w| w.contains("plausible in mm-t-s")));
+    }
+
+    #[test]
+    fn checks_gravity_magnitude() {
+        let deck = Deck::parse_str(
+            "*STEP\n*STATIC\n*DLOAD\nEALL, GRAV, 9.81, 0., 0., -1.\nEALL, P, 5.\n*END STEP\n",
+        )
+        .unwrap();
+        assert!(UnitSystem::Si.check_gravity(&deck).is_empty());
+        let warnings = UnitSystem::MmTonneSecond.check_gravity(&deck);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("standard gravity is 9806.65"));
+        assert!((UnitSystem::InchPoundSecond.standard_gravity() - 386.09).abs() < 0.01);
+    }
+}
@@ -19,6 +19,7 @@ fn steel_material() -> Material {
         thermal_expansion: None,
         conductivity: None,
         specific_heat: None,
+        yield_stress: None,
     }
 }
 